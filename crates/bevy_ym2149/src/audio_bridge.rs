@@ -2,11 +2,35 @@
 //!
 //! This module provides infrastructure for capturing audio output from playback
 //! entities and making it available for visualization, analysis, or custom routing.
+//!
+//! It also hosts the named audio buses: each [`Ym2149Playback`] can target a
+//! bus by name, and the bus gain and low-pass settings in [`AudioBuses`] are
+//! applied to its audio stream with smooth ramps.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_ym2149::{AudioBus, AudioBuses, Ym2149Playback};
+//! fn setup(mut commands: Commands) {
+//!     commands.spawn(Ym2149Playback::new("music/title.ym").with_bus("music"));
+//! }
+//!
+//! // Muffle the music while the game is paused
+//! fn on_pause(mut buses: ResMut<AudioBuses>) {
+//!     buses.set("music", AudioBus::NEUTRAL.with_gain(0.6).with_lowpass(800.0));
+//! }
+//!
+//! fn on_resume(mut buses: ResMut<AudioBuses>) {
+//!     buses.set("music", AudioBus::NEUTRAL);
+//! }
+//! ```
 
 use crate::events::AudioBridgeRequest;
+use crate::lifecycle::Ym2149Focus;
+use crate::playback::Ym2149Playback;
+use crate::plugin::Ym2149PluginConfig;
 use bevy::prelude::*;
 use std::collections::{HashMap, HashSet};
-use std::f32::consts::FRAC_PI_2;
+use std::f32::consts::{FRAC_PI_2, TAU};
 
 /// Tracks which playback entities should publish audio frames to the bridge buffers.
 #[derive(Resource, Default)]
@@ -186,3 +210,171 @@ pub fn drive_bridge_audio_buffers(
         }
     }
 }
+
+/// Gain and low-pass settings shared by every playback routed to a bus.
+///
+/// Changes glide over [`ramp_seconds`](Self::ramp_seconds) instead of jumping,
+/// so automating a bus (fades, pause muffling) does not produce zipper noise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioBus {
+    /// Gain multiplier (0.0 = silent, 1.0 = unchanged).
+    pub gain: f32,
+    /// One-pole low-pass cutoff in Hz (`None` = filter open).
+    pub lowpass_hz: Option<f32>,
+    /// Time in seconds to ramp from the previous settings to these.
+    pub ramp_seconds: f32,
+}
+
+impl AudioBus {
+    /// Default ramp time for parameter changes.
+    pub const DEFAULT_RAMP_SECONDS: f32 = 0.05;
+
+    /// Unity gain, filter open.
+    pub const NEUTRAL: Self = Self {
+        gain: 1.0,
+        lowpass_hz: None,
+        ramp_seconds: Self::DEFAULT_RAMP_SECONDS,
+    };
+
+    /// Set the gain multiplier.
+    pub fn with_gain(mut self, gain: f32) -> Self {
+        self.gain = gain;
+        self
+    }
+
+    /// Set the low-pass cutoff in Hz.
+    pub fn with_lowpass(mut self, cutoff_hz: f32) -> Self {
+        self.lowpass_hz = Some(cutoff_hz);
+        self
+    }
+
+    /// Open the low-pass filter.
+    pub fn without_lowpass(mut self) -> Self {
+        self.lowpass_hz = None;
+        self
+    }
+
+    /// Set the ramp time in seconds (0.0 = apply immediately).
+    pub fn with_ramp(mut self, seconds: f32) -> Self {
+        self.ramp_seconds = seconds;
+        self
+    }
+
+    /// Clamped gain, as applied to the stream.
+    pub(crate) fn effective_gain(self) -> f32 {
+        self.gain.clamp(0.0, 2.0)
+    }
+
+    /// One-pole smoothing coefficient for `sample_rate` (1.0 = filter open).
+    pub(crate) fn lowpass_coefficient(self, sample_rate: u32) -> f32 {
+        match self.lowpass_hz {
+            Some(cutoff) => {
+                let cutoff = cutoff.clamp(0.0, sample_rate as f32 * 0.5);
+                1.0 - (-TAU * cutoff / sample_rate as f32).exp()
+            }
+            None => 1.0,
+        }
+    }
+
+    /// Number of samples a ramp to these settings takes at `sample_rate`.
+    pub(crate) fn ramp_samples(self, sample_rate: u32) -> u32 {
+        (self.ramp_seconds.max(0.0) * sample_rate as f32) as u32
+    }
+}
+
+impl Default for AudioBus {
+    fn default() -> Self {
+        Self::NEUTRAL
+    }
+}
+
+/// Named audio buses that playbacks target via [`Ym2149Playback::set_bus`].
+///
+/// Playbacks routed to a bus that has no entry use [`AudioBus::NEUTRAL`].
+#[derive(Resource, Default)]
+pub struct AudioBuses(pub HashMap<String, AudioBus>);
+
+impl AudioBuses {
+    /// Replaces the settings of a bus.
+    pub fn set(&mut self, name: impl Into<String>, bus: AudioBus) {
+        self.0.insert(name.into(), bus);
+    }
+
+    /// Adjusts only the gain of a bus (inserts defaults if needed).
+    pub fn set_gain(&mut self, name: impl Into<String>, gain: f32) {
+        self.0.entry(name.into()).or_default().gain = gain;
+    }
+
+    /// Adjusts only the low-pass cutoff of a bus (inserts defaults if needed).
+    pub fn set_lowpass(&mut self, name: impl Into<String>, cutoff_hz: Option<f32>) {
+        self.0.entry(name.into()).or_default().lowpass_hz = cutoff_hz;
+    }
+
+    /// Removes a bus, returning its playbacks to neutral settings.
+    pub fn clear(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    /// Fetches the effective settings of a bus.
+    pub fn get(&self, name: &str) -> AudioBus {
+        self.0.get(name).copied().unwrap_or_default()
+    }
+}
+
+/// System that pushes bus settings to the audio stream of each playback.
+///
+/// Also applies [`FocusLoss::Duck`](crate::FocusLoss::Duck) on top of the bus
+/// gain. The stream ramps toward the settings itself; unchanged settings cost
+/// a lock per playback.
+pub fn apply_audio_buses(
+    config: Res<Ym2149PluginConfig>,
+    buses: Res<AudioBuses>,
+    focus: Res<Ym2149Focus>,
+    playbacks: Query<&Ym2149Playback>,
+) {
+    let duck = focus.duck_gain(config.focus_loss);
+    for playback in playbacks.iter() {
+        let mut bus = playback
+            .bus
+            .as_deref()
+            .map(|name| buses.get(name))
+            .unwrap_or_default();
+        bus.gain *= duck;
+        if let Some(state) = &playback.audio_stream_state {
+            state.set_bus(bus);
+        }
+        if let Some(crossfade) = &playback.crossfade {
+            crossfade.audio_stream_state.set_bus(bus);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_bus_lowpass_coefficient() {
+        assert_eq!(AudioBus::NEUTRAL.lowpass_coefficient(44_100), 1.0);
+
+        let muffled = AudioBus::NEUTRAL.with_lowpass(800.0);
+        let coefficient = muffled.lowpass_coefficient(44_100);
+        assert!(coefficient > 0.0 && coefficient < 0.2);
+        assert_eq!(muffled.without_lowpass(), AudioBus::NEUTRAL);
+    }
+
+    #[test]
+    fn test_audio_buses_default_to_neutral() {
+        let mut buses = AudioBuses::default();
+        assert_eq!(buses.get("music"), AudioBus::NEUTRAL);
+
+        buses.set_gain("music", 0.5);
+        buses.set_lowpass("music", Some(1_000.0));
+        let bus = buses.get("music");
+        assert_eq!(bus.gain, 0.5);
+        assert_eq!(bus.lowpass_hz, Some(1_000.0));
+
+        buses.clear("music");
+        assert_eq!(buses.get("music"), AudioBus::NEUTRAL);
+    }
+}
@@ -1,21 +1,195 @@
+//! Audio-reactive metrics for shaders, VFX and gameplay.
+//!
+//! [`AudioReactiveState`] holds per-playback [`ReactiveMetrics`] updated once
+//! per YM frame: smoothed average/RMS levels, peak envelopes, per-channel
+//! onsets and a beat-phase estimate derived from those onsets. Tune the
+//! smoothing window and onset sensitivity through [`AudioReactiveConfig`].
+
 use bevy::prelude::*;
 use std::collections::HashMap;
 
+/// Shortest plausible beat (300 BPM at 50 Hz); faster onsets are ignored for tempo.
+const MIN_BEAT_FRAMES: u64 = 10;
+/// Longest plausible beat (20 BPM at 50 Hz); slower onsets restart tempo tracking.
+const MAX_BEAT_FRAMES: u64 = 150;
+/// Weight of a new inter-onset interval in the beat period estimate.
+const BEAT_PERIOD_SMOOTHING: f32 = 0.2;
+/// Frames after an onset during which the same channel cannot fire again.
+const ONSET_HOLD_FRAMES: u64 = 3;
+
+/// Tuning for [`AudioReactiveState`] updates.
+#[derive(Resource, Clone, Debug)]
+pub struct AudioReactiveConfig {
+    /// Length of the smoothing window in YM frames (at least 1).
+    ///
+    /// Averages and RMS use an exponential moving average weighting each new
+    /// frame by `1 / smoothing_frames`; peak envelopes release over the same
+    /// window. Default is 4 frames (80 ms at 50 Hz).
+    pub smoothing_frames: u32,
+    /// Ratio by which a frame's RMS must exceed the previous frame's RMS to
+    /// count as an onset. Default is 1.5.
+    pub onset_threshold: f32,
+    /// Minimum frame RMS (0.0–1.0) for an onset, so noise floors do not fire.
+    /// Default is 0.05.
+    pub onset_min_level: f32,
+}
+
+impl Default for AudioReactiveConfig {
+    fn default() -> Self {
+        Self {
+            smoothing_frames: 4,
+            onset_threshold: 1.5,
+            onset_min_level: 0.05,
+        }
+    }
+}
+
+impl AudioReactiveConfig {
+    fn smoothing(&self) -> f32 {
+        1.0 / self.smoothing_frames.max(1) as f32
+    }
+}
+
 /// Smoothed per-entity audio metrics for visualization and gameplay hooks.
 #[derive(Clone, Debug)]
 pub struct ReactiveMetrics {
+    /// Smoothed mean absolute amplitude per channel (0.0–1.0).
     pub average: [f32; 3],
+    /// Smoothed per-frame peak amplitude per channel (0.0–1.0).
     pub peak: [f32; 3],
+    /// Smoothed RMS level per channel (0.0–1.0).
+    pub rms: [f32; 3],
+    /// Peak envelope per channel: jumps to each new peak and releases over
+    /// the smoothing window.
+    pub envelope: [f32; 3],
+    /// Whether each channel had an onset (sudden RMS rise) this frame.
+    pub onset: [bool; 3],
+    /// Frame of the most recent onset per channel.
+    pub last_onset_frame: [Option<u64>; 3],
+    /// Estimated beat length in frames, once two onsets a plausible beat apart were seen.
+    pub beat_period_frames: Option<f32>,
+    /// Position within the estimated beat (0.0 at the beat, approaching 1.0
+    /// just before the next one). Stays 0.0 until a period is known.
+    pub beat_phase: f32,
+    /// Latest frequency per channel in Hz, if a tone is playing.
     pub frequencies: [Option<f32>; 3],
+    /// Frame index of the latest update.
+    pub frame: u64,
+    frame_rms: [f32; 3],
+    last_beat_frame: Option<u64>,
 }
 
 impl ReactiveMetrics {
+    /// Create silent metrics with no beat estimate.
     pub fn new() -> Self {
         Self {
             average: [0.0; 3],
             peak: [0.0; 3],
+            rms: [0.0; 3],
+            envelope: [0.0; 3],
+            onset: [false; 3],
+            last_onset_frame: [None; 3],
+            beat_period_frames: None,
+            beat_phase: 0.0,
             frequencies: [None; 3],
+            frame: 0,
+            frame_rms: [0.0; 3],
+            last_beat_frame: None,
+        }
+    }
+
+    /// Whether any channel had an onset this frame.
+    pub fn any_onset(&self) -> bool {
+        self.onset.iter().any(|&onset| onset)
+    }
+
+    /// Fold one frame of per-channel samples into the metrics.
+    pub(crate) fn update(
+        &mut self,
+        frame: u64,
+        channel_samples: &[[f32; 3]],
+        frequencies: [Option<f32>; 3],
+        config: &AudioReactiveConfig,
+    ) {
+        let smoothing = config.smoothing();
+        let inv_len = 1.0 / channel_samples.len().max(1) as f32;
+
+        for channel in 0..3 {
+            let mut sum = 0.0;
+            let mut sum_squares = 0.0;
+            let mut peak: f32 = 0.0;
+            for sample in channel_samples {
+                let value = sample[channel].abs();
+                sum += value;
+                sum_squares += value * value;
+                peak = peak.max(value);
+            }
+            let average = (sum * inv_len).clamp(0.0, 1.0);
+            let rms = (sum_squares * inv_len).sqrt().clamp(0.0, 1.0);
+            let peak = peak.clamp(0.0, 1.0);
+
+            let holding = self.last_onset_frame[channel]
+                .is_some_and(|last| frame.saturating_sub(last) < ONSET_HOLD_FRAMES);
+            let onset = !holding
+                && rms >= config.onset_min_level
+                && rms > self.frame_rms[channel] * config.onset_threshold;
+            self.onset[channel] = onset;
+            if onset {
+                self.last_onset_frame[channel] = Some(frame);
+            }
+
+            self.average[channel] += (average - self.average[channel]) * smoothing;
+            self.peak[channel] += (peak - self.peak[channel]) * smoothing;
+            self.rms[channel] += (rms - self.rms[channel]) * smoothing;
+            self.frame_rms[channel] = rms;
+            self.envelope[channel] = if peak >= self.envelope[channel] {
+                peak
+            } else {
+                self.envelope[channel] * (1.0 - smoothing)
+            };
         }
+
+        if self.any_onset() {
+            self.track_beat(frame);
+        }
+        self.beat_phase = match (self.beat_period_frames, self.last_beat_frame) {
+            (Some(period), Some(last)) => (frame.saturating_sub(last) as f32 / period).fract(),
+            _ => 0.0,
+        };
+        self.frequencies = frequencies;
+        self.frame = frame;
+    }
+
+    fn track_beat(&mut self, frame: u64) {
+        let Some(last) = self.last_beat_frame else {
+            self.last_beat_frame = Some(frame);
+            return;
+        };
+        let interval = frame.saturating_sub(last);
+        if interval < MIN_BEAT_FRAMES {
+            // Off-beat hit (hi-hat, arpeggio); keep the current beat grid.
+            return;
+        }
+        self.last_beat_frame = Some(frame);
+        if interval > MAX_BEAT_FRAMES {
+            self.beat_period_frames = None;
+            return;
+        }
+
+        let mut interval = interval as f32;
+        self.beat_period_frames = Some(match self.beat_period_frames {
+            None => interval,
+            Some(period) => {
+                // Fold half/double-time intervals onto the current estimate.
+                while interval > period * std::f32::consts::SQRT_2 {
+                    interval /= 2.0;
+                }
+                while interval < period / std::f32::consts::SQRT_2 {
+                    interval *= 2.0;
+                }
+                period + (interval - period) * BEAT_PERIOD_SMOOTHING
+            }
+        });
     }
 }
 
@@ -28,5 +202,57 @@ impl Default for ReactiveMetrics {
 /// Resource mapping playback entities to their most recent reactive metrics.
 #[derive(Resource, Default)]
 pub struct AudioReactiveState {
+    /// Metrics keyed by playback entity.
     pub metrics: HashMap<Entity, ReactiveMetrics>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(levels: [f32; 3]) -> Vec<[f32; 3]> {
+        vec![levels; 16]
+    }
+
+    #[test]
+    fn onsets_are_detected_per_channel() {
+        let config = AudioReactiveConfig::default();
+        let mut metrics = ReactiveMetrics::new();
+
+        metrics.update(0, &frame([0.0, 0.3, 0.0]), [None; 3], &config);
+        assert_eq!(metrics.onset, [false, true, false]);
+        assert_eq!(metrics.envelope[1], 0.3);
+
+        // Sustained level: no new onset, envelope holds at the peak
+        for index in 1..10 {
+            metrics.update(index, &frame([0.0, 0.3, 0.0]), [None; 3], &config);
+            assert!(!metrics.any_onset());
+        }
+        assert!((metrics.rms[1] - 0.3).abs() < 0.05);
+
+        // Bass hit on channel A while B keeps playing
+        metrics.update(10, &frame([0.8, 0.3, 0.0]), [None; 3], &config);
+        assert_eq!(metrics.onset, [true, false, false]);
+        assert_eq!(metrics.last_onset_frame[0], Some(10));
+
+        // Silence releases the envelope over the smoothing window
+        metrics.update(11, &frame([0.0; 3]), [None; 3], &config);
+        assert!((metrics.envelope[0] - 0.6).abs() < 1e-6);
+    }
+
+    #[test]
+    fn beat_phase_follows_regular_onsets() {
+        let config = AudioReactiveConfig {
+            smoothing_frames: 1,
+            ..Default::default()
+        };
+        let mut metrics = ReactiveMetrics::new();
+        for index in 0..200u64 {
+            let level = if index % 25 == 0 { 0.8 } else { 0.0 };
+            metrics.update(index, &frame([level, 0.0, 0.0]), [None; 3], &config);
+        }
+        assert_eq!(metrics.beat_period_frames, Some(25.0));
+        // Frame 199 is 24 frames after the beat at 175
+        assert!((metrics.beat_phase - 24.0 / 25.0).abs() < 1e-6);
+    }
+}
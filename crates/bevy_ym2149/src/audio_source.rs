@@ -9,8 +9,11 @@ use thiserror::Error;
 use ym2149_common::MetadataFields;
 
 use crate::error::{BevyYm2149Error, Result};
-use crate::playback::ToneSettings;
-use crate::song_player::{SharedSongPlayer, load_song_from_bytes};
+use crate::playback::{PlaybackMetrics, ToneSettings};
+use crate::plugin::ChipBackend;
+use crate::song_player::{
+    SharedSongPlayer, SongFormat, YmSongPlayer, load_song_as, load_song_from_bytes,
+};
 use crate::streaming::{AudioStream, StreamingDecoder};
 
 /// A loaded YM2149 audio file ready to be played
@@ -51,6 +54,38 @@ impl Clone for Ym2149AudioSource {
     }
 }
 
+/// Raw song data stored as a Bevy asset
+///
+/// Lets songs that were downloaded or generated at runtime be shared by handle
+/// without writing them to the asset directory. Add the data to
+/// `Assets<Ym2149SongBytes>` and play it with
+/// [`Ym2149Playback::from_song_bytes`](crate::Ym2149Playback::from_song_bytes);
+/// the format is detected when the track loads.
+#[derive(Asset, TypePath, Clone, Debug)]
+pub struct Ym2149SongBytes {
+    data: Arc<Vec<u8>>,
+}
+
+impl Ym2149SongBytes {
+    /// Wrap raw song data (any supported format).
+    pub fn new(data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            data: Arc::new(data.into()),
+        }
+    }
+
+    /// The raw song data.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<Vec<u8>> for Ym2149SongBytes {
+    fn from(data: Vec<u8>) -> Self {
+        Self::new(data)
+    }
+}
+
 /// Metadata about a YM2149 audio file
 ///
 /// Implements [`MetadataFields`] from `ym2149-common` for unified metadata access.
@@ -62,6 +97,9 @@ pub struct Ym2149Metadata {
     pub author: String,
     /// Comments/notes
     pub comment: String,
+    /// Source format ("YM", "AKS", "AY", "SNDH"; empty when unknown)
+    #[serde(default)]
+    pub format: String,
     /// Number of frames in the song
     pub frame_count: usize,
     /// Duration in seconds (approximate)
@@ -82,7 +120,7 @@ impl MetadataFields for Ym2149Metadata {
     }
 
     fn format(&self) -> &str {
-        "YM" // Generic - actual format could be YM, AKS, AY, SNDH
+        &self.format
     }
 
     fn frame_count(&self) -> Option<usize> {
@@ -97,10 +135,32 @@ impl MetadataFields for Ym2149Metadata {
 impl Ym2149AudioSource {
     /// Create a new audio source from raw YM file data
     pub fn new(data: Vec<u8>) -> Result<Self> {
+        Self::new_with_chip(data, ChipBackend::Hardware)
+    }
+
+    /// Create a new audio source whose YM songs render through `chip`.
+    pub fn new_with_chip(data: Vec<u8>, chip: ChipBackend) -> Result<Self> {
         // Load the song to create a player
         let (player, metrics, metadata) =
-            load_song_from_bytes(&data).map_err(BevyYm2149Error::MetadataExtraction)?;
+            load_song_from_bytes(&data, chip).map_err(BevyYm2149Error::MetadataExtraction)?;
+        Ok(Self::from_loaded(data, player, metrics, metadata))
+    }
+
+    /// Create a new audio source from data of a known `format`, skipping format detection.
+    ///
+    /// `chip` selects the backend for YM songs; other formats ignore it.
+    pub fn new_with_format(data: Vec<u8>, format: SongFormat, chip: ChipBackend) -> Result<Self> {
+        let (player, metrics, metadata) =
+            load_song_as(&data, format, chip).map_err(BevyYm2149Error::MetadataExtraction)?;
+        Ok(Self::from_loaded(data, player, metrics, metadata))
+    }
 
+    fn from_loaded(
+        data: Vec<u8>,
+        player: YmSongPlayer,
+        metrics: PlaybackMetrics,
+        metadata: Ym2149Metadata,
+    ) -> Self {
         let sample_rate = crate::playback::YM2149_SAMPLE_RATE;
         let total_samples = metrics.total_samples();
 
@@ -109,14 +169,14 @@ impl Ym2149AudioSource {
         // Start the audio stream (spawns producer thread)
         let stream = Arc::new(AudioStream::start(Arc::clone(&player)));
 
-        Ok(Self {
+        Self {
             data,
             metadata,
             player,
             stream,
             sample_rate,
             total_samples,
-        })
+        }
     }
 
     /// Create a new audio source from raw YM file data (compatibility method for crossfade).
@@ -139,15 +199,38 @@ impl Ym2149AudioSource {
         stereo_gain: Arc<parking_lot::RwLock<(f32, f32)>>,
         tone_settings: Arc<parking_lot::RwLock<ToneSettings>>,
         subsong: Option<usize>,
+    ) -> Result<Self> {
+        Self::new_with_options(
+            data,
+            stereo_gain,
+            tone_settings,
+            subsong,
+            None,
+            ChipBackend::Hardware,
+        )
+    }
+
+    /// Create a new audio source with subsong selection, an optional start
+    /// frame and an explicit YM chip backend.
+    pub(crate) fn new_with_options(
+        data: Vec<u8>,
+        stereo_gain: Arc<parking_lot::RwLock<(f32, f32)>>,
+        tone_settings: Arc<parking_lot::RwLock<ToneSettings>>,
+        subsong: Option<usize>,
+        start_frame: Option<usize>,
+        chip: ChipBackend,
     ) -> Result<Self> {
         // Load the song to create a player
         let (mut player, metrics, metadata) =
-            load_song_from_bytes(&data).map_err(BevyYm2149Error::MetadataExtraction)?;
+            load_song_from_bytes(&data, chip).map_err(BevyYm2149Error::MetadataExtraction)?;
 
         // Apply subsong selection BEFORE starting the stream
         if let Some(index) = subsong {
             player.set_subsong(index);
         }
+        if let Some(frame) = start_frame {
+            player.skip_to_frame(frame);
+        }
 
         let sample_rate = crate::playback::YM2149_SAMPLE_RATE;
         let total_samples = metrics.total_samples();
@@ -225,6 +308,12 @@ impl Ym2149AudioSource {
     pub fn set_tone_settings(&self, settings: ToneSettings) {
         self.stream.state.set_tone_settings(settings);
     }
+
+    /// Set insert effects (an empty config bypasses them)
+    #[cfg(feature = "fx")]
+    pub fn set_fx(&self, config: &ym2149_common::FxConfig) {
+        self.stream.state.set_fx(config);
+    }
 }
 
 /// Error type for YM2149 asset loading
@@ -232,9 +321,34 @@ impl Ym2149AudioSource {
 #[error("{0}")]
 pub struct Ym2149LoadError(String);
 
-/// Asset loader for YM2149 files
+/// Read a whole asset and load it as a song of the given `format`.
+async fn load_song_asset(
+    reader: &mut dyn bevy::asset::io::Reader,
+    format: SongFormat,
+    chip: ChipBackend,
+) -> std::result::Result<Ym2149AudioSource, Ym2149LoadError> {
+    let mut data = Vec::new();
+    reader
+        .read_to_end(&mut data)
+        .await
+        .map_err(|e| Ym2149LoadError(format!("Failed to read asset: {e}")))?;
+
+    Ym2149AudioSource::new_with_format(data, format, chip)
+        .map_err(|e| Ym2149LoadError(e.to_string()))
+}
+
+/// Asset loader for YM files (`.ym`)
 #[derive(Default)]
-pub struct Ym2149Loader;
+pub struct Ym2149Loader {
+    chip: ChipBackend,
+}
+
+impl Ym2149Loader {
+    /// Create a loader whose YM songs render through `chip`.
+    pub fn new(chip: ChipBackend) -> Self {
+        Self { chip }
+    }
+}
 
 impl AssetLoader for Ym2149Loader {
     type Asset = Ym2149AudioSource;
@@ -247,17 +361,80 @@ impl AssetLoader for Ym2149Loader {
         _settings: &Self::Settings,
         _load_context: &mut LoadContext<'_>,
     ) -> std::result::Result<Self::Asset, Self::Error> {
-        let mut data = Vec::new();
-        reader
-            .read_to_end(&mut data)
-            .await
-            .map_err(|e| Ym2149LoadError(format!("Failed to read asset: {e}")))?;
+        load_song_asset(reader, SongFormat::Ym, self.chip).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        SongFormat::Ym.extensions()
+    }
+}
+
+/// Asset loader for Arkos Tracker songs (`.aks`)
+#[derive(Default)]
+pub struct ArkosLoader;
+
+impl AssetLoader for ArkosLoader {
+    type Asset = Ym2149AudioSource;
+    type Settings = ();
+    type Error = Ym2149LoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> std::result::Result<Self::Asset, Self::Error> {
+        load_song_asset(reader, SongFormat::Arkos, ChipBackend::Hardware).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        SongFormat::Arkos.extensions()
+    }
+}
+
+/// Asset loader for ZXAY/EMUL files (`.ay`)
+#[derive(Default)]
+pub struct AyLoader;
+
+impl AssetLoader for AyLoader {
+    type Asset = Ym2149AudioSource;
+    type Settings = ();
+    type Error = Ym2149LoadError;
 
-        Ym2149AudioSource::new(data).map_err(|e| Ym2149LoadError(e.to_string()))
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> std::result::Result<Self::Asset, Self::Error> {
+        load_song_asset(reader, SongFormat::Ay, ChipBackend::Hardware).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        SongFormat::Ay.extensions()
+    }
+}
+
+/// Asset loader for Atari ST SNDH files (`.sndh`)
+#[derive(Default)]
+pub struct SndhLoader;
+
+impl AssetLoader for SndhLoader {
+    type Asset = Ym2149AudioSource;
+    type Settings = ();
+    type Error = Ym2149LoadError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn bevy::asset::io::Reader,
+        _settings: &Self::Settings,
+        _load_context: &mut LoadContext<'_>,
+    ) -> std::result::Result<Self::Asset, Self::Error> {
+        load_song_asset(reader, SongFormat::Sndh, ChipBackend::Hardware).await
     }
 
     fn extensions(&self) -> &[&str] {
-        &["ym", "aks", "ay", "sndh"]
+        SongFormat::Sndh.extensions()
     }
 }
 
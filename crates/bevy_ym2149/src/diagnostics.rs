@@ -8,16 +8,21 @@ use crate::plugin::Ym2149PluginConfig;
 use bevy::diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic};
 use bevy::prelude::*;
 
-/// Diagnostic path for audio buffer fill level (reserved for future use).
+/// Diagnostic path for the lowest streaming buffer fill level (0.0–1.0) across playbacks.
 pub const BUFFER_FILL_PATH: DiagnosticPath = DiagnosticPath::const_new("ym2149/buffer_fill");
 
 /// Diagnostic path for current frame position across all playbacks.
 pub const FRAME_POSITION_PATH: DiagnosticPath = DiagnosticPath::const_new("ym2149/frame_position");
 
+/// Diagnostic path for the largest gap between Bevy's clock and the consumed
+/// audio across playbacks, in milliseconds (positive = `Time` runs ahead).
+pub const CLOCK_DRIFT_PATH: DiagnosticPath = DiagnosticPath::const_new("ym2149/clock_drift_ms");
+
 /// Register YM2149 diagnostics with the Bevy app.
 pub fn register(app: &mut App) {
     app.register_diagnostic(Diagnostic::new(BUFFER_FILL_PATH));
     app.register_diagnostic(Diagnostic::new(FRAME_POSITION_PATH));
+    app.register_diagnostic(Diagnostic::new(CLOCK_DRIFT_PATH));
 }
 
 /// System that updates diagnostic measurements each frame.
@@ -31,13 +36,27 @@ pub fn update_diagnostics(
     }
 
     let mut max_frame = 0.0f64;
+    let mut min_fill: Option<f64> = None;
+    let mut max_drift: Option<f64> = None;
 
     for playback in playbacks.iter() {
         max_frame = max_frame.max(playback.frame_position() as f64);
+        if let Some(fill) = playback.audio_buffer_fill() {
+            min_fill = Some(min_fill.map_or(fill as f64, |min| min.min(fill as f64)));
+        }
+        if let Some(drift) = playback.clock_drift_seconds() {
+            let drift = drift as f64 * 1000.0;
+            if max_drift.is_none_or(|max| drift.abs() > max.abs()) {
+                max_drift = Some(drift);
+            }
+        }
     }
 
-    // TODO: Buffer fill diagnostic not available with Bevy audio
-    // Could potentially track via AudioPlayer stats if available
-
     diagnostics.add_measurement(&FRAME_POSITION_PATH, || max_frame);
+    if let Some(fill) = min_fill {
+        diagnostics.add_measurement(&BUFFER_FILL_PATH, || fill);
+    }
+    if let Some(drift) = max_drift {
+        diagnostics.add_measurement(&CLOCK_DRIFT_PATH, || drift);
+    }
 }
@@ -45,6 +45,17 @@ pub struct PlaybackFrameMarker {
     pub looped: bool,
 }
 
+/// Fired every VBL-frame while a crossfade is fading, ending with `progress` 1.0.
+#[derive(Event, Message, Clone, Debug)]
+pub struct CrossfadeProgress {
+    /// The playback entity that is crossfading.
+    pub entity: Entity,
+    /// Playlist index of the incoming track (0 for music state crossfades).
+    pub target_index: usize,
+    /// Fade progress from 0.0 (outgoing only) to 1.0 (incoming only).
+    pub progress: f32,
+}
+
 /// Request to switch to a named music state.
 #[derive(Event, Message, Clone, Debug)]
 pub struct MusicStateRequest {
@@ -0,0 +1,447 @@
+//! Procedural jingles and stingers.
+//!
+//! A [`Jingle`] combines up to three parts on a step grid: a melody given as
+//! scale degrees (channel A), an arpeggiated chord (channel B) and a drum
+//! pattern on the noise generator (channel C). Jingles play live through a
+//! [`YmSynthController`] or render offline to PCM on any
+//! [`Ym2149Backend`], including `ym2149_softsynth::SoftSynth`.
+//!
+//! ```
+//! # use bevy_ym2149::jingle::{ArpeggioPattern, DrumPattern, Jingle, Scale};
+//! # use bevy_ym2149::YmSynthController;
+//! let jingle = Jingle::new(60, Scale::Major)
+//!     .with_tempo(160.0)
+//!     .with_melody([Some(0), Some(2), Some(4), Some(7), None, Some(7)])
+//!     .with_arpeggio(&[0, 2, 4], ArpeggioPattern::Up, 6)
+//!     .with_drums(DrumPattern::new("k.s.kh"));
+//!
+//! let synth = YmSynthController::new();
+//! jingle.play(&synth);
+//!
+//! let pcm = jingle.render();
+//! assert!(!pcm.is_empty());
+//! ```
+
+use crate::playback::YM2149_SAMPLE_RATE;
+use crate::synth::{SynthInstrument, SynthNote, YmSynthController};
+use ym2149::{Ym2149, Ym2149Backend};
+
+/// Fraction of a step a note sounds before the next one starts.
+const GATE: f64 = 0.9;
+
+/// Silence rendered after the last step so decays ring out.
+const RENDER_TAIL_SECONDS: f64 = 0.25;
+
+/// Musical scale used to map degrees to notes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Scale {
+    /// Major (Ionian).
+    #[default]
+    Major,
+    /// Natural minor (Aeolian).
+    Minor,
+    /// Harmonic minor.
+    HarmonicMinor,
+    /// Major pentatonic.
+    MajorPentatonic,
+    /// Minor pentatonic.
+    MinorPentatonic,
+    /// Blues scale.
+    Blues,
+    /// All twelve semitones.
+    Chromatic,
+}
+
+impl Scale {
+    /// Semitone offsets of the scale within one octave.
+    pub fn intervals(self) -> &'static [u8] {
+        match self {
+            Self::Major => &[0, 2, 4, 5, 7, 9, 11],
+            Self::Minor => &[0, 2, 3, 5, 7, 8, 10],
+            Self::HarmonicMinor => &[0, 2, 3, 5, 7, 8, 11],
+            Self::MajorPentatonic => &[0, 2, 4, 7, 9],
+            Self::MinorPentatonic => &[0, 3, 5, 7, 10],
+            Self::Blues => &[0, 3, 5, 6, 7, 10],
+            Self::Chromatic => &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11],
+        }
+    }
+
+    /// MIDI note of `degree` above `root` (0 = root; negative degrees go down,
+    /// degrees past the scale length wrap into the next octave).
+    pub fn note(self, root: u8, degree: i32) -> u8 {
+        let intervals = self.intervals();
+        let len = intervals.len() as i32;
+        let octave = degree.div_euclid(len);
+        let offset = i32::from(intervals[degree.rem_euclid(len) as usize]);
+        (i32::from(root) + octave * 12 + offset).clamp(0, 127) as u8
+    }
+}
+
+/// Order in which an arpeggio walks its chord.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArpeggioPattern {
+    /// Lowest to highest, then restart.
+    #[default]
+    Up,
+    /// Highest to lowest, then restart.
+    Down,
+    /// Up and back down without repeating the end notes.
+    UpDown,
+    /// Root alternating with each higher chord note in turn.
+    Alternate,
+}
+
+impl ArpeggioPattern {
+    /// Chord index played at `step` for a chord of `len` notes.
+    pub fn index(self, step: usize, len: usize) -> usize {
+        if len <= 1 {
+            return 0;
+        }
+        match self {
+            Self::Up => step % len,
+            Self::Down => len - 1 - step % len,
+            Self::UpDown => {
+                let cycle = 2 * (len - 1);
+                let pos = step % cycle;
+                if pos < len { pos } else { cycle - pos }
+            }
+            Self::Alternate => {
+                if step % 2 == 0 {
+                    0
+                } else {
+                    1 + (step / 2) % (len - 1)
+                }
+            }
+        }
+    }
+}
+
+/// Drum sound on the noise generator.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DrumHit {
+    /// Low tone with a noise burst.
+    Kick,
+    /// Mid tone with coarse noise.
+    Snare,
+    /// Short bright noise.
+    HiHat,
+}
+
+impl DrumHit {
+    /// Pitch and instrument used for the hit.
+    pub fn voice(self) -> (f32, SynthInstrument) {
+        match self {
+            Self::Kick => (60.0, SynthInstrument::DRUM),
+            Self::Snare => (
+                180.0,
+                SynthInstrument {
+                    decay: 3,
+                    noise: Some(6),
+                    ..SynthInstrument::DRUM
+                },
+            ),
+            Self::HiHat => (1000.0, SynthInstrument::HIHAT),
+        }
+    }
+}
+
+/// Sequence of drum hits, one per step.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrumPattern(pub Vec<Option<DrumHit>>);
+
+impl DrumPattern {
+    /// Parse a step string: `k` kick, `s` snare, `h` hi-hat, anything else rests.
+    ///
+    /// `"k.h.s.h."` is a basic rock beat at two steps per beat.
+    pub fn new(steps: &str) -> Self {
+        Self(
+            steps
+                .chars()
+                .map(|c| match c.to_ascii_lowercase() {
+                    'k' => Some(DrumHit::Kick),
+                    's' => Some(DrumHit::Snare),
+                    'h' => Some(DrumHit::HiHat),
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Arpeggio {
+    chord: Vec<i32>,
+    pattern: ArpeggioPattern,
+    steps: usize,
+}
+
+/// Short procedural piece for fanfares and stingers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Jingle {
+    /// MIDI note of scale degree 0.
+    pub root: u8,
+    /// Scale used for melody and arpeggio degrees.
+    pub scale: Scale,
+    /// Tempo in beats per minute.
+    pub tempo: f32,
+    /// Grid steps per beat.
+    pub steps_per_beat: u32,
+    /// Instrument of the melody (channel A).
+    pub melody_instrument: SynthInstrument,
+    /// Instrument of the arpeggio (channel B).
+    pub arpeggio_instrument: SynthInstrument,
+    melody: Vec<Option<i32>>,
+    arpeggio: Option<Arpeggio>,
+    drums: DrumPattern,
+}
+
+impl Jingle {
+    /// Empty jingle in `scale` starting at MIDI note `root`, 120 BPM, 4 steps per beat.
+    pub fn new(root: u8, scale: Scale) -> Self {
+        Self {
+            root,
+            scale,
+            tempo: 120.0,
+            steps_per_beat: 4,
+            melody_instrument: SynthInstrument::LEAD,
+            arpeggio_instrument: SynthInstrument {
+                volume: 11,
+                ..SynthInstrument::PLUCK
+            },
+            melody: Vec::new(),
+            arpeggio: None,
+            drums: DrumPattern::default(),
+        }
+    }
+
+    /// Rising major fanfare for wins and level-ups.
+    pub fn victory() -> Self {
+        Self::new(60, Scale::Major)
+            .with_tempo(150.0)
+            .with_melody([
+                Some(0),
+                Some(2),
+                Some(4),
+                Some(7),
+                None,
+                Some(4),
+                Some(7),
+                Some(7),
+                Some(7),
+            ])
+            .with_arpeggio(&[0, 2, 4, 7], ArpeggioPattern::Up, 9)
+            .with_drums(DrumPattern::new("k...s.k.s"))
+    }
+
+    /// Falling minor phrase for game over screens.
+    pub fn defeat() -> Self {
+        Self::new(57, Scale::Minor)
+            .with_tempo(90.0)
+            .with_steps_per_beat(2)
+            .with_melody([Some(4), Some(3), Some(2), Some(1), Some(0), Some(0)])
+            .with_arpeggio(&[-7, -5, -3], ArpeggioPattern::Down, 6)
+            .with_drums(DrumPattern::new("k.k..k"))
+    }
+
+    /// Quick two-note blip for pickups.
+    pub fn pickup() -> Self {
+        Self::new(76, Scale::MajorPentatonic)
+            .with_tempo(240.0)
+            .with_melody([Some(0), Some(3), Some(3)])
+            .with_melody_instrument(SynthInstrument::PLUCK)
+    }
+
+    /// Set the tempo in beats per minute.
+    pub fn with_tempo(mut self, bpm: f32) -> Self {
+        self.tempo = bpm.max(1.0);
+        self
+    }
+
+    /// Set the number of grid steps per beat.
+    pub fn with_steps_per_beat(mut self, steps: u32) -> Self {
+        self.steps_per_beat = steps.max(1);
+        self
+    }
+
+    /// Melody as scale degrees per step; `None` rests.
+    pub fn with_melody(mut self, degrees: impl IntoIterator<Item = Option<i32>>) -> Self {
+        self.melody = degrees.into_iter().collect();
+        self
+    }
+
+    /// Arpeggiate `chord` (scale degrees) for `steps` steps.
+    pub fn with_arpeggio(mut self, chord: &[i32], pattern: ArpeggioPattern, steps: usize) -> Self {
+        self.arpeggio = (!chord.is_empty()).then(|| Arpeggio {
+            chord: chord.to_vec(),
+            pattern,
+            steps,
+        });
+        self
+    }
+
+    /// Instrument of the melody.
+    pub fn with_melody_instrument(mut self, instrument: SynthInstrument) -> Self {
+        self.melody_instrument = instrument;
+        self
+    }
+
+    /// Instrument of the arpeggio.
+    pub fn with_arpeggio_instrument(mut self, instrument: SynthInstrument) -> Self {
+        self.arpeggio_instrument = instrument;
+        self
+    }
+
+    /// Drum pattern played on channel C.
+    pub fn with_drums(mut self, drums: DrumPattern) -> Self {
+        self.drums = drums;
+        self
+    }
+
+    /// Length of one grid step in seconds.
+    pub fn step_seconds(&self) -> f64 {
+        60.0 / (f64::from(self.tempo) * f64::from(self.steps_per_beat))
+    }
+
+    /// Number of steps until the last part ends.
+    pub fn steps(&self) -> usize {
+        let arpeggio = self.arpeggio.as_ref().map_or(0, |arp| arp.steps);
+        self.melody.len().max(arpeggio).max(self.drums.0.len())
+    }
+
+    /// Length of the jingle in seconds.
+    pub fn duration(&self) -> f64 {
+        self.steps() as f64 * self.step_seconds()
+    }
+
+    /// Notes of the jingle with the first step at `start` seconds.
+    pub fn notes(&self, start: f64) -> Vec<SynthNote> {
+        let step = self.step_seconds();
+        let at = |index: usize| start + index as f64 * step;
+        let mut notes = Vec::new();
+
+        for (index, degree) in self.melody.iter().enumerate() {
+            if let Some(degree) = degree {
+                let note = self.scale.note(self.root, *degree);
+                notes.push(
+                    SynthNote::midi(0, note, at(index), step * GATE)
+                        .with_instrument(self.melody_instrument),
+                );
+            }
+        }
+
+        if let Some(arp) = &self.arpeggio {
+            for index in 0..arp.steps {
+                let degree = arp.chord[arp.pattern.index(index, arp.chord.len())];
+                let note = self.scale.note(self.root, degree);
+                notes.push(
+                    SynthNote::midi(1, note, at(index), step * GATE)
+                        .with_instrument(self.arpeggio_instrument),
+                );
+            }
+        }
+
+        for (index, hit) in self.drums.0.iter().enumerate() {
+            if let Some(hit) = hit {
+                let (frequency, instrument) = hit.voice();
+                notes.push(
+                    SynthNote::new(2, frequency, at(index), step * GATE)
+                        .with_instrument(instrument),
+                );
+            }
+        }
+        notes
+    }
+
+    /// Play the jingle now on a live synth; returns its end on the audio clock.
+    pub fn play(&self, synth: &YmSynthController) -> f64 {
+        self.play_at(synth, synth.audio_time())
+    }
+
+    /// Play the jingle at `start` on the synth's audio clock; returns its end.
+    pub fn play_at(&self, synth: &YmSynthController, start: f64) -> f64 {
+        synth.schedule_notes(self.notes(start));
+        start + self.duration()
+    }
+
+    /// Render the jingle to mono PCM at the plugin sample rate with the
+    /// cycle-accurate emulator.
+    pub fn render(&self) -> Vec<f32> {
+        self.render_with::<Ym2149>()
+    }
+
+    /// Render the jingle to mono PCM on backend `B`, e.g. `SoftSynth`.
+    pub fn render_with<B: Ym2149Backend>(&self) -> Vec<f32> {
+        let controller = YmSynthController::new();
+        controller.schedule_notes(self.notes(0.0));
+        let mut chip = B::new();
+        let seconds = self.duration() + RENDER_TAIL_SECONDS;
+        let len = (seconds * f64::from(YM2149_SAMPLE_RATE)) as usize;
+        (0..len)
+            .map(|_| {
+                controller.sync_sample(&mut chip);
+                chip.clock();
+                chip.get_sample()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::midi_note_frequency;
+
+    #[test]
+    fn scale_degrees_wrap_octaves() {
+        assert_eq!(Scale::Major.note(60, 0), 60);
+        assert_eq!(Scale::Major.note(60, 4), 67);
+        assert_eq!(Scale::Major.note(60, 7), 72);
+        assert_eq!(Scale::Major.note(60, -1), 59);
+        assert_eq!(Scale::MinorPentatonic.note(60, 6), 75);
+    }
+
+    #[test]
+    fn arpeggio_patterns_walk_the_chord() {
+        let walk = |pattern: ArpeggioPattern| -> Vec<usize> {
+            (0..6).map(|step| pattern.index(step, 3)).collect()
+        };
+        assert_eq!(walk(ArpeggioPattern::Up), [0, 1, 2, 0, 1, 2]);
+        assert_eq!(walk(ArpeggioPattern::Down), [2, 1, 0, 2, 1, 0]);
+        assert_eq!(walk(ArpeggioPattern::UpDown), [0, 1, 2, 1, 0, 1]);
+        assert_eq!(walk(ArpeggioPattern::Alternate), [0, 1, 0, 2, 0, 1]);
+    }
+
+    #[test]
+    fn jingle_lays_parts_on_separate_channels() {
+        let jingle = Jingle::new(60, Scale::Major)
+            .with_tempo(120.0)
+            .with_melody([Some(0), None, Some(2)])
+            .with_arpeggio(&[0, 2], ArpeggioPattern::Up, 2)
+            .with_drums(DrumPattern::new("k.h.s"));
+        assert_eq!(jingle.steps(), 5);
+        assert!((jingle.duration() - 0.625).abs() < 1e-9);
+
+        let notes = jingle.notes(1.0);
+        let channel = |c: usize| notes.iter().filter(|n| n.channel == c).count();
+        assert_eq!((channel(0), channel(1), channel(2)), (2, 2, 3));
+        let third = notes
+            .iter()
+            .find(|n| n.channel == 0 && n.start > 1.0)
+            .unwrap();
+        assert!((third.start - 1.25).abs() < 1e-9);
+        assert!((third.frequency - midi_note_frequency(64)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn render_produces_sound_on_any_backend() {
+        let jingle = Jingle::pickup();
+        let pcm = jingle.render();
+        let expected = (jingle.duration() + RENDER_TAIL_SECONDS) * f64::from(YM2149_SAMPLE_RATE);
+        assert_eq!(pcm.len(), expected as usize);
+        assert!(pcm.iter().any(|s| s.abs() > 0.01));
+
+        let soft = jingle.render_with::<ym2149_softsynth::SoftSynth>();
+        assert_eq!(soft.len(), pcm.len());
+        assert!(soft.iter().any(|s| s.abs() > 0.01));
+    }
+}
@@ -20,7 +20,7 @@
 //! - **Live Channel Visualization**: Real-time visual feedback for all three PSG channels with frequency/note info
 //! - **Metadata Display**: Automatic extraction and display of song title and artist information
 //! - **Frame-by-Frame Access**: Direct access to individual playback frames for analysis
-//! - **Time-Accurate Pacing**: Proper time-based frame advancement matching original YM file rate
+//! - **Time-Accurate Pacing**: Frames advance with the audio output's consumed samples, free of clock drift
 //! - **Audio Buffering**: Ring buffer architecture for smooth, artifact-free playback
 //! - **Multiple Playbacks**: Support for simultaneous independent YM file playbacks
 //!
@@ -99,21 +99,26 @@
 pub mod chip_state;
 pub mod error;
 pub mod events;
+pub mod jingle;
+pub mod lifecycle;
 pub mod music_state;
 pub mod patterns;
 pub mod playback;
 pub mod playlist;
 pub mod plugin;
 pub mod presets;
+pub mod sync_group;
 pub mod synth;
 
 // Semi-public modules - advanced features (documented but not primary API)
 pub mod audio_bridge;
+pub mod audio_reactive;
 pub mod audio_source;
 pub mod oscilloscope;
+#[cfg(feature = "diagnostics-overlay")]
+pub mod overlay;
 
 // Internal modules - implementation details (not part of public API)
-pub(crate) mod audio_reactive;
 pub mod diagnostics;
 pub(crate) mod song_player;
 pub(crate) mod streaming;
@@ -124,15 +129,23 @@ pub use ::ym2149::*;
 
 // Re-export common types from ym2149-common for unified API
 pub use ym2149_common::MetadataFields;
+#[cfg(feature = "fx")]
+pub use ym2149_common::{EffectChain, FxConfig, InsertEffect};
 
 // === Primary Public API ===
 
 // Plugin and configuration
-pub use plugin::{Ym2149Plugin, Ym2149PluginConfig};
+pub use plugin::{ChipBackend, FocusLoss, HotReload, Ym2149Plugin, Ym2149PluginConfig};
+
+// State-scoped playback and window focus handling
+pub use lifecycle::{PlayInState, Ym2149Focus, Ym2149StateScopePlugin};
 
 // Playback control (main user-facing types)
 pub use playback::{PlaybackState, Ym2149Playback, Ym2149Settings};
 
+// Phase-locked playback groups
+pub use sync_group::SyncGroup;
+
 // Register snapshot for visualization
 pub use chip_state::ChipStateSnapshot;
 
@@ -140,32 +153,48 @@ pub use chip_state::ChipStateSnapshot;
 pub use error::{BevyYm2149Error, Result};
 
 // Events for user systems to react to
-pub use events::{PatternTriggered, PlaybackFrameMarker, TrackFinished, TrackStarted};
+pub use events::{
+    CrossfadeProgress, PatternTriggered, PlaybackFrameMarker, TrackFinished, TrackStarted,
+};
 
 // Music state machine
-pub use music_state::{MusicStateDefinition, MusicStateGraph};
+pub use music_state::{
+    MusicStateDefinition, MusicStateGraph, MusicTransition, PendingMusicTransition, TransitionKind,
+    TransitionQuantize,
+};
 
 // Patterns for game integration
-pub use patterns::{PatternTrigger, PatternTriggerSet};
+pub use patterns::{PatternTrigger, PatternTriggerSet, RegisterCondition};
 
 // Playlist support
 pub use playlist::{
-    CrossfadeConfig, PlaylistMode, PlaylistSource, Ym2149Playlist, Ym2149PlaylistPlayer,
+    CrossfadeConfig, CrossfadeCurve, PlaylistMode, PlaylistSource, Ym2149Playlist,
+    Ym2149PlaylistPlayer,
 };
 
 // Synth controller
-pub use synth::YmSynthController;
+pub use synth::{SynthInstrument, SynthNote, YmSynthController};
+
+// Procedural jingles
+pub use jingle::{ArpeggioPattern, DrumHit, DrumPattern, Jingle, Scale};
 
 // === Advanced API (documented, for power users) ===
 
+// Audio-reactive metrics for visuals and gameplay
+pub use audio_reactive::{AudioReactiveConfig, AudioReactiveState, ReactiveMetrics};
+
 // Audio bridge for custom audio routing
 pub use audio_bridge::{
-    AudioBridgeBuffers, AudioBridgeMix, AudioBridgeMixes, AudioBridgeTargets, BridgeAudioDevice,
-    BridgeAudioSinks,
+    AudioBridgeBuffers, AudioBridgeMix, AudioBridgeMixes, AudioBridgeTargets, AudioBus, AudioBuses,
+    BridgeAudioDevice, BridgeAudioSinks, apply_audio_buses,
 };
 
 // Audio source for direct asset manipulation
-pub use audio_source::{Ym2149AudioSource, Ym2149Loader, Ym2149Metadata};
+pub use audio_source::{
+    ArkosLoader, AyLoader, SndhLoader, Ym2149AudioSource, Ym2149Loader, Ym2149Metadata,
+    Ym2149SongBytes,
+};
+pub use song_player::SongFormat;
 
 // Oscilloscope buffer for visualization
 pub use oscilloscope::OscilloscopeBuffer;
@@ -182,9 +211,14 @@ pub use playlist::{
 };
 
 // Music state processing
-pub use music_state::process_music_state_requests;
+pub use music_state::{
+    MusicStateSwitcher, advance_music_transitions, process_music_state_requests,
+};
 
 // Diagnostics
 pub use diagnostics::{
-    BUFFER_FILL_PATH, FRAME_POSITION_PATH, register as register_diagnostics, update_diagnostics,
+    BUFFER_FILL_PATH, CLOCK_DRIFT_PATH, FRAME_POSITION_PATH, register as register_diagnostics,
+    update_diagnostics,
 };
+#[cfg(feature = "diagnostics-overlay")]
+pub use overlay::{DiagnosticsOverlay, DiagnosticsOverlayRoot, Ym2149DiagnosticsOverlayPlugin};
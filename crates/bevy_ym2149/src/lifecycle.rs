@@ -0,0 +1,159 @@
+//! Playback lifecycle integration with Bevy states and window focus.
+//!
+//! - [`PlayInState`] scopes a playback to a state: it is paused when the app
+//!   leaves the state and resumed when it comes back. Register the state type
+//!   with [`Ym2149StateScopePlugin`].
+//! - [`Ym2149PluginConfig::focus_loss`] pauses or ducks every playback while
+//!   no window has focus; [`Ym2149Focus`] tracks the current focus.
+//!
+//! ```no_run
+//! # use bevy::prelude::*;
+//! # use bevy_ym2149::{PlayInState, Ym2149Playback, Ym2149Plugin, Ym2149StateScopePlugin};
+//! #[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+//! enum GameState {
+//!     #[default]
+//!     Menu,
+//!     InGame,
+//! }
+//!
+//! App::new()
+//!     .add_plugins((DefaultPlugins, Ym2149Plugin::default()))
+//!     .add_plugins(Ym2149StateScopePlugin::<GameState>::default())
+//!     .init_state::<GameState>()
+//!     .add_systems(Startup, |mut commands: Commands| {
+//!         let mut music = Ym2149Playback::new("music/menu.ym");
+//!         music.play();
+//!         commands.spawn((music, PlayInState::new(GameState::Menu)));
+//!     });
+//! ```
+
+use crate::playback::Ym2149Playback;
+use crate::plugin::{FocusLoss, Ym2149PluginConfig};
+use bevy::prelude::*;
+use std::marker::PhantomData;
+
+/// Plays a [`Ym2149Playback`] only while the app is in `state`.
+///
+/// Leaving the state pauses a playing song; entering it again resumes songs
+/// paused that way. Songs paused or stopped by other code stay as they are.
+#[derive(Component, Clone, Debug)]
+pub struct PlayInState<S: States> {
+    /// State in which the playback may play.
+    pub state: S,
+    paused_by_scope: bool,
+}
+
+impl<S: States> PlayInState<S> {
+    /// Scope a playback to `state`.
+    pub fn new(state: S) -> Self {
+        Self {
+            state,
+            paused_by_scope: false,
+        }
+    }
+}
+
+/// Enables [`PlayInState<S>`] for the state type `S`.
+pub struct Ym2149StateScopePlugin<S: States>(PhantomData<fn() -> S>);
+
+impl<S: States> Default for Ym2149StateScopePlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: States> Plugin for Ym2149StateScopePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            enforce_state_scope::<S>
+                .after(track_window_focus)
+                .run_if(resource_exists::<State<S>>),
+        );
+    }
+}
+
+/// Pause scoped playbacks outside their state and resume them inside it.
+///
+/// Runs every frame, so songs started while the state does not match are
+/// held as well.
+pub fn enforce_state_scope<S: States>(
+    state: Res<State<S>>,
+    mut playbacks: Query<(&mut Ym2149Playback, &mut PlayInState<S>)>,
+) {
+    for (mut playback, mut scope) in playbacks.iter_mut() {
+        if *state.get() == scope.state {
+            if scope.paused_by_scope {
+                scope.paused_by_scope = false;
+                playback.resume();
+            }
+        } else if playback.is_playing() {
+            playback.pause();
+            scope.paused_by_scope = true;
+        }
+    }
+}
+
+/// Window focus as seen by the plugin.
+#[derive(Resource, Debug)]
+pub struct Ym2149Focus {
+    focused: bool,
+    /// Playbacks paused by [`FocusLoss::Pause`], resumed on focus gain
+    paused: Vec<Entity>,
+}
+
+impl Default for Ym2149Focus {
+    fn default() -> Self {
+        Self {
+            focused: true,
+            paused: Vec::new(),
+        }
+    }
+}
+
+impl Ym2149Focus {
+    /// Whether any window has focus (always true without windows).
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    /// Gain multiplier for [`FocusLoss::Duck`], 1.0 otherwise.
+    pub(crate) fn duck_gain(&self, behavior: FocusLoss) -> f32 {
+        match behavior {
+            FocusLoss::Duck(gain) if !self.focused => gain.clamp(0.0, 1.0),
+            _ => 1.0,
+        }
+    }
+}
+
+/// Track window focus and apply [`Ym2149PluginConfig::focus_loss`].
+///
+/// Ducking is applied by [`apply_audio_buses`](crate::apply_audio_buses) so it
+/// ramps like any other bus change.
+pub fn track_window_focus(
+    config: Res<Ym2149PluginConfig>,
+    windows: Query<&Window>,
+    mut focus: ResMut<Ym2149Focus>,
+    mut playbacks: Query<(Entity, &mut Ym2149Playback)>,
+) {
+    let focused = windows.is_empty() || windows.iter().any(|window| window.focused);
+    if focused == focus.focused {
+        return;
+    }
+    focus.focused = focused;
+
+    if focused {
+        for entity in std::mem::take(&mut focus.paused) {
+            if let Ok((_, mut playback)) = playbacks.get_mut(entity) {
+                playback.resume();
+            }
+        }
+    } else if config.focus_loss == FocusLoss::Pause {
+        for (entity, mut playback) in playbacks.iter_mut() {
+            if playback.is_playing() {
+                playback.pause();
+                focus.paused.push(entity);
+            }
+        }
+    }
+}
@@ -2,12 +2,22 @@
 //!
 //! This module provides a graph-based state machine for switching between
 //! different music tracks or playlists based on game events.
+//!
+//! Each state can carry a [`MusicTransition`] describing *when* the switch
+//! happens (immediately, on the next beat/bar/pattern or at the loop point)
+//! and *how* (hard cut, crossfade, or a stinger followed by the new track).
 
 use crate::events::MusicStateRequest;
-use crate::playback::Ym2149Playback;
-use crate::playlist::{Ym2149Playlist, Ym2149PlaylistPlayer, apply_playlist_entry};
+use crate::playback::{CrossfadeRequest, PlaybackState, TrackSource, Ym2149Playback};
+use crate::playlist::{
+    CrossfadeCurve, Ym2149Playlist, Ym2149PlaylistPlayer, apply_playlist_entry,
+    resolve_track_source,
+};
+use crate::plugin::Ym2149PluginConfig;
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use std::collections::HashMap;
+use std::sync::Arc;
 
 /// Definition of a named music state.
 #[derive(Clone)]
@@ -20,11 +30,79 @@ pub enum MusicStateDefinition {
     Bytes(Vec<u8>),
 }
 
+/// Musical position at which a state change takes effect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TransitionQuantize {
+    /// Switch as soon as the request is processed.
+    #[default]
+    Immediate,
+    /// Switch on the next beat ([`Ym2149PluginConfig::frames_per_beat`], 50 frames by default).
+    Beat,
+    /// Switch on the next bar of the given number of beats.
+    Bar(u32),
+    /// Switch on the next multiple of this many frames, e.g. a 64-row pattern
+    /// at speed 6 is 384 frames.
+    Frames(u32),
+    /// Switch when the current track loops or finishes.
+    LoopPoint,
+}
+
+/// How the new state's music replaces the current one.
+#[derive(Clone, Default)]
+pub enum TransitionKind {
+    /// Stop the current track and start the new one.
+    #[default]
+    Cut,
+    /// Mix both tracks for the given number of seconds.
+    Crossfade(f32),
+    /// Cut to a short stinger, then cut to the new state once the stinger ends.
+    Stinger(MusicStateDefinition),
+}
+
+/// When and how a music state is entered.
+#[derive(Clone, Default)]
+pub struct MusicTransition {
+    /// Musical position at which the switch happens.
+    pub quantize: TransitionQuantize,
+    /// Cut, crossfade or stinger.
+    pub kind: TransitionKind,
+}
+
+impl MusicTransition {
+    /// Immediate hard cut (the default).
+    pub fn cut() -> Self {
+        Self::default()
+    }
+
+    /// Crossfade over `seconds`.
+    pub fn crossfade(seconds: f32) -> Self {
+        Self {
+            kind: TransitionKind::Crossfade(seconds),
+            ..Self::default()
+        }
+    }
+
+    /// Play `stinger` first, then switch to the state.
+    pub fn stinger(stinger: MusicStateDefinition) -> Self {
+        Self {
+            kind: TransitionKind::Stinger(stinger),
+            ..Self::default()
+        }
+    }
+
+    /// Delay the transition to the given musical position.
+    pub fn quantized(mut self, quantize: TransitionQuantize) -> Self {
+        self.quantize = quantize;
+        self
+    }
+}
+
 /// Graph mapping state names to definitions along with an optional default target entity.
 #[derive(Resource, Default)]
 pub struct MusicStateGraph {
     target: Option<Entity>,
     states: HashMap<String, MusicStateDefinition>,
+    transitions: HashMap<String, MusicTransition>,
 }
 
 impl MusicStateGraph {
@@ -43,6 +121,28 @@ impl MusicStateGraph {
         self.states.insert(name.into(), definition);
     }
 
+    /// Register or replace a state definition entered with `transition`.
+    pub fn insert_with_transition(
+        &mut self,
+        name: impl Into<String>,
+        definition: MusicStateDefinition,
+        transition: MusicTransition,
+    ) {
+        let name = name.into();
+        self.transitions.insert(name.clone(), transition);
+        self.states.insert(name, definition);
+    }
+
+    /// Set how an existing or future state is entered.
+    pub fn set_transition(&mut self, name: impl Into<String>, transition: MusicTransition) {
+        self.transitions.insert(name.into(), transition);
+    }
+
+    /// Transition used when entering the named state (an immediate cut unless configured).
+    pub fn transition(&self, name: &str) -> MusicTransition {
+        self.transitions.get(name).cloned().unwrap_or_default()
+    }
+
     /// Retrieve a state definition by name.
     pub fn get(&self, name: &str) -> Option<&MusicStateDefinition> {
         self.states.get(name)
@@ -54,36 +154,137 @@ impl MusicStateGraph {
     }
 }
 
-/// Process queued music state requests, switching the associated playback sources.
-pub fn process_music_state_requests(
-    mut commands: Commands,
-    mut events: MessageReader<MusicStateRequest>,
-    graph: Res<MusicStateGraph>,
-    mut playbacks: Query<&mut Ym2149Playback>,
-    mut playlist_players: Query<&mut Ym2149PlaylistPlayer>,
-    playlists: Res<Assets<Ym2149Playlist>>,
-    asset_server: Res<AssetServer>,
-) {
-    for request in events.read() {
-        let Some(definition) = graph.get(&request.state) else {
-            warn!("Requested music state '{}' not found", request.state);
-            continue;
-        };
+/// A music state change waiting for its quantization point.
+///
+/// Inserted on the playback entity by [`process_music_state_requests`] and
+/// removed once the switch happens. Remove it yourself to cancel the change.
+#[derive(Component, Clone)]
+pub struct PendingMusicTransition {
+    state: String,
+    definition: MusicStateDefinition,
+    kind: TransitionKind,
+    quantize: TransitionQuantize,
+    requested_frame: u32,
+    last_frame: u32,
+}
 
-        let target = request.target.or_else(|| graph.target());
-        let Some(entity) = target else {
-            warn!("Music state '{}' had no target entity", request.state);
-            continue;
-        };
+impl PendingMusicTransition {
+    /// Name of the state that will be entered.
+    pub fn state(&self) -> &str {
+        &self.state
+    }
 
-        let Ok(mut playback) = playbacks.get_mut(entity) else {
-            warn!(
-                "Music state '{}' target entity missing Ym2149Playback",
-                request.state
-            );
-            continue;
+    /// Musical position the transition waits for.
+    pub fn quantize(&self) -> TransitionQuantize {
+        self.quantize
+    }
+
+    /// Whether the switch point has been reached at `frame`.
+    fn is_due(&self, frame: u32, finished: bool, frames_per_beat: u64) -> bool {
+        // Looping (the frame counter wrapped) or finishing is a boundary for every mode.
+        if finished || frame < self.last_frame {
+            return true;
+        }
+        let step = match self.quantize {
+            TransitionQuantize::Immediate => return true,
+            TransitionQuantize::LoopPoint => return false,
+            TransitionQuantize::Beat => frames_per_beat,
+            TransitionQuantize::Bar(beats) => frames_per_beat * u64::from(beats.max(1)),
+            TransitionQuantize::Frames(frames) => u64::from(frames.max(1)),
         };
+        let next_boundary = (u64::from(self.requested_frame) / step + 1) * step;
+        u64::from(frame) >= next_boundary
+    }
+}
+
+/// Resources needed to switch a playback entity to a new music state.
+#[derive(SystemParam)]
+pub struct MusicStateSwitcher<'w, 's> {
+    commands: Commands<'w, 's>,
+    playlist_players: Query<'w, 's, &'static mut Ym2149PlaylistPlayer>,
+    playlists: Res<'w, Assets<Ym2149Playlist>>,
+    asset_server: Res<'w, AssetServer>,
+}
 
+impl MusicStateSwitcher<'_, '_> {
+    /// Enter `state` on `entity` right away using the given transition kind.
+    fn enter(
+        &mut self,
+        entity: Entity,
+        playback: &mut Ym2149Playback,
+        state: &str,
+        definition: &MusicStateDefinition,
+        kind: &TransitionKind,
+    ) {
+        match kind {
+            TransitionKind::Cut => self.cut_to(entity, playback, state, definition),
+            TransitionKind::Crossfade(seconds) => {
+                if !playback.is_playing() {
+                    self.cut_to(entity, playback, state, definition);
+                    return;
+                }
+                let Some(source) = self.crossfade_source(entity, definition) else {
+                    self.cut_to(entity, playback, state, definition);
+                    return;
+                };
+                playback.set_crossfade_request(CrossfadeRequest {
+                    source,
+                    duration: seconds.max(0.1),
+                    target_index: 0,
+                    curve: CrossfadeCurve::default(),
+                    start_at: None,
+                });
+            }
+            TransitionKind::Stinger(stinger) => {
+                self.cut_to(entity, playback, state, stinger);
+                self.commands.entity(entity).insert(PendingMusicTransition {
+                    state: state.to_string(),
+                    definition: definition.clone(),
+                    kind: TransitionKind::Cut,
+                    quantize: TransitionQuantize::LoopPoint,
+                    requested_frame: 0,
+                    last_frame: 0,
+                });
+            }
+        }
+    }
+
+    /// Track source for crossfading into `definition`, attaching playlists as needed.
+    fn crossfade_source(
+        &mut self,
+        entity: Entity,
+        definition: &MusicStateDefinition,
+    ) -> Option<TrackSource> {
+        match definition {
+            MusicStateDefinition::SourcePath(path) => Some(TrackSource::File(path.clone())),
+            MusicStateDefinition::Bytes(bytes) => Some(TrackSource::Bytes(Arc::new(bytes.clone()))),
+            MusicStateDefinition::Playlist(handle) => {
+                let entry = self.playlists.get(handle)?.tracks.first()?;
+                let source = resolve_track_source(entry, &self.asset_server);
+                self.attach_playlist(entity, handle);
+                Some(source)
+            }
+        }
+    }
+
+    fn attach_playlist(&mut self, entity: Entity, handle: &Handle<Ym2149Playlist>) {
+        if let Ok(mut controller) = self.playlist_players.get_mut(entity) {
+            controller.playlist = handle.clone();
+            controller.current_index = 0;
+        } else {
+            self.commands
+                .entity(entity)
+                .insert(Ym2149PlaylistPlayer::new(handle.clone()));
+        }
+    }
+
+    fn cut_to(
+        &mut self,
+        entity: Entity,
+        playback: &mut Ym2149Playback,
+        state: &str,
+        definition: &MusicStateDefinition,
+    ) {
         match definition.clone() {
             MusicStateDefinition::SourcePath(path) => {
                 playback.set_source_path(path);
@@ -96,22 +297,15 @@ pub fn process_music_state_requests(
                 playback.play();
             }
             MusicStateDefinition::Playlist(handle) => {
-                if let Ok(mut controller) = playlist_players.get_mut(entity) {
-                    controller.playlist = handle.clone();
-                    controller.current_index = 0;
-                } else {
-                    commands
-                        .entity(entity)
-                        .insert(Ym2149PlaylistPlayer::new(handle.clone()));
-                }
+                self.attach_playlist(entity, &handle);
 
-                if let Some(playlist) = playlists.get(&handle) {
+                if let Some(playlist) = self.playlists.get(&handle) {
                     if let Some(entry) = playlist.tracks.first() {
-                        apply_playlist_entry(entry, &mut playback, &asset_server);
+                        apply_playlist_entry(entry, playback, &self.asset_server);
                         playback.restart();
                         playback.play();
                     } else {
-                        warn!("Playlist for state '{}' had no tracks", request.state);
+                        warn!("Playlist for state '{}' had no tracks", state);
                     }
                 } else {
                     // Asset not yet loaded; the playlist advance system will apply once ready.
@@ -122,3 +316,96 @@ pub fn process_music_state_requests(
         }
     }
 }
+
+/// Process queued music state requests, switching the associated playback sources.
+///
+/// Immediate transitions are applied here; quantized ones are queued as a
+/// [`PendingMusicTransition`] and applied by [`advance_music_transitions`].
+pub fn process_music_state_requests(
+    mut events: MessageReader<MusicStateRequest>,
+    graph: Res<MusicStateGraph>,
+    mut playbacks: Query<&mut Ym2149Playback>,
+    mut switcher: MusicStateSwitcher,
+) {
+    for request in events.read() {
+        let Some(definition) = graph.get(&request.state) else {
+            warn!("Requested music state '{}' not found", request.state);
+            continue;
+        };
+
+        let target = request.target.or_else(|| graph.target());
+        let Some(entity) = target else {
+            warn!("Music state '{}' had no target entity", request.state);
+            continue;
+        };
+
+        let Ok(mut playback) = playbacks.get_mut(entity) else {
+            warn!(
+                "Music state '{}' target entity missing Ym2149Playback",
+                request.state
+            );
+            continue;
+        };
+
+        let transition = graph.transition(&request.state);
+        // A newer request replaces one still waiting for its boundary.
+        switcher
+            .commands
+            .entity(entity)
+            .remove::<PendingMusicTransition>();
+
+        // Nothing is advancing when stopped or paused, so there is no boundary to wait for.
+        if transition.quantize == TransitionQuantize::Immediate || !playback.is_playing() {
+            switcher.enter(
+                entity,
+                &mut playback,
+                &request.state,
+                definition,
+                &transition.kind,
+            );
+        } else {
+            let frame = playback.frame_position();
+            switcher
+                .commands
+                .entity(entity)
+                .insert(PendingMusicTransition {
+                    state: request.state.clone(),
+                    definition: definition.clone(),
+                    kind: transition.kind,
+                    quantize: transition.quantize,
+                    requested_frame: frame,
+                    last_frame: frame,
+                });
+        }
+    }
+}
+
+/// Apply queued [`PendingMusicTransition`]s once playback reaches their boundary.
+pub fn advance_music_transitions(
+    mut pending: Query<(Entity, &mut Ym2149Playback, &mut PendingMusicTransition)>,
+    mut switcher: MusicStateSwitcher,
+    config: Res<Ym2149PluginConfig>,
+) {
+    let frames_per_beat = config.frames_per_beat.unwrap_or(50).max(1);
+    for (entity, mut playback, mut transition) in pending.iter_mut() {
+        let frame = playback.frame_position();
+        let finished = playback.state == PlaybackState::Finished;
+        if !transition.is_due(frame, finished, frames_per_beat) {
+            transition.last_frame = frame;
+            continue;
+        }
+
+        switcher
+            .commands
+            .entity(entity)
+            .remove::<PendingMusicTransition>();
+        let transition = transition.clone();
+        switcher.enter(
+            entity,
+            &mut playback,
+            &transition.state,
+            &transition.definition,
+            &transition.kind,
+        );
+    }
+}
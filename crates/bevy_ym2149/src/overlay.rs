@@ -0,0 +1,318 @@
+//! On-screen diagnostics overlay (requires the `diagnostics-overlay` feature).
+//!
+//! [`Ym2149DiagnosticsOverlayPlugin`] draws a small bevy_ui panel with the
+//! buffer fill and frame position diagnostics, a heatmap of the 16 PSG
+//! registers and per-channel level meters. Add it next to
+//! [`Ym2149Plugin`](crate::Ym2149Plugin); the panel reads the same data paths
+//! as [`diagnostics`](crate::diagnostics), [`ChipStateSnapshot`] and
+//! [`AudioReactiveState`].
+
+use crate::audio_reactive::AudioReactiveState;
+use crate::chip_state::ChipStateSnapshot;
+use crate::diagnostics::{BUFFER_FILL_PATH, CLOCK_DRIFT_PATH, FRAME_POSITION_PATH};
+use bevy::diagnostic::{DiagnosticPath, DiagnosticsStore};
+use bevy::prelude::*;
+
+const PANEL_BG: Color = Color::srgba(0.02, 0.02, 0.04, 0.85);
+const CELL_COLD: Color = Color::srgb(0.08, 0.1, 0.16);
+const CELL_HOT: Color = Color::srgb(0.95, 0.45, 0.1);
+const METER_BG: Color = Color::srgba(0.18, 0.2, 0.24, 0.6);
+const METER_COLORS: [Color; 3] = [
+    Color::srgb(0.95, 0.35, 0.35),
+    Color::srgb(0.35, 0.9, 0.45),
+    Color::srgb(0.35, 0.6, 0.95),
+];
+const CELL_SIZE: f32 = 22.0;
+const METER_WIDTH: f32 = 120.0;
+/// Fraction of write highlight kept per frame.
+const HEAT_DECAY: f32 = 0.85;
+
+/// Largest meaningful value of each PSG register, for heatmap scaling.
+const REGISTER_MAX: [u8; 16] = [
+    255, 15, 255, 15, 255, 15, 31, 63, 31, 31, 31, 255, 255, 15, 255, 255,
+];
+
+/// Plugin that spawns and updates the diagnostics overlay.
+pub struct Ym2149DiagnosticsOverlayPlugin;
+
+impl Plugin for Ym2149DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsOverlay>();
+        app.init_resource::<ChipStateSnapshot>();
+        app.init_resource::<AudioReactiveState>();
+        app.add_systems(Startup, spawn_overlay);
+        app.add_systems(
+            Update,
+            (
+                toggle_overlay,
+                update_overlay_text,
+                update_register_heatmap,
+                update_channel_meters,
+            ),
+        );
+    }
+}
+
+/// Overlay settings.
+#[derive(Resource, Clone, Debug)]
+pub struct DiagnosticsOverlay {
+    /// Whether the panel is shown.
+    pub visible: bool,
+    /// Key that toggles the panel (default F3), or `None` to disable toggling.
+    pub toggle_key: Option<KeyCode>,
+}
+
+impl Default for DiagnosticsOverlay {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            toggle_key: Some(KeyCode::F3),
+        }
+    }
+}
+
+/// Root node of the overlay panel.
+#[derive(Component)]
+pub struct DiagnosticsOverlayRoot;
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct RegisterCell {
+    index: usize,
+    heat: f32,
+}
+
+#[derive(Component)]
+struct ChannelMeter(usize);
+
+fn spawn_overlay(mut commands: Commands, overlay: Res<DiagnosticsOverlay>) {
+    let visibility = if overlay.visible {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            BackgroundColor(PANEL_BG),
+            visibility,
+            DiagnosticsOverlayRoot,
+        ))
+        .with_children(|panel| {
+            panel.spawn((
+                Text::new("YM2149\nBuffer: --\nFrame: --"),
+                TextFont::from_font_size(12.0),
+                OverlayText,
+            ));
+
+            panel
+                .spawn(Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::px(8, CELL_SIZE),
+                    row_gap: Val::Px(2.0),
+                    column_gap: Val::Px(2.0),
+                    ..default()
+                })
+                .with_children(|grid| {
+                    for index in 0..16 {
+                        grid.spawn((
+                            Node {
+                                width: Val::Px(CELL_SIZE),
+                                height: Val::Px(CELL_SIZE),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BackgroundColor(CELL_COLD),
+                            RegisterCell { index, heat: 0.0 },
+                        ))
+                        .with_child((
+                            Text::new(format!("{index:X}")),
+                            TextFont::from_font_size(9.0),
+                        ));
+                    }
+                });
+
+            for (channel, color) in METER_COLORS.into_iter().enumerate() {
+                panel
+                    .spawn((
+                        Node {
+                            width: Val::Px(METER_WIDTH),
+                            height: Val::Px(8.0),
+                            ..default()
+                        },
+                        BackgroundColor(METER_BG),
+                    ))
+                    .with_child((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(color),
+                        ChannelMeter(channel),
+                    ));
+            }
+        });
+}
+
+fn toggle_overlay(
+    keys: Option<Res<ButtonInput<KeyCode>>>,
+    mut overlay: ResMut<DiagnosticsOverlay>,
+    mut roots: Query<&mut Visibility, With<DiagnosticsOverlayRoot>>,
+) {
+    if let (Some(keys), Some(key)) = (keys, overlay.toggle_key)
+        && keys.just_pressed(key)
+    {
+        overlay.visible = !overlay.visible;
+    }
+    if !overlay.is_changed() {
+        return;
+    }
+    for mut visibility in roots.iter_mut() {
+        *visibility = if overlay.visible {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_overlay_text(
+    store: Option<Res<DiagnosticsStore>>,
+    mut texts: Query<&mut Text, With<OverlayText>>,
+) {
+    let value = |path: &DiagnosticPath| {
+        store
+            .as_ref()
+            .and_then(|store| store.get(path))
+            .and_then(|diagnostic| diagnostic.value())
+    };
+    let buffer = value(&BUFFER_FILL_PATH)
+        .map(|fill| format!("{:.0}%", fill * 100.0))
+        .unwrap_or_else(|| "--".into());
+    let frame = value(&FRAME_POSITION_PATH)
+        .map(|frame| format!("{frame:.0}"))
+        .unwrap_or_else(|| "--".into());
+    let drift = value(&CLOCK_DRIFT_PATH)
+        .map(|drift| format!("{drift:+.1} ms"))
+        .unwrap_or_else(|| "--".into());
+    for mut text in texts.iter_mut() {
+        text.0 = format!("YM2149\nBuffer: {buffer}\nFrame: {frame}\nDrift: {drift}");
+    }
+}
+
+fn update_register_heatmap(
+    snapshot: Res<ChipStateSnapshot>,
+    mut previous: Local<[u8; 16]>,
+    mut cells: Query<(&mut RegisterCell, &mut BackgroundColor)>,
+) {
+    for (mut cell, mut color) in cells.iter_mut() {
+        let value = snapshot.registers[cell.index];
+        cell.heat = if value != previous[cell.index] {
+            1.0
+        } else {
+            cell.heat * HEAT_DECAY
+        };
+        let level =
+            (value.min(REGISTER_MAX[cell.index]) as f32 / REGISTER_MAX[cell.index] as f32) * 0.5;
+        color.0 = CELL_COLD.mix(&CELL_HOT, (level + cell.heat * 0.5).min(1.0));
+    }
+    *previous = snapshot.registers;
+}
+
+fn update_channel_meters(
+    state: Res<AudioReactiveState>,
+    mut meters: Query<(&ChannelMeter, &mut Node)>,
+) {
+    let mut levels = [0.0f32; 3];
+    for metrics in state.metrics.values() {
+        for (level, envelope) in levels.iter_mut().zip(metrics.envelope) {
+            *level = level.max(envelope);
+        }
+    }
+    for (meter, mut node) in meters.iter_mut() {
+        node.width = Val::Percent(levels[meter.0].clamp(0.0, 1.0) * 100.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_reactive::ReactiveMetrics;
+
+    #[test]
+    fn overlay_tracks_registers_and_levels() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, Ym2149DiagnosticsOverlayPlugin));
+        app.update();
+
+        let cells = app
+            .world_mut()
+            .query::<&RegisterCell>()
+            .iter(app.world())
+            .count();
+        assert_eq!(cells, 16);
+
+        let entity = app.world_mut().spawn_empty().id();
+        let mut metrics = ReactiveMetrics::new();
+        metrics.envelope = [0.5, 0.0, 0.25];
+        app.world_mut()
+            .resource_mut::<AudioReactiveState>()
+            .metrics
+            .insert(entity, metrics);
+        app.world_mut()
+            .resource_mut::<ChipStateSnapshot>()
+            .update_from_registers([0x0F; 16]);
+        app.update();
+
+        let world = app.world_mut();
+        let mut widths: Vec<_> = world
+            .query::<(&ChannelMeter, &Node)>()
+            .iter(world)
+            .map(|(meter, node)| (meter.0, node.width))
+            .collect();
+        widths.sort_by_key(|(channel, _)| *channel);
+        assert_eq!(
+            widths,
+            vec![
+                (0, Val::Percent(50.0)),
+                (1, Val::Percent(0.0)),
+                (2, Val::Percent(25.0))
+            ]
+        );
+        assert!(
+            world
+                .query::<&RegisterCell>()
+                .iter(world)
+                .all(|cell| cell.heat == 1.0)
+        );
+    }
+
+    #[test]
+    fn overlay_visibility_follows_resource() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, Ym2149DiagnosticsOverlayPlugin));
+        app.update();
+
+        app.world_mut().resource_mut::<DiagnosticsOverlay>().visible = false;
+        app.update();
+
+        let world = app.world_mut();
+        let visibility = world
+            .query_filtered::<&Visibility, With<DiagnosticsOverlayRoot>>()
+            .single(world)
+            .unwrap();
+        assert_eq!(*visibility, Visibility::Hidden);
+    }
+}
@@ -3,6 +3,10 @@
 //! Attach [`PatternTriggerSet`] to a [`Ym2149Playback`](crate::playback::Ym2149Playback)
 //! entity to receive [`PatternTriggered`](crate::events::PatternTriggered) events
 //! whenever a channel matches your criteria.
+//!
+//! Besides amplitude and frequency, triggers can test the PSG registers of the
+//! current frame through [`RegisterCondition`]s, e.g. to fire on every noise
+//! drum hit without annotating frames by hand.
 
 use bevy::prelude::{Component, Entity, Resource};
 use std::collections::HashMap;
@@ -11,7 +15,8 @@ use std::collections::HashMap;
 ///
 /// A trigger matches when the configured channel's average amplitude
 /// surpasses `min_amplitude` (0.0–1.0) and, optionally, when the reported
-/// frequency is within `frequency_tolerance_hz` of `frequency_hz`.
+/// frequency is within `frequency_tolerance_hz` of `frequency_hz`. Every
+/// entry in `conditions` must hold as well.
 #[derive(Clone, Debug)]
 pub struct PatternTrigger {
     /// Application-defined identifier returned via [`PatternTriggered`](crate::events::PatternTriggered).
@@ -26,6 +31,8 @@ pub struct PatternTrigger {
     pub frequency_tolerance_hz: f32,
     /// Cooldown in frames before the pattern may fire again.
     pub cooldown_frames: u64,
+    /// Register predicates that must all hold for the trigger to fire.
+    pub conditions: Vec<RegisterCondition>,
 }
 
 impl PatternTrigger {
@@ -40,6 +47,7 @@ impl PatternTrigger {
             frequency_hz: None,
             frequency_tolerance_hz: 12.0,
             cooldown_frames: 0,
+            conditions: Vec::new(),
         }
     }
 
@@ -61,6 +69,66 @@ impl PatternTrigger {
         self.cooldown_frames = frames;
         self
     }
+
+    /// Require a register condition on the trigger's channel.
+    ///
+    /// Combine with `with_min_amplitude(0.0)` to match on registers alone.
+    pub fn with_condition(mut self, condition: RegisterCondition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+}
+
+/// Chip-state predicate evaluated against the PSG registers of a frame.
+///
+/// Channel-specific conditions refer to the trigger's channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegisterCondition {
+    /// Channel volume (R8–R10, 0–15) is strictly above the threshold.
+    /// A channel in envelope mode counts as volume 15.
+    VolumeAbove(u8),
+    /// Noise is enabled for the channel in the mixer (R7).
+    NoiseEnabled,
+    /// Tone is enabled for the channel in the mixer (R7).
+    ToneEnabled,
+    /// The channel uses the hardware envelope.
+    EnvelopeEnabled,
+    /// The envelope shape (R13) changed since the previous frame.
+    ///
+    /// Rewriting R13 with the same shape cannot be seen in register dumps
+    /// and does not match.
+    EnvelopeRetrigger,
+}
+
+impl RegisterCondition {
+    /// Whether the condition holds for `channel` given this frame's
+    /// registers and, if known, the previous frame's.
+    pub(crate) fn matches(
+        self,
+        channel: usize,
+        registers: &[u8; 16],
+        previous: Option<&[u8; 16]>,
+    ) -> bool {
+        let channel = channel.min(2);
+        let volume = registers[8 + channel];
+        let mixer = registers[7];
+        match self {
+            Self::VolumeAbove(threshold) => {
+                let level = if volume & 0x10 != 0 {
+                    15
+                } else {
+                    volume & 0x0F
+                };
+                level > threshold
+            }
+            Self::NoiseEnabled => mixer & (0x08 << channel) == 0,
+            Self::ToneEnabled => mixer & (0x01 << channel) == 0,
+            Self::EnvelopeEnabled => volume & 0x10 != 0,
+            Self::EnvelopeRetrigger => {
+                previous.is_some_and(|previous| previous[13] != registers[13])
+            }
+        }
+    }
 }
 
 /// Component that stores multiple pattern triggers for a playback entity.
@@ -31,7 +31,8 @@
 //! }
 //! ```
 
-use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata};
+use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata, Ym2149SongBytes};
+use crate::playlist::CrossfadeCurve;
 use crate::song_player::{SharedSongPlayer, YmSongPlayer};
 use crate::synth::YmSynthController;
 use bevy::prelude::*;
@@ -105,6 +106,7 @@ pub(crate) enum TrackSource {
     File(String),
     Asset(Handle<Ym2149AudioSource>),
     Bytes(Arc<Vec<u8>>),
+    SongBytes(Handle<Ym2149SongBytes>),
 }
 
 /// Pending crossfade to be loaded by the playback systems.
@@ -113,6 +115,10 @@ pub(crate) struct CrossfadeRequest {
     pub source: TrackSource,
     pub duration: f32,
     pub target_index: usize,
+    pub curve: CrossfadeCurve,
+    /// Song position (seconds) of the outgoing track at which the fade begins;
+    /// `None` starts it as soon as the deck is loaded
+    pub start_at: Option<f32>,
 }
 
 /// Active crossfade layer being mixed alongside the primary player.
@@ -133,11 +139,17 @@ pub(crate) struct ActiveCrossfade {
     pub elapsed: f32,
     pub duration: f32,
     pub target_index: usize,
+    pub curve: CrossfadeCurve,
+    /// Outgoing song position at which the fade begins (`None` once fading)
+    pub start_at: Option<f32>,
     pub audio_handle: Handle<crate::audio_source::Ym2149AudioSource>,
     /// Raw YM data for recreating the AudioPlayer after crossfade completes
     pub data: Arc<Vec<u8>>,
     /// Entity of the separate AudioPlayer playing the incoming track during crossfade
     pub crossfade_entity: Option<Entity>,
+    /// Audio stream and player of the incoming deck, taken over on completion
+    pub audio_stream_state: Arc<crate::streaming::AudioStreamState>,
+    pub audio_player: SharedSongPlayer,
 }
 
 /// Component for managing YM2149 playback on an entity
@@ -150,6 +162,7 @@ pub(crate) struct ActiveCrossfade {
 ///
 /// - `source_path`: Optional filesystem path to a YM file
 /// - `source_asset`: Optional Bevy asset handle referencing a YM file
+/// - `source_song_bytes`: Optional handle to in-memory song data ([`Ym2149SongBytes`])
 /// - `state`: Current playback state (Idle, Playing, Paused, Finished)
 /// - `frame_position`: Current frame in the song
 /// - `volume`: Volume multiplier (0.0 = silent, 1.0 = full)
@@ -180,6 +193,8 @@ pub struct Ym2149Playback {
     pub source_bytes: Option<Arc<Vec<u8>>>,
     /// Handle to a YM2149 asset
     pub source_asset: Option<Handle<crate::audio_source::Ym2149AudioSource>>,
+    /// Handle to raw song data held in `Assets<Ym2149SongBytes>`
+    pub source_song_bytes: Option<Handle<Ym2149SongBytes>>,
     /// Current playback state
     pub state: PlaybackState,
     /// Current frame position in the song
@@ -187,6 +202,12 @@ pub struct Ym2149Playback {
     pub(crate) frame_position: u32,
     /// Volume level (0.0 = mute, 1.0 = full volume)
     pub volume: f32,
+    /// Name of the [`AudioBus`](crate::AudioBus) this playback is routed to
+    /// (`None` = no bus processing)
+    pub bus: Option<String>,
+    /// [`SyncGroup`](crate::SyncGroup) this playback starts and stays in
+    /// lockstep with; joined when the audio stream is created
+    pub sync_group: Option<crate::sync_group::SyncGroup>,
     /// Left channel gain used during stereo mixing.
     /// Use the [`set_stereo_gain()`](Self::set_stereo_gain) method to modify
     pub(crate) left_gain: f32,
@@ -222,6 +243,8 @@ pub struct Ym2149Playback {
     pub(crate) inline_metadata: Option<Ym2149Metadata>,
     /// Pending subsong index to set after reload (1-based, None means default)
     pub(crate) pending_subsong: Option<usize>,
+    /// Frame to resume from after a hot reload
+    pub(crate) resume_frame: Option<usize>,
     /// Cached subsong count (preserved during reload)
     pub(crate) cached_subsong_count: usize,
     /// Cached current subsong index (preserved during reload, 1-based)
@@ -230,6 +253,8 @@ pub struct Ym2149Playback {
     pub(crate) audio_stream_state: Option<Arc<crate::streaming::AudioStreamState>>,
     /// The audio source's player (separate from visualization player) for seeking
     pub(crate) audio_player: Option<SharedSongPlayer>,
+    /// Gap between Bevy's clock and the consumed audio, in seconds
+    pub(crate) clock_drift: Option<f32>,
 }
 
 /// The current state of YM2149 playback
@@ -256,7 +281,7 @@ impl Ym2149Playback {
     ///
     /// # Arguments
     ///
-    /// * `source_path` - Path to a YM file (YM2-YM6 formats supported).
+    /// * `source_path` - Path to a `.ym` (YM2-YM6), `.aks`, `.ay` or `.sndh` file.
     ///   Should not be empty; an empty path will cause a load error.
     ///
     /// # Example
@@ -282,12 +307,12 @@ impl Ym2149Playback {
         }
     }
 
-    /// Create a new playback component backed by an in-memory YM buffer.
+    /// Create a new playback component backed by an in-memory song buffer.
     ///
     /// # Arguments
     ///
-    /// * `bytes` - Raw YM file data. Should not be empty; empty data will cause
-    ///   a load error.
+    /// * `bytes` - Raw song data (YM, AKS, AY or SNDH). Should not be empty;
+    ///   empty data will cause a load error.
     pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
         let data = bytes.into();
         debug_assert!(!data.is_empty(), "bytes should not be empty");
@@ -297,6 +322,16 @@ impl Ym2149Playback {
         }
     }
 
+    /// Create a new playback component backed by a [`Ym2149SongBytes`] asset.
+    ///
+    /// Playback starts loading once the asset is available in `Assets<Ym2149SongBytes>`.
+    pub fn from_song_bytes(handle: Handle<Ym2149SongBytes>) -> Self {
+        Self {
+            source_song_bytes: Some(handle),
+            ..Default::default()
+        }
+    }
+
     /// Create a playback component that drives a live YM2149 synthesizer.
     pub fn synth(controller: YmSynthController) -> Self {
         let synth_player = YmSongPlayer::new_synth(controller);
@@ -310,9 +345,12 @@ impl Ym2149Playback {
             source_path: None,
             source_bytes: None,
             source_asset: None,
+            source_song_bytes: None,
             state: PlaybackState::Idle,
             frame_position: 0,
             volume: 1.0,
+            bus: None,
+            sync_group: None,
             left_gain: 1.0,
             right_gain: 1.0,
             stereo_gain: Arc::new(RwLock::new((1.0, 1.0))),
@@ -328,11 +366,13 @@ impl Ym2149Playback {
             inline_audio_ready: false,
             inline_metadata: Some(metadata),
             pending_subsong: None,
+            resume_frame: None,
             cached_subsong_count: 1,
             cached_current_subsong: 1,
             tone_settings: Arc::new(RwLock::new(ToneSettings::default())),
             audio_stream_state: None,
             audio_player: None,
+            clock_drift: None,
         }
     }
 
@@ -395,6 +435,25 @@ impl Ym2149Playback {
         self.pending_crossfade = None;
     }
 
+    /// Reload the current source in place, keeping the playback state.
+    ///
+    /// With `resume` the reloaded song continues from the current frame,
+    /// otherwise it starts over. The selected subsong is kept.
+    pub(crate) fn reload_source(&mut self, resume: bool) {
+        self.resume_frame = resume.then_some(self.frame_position as usize);
+        if !resume {
+            self.frame_position = 0;
+        }
+        if self.cached_subsong_count > 1 {
+            self.pending_subsong = Some(self.cached_current_subsong);
+        }
+        self.metrics = None;
+        self.player = None;
+        self.needs_reload = true;
+        self.crossfade = None;
+        self.pending_crossfade = None;
+    }
+
     /// Seek to a specific frame
     ///
     /// Updates the frame position without changing the playback state.
@@ -477,6 +536,33 @@ impl Ym2149Playback {
         self.volume = volume.max(0.0);
     }
 
+    /// Route this playback to a named [`AudioBus`](crate::AudioBus).
+    pub fn with_bus(mut self, bus: impl Into<String>) -> Self {
+        self.bus = Some(bus.into());
+        self
+    }
+
+    /// Route this playback to a named bus.
+    pub fn set_bus(&mut self, bus: impl Into<String>) {
+        self.bus = Some(bus.into());
+    }
+
+    /// Remove the bus routing, returning to unprocessed output.
+    pub fn clear_bus(&mut self) {
+        self.bus = None;
+    }
+
+    /// Start this playback in lockstep with the other members of `group`.
+    pub fn with_sync_group(mut self, group: &crate::sync_group::SyncGroup) -> Self {
+        self.sync_group = Some(group.clone());
+        self
+    }
+
+    /// Join a sync group; takes effect when the audio stream is next created.
+    pub fn set_sync_group(&mut self, group: &crate::sync_group::SyncGroup) {
+        self.sync_group = Some(group.clone());
+    }
+
     /// Current tone settings (copied out of the shared state).
     pub fn tone_settings(&self) -> ToneSettings {
         *self.tone_settings.read()
@@ -504,6 +590,7 @@ impl Ym2149Playback {
         self.source_path = Some(path.into());
         self.source_bytes = None;
         self.source_asset = None;
+        self.source_song_bytes = None;
         self.needs_reload = true;
         self.metrics = None;
         self.pending_playlist_index = None;
@@ -516,6 +603,7 @@ impl Ym2149Playback {
         self.source_asset = Some(handle);
         self.source_path = None;
         self.source_bytes = None;
+        self.source_song_bytes = None;
         self.needs_reload = true;
         self.metrics = None;
         self.pending_playlist_index = None;
@@ -528,6 +616,20 @@ impl Ym2149Playback {
         self.source_bytes = Some(Arc::new(bytes.into()));
         self.source_path = None;
         self.source_asset = None;
+        self.source_song_bytes = None;
+        self.needs_reload = true;
+        self.metrics = None;
+        self.pending_playlist_index = None;
+        self.pending_crossfade = None;
+        self.crossfade = None;
+    }
+
+    /// Replace the playback source with a [`Ym2149SongBytes`] asset handle.
+    pub fn set_source_song_bytes(&mut self, handle: Handle<Ym2149SongBytes>) {
+        self.source_song_bytes = Some(handle);
+        self.source_path = None;
+        self.source_bytes = None;
+        self.source_asset = None;
         self.needs_reload = true;
         self.metrics = None;
         self.pending_playlist_index = None;
@@ -550,6 +652,11 @@ impl Ym2149Playback {
         self.source_bytes.as_ref().map(Arc::clone)
     }
 
+    /// Access the configured [`Ym2149SongBytes`] asset handle, if any.
+    pub fn source_song_bytes(&self) -> Option<&Handle<Ym2149SongBytes>> {
+        self.source_song_bytes.as_ref()
+    }
+
     /// Check if currently playing
     ///
     /// Returns true if the playback state is `Playing`.
@@ -581,12 +688,34 @@ impl Ym2149Playback {
         self.player.as_ref().map(Arc::clone)
     }
 
-    /// Query the current audio sink buffer fill percentage (0.0 - 1.0).
+    /// Query the fill level of the streaming ring buffer (0.0 - 1.0).
     ///
-    /// Note: This method is deprecated with Bevy audio and always returns None.
-    /// Audio buffering is now handled internally by Bevy's audio system.
+    /// Returns `None` until the playback's audio source has been loaded.
     pub fn audio_buffer_fill(&self) -> Option<f32> {
-        None
+        self.audio_stream_state
+            .as_ref()
+            .map(|state| state.buffer.fill_percentage())
+    }
+
+    /// How far Bevy's `Time` has run ahead of the audio output, in seconds.
+    ///
+    /// Frames are paced from the samples the output consumes, so this only
+    /// measures the clocks, it does not accumulate into the playback.
+    /// Returns `None` until the output has started pulling audio.
+    pub fn clock_drift_seconds(&self) -> Option<f32> {
+        self.clock_drift
+    }
+
+    /// PSG registers of the audio currently being heard.
+    ///
+    /// The audio stream renders ahead of the output by its ring buffer;
+    /// this returns the registers of the batch now playing so visuals do not
+    /// lead the audio. Returns `None` until the stream has started; fall back
+    /// to the player's own registers then.
+    pub fn audible_registers(&self) -> Option<[u8; 16]> {
+        self.audio_stream_state
+            .as_ref()
+            .and_then(|state| state.audible_registers())
     }
 
     /// Access the metrics of the currently loaded track, if known.
@@ -719,9 +848,12 @@ impl Default for Ym2149Playback {
             source_path: None,
             source_bytes: None,
             source_asset: None,
+            source_song_bytes: None,
             state: PlaybackState::Idle,
             frame_position: 0,
             volume: 1.0,
+            bus: None,
+            sync_group: None,
             left_gain: 1.0,
             right_gain: 1.0,
             stereo_gain: Arc::new(RwLock::new((1.0, 1.0))),
@@ -737,11 +869,13 @@ impl Default for Ym2149Playback {
             inline_audio_ready: false,
             inline_metadata: None,
             pending_subsong: None,
+            resume_frame: None,
             cached_subsong_count: 1,
             cached_current_subsong: 1,
             tone_settings: Arc::new(RwLock::new(ToneSettings::default())),
             audio_stream_state: None,
             audio_player: None,
+            clock_drift: None,
         }
     }
 }
@@ -61,21 +61,32 @@ pub enum PlaylistSource {
 
 /// Configuration for seamless playlist crossfades.
 ///
-/// Controls when a crossfade begins and how long both tracks overlap.
+/// Controls when a crossfade begins, how long both tracks overlap and how
+/// their volumes are shaped.
 #[derive(Debug, Clone)]
 pub struct CrossfadeConfig {
     /// When to start the crossfade.
     pub trigger: CrossfadeTrigger,
     /// How long both tracks play simultaneously.
     pub window: CrossfadeWindow,
+    /// Volume curve of the fade.
+    pub curve: CrossfadeCurve,
+    /// Seconds before the trigger at which the next track is loaded and
+    /// buffered, so the fade starts on time with audio ready.
+    pub prebuffer_seconds: f32,
 }
 
 impl CrossfadeConfig {
+    /// Default time to load the next track ahead of the fade.
+    pub const DEFAULT_PREBUFFER_SECONDS: f32 = 1.0;
+
     /// Start the crossfade once the given ratio of the song has elapsed (0.0 - 1.0).
     pub fn start_at_ratio(ratio: f32) -> Self {
         Self {
             trigger: CrossfadeTrigger::SongRatio(ratio),
             window: CrossfadeWindow::UntilSongEnd,
+            curve: CrossfadeCurve::default(),
+            prebuffer_seconds: Self::DEFAULT_PREBUFFER_SECONDS,
         }
     }
 
@@ -83,7 +94,7 @@ impl CrossfadeConfig {
     pub fn start_at_seconds(seconds: f32) -> Self {
         Self {
             trigger: CrossfadeTrigger::Seconds(seconds),
-            window: CrossfadeWindow::UntilSongEnd,
+            ..Self::start_at_ratio(0.0)
         }
     }
 
@@ -92,6 +103,18 @@ impl CrossfadeConfig {
         self.window = CrossfadeWindow::FixedSeconds(seconds.max(0.001));
         self
     }
+
+    /// Override the volume curve of the fade.
+    pub fn with_curve(mut self, curve: CrossfadeCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    /// Override how early the next track is loaded (0.0 = load at the trigger).
+    pub fn with_prebuffer_seconds(mut self, seconds: f32) -> Self {
+        self.prebuffer_seconds = seconds.max(0.0);
+        self
+    }
 }
 
 impl Default for CrossfadeConfig {
@@ -109,6 +132,36 @@ pub enum CrossfadeTrigger {
     Seconds(f32),
 }
 
+/// Volume curve applied to both decks during a crossfade.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CrossfadeCurve {
+    /// Straight volume ramps; the summed level dips by about 3 dB mid-fade.
+    Linear,
+    /// Sine/cosine ramps that keep the perceived loudness constant.
+    #[default]
+    EqualPower,
+    /// Smoothstep ramps that ease in and out of the fade.
+    SCurve,
+}
+
+impl CrossfadeCurve {
+    /// Outgoing and incoming gain at `progress` (0.0 - 1.0) through the fade.
+    pub fn gains(self, progress: f32) -> (f32, f32) {
+        let t = progress.clamp(0.0, 1.0);
+        match self {
+            CrossfadeCurve::Linear => (1.0 - t, t),
+            CrossfadeCurve::EqualPower => {
+                let angle = t * std::f32::consts::FRAC_PI_2;
+                (angle.cos(), angle.sin())
+            }
+            CrossfadeCurve::SCurve => {
+                let s = t * t * (3.0 - 2.0 * t);
+                (1.0 - s, s)
+            }
+        }
+    }
+}
+
 /// Duration of the overlap between decks once a fade starts.
 #[derive(Debug, Clone, Copy)]
 pub enum CrossfadeWindow {
@@ -364,6 +417,8 @@ pub fn handle_playlist_requests(
                     source,
                     duration: desired.max(0.1),
                     target_index,
+                    curve: cfg.curve,
+                    start_at: None,
                 });
                 controller.crossfade_stage = CrossfadeStage::Loading { target_index };
                 continue;
@@ -435,11 +490,13 @@ pub fn drive_crossfade_playlists(
             CrossfadeTrigger::Seconds(seconds) => seconds.max(0.0).min(duration.max(0.0)),
         };
 
-        if elapsed < trigger_point {
+        // Load the next deck early; the fade itself still begins at the trigger
+        if elapsed < trigger_point - config.prebuffer_seconds.max(0.0) {
             continue;
         }
+        let fade_start = trigger_point.max(elapsed);
 
-        let remaining = (duration - elapsed).max(0.0);
+        let remaining = (duration - fade_start).max(0.0);
         if remaining <= f32::EPSILON {
             continue;
         }
@@ -463,6 +520,8 @@ pub fn drive_crossfade_playlists(
             source,
             duration: fade_duration,
             target_index: next_index,
+            curve: config.curve,
+            start_at: (fade_start > elapsed).then_some(fade_start),
         });
         controller.crossfade_stage = CrossfadeStage::Loading {
             target_index: next_index,
@@ -491,7 +550,10 @@ fn frames_to_seconds(frame: u32, samples_per_frame: u32) -> f32 {
     samples as f32 / YM2149_SAMPLE_RATE_F32
 }
 
-fn resolve_track_source(entry: &PlaylistSource, asset_server: &AssetServer) -> TrackSource {
+pub(crate) fn resolve_track_source(
+    entry: &PlaylistSource,
+    asset_server: &AssetServer,
+) -> TrackSource {
     match entry {
         PlaylistSource::File { path } => TrackSource::File(path.clone()),
         PlaylistSource::Asset { path } => {
@@ -695,6 +757,8 @@ mod tests {
                 source: TrackSource::Bytes(Arc::new(vec![2; 16])),
                 duration: 1.0,
                 target_index: 1,
+                curve: CrossfadeCurve::Linear,
+                start_at: None,
             }),
             ..Default::default()
         };
@@ -723,4 +787,72 @@ mod tests {
             .unwrap();
         assert_eq!(controller.current_index, 0);
     }
+
+    #[test]
+    fn crossfade_curves_span_both_decks() {
+        for curve in [
+            CrossfadeCurve::Linear,
+            CrossfadeCurve::EqualPower,
+            CrossfadeCurve::SCurve,
+        ] {
+            assert_eq!(curve.gains(0.0), (1.0, 0.0));
+            let (out_gain, in_gain) = curve.gains(1.0);
+            assert!(out_gain.abs() < 1e-6 && (in_gain - 1.0).abs() < 1e-6);
+        }
+
+        // Equal power keeps the summed power constant mid-fade
+        let (out_gain, in_gain) = CrossfadeCurve::EqualPower.gains(0.5);
+        assert!((out_gain * out_gain + in_gain * in_gain - 1.0).abs() < 1e-6);
+        assert_eq!(CrossfadeCurve::Linear.gains(0.5), (0.5, 0.5));
+    }
+
+    #[test]
+    fn crossfade_prebuffers_before_trigger() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+        app.world_mut().init_resource::<Assets<Ym2149Playlist>>();
+
+        let playlist_handle = {
+            let mut assets = app.world_mut().resource_mut::<Assets<Ym2149Playlist>>();
+            assets.add(Ym2149Playlist {
+                tracks: vec![
+                    PlaylistSource::Bytes { data: vec![0; 16] },
+                    PlaylistSource::Bytes { data: vec![1; 16] },
+                ],
+                mode: PlaylistMode::Loop,
+            })
+        };
+
+        // 20 s song at 18 s; the fade is due at 19 s
+        let playback = Ym2149Playback {
+            metrics: Some(PlaybackMetrics {
+                frame_count: 1_000,
+                samples_per_frame: 882,
+            }),
+            frame_position: 900,
+            state: PlaybackState::Playing,
+            ..Default::default()
+        };
+
+        let controller = Ym2149PlaylistPlayer {
+            playlist: playlist_handle,
+            current_index: 0,
+            crossfade: Some(CrossfadeConfig::start_at_seconds(19.0).with_prebuffer_seconds(1.5)),
+            crossfade_stage: CrossfadeStage::Idle,
+        };
+
+        let entity = app.world_mut().spawn((playback, controller)).id();
+
+        app.add_systems(Update, drive_crossfade_playlists);
+        app.update();
+
+        let playback = app.world().entity(entity).get::<Ym2149Playback>().unwrap();
+        let request = playback
+            .pending_crossfade
+            .as_ref()
+            .expect("next track should be loaded ahead of the fade");
+        assert_eq!(request.start_at, Some(19.0));
+        assert_eq!(request.curve, CrossfadeCurve::EqualPower);
+        assert!((request.duration - 1.0).abs() < 1e-3);
+    }
 }
@@ -1,14 +1,62 @@
 use bevy::prelude::Resource;
+#[cfg(feature = "fx")]
+use ym2149_common::FxConfig;
+
+/// Chip backend used to render YM songs.
+///
+/// Only affects YM files; Arkos, AY and SNDH songs always use the
+/// hardware-accurate emulation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChipBackend {
+    /// Cycle-accurate YM2149 emulation with full YM6 effect support.
+    #[default]
+    Hardware,
+    /// Experimental `ym2149-softsynth` backend (tone and volume only, no
+    /// envelopes, noise or hardware effects).
+    SoftSynth,
+}
+
+/// How playing songs react when their asset changes on disk.
+///
+/// Applies to [`Ym2149AudioSource`](crate::Ym2149AudioSource) and
+/// [`Ym2149SongBytes`](crate::Ym2149SongBytes) handles. File watching itself
+/// is Bevy's job: enable its `file_watcher` feature (or set
+/// `AssetPlugin::watch_for_changes_override`) to receive the changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HotReload {
+    /// Keep playing the old song until the source is changed explicitly.
+    Disabled,
+    /// Reload the song and start it from the beginning.
+    Restart,
+    /// Reload the song and continue at the current frame.
+    #[default]
+    Resume,
+}
+
+/// What playbacks do while no window has focus.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FocusLoss {
+    /// Keep playing unchanged.
+    #[default]
+    Ignore,
+    /// Pause playing songs and resume them when focus returns.
+    Pause,
+    /// Keep playing at the given gain (0.0 - 1.0), ramped like a bus change.
+    Duck(f32),
+}
 
 /// Configuration object used to enable/disable individual subsystems of the plugin.
 ///
-/// All features are enabled by default. Modify fields directly to customize:
+/// All features are enabled by default and YM songs use the hardware-accurate
+/// chip. Modify fields directly to customize:
 ///
 /// ```
 /// # use bevy_ym2149::Ym2149PluginConfig;
+/// # use bevy_ym2149::ChipBackend;
 /// let config = Ym2149PluginConfig {
 ///     playlists: false,
 ///     diagnostics: false,
+///     chip: ChipBackend::SoftSynth,
 ///     ..Default::default()
 /// };
 /// ```
@@ -30,6 +78,17 @@ pub struct Ym2149PluginConfig {
     ///
     /// Default is `None`, which uses 50 frames (60 BPM at 50Hz).
     pub frames_per_beat: Option<u64>,
+    /// Chip backend used to render YM songs loaded by the plugin.
+    pub chip: ChipBackend,
+    /// Reload songs whose asset was modified (e.g. re-exported from a tracker).
+    pub hot_reload: HotReload,
+    /// Pause or duck playbacks while the app window is unfocused.
+    pub focus_loss: FocusLoss,
+    /// Insert effects applied to every song started by the plugin.
+    ///
+    /// Empty by default (no processing). Requires the `fx` feature.
+    #[cfg(feature = "fx")]
+    pub fx: FxConfig,
 }
 
 impl Default for Ym2149PluginConfig {
@@ -43,6 +102,11 @@ impl Default for Ym2149PluginConfig {
             bevy_audio_bridge: true,
             pattern_events: true,
             frames_per_beat: None,
+            chip: ChipBackend::Hardware,
+            hot_reload: HotReload::Resume,
+            focus_loss: FocusLoss::Ignore,
+            #[cfg(feature = "fx")]
+            fx: FxConfig::default(),
         }
     }
 }
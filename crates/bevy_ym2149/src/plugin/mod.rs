@@ -6,26 +6,33 @@
 mod config;
 mod systems;
 
-pub use config::Ym2149PluginConfig;
+pub use config::{ChipBackend, FocusLoss, HotReload, Ym2149PluginConfig};
 
 use self::systems::{
     FrameAudioData, detect_pattern_triggers, drive_playback_state, emit_beat_hits,
     emit_frame_markers, emit_playback_diagnostics, initialize_playback, process_playback_frames,
-    process_sfx_requests, publish_bridge_audio, update_audio_reactive_state,
+    process_sfx_requests, publish_bridge_audio, reload_modified_sources,
+    update_audio_reactive_state,
 };
 use crate::audio_bridge::{
-    AudioBridgeBuffers, AudioBridgeMixes, AudioBridgeTargets, BridgeAudioDevice, BridgeAudioSinks,
-    drive_bridge_audio_buffers, handle_bridge_requests,
+    AudioBridgeBuffers, AudioBridgeMixes, AudioBridgeTargets, AudioBuses, BridgeAudioDevice,
+    BridgeAudioSinks, apply_audio_buses, drive_bridge_audio_buffers, handle_bridge_requests,
+};
+use crate::audio_reactive::{AudioReactiveConfig, AudioReactiveState};
+use crate::audio_source::{
+    ArkosLoader, AyLoader, SndhLoader, Ym2149AudioSource, Ym2149Loader, Ym2149SongBytes,
 };
-use crate::audio_reactive::AudioReactiveState;
-use crate::audio_source::{Ym2149AudioSource, Ym2149Loader};
 use crate::chip_state::ChipStateSnapshot;
 use crate::diagnostics::{register as register_diagnostics, update_diagnostics};
 use crate::events::{
-    AudioBridgeRequest, BeatHit, ChannelSnapshot, MusicStateRequest, PatternTriggered,
-    PlaybackFrameMarker, PlaylistAdvanceRequest, TrackFinished, TrackStarted, YmSfxRequest,
+    AudioBridgeRequest, BeatHit, ChannelSnapshot, CrossfadeProgress, MusicStateRequest,
+    PatternTriggered, PlaybackFrameMarker, PlaylistAdvanceRequest, TrackFinished, TrackStarted,
+    YmSfxRequest,
+};
+use crate::lifecycle::{Ym2149Focus, track_window_focus};
+use crate::music_state::{
+    MusicStateGraph, advance_music_transitions, process_music_state_requests,
 };
-use crate::music_state::{MusicStateGraph, process_music_state_requests};
 use crate::patterns::PatternTriggerRuntime;
 use crate::playback::Ym2149Settings;
 use crate::playlist::{
@@ -73,7 +80,12 @@ impl Plugin for Ym2149Plugin {
 
         // Register YM assets with Bevy's asset server.
         app.init_asset::<Ym2149AudioSource>();
-        app.init_asset_loader::<Ym2149Loader>();
+        app.register_asset_loader(Ym2149Loader::new(self.config.chip));
+        app.init_asset_loader::<ArkosLoader>();
+        app.init_asset_loader::<AyLoader>();
+        app.init_asset_loader::<SndhLoader>();
+        // In-memory song data shared by handle (downloads, generated songs).
+        app.init_asset::<Ym2149SongBytes>();
         // Register Ym2149AudioSource as a Decodable audio source
         app.add_audio_source::<Ym2149AudioSource>();
 
@@ -84,6 +96,7 @@ impl Plugin for Ym2149Plugin {
         app.add_message::<TrackFinished>();
         app.add_message::<MusicStateRequest>();
         app.add_message::<PlaylistAdvanceRequest>();
+        app.add_message::<CrossfadeProgress>();
         app.add_message::<AudioBridgeRequest>();
         app.add_message::<FrameAudioData>();
         app.add_message::<PlaybackFrameMarker>();
@@ -91,11 +104,21 @@ impl Plugin for Ym2149Plugin {
         app.add_message::<YmSfxRequest>();
         app.add_message::<PatternTriggered>();
         app.init_resource::<AudioReactiveState>();
+        app.init_resource::<AudioReactiveConfig>();
         app.init_resource::<PatternTriggerRuntime>();
         app.init_resource::<ChipStateSnapshot>();
+        app.init_resource::<AudioBuses>();
+        app.init_resource::<Ym2149Focus>();
 
         // Core playback lifecycle.
-        app.add_systems(PreUpdate, (initialize_playback, drive_playback_state));
+        app.add_systems(
+            PreUpdate,
+            (
+                reload_modified_sources.before(initialize_playback),
+                initialize_playback,
+                drive_playback_state,
+            ),
+        );
         app.add_systems(
             Update,
             (
@@ -105,6 +128,8 @@ impl Plugin for Ym2149Plugin {
                 update_audio_reactive_state.after(process_playback_frames),
                 detect_pattern_triggers.after(process_playback_frames),
                 emit_beat_hits.after(emit_frame_markers),
+                track_window_focus,
+                apply_audio_buses.after(track_window_focus),
             ),
         );
         // Optional playlist support.
@@ -128,7 +153,10 @@ impl Plugin for Ym2149Plugin {
         // Optional music state graph.
         if self.config.music_state {
             app.init_resource::<MusicStateGraph>();
-            app.add_systems(Update, process_music_state_requests);
+            app.add_systems(
+                Update,
+                (process_music_state_requests, advance_music_transitions).chain(),
+            );
         }
 
         if self.config.bevy_audio_bridge {
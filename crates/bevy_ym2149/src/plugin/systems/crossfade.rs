@@ -1,4 +1,4 @@
-use crate::audio_source::Ym2149AudioSource;
+use crate::audio_source::{Ym2149AudioSource, Ym2149SongBytes};
 use crate::events::{TrackFinished, TrackStarted};
 use crate::playback::{ActiveCrossfade, Ym2149Playback};
 use crate::plugin::Ym2149PluginConfig;
@@ -14,9 +14,11 @@ use super::main_systems::{PlaybackRuntimeState, load_player_from_bytes};
 pub(super) fn process_pending_crossfade(
     commands: &mut Commands,
     audio_assets: &mut Assets<Ym2149AudioSource>,
+    song_bytes: &Assets<Ym2149SongBytes>,
     entity: Entity,
     playback: &mut Ym2149Playback,
     pending_reads: &mut HashMap<(Entity, PendingSlot), PendingFileRead>,
+    config: &Ym2149PluginConfig,
 ) {
     if playback.crossfade.is_some() {
         return;
@@ -33,6 +35,7 @@ pub(super) fn process_pending_crossfade(
         &request.source,
         pending_reads,
         audio_assets,
+        song_bytes,
     ) {
         SourceLoadResult::Pending => return,
         SourceLoadResult::Failed(err) => {
@@ -46,7 +49,7 @@ pub(super) fn process_pending_crossfade(
     let bytes = loaded.data;
     let data_for_state = Arc::new(bytes.clone());
 
-    let mut load = match load_player_from_bytes(&bytes, loaded.metadata.as_ref()) {
+    let mut load = match load_player_from_bytes(&bytes, loaded.metadata.as_ref(), config.chip) {
         Ok(load) => load,
         Err(err) => {
             error!("Failed to prepare crossfade deck: {}", err);
@@ -60,10 +63,13 @@ pub(super) fn process_pending_crossfade(
     let duration = request.duration.max(0.001);
     let player_arc = Arc::new(RwLock::new(load.player));
 
-    let crossfade_audio_source = match Ym2149AudioSource::new_with_shared(
+    let crossfade_audio_source = match Ym2149AudioSource::new_with_options(
         bytes,
         playback.stereo_gain.clone(),
         playback.tone_settings.clone(),
+        None,
+        None,
+        config.chip,
     ) {
         Ok(source) => source,
         Err(err) => {
@@ -72,13 +78,17 @@ pub(super) fn process_pending_crossfade(
             return;
         }
     };
+    #[cfg(feature = "fx")]
+    crossfade_audio_source.set_fx(&config.fx);
+    let crossfade_stream_state = crossfade_audio_source.stream_state();
+    let crossfade_audio_player = crossfade_audio_source.shared_player();
     let crossfade_handle = audio_assets.add(crossfade_audio_source);
 
+    // A pre-buffered deck fills its stream but stays paused until the fade begins
+    let mut settings = PlaybackSettings::LOOP.with_volume(bevy::audio::Volume::Linear(0.0));
+    settings.paused = request.start_at.is_some();
     let crossfade_entity = commands
-        .spawn((
-            AudioPlayer(crossfade_handle.clone()),
-            PlaybackSettings::LOOP.with_volume(bevy::audio::Volume::Linear(0.0)),
-        ))
+        .spawn((AudioPlayer(crossfade_handle.clone()), settings))
         .id();
 
     playback.crossfade = Some(ActiveCrossfade {
@@ -89,9 +99,13 @@ pub(super) fn process_pending_crossfade(
         elapsed: 0.0,
         duration,
         target_index: request.target_index,
+        curve: request.curve,
+        start_at: request.start_at,
         audio_handle: crossfade_handle,
         data: data_for_state,
         crossfade_entity: Some(crossfade_entity),
+        audio_stream_state: crossfade_stream_state,
+        audio_player: crossfade_audio_player,
     });
     playback.clear_crossfade_request();
 }
@@ -138,10 +152,13 @@ pub(super) fn finalize_crossfade(
     playback.pending_playlist_index = Some(crossfade.target_index);
     playback.source_path = None;
     playback.source_asset = None;
+    playback.source_song_bytes = None;
     playback.needs_reload = false;
     // Keep position as-is; the player already advanced during crossfade.
 
     playback.source_bytes = Some(crossfade.data);
+    playback.audio_stream_state = Some(crossfade.audio_stream_state);
+    playback.audio_player = Some(crossfade.audio_player);
 
     playback.volume = 1.0;
     runtime.reset_for_crossfade();
@@ -0,0 +1,125 @@
+//! Hot reload of modified song assets
+
+use crate::audio_source::{Ym2149AudioSource, Ym2149SongBytes};
+use crate::playback::Ym2149Playback;
+use crate::plugin::{HotReload, Ym2149PluginConfig};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Reload playbacks whose source asset was modified on disk (or replaced in memory).
+pub(in crate::plugin) fn reload_modified_sources(
+    mut audio_events: MessageReader<AssetEvent<Ym2149AudioSource>>,
+    mut bytes_events: MessageReader<AssetEvent<Ym2149SongBytes>>,
+    mut playbacks: Query<&mut Ym2149Playback>,
+    config: Res<Ym2149PluginConfig>,
+) {
+    let modified_audio: HashSet<_> = audio_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    let modified_bytes: HashSet<_> = bytes_events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect();
+    if config.hot_reload == HotReload::Disabled
+        || (modified_audio.is_empty() && modified_bytes.is_empty())
+    {
+        return;
+    }
+
+    for mut playback in playbacks.iter_mut() {
+        let modified = playback
+            .source_asset()
+            .is_some_and(|handle| modified_audio.contains(&handle.id()))
+            || playback
+                .source_song_bytes()
+                .is_some_and(|handle| modified_bytes.contains(&handle.id()));
+        if modified {
+            info!("Song asset changed on disk, reloading");
+            playback.reload_source(config.hot_reload == HotReload::Resume);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::prelude::Messages;
+
+    fn app_with(hot_reload: HotReload) -> App {
+        let mut app = App::new();
+        app.insert_resource(Ym2149PluginConfig {
+            hot_reload,
+            ..Default::default()
+        });
+        app.add_message::<AssetEvent<Ym2149AudioSource>>();
+        app.add_message::<AssetEvent<Ym2149SongBytes>>();
+        app.add_systems(Update, reload_modified_sources);
+        app
+    }
+
+    fn modify(app: &mut App, handle: &Handle<Ym2149SongBytes>) {
+        app.world_mut()
+            .resource_mut::<Messages<AssetEvent<Ym2149SongBytes>>>()
+            .write(AssetEvent::Modified { id: handle.id() });
+    }
+
+    #[test]
+    fn modified_source_reloads_at_current_frame() {
+        let mut app = app_with(HotReload::Resume);
+        let handle = Handle::<Ym2149SongBytes>::default();
+        let mut playback = Ym2149Playback::from_song_bytes(handle.clone());
+        playback.seek(120);
+        let entity = app.world_mut().spawn(playback).id();
+        let other = app.world_mut().spawn(Ym2149Playback::default()).id();
+
+        modify(&mut app, &handle);
+        app.update();
+
+        let playback = app.world().get::<Ym2149Playback>(entity).unwrap();
+        assert!(playback.needs_reload);
+        assert_eq!(playback.resume_frame, Some(120));
+        assert!(
+            !app.world()
+                .get::<Ym2149Playback>(other)
+                .unwrap()
+                .needs_reload
+        );
+    }
+
+    #[test]
+    fn restart_mode_rewinds_and_disabled_ignores() {
+        let handle = Handle::<Ym2149SongBytes>::default();
+
+        let mut app = app_with(HotReload::Restart);
+        let mut playback = Ym2149Playback::from_song_bytes(handle.clone());
+        playback.seek(120);
+        let entity = app.world_mut().spawn(playback).id();
+        modify(&mut app, &handle);
+        app.update();
+        let playback = app.world().get::<Ym2149Playback>(entity).unwrap();
+        assert!(playback.needs_reload);
+        assert_eq!(playback.resume_frame, None);
+        assert_eq!(playback.frame_position(), 0);
+
+        let mut app = app_with(HotReload::Disabled);
+        let entity = app
+            .world_mut()
+            .spawn(Ym2149Playback::from_song_bytes(handle.clone()))
+            .id();
+        modify(&mut app, &handle);
+        app.update();
+        assert!(
+            !app.world()
+                .get::<Ym2149Playback>(entity)
+                .unwrap()
+                .needs_reload
+        );
+    }
+}
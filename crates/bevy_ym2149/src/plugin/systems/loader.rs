@@ -1,6 +1,6 @@
 //! Track loading systems and helpers
 
-use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata};
+use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata, Ym2149SongBytes};
 use crate::playback::{TrackSource, Ym2149Playback};
 use bevy::prelude::*;
 use bevy::tasks::{IoTaskPool, Task, block_on, poll_once};
@@ -16,7 +16,7 @@ impl PendingFileRead {
         let task_path = path.clone();
         let task = IoTaskPool::get().spawn(async move {
             std::fs::read(&task_path)
-                .map_err(|err| format!("Failed to read YM file '{task_path}': {err}"))
+                .map_err(|err| format!("Failed to read song file '{task_path}': {err}"))
         });
         Self { path, task }
     }
@@ -49,6 +49,12 @@ pub(super) fn current_track_source(playback: &Ym2149Playback) -> Option<TrackSou
                 .map(|path| TrackSource::File(path.to_owned()))
         })
         .or_else(|| playback.source_asset().cloned().map(TrackSource::Asset))
+        .or_else(|| {
+            playback
+                .source_song_bytes()
+                .cloned()
+                .map(TrackSource::SongBytes)
+        })
 }
 
 /// Load track source (file or bytes)
@@ -58,6 +64,7 @@ pub(super) fn load_track_source(
     source: &TrackSource,
     pending_reads: &mut std::collections::HashMap<(Entity, PendingSlot), PendingFileRead>,
     assets: &Assets<Ym2149AudioSource>,
+    song_bytes: &Assets<Ym2149SongBytes>,
 ) -> SourceLoadResult {
     match source {
         TrackSource::Bytes(bytes) => SourceLoadResult::Ready(LoadedBytes {
@@ -98,5 +105,12 @@ pub(super) fn load_track_source(
             }),
             None => SourceLoadResult::Pending,
         },
+        TrackSource::SongBytes(handle) => match song_bytes.get(handle) {
+            Some(asset) => SourceLoadResult::Ready(LoadedBytes {
+                data: asset.data().to_vec(),
+                metadata: None,
+            }),
+            None => SourceLoadResult::Pending,
+        },
     }
 }
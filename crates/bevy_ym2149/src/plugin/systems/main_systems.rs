@@ -37,27 +37,27 @@
 //! - [`SfxLayer`]: Overlay synth for one-shot sound effects
 
 use crate::audio_bridge::{AudioBridgeBuffers, AudioBridgeTargets};
-use crate::audio_reactive::AudioReactiveState;
-use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata};
+use crate::audio_reactive::{AudioReactiveConfig, AudioReactiveState};
+use crate::audio_source::{Ym2149AudioSource, Ym2149Metadata, Ym2149SongBytes};
 use crate::chip_state::ChipStateSnapshot;
 use crate::events::{
-    BeatHit, ChannelSnapshot, PatternTriggered, PlaybackFrameMarker, TrackFinished, TrackStarted,
-    YmSfxRequest,
+    BeatHit, ChannelSnapshot, CrossfadeProgress, PatternTriggered, PlaybackFrameMarker,
+    TrackFinished, TrackStarted, YmSfxRequest,
 };
 use crate::oscilloscope::OscilloscopeBuffer;
 use crate::patterns::{PatternTriggerRuntime, PatternTriggerSet};
 use crate::playback::{
     PlaybackMetrics, PlaybackState, YM2149_SAMPLE_RATE_F32, Ym2149Playback, Ym2149Settings,
 };
-use crate::plugin::Ym2149PluginConfig;
-use crate::song_player::{YmSongPlayer, load_song_from_bytes};
+use crate::plugin::{ChipBackend, Ym2149PluginConfig};
+use crate::song_player::{SongFormat, YmSongPlayer, load_song_as, load_song_from_bytes};
+use crate::streaming::AudioStreamState;
 use crate::synth::{YmSynthController, YmSynthPlayer};
 use bevy::audio::{AudioPlayer, AudioSink, PlaybackSettings};
 use bevy::prelude::*;
 use parking_lot::RwLock;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use ym2149::Ym2149Backend;
 use ym2149_common::{PSG_MASTER_CLOCK_HZ, channel_frequencies};
 
 // Import from sibling modules
@@ -82,6 +82,7 @@ pub(in crate::plugin) struct PlaybackRuntimeState {
     frames_rendered: u64,
     emitted_finished: bool,
     sfx: Option<SfxLayer>,
+    audio_clock: Option<AudioClock>,
 }
 
 impl Default for PlaybackRuntimeState {
@@ -93,10 +94,93 @@ impl Default for PlaybackRuntimeState {
             frames_rendered: 0,
             emitted_finished: false,
             sfx: None,
+            audio_clock: None,
         }
     }
 }
 
+/// Frame clock following the samples consumed by the audio output.
+///
+/// Bevy's `Time` and the audio device run from different clocks, so pacing
+/// frames from `Time` slowly drifts away from what is heard. The consumed
+/// sample count of the playback's stream is used instead once the output
+/// has started pulling audio, and the gap between both clocks is reported
+/// as drift.
+struct AudioClock {
+    stream: Arc<AudioStreamState>,
+    consumed_frames: u64,
+    audio_seconds: f64,
+    time_seconds: f64,
+}
+
+impl AudioClock {
+    fn new(stream: Arc<AudioStreamState>) -> Self {
+        let consumed_frames = stream.consumed_frames();
+        Self {
+            stream,
+            consumed_frames,
+            audio_seconds: 0.0,
+            time_seconds: 0.0,
+        }
+    }
+
+    /// Seconds of audio consumed since the last call, or `None` while the
+    /// output has not pulled any samples yet (no audio device, still starting).
+    fn advance(&mut self) -> Option<f32> {
+        let consumed = self.stream.consumed_frames();
+        if consumed == 0 {
+            return None;
+        }
+        let frames = consumed.saturating_sub(self.consumed_frames);
+        self.consumed_frames = consumed;
+        Some(frames as f32 / YM2149_SAMPLE_RATE_F32)
+    }
+
+    /// Add one update of both clocks to the drift measurement.
+    fn measure(&mut self, audio_delta: f32, time_delta: f32) {
+        self.audio_seconds += f64::from(audio_delta);
+        self.time_seconds += f64::from(time_delta);
+    }
+
+    /// How far Bevy's clock ran ahead of the audio output, in seconds.
+    fn drift_seconds(&self) -> f32 {
+        (self.time_seconds - self.audio_seconds) as f32
+    }
+}
+
+/// Seconds to advance the playback by this update.
+///
+/// Follows the consumed audio when available and falls back to `Time`
+/// otherwise. Consumption is tracked while paused too, so resuming does not
+/// replay the paused span.
+fn playback_delta(
+    runtime: &mut PlaybackRuntimeState,
+    playback: &mut Ym2149Playback,
+    time_delta: f32,
+) -> f32 {
+    let Some(stream) = playback.audio_stream_state.as_ref() else {
+        runtime.audio_clock = None;
+        playback.clock_drift = None;
+        return time_delta;
+    };
+    let clock = match runtime.audio_clock.as_mut() {
+        Some(clock) if Arc::ptr_eq(&clock.stream, stream) => clock,
+        // New stream (reload, finished crossfade): restart the measurement
+        _ => runtime
+            .audio_clock
+            .insert(AudioClock::new(Arc::clone(stream))),
+    };
+    let Some(audio_delta) = clock.advance() else {
+        playback.clock_drift = None;
+        return time_delta;
+    };
+    if playback.state == PlaybackState::Playing {
+        clock.measure(audio_delta, time_delta);
+    }
+    playback.clock_drift = Some(clock.drift_seconds());
+    audio_delta
+}
+
 struct SfxLayer {
     player: YmSynthPlayer,
     controller: YmSynthController,
@@ -219,23 +303,15 @@ pub(in crate::plugin) fn emit_frame_markers(
 pub(in crate::plugin) fn update_audio_reactive_state(
     mut frames: MessageReader<FrameAudioData>,
     mut state: ResMut<AudioReactiveState>,
+    config: Res<AudioReactiveConfig>,
 ) {
-    const SMOOTHING: f32 = 0.25;
     for frame in frames.read() {
-        let entry = state.metrics.entry(frame.entity).or_default();
-
-        let inv_len = 1.0 / frame.samples_per_frame.max(1) as f32;
-        for channel in 0..3 {
-            let avg = (frame.channel_energy[channel] * inv_len).clamp(0.0, 1.0);
-            entry.average[channel] = entry.average[channel] * (1.0 - SMOOTHING) + avg * SMOOTHING;
-
-            let mut peak: f32 = 0.0;
-            for sample in frame.channel_samples.iter() {
-                peak = peak.max(sample[channel].abs());
-            }
-            entry.peak[channel] = entry.peak[channel] * (1.0 - SMOOTHING) + peak * SMOOTHING;
-        }
-        entry.frequencies = frame.frequencies;
+        state.metrics.entry(frame.entity).or_default().update(
+            frame.frame_index,
+            &frame.channel_samples,
+            frame.frequencies,
+            &config,
+        );
     }
 }
 
@@ -244,6 +320,7 @@ pub(in crate::plugin) fn detect_pattern_triggers(
     mut frames: MessageReader<FrameAudioData>,
     pattern_sets: Query<&PatternTriggerSet>,
     mut runtime: ResMut<PatternTriggerRuntime>,
+    mut previous_registers: Local<HashMap<Entity, [u8; 16]>>,
     mut pattern_events: MessageWriter<PatternTriggered>,
 ) {
     if !config.pattern_events {
@@ -253,14 +330,18 @@ pub(in crate::plugin) fn detect_pattern_triggers(
     for frame in frames.read() {
         let Ok(set) = pattern_sets.get(frame.entity) else {
             runtime.0.remove(&frame.entity);
+            previous_registers.remove(&frame.entity);
             continue;
         };
 
         if set.patterns.is_empty() {
             runtime.0.remove(&frame.entity);
+            previous_registers.remove(&frame.entity);
             continue;
         }
 
+        let previous = previous_registers.insert(frame.entity, frame.registers);
+
         let samples = frame.samples_per_frame.max(1) as f32;
         let entry = runtime.0.entry(frame.entity).or_default();
         if entry.len() < set.patterns.len() {
@@ -284,6 +365,14 @@ pub(in crate::plugin) fn detect_pattern_triggers(
                 }
             }
 
+            let conditions_hold = trigger
+                .conditions
+                .iter()
+                .all(|condition| condition.matches(channel, &frame.registers, previous.as_ref()));
+            if !conditions_hold {
+                continue;
+            }
+
             let last_frame = entry[idx];
             let on_cooldown = last_frame != u64::MAX
                 && frame.frame_index < last_frame.saturating_add(trigger.cooldown_frames);
@@ -395,8 +484,10 @@ pub(in crate::plugin) fn initialize_playback(
         Option<&mut PlaybackRuntimeState>,
     )>,
     mut audio_assets: ResMut<Assets<Ym2149AudioSource>>,
+    song_bytes: Res<Assets<Ym2149SongBytes>>,
     mut pending_reads: Local<HashMap<(Entity, PendingSlot), PendingFileRead>>,
     audio_sinks: Query<&AudioSink>,
+    config: Res<Ym2149PluginConfig>,
 ) {
     let mut alive = HashSet::new();
 
@@ -424,6 +515,7 @@ pub(in crate::plugin) fn initialize_playback(
                     title: playback.song_title.clone(),
                     author: playback.song_author.clone(),
                     comment: String::new(),
+                    format: player_arc.read().metadata().format.clone(),
                     frame_count: playback
                         .metrics
                         .as_ref()
@@ -440,6 +532,11 @@ pub(in crate::plugin) fn initialize_playback(
                 Ym2149AudioSource::from_shared_player(player_arc, metadata, total_samples);
             // Store stream state for seek buffer flushing
             playback.audio_stream_state = Some(audio_source.stream_state());
+            if let Some(group) = &playback.sync_group {
+                audio_source.stream_state().join_sync_group(group);
+            }
+            #[cfg(feature = "fx")]
+            audio_source.set_fx(&config.fx);
             let audio_handle = audio_assets.add(audio_source);
             let settings = if playback.state == PlaybackState::Playing {
                 PlaybackSettings::LOOP.with_volume(bevy::audio::Volume::Linear(playback.volume))
@@ -466,6 +563,7 @@ pub(in crate::plugin) fn initialize_playback(
                 title: playback.song_title.clone(),
                 author: playback.song_author.clone(),
                 comment: String::new(),
+                format: player_arc.read().metadata().format.clone(),
                 frame_count: metrics.frame_count,
                 duration_seconds: metrics.duration_seconds(),
             };
@@ -475,6 +573,11 @@ pub(in crate::plugin) fn initialize_playback(
                 Ym2149AudioSource::from_shared_player(player_arc, metadata, total_samples);
             // Store stream state for seek buffer flushing
             playback.audio_stream_state = Some(audio_source.stream_state());
+            if let Some(group) = &playback.sync_group {
+                audio_source.stream_state().join_sync_group(group);
+            }
+            #[cfg(feature = "fx")]
+            audio_source.set_fx(&config.fx);
             let audio_handle = audio_assets.add(audio_source);
 
             // Remove old AudioPlayer and AudioSink components if they exist
@@ -514,6 +617,7 @@ pub(in crate::plugin) fn initialize_playback(
                 &source,
                 &mut pending_reads,
                 audio_assets.as_ref(),
+                song_bytes.as_ref(),
             ) {
                 SourceLoadResult::Pending => continue,
                 SourceLoadResult::Failed(err) => {
@@ -523,13 +627,14 @@ pub(in crate::plugin) fn initialize_playback(
                 SourceLoadResult::Ready(bytes) => bytes,
             };
 
-            let mut load = match load_player_from_bytes(&loaded.data, loaded.metadata.as_ref()) {
-                Ok(load) => load,
-                Err(err) => {
-                    error!("Failed to initialize YM2149 player: {}", err);
-                    continue;
-                }
-            };
+            let mut load =
+                match load_player_from_bytes(&loaded.data, loaded.metadata.as_ref(), config.chip) {
+                    Ok(load) => load,
+                    Err(err) => {
+                        error!("Failed to initialize YM2149 player: {}", err);
+                        continue;
+                    }
+                };
 
             playback.song_title = load.metadata.title.clone();
             playback.song_author = load.metadata.author.clone();
@@ -547,6 +652,13 @@ pub(in crate::plugin) fn initialize_playback(
                 load.player.set_subsong(subsong_index);
             }
 
+            // Resume position after a hot reload
+            let resume_frame = playback.resume_frame.take();
+            if let Some(frame) = resume_frame {
+                load.player.skip_to_frame(frame);
+                playback.frame_position = load.player.current_frame() as u32;
+            }
+
             let player_arc = Arc::new(RwLock::new(load.player));
             // Diagnostics/crossfade use this player; audio playback uses its own player below
             playback.player = Some(player_arc);
@@ -562,11 +674,13 @@ pub(in crate::plugin) fn initialize_playback(
 
             // Create a Ym2149AudioSource with its own player
             // but with the same subsong selection applied
-            let audio_source = match Ym2149AudioSource::new_with_subsong(
+            let audio_source = match Ym2149AudioSource::new_with_options(
                 loaded.data.clone(),
                 playback.stereo_gain.clone(),
                 playback.tone_settings.clone(),
                 pending_subsong,
+                resume_frame,
+                config.chip,
             ) {
                 Ok(source) => source,
                 Err(err) => {
@@ -579,6 +693,11 @@ pub(in crate::plugin) fn initialize_playback(
             playback.audio_player = Some(audio_source.shared_player());
             // Store stream state for seek buffer flushing
             playback.audio_stream_state = Some(audio_source.stream_state());
+            if let Some(group) = &playback.sync_group {
+                audio_source.stream_state().join_sync_group(group);
+            }
+            #[cfg(feature = "fx")]
+            audio_source.set_fx(&config.fx);
 
             // Add the asset and get a handle
             let audio_handle = audio_assets.add(audio_source);
@@ -610,9 +729,11 @@ pub(in crate::plugin) fn initialize_playback(
         process_pending_crossfade(
             &mut commands,
             audio_assets.as_mut(),
+            song_bytes.as_ref(),
             entity,
             &mut playback,
             &mut pending_reads,
+            &config,
         );
     }
 
@@ -711,8 +832,9 @@ pub(in crate::plugin) fn process_playback_frames(
     mut finished_events: MessageWriter<TrackFinished>,
     mut audio_sinks: Query<&mut AudioSink>,
     mut frame_events: MessageWriter<FrameAudioData>,
+    mut progress_events: MessageWriter<CrossfadeProgress>,
 ) {
-    let delta = time.delta_secs();
+    let time_delta = time.delta_secs();
     let master_volume = settings.master_volume.clamp(0.0, 1.0);
 
     for (entity, mut playback, mut runtime) in playbacks.iter_mut() {
@@ -734,6 +856,7 @@ pub(in crate::plugin) fn process_playback_frames(
             runtime.last_volume = playback.volume;
         }
 
+        let delta = playback_delta(&mut runtime, &mut playback, time_delta);
         if playback.state != PlaybackState::Playing {
             playback.seek(player.current_frame() as u32);
             continue;
@@ -766,11 +889,12 @@ pub(in crate::plugin) fn process_playback_frames(
                 .crossfade
                 .as_ref()
                 .map(|cf| {
-                    if cf.duration <= f32::EPSILON {
+                    if cf.start_at.is_some() {
+                        (1.0, 0.0)
+                    } else if cf.duration <= f32::EPSILON {
                         (0.0, 1.0)
                     } else {
-                        let ratio = (cf.elapsed / cf.duration).clamp(0.0, 1.0);
-                        (1.0 - ratio, ratio)
+                        cf.curve.gains(cf.elapsed / cf.duration)
                     }
                 })
                 .unwrap_or((1.0, 0.0));
@@ -814,10 +938,7 @@ pub(in crate::plugin) fn process_playback_frames(
                 stereo_samples.push(scaled * right_gain);
             }
 
-            let registers = player
-                .chip()
-                .map(|chip| chip.dump_registers())
-                .unwrap_or([0; 16]);
+            let registers = player.dump_registers().unwrap_or([0; 16]);
             let frequencies = channel_frequencies(&registers);
 
             let elapsed_seconds = runtime.frames_rendered as f32 * frame_duration;
@@ -838,13 +959,40 @@ pub(in crate::plugin) fn process_playback_frames(
                 sfx.tick_frame();
             }
 
+            let song_seconds = playback.frame_position as f32 * frame_duration;
             if let Some(state) = playback.crossfade.as_mut() {
+                if let Some(start_at) = state.start_at {
+                    // Pre-buffered deck: wait for the trigger point (or the
+                    // outgoing song ending early) and for its sink to exist
+                    let due = song_seconds >= start_at
+                        || player.state() != ym2149_ym_replayer::PlaybackState::Playing;
+                    let sink_ready = state
+                        .crossfade_entity
+                        .is_none_or(|cf_entity| audio_sinks.get(cf_entity).is_ok());
+                    if !(due && sink_ready) {
+                        continue;
+                    }
+                    state.start_at = None;
+                    if let Some(cf_entity) = state.crossfade_entity
+                        && let Ok(cf_sink) = audio_sinks.get_mut(cf_entity)
+                    {
+                        cf_sink.play();
+                    }
+                }
                 state.elapsed = (state.elapsed + frame_duration).min(state.duration);
+                progress_events.write(CrossfadeProgress {
+                    entity,
+                    target_index: state.target_index,
+                    progress: if state.duration > f32::EPSILON {
+                        state.elapsed / state.duration
+                    } else {
+                        1.0
+                    },
+                });
 
                 if state.duration > f32::EPSILON {
                     let fade_ratio = (state.elapsed / state.duration).clamp(0.0, 1.0);
-                    let fade_out_volume = 1.0 - fade_ratio;
-                    let fade_in_volume = fade_ratio;
+                    let (fade_out_volume, fade_in_volume) = state.curve.gains(fade_ratio);
                     let cf_entity_opt = state.crossfade_entity;
 
                     if let Ok(mut sink) = audio_sinks.get_mut(entity) {
@@ -923,8 +1071,14 @@ pub(super) struct LoadResult {
 pub(super) fn load_player_from_bytes(
     data: &[u8],
     override_metadata: Option<&Ym2149Metadata>,
+    chip: ChipBackend,
 ) -> Result<LoadResult, String> {
-    let (player, metrics, mut metadata) = load_song_from_bytes(data)?;
+    // Assets were already decoded by their format's loader; don't re-detect them.
+    let format = override_metadata.and_then(|meta| SongFormat::from_name(&meta.format));
+    let (player, metrics, mut metadata) = match format {
+        Some(format) => load_song_as(data, format, chip)?,
+        None => load_song_from_bytes(data, chip)?,
+    };
     if let Some(meta) = override_metadata {
         metadata.title = meta.title.clone();
         metadata.author = meta.author.clone();
@@ -940,7 +1094,7 @@ pub(super) fn load_player_from_bytes(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::patterns::PatternTrigger;
+    use crate::patterns::{PatternTrigger, RegisterCondition};
     use bevy::prelude::Messages;
     use std::sync::Arc;
 
@@ -955,6 +1109,17 @@ mod tests {
         frame_index: u64,
         amplitude: f32,
         freq: Option<f32>,
+    ) {
+        send_frame_with_registers(app, entity, frame_index, amplitude, freq, [0; 16]);
+    }
+
+    fn send_frame_with_registers(
+        app: &mut App,
+        entity: Entity,
+        frame_index: u64,
+        amplitude: f32,
+        freq: Option<f32>,
+        registers: [u8; 16],
     ) {
         let mut events = app.world_mut().resource_mut::<Messages<FrameAudioData>>();
         events.write(FrameAudioData {
@@ -967,12 +1132,11 @@ mod tests {
             channel_energy: [amplitude, 0.0, 0.0],
             frequencies: [freq, None, None],
             samples_per_frame: 1,
-            registers: [0; 16],
+            registers,
         });
     }
 
-    #[test]
-    fn pattern_trigger_emits_and_respects_cooldown() {
+    fn pattern_app(patterns: Vec<PatternTrigger>) -> (App, Entity) {
         let mut app = App::new();
         app.insert_resource(Ym2149PluginConfig {
             pattern_events: true,
@@ -981,18 +1145,22 @@ mod tests {
         app.add_message::<FrameAudioData>();
         app.add_message::<PatternTriggered>();
         app.insert_resource(PatternTriggerRuntime::default());
-
         let entity = app
             .world_mut()
-            .spawn(PatternTriggerSet::from_patterns(vec![
-                PatternTrigger::new("lead", 0)
-                    .with_min_amplitude(0.2)
-                    .with_frequency(440.0, 5.0)
-                    .with_cooldown(2),
-            ]))
+            .spawn(PatternTriggerSet::from_patterns(patterns))
             .id();
-
         app.add_systems(Update, detect_pattern_triggers);
+        (app, entity)
+    }
+
+    #[test]
+    fn pattern_trigger_emits_and_respects_cooldown() {
+        let (mut app, entity) = pattern_app(vec![
+            PatternTrigger::new("lead", 0)
+                .with_min_amplitude(0.2)
+                .with_frequency(440.0, 5.0)
+                .with_cooldown(2),
+        ]);
 
         send_frame(&mut app, entity, 1, 0.4, Some(441.0));
         app.update();
@@ -1008,4 +1176,81 @@ mod tests {
         app.update();
         assert_eq!(drain_hits(&mut app).len(), 1);
     }
+
+    #[test]
+    fn register_conditions_gate_pattern_triggers() {
+        let (mut app, entity) = pattern_app(vec![
+            PatternTrigger::new("drum", 1)
+                .with_min_amplitude(0.0)
+                .with_condition(RegisterCondition::NoiseEnabled)
+                .with_condition(RegisterCondition::VolumeAbove(10)),
+            PatternTrigger::new("buzz", 0)
+                .with_min_amplitude(0.0)
+                .with_condition(RegisterCondition::EnvelopeRetrigger),
+        ]);
+        let ids = |app: &mut App| -> Vec<String> {
+            drain_hits(app)
+                .into_iter()
+                .map(|hit| hit.pattern_id)
+                .collect()
+        };
+
+        let mut registers = [0u8; 16];
+        registers[7] = 0b0011_1111; // all tone and noise disabled
+        registers[9] = 12;
+        registers[13] = 0x08;
+        send_frame_with_registers(&mut app, entity, 0, 0.0, None, registers);
+        app.update();
+        assert!(ids(&mut app).is_empty());
+
+        // Noise on channel B at volume 12, envelope shape rewritten
+        registers[7] = 0b0010_1111;
+        registers[13] = 0x0E;
+        send_frame_with_registers(&mut app, entity, 1, 0.0, None, registers);
+        app.update();
+        assert_eq!(ids(&mut app), vec!["drum", "buzz"]);
+
+        // Volume too low, envelope shape unchanged
+        registers[9] = 10;
+        send_frame_with_registers(&mut app, entity, 2, 0.0, None, registers);
+        app.update();
+        assert!(ids(&mut app).is_empty());
+
+        // Envelope mode counts as full volume
+        registers[9] = 0x10;
+        send_frame_with_registers(&mut app, entity, 3, 0.0, None, registers);
+        app.update();
+        assert_eq!(ids(&mut app), vec!["drum"]);
+    }
+
+    #[test]
+    fn playback_follows_consumed_audio_and_reports_drift() {
+        use crate::playback::YM2149_SAMPLE_RATE;
+        use crate::streaming::StreamingDecoder;
+
+        let mut runtime = PlaybackRuntimeState::default();
+        let mut playback = Ym2149Playback {
+            state: PlaybackState::Playing,
+            ..Default::default()
+        };
+        assert_eq!(playback_delta(&mut runtime, &mut playback, 0.5), 0.5);
+
+        // A stream the output has not pulled from yet keeps the Time pacing
+        let stream = Arc::new(AudioStreamState::new());
+        playback.audio_stream_state = Some(Arc::clone(&stream));
+        assert_eq!(playback_delta(&mut runtime, &mut playback, 0.5), 0.5);
+        assert_eq!(playback.clock_drift_seconds(), None);
+
+        let mut decoder = StreamingDecoder::new(Arc::clone(&stream), YM2149_SAMPLE_RATE, 0);
+        let frames = YM2149_SAMPLE_RATE as usize / 100;
+        stream.buffer.write(&vec![0.1; frames * 2]);
+        decoder.next();
+        let delta = playback_delta(&mut runtime, &mut playback, 0.02);
+        assert!((delta - 0.01).abs() < 1e-6);
+        let drift = playback.clock_drift_seconds().unwrap();
+        assert!((drift - 0.01).abs() < 1e-6);
+
+        // Nothing consumed: the playback holds while Time moves on
+        assert_eq!(playback_delta(&mut runtime, &mut playback, 0.02), 0.0);
+    }
 }
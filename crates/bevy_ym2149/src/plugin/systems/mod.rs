@@ -12,11 +12,13 @@
 //! - [`main_systems`] - Core playback state, frame processing, diagnostics, and SFX
 //! - [`crossfade`] - Dual-deck crossfade transitions
 //! - [`loader`] - Asset loading helpers
+//! - [`hot_reload`] - Reloading songs whose asset changed on disk
 //!
 //! # System Overview
 //!
 //! ```text
 //! PreUpdate:
+//!   reload_modified_sources - Hot reload songs whose asset changed
 //!   initialize_playback    - Load assets, create audio sources
 //!   drive_playback_state   - Sync playback state (play/pause/stop)
 //!
@@ -32,8 +34,11 @@
 //! ```
 
 pub(super) mod crossfade;
+mod hot_reload;
 pub(super) mod loader;
 
+pub(super) use hot_reload::reload_modified_sources;
+
 // Main systems module - re-export all public functions
 mod main_systems;
 pub(super) use main_systems::*;
@@ -5,18 +5,85 @@ use parking_lot::RwLock;
 use ym2149::Ym2149Backend;
 use ym2149_arkos_replayer::{AksSong, parser::load_aks, player::ArkosPlayer};
 use ym2149_ay_replayer::{AyMetadata as AyFileMetadata, AyPlayer, CPC_UNSUPPORTED_MSG};
-use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, MetadataFields, SampleCache};
-use ym2149_sndh_replayer::{SndhPlayer, is_sndh_data, load_sndh};
-use ym2149_ym_replayer::{self, LoadSummary, YmPlayer};
+use ym2149_common::{
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, MetadataFields, SampleCache,
+    load_with_detection,
+};
+use ym2149_sndh_replayer::{SndhPlayer, load_sndh};
+use ym2149_softsynth::SoftSynth;
+use ym2149_ym_replayer::{self, LoadSummary, YmPlayer, YmPlayerGeneric};
 
 use crate::audio_source::Ym2149Metadata;
 use crate::error::BevyYm2149Error;
 use crate::playback::{PlaybackMetrics, YM2149_SAMPLE_RATE, YM2149_SAMPLE_RATE_F32};
+use crate::plugin::ChipBackend;
 use crate::synth::{YmSynthController, YmSynthPlayer};
 
 /// Shared song player handle used throughout the plugin.
 pub type SharedSongPlayer = Arc<RwLock<YmSongPlayer>>;
 
+/// Song file formats understood by the plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SongFormat {
+    /// ST-Sound register dumps (YM2-YM6)
+    Ym,
+    /// Arkos Tracker 2/3 songs (`.aks`)
+    Arkos,
+    /// ZXAY/EMUL files (`.ay`)
+    Ay,
+    /// Atari ST SNDH files (`.sndh`)
+    Sndh,
+}
+
+impl SongFormat {
+    /// All supported formats.
+    pub const ALL: [SongFormat; 4] = [Self::Ym, Self::Arkos, Self::Ay, Self::Sndh];
+
+    /// File extensions (lowercase, without dot) handled for this format.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            Self::Ym => &["ym"],
+            Self::Arkos => &["aks"],
+            Self::Ay => &["ay"],
+            Self::Sndh => &["sndh"],
+        }
+    }
+
+    /// Look up the format for a file extension (case-insensitive, without dot).
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        let extension = extension.to_ascii_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extensions().contains(&extension.as_str()))
+    }
+
+    /// Short format name, as reported by [`Ym2149Metadata::format`](MetadataFields::format).
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ym => "YM",
+            Self::Arkos => "AKS",
+            Self::Ay => "AY",
+            Self::Sndh => "SNDH",
+        }
+    }
+
+    /// Song format for a [`detect_format`](ym2149_common::detect_format) result.
+    pub(crate) fn from_detected(format: ChiptuneFormat) -> Option<Self> {
+        match format {
+            ChiptuneFormat::Ym => Some(Self::Ym),
+            ChiptuneFormat::Arkos => Some(Self::Arkos),
+            ChiptuneFormat::Ay => Some(Self::Ay),
+            ChiptuneFormat::Sndh => Some(Self::Sndh),
+            ChiptuneFormat::Kss | ChiptuneFormat::Zip | ChiptuneFormat::Unknown => None,
+        }
+    }
+
+    /// Inverse of [`SongFormat::name`].
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+}
+
 // ============================================================================
 // BevyPlayerTrait - Common interface for all player wrappers
 // ============================================================================
@@ -37,6 +104,9 @@ pub(crate) trait BevyPlayerTrait {
     fn metadata(&self) -> &Ym2149Metadata;
     fn metrics(&self) -> Option<PlaybackMetrics>;
     fn chip(&self) -> Option<&ym2149::Ym2149>;
+    fn dump_registers(&self) -> Option<[u8; 16]> {
+        self.chip().map(|chip| chip.dump_registers())
+    }
     fn frame_count(&self) -> usize;
     fn subsong_count(&self) -> usize;
     fn current_subsong(&self) -> usize;
@@ -84,7 +154,7 @@ pub enum YmSongPlayer {
 
 impl YmSongPlayer {
     pub(crate) fn new_ym(
-        player: YmPlayer,
+        player: YmBackendPlayer,
         summary: &LoadSummary,
         metadata: Ym2149Metadata,
     ) -> Self {
@@ -98,6 +168,7 @@ impl YmSongPlayer {
             title: song.metadata.title.clone(),
             author: song.metadata.author.clone(),
             comment: song.metadata.comments.clone(),
+            format: SongFormat::Arkos.name().to_string(),
             frame_count: 0,
             duration_seconds: 0.0,
         };
@@ -134,6 +205,7 @@ impl YmSongPlayer {
             title: meta.title().to_string(),
             author: meta.author().to_string(),
             comment: meta.comments().to_string(),
+            format: SongFormat::Sndh.name().to_string(),
             frame_count: 0, // SNDH doesn't track frames like YM
             duration_seconds: 0.0,
         };
@@ -205,10 +277,17 @@ impl YmSongPlayer {
     }
 
     /// Returns a reference to the underlying YM2149 chip, if available.
+    ///
+    /// `None` for YM songs rendered by the [`ChipBackend::SoftSynth`] backend.
     pub fn chip(&self) -> Option<&ym2149::Ym2149> {
         delegate_to_inner!(self, chip)
     }
 
+    /// Current PSG register values (R0-R15) of the primary chip, whichever backend renders it.
+    pub fn dump_registers(&self) -> Option<[u8; 16]> {
+        delegate_to_inner!(self, dump_registers)
+    }
+
     /// Returns a reference to the underlying YM2149 chip.
     ///
     /// # Panics
@@ -246,6 +325,31 @@ impl YmSongPlayer {
         self.subsong_count() > 1
     }
 
+    /// Move a freshly loaded player forward to `frame`.
+    ///
+    /// YM songs seek directly; Arkos, AY and SNDH songs are fast-forwarded by
+    /// running their replay routine silently. Returns false for the live synth.
+    pub(crate) fn skip_to_frame(&mut self, frame: usize) -> bool {
+        let was_playing = self.state() == ym2149_common::PlaybackState::Playing;
+        if was_playing {
+            self.pause();
+        }
+        let skipped = match self {
+            Self::Ym(p) => {
+                p.seek_frame(frame);
+                true
+            }
+            Self::Arkos(p) => p.player.step_frames(frame).is_some(),
+            Self::Ay(p) => p.player.step_frames(frame).is_some(),
+            Self::Sndh(p) => p.player.step_frames(frame).is_some(),
+            Self::Synth(_) => false,
+        };
+        if was_playing {
+            self.play();
+        }
+        skipped
+    }
+
     /// Seek to a percentage position (0.0 to 1.0).
     ///
     /// Returns true if seeking succeeded. Supported for YM and SNDH formats.
@@ -285,15 +389,46 @@ impl YmSongPlayer {
 // YmBevyPlayer - Wrapper for YM format
 // ============================================================================
 
+/// YM player running on the backend chosen by [`ChipBackend`].
+pub(crate) enum YmBackendPlayer {
+    Hardware(YmPlayer),
+    SoftSynth(YmPlayerGeneric<SoftSynth>),
+}
+
+/// Macro for forwarding a call to the YM player, whichever backend it uses.
+macro_rules! with_ym_player {
+    ($player:expr, $p:ident => $body:expr) => {
+        match $player {
+            YmBackendPlayer::Hardware($p) => $body,
+            YmBackendPlayer::SoftSynth($p) => $body,
+        }
+    };
+}
+
+impl YmBackendPlayer {
+    /// Load YM data with the requested backend.
+    fn load(data: &[u8], chip: ChipBackend) -> ym2149_ym_replayer::Result<(Self, LoadSummary)> {
+        match chip {
+            ChipBackend::Hardware => ym2149_ym_replayer::load_song(data)
+                .map(|(player, summary)| (Self::Hardware(player), summary)),
+            ChipBackend::SoftSynth => {
+                let mut player = YmPlayerGeneric::<SoftSynth>::new();
+                let summary = player.load_data(data)?;
+                Ok((Self::SoftSynth(player), summary))
+            }
+        }
+    }
+}
+
 /// Wrapper for YM player that implements `BevyPlayerTrait`.
 pub struct YmBevyPlayer {
-    player: YmPlayer,
+    player: YmBackendPlayer,
     metrics: PlaybackMetrics,
     metadata: Ym2149Metadata,
 }
 
 impl YmBevyPlayer {
-    fn new(player: YmPlayer, summary: &LoadSummary, metadata: Ym2149Metadata) -> Self {
+    fn new(player: YmBackendPlayer, summary: &LoadSummary, metadata: Ym2149Metadata) -> Self {
         Self {
             player,
             metrics: PlaybackMetrics::from(summary),
@@ -301,17 +436,22 @@ impl YmBevyPlayer {
         }
     }
 
+    /// Seek to an absolute frame.
+    fn seek_frame(&mut self, frame: usize) {
+        with_ym_player!(&mut self.player, p => p.seek_frame(frame));
+    }
+
     /// Seek to a percentage position (0.0 to 1.0).
     pub fn seek_percentage(&mut self, position: f32) -> bool {
-        let frame_count = self.player.frame_count();
+        let frame_count = with_ym_player!(&self.player, p => p.frame_count());
         if frame_count == 0 {
             bevy::log::warn!("YM seek failed: frame_count=0");
             return false;
         }
         let target_frame = (position.clamp(0.0, 1.0) * frame_count as f32) as usize;
-        let before = self.player.get_current_frame();
-        self.player.seek_frame(target_frame);
-        let after = self.player.get_current_frame();
+        let before = with_ym_player!(&self.player, p => p.get_current_frame());
+        with_ym_player!(&mut self.player, p => p.seek_frame(target_frame));
+        let after = with_ym_player!(&self.player, p => p.get_current_frame());
         bevy::log::info!(
             "YM seek: target={}, before={}, after={}",
             target_frame,
@@ -324,41 +464,43 @@ impl YmBevyPlayer {
 
 impl BevyPlayerTrait for YmBevyPlayer {
     fn play(&mut self) {
-        self.player.play();
+        with_ym_player!(&mut self.player, p => p.play());
     }
 
     fn pause(&mut self) {
-        self.player.pause();
+        with_ym_player!(&mut self.player, p => p.pause());
     }
 
     fn stop(&mut self) {
-        self.player.stop();
+        with_ym_player!(&mut self.player, p => p.stop());
     }
 
     fn state(&self) -> ym2149_common::PlaybackState {
-        self.player.state()
+        with_ym_player!(&self.player, p => p.state())
     }
 
     fn current_frame(&self) -> usize {
-        self.player.get_current_frame()
+        with_ym_player!(&self.player, p => p.get_current_frame())
     }
 
     fn samples_per_frame(&self) -> u32 {
-        self.player.samples_per_frame_value()
+        with_ym_player!(&self.player, p => p.samples_per_frame_value())
     }
 
     fn generate_sample(&mut self) -> f32 {
-        self.player.generate_sample()
+        with_ym_player!(&mut self.player, p => p.generate_sample())
     }
 
     fn generate_samples_into(&mut self, buffer: &mut [f32]) {
-        self.player.generate_samples_into(buffer);
+        with_ym_player!(&mut self.player, p => p.generate_samples_into(buffer));
     }
 
     fn generate_sample_with_channels(&mut self) -> (f32, [f32; 3]) {
-        let sample = self.player.generate_sample();
-        let (a, b, c) = self.player.get_chip().get_channel_outputs();
-        (sample, [a, b, c])
+        with_ym_player!(&mut self.player, p => {
+            let sample = p.generate_sample();
+            let (a, b, c) = p.backend().get_channel_outputs();
+            (sample, [a, b, c])
+        })
     }
 
     fn metadata(&self) -> &Ym2149Metadata {
@@ -370,7 +512,14 @@ impl BevyPlayerTrait for YmBevyPlayer {
     }
 
     fn chip(&self) -> Option<&ym2149::Ym2149> {
-        Some(self.player.get_chip())
+        match &self.player {
+            YmBackendPlayer::Hardware(player) => Some(player.get_chip()),
+            YmBackendPlayer::SoftSynth(_) => None,
+        }
+    }
+
+    fn dump_registers(&self) -> Option<[u8; 16]> {
+        Some(with_ym_player!(&self.player, p => p.dump_registers()))
     }
 
     fn frame_count(&self) -> usize {
@@ -395,59 +544,68 @@ impl BevyPlayerTrait for YmBevyPlayer {
 // ============================================================================
 
 /// Load a song (YM, AKS, AY, or SNDH) from raw bytes.
+///
+/// `chip` selects the backend for YM songs; other formats ignore it.
 pub(crate) fn load_song_from_bytes(
     data: &[u8],
+    chip: ChipBackend,
 ) -> std::result::Result<(YmSongPlayer, PlaybackMetrics, Ym2149Metadata), String> {
-    // Check if this looks like SNDH data first (to avoid wrong format fallback)
-    if is_sndh_data(data) {
-        return YmSongPlayer::new_sndh(data)
-            .map(|player| {
-                let metadata = player.metadata().clone();
-                let metrics = player.metrics().unwrap_or(PlaybackMetrics {
-                    frame_count: 0,
-                    samples_per_frame: YM2149_SAMPLE_RATE,
-                });
-                (player, metrics, metadata)
-            })
-            .map_err(|e| format!("Failed to load SNDH: {e}"));
-    }
+    // Try formats in order of header evidence; on failure, report why each
+    // parser rejected the data rather than only the last fallback's error.
+    load_with_detection(data, |format| match SongFormat::from_detected(format) {
+        Some(format) => load_format(data, format, chip),
+        None => Err(format!("no {} player", format.name())),
+    })
+    .map_err(|e| format!("Failed to load song: {e}"))
+}
 
-    // Try other formats in order
-    if let Ok((player, summary)) = ym2149_ym_replayer::load_song(data) {
-        let metadata = metadata_from_player(&player, &summary);
-        let metrics = PlaybackMetrics::from(&summary);
-        Ok((
-            YmSongPlayer::new_ym(player, &summary, metadata.clone()),
-            metrics,
-            metadata,
-        ))
-    } else if let Ok(player) = YmSongPlayer::new_arkos(data) {
-        let metadata = player.metadata().clone();
-        let metrics = player.metrics().unwrap_or(PlaybackMetrics {
-            frame_count: metadata.frame_count,
-            samples_per_frame: YM2149_SAMPLE_RATE,
-        });
-        Ok((player, metrics, metadata))
-    } else if let Ok(player) = YmSongPlayer::new_sndh(data) {
-        let metadata = player.metadata().clone();
-        let metrics = player.metrics().unwrap_or(PlaybackMetrics {
-            frame_count: 0,
-            samples_per_frame: YM2149_SAMPLE_RATE,
-        });
-        Ok((player, metrics, metadata))
-    } else {
-        let player =
-            YmSongPlayer::new_ay(data).map_err(|e| format!("Failed to load AY song: {e}"))?;
-        let metadata = player.metadata().clone();
-        let metrics = PlaybackMetrics {
-            frame_count: metadata.frame_count,
-            samples_per_frame: YM2149_SAMPLE_RATE,
-        };
-        Ok((player, metrics, metadata))
-    }
+/// Load a song of a known `format` from raw bytes, without format detection.
+///
+/// `chip` selects the backend for YM songs; other formats ignore it.
+pub(crate) fn load_song_as(
+    data: &[u8],
+    format: SongFormat,
+    chip: ChipBackend,
+) -> std::result::Result<(YmSongPlayer, PlaybackMetrics, Ym2149Metadata), String> {
+    load_format(data, format, chip)
+        .map_err(|e| format!("Failed to load {} song: {e}", format.name()))
+}
+
+/// [`load_song_as`] without the format prefix on errors.
+fn load_format(
+    data: &[u8],
+    format: SongFormat,
+    chip: ChipBackend,
+) -> std::result::Result<(YmSongPlayer, PlaybackMetrics, Ym2149Metadata), String> {
+    let player = match format {
+        SongFormat::Ym => {
+            let (player, summary) = YmBackendPlayer::load(data, chip).map_err(|e| e.to_string())?;
+            let metadata = with_ym_player!(&player, p => metadata_from_player(p, &summary));
+            let metrics = PlaybackMetrics::from(&summary);
+            return Ok((
+                YmSongPlayer::new_ym(player, &summary, metadata.clone()),
+                metrics,
+                metadata,
+            ));
+        }
+        SongFormat::Arkos => YmSongPlayer::new_arkos(data),
+        SongFormat::Ay => YmSongPlayer::new_ay(data),
+        SongFormat::Sndh => YmSongPlayer::new_sndh(data),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let metadata = player.metadata().clone();
+    let metrics = player.metrics().unwrap_or(PlaybackMetrics {
+        frame_count: metadata.frame_count,
+        samples_per_frame: YM2149_SAMPLE_RATE,
+    });
+    Ok((player, metrics, metadata))
 }
 
-fn metadata_from_player(player: &YmPlayer, summary: &LoadSummary) -> Ym2149Metadata {
+fn metadata_from_player<B: Ym2149Backend>(
+    player: &YmPlayerGeneric<B>,
+    summary: &LoadSummary,
+) -> Ym2149Metadata {
     let frame_count = summary.frame_count;
     let (title, author, comment) = if let Some(info) = player.info() {
         (
@@ -467,6 +625,7 @@ fn metadata_from_player(player: &YmPlayer, summary: &LoadSummary) -> Ym2149Metad
         title,
         author,
         comment,
+        format: SongFormat::Ym.name().to_string(),
         frame_count,
         duration_seconds,
     }
@@ -481,6 +640,7 @@ fn metadata_from_ay(meta: &AyFileMetadata) -> Ym2149Metadata {
         title: meta.song_name.clone(),
         author: meta.author.clone(),
         comment: meta.misc.clone(),
+        format: SongFormat::Ay.name().to_string(),
         frame_count,
         duration_seconds,
     }
@@ -756,8 +916,13 @@ impl BevyPlayerTrait for AyBevyPlayer {
         self.player.metadata().song_index + 1
     }
 
-    fn set_subsong(&mut self, _index: usize) -> bool {
-        false
+    fn set_subsong(&mut self, index: usize) -> bool {
+        if !ChiptunePlayerBase::set_subsong(&mut self.player, index) {
+            return false;
+        }
+        self.metadata = metadata_from_ay(self.player.metadata());
+        self.cache.reset();
+        true
     }
 }
 
@@ -11,12 +11,17 @@
 //! computationally intensive SNDH files.
 
 use parking_lot::{Mutex, RwLock};
-use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::thread::{self, JoinHandle};
 
-use crate::playback::ToneSettings;
+use crate::audio_bridge::AudioBus;
+use crate::playback::{ToneSettings, YM2149_SAMPLE_RATE};
 use crate::song_player::SharedSongPlayer;
+use crate::sync_group::{SyncGroup, SyncMember};
+use ym2149_common::RegisterDelayLine;
+#[cfg(feature = "fx")]
+use ym2149_common::{FxConfig, FxRack};
 
 /// Default ring buffer size (enough for ~370ms at 44100Hz stereo)
 const DEFAULT_BUFFER_SIZE: usize = 32768;
@@ -162,8 +167,19 @@ pub struct AudioStreamState {
     pub stereo_gain: RwLock<(f32, f32)>,
     /// Tone processing settings
     pub tone_settings: RwLock<ToneSettings>,
+    /// Settings of the bus the playback is routed to (ramped by the producer)
+    pub bus: RwLock<AudioBus>,
     /// Seek counter - incremented on each seek to signal decoder to clear local buffer
     pub seek_counter: AtomicUsize,
+    /// Stereo samples handed to the audio output so far
+    consumed: AtomicU64,
+    /// Group this stream starts and stays in lockstep with
+    sync: OnceLock<SyncMember>,
+    /// Registers of each generated batch, delayed by the buffered audio
+    registers: Mutex<RegisterDelayLine<[u8; 16]>>,
+    /// Insert effects applied after tone processing (`None` = bypass)
+    #[cfg(feature = "fx")]
+    pub fx: Mutex<Option<FxRack>>,
 }
 
 impl AudioStreamState {
@@ -174,15 +190,63 @@ impl AudioStreamState {
             ready: AtomicBool::new(false),
             stereo_gain: RwLock::new((1.0, 1.0)),
             tone_settings: RwLock::new(ToneSettings::default()),
+            bus: RwLock::new(AudioBus::NEUTRAL),
             seek_counter: AtomicUsize::new(0),
+            consumed: AtomicU64::new(0),
+            sync: OnceLock::new(),
+            registers: Mutex::new(RegisterDelayLine::default()),
+            #[cfg(feature = "fx")]
+            fx: Mutex::new(None),
         }
     }
 
     /// Signal that a seek occurred - decoder should clear its local buffer
     pub fn notify_seek(&self) {
+        self.registers.lock().clear();
         self.seek_counter.fetch_add(1, Ordering::Release);
     }
 
+    /// Stereo frames the audio output has taken from the ring buffer.
+    ///
+    /// Counts only rendered audio, not the silence filled in on underruns,
+    /// so it advances exactly with the song as it is heard.
+    pub fn consumed_frames(&self) -> u64 {
+        self.consumed.load(Ordering::Acquire) / 2
+    }
+
+    /// Signal that enough audio is buffered to start playback.
+    pub fn mark_ready(&self) {
+        self.ready.store(true, Ordering::Release);
+        if let Some(member) = self.sync.get() {
+            member.mark_ready();
+        }
+    }
+
+    /// Join a [`SyncGroup`]; must happen before the output starts pulling.
+    ///
+    /// A stream can only be in one group; later calls are ignored.
+    pub fn join_sync_group(&self, group: &SyncGroup) {
+        if self.sync.get().is_none() && self.sync.set(group.join()).is_ok() && self.is_ready() {
+            self.mark_ready();
+        }
+    }
+
+    /// Whether this stream may play: always for ungrouped streams, otherwise
+    /// once every member of the group has audio buffered.
+    fn sync_released(&self) -> bool {
+        self.sync
+            .get()
+            .is_none_or(|member| member.poll(self.is_ready()))
+    }
+
+    /// PSG registers of the samples currently leaving the ring buffer.
+    ///
+    /// Lags the player by the buffered audio, so visuals built from it match
+    /// what is heard. `None` until the first batch reaches the output.
+    pub fn audible_registers(&self) -> Option<[u8; 16]> {
+        self.registers.lock().delayed().copied()
+    }
+
     pub fn set_stereo_gain(&self, left: f32, right: f32) {
         *self.stereo_gain.write() = (left, right);
     }
@@ -191,6 +255,17 @@ impl AudioStreamState {
         *self.tone_settings.write() = settings;
     }
 
+    /// Set the bus settings; the producer ramps toward them.
+    pub fn set_bus(&self, bus: AudioBus) {
+        *self.bus.write() = bus;
+    }
+
+    /// Replace the insert effects (an empty config bypasses them).
+    #[cfg(feature = "fx")]
+    pub fn set_fx(&self, config: &FxConfig) {
+        *self.fx.lock() = (!config.is_empty()).then(|| FxRack::new(config, YM2149_SAMPLE_RATE));
+    }
+
     /// Check if buffer is ready for playback
     pub fn is_ready(&self) -> bool {
         self.ready.load(Ordering::Acquire)
@@ -310,11 +385,78 @@ impl ToneFilter {
     }
 }
 
+/// Bus gain and low-pass, ramped per sample toward the bus settings.
+struct BusRamp {
+    target: AudioBus,
+    target_coefficient: f32,
+    gain: f32,
+    gain_step: f32,
+    coefficient: f32,
+    coefficient_step: f32,
+    /// Samples left in the current ramp
+    remaining: u32,
+    /// One-pole low-pass state
+    lowpass: f32,
+}
+
+impl BusRamp {
+    fn new() -> Self {
+        Self {
+            target: AudioBus::NEUTRAL,
+            target_coefficient: 1.0,
+            gain: 1.0,
+            gain_step: 0.0,
+            coefficient: 1.0,
+            coefficient_step: 0.0,
+            remaining: 0,
+            lowpass: 0.0,
+        }
+    }
+
+    /// Start ramping from the current values toward `bus`.
+    fn retarget(&mut self, bus: AudioBus) {
+        if bus == self.target {
+            return;
+        }
+        self.target = bus;
+        self.target_coefficient = bus.lowpass_coefficient(YM2149_SAMPLE_RATE);
+        let samples = bus.ramp_samples(YM2149_SAMPLE_RATE);
+        if samples == 0 {
+            self.gain = bus.effective_gain();
+            self.coefficient = self.target_coefficient;
+            self.remaining = 0;
+            return;
+        }
+        self.gain_step = (bus.effective_gain() - self.gain) / samples as f32;
+        self.coefficient_step = (self.target_coefficient - self.coefficient) / samples as f32;
+        self.remaining = samples;
+    }
+
+    fn process(&mut self, sample: f32) -> f32 {
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.remaining == 0 {
+                // Land exactly on the target despite rounding
+                self.gain = self.target.effective_gain();
+                self.coefficient = self.target_coefficient;
+            } else {
+                self.gain += self.gain_step;
+                self.coefficient += self.coefficient_step;
+            }
+        }
+        self.lowpass += self.coefficient * (sample - self.lowpass);
+        self.lowpass * self.gain
+    }
+}
+
 /// Producer loop that generates samples and writes them to the ring buffer.
 fn run_producer_loop(player: SharedSongPlayer, state: Arc<AudioStreamState>) {
     let mut mono_buffer = vec![0.0f32; SAMPLES_PER_BATCH];
     let mut stereo_buffer = vec![0.0f32; SAMPLES_PER_BATCH * 2];
+    #[cfg(feature = "fx")]
+    let mut channel_buffer = vec![[0.0f32; 3]; SAMPLES_PER_BATCH];
     let mut filter = ToneFilter::new();
+    let mut bus = BusRamp::new();
     let mut marked_ready = false;
 
     // Start playback
@@ -324,23 +466,51 @@ fn run_producer_loop(player: SharedSongPlayer, state: Arc<AudioStreamState>) {
     }
 
     while state.running.load(Ordering::Acquire) {
+        #[cfg(feature = "fx")]
+        let mut fx = state.fx.lock();
+        #[cfg(feature = "fx")]
+        let channel_fx = fx.as_ref().is_some_and(FxRack::has_channel_effects);
+
         // Generate mono samples
-        {
+        let registers = {
             let mut player_guard = player.write();
+            #[cfg(feature = "fx")]
+            if channel_fx {
+                player_guard.generate_samples_with_channels(&mut mono_buffer, &mut channel_buffer);
+            } else {
+                player_guard.generate_samples_into(&mut mono_buffer);
+            }
+            #[cfg(not(feature = "fx"))]
             player_guard.generate_samples_into(&mut mono_buffer);
+            player_guard.dump_registers()
+        };
+        if let Some(registers) = registers {
+            // Everything already buffered plays before this batch
+            let mut delay = state.registers.lock();
+            delay.set_latency(state.buffer.available_read() / 2);
+            delay.push(SAMPLES_PER_BATCH, registers);
         }
 
         // Read current settings
         let (left_gain, right_gain) = *state.stereo_gain.read();
         let tone_settings = *state.tone_settings.read();
+        bus.retarget(*state.bus.read());
 
         // Convert to stereo with tone processing
         for (i, &mono_sample) in mono_buffer.iter().enumerate() {
             let processed = filter.process(mono_sample, &tone_settings);
+            #[cfg(feature = "fx")]
+            let processed = match fx.as_mut() {
+                Some(rack) => rack.process(processed, channel_buffer[i]),
+                None => processed,
+            };
+            let processed = bus.process(processed);
             let width = tone_settings.widen.clamp(-0.5, 0.5);
             stereo_buffer[i * 2] = processed * (left_gain + width);
             stereo_buffer[i * 2 + 1] = processed * (right_gain - width);
         }
+        #[cfg(feature = "fx")]
+        drop(fx);
 
         // Write to ring buffer with backpressure
         let mut written = 0;
@@ -356,7 +526,7 @@ fn run_producer_loop(player: SharedSongPlayer, state: Arc<AudioStreamState>) {
 
         // Mark ready once buffer is sufficiently filled
         if !marked_ready && state.buffer.fill_percentage() >= MIN_BUFFER_FILL {
-            state.ready.store(true, Ordering::Release);
+            state.mark_ready();
             marked_ready = true;
         }
     }
@@ -380,12 +550,18 @@ pub struct StreamingDecoder {
     local_pos: usize,
     /// Last observed seek counter to detect when a seek occurred
     last_seek_counter: usize,
+    /// Silent samples output while waiting for the sync group to start
+    /// (`None` once playing or when not grouped)
+    sync_wait: Option<usize>,
+    /// Silence emitted while the group played, skipped once audio returns
+    sync_debt: usize,
 }
 
 impl StreamingDecoder {
     /// Create a new streaming decoder.
     pub fn new(state: Arc<AudioStreamState>, sample_rate: u32, total_samples: usize) -> Self {
         let last_seek_counter = state.seek_counter.load(Ordering::Acquire);
+        let sync_wait = state.sync.get().map(|_| 0);
         Self {
             state,
             sample_rate,
@@ -394,6 +570,8 @@ impl StreamingDecoder {
             local_buffer: Vec::new(),
             local_pos: 0,
             last_seek_counter,
+            sync_wait,
+            sync_debt: 0,
         }
     }
 
@@ -404,6 +582,7 @@ impl StreamingDecoder {
             self.last_seek_counter = current;
             self.local_buffer.clear();
             self.local_pos = 0;
+            self.sync_debt = 0;
         }
     }
 
@@ -414,7 +593,30 @@ impl StreamingDecoder {
             self.local_buffer.resize(LOCAL_BATCH, 0.0);
         }
 
-        let read = self.state.buffer.read(&mut self.local_buffer);
+        // A grouped stream that underran skips the audio it missed to get
+        // back in phase with the other members
+        let mut skipped = 0;
+        while self.sync_debt > 0 {
+            let chunk = self.sync_debt.min(LOCAL_BATCH);
+            let count = self.state.buffer.read(&mut self.local_buffer[..chunk]);
+            if count == 0 {
+                break;
+            }
+            self.sync_debt -= count;
+            skipped += count;
+        }
+
+        let read = if self.sync_debt == 0 {
+            self.state.buffer.read(&mut self.local_buffer)
+        } else {
+            0
+        };
+        self.state
+            .consumed
+            .fetch_add((skipped + read) as u64, Ordering::Release);
+        if self.state.sync.get().is_some() {
+            self.sync_debt += LOCAL_BATCH - read;
+        }
 
         // If we didn't get enough samples, fill remainder with silence
         for sample in self.local_buffer[read..].iter_mut() {
@@ -434,6 +636,17 @@ impl Iterator for StreamingDecoder {
             return None;
         }
 
+        // Grouped streams stay silent until every member can start; the
+        // gate only opens on a left sample so channels stay in order
+        if let Some(waited) = self.sync_wait {
+            if waited.is_multiple_of(2) && self.state.sync_released() {
+                self.sync_wait = None;
+            } else {
+                self.sync_wait = Some(waited + 1);
+                return Some(0.0);
+            }
+        }
+
         // Check if a seek occurred - clear local buffer if so
         self.check_seek();
 
@@ -534,4 +747,126 @@ mod tests {
         assert!((left - 0.5).abs() < 0.001);
         assert!((right - 0.8).abs() < 0.001);
     }
+
+    #[test]
+    fn test_audible_registers_lag_buffered_audio() {
+        let state = AudioStreamState::new();
+        assert_eq!(state.audible_registers(), None);
+
+        {
+            let mut delay = state.registers.lock();
+            delay.set_latency(SAMPLES_PER_BATCH);
+            delay.push(SAMPLES_PER_BATCH, [1; 16]);
+            delay.push(SAMPLES_PER_BATCH, [2; 16]);
+        }
+        assert_eq!(state.audible_registers(), Some([1; 16]));
+
+        state.notify_seek();
+        assert_eq!(state.audible_registers(), None);
+    }
+
+    #[test]
+    fn test_decoder_counts_consumed_frames() {
+        let state = Arc::new(AudioStreamState::new());
+        state.buffer.write(&[0.25; 600]);
+        let mut decoder = StreamingDecoder::new(Arc::clone(&state), YM2149_SAMPLE_RATE, 0);
+        assert_eq!(state.consumed_frames(), 0);
+
+        // The first sample pulls a whole batch; the silence padding after
+        // the 600 buffered samples is not counted
+        assert_eq!(decoder.next(), Some(0.25));
+        assert_eq!(state.consumed_frames(), 300);
+    }
+
+    #[test]
+    fn test_grouped_decoders_start_together_and_skip_missed_audio() {
+        let group = SyncGroup::new();
+        let a = Arc::new(AudioStreamState::new());
+        let b = Arc::new(AudioStreamState::new());
+        a.join_sync_group(&group);
+        b.join_sync_group(&group);
+        let mut decoder_a = StreamingDecoder::new(Arc::clone(&a), YM2149_SAMPLE_RATE, 0);
+        let mut decoder_b = StreamingDecoder::new(Arc::clone(&b), YM2149_SAMPLE_RATE, 0);
+
+        a.buffer.write(&[0.5; 2048]);
+        a.mark_ready();
+        for _ in 0..4 {
+            assert_eq!(decoder_a.next(), Some(0.0));
+            assert_eq!(decoder_b.next(), Some(0.0));
+        }
+        assert_eq!(a.consumed_frames(), 0);
+
+        b.buffer.write(&[0.25; 1024]);
+        b.mark_ready();
+        assert_eq!(decoder_a.next(), Some(0.5));
+        assert_eq!(decoder_b.next(), Some(0.25));
+        for _ in 1..1024 {
+            decoder_a.next();
+            decoder_b.next();
+        }
+
+        // B underruns for a batch while A keeps playing
+        for _ in 0..1024 {
+            assert_eq!(decoder_a.next(), Some(0.5));
+            assert_eq!(decoder_b.next(), Some(0.0));
+        }
+
+        // Once audio returns, B drops the batch it missed
+        let mut late = vec![0.75; 1024];
+        late.extend([0.125; 1024]);
+        b.buffer.write(&late);
+        assert_eq!(decoder_b.next(), Some(0.125));
+        assert_eq!(b.consumed_frames(), 3 * 1024 / 2);
+    }
+
+    #[test]
+    fn test_bus_ramp_glides_to_target() {
+        let mut ramp = BusRamp::new();
+        assert_eq!(ramp.process(0.5), 0.5);
+
+        let bus = AudioBus::NEUTRAL.with_gain(0.0).with_ramp(0.01);
+        ramp.retarget(bus);
+        let samples = bus.ramp_samples(YM2149_SAMPLE_RATE);
+        let first = ramp.process(0.5);
+        assert!(first > 0.49 && first < 0.5, "no jump at ramp start");
+        for _ in 1..samples {
+            ramp.process(0.5);
+        }
+        assert_eq!(ramp.process(0.5), 0.0);
+
+        // Immediate changes skip the ramp
+        ramp.retarget(AudioBus::NEUTRAL.with_ramp(0.0));
+        assert_eq!(ramp.process(0.5), 0.5);
+    }
+
+    #[test]
+    fn test_bus_ramp_lowpass_smooths_steps() {
+        let mut ramp = BusRamp::new();
+        ramp.retarget(AudioBus::NEUTRAL.with_lowpass(500.0).with_ramp(0.0));
+        let first = ramp.process(1.0);
+        assert!(first > 0.0 && first < 0.1);
+        let later = (0..200).map(|_| ramp.process(1.0)).last().unwrap();
+        assert!(later > first && later <= 1.0);
+    }
+
+    #[cfg(feature = "fx")]
+    #[test]
+    fn test_audio_stream_state_fx() {
+        let state = AudioStreamState::new();
+        assert!(state.fx.lock().is_none());
+
+        let mut config = FxConfig::default();
+        config.apply_spec("a=chorus").unwrap();
+        state.set_fx(&config);
+        assert!(
+            state
+                .fx
+                .lock()
+                .as_ref()
+                .is_some_and(FxRack::has_channel_effects)
+        );
+
+        state.set_fx(&FxConfig::default());
+        assert!(state.fx.lock().is_none());
+    }
 }
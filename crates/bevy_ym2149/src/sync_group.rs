@@ -0,0 +1,160 @@
+//! Phase-locked start of several playbacks.
+//!
+//! Playbacks sharing a [`SyncGroup`] hold their audio output silent until
+//! every member has audio buffered, then all start on the same audio
+//! callback. Afterwards each member makes up for its own underruns by
+//! skipping the audio it missed, so layers stay aligned to the frame.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_ym2149::{SyncGroup, Ym2149Playback};
+//!
+//! fn spawn_layers(mut commands: Commands) {
+//!     let group = SyncGroup::new();
+//!     for layer in ["music/drums.ym", "music/bass.ym", "music/lead.ym"] {
+//!         let mut playback = Ym2149Playback::new(layer).with_sync_group(&group);
+//!         playback.play();
+//!         commands.spawn(playback);
+//!     }
+//! }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Shared start gate for playbacks that must play in lockstep.
+///
+/// Cheap to clone; clones refer to the same group. A playback joins when
+/// its audio stream is created, so set the group before the playback loads
+/// (members joining after the group started play right away). Crossfaded
+/// decks do not join.
+#[derive(Clone, Default)]
+pub struct SyncGroup {
+    shared: Arc<SyncShared>,
+}
+
+#[derive(Default)]
+struct SyncShared {
+    members: AtomicUsize,
+    ready: AtomicUsize,
+    started: AtomicBool,
+}
+
+impl SyncGroup {
+    /// Create an empty group.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of audio streams currently in the group.
+    pub fn member_count(&self) -> usize {
+        self.shared.members.load(Ordering::Acquire)
+    }
+
+    /// Whether the members have been released.
+    pub fn is_started(&self) -> bool {
+        self.shared.started.load(Ordering::Acquire)
+    }
+
+    /// Whether two handles refer to the same group.
+    pub fn ptr_eq(&self, other: &SyncGroup) -> bool {
+        Arc::ptr_eq(&self.shared, &other.shared)
+    }
+
+    pub(crate) fn join(&self) -> SyncMember {
+        self.shared.members.fetch_add(1, Ordering::AcqRel);
+        SyncMember {
+            shared: Arc::clone(&self.shared),
+            ready: AtomicBool::new(false),
+        }
+    }
+}
+
+impl fmt::Debug for SyncGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SyncGroup")
+            .field("members", &self.member_count())
+            .field("started", &self.is_started())
+            .finish()
+    }
+}
+
+/// Membership of one audio stream; leaves the group when dropped.
+pub(crate) struct SyncMember {
+    shared: Arc<SyncShared>,
+    ready: AtomicBool,
+}
+
+impl SyncMember {
+    /// Count this member as having audio buffered.
+    pub(crate) fn mark_ready(&self) {
+        if !self.ready.swap(true, Ordering::AcqRel) {
+            self.shared.ready.fetch_add(1, Ordering::AcqRel);
+        }
+    }
+
+    /// Returns whether the group has started and the member may play.
+    ///
+    /// `has_audio` marks the member ready in case the producer signalled
+    /// before the stream joined.
+    pub(crate) fn poll(&self, has_audio: bool) -> bool {
+        let shared = &self.shared;
+        if shared.started.load(Ordering::Acquire) {
+            return true;
+        }
+        if has_audio {
+            self.mark_ready();
+        }
+        if shared.ready.load(Ordering::Acquire) >= shared.members.load(Ordering::Acquire) {
+            shared.started.store(true, Ordering::Release);
+            return true;
+        }
+        false
+    }
+}
+
+impl Drop for SyncMember {
+    fn drop(&mut self) {
+        if self.ready.load(Ordering::Acquire) {
+            self.shared.ready.fetch_sub(1, Ordering::AcqRel);
+        }
+        self.shared.members.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_starts_when_all_members_ready() {
+        let group = SyncGroup::new();
+        let a = group.join();
+        let b = group.join();
+        assert_eq!(group.member_count(), 2);
+
+        assert!(!a.poll(true));
+        assert!(!b.poll(false));
+        assert!(!a.poll(true));
+        assert!(b.poll(true));
+        assert!(a.poll(false));
+        assert!(group.is_started());
+
+        // Late members play right away
+        let c = group.join();
+        assert!(c.poll(false));
+    }
+
+    #[test]
+    fn test_leaving_member_releases_the_rest() {
+        let group = SyncGroup::new();
+        let a = group.join();
+        let b = group.join();
+        assert!(!a.poll(true));
+
+        drop(b);
+        assert_eq!(group.member_count(), 1);
+        assert!(a.poll(true));
+    }
+}
@@ -1,13 +1,27 @@
 //! Software synthesizer using the YM2149 chip emulator.
 //!
 //! This module provides direct register-level access to the YM2149 for creating
-//! custom sound effects and programmatic audio.
+//! custom sound effects and programmatic audio, plus a note scheduler that
+//! plays [`SynthNote`]s at exact times on the synth's audio clock:
+//!
+//! ```
+//! # use bevy_ym2149::synth::{SynthInstrument, SynthNote, YmSynthController};
+//! let synth = YmSynthController::new();
+//! let start = synth.audio_time() + 0.1;
+//! for (step, note) in [60, 64, 67, 72].into_iter().enumerate() {
+//!     synth.schedule_note(
+//!         SynthNote::midi(0, note, start + step as f64 * 0.125, 0.1)
+//!             .with_instrument(SynthInstrument::PLUCK),
+//!     );
+//! }
+//! ```
 
 use crate::audio_source::Ym2149Metadata;
 use crate::playback::{PlaybackMetrics, YM2149_SAMPLE_RATE};
 use parking_lot::RwLock;
 use std::sync::Arc;
 use ym2149::{Ym2149, Ym2149Backend};
+use ym2149_common::{frequency_to_period, midi_note_frequency};
 use ym2149_ym_replayer::PlaybackState as YmPlaybackState;
 
 const DEFAULT_SAMPLES_PER_FRAME: u32 = YM2149_SAMPLE_RATE / 50;
@@ -16,6 +30,160 @@ const DEFAULT_SAMPLES_PER_FRAME: u32 = YM2149_SAMPLE_RATE / 50;
 struct SynthState {
     registers: [u8; 16],
     dirty_mask: u16,
+    scheduler: NoteScheduler,
+}
+
+impl SynthState {
+    fn write(&mut self, index: usize, value: u8) {
+        if self.registers[index] != value {
+            self.registers[index] = value;
+            self.dirty_mask |= 1 << index;
+        }
+    }
+}
+
+/// Volume shape and mixer setup of a scheduled note.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SynthInstrument {
+    /// Volume at note start (0-15).
+    pub volume: u8,
+    /// Volume lost per 50Hz frame (0 = hold).
+    pub decay: u8,
+    /// Volume at which the decay stops while the note is held.
+    pub sustain: u8,
+    /// Enable the tone generator.
+    pub tone: bool,
+    /// Enable the noise generator with this noise period (0-31).
+    pub noise: Option<u8>,
+    /// Drive the volume from the hardware envelope as `(shape, period)`
+    /// instead of the software volume.
+    pub envelope: Option<(u8, u16)>,
+}
+
+impl SynthInstrument {
+    /// Square wave held at full volume.
+    pub const SQUARE: Self = Self {
+        volume: 15,
+        decay: 0,
+        sustain: 15,
+        tone: true,
+        noise: None,
+        envelope: None,
+    };
+    /// Plucked square wave decaying to silence.
+    pub const PLUCK: Self = Self {
+        decay: 1,
+        sustain: 0,
+        ..Self::SQUARE
+    };
+    /// Square wave with a short drop to a softer sustain level.
+    pub const LEAD: Self = Self {
+        decay: 1,
+        sustain: 11,
+        ..Self::SQUARE
+    };
+    /// Bell-like tone using the single-shot hardware decay envelope.
+    pub const BELL: Self = Self {
+        envelope: Some((0x00, 0x0800)),
+        ..Self::SQUARE
+    };
+    /// Noise burst with a tone body, for kicks and toms.
+    pub const DRUM: Self = Self {
+        decay: 2,
+        sustain: 0,
+        noise: Some(12),
+        ..Self::SQUARE
+    };
+    /// Short bright noise burst.
+    pub const HIHAT: Self = Self {
+        volume: 12,
+        decay: 4,
+        sustain: 0,
+        tone: false,
+        noise: Some(1),
+        envelope: None,
+    };
+}
+
+impl Default for SynthInstrument {
+    fn default() -> Self {
+        Self::SQUARE
+    }
+}
+
+/// A note for [`YmSynthController::schedule_note`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SynthNote {
+    /// Channel to play on (0-2).
+    pub channel: usize,
+    /// Pitch in Hz.
+    pub frequency: f32,
+    /// Start time in seconds on the synth's audio clock
+    /// (see [`YmSynthController::audio_time`]).
+    pub start: f64,
+    /// Length in seconds.
+    pub duration: f64,
+    /// Volume shape and mixer setup.
+    pub instrument: SynthInstrument,
+}
+
+impl SynthNote {
+    /// Note of `frequency` Hz played with [`SynthInstrument::SQUARE`].
+    pub fn new(channel: usize, frequency: f32, start: f64, duration: f64) -> Self {
+        Self {
+            channel,
+            frequency,
+            start,
+            duration,
+            instrument: SynthInstrument::SQUARE,
+        }
+    }
+
+    /// Note given as a MIDI note number (60 = middle C).
+    pub fn midi(channel: usize, note: u8, start: f64, duration: f64) -> Self {
+        Self::new(channel, midi_note_frequency(note), start, duration)
+    }
+
+    /// Play the note with `instrument`.
+    pub fn with_instrument(mut self, instrument: SynthInstrument) -> Self {
+        self.instrument = instrument;
+        self
+    }
+}
+
+/// Note sounding on a channel.
+#[derive(Clone, Copy)]
+struct Voice {
+    instrument: SynthInstrument,
+    level: u8,
+    end: u64,
+    next_step: u64,
+}
+
+/// Scheduled notes and voices, advanced by the player one sample at a time.
+#[derive(Default)]
+struct NoteScheduler {
+    /// Samples generated so far (the audio clock)
+    clock: u64,
+    /// Pending notes as `(start, end, note)`, latest first so `pop` is next
+    pending: Vec<(u64, u64, SynthNote)>,
+    voices: [Option<Voice>; 3],
+    /// Clock value of the next start, end or decay step
+    next_event: u64,
+}
+
+impl NoteScheduler {
+    fn schedule(&mut self, note: SynthNote) {
+        if note.channel > 2 {
+            return;
+        }
+        let rate = f64::from(YM2149_SAMPLE_RATE);
+        let start = (note.start.max(0.0) * rate) as u64;
+        let end = start + ((note.duration.max(0.0) * rate) as u64).max(1);
+        let index = self.pending.partition_point(|(s, _, _)| *s > start);
+        self.pending.insert(index, (start, end, note));
+        self.next_event = self.next_event.min(start);
+    }
 }
 
 /// Thread-safe controller that allows direct writes to the PSG registers.
@@ -32,23 +200,80 @@ impl YmSynthController {
         Self::default()
     }
 
-    /// Reset all registers to zero (silence).
+    /// Reset all registers to zero (silence) and drop scheduled notes.
     pub fn reset(&self) {
         let mut state = self.inner.write();
         state.registers = [0; 16];
         state.dirty_mask = u16::MAX;
+        state.scheduler.pending.clear();
+        state.scheduler.voices = [None; 3];
     }
 
     /// Write a value to a YM2149 register (0x00-0x0F).
     pub fn write_register(&self, addr: u8, value: u8) {
-        let index = (addr & 0x0F) as usize;
+        self.inner.write().write((addr & 0x0F) as usize, value);
+    }
+
+    /// Current time of the synth's audio clock in seconds.
+    ///
+    /// The clock advances with generated samples, so it stops while the
+    /// synth is paused and runs ahead of the speakers by the stream buffer.
+    pub fn audio_time(&self) -> f64 {
+        self.inner.read().scheduler.clock as f64 / f64::from(YM2149_SAMPLE_RATE)
+    }
+
+    /// Play `note` at its start time; notes already due start immediately.
+    ///
+    /// A note takes over its channel's tone, mixer and volume registers (and
+    /// the shared noise/envelope registers if its instrument uses them) until
+    /// it ends, replacing any note still sounding there.
+    pub fn schedule_note(&self, note: SynthNote) {
+        self.inner.write().scheduler.schedule(note);
+    }
+
+    /// Schedule several notes at once.
+    pub fn schedule_notes(&self, notes: impl IntoIterator<Item = SynthNote>) {
+        let mut state = self.inner.write();
+        for note in notes {
+            state.scheduler.schedule(note);
+        }
+    }
+
+    /// Drop pending notes and silence notes that are sounding.
+    pub fn clear_schedule(&self) {
         let mut state = self.inner.write();
-        if state.registers[index] != value {
-            state.registers[index] = value;
-            state.dirty_mask |= 1 << index;
+        state.scheduler.pending.clear();
+        for channel in 0..3 {
+            if state.scheduler.voices[channel].take().is_some() {
+                release_channel(&mut state, channel);
+            }
         }
     }
 
+    /// Number of scheduled notes that have not started yet.
+    pub fn pending_notes(&self) -> usize {
+        self.inner.read().scheduler.pending.len()
+    }
+
+    /// Advance the audio clock by one sample and flush changed registers to `chip`.
+    pub(crate) fn sync_sample<B: Ym2149Backend>(&self, chip: &mut B) {
+        let mut state = self.inner.write();
+        if state.scheduler.clock >= state.scheduler.next_event {
+            run_scheduler(&mut state);
+        }
+        state.scheduler.clock += 1;
+        let mask = state.dirty_mask;
+        if mask == 0 {
+            return;
+        }
+        for idx in 0..16 {
+            if (mask & (1 << idx)) != 0 {
+                chip.write_register(idx as u8, state.registers[idx]);
+            }
+        }
+        state.dirty_mask = 0;
+    }
+
     /// Set the tone period for a channel (0-2). Lower values = higher pitch.
     pub fn set_tone_period(&self, channel: usize, period: u16) {
         if channel > 2 {
@@ -142,6 +367,7 @@ impl YmSynthPlayer {
             title: "Live PSG Synth".into(),
             author: "bevy_ym2149".into(),
             comment: "Procedural YM2149 source".into(),
+            format: "Synth".into(),
             frame_count: u32::MAX as usize,
             duration_seconds: 0.0,
         };
@@ -242,17 +468,7 @@ impl YmSynthPlayer {
     }
 
     fn sync_registers(&mut self) {
-        let mut state = self.shared.controller.inner.write();
-        let mask = state.dirty_mask;
-        if mask == 0 {
-            return;
-        }
-        for idx in 0..16 {
-            if (mask & (1 << idx)) != 0 {
-                self.chip.write_register(idx as u8, state.registers[idx]);
-            }
-        }
-        state.dirty_mask = 0;
+        self.shared.controller.sync_sample(&mut self.chip);
     }
 
     fn advance_frame_counter(&mut self) {
@@ -263,3 +479,148 @@ impl YmSynthPlayer {
         }
     }
 }
+
+/// Start due notes, step decays and end finished notes.
+fn run_scheduler(state: &mut SynthState) {
+    let now = state.scheduler.clock;
+    while let Some(&(start, end, note)) = state.scheduler.pending.last() {
+        if start > now {
+            break;
+        }
+        state.scheduler.pending.pop();
+        start_note(state, end, &note);
+    }
+
+    for channel in 0..3 {
+        let Some(mut voice) = state.scheduler.voices[channel] else {
+            continue;
+        };
+        if now >= voice.end {
+            state.scheduler.voices[channel] = None;
+            release_channel(state, channel);
+            continue;
+        }
+        if voice.instrument.envelope.is_none() && now >= voice.next_step {
+            voice.level = voice
+                .level
+                .saturating_sub(voice.instrument.decay)
+                .max(voice.instrument.sustain.min(voice.level));
+            voice.next_step = now + u64::from(DEFAULT_SAMPLES_PER_FRAME);
+            state.write(0x08 + channel, voice.level);
+            state.scheduler.voices[channel] = Some(voice);
+        }
+    }
+
+    let scheduler = &mut state.scheduler;
+    let mut next = scheduler
+        .pending
+        .last()
+        .map_or(u64::MAX, |&(start, _, _)| start);
+    for voice in scheduler.voices.iter().flatten() {
+        next = next.min(voice.end);
+        let decaying = voice.instrument.decay > 0 && voice.level > voice.instrument.sustain;
+        if decaying && voice.instrument.envelope.is_none() {
+            next = next.min(voice.next_step);
+        }
+    }
+    scheduler.next_event = next;
+}
+
+fn start_note(state: &mut SynthState, end: u64, note: &SynthNote) {
+    let channel = note.channel;
+    let instrument = note.instrument;
+    let period = frequency_to_period(note.frequency);
+    state.write(channel * 2, (period & 0xFF) as u8);
+    state.write(channel * 2 + 1, (period >> 8) as u8);
+
+    // Mixer bits are active-low
+    let mut mixer = state.registers[0x07] | (0x09 << channel);
+    if instrument.tone {
+        mixer &= !(1 << channel);
+    }
+    if let Some(noise) = instrument.noise {
+        mixer &= !(0x08 << channel);
+        state.write(0x06, noise & 0x1F);
+    }
+    state.write(0x07, mixer);
+
+    let level = instrument.volume.min(15);
+    match instrument.envelope {
+        Some((shape, envelope_period)) => {
+            state.write(0x08 + channel, 0x10);
+            state.write(0x0B, (envelope_period & 0xFF) as u8);
+            state.write(0x0C, (envelope_period >> 8) as u8);
+            // Rewriting the shape restarts the envelope even when unchanged
+            state.registers[0x0D] = shape & 0x0F;
+            state.dirty_mask |= 1 << 0x0D;
+        }
+        None => state.write(0x08 + channel, level),
+    }
+
+    state.scheduler.voices[channel] = Some(Voice {
+        instrument,
+        level,
+        end,
+        next_step: state.scheduler.clock + u64::from(DEFAULT_SAMPLES_PER_FRAME),
+    });
+}
+
+/// Silence a channel and disconnect it from the mixer.
+fn release_channel(state: &mut SynthState, channel: usize) {
+    state.write(0x08 + channel, 0);
+    let mixer = state.registers[0x07] | (0x09 << channel);
+    state.write(0x07, mixer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(player: &mut YmSynthPlayer, seconds: f64) {
+        let mut buffer = vec![0.0; (seconds * f64::from(YM2149_SAMPLE_RATE)) as usize];
+        player.generate_samples_into(&mut buffer);
+    }
+
+    #[test]
+    fn scheduled_note_plays_on_the_audio_clock() {
+        let controller = YmSynthController::new();
+        let mut player = YmSynthPlayer::new(controller.clone());
+        player.play();
+        controller.schedule_note(SynthNote::midi(1, 69, 0.1, 0.2));
+        assert_eq!(controller.pending_notes(), 1);
+
+        render(&mut player, 0.05);
+        assert_eq!(player.chip().dump_registers()[0x09], 0);
+
+        render(&mut player, 0.1);
+        let regs = player.chip().dump_registers();
+        assert_eq!(controller.pending_notes(), 0);
+        assert_eq!(regs[0x09], 15);
+        // 2MHz / (16 * 440Hz) = 284
+        assert_eq!(u16::from(regs[0x02]) | (u16::from(regs[0x03]) << 8), 284);
+        assert_eq!(regs[0x07] & 0x12, 0x10, "tone on, noise off for channel B");
+
+        render(&mut player, 0.2);
+        assert_eq!(player.chip().dump_registers()[0x09], 0);
+        assert!((controller.audio_time() - 0.35).abs() < 0.001);
+    }
+
+    #[test]
+    fn instrument_decays_per_frame() {
+        let controller = YmSynthController::new();
+        let mut player = YmSynthPlayer::new(controller.clone());
+        player.play();
+        controller.schedule_note(
+            SynthNote::new(0, 220.0, 0.0, 1.0).with_instrument(SynthInstrument::LEAD),
+        );
+
+        render(&mut player, 0.01);
+        assert_eq!(player.chip().dump_registers()[0x08], 15);
+        render(&mut player, 0.2);
+        assert_eq!(player.chip().dump_registers()[0x08], 11, "holds at sustain");
+
+        controller.clear_schedule();
+        render(&mut player, 0.01);
+        assert_eq!(player.chip().dump_registers()[0x08], 0);
+    }
+}
@@ -7,8 +7,9 @@ use std::sync::Arc;
 
 use bevy::prelude::*;
 use bevy_ym2149::{
-    AudioBridgeBuffers, AudioBridgeRequest, PlaybackState, Ym2149AudioSource, Ym2149Playback,
-    Ym2149Plugin, Ym2149Settings,
+    AudioBridgeBuffers, AudioBridgeRequest, ChipBackend, PlaybackState, SongFormat,
+    Ym2149AudioSource, Ym2149Playback, Ym2149Plugin, Ym2149PluginConfig, Ym2149Settings,
+    Ym2149SongBytes,
 };
 
 /// Helper to create a minimal test app with YM2149 plugin
@@ -553,6 +554,44 @@ fn test_multiple_playbacks_independent() {
     assert_eq!(pb2_state, PlaybackState::Paused, "Entity2 should be paused");
 }
 
+#[test]
+fn test_softsynth_chip_backend() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        bevy::asset::AssetPlugin::default(),
+        bevy::audio::AudioPlugin::default(),
+        Ym2149Plugin::with_config(Ym2149PluginConfig {
+            chip: ChipBackend::SoftSynth,
+            ..Default::default()
+        }),
+    ));
+
+    let entity = app
+        .world_mut()
+        .spawn(Ym2149Playback::from_bytes(create_minimal_ym_file()))
+        .id();
+    app.update();
+
+    let playback = app.world().entity(entity).get::<Ym2149Playback>().unwrap();
+    assert!(!playback.song_title.is_empty() || !playback.song_author.is_empty());
+    let player = playback
+        .player_handle()
+        .expect("player should be initialized");
+    let player = player.read();
+    assert!(
+        player.chip().is_none(),
+        "SoftSynth playback should not expose the hardware chip"
+    );
+    assert!(player.dump_registers().is_some());
+
+    let hardware = Ym2149AudioSource::new(create_minimal_ym_file()).unwrap();
+    assert!(hardware.player().read().chip().is_some());
+    let soft =
+        Ym2149AudioSource::new_with_chip(create_minimal_ym_file(), ChipBackend::SoftSynth).unwrap();
+    assert!(soft.player().read().chip().is_none());
+}
+
 #[test]
 fn test_metadata_extraction() {
     let mut app = create_test_app();
@@ -573,3 +612,126 @@ fn test_metadata_extraction() {
         "Metadata should be extracted from YM file"
     );
 }
+
+#[test]
+fn test_asset_loaders_for_each_format() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        bevy::asset::AssetPlugin {
+            file_path: "../bevy_ym2149_examples/assets".into(),
+            ..Default::default()
+        },
+        bevy::audio::AudioPlugin::default(),
+        Ym2149Plugin::default(),
+    ));
+
+    let server = app.world().resource::<AssetServer>().clone();
+    let handles: Vec<(Handle<Ym2149AudioSource>, &str)> = vec![
+        (server.load("music/Ashtray.ym"), "YM"),
+        (server.load("music/Andy Severn - Lop Ears.aks"), "AKS"),
+        (server.load("music/Wings_Of_Death.sndh"), "SNDH"),
+    ];
+
+    for _ in 0..500 {
+        app.update();
+        if handles
+            .iter()
+            .all(|(handle, _)| server.is_loaded_with_dependencies(handle))
+        {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let assets = app.world().resource::<Assets<Ym2149AudioSource>>();
+    for (handle, format) in &handles {
+        let source = assets.get(handle).expect("song asset should load");
+        assert_eq!(source.metadata().format, *format);
+    }
+}
+
+#[test]
+fn test_audio_source_with_explicit_format() {
+    let source = Ym2149AudioSource::new_with_format(
+        create_minimal_ym_file(),
+        SongFormat::Ym,
+        ChipBackend::Hardware,
+    )
+    .expect("YM data should load as YM");
+    assert_eq!(source.metadata().format, "YM");
+
+    assert!(
+        Ym2149AudioSource::new_with_format(
+            create_minimal_ym_file(),
+            SongFormat::Sndh,
+            ChipBackend::Hardware
+        )
+        .is_err(),
+        "YM data must not load when SNDH is requested"
+    );
+    assert_eq!(SongFormat::from_extension("SNDH"), Some(SongFormat::Sndh));
+    assert_eq!(SongFormat::from_extension("wav"), None);
+}
+
+#[test]
+fn test_playback_from_song_bytes_asset() {
+    let mut app = create_test_app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<Ym2149SongBytes>>()
+        .add(Ym2149SongBytes::new(create_minimal_ym_file()));
+
+    let entity = app
+        .world_mut()
+        .spawn(Ym2149Playback::from_song_bytes(handle))
+        .id();
+
+    app.update();
+
+    let playback = app.world().entity(entity).get::<Ym2149Playback>().unwrap();
+    assert!(
+        playback.player_handle().is_some(),
+        "player should load from the song bytes asset"
+    );
+    assert!(!playback.song_title.is_empty() || !playback.song_author.is_empty());
+}
+
+#[test]
+fn test_hot_reload_resumes_at_current_frame() {
+    let mut app = create_test_app();
+    let handle = app
+        .world_mut()
+        .resource_mut::<Assets<Ym2149SongBytes>>()
+        .add(Ym2149SongBytes::new(create_minimal_ym_file()));
+
+    let entity = app
+        .world_mut()
+        .spawn(Ym2149Playback::from_song_bytes(handle.clone()))
+        .id();
+    app.update();
+
+    // Replacing the asset emits the same Modified event as a file change;
+    // it is delivered after this update's Update schedule.
+    app.world_mut()
+        .resource_mut::<Assets<Ym2149SongBytes>>()
+        .insert(&handle, Ym2149SongBytes::new(create_minimal_ym_file()))
+        .unwrap();
+    app.update();
+
+    let old_player = {
+        let mut playback = app
+            .world_mut()
+            .entity_mut(entity)
+            .into_mut::<Ym2149Playback>()
+            .unwrap();
+        playback.seek(100);
+        playback.player_handle().unwrap()
+    };
+    app.update();
+
+    let playback = app.world().entity(entity).get::<Ym2149Playback>().unwrap();
+    let new_player = playback.player_handle().expect("player should reload");
+    assert!(!Arc::ptr_eq(&old_player, &new_player));
+    assert!(playback.frame_position() >= 100);
+}
@@ -2,10 +2,14 @@ use bevy::asset::AssetPlugin;
 use bevy::diagnostic::{DiagnosticsPlugin, DiagnosticsStore};
 use bevy::prelude::Messages;
 use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy_ym2149::lifecycle::track_window_focus;
 use bevy_ym2149::{
-    FRAME_POSITION_PATH, MusicStateDefinition, MusicStateGraph, MusicStateRequest, PlaylistMode,
-    PlaylistSource, TrackFinished, Ym2149Playback, Ym2149Playlist, Ym2149PlaylistPlayer,
-    Ym2149PluginConfig, advance_playlist_players, process_music_state_requests, update_diagnostics,
+    FRAME_POSITION_PATH, FocusLoss, MusicStateDefinition, MusicStateGraph, MusicStateRequest,
+    MusicTransition, PendingMusicTransition, PlayInState, PlaybackState, PlaylistMode,
+    PlaylistSource, TrackFinished, TransitionQuantize, Ym2149Focus, Ym2149Playback, Ym2149Playlist,
+    Ym2149PlaylistPlayer, Ym2149PluginConfig, Ym2149StateScopePlugin, advance_music_transitions,
+    advance_playlist_players, process_music_state_requests, update_diagnostics,
 };
 
 #[test]
@@ -80,6 +84,127 @@ fn music_state_request_switches_source_path() {
     assert_eq!(playback.state, bevy_ym2149::PlaybackState::Playing);
 }
 
+fn music_transition_app() -> (App, Entity) {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+    app.add_message::<MusicStateRequest>();
+    app.insert_resource(MusicStateGraph::default());
+    app.insert_resource(Ym2149PluginConfig::default());
+    app.world_mut().init_resource::<Assets<Ym2149Playlist>>();
+    app.add_systems(
+        Update,
+        (process_music_state_requests, advance_music_transitions).chain(),
+    );
+
+    let mut playback = Ym2149Playback::new("music/title.ym");
+    playback.play();
+    let entity = app.world_mut().spawn(playback).id();
+    app.world_mut()
+        .resource_mut::<MusicStateGraph>()
+        .set_target(entity);
+    (app, entity)
+}
+
+fn request_state(app: &mut App, state: &str) {
+    app.world_mut()
+        .resource_mut::<Messages<MusicStateRequest>>()
+        .write(MusicStateRequest {
+            state: state.into(),
+            target: None,
+        });
+}
+
+fn set_frame(app: &mut App, entity: Entity, frame: u32) {
+    app.world_mut()
+        .get_mut::<Ym2149Playback>(entity)
+        .unwrap()
+        .seek(frame);
+}
+
+#[test]
+fn quantized_music_transition_waits_for_bar() {
+    let (mut app, entity) = music_transition_app();
+    app.world_mut()
+        .resource_mut::<MusicStateGraph>()
+        .insert_with_transition(
+            "battle",
+            MusicStateDefinition::SourcePath("music/battle.ym".into()),
+            // 2 beats of 50 frames per bar
+            MusicTransition::cut().quantized(TransitionQuantize::Bar(2)),
+        );
+
+    set_frame(&mut app, entity, 30);
+    request_state(&mut app, "battle");
+    app.update();
+
+    let world = app.world();
+    assert_eq!(
+        world.get::<Ym2149Playback>(entity).unwrap().source_path(),
+        Some("music/title.ym")
+    );
+    assert_eq!(
+        world.get::<PendingMusicTransition>(entity).unwrap().state(),
+        "battle"
+    );
+
+    set_frame(&mut app, entity, 99);
+    app.update();
+    assert_eq!(
+        app.world()
+            .get::<Ym2149Playback>(entity)
+            .unwrap()
+            .source_path(),
+        Some("music/title.ym")
+    );
+
+    set_frame(&mut app, entity, 100);
+    app.update();
+    assert_eq!(
+        app.world()
+            .get::<Ym2149Playback>(entity)
+            .unwrap()
+            .source_path(),
+        Some("music/battle.ym")
+    );
+    assert!(app.world().get::<PendingMusicTransition>(entity).is_none());
+}
+
+#[test]
+fn stinger_transition_switches_after_stinger_ends() {
+    let (mut app, entity) = music_transition_app();
+    app.world_mut()
+        .resource_mut::<MusicStateGraph>()
+        .insert_with_transition(
+            "boss",
+            MusicStateDefinition::SourcePath("music/boss.ym".into()),
+            MusicTransition::stinger(MusicStateDefinition::SourcePath("music/stinger.ym".into())),
+        );
+
+    request_state(&mut app, "boss");
+    app.update();
+    assert_eq!(
+        app.world()
+            .get::<Ym2149Playback>(entity)
+            .unwrap()
+            .source_path(),
+        Some("music/stinger.ym")
+    );
+
+    set_frame(&mut app, entity, 20);
+    app.update();
+    assert!(app.world().get::<PendingMusicTransition>(entity).is_some());
+
+    app.world_mut()
+        .get_mut::<Ym2149Playback>(entity)
+        .unwrap()
+        .state = PlaybackState::Finished;
+    app.update();
+    let playback = app.world().get::<Ym2149Playback>(entity).unwrap();
+    assert_eq!(playback.source_path(), Some("music/boss.ym"));
+    assert_eq!(playback.state, PlaybackState::Playing);
+    assert!(app.world().get::<PendingMusicTransition>(entity).is_none());
+}
+
 #[test]
 fn diagnostics_record_frame_position() {
     let mut app = App::new();
@@ -104,3 +229,87 @@ fn diagnostics_record_frame_position() {
         .and_then(|diag| diag.value());
     assert_eq!(diagnostic, Some(128.0));
 }
+
+#[derive(States, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+enum GameState {
+    #[default]
+    Menu,
+    InGame,
+}
+
+#[test]
+fn state_scoped_playback_pauses_outside_its_state() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin));
+    app.add_plugins(Ym2149StateScopePlugin::<GameState>::default());
+    app.init_state::<GameState>();
+
+    let mut menu_music = Ym2149Playback::default();
+    menu_music.play();
+    let menu = app
+        .world_mut()
+        .spawn((menu_music, PlayInState::new(GameState::Menu)))
+        .id();
+    let mut game_music = Ym2149Playback::default();
+    game_music.play();
+    let game = app
+        .world_mut()
+        .spawn((game_music, PlayInState::new(GameState::InGame)))
+        .id();
+
+    let state_of = |app: &App, entity| app.world().get::<Ym2149Playback>(entity).unwrap().state;
+
+    app.update();
+    assert_eq!(state_of(&app, menu), PlaybackState::Playing);
+    assert_eq!(state_of(&app, game), PlaybackState::Paused);
+
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::InGame);
+    app.update();
+    assert_eq!(state_of(&app, menu), PlaybackState::Paused);
+    assert_eq!(state_of(&app, game), PlaybackState::Playing);
+
+    // A song stopped by game code stays stopped when its state returns
+    app.world_mut()
+        .get_mut::<Ym2149Playback>(menu)
+        .unwrap()
+        .stop();
+    app.world_mut()
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Menu);
+    app.update();
+    assert_eq!(state_of(&app, menu), PlaybackState::Idle);
+}
+
+#[test]
+fn focus_loss_pauses_and_resumes_playback() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.insert_resource(Ym2149PluginConfig {
+        focus_loss: FocusLoss::Pause,
+        ..Default::default()
+    });
+    app.init_resource::<Ym2149Focus>();
+    app.add_systems(Update, track_window_focus);
+
+    let window = app.world_mut().spawn(Window::default()).id();
+    let mut playback = Ym2149Playback::default();
+    playback.play();
+    let entity = app.world_mut().spawn(playback).id();
+
+    app.world_mut().get_mut::<Window>(window).unwrap().focused = false;
+    app.update();
+    assert!(!app.world().resource::<Ym2149Focus>().is_focused());
+    assert_eq!(
+        app.world().get::<Ym2149Playback>(entity).unwrap().state,
+        PlaybackState::Paused
+    );
+
+    app.world_mut().get_mut::<Window>(window).unwrap().focused = true;
+    app.update();
+    assert_eq!(
+        app.world().get::<Ym2149Playback>(entity).unwrap().state,
+        PlaybackState::Playing
+    );
+}
@@ -4,13 +4,14 @@
 //! - Real-time visualization (oscilloscope, channel display, spectrum analysis)
 //! - File drag-and-drop loading
 //! - Keyboard-based playback control
+//! - Diagnostics overlay (toggle with F3)
 
 use bevy::prelude::*;
 use bevy::ui::UiGlobalTransform;
 use bevy::window::FileDragAndDrop;
 use bevy_ym2149::{
-    PatternTrigger, PatternTriggerSet, PatternTriggered, PlaybackState, Ym2149Playback,
-    Ym2149Plugin, Ym2149Settings,
+    DiagnosticsOverlay, PatternTrigger, PatternTriggerSet, PatternTriggered, PlaybackState,
+    Ym2149DiagnosticsOverlayPlugin, Ym2149Playback, Ym2149Plugin, Ym2149Settings,
 };
 use bevy_ym2149_examples::{embedded_asset_plugin, example_plugins_with_window};
 use bevy_ym2149_viz::{
@@ -27,6 +28,11 @@ fn main() {
         }))
         .add_plugins(Ym2149Plugin::default())
         .add_plugins(Ym2149VizPlugin)
+        .insert_resource(DiagnosticsOverlay {
+            visible: false,
+            ..default()
+        })
+        .add_plugins(Ym2149DiagnosticsOverlayPlugin)
         .add_systems(Startup, setup)
         .add_systems(
             Update,
@@ -60,6 +66,7 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
              - C: Toggle Color Filter\n\
              - LEFT/RIGHT: Previous/Next Subsong (AKS/SNDH)\n\
              - UP/DOWN: Volume Control\n\
+             - F3: Diagnostics Overlay\n\
              - Pattern hits are logged to the console",
         ),
         TextFont {
@@ -85,6 +85,7 @@ fn setup(mut commands: Commands, mut playlists: ResMut<Assets<Ym2149Playlist>>)
     let crossfade = CrossfadeConfig {
         trigger: CrossfadeTrigger::SongRatio(0.8),
         window: bevy_ym2149::playlist::CrossfadeWindow::FixedSeconds(5.0),
+        ..Default::default()
     };
 
     let entity = commands
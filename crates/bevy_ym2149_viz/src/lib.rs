@@ -35,7 +35,15 @@ pub struct Ym2149VizPlugin;
 
 impl Plugin for Ym2149VizPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<bevy_ym2149::OscilloscopeBuffer>();
+        // Hold a full FFT window of channel samples for the spectrum analyzer
+        if !app
+            .world()
+            .contains_resource::<bevy_ym2149::OscilloscopeBuffer>()
+        {
+            app.insert_resource(bevy_ym2149::OscilloscopeBuffer::new(
+                ym2149_common::DEFAULT_FFT_SIZE,
+            ));
+        }
         app.init_resource::<OscilloscopeUniform>();
         app.init_resource::<SpectrumUniform>();
         app.init_resource::<RegisterWaveformState>();
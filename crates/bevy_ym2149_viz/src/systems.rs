@@ -8,10 +8,9 @@ use crate::helpers::{
 use crate::uniforms::{OscilloscopeUniform, RegisterWaveformState, SpectrumUniform};
 use bevy::prelude::*;
 use bevy::ui::ComputedNode;
-use bevy_ym2149::ChipStateSnapshot;
 use bevy_ym2149::playback::{PlaybackState, Ym2149Playback, Ym2149Settings};
+use bevy_ym2149::{ChipStateSnapshot, OscilloscopeBuffer};
 use std::array::from_fn;
-use ym2149::Ym2149Backend;
 use ym2149_common::SPECTRUM_BINS;
 
 // Oscilloscope rendering constants
@@ -96,11 +95,12 @@ pub fn update_detailed_channel_display(
     if let Some(playback) = playbacks.iter().next()
         && let Some(player) = playback.player_handle()
     {
-        let player_locked = player.read();
-        let Some(chip) = player_locked.chip() else {
+        let Some(regs) = playback
+            .audible_registers()
+            .or_else(|| player.read().dump_registers())
+        else {
             return;
         };
-        let regs = chip.dump_registers();
 
         let period_a = get_channel_period(regs[0], regs[1]);
         let period_b = get_channel_period(regs[2], regs[3]);
@@ -202,6 +202,7 @@ pub fn update_song_progress(
 #[allow(clippy::type_complexity)]
 pub fn update_oscilloscope(
     chip_state: Option<Res<ChipStateSnapshot>>,
+    sample_buffer: Option<Res<OscilloscopeBuffer>>,
     osc_nodes: Query<&ComputedNode, With<Oscilloscope>>,
     mut osc_uniform: ResMut<OscilloscopeUniform>,
     mut spectrum_uniform: ResMut<SpectrumUniform>,
@@ -218,6 +219,11 @@ pub fn update_oscilloscope(
 
     // Update register-based waveform synthesis
     register_waveform.update_from_channel_states(&channel_states);
+    if let Some(buffer) = sample_buffer.as_deref()
+        && register_waveform.fft_spectrum
+    {
+        register_waveform.update_from_samples(buffer);
+    }
 
     // Get synthesized samples from register state
     let samples = register_waveform.get_samples();
@@ -371,7 +377,7 @@ pub fn update_oscilloscope(
         let ch = bar.channel.min(2);
         let base = BASE_COLORS[ch];
         let bin_idx = bar.bin.min(SPECTRUM_BINS - 1);
-        // Magnitude is already normalized 0-1 from register amplitude
+        // Magnitude is already normalized 0-1 (FFT level or register amplitude)
         let magnitude = spectrum[ch][bin_idx];
         let bar_height = (magnitude.powf(0.75) * 48.0).max(2.0);
         node.height = Val::Px(bar_height);
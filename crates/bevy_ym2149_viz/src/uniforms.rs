@@ -1,7 +1,9 @@
 //! GPU uniform buffer resources for visualization shaders.
 
 use bevy::prelude::*;
+use bevy_ym2149::OscilloscopeBuffer;
 use ym2149_common::visualization::{SPECTRUM_BINS, SpectrumAnalyzer, WaveformSynthesizer};
+use ym2149_common::{DEFAULT_SAMPLE_RATE, FftSpectrumAnalyzer, FftSpectrumConfig};
 
 /// Buffer storing oscilloscope samples ready to upload to GPU uniforms.
 ///
@@ -28,12 +30,19 @@ pub struct SpectrumUniform(
 /// digidrums or STE-DAC samples that bypass the PSG.
 ///
 /// Wraps the shared implementation from `ym2149_common::visualization`.
+/// The spectrum bars use FFT analysis of the generated channel samples
+/// (see [`OscilloscopeBuffer`]) unless `fft_spectrum` is disabled.
 #[derive(Resource, Clone)]
 pub struct RegisterWaveformState {
     /// Waveform synthesizer from shared library.
     pub synthesizer: WaveformSynthesizer,
     /// Spectrum analyzer from shared library.
     pub spectrum: SpectrumAnalyzer,
+    /// FFT analyzers for channels A, B, C.
+    pub fft: [FftSpectrumAnalyzer; 3],
+    /// Use the FFT spectrum instead of the register-derived one.
+    pub fft_spectrum: bool,
+    fft_fed: bool,
 }
 
 impl Default for RegisterWaveformState {
@@ -41,6 +50,11 @@ impl Default for RegisterWaveformState {
         Self {
             synthesizer: WaveformSynthesizer::new(),
             spectrum: SpectrumAnalyzer::new(),
+            fft: std::array::from_fn(|_| {
+                FftSpectrumAnalyzer::new(FftSpectrumConfig::default(), DEFAULT_SAMPLE_RATE)
+            }),
+            fft_spectrum: true,
+            fft_fed: false,
         }
     }
 }
@@ -52,13 +66,38 @@ impl RegisterWaveformState {
         self.spectrum.update(channel_states);
     }
 
+    /// Run the FFT analyzers on the latest generated channel samples.
+    pub fn update_from_samples(&mut self, buffer: &OscilloscopeBuffer) {
+        let samples = buffer.get_samples();
+        let mut channel = Vec::with_capacity(samples.len());
+        for (ch, analyzer) in self.fft.iter_mut().enumerate() {
+            channel.clear();
+            channel.extend(samples.iter().map(|frame| frame[ch]));
+            analyzer.push_samples(&channel);
+            analyzer.update();
+        }
+        self.fft_fed = true;
+    }
+
     /// Get waveform samples for oscilloscope display.
     pub fn get_samples(&self) -> Vec<[f32; 3]> {
         self.synthesizer.get_samples()
     }
 
     /// Get spectrum bins for the first 3 channels (single PSG).
+    ///
+    /// Returns the FFT spectrum once samples have been analyzed and
+    /// `fft_spectrum` is enabled, otherwise the register-derived spectrum.
     pub fn get_spectrum(&self) -> [[f32; SPECTRUM_BINS]; 3] {
+        if self.fft_spectrum && self.fft_fed {
+            return std::array::from_fn(|ch| {
+                let mut bins = [0.0; SPECTRUM_BINS];
+                let fft_bins = self.fft[ch].bins();
+                let len = fft_bins.len().min(SPECTRUM_BINS);
+                bins[..len].copy_from_slice(&fft_bins[..len]);
+                bins
+            });
+        }
         let all = self.spectrum.all_channel_spectrums();
         [all[0], all[1], all[2]]
     }
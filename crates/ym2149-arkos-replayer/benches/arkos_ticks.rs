@@ -0,0 +1,75 @@
+//! Benchmarks for Arkos Tracker replay ticks
+//!
+//! Run with: cargo bench --bench arkos_ticks -p ym2149-arkos-replayer
+//!
+//! The song is read from the Bevy example assets; benchmarks are skipped when
+//! the asset is not available. Multi-PSG variants play the same song on every
+//! PSG, so the cost per PSG can be compared directly.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use std::hint::black_box;
+use ym2149_arkos_replayer::{AksSong, ArkosPlayer, load_aks};
+use ym2149_common::test_support::read_asset;
+
+const SONG: &str = "Doclands - Pong Cracktro (YM).aks";
+
+/// PSG counts to benchmark (1 = the song as composed).
+const PSG_COUNTS: [usize; 3] = [1, 2, 3];
+
+fn read_song() -> Option<AksSong> {
+    let data = read_asset(SONG)?;
+    Some(load_aks(&data).expect("song loads"))
+}
+
+/// Duplicate the first subsong's PSGs and channels `psg_count` times.
+fn with_psg_count(song: &AksSong, psg_count: usize) -> AksSong {
+    let mut song = song.clone();
+    let subsong = &mut song.subsongs[0];
+    subsong.psgs = (0..psg_count).flat_map(|_| subsong.psgs.clone()).collect();
+    for pattern in &mut subsong.patterns {
+        pattern.track_indexes = pattern.track_indexes.repeat(psg_count);
+    }
+    for position in &mut subsong.positions {
+        position.transpositions = position.transpositions.repeat(psg_count);
+    }
+    song
+}
+
+fn playing(song: AksSong) -> ArkosPlayer {
+    let mut player = ArkosPlayer::new(song, 0).expect("player builds");
+    player.play().expect("song plays");
+    player
+}
+
+fn bench_ticks(c: &mut Criterion) {
+    let Some(song) = read_song() else {
+        return;
+    };
+
+    // Replay logic only: pattern, instrument and effect processing
+    let mut group = c.benchmark_group("arkos_tick_registers");
+    for psg_count in PSG_COUNTS {
+        let mut player = playing(with_psg_count(&song, psg_count));
+        group.bench_function(BenchmarkId::from_parameter(psg_count), |b| {
+            b.iter(|| black_box(player.tick_registers()));
+        });
+    }
+    group.finish();
+
+    // One tick of audio, including PSG emulation and mixing
+    let mut group = c.benchmark_group("arkos_tick_samples");
+    for psg_count in PSG_COUNTS {
+        let mut player = playing(with_psg_count(&song, psg_count));
+        let mut buffer = vec![0.0f32; player.samples_per_tick().round() as usize];
+        group.bench_function(BenchmarkId::from_parameter(psg_count), |b| {
+            b.iter(|| {
+                player.generate_samples_into(&mut buffer);
+                black_box(&buffer);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_ticks);
+criterion_main!(benches);
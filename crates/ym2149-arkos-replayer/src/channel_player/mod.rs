@@ -58,11 +58,14 @@ pub(crate) struct ChannelDebugState {
 }
 
 /// Complete state for one channel
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelPlayer {
     /// Channel index in song
     _channel_index: usize,
 
-    /// Reference to song data
+    /// Reference to song data (not part of serialized state)
+    #[cfg_attr(feature = "serde", serde(skip))]
     song: Arc<AksSong>,
 
     // Current note state
@@ -120,6 +123,13 @@ pub struct ChannelPlayer {
 }
 
 impl ChannelPlayer {
+    /// Copy the playback state of `saved`, keeping this channel's song reference.
+    pub(crate) fn restore_from(&mut self, saved: &ChannelPlayer) {
+        let song = Arc::clone(&self.song);
+        self.clone_from(saved);
+        self.song = song;
+    }
+
     #[cfg(all(test, feature = "extended-tests"))]
     pub(crate) fn debug_state(&self) -> ChannelDebugState {
         ChannelDebugState {
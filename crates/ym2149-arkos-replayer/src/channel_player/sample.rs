@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 /// Internal sample voice state for tracking active sample playback
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(super) struct SampleVoiceState {
     pub data: Arc<Vec<f32>>,
     pub loop_start: usize,
@@ -107,6 +107,7 @@ impl EffectType {
 
 /// State for volume slide effects
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VolumeSlide {
     /// Current track volume (0-15)
     pub current: FixedPoint,
@@ -165,6 +166,7 @@ impl VolumeSlide {
 
 /// State for pitch slide effects
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PitchSlide {
     /// Current pitch offset (added to base period)
     pub current: FixedPoint,
@@ -220,6 +222,7 @@ impl PitchSlide {
 
 /// State for glide effect
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlideState {
     /// Whether glide is active
     pub active: bool,
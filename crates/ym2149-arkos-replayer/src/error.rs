@@ -44,6 +44,14 @@ pub enum ArkosError {
     #[error("PSG error: {0}")]
     PsgError(String),
 
+    /// State snapshot does not match the loaded song or subsong.
+    #[error("Incompatible state snapshot: {0}")]
+    IncompatibleState(String),
+
+    /// YM export error.
+    #[error("YM export error: {0}")]
+    ExportError(String),
+
     /// I/O error.
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
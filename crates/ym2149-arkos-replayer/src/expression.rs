@@ -11,6 +11,7 @@ use crate::format::AksSong;
 /// This is built on-the-fly from effect values and doesn't reference
 /// the song's arpeggio table.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InlineArpeggio {
     values: Vec<i8>,
     speed: u8,
@@ -109,6 +110,7 @@ impl InlineArpeggio {
 
 /// State for reading an expression (arpeggio or pitch table)
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ExpressionReader {
     current_index: usize,
     current_tick: u8,
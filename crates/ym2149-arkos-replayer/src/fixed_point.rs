@@ -13,6 +13,7 @@ use std::ops::{Add, AddAssign, Sub, SubAssign};
 ///
 /// Example: Value 5.25 is stored as (5 << 8) + 64 = 1344
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FixedPoint {
     raw: i32, // Integer part in high bits, fractional in low 8 bits
 }
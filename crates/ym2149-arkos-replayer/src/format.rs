@@ -4,10 +4,11 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use ym2149_common::MetadataFields;
+use ym2149_common::{CompressionInfo, MetadataFields};
 
 /// AKS song metadata
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SongMetadata {
     /// Song title
     pub title: String,
@@ -274,6 +275,9 @@ pub struct Pattern {
     pub color_argb: u32,
 }
 
+/// Highest pattern height Arkos Tracker can produce; taller positions are clamped.
+pub const MAX_PATTERN_HEIGHT: usize = 128;
+
 /// Position (references a pattern with height and transpositions)
 #[derive(Debug, Clone, Default)]
 pub struct Position {
@@ -420,6 +424,8 @@ pub struct AksSong {
     pub pitch_tables: Vec<PitchTable>,
     /// List of subsongs
     pub subsongs: Vec<Subsong>,
+    /// ZIP packing of the loaded file (`None` for plain XML)
+    pub compression: Option<CompressionInfo>,
 }
 
 impl Default for SongMetadata {
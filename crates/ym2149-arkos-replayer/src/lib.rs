@@ -12,6 +12,15 @@
 //! - Arpeggios and pitch tables
 //! - Pattern-based sequencing with positions
 //! - Subsong support
+//! - Register capture and AKS to YM6 conversion (`ym-export` feature)
+//!
+//! # Untrusted input
+//!
+//! [`load_aks`] and [`ArkosPlayer`] never panic on malformed songs. Invalid
+//! XML and ZIP data are errors; out-of-range values (missing instruments,
+//! oversized pattern heights) fall back to defaults or are clamped, the way
+//! Arkos Tracker itself reads them. `tests/hostile_input.rs` and the
+//! `load_aks` fuzz target check this.
 //!
 //! # Quick Start
 //!
@@ -59,6 +68,7 @@ pub mod error;
 pub mod format;
 pub mod parser;
 pub mod player;
+pub mod ym_export;
 
 // Re-export public API (explicit, no star exports)
 pub use error::{ArkosError, Result};
@@ -68,7 +78,10 @@ pub use format::{
     SpecialTrack, Subsong, Track,
 };
 pub use parser::load_aks;
-pub use player::{ArkosMetadata, ArkosPlayer};
+pub use player::{ArkosMetadata, ArkosPlayer, ArkosPlayerState};
+#[cfg(feature = "ym-export")]
+pub use ym_export::export_ym;
+pub use ym_export::{RegisterCapture, capture_registers};
 
 // Re-export unified player trait from ym2149-common
 pub use ym2149_common::{ChiptunePlayer, PlaybackMetadata};
@@ -5,10 +5,17 @@
 
 use super::state::local_name_from_bytes;
 use crate::error::{ArkosError, Result};
-use crate::format::Position;
+use crate::format::{MAX_PATTERN_HEIGHT, Position};
 use quick_xml::Reader;
 use quick_xml::events::Event;
 
+/// Parses a pattern height, defaulting to 64 lines and clamping hostile values
+/// (the player precomputes per-line state for every position).
+pub(crate) fn parse_pattern_height(text: &str) -> usize {
+    text.parse::<usize>()
+        .map_or(64, |height| height.min(MAX_PATTERN_HEIGHT))
+}
+
 /// Parses the `<positions>` block from subsong XML.
 ///
 /// Extracts position entries with pattern index, height, markers,
@@ -74,7 +81,7 @@ pub fn parse_positions_block<R: std::io::BufRead>(
                     let text = e.unescape()?.to_string();
                     match field {
                         "patternIndex" => pos.pattern_index = text.parse().unwrap_or(0),
-                        "height" => pos.height = text.parse().unwrap_or(64),
+                        "height" => pos.height = parse_pattern_height(&text),
                         "markerName" => pos.marker_name = text,
                         "markerColor" => pos.marker_color = text.parse().unwrap_or(0),
                         "transposition" => pos.transpositions.push(text.parse().unwrap_or(0)),
@@ -131,6 +131,7 @@ pub fn load_aks(data: &[u8]) -> Result<AksSong> {
 /// - Contained file cannot be read
 fn load_aks_zip(data: &[u8]) -> Result<AksSong> {
     use std::io::{Cursor, Read};
+    use ym2149_common::{Compression, CompressionInfo};
     use zip::ZipArchive;
 
     let cursor = Cursor::new(data);
@@ -153,5 +154,11 @@ fn load_aks_zip(data: &[u8]) -> Result<AksSong> {
     file.read_to_end(&mut xml_data)
         .map_err(ArkosError::IoError)?;
 
-    xml_parser::parse_aks_xml(&xml_data)
+    let mut song = xml_parser::parse_aks_xml(&xml_data)?;
+    song.compression = Some(CompressionInfo {
+        method: Compression::Zip,
+        packed_size: data.len(),
+        unpacked_size: xml_data.len(),
+    });
+    Ok(song)
 }
@@ -151,6 +151,57 @@ fn test_parse_patterns() {
     assert_eq!(pattern1.event_track_index, 1);
 }
 
+#[test]
+fn test_hostile_pattern_heights_are_clamped() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<song>
+  <subsongs>
+    <subsong>
+      <positions>
+        <position>
+          <patternIndex>0</patternIndex>
+          <height>4294967296</height>
+        </position>
+        <position>
+          <patternIndex>0</patternIndex>
+          <height>-1</height>
+        </position>
+      </positions>
+    </subsong>
+  </subsongs>
+</song>"#;
+
+    let song = load_aks(xml.as_bytes()).unwrap();
+    let positions = &song.subsongs[0].positions;
+    assert_eq!(positions[0].height, crate::format::MAX_PATTERN_HEIGHT);
+    assert_eq!(positions[1].height, 64);
+}
+
+#[test]
+fn test_zipped_aks_records_compression() {
+    use std::io::Write;
+
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<song xmlns:aks="https://www.julien-nevo.com/arkostracker/ArkosTrackerSong">
+  <formatVersion>3.0</formatVersion>
+  <title>Zipped</title>
+</song>"#;
+    assert_eq!(load_aks(xml.as_bytes()).unwrap().compression, None);
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    zip.start_file("song.aks", zip::write::SimpleFileOptions::default())
+        .unwrap();
+    zip.write_all(xml.as_bytes()).unwrap();
+    let data = zip.finish().unwrap().into_inner();
+
+    let song = load_aks(&data).unwrap();
+    assert_eq!(song.metadata.title, "Zipped");
+    let packing = song.compression.expect("ZIP packing recorded");
+    assert_eq!(packing.method, ym2149_common::Compression::Zip);
+    assert_eq!(packing.packed_size, data.len());
+    assert_eq!(packing.unpacked_size, xml.len());
+}
+
 #[cfg(feature = "extended-tests")]
 #[test]
 fn test_load_real_aks_file() {
@@ -215,5 +215,6 @@ pub fn parse_aks_xml(data: &[u8]) -> Result<AksSong> {
         arpeggios,
         pitch_tables,
         subsongs,
+        compression: None,
     })
 }
@@ -7,6 +7,7 @@ use crate::psg::split_note;
 use base64::{Engine as _, engine::general_purpose};
 use std::collections::HashMap;
 
+use super::super::helpers::parse_pattern_height;
 use super::super::state::*;
 
 /// Handles text content for the current element.
@@ -401,7 +402,7 @@ pub fn handle_text_content(
             current_pattern_track_indexes.push(track_index);
         }
         (ParseState::Pattern, "height") => {
-            *current_pattern_height = current_text.parse().unwrap_or(64);
+            *current_pattern_height = parse_pattern_height(current_text);
         }
         (ParseState::PatternSpeedTrackIndex, "trackIndex") => {
             if let Some(pat) = current_pattern {
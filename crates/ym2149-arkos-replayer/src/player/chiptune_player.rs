@@ -4,7 +4,10 @@
 //! providing a common interface for AKS file playback alongside other chiptune formats.
 
 use super::ArkosPlayer;
-use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, MetadataFields, PlaybackState};
+use ym2149_common::{
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, LoadReport, MetadataFields, OutputModel,
+    PlaybackState, RegisterDelta,
+};
 
 /// Metadata wrapper for Arkos songs.
 ///
@@ -110,6 +113,60 @@ impl ChiptunePlayerBase for ArkosPlayer {
         ArkosPlayer::is_channel_muted(self, channel)
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        ArkosPlayer::step_frames(self, frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        ArkosPlayer::advance_frames(self, frames)
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        for index in 0..self.chip_count() {
+            if let Some(chip) = self.chip_mut(index) {
+                chip.set_output_model(model);
+            }
+        }
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        for index in 0..self.chip_count() {
+            if let Some(chip) = self.chip_mut(index) {
+                chip.set_channel_tap(capacity);
+            }
+        }
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.chip(channel / 3)
+            .and_then(|chip| chip.channel_tap())
+            .map_or(0, |tap| tap.read_channel(channel % 3, out))
+    }
+
     fn playback_position(&self) -> f32 {
         let current = self.current_tick_index();
         let total = self.estimated_total_ticks();
@@ -120,6 +177,15 @@ impl ChiptunePlayerBase for ArkosPlayer {
         }
     }
 
+    fn seek(&mut self, position: f32) -> bool {
+        let total = self.estimated_total_ticks();
+        if total == 0 {
+            return false;
+        }
+        let target = (position.clamp(0.0, 1.0) * total as f32) as usize;
+        self.seek_tick(target.min(total - 1)).is_ok()
+    }
+
     fn subsong_count(&self) -> usize {
         self.song.subsongs.len()
     }
@@ -148,4 +214,10 @@ impl ChiptunePlayer for ArkosPlayer {
     fn metadata(&self) -> &Self::Metadata {
         &self.cached_metadata
     }
+
+    fn load_report(&self) -> LoadReport {
+        LoadReport::from_player(self)
+            .with_family(ChiptuneFormat::Arkos)
+            .with_compression(self.song.compression)
+    }
 }
@@ -33,9 +33,11 @@
 mod chiptune_player;
 mod psg_output;
 mod sample_voice;
+mod snapshot;
 mod tick;
 
 pub use chiptune_player::ArkosMetadata;
+pub use snapshot::ArkosPlayerState;
 
 #[cfg(all(test, feature = "extended-tests"))]
 mod tests;
@@ -48,6 +50,7 @@ use crate::effect_context::EffectContext;
 use crate::error::{ArkosError, Result};
 use crate::format::{AksSong, SongMetadata};
 use ym2149::{PsgBank, Ym2149, Ym2149Backend};
+use ym2149_common::{OutputGain, PlaybackRate, RegisterDelta};
 
 use sample_voice::{HardwareEnvelopeState, SampleVoiceMixer};
 use tick::{TickContext, determine_speed_for_location};
@@ -77,7 +80,7 @@ pub struct ArkosPlayer {
     /// Current speed (ticks per line)
     pub(crate) current_speed: u8,
     /// Current tick counter (0..speed)
-    current_tick: u8,
+    pub(crate) current_tick: u8,
 
     // Sample generation timing
     /// Sample counter for tick timing
@@ -94,6 +97,10 @@ pub struct ArkosPlayer {
     cached_metadata: ArkosMetadata,
     /// Reusable frame buffer to avoid per-tick allocations
     frame_buffer: Vec<ChannelFrame>,
+    /// Output gain applied to rendered buffers
+    pub(crate) output_gain: OutputGain,
+    /// Playback rate (speed and pitch) applied to rendered buffers
+    pub(crate) playback_rate: PlaybackRate,
 }
 
 impl ArkosPlayer {
@@ -212,6 +219,8 @@ impl ArkosPlayer {
             output_sample_rate,
             cached_metadata,
             frame_buffer,
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
         };
 
         player.current_speed = determine_speed_for_location(&player.song, subsong_index, 0, 0);
@@ -227,6 +236,18 @@ impl ArkosPlayer {
         if was_playing {
             rebuilt.play()?;
         }
+        rebuilt.output_gain = self.output_gain;
+        rebuilt.playback_rate.set_rate(self.playback_rate.rate());
+        if let Some(chip) = self.chip(0) {
+            let chain = chip.filter_chain();
+            let tap_capacity = chip.channel_tap().map(|tap| tap.capacity());
+            for index in 0..rebuilt.chip_count() {
+                if let Some(chip) = rebuilt.chip_mut(index) {
+                    chip.set_filter_chain(chain);
+                    chip.set_channel_tap(tap_capacity);
+                }
+            }
+        }
         *self = rebuilt;
         Ok(())
     }
@@ -404,6 +425,15 @@ impl ArkosPlayer {
 
     /// Generate audio directly into provided buffer (avoids reallocations on hot path).
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| self.render_native(source));
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+    }
+
+    /// Render samples at the song's original speed.
+    fn render_native(&mut self, buffer: &mut [f32]) {
         if buffer.is_empty() {
             return;
         }
@@ -484,6 +514,75 @@ impl ArkosPlayer {
         ctx.process_tick();
     }
 
+    /// Advance playback by one tick without rendering audio.
+    ///
+    /// Returns the 16 registers of every PSG after the tick, in PSG order.
+    /// R13 is reported as `0xFF` when the envelope shape was not written during
+    /// the tick, matching the YM file convention for "no envelope restart".
+    ///
+    /// Sample and digidrum voices are mixed at audio rate and are not reflected
+    /// in the register state.
+    pub fn tick_registers(&mut self) -> Vec<[u8; 16]> {
+        self.process_tick();
+        let mut frames = self.psg_bank.dump_all_registers();
+        for (registers, state) in frames.iter_mut().zip(&self.hardware_envelope_state) {
+            if !state.shape_written {
+                registers[13] = 0xFF;
+            }
+        }
+        frames
+    }
+
+    /// Advance exactly `frames` ticks while paused.
+    ///
+    /// Resumes like [`play`](Self::play), renders silently until the last
+    /// tick has been processed and pauses again, so the returned delta shows
+    /// the registers written by that tick. Returns `None` while playing.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        if self.is_playing {
+            return None;
+        }
+
+        let before = self.psg_bank.dump_all_registers();
+        let _ = self.play();
+        let mut ticks = 0;
+        let mut sample = [0.0f32];
+        while ticks < frames {
+            // Same condition render_native uses to trigger a tick
+            ticks += usize::from(self.sample_counter + 1.0 >= self.samples_per_tick);
+            self.render_native(&mut sample);
+        }
+        self.is_playing = false;
+        Some(RegisterDelta::new(
+            before,
+            self.psg_bank.dump_all_registers(),
+        ))
+    }
+
+    /// Run `frames` ticks without rendering audio.
+    ///
+    /// Only the tick logic runs; sample voices are not advanced. Works in any
+    /// state and leaves the next rendered sample starting a new tick. Arkos
+    /// songs loop, so this always returns `frames`.
+    pub fn advance_frames(&mut self, frames: usize) -> usize {
+        for _ in 0..frames {
+            self.process_tick();
+        }
+        self.sample_counter = self.samples_per_tick;
+        frames
+    }
+
+    /// Restart the song and advance to `tick` without rendering audio.
+    ///
+    /// Keeps the play/pause state.
+    pub fn seek_tick(&mut self, tick: usize) -> Result<()> {
+        let was_playing = self.is_playing;
+        self.stop()?;
+        self.advance_frames(tick);
+        self.is_playing = was_playing;
+        Ok(())
+    }
+
     /// Capture tick frames for testing (extended-tests feature only).
     #[cfg(all(test, feature = "extended-tests"))]
     pub(crate) fn capture_tick_frames(&mut self) -> Vec<ChannelFrame> {
@@ -19,6 +19,9 @@ pub(crate) fn write_frames_to_psg(
 ) {
     for psg_idx in 0..psg_bank.psg_count() {
         let base_channel = psg_idx * 3;
+        if let Some(state) = hardware_envelope_state.get_mut(psg_idx) {
+            state.shape_written = false;
+        }
 
         for ch_in_psg in 0..3 {
             let channel_idx = base_channel + ch_in_psg;
@@ -83,6 +86,7 @@ fn write_channel_registers(
             if should_write {
                 psg.write_register(13, shape);
                 state.last_shape = shape;
+                state.shape_written = true;
             }
         }
     } else {
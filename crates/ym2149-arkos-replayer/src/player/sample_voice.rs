@@ -11,6 +11,7 @@ use std::sync::Arc;
 /// Each channel can have an active sample that is mixed into the final audio.
 /// Samples support looping, pitch adjustment, and amplitude control.
 #[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct SampleVoiceMixer {
     active: Option<ActiveSample>,
 }
@@ -157,6 +158,7 @@ impl SampleVoiceMixer {
 
 /// Active sample playback state.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct ActiveSample {
     data: Arc<Vec<f32>>,
     pub(crate) position: f32,
@@ -277,12 +279,18 @@ pub fn sample_note_frequency(reference_frequency: f32, note: i32) -> f32 {
 
 /// Hardware envelope state tracking to avoid unwanted retriggering.
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub(crate) struct HardwareEnvelopeState {
     pub last_shape: u8,
+    /// Whether R13 was written during the current tick (used by register capture).
+    pub shape_written: bool,
 }
 
 impl Default for HardwareEnvelopeState {
     fn default() -> Self {
-        Self { last_shape: 0xFF }
+        Self {
+            last_shape: 0xFF,
+            shape_written: false,
+        }
     }
 }
@@ -0,0 +1,88 @@
+//! Playback state snapshots.
+
+use super::ArkosPlayer;
+use super::sample_voice::{HardwareEnvelopeState, SampleVoiceMixer};
+use crate::channel_player::ChannelPlayer;
+use crate::error::{ArkosError, Result};
+use ym2149::Ym2149State;
+
+/// Snapshot of an [`ArkosPlayer`]'s playback state
+///
+/// Captured by [`ArkosPlayer::save_state`] and restored with
+/// [`ArkosPlayer::load_state`] into a player for the same song and subsong.
+/// Holds the PSG chips, the song position, every channel's instrument and
+/// effect state and the active sample voices. Song data and playback
+/// settings (mutes, gain, rate, output model) are not included.
+///
+/// With the `serde` feature the state can be serialized.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArkosPlayerState {
+    subsong_index: usize,
+    chips: Vec<Ym2149State>,
+    channel_players: Vec<ChannelPlayer>,
+    current_position: usize,
+    current_line: usize,
+    current_speed: u8,
+    current_tick: u8,
+    sample_counter: f32,
+    sample_voices: Vec<SampleVoiceMixer>,
+    hardware_envelope_state: Vec<HardwareEnvelopeState>,
+}
+
+impl ArkosPlayer {
+    /// Capture the playback state (see [`ArkosPlayerState`]).
+    pub fn save_state(&self) -> ArkosPlayerState {
+        ArkosPlayerState {
+            subsong_index: self.subsong_index,
+            chips: (0..self.psg_bank.psg_count())
+                .map(|index| self.psg_bank.get_chip(index).save_state())
+                .collect(),
+            channel_players: self.channel_players.clone(),
+            current_position: self.current_position,
+            current_line: self.current_line,
+            current_speed: self.current_speed,
+            current_tick: self.current_tick,
+            sample_counter: self.sample_counter,
+            sample_voices: self.sample_voices.clone(),
+            hardware_envelope_state: self.hardware_envelope_state.clone(),
+        }
+    }
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    ///
+    /// The play/pause state is left alone. Since [`play`](Self::play)
+    /// re-arms the tick timer, call it before loading to resume
+    /// sample-exactly. Fails without changing anything if the state was
+    /// saved from a different song or subsong.
+    pub fn load_state(&mut self, state: &ArkosPlayerState) -> Result<()> {
+        let positions = self.song.subsongs[self.subsong_index].positions.len();
+        let compatible = state.subsong_index == self.subsong_index
+            && state.chips.len() == self.psg_bank.psg_count()
+            && state.channel_players.len() == self.channel_players.len()
+            && state.sample_voices.len() == self.sample_voices.len()
+            && state.hardware_envelope_state.len() == self.hardware_envelope_state.len()
+            && state.current_position < positions.max(1);
+        if !compatible {
+            return Err(ArkosError::IncompatibleState(
+                "saved from a different song or subsong".to_string(),
+            ));
+        }
+
+        for (index, chip) in state.chips.iter().enumerate() {
+            self.psg_bank.get_chip_mut(index).load_state(chip);
+        }
+        for (channel, saved) in self.channel_players.iter_mut().zip(&state.channel_players) {
+            channel.restore_from(saved);
+        }
+        self.current_position = state.current_position;
+        self.current_line = state.current_line;
+        self.current_speed = state.current_speed;
+        self.current_tick = state.current_tick;
+        self.sample_counter = state.sample_counter;
+        self.sample_voices.clone_from(&state.sample_voices);
+        self.hardware_envelope_state
+            .clone_from(&state.hardware_envelope_state);
+        Ok(())
+    }
+}
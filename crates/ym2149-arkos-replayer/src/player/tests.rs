@@ -91,6 +91,39 @@ fn at2_first_tick_is_silent() {
     );
 }
 
+#[test]
+fn load_state_resumes_identically() {
+    let song_data =
+        std::fs::read(data_path("Excellence in Art 2018 - Just add cream.aks")).expect("read AKS");
+    let song = Arc::new(load_aks(&song_data).expect("parse AKS"));
+    let mut player = ArkosPlayer::new_from_arc(Arc::clone(&song), 0).expect("player init");
+    player.play().unwrap();
+    player.generate_samples(20_000);
+    let state = player.save_state();
+    let expected = player.generate_samples(20_000);
+    assert!(expected.iter().any(|&sample| sample != 0.0));
+
+    let mut other = ArkosPlayer::new_from_arc(song, 0).expect("player init");
+    other.play().unwrap();
+    other.load_state(&state).unwrap();
+    assert_eq!(other.generate_samples(20_000), expected);
+    assert_eq!(other.current_tick_index(), player.current_tick_index());
+}
+
+#[test]
+fn advance_frames_matches_stepped_ticks() {
+    let song_data =
+        std::fs::read(data_path("Excellence in Art 2018 - Just add cream.aks")).expect("read AKS");
+    let song = Arc::new(load_aks(&song_data).expect("parse AKS"));
+    let mut stepped = ArkosPlayer::new_from_arc(Arc::clone(&song), 0).expect("player init");
+    let delta = stepped.step_frames(10).unwrap();
+
+    let mut advanced = ArkosPlayer::new_from_arc(song, 0).expect("player init");
+    assert_eq!(advanced.advance_frames(10), 10);
+    assert_eq!(advanced.psg_bank.dump_all_registers(), delta.after);
+    assert_eq!(advanced.current_tick_index(), stepped.current_tick_index());
+}
+
 #[test]
 #[ignore]
 fn doclands_matches_reference_ym() {
@@ -0,0 +1,279 @@
+//! AKS to YM conversion.
+//!
+//! Runs [`ArkosPlayer`] headless, captures the register state of every PSG
+//! once per tick and (with the `ym-export` feature) writes one YM6 file per
+//! PSG, so Arkos Tracker songs can be played on hardware and software
+//! players that only understand YM.
+//!
+//! Only the PSG registers are captured. Sample instruments and event-track
+//! digidrums are mixed by the replayer at audio rate and have no register
+//! representation, so they are absent from the exported files.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #[cfg(feature = "ym-export")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use ym2149_arkos_replayer::{load_aks, ym_export::export_ym};
+//!
+//! let song = load_aks(&std::fs::read("song.aks")?)?;
+//! for (psg, bytes) in export_ym(&song, 0, true)?.into_iter().enumerate() {
+//!     std::fs::write(format!("song-psg{psg}.ym"), bytes)?;
+//! }
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "ym-export"))]
+//! # fn main() {}
+//! ```
+
+use std::sync::Arc;
+
+use crate::error::{ArkosError, Result};
+use crate::format::AksSong;
+use crate::player::ArkosPlayer;
+
+/// Upper bound on captured ticks (the YM6 parser rejects longer files).
+pub const MAX_EXPORT_FRAMES: usize = 100_000;
+
+/// Register frames captured from one subsong.
+#[derive(Debug, Clone)]
+pub struct RegisterCapture {
+    /// Register frames per PSG: `psg_frames[psg][tick]`.
+    pub psg_frames: Vec<Vec<[u8; 16]>>,
+    /// Tick at which the subsong's loop start position begins.
+    pub loop_frame: usize,
+    /// Ticks per second (the subsong replay frequency).
+    pub frame_rate_hz: f32,
+    /// Master clock of each PSG in Hz.
+    pub psg_frequencies: Vec<u32>,
+}
+
+impl RegisterCapture {
+    /// Number of captured ticks.
+    pub fn frame_count(&self) -> usize {
+        self.psg_frames.first().map_or(0, Vec::len)
+    }
+}
+
+/// Play a subsong headless and record the registers of every PSG per tick.
+///
+/// Capture runs from the start of the subsong until playback wraps back to
+/// the loop start position for the first time, or until `max_frames` ticks
+/// have been recorded.
+///
+/// # Errors
+///
+/// Returns an error if the subsong index is invalid, the subsong has no
+/// positions, or `max_frames` is zero.
+pub fn capture_registers(
+    song: Arc<AksSong>,
+    subsong_index: usize,
+    max_frames: usize,
+) -> Result<RegisterCapture> {
+    if max_frames == 0 {
+        return Err(ArkosError::InvalidFormat(
+            "Capture needs at least one frame".to_string(),
+        ));
+    }
+
+    let mut player = ArkosPlayer::new_from_arc(Arc::clone(&song), subsong_index)?;
+    let subsong = &song.subsongs[subsong_index];
+    if subsong.positions.is_empty() {
+        return Err(ArkosError::InvalidFormat(format!(
+            "Subsong {subsong_index} has no positions"
+        )));
+    }
+
+    // Same clamping as the tick loop uses for its loop bounds
+    let loop_position = subsong.loop_start_position.min(subsong.positions.len() - 1);
+
+    let psg_count = player.psg_count();
+    let mut psg_frames = vec![Vec::new(); psg_count];
+    let mut loop_frame = None;
+
+    while psg_frames[0].len() < max_frames {
+        let at_loop_point = player.current_position == loop_position
+            && player.current_line == 0
+            && player.current_tick == 0;
+        if at_loop_point {
+            if loop_frame.is_some() {
+                break;
+            }
+            loop_frame = Some(psg_frames[0].len());
+        }
+
+        for (frames, registers) in psg_frames.iter_mut().zip(player.tick_registers()) {
+            frames.push(registers);
+        }
+    }
+
+    Ok(RegisterCapture {
+        psg_frames,
+        loop_frame: loop_frame.unwrap_or(0),
+        frame_rate_hz: subsong.replay_frequency_hz,
+        psg_frequencies: subsong.psgs.iter().map(|psg| psg.psg_frequency).collect(),
+    })
+}
+
+/// Convert a subsong to YM6 files, one per PSG.
+///
+/// Each file carries the PSG's master clock, the loop frame and the song
+/// metadata; multi-PSG songs get a `PSG n/m` note appended to the comment.
+/// The replay frequency is rounded to whole hertz as required by YM6.
+///
+/// # Errors
+///
+/// Returns an error if capture fails or the YM6 writer rejects the data.
+#[cfg(feature = "ym-export")]
+pub fn export_ym(song: &AksSong, subsong_index: usize, compressed: bool) -> Result<Vec<Vec<u8>>> {
+    use ym2149_ym_replayer::YmWriter;
+
+    let capture = capture_registers(Arc::new(song.clone()), subsong_index, MAX_EXPORT_FRAMES)?;
+
+    let metadata = &song.metadata;
+    let author = if metadata.author.is_empty() {
+        &metadata.composer
+    } else {
+        &metadata.author
+    };
+    let frame_rate = capture.frame_rate_hz.round().clamp(1.0, u16::MAX as f32) as u16;
+    let psg_count = capture.psg_frames.len();
+
+    capture
+        .psg_frames
+        .into_iter()
+        .zip(&capture.psg_frequencies)
+        .enumerate()
+        .map(|(psg_index, (frames, &frequency))| {
+            let comment = if psg_count > 1 {
+                let note = format!("PSG {}/{psg_count}", psg_index + 1);
+                if metadata.comments.is_empty() {
+                    note
+                } else {
+                    format!("{} ({note})", metadata.comments)
+                }
+            } else {
+                metadata.comments.clone()
+            };
+
+            YmWriter::new(frames)
+                .song_name(metadata.title.as_str())
+                .author(author.as_str())
+                .comment(comment)
+                .master_clock(frequency)
+                .frame_rate(frame_rate)
+                .loop_frame(capture.loop_frame as u32)
+                .compressed(compressed)
+                .to_bytes()
+                .map_err(|e| ArkosError::ExportError(e.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::load_aks;
+
+    /// Two PSGs, three one-line positions of an empty pattern, looping back to position 1.
+    fn two_psg_song() -> AksSong {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<song>
+    <title>Export Test</title>
+    <author>Tester</author>
+    <subsongs>
+        <subsong>
+            <title>Main</title>
+            <initialSpeed>2</initialSpeed>
+            <replayFrequency>50</replayFrequency>
+            <loopStartPosition>1</loopStartPosition>
+            <endPosition>2</endPosition>
+            <psgs>
+                <psg>
+                    <type>ym</type>
+                    <frequencyHz>2000000</frequencyHz>
+                    <referenceFrequencyHz>440</referenceFrequencyHz>
+                    <samplePlayerFrequencyHz>11025</samplePlayerFrequencyHz>
+                    <mixingOutput>ABC</mixingOutput>
+                </psg>
+                <psg>
+                    <type>ay</type>
+                    <frequencyHz>1000000</frequencyHz>
+                    <referenceFrequencyHz>440</referenceFrequencyHz>
+                    <samplePlayerFrequencyHz>11025</samplePlayerFrequencyHz>
+                    <mixingOutput>ABC</mixingOutput>
+                </psg>
+            </psgs>
+            <positions>
+                <position><patternIndex>0</patternIndex><height>1</height></position>
+                <position><patternIndex>0</patternIndex><height>1</height></position>
+                <position><patternIndex>0</patternIndex><height>1</height></position>
+            </positions>
+            <patterns>
+                <pattern>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <trackIndexes><trackIndex>0</trackIndex></trackIndexes>
+                    <speedTrackIndex><trackIndex>0</trackIndex></speedTrackIndex>
+                    <eventTrackIndex><trackIndex>0</trackIndex></eventTrackIndex>
+                </pattern>
+            </patterns>
+        </subsong>
+    </subsongs>
+</song>"#;
+        load_aks(xml.as_bytes()).expect("test song should parse")
+    }
+
+    #[test]
+    fn test_capture_stops_at_loop_wrap() {
+        let capture = capture_registers(Arc::new(two_psg_song()), 0, MAX_EXPORT_FRAMES).unwrap();
+
+        // 3 positions x 1 line x speed 2
+        assert_eq!(capture.frame_count(), 6);
+        assert_eq!(capture.loop_frame, 2);
+        assert_eq!(capture.psg_frames.len(), 2);
+        assert_eq!(capture.psg_frames[1].len(), 6);
+        assert_eq!(capture.psg_frequencies, [2_000_000, 1_000_000]);
+        assert_eq!(capture.frame_rate_hz, 50.0);
+
+        // No envelope instrument plays, so R13 is never written
+        assert!(
+            capture
+                .psg_frames
+                .iter()
+                .flatten()
+                .all(|registers| registers[13] == 0xFF)
+        );
+    }
+
+    #[test]
+    fn test_capture_respects_max_frames() {
+        let capture = capture_registers(Arc::new(two_psg_song()), 0, 3).unwrap();
+        assert_eq!(capture.frame_count(), 3);
+        assert!(capture_registers(Arc::new(two_psg_song()), 0, 0).is_err());
+        assert!(capture_registers(Arc::new(two_psg_song()), 1, 10).is_err());
+    }
+
+    #[cfg(feature = "ym-export")]
+    #[test]
+    fn test_export_ym_one_file_per_psg() {
+        use ym2149_ym_replayer::parser::ym6::Ym6Parser;
+
+        let files = export_ym(&two_psg_song(), 0, false).unwrap();
+        assert_eq!(files.len(), 2);
+
+        for (index, (bytes, clock)) in files.iter().zip([2_000_000, 1_000_000]).enumerate() {
+            let (frames, header, metadata, _) = Ym6Parser.parse_full(bytes).unwrap();
+            assert_eq!(frames.len(), 6);
+            assert_eq!(header.loop_frame, 2);
+            assert_eq!(header.master_clock, clock);
+            assert_eq!(header.frame_rate, 50);
+            assert_eq!(metadata.song_name, "Export Test");
+            assert_eq!(metadata.author, "Tester");
+            assert_eq!(metadata.comment, format!("PSG {}/2", index + 1));
+        }
+    }
+}
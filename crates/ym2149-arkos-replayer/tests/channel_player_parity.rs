@@ -204,6 +204,7 @@ fn build_song_with_data(instruments: Vec<Instrument>, extra_arps: Vec<Arpeggio>)
         arpeggios,
         pitch_tables: Vec::new(),
         subsongs: vec![subsong],
+        compression: None,
     })
 }
 
@@ -0,0 +1,107 @@
+//! Benchmarks for AY playback on the emulated Z80
+//!
+//! Run with: cargo bench --bench z80_frames -p ym2149-ay-replayer
+//!
+//! The song is assembled in memory: a ZX Spectrum interrupt routine that
+//! writes all 14 sound registers through the 0xFFFD/0xBFFD ports every frame,
+//! like a typical tracker replay routine.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use ym2149_ay_replayer::{AyBlock, AyFile, AyHeader, AyPlayer, AyPoints, AySong, AySongData};
+
+/// Samples per frame at 44.1kHz and 50Hz.
+const SAMPLES_PER_FRAME: usize = 882;
+
+const INIT_ADDRESS: u16 = 0x8000;
+const INTERRUPT_ADDRESS: u16 = 0x8010;
+const TABLE_ADDRESS: u16 = 0x9000;
+
+/// INIT: a bare `RET`.
+const INIT_ROUTINE: &[u8] = &[0xC9];
+
+/// INTERRUPT: bump a frame counter and write `table[r] + counter` to R0-R13.
+const INTERRUPT_ROUTINE: &[u8] = &[
+    0x21, 0x00, 0x90, // ld hl, 0x9000      ; register table
+    0x3A, 0x00, 0x91, // ld a, (0x9100)     ; frame counter
+    0x3C, //             inc a
+    0x32, 0x00, 0x91, // ld (0x9100), a
+    0x5F, //             ld e, a
+    0xAF, //             xor a              ; register index
+    0x01, 0xFD, 0xFF, // loop: ld bc, 0xFFFD
+    0xED, 0x79, //       out (c), a         ; select register
+    0x57, //             ld d, a
+    0x7E, //             ld a, (hl)
+    0x83, //             add a, e
+    0x06, 0xBF, //       ld b, 0xBF
+    0xED, 0x79, //       out (c), a         ; write value
+    0x23, //             inc hl
+    0x7A, //             ld a, d
+    0x3C, //             inc a
+    0xFE, 0x0E, //       cp 14
+    0x20, 0xED, //       jr nz, loop
+    0xC9, //             ret
+];
+
+/// Base register values: three tones, mixer, volumes and an envelope.
+const REGISTER_TABLE: [u8; 14] = [
+    0x10, 0x01, 0x20, 0x02, 0x30, 0x03, 0x10, 0x38, 0x0F, 0x0C, 0x08, 0x00, 0x10, 0x0E,
+];
+
+fn block(address: u16, data: Vec<u8>) -> AyBlock {
+    AyBlock {
+        address,
+        length: data.len() as u16,
+        data,
+    }
+}
+
+fn register_writer_song() -> AyFile {
+    let mut code = vec![0u8; usize::from(INTERRUPT_ADDRESS - INIT_ADDRESS)];
+    code[..INIT_ROUTINE.len()].copy_from_slice(INIT_ROUTINE);
+    code.extend_from_slice(INTERRUPT_ROUTINE);
+
+    AyFile {
+        header: AyHeader {
+            song_count: 1,
+            ..Default::default()
+        },
+        songs: vec![AySong {
+            name: "Register writer".to_string(),
+            data: AySongData {
+                channel_map: [0, 1, 2, 3],
+                points: Some(AyPoints {
+                    stack: 0xF000,
+                    init: INIT_ADDRESS,
+                    interrupt: INTERRUPT_ADDRESS,
+                }),
+                blocks: vec![
+                    block(INIT_ADDRESS, code),
+                    block(TABLE_ADDRESS, REGISTER_TABLE.to_vec()),
+                ],
+                ..Default::default()
+            },
+        }],
+    }
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let mut player = AyPlayer::new(register_writer_song(), 0).expect("song loads");
+    player.play().expect("song plays");
+    let mut buffer = vec![0.0f32; SAMPLES_PER_FRAME];
+
+    let mut group = c.benchmark_group("ay_frames");
+    group.throughput(Throughput::Elements(SAMPLES_PER_FRAME as u64));
+
+    group.bench_function("zx_register_writes", |b| {
+        b.iter(|| {
+            player.generate_samples_into(&mut buffer);
+            black_box(&buffer);
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_frames);
+criterion_main!(benches);
@@ -44,6 +44,14 @@ pub enum AyError {
         /// Offset where block parsing stopped.
         offset: usize,
     },
+    /// Requested song index does not exist in the file.
+    #[error("song index {index} out of range ({available} available)")]
+    SubsongOutOfRange {
+        /// Requested zero-based song index.
+        index: usize,
+        /// Number of songs in the file.
+        available: usize,
+    },
     /// Generic validation error.
     #[error("{msg}")]
     InvalidData {
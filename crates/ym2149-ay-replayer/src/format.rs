@@ -2,6 +2,7 @@
 
 /// Parsed AY file with header information and available songs.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AyFile {
     /// Header metadata.
     pub header: AyHeader,
@@ -11,6 +12,7 @@ pub struct AyFile {
 
 /// AY file header metadata.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AyHeader {
     /// Container format version.
     pub file_version: u16,
@@ -30,6 +32,7 @@ pub struct AyHeader {
 
 /// AY song entry.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AySong {
     /// Song title as stored in the AY file.
     pub name: String,
@@ -39,6 +42,7 @@ pub struct AySong {
 
 /// Metadata and PSG/memory layout for a single AY song.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AySongData {
     /// Channel routing (Amiga channel order A/B/C/Noise).
     pub channel_map: [u8; 4],
@@ -58,6 +62,7 @@ pub struct AySongData {
 
 /// Z80 register setup extracted from the Points structure.
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AyPoints {
     /// Initial stack pointer.
     pub stack: u16,
@@ -69,6 +74,7 @@ pub struct AyPoints {
 
 /// Memory block definition (address + data payload).
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AyBlock {
     /// Load address inside the Z80 memory map.
     pub address: u16,
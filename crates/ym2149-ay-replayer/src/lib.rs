@@ -4,19 +4,28 @@
 //! - Robust parser that understands the ZXAY/EMUL container format
 //! - Structured representation of metadata, song entries, and memory blocks
 //! - (Upcoming) high-level player that can execute the embedded Z80 players
+//! - Pluggable target machines ([`AyMachine`]) for the Z80 player
+//!
+//! Parsing never panics on malformed data: every pointer and block is
+//! bounds-checked and rejected with an [`AyError`]. `tests/hostile_input.rs`
+//! and the `load_ay` fuzz target check this.
 
 #![warn(missing_docs)]
 
 pub mod error;
 pub mod format;
-mod machine;
+pub mod machine;
 mod parser;
 pub mod player;
 
 pub use crate::error::{AyError, Result};
 pub use crate::format::{AyBlock, AyFile, AyHeader, AyPoints, AySong, AySongData};
+pub use crate::machine::{AutoDetect, AyMachine, Cpc, FrameInterrupt, PsgBus, Zx48, Zx128};
 pub use crate::parser::load_ay;
-pub use crate::player::{AyMetadata, AyPlayer, CPC_UNSUPPORTED_MSG};
+pub use crate::player::{AyMetadata, AyPlayer, AyPlayerState, CPC_UNSUPPORTED_MSG};
+
+// Machine timing from the shared Z80 core
+pub use ym2149_z80::{Contention, Timing};
 
 // Re-export unified player trait from ym2149-common
 pub use ym2149_common::{ChiptunePlayer, PlaybackMetadata, PlaybackState};
@@ -107,4 +116,20 @@ mod tests {
             "expected waveform data"
         );
     }
+
+    #[test]
+    fn ay_player_load_state_resumes_identically() {
+        let (mut player, _) = AyPlayer::load_from_bytes(SPACE_MADNESS, 0).unwrap();
+        player.play().unwrap();
+        player.generate_samples(10_000);
+        let state = player.save_state();
+        let expected = player.generate_samples(10_000);
+
+        let (mut other, _) = AyPlayer::load_from_bytes(SPACE_MADNESS, 0).unwrap();
+        other.load_state(&state).unwrap();
+        assert_eq!(other.playback_state(), PlaybackState::Paused);
+        other.play().unwrap();
+        assert_eq!(other.generate_samples(10_000), expected);
+        assert_eq!(other.current_frame(), player.current_frame());
+    }
 }
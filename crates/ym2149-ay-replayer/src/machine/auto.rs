@@ -0,0 +1,121 @@
+//! Machine detection from the ports a song uses.
+
+use super::cpc::{CPC_PSG_CLOCKS, Cpc, PlayCityPort};
+use super::zx::{spectrum_port_in, spectrum_port_out};
+use super::{AyMachine, MEMORY_SIZE, PsgBus};
+
+/// Spectrum PSG clock, alone and with PlayCity chips.
+const ZX_PSG_CLOCKS: [u32; 3] = [2_000_000; 3];
+
+/// The player's default machine: most Project AY files do not say which
+/// computer they come from.
+///
+/// It starts as a Spectrum (3.5 MHz Z80, 2 MHz PSG) and becomes a CPC
+/// (4 MHz Z80, 1 MHz PSG) on the first PPI access. Such songs usually rely
+/// on the CPC firmware, so that access also reports
+/// [`requires_firmware`](AyMachine::requires_firmware). PlayCity ports work
+/// on either.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AutoDetect {
+    cpc: Cpc,
+    cpc_active: bool,
+    requires_firmware: bool,
+}
+
+impl AutoDetect {
+    /// Whether CPC port accesses have been detected.
+    pub fn is_cpc(&self) -> bool {
+        self.cpc_active
+    }
+
+    fn clocks(&self, playcity: bool) -> &'static [u32] {
+        let clocks = if self.cpc_active {
+            &CPC_PSG_CLOCKS
+        } else {
+            &ZX_PSG_CLOCKS
+        };
+        if playcity { clocks } else { &clocks[..1] }
+    }
+}
+
+impl AyMachine for AutoDetect {
+    fn name(&self) -> &'static str {
+        if self.cpc_active {
+            "Amstrad CPC (detected)"
+        } else {
+            "ZX Spectrum (detected)"
+        }
+    }
+
+    fn cpu_clock_hz(&self) -> u32 {
+        if self.cpc_active {
+            4_000_000
+        } else {
+            3_500_000
+        }
+    }
+
+    fn psg_clocks(&self) -> &[u32] {
+        self.clocks(self.cpc.has_playcity())
+    }
+
+    fn reset(&mut self, memory: &mut [u8; MEMORY_SIZE]) {
+        *self = Self::default();
+        super::ay_memory_layout(memory);
+    }
+
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        // PlayCity ports are fully decoded, so check them before the ZX
+        // ports they would otherwise alias
+        if let Some(port) = PlayCityPort::decode(address) {
+            self.cpc.enable_playcity(psgs, self.clocks(true));
+            port.access(value, psgs);
+            return;
+        }
+        if spectrum_port_out(address, value, psgs) {
+            return;
+        }
+        if Cpc::is_ppi_port(address) {
+            if !self.cpc_active {
+                self.cpc_active = true;
+                psgs.set_clocks(self.psg_clocks());
+            }
+            self.requires_firmware = true;
+            self.cpc.ppi_out(address, value, psgs);
+        }
+    }
+
+    fn port_in(&mut self, address: u16, psgs: &mut PsgBus<'_>) -> u8 {
+        if self.cpc_active {
+            self.cpc.port_in(address, psgs)
+        } else {
+            spectrum_port_in(address, psgs).unwrap_or(0xFF)
+        }
+    }
+
+    fn requires_firmware(&self) -> bool {
+        self.requires_firmware
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        let mut state = self.cpc.save_state();
+        state.extend([u8::from(self.cpc_active), u8::from(self.requires_firmware)]);
+        state
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> bool {
+        let [cpc @ .., cpc_active, requires_firmware] = state else {
+            return false;
+        };
+        let mut detected = Cpc::default();
+        if !detected.load_state(cpc) {
+            return false;
+        }
+        *self = Self {
+            cpc: detected,
+            cpc_active: *cpc_active != 0,
+            requires_firmware: *requires_firmware != 0,
+        };
+        true
+    }
+}
@@ -0,0 +1,169 @@
+//! Amstrad CPC.
+//!
+//! The PSG sits behind the 8255 PPI: port A (0xF4xx) carries the data bus
+//! and bits 7/6 of port C (0xF6xx) drive BDIR/BC1. The PlayCity cartridge
+//! adds two YMZ294s on fully decoded ports; the first access to one of
+//! them adds the chips as PSGs 1 (right) and 2 (left).
+
+use super::{AyMachine, FrameInterrupt, PsgBus};
+
+const CPC_DATA_BUS_MASK: u16 = 0xFF00;
+const CPC_PORT_A: u16 = 0xF400;
+const CPC_PORT_C: u16 = 0xF600;
+/// PlayCity register select / data ports: right chip, then left chip
+const PLAYCITY_SELECT_PORTS: [u16; 2] = [0xF984, 0xF988];
+const PLAYCITY_DATA_PORTS: [u16; 2] = [0xF884, 0xF888];
+
+/// CPC PSG clock, alone and with the 2 MHz PlayCity chips.
+pub(super) const CPC_PSG_CLOCKS: [u32; 3] = [1_000_000, 2_000_000, 2_000_000];
+
+/// A decoded PlayCity port.
+pub(super) enum PlayCityPort {
+    /// Register select of PSG 1 or 2
+    Select(usize),
+    /// Data write to PSG 1 or 2
+    Data(usize),
+}
+
+impl PlayCityPort {
+    pub(super) fn decode(address: u16) -> Option<Self> {
+        if let Some(index) = PLAYCITY_SELECT_PORTS
+            .iter()
+            .position(|&port| port == address)
+        {
+            return Some(Self::Select(index + 1));
+        }
+        PLAYCITY_DATA_PORTS
+            .iter()
+            .position(|&port| port == address)
+            .map(|index| Self::Data(index + 1))
+    }
+
+    /// Forward the access; the PlayCity chips must already be present.
+    pub(super) fn access(self, value: u8, psgs: &mut PsgBus<'_>) {
+        match self {
+            Self::Select(psg) => psgs.select(psg, value & 0x0F),
+            Self::Data(psg) => psgs.write(psg, value),
+        }
+    }
+}
+
+/// Amstrad CPC 464/664/6128 with optional PlayCity.
+///
+/// No firmware is emulated: drivers that call the CPC jump blocks will not
+/// play.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cpc {
+    bus_latch: u8,
+    control: u8,
+    playcity: bool,
+}
+
+impl Cpc {
+    /// Whether `address` is the PPI port A or C.
+    pub(super) fn is_ppi_port(address: u16) -> bool {
+        matches!(address & CPC_DATA_BUS_MASK, CPC_PORT_A | CPC_PORT_C)
+    }
+
+    /// Whether the PlayCity ports were accessed.
+    pub(super) fn has_playcity(&self) -> bool {
+        self.playcity
+    }
+
+    /// Add the PlayCity chips on first use; `clocks` is the machine's clock
+    /// list once they are present.
+    pub(super) fn enable_playcity(&mut self, psgs: &mut PsgBus<'_>, clocks: &[u32]) {
+        if !self.playcity {
+            self.playcity = true;
+            psgs.set_clocks(clocks);
+        }
+    }
+
+    /// Decode a write to the PPI ports.
+    pub(super) fn ppi_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        match address & CPC_DATA_BUS_MASK {
+            CPC_PORT_A => self.bus_latch = value,
+            CPC_PORT_C => {
+                self.control = value;
+                self.apply_control(psgs);
+            }
+            _ => {}
+        }
+    }
+
+    /// Run the PSG bus cycle selected by BDIR/BC1.
+    fn apply_control(&mut self, psgs: &mut PsgBus<'_>) {
+        let bdir = (self.control & 0x80) != 0;
+        let bc1 = (self.control & 0x40) != 0;
+        match (bc1, bdir) {
+            (true, true) => psgs.select(0, self.bus_latch & 0x0F),
+            (false, true) => psgs.write(0, self.bus_latch),
+            (true, false) => self.bus_latch = psgs.read(0),
+            (false, false) => {}
+        }
+    }
+}
+
+impl AyMachine for Cpc {
+    fn name(&self) -> &'static str {
+        "Amstrad CPC"
+    }
+
+    fn cpu_clock_hz(&self) -> u32 {
+        4_000_000
+    }
+
+    fn psg_clocks(&self) -> &[u32] {
+        if self.playcity {
+            &CPC_PSG_CLOCKS
+        } else {
+            &CPC_PSG_CLOCKS[..1]
+        }
+    }
+
+    /// The gate array interrupts at 300 Hz; players run every sixth one.
+    fn frame_interrupt(&self) -> FrameInterrupt {
+        FrameInterrupt {
+            rate_hz: 300,
+            divider: 6,
+        }
+    }
+
+    fn reset(&mut self, memory: &mut [u8; super::MEMORY_SIZE]) {
+        *self = Self::default();
+        super::ay_memory_layout(memory);
+    }
+
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        if let Some(port) = PlayCityPort::decode(address) {
+            self.enable_playcity(psgs, &CPC_PSG_CLOCKS);
+            port.access(value, psgs);
+        } else {
+            self.ppi_out(address, value, psgs);
+        }
+    }
+
+    fn port_in(&mut self, address: u16, _psgs: &mut PsgBus<'_>) -> u8 {
+        if address & CPC_DATA_BUS_MASK == CPC_PORT_A {
+            self.bus_latch
+        } else {
+            0xFF
+        }
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        vec![self.bus_latch, self.control, u8::from(self.playcity)]
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> bool {
+        let &[bus_latch, control, playcity] = state else {
+            return false;
+        };
+        *self = Self {
+            bus_latch,
+            control,
+            playcity: playcity != 0,
+        };
+        true
+    }
+}
@@ -0,0 +1,353 @@
+//! Target machines for the embedded Z80 players.
+//!
+//! An [`AyMachine`] describes the computer a song was ripped from: its
+//! clocks, the memory layout the player starts with, how I/O ports reach
+//! the PSGs, how often the interrupt routine runs and the Z80 timing
+//! ([`Timing`], e.g. Spectrum memory contention). The player core only
+//! runs the Z80 and renders the PSGs, so a new target (e.g. the MSX) is a
+//! trait implementation passed to [`AyPlayer::with_machine`].
+//!
+//! Included machines:
+//! - [`Zx48`] - ZX Spectrum 48K with an AY interface on the 128K ports
+//! - [`Zx128`] - ZX Spectrum 128K/+2/+3
+//! - [`Cpc`] - Amstrad CPC, PSG behind the 8255 PPI, plus PlayCity
+//! - [`AutoDetect`] - Spectrum ports that switch to the CPC on its first PPI
+//!   access (the default)
+//!
+//! [`AyPlayer::with_machine`]: crate::AyPlayer::with_machine
+
+mod auto;
+mod cpc;
+mod zx;
+
+pub use auto::AutoDetect;
+pub use cpc::Cpc;
+pub use zx::{Zx48, Zx128};
+
+use ym2149::{PsgBank, Ym2149, Ym2149Backend, Ym2149State};
+use ym2149_z80::{Timing, Z80Bus};
+
+use crate::format::AyBlock;
+
+/// Size of the Z80 address space.
+pub const MEMORY_SIZE: usize = 0x10000;
+
+/// How often a machine calls the song's interrupt routine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInterrupt {
+    /// Interrupts raised per second.
+    pub rate_hz: u32,
+    /// Interrupts per call of the interrupt routine.
+    pub divider: u32,
+}
+
+impl FrameInterrupt {
+    /// One call per 50 Hz video frame.
+    pub const PAL: Self = Self {
+        rate_hz: 50,
+        divider: 1,
+    };
+
+    /// Calls of the interrupt routine per second.
+    pub fn call_rate_hz(self) -> f32 {
+        self.rate_hz as f32 / self.divider.max(1) as f32
+    }
+}
+
+/// A computer the embedded Z80 player runs on.
+///
+/// Implementations own their bus latches and decode port accesses into
+/// PSG operations through [`PsgBus`]. PSG 0 is the machine's own chip;
+/// expansions such as PlayCity add more by calling [`PsgBus::set_clocks`].
+pub trait AyMachine: Send + Sync {
+    /// Human-readable machine name.
+    fn name(&self) -> &'static str;
+
+    /// Z80 clock in Hz.
+    fn cpu_clock_hz(&self) -> u32;
+
+    /// Master clock of every PSG in use, starting with the machine's chip.
+    fn psg_clocks(&self) -> &[u32];
+
+    /// When the interrupt routine runs.
+    fn frame_interrupt(&self) -> FrameInterrupt {
+        FrameInterrupt::PAL
+    }
+
+    /// T-states between calls of the interrupt routine and the memory
+    /// contention the Z80 runs with. This sets how many samples each call
+    /// renders; the default is an uncontended frame of the CPU clock
+    /// divided by the call rate.
+    fn timing(&self) -> Timing {
+        let frame = self.cpu_clock_hz() as f32 / self.frame_interrupt().call_rate_hz();
+        Timing::uncontended(frame.round() as u32)
+    }
+
+    /// Clear the bus latches and lay out memory before the song's blocks
+    /// load. The default is the Project AY layout ([`ay_memory_layout`]).
+    fn reset(&mut self, memory: &mut [u8; MEMORY_SIZE]) {
+        ay_memory_layout(memory);
+    }
+
+    /// Decode an `OUT` to `address`.
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>);
+
+    /// Decode an `IN` from `address`; unmapped ports read 0xFF.
+    fn port_in(&mut self, address: u16, psgs: &mut PsgBus<'_>) -> u8 {
+        let _ = (address, psgs);
+        0xFF
+    }
+
+    /// Whether the song called into firmware the machine does not provide.
+    fn requires_firmware(&self) -> bool {
+        false
+    }
+
+    /// Bus latches for player snapshots.
+    fn save_state(&self) -> Vec<u8> {
+        Vec::new()
+    }
+
+    /// Restore latches from [`save_state`](Self::save_state); returns
+    /// `false` (leaving the machine untouched) for a foreign capture.
+    fn load_state(&mut self, state: &[u8]) -> bool {
+        state.is_empty()
+    }
+}
+
+/// Memory layout Project AY players expect: `RET` over 0x0000-0x00FF with
+/// `EI` at the IM 1 vector 0x0038, 0xFF up to 0x3FFF and cleared RAM above.
+pub fn ay_memory_layout(memory: &mut [u8; MEMORY_SIZE]) {
+    memory[..=0x00FF].fill(0xC9);
+    memory[0x0100..=0x3FFF].fill(0xFF);
+    memory[0x4000..].fill(0x00);
+    memory[0x0038] = 0xFB;
+}
+
+/// The PSGs as a machine's port decoder sees them.
+///
+/// Register selection uses each chip's own address latch, so reads follow
+/// the chip's read-back behaviour. Accesses to PSGs that do not exist are
+/// ignored and read 0xFF.
+pub struct PsgBus<'a> {
+    psgs: &'a mut PsgBank,
+}
+
+impl<'a> PsgBus<'a> {
+    pub(crate) fn new(psgs: &'a mut PsgBank) -> Self {
+        Self { psgs }
+    }
+
+    /// Number of PSGs in use.
+    pub fn psg_count(&self) -> usize {
+        self.psgs.psg_count()
+    }
+
+    /// Latch the register address of PSG `psg`.
+    pub fn select(&mut self, psg: usize, register: u8) {
+        if let Some(chip) = self.chip_mut(psg) {
+            chip.write_port_immediate(0, register);
+        }
+    }
+
+    /// Write `value` to the selected register of PSG `psg`.
+    pub fn write(&mut self, psg: usize, value: u8) {
+        if let Some(chip) = self.chip_mut(psg) {
+            chip.write_port_immediate(2, value);
+        }
+    }
+
+    /// Read the selected register of PSG `psg`.
+    pub fn read(&self, psg: usize) -> u8 {
+        if psg < self.psgs.psg_count() {
+            self.psgs.get_chip(psg).read_port(0)
+        } else {
+            0xFF
+        }
+    }
+
+    /// Direct access to PSG `psg`, e.g. to wire its I/O ports.
+    pub fn chip_mut(&mut self, psg: usize) -> Option<&mut Ym2149> {
+        (psg < self.psgs.psg_count()).then(|| self.psgs.get_chip_mut(psg))
+    }
+
+    /// Run the PSGs at `clocks`, adding or removing chips as needed.
+    ///
+    /// Chips that stay keep their state; new ones follow PSG 0's output
+    /// settings. An empty list is ignored.
+    pub fn set_clocks(&mut self, clocks: &[u32]) {
+        let count = self.psgs.psg_count();
+        if clocks.is_empty()
+            || (clocks.len() == count
+                && clocks
+                    .iter()
+                    .enumerate()
+                    .all(|(index, &clock)| self.psgs.get_frequency(index) == clock))
+        {
+            return;
+        }
+        let mut psgs = PsgBank::new_with_sample_rate(clocks.to_vec(), self.psgs.sample_rate());
+        for index in 0..psgs.psg_count() {
+            let existing = index < count;
+            let current = self.psgs.get_chip(if existing { index } else { 0 });
+            let chip = psgs.get_chip_mut(index);
+            if existing {
+                chip.load_state(&current.save_state());
+            }
+            chip.set_filter_chain(current.filter_chain());
+            chip.set_channel_tap(current.channel_tap().map(|tap| tap.capacity()));
+            for channel in 0..3 {
+                chip.set_channel_mute(channel, existing && current.is_channel_muted(channel));
+            }
+        }
+        *self.psgs = psgs;
+    }
+}
+
+/// Memory, bus and chip state of a [`MachineBus`], for player snapshots.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct MachineBusState {
+    memory: Box<[u8]>,
+    chip: Ym2149State,
+    #[cfg_attr(feature = "serde", serde(default, alias = "playcity_chips"))]
+    expansion_chips: Vec<Ym2149State>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    machine: Vec<u8>,
+}
+
+/// Memory and PSGs of the emulated computer, wired by an [`AyMachine`].
+pub(crate) struct MachineBus {
+    memory: [u8; MEMORY_SIZE],
+    psgs: PsgBank,
+    machine: Box<dyn AyMachine>,
+    #[cfg(feature = "trace-ports")]
+    port_log: Vec<String>,
+}
+
+impl MachineBus {
+    /// Create the bus for `machine` with fresh PSGs.
+    pub(crate) fn new(machine: Box<dyn AyMachine>, sample_rate: u32) -> Self {
+        Self {
+            memory: [0; MEMORY_SIZE],
+            psgs: PsgBank::new_with_sample_rate(machine.psg_clocks().to_vec(), sample_rate),
+            machine,
+            #[cfg(feature = "trace-ports")]
+            port_log: Vec::new(),
+        }
+    }
+
+    /// Reset the machine, its memory layout and the PSGs.
+    pub(crate) fn reset_layout(&mut self) {
+        self.machine.reset(&mut self.memory);
+        self.psgs.reset();
+        PsgBus::new(&mut self.psgs).set_clocks(self.machine.psg_clocks());
+    }
+
+    /// Load block payload into memory (clamped to 64K).
+    pub(crate) fn load_block(&mut self, block: &AyBlock) {
+        let start = block.address as usize;
+        let end = start
+            .saturating_add(block.length as usize)
+            .min(self.memory.len());
+        let data_len = end - start;
+        if data_len == 0 {
+            return;
+        }
+        self.memory[start..end].copy_from_slice(&block.data[..data_len]);
+    }
+
+    /// The machine wiring the bus.
+    pub(crate) fn machine(&self) -> &dyn AyMachine {
+        self.machine.as_ref()
+    }
+
+    /// Access the machine's own chip, PSG 0 (immutable).
+    pub(crate) fn chip(&self) -> &Ym2149 {
+        self.psgs.get_chip(0)
+    }
+
+    /// Access the machine's own chip, PSG 0 (mutable).
+    pub(crate) fn chip_mut(&mut self) -> &mut Ym2149 {
+        self.psgs.get_chip_mut(0)
+    }
+
+    /// All PSGs: the machine's chip plus any expansion chips.
+    pub(crate) fn psgs(&self) -> &PsgBank {
+        &self.psgs
+    }
+
+    /// All PSGs (mutable).
+    pub(crate) fn psgs_mut(&mut self) -> &mut PsgBank {
+        &mut self.psgs
+    }
+
+    /// Number of PSGs in use.
+    pub(crate) fn psg_count(&self) -> usize {
+        self.psgs.psg_count()
+    }
+
+    /// Clock every PSG by one sample and return the mix.
+    pub(crate) fn clock_sample(&mut self) -> f32 {
+        self.psgs.clock_sample()
+    }
+
+    #[cfg(feature = "trace-ports")]
+    pub(crate) fn take_port_log(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.port_log)
+    }
+
+    /// Capture memory, machine latches and chip state.
+    pub(crate) fn save_state(&self) -> MachineBusState {
+        MachineBusState {
+            memory: self.memory.into(),
+            chip: self.chip().save_state(),
+            expansion_chips: (1..self.psgs.psg_count())
+                .map(|index| self.psgs.get_chip(index).save_state())
+                .collect(),
+            machine: self.machine.save_state(),
+        }
+    }
+
+    /// Restore a capture from [`save_state`](Self::save_state).
+    ///
+    /// Returns `false` (leaving the bus untouched) if the memory image is
+    /// not 64K or the machine rejects its latches.
+    pub(crate) fn load_state(&mut self, state: &MachineBusState) -> bool {
+        if state.memory.len() != self.memory.len() || !self.machine.load_state(&state.machine) {
+            return false;
+        }
+        PsgBus::new(&mut self.psgs).set_clocks(self.machine.psg_clocks());
+        self.memory.copy_from_slice(&state.memory);
+        self.chip_mut().load_state(&state.chip);
+        let count = self.psgs.psg_count();
+        for (index, chip) in state.expansion_chips.iter().enumerate() {
+            if index + 1 < count {
+                self.psgs.get_chip_mut(index + 1).load_state(chip);
+            }
+        }
+        true
+    }
+}
+
+impl Z80Bus for MachineBus {
+    fn peek(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+    }
+
+    fn port_in(&mut self, address: u16) -> u8 {
+        self.machine
+            .port_in(address, &mut PsgBus::new(&mut self.psgs))
+    }
+
+    fn port_out(&mut self, address: u16, value: u8) {
+        #[cfg(feature = "trace-ports")]
+        self.port_log.push(format!("out {address:04X} {value:02X}"));
+        self.machine
+            .port_out(address, value, &mut PsgBus::new(&mut self.psgs));
+    }
+}
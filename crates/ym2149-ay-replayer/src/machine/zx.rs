@@ -0,0 +1,91 @@
+//! ZX Spectrum machines.
+//!
+//! Both models reach the AY through the 128K ports: register select at
+//! 0xFFFD and data at 0xBFFD, decoded on A15, A14 and A1. Reading 0xFFFD
+//! returns the selected register. Project AY files hold a flat 64K image,
+//! so 128K memory paging (0x7FFD) is not modelled. Both run with ULA
+//! contention on 0x4000-0x7FFF and the real frame length, which is
+//! slightly shorter than 1/50 s.
+
+use ym2149_z80::Timing;
+
+use super::{AyMachine, PsgBus};
+
+const ZX_PORT_MASK: u16 = 0xC002;
+const ZX_REG_PORT: u16 = 0xC000;
+const ZX_DATA_PORT: u16 = 0x8000;
+
+/// Decode a write to the Spectrum AY ports; returns `false` for other ports.
+pub(super) fn spectrum_port_out(address: u16, value: u8, psgs: &mut PsgBus<'_>) -> bool {
+    match address & ZX_PORT_MASK {
+        ZX_REG_PORT => psgs.select(0, value & 0x0F),
+        ZX_DATA_PORT => psgs.write(0, value),
+        _ => return false,
+    }
+    true
+}
+
+/// Decode a read from the Spectrum AY ports.
+pub(super) fn spectrum_port_in(address: u16, psgs: &PsgBus<'_>) -> Option<u8> {
+    (address & ZX_PORT_MASK == ZX_REG_PORT).then(|| psgs.read(0))
+}
+
+/// ZX Spectrum 48K with an AY interface (Melodik style) on the 128K ports.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zx48;
+
+impl AyMachine for Zx48 {
+    fn name(&self) -> &'static str {
+        "ZX Spectrum 48K"
+    }
+
+    fn cpu_clock_hz(&self) -> u32 {
+        3_500_000
+    }
+
+    fn psg_clocks(&self) -> &[u32] {
+        &[1_750_000]
+    }
+
+    fn timing(&self) -> Timing {
+        Timing::ZX48
+    }
+
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        spectrum_port_out(address, value, psgs);
+    }
+
+    fn port_in(&mut self, address: u16, psgs: &mut PsgBus<'_>) -> u8 {
+        spectrum_port_in(address, psgs).unwrap_or(0xFF)
+    }
+}
+
+/// ZX Spectrum 128K, +2 and +3.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Zx128;
+
+impl AyMachine for Zx128 {
+    fn name(&self) -> &'static str {
+        "ZX Spectrum 128K"
+    }
+
+    fn cpu_clock_hz(&self) -> u32 {
+        3_546_900
+    }
+
+    fn psg_clocks(&self) -> &[u32] {
+        &[1_773_400]
+    }
+
+    fn timing(&self) -> Timing {
+        Timing::ZX128
+    }
+
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        spectrum_port_out(address, value, psgs);
+    }
+
+    fn port_in(&mut self, address: u16, psgs: &mut PsgBus<'_>) -> u8 {
+        spectrum_port_in(address, psgs).unwrap_or(0xFF)
+    }
+}
@@ -48,7 +48,7 @@ impl<'a> AyParser<'a> {
             return Err(AyError::InvalidData {
                 msg: format!(
                     "first song index {} outside available {} songs",
-                    raw_first_song + 1,
+                    u16::from(raw_first_song) + 1,
                     song_count
                 ),
             });
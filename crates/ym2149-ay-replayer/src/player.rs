@@ -1,23 +1,22 @@
 //! High-level AY song player (Z80 + YM2149 bridge).
 
-use iz80::{Cpu, Machine, Reg8, Reg16};
 use std::mem;
 
 use crate::error::{AyError, Result};
 use crate::format::{AyFile, AyPoints, AySong};
-use crate::machine::AyMachine;
+use crate::machine::{AutoDetect, AyMachine, MachineBus, MachineBusState};
 use ym2149::Ym2149Backend;
 use ym2149_common::{
-    ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE, FRAME_RATE_PAL, MetadataFields,
-    PlaybackState,
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE, FRAME_RATE_PAL,
+    LoadReport, MetadataFields, OutputGain, OutputModel, PlaybackRate, PlaybackState,
+    RegisterDelta,
 };
+use ym2149_z80::{DefaultZ80, RegisterPair, Z80, Z80Bus, Z80Cpu};
 
 const SAMPLE_RATE: u32 = DEFAULT_SAMPLE_RATE;
 const FRAME_RATE_HZ: f32 = FRAME_RATE_PAL as f32;
 const RETURN_ADDRESS: u16 = 0x0000;
 const MAX_INSTRUCTIONS_PER_CALL: usize = 250_000;
-const ZX_CPU_CLOCK_HZ: f64 = 3_500_000.0;
-const CPC_CPU_CLOCK_HZ: f64 = 4_000_000.0;
 /// User-facing message when CPC AY playback is attempted.
 pub const CPC_UNSUPPORTED_MSG: &str =
     "CPC AY songs currently require full CPC firmware emulation, which is not supported";
@@ -52,6 +51,21 @@ pub struct AyMetadata {
     pub player_version: u8,
 }
 
+impl AyMetadata {
+    /// Describe one song of a parsed file without setting up the Z80 machine.
+    ///
+    /// Returns `None` when `song_index` is out of range.
+    pub fn from_file(file: &AyFile, song_index: usize) -> Option<Self> {
+        let song = file.songs.get(song_index)?;
+        Some(build_metadata(
+            &file.header,
+            song_index,
+            file.songs.len(),
+            song,
+        ))
+    }
+}
+
 impl MetadataFields for AyMetadata {
     fn title(&self) -> &str {
         &self.song_name
@@ -95,16 +109,37 @@ impl AyMetadata {
     }
 }
 
+/// Snapshot of an [`AyPlayer`]'s emulation state
+///
+/// Captured by [`AyPlayer::save_state`] and restored with
+/// [`AyPlayer::load_state`] into a player for the same song. Holds the Z80,
+/// the 64K memory image, the chip and the frame position, so playback
+/// continues sample-exactly. Playback settings (mutes, gain, rate, output
+/// model) are not included.
+///
+/// With the `serde` feature the state can be serialized.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AyPlayerState {
+    init_address: u16,
+    interrupt_address: u16,
+    cpu: Vec<u8>,
+    machine: MachineBusState,
+    sample_cache: Vec<f32>,
+    cache_pos: usize,
+    frame_counter: usize,
+    init_executed: bool,
+}
+
 /// High-level AY song player.
 pub struct AyPlayer {
-    song: AySong,
+    file: AyFile,
     metadata: AyMetadata,
     points: AyPoints,
     init_address: u16,
     interrupt_address: u16,
-    machine: AyMachine,
-    cpu: Cpu,
-    samples_per_frame: usize,
+    bus: MachineBus,
+    z80: Z80,
     sample_cache: Vec<f32>,
     cache_pos: usize,
     cache_len: usize,
@@ -113,55 +148,57 @@ pub struct AyPlayer {
     state: PlaybackState,
     init_executed: bool,
     sample_period: f64,
+    output_gain: OutputGain,
+    playback_rate: PlaybackRate,
 }
 
 impl AyPlayer {
     /// Create a player for the selected song index.
+    ///
+    /// The song runs on [`AutoDetect`], which picks the Spectrum or the CPC
+    /// from the ports the song uses.
     pub fn new(file: AyFile, song_index: usize) -> Result<Self> {
-        if song_index >= file.songs.len() {
-            return Err(AyError::InvalidData {
-                msg: format!(
-                    "Song index {song_index} out of range ({} available)",
-                    file.songs.len()
-                ),
-            });
-        }
-
-        let header = file.header;
-        let song = file.songs[song_index].clone();
-        let points = song
-            .data
-            .points
-            .clone()
-            .ok_or_else(|| AyError::InvalidData {
-                msg: "AY song is missing points data".to_string(),
-            })?;
-        let init_address = resolve_init_address(&song, &points)?;
-        let interrupt_address = if points.interrupt != 0 {
-            points.interrupt
-        } else {
-            init_address
-        };
-
-        let samples_per_frame = (SAMPLE_RATE as f32 / FRAME_RATE_HZ).round() as usize;
-        let metadata = build_metadata(&header, song_index, file.songs.len(), &song);
+        Self::with_machine(file, song_index, Box::new(AutoDetect::default()))
+    }
+
+    /// Create a player that runs the selected song on `machine`.
+    ///
+    /// ```no_run
+    /// use ym2149_ay_replayer::{AyPlayer, Zx128, load_ay};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let file = load_ay(&std::fs::read("song.ay")?)?;
+    /// let mut player = AyPlayer::with_machine(file, 0, Box::new(Zx128))?;
+    /// player.play()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_machine(
+        file: AyFile,
+        song_index: usize,
+        machine: Box<dyn AyMachine>,
+    ) -> Result<Self> {
+        let entry = SongEntry::resolve(&file, song_index)?;
+        let song = &file.songs[song_index];
+        let timing = machine.timing();
         let mut player = Self {
-            song,
-            metadata,
-            points,
-            init_address,
-            interrupt_address,
-            machine: AyMachine::new(SAMPLE_RATE),
-            cpu: Cpu::new(),
-            samples_per_frame,
-            sample_cache: Vec::with_capacity(samples_per_frame),
+            metadata: build_metadata(&file.header, song_index, file.songs.len(), song),
+            max_frames: frame_limit(song),
+            points: entry.points,
+            init_address: entry.init_address,
+            interrupt_address: entry.interrupt_address,
+            file,
+            bus: MachineBus::new(machine, SAMPLE_RATE),
+            z80: Z80::new(timing),
+            sample_cache: Vec::new(),
             cache_pos: 0,
             cache_len: 0,
             frame_counter: 0,
-            max_frames: frame_limit(&file.songs[song_index]),
             state: PlaybackState::Stopped,
             init_executed: false,
             sample_period: 1.0 / SAMPLE_RATE as f64,
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
         };
 
         player.reset_runtime()?;
@@ -171,24 +208,33 @@ impl AyPlayer {
     /// Helper that parses bytes and builds both metadata + player.
     pub fn load_from_bytes(data: &[u8], song_index: usize) -> Result<(Self, AyMetadata)> {
         let file = crate::parser::load_ay(data)?;
-        if song_index >= file.songs.len() {
-            return Err(AyError::InvalidData {
-                msg: format!(
-                    "Song index {song_index} out of range ({} available)",
-                    file.songs.len()
-                ),
-            });
-        }
-        let metadata_stub = build_metadata(
-            &file.header,
-            song_index,
-            file.songs.len(),
-            &file.songs[song_index],
-        );
+        let metadata_stub =
+            AyMetadata::from_file(&file, song_index).ok_or(AyError::SubsongOutOfRange {
+                index: song_index,
+                available: file.songs.len(),
+            })?;
         let player = AyPlayer::new(file, song_index)?;
         Ok((player, metadata_stub))
     }
 
+    /// Switch to another song of the loaded file (0-based, like [`new`](Self::new)).
+    ///
+    /// Reuses the parsed file and reinitializes the Z80 machine in place, so
+    /// the song starts from the beginning without re-parsing the data.
+    /// Playback state and settings (mutes, gain, rate, output model) are kept.
+    /// Fails without changing anything if the index is out of range or the
+    /// song has no usable entry points.
+    pub fn set_subsong(&mut self, song_index: usize) -> Result<()> {
+        let entry = SongEntry::resolve(&self.file, song_index)?;
+        let song = &self.file.songs[song_index];
+        self.metadata = build_metadata(&self.file.header, song_index, self.file.songs.len(), song);
+        self.max_frames = frame_limit(song);
+        self.points = entry.points;
+        self.init_address = entry.init_address;
+        self.interrupt_address = entry.interrupt_address;
+        self.reset_runtime()
+    }
+
     /// Access metadata.
     pub fn metadata(&self) -> &AyMetadata {
         &self.metadata
@@ -237,22 +283,31 @@ impl AyPlayer {
 
     /// Generate mono samples into the provided buffer.
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| self.render_native(source));
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+    }
+
+    /// Copy samples at the song's original speed, rendering frames as needed.
+    fn render_native(&mut self, buffer: &mut [f32]) {
         let mut written = 0;
         while written < buffer.len() {
             if self.cache_pos >= self.cache_len {
                 if self.state != PlaybackState::Playing {
                     buffer[written..].fill(0.0);
-                    return;
+                    break;
                 }
                 if let Err(err) = self.render_frame() {
                     eprintln!("AY frame rendering error: {err}");
                     buffer[written..].fill(0.0);
                     self.state = PlaybackState::Stopped;
-                    return;
+                    break;
                 }
                 if self.cache_len == 0 {
                     buffer[written..].fill(0.0);
-                    return;
+                    break;
                 }
             }
 
@@ -266,40 +321,198 @@ impl AyPlayer {
         }
     }
 
-    /// Access the underlying YM2149 chip.
+    /// Advance exactly `frames` interrupt frames while not playing.
+    ///
+    /// Each frame runs the interrupt routine and renders its audio into the
+    /// sample cache, so resuming plays the last stepped frame. A stopped
+    /// player steps from the beginning. The player is left paused (or stopped
+    /// if the song ended). Returns `None` while playing.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        match self.state {
+            PlaybackState::Playing => return None,
+            PlaybackState::Paused => {}
+            PlaybackState::Stopped => self.reset_runtime().ok()?,
+        }
+
+        let before = self.dump_all_registers();
+        self.state = PlaybackState::Paused;
+        for _ in 0..frames {
+            if let Err(err) = self.render_frame() {
+                eprintln!("AY frame rendering error: {err}");
+                self.state = PlaybackState::Stopped;
+            }
+            if self.state == PlaybackState::Stopped {
+                break;
+            }
+        }
+        Some(RegisterDelta::new(before, self.dump_all_registers()))
+    }
+
+    /// Run `frames` interrupt frames without rendering audio.
+    ///
+    /// The interrupt routine runs to completion each frame without clocking
+    /// the chip, and any audio left in the sample cache is dropped. A stopped
+    /// player starts from the beginning and is left paused. Returns the
+    /// number of frames advanced, which is less than `frames` if the song
+    /// reached its length or the routine failed (the player is then stopped).
+    pub fn advance_frames(&mut self, frames: usize) -> usize {
+        if self.state == PlaybackState::Stopped {
+            if self.reset_runtime().is_err() {
+                return 0;
+            }
+            self.state = PlaybackState::Paused;
+        }
+
+        let mut advanced = 0;
+        while advanced < frames && self.state != PlaybackState::Stopped {
+            let result = self
+                .ensure_initialized()
+                .and_then(|()| self.run_subroutine(self.interrupt_address));
+            if let Err(err) = result {
+                eprintln!("AY frame rendering error: {err}");
+                self.state = PlaybackState::Stopped;
+                break;
+            }
+            advanced += 1;
+            self.frame_counter = self.frame_counter.saturating_add(1);
+            if let Some(limit) = self.max_frames
+                && self.frame_counter >= limit
+            {
+                self.state = PlaybackState::Stopped;
+            }
+        }
+        self.cache_pos = self.cache_len;
+        advanced
+    }
+
+    /// Restart the song and advance to `frame` without rendering audio.
+    ///
+    /// Keeps playing if the player was playing; otherwise it is left paused
+    /// at the new position.
+    pub fn seek_frame(&mut self, frame: usize) -> Result<()> {
+        let was_playing = self.state == PlaybackState::Playing;
+        self.reset_runtime()?;
+        self.state = PlaybackState::Paused;
+        self.advance_frames(frame);
+        if was_playing && self.state == PlaybackState::Paused {
+            self.state = PlaybackState::Playing;
+        }
+        Ok(())
+    }
+
+    /// Capture the emulation state (see [`AyPlayerState`]).
+    pub fn save_state(&self) -> AyPlayerState {
+        AyPlayerState {
+            init_address: self.init_address,
+            interrupt_address: self.interrupt_address,
+            cpu: self.z80.cpu().save_state(),
+            machine: self.bus.save_state(),
+            sample_cache: self.sample_cache[..self.cache_len].to_vec(),
+            cache_pos: self.cache_pos,
+            frame_counter: self.frame_counter,
+            init_executed: self.init_executed,
+        }
+    }
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    ///
+    /// A stopped player is left paused so that `play` resumes from the
+    /// restored position. Fails without changing anything if the state was
+    /// saved from a different song or is malformed.
+    pub fn load_state(&mut self, state: &AyPlayerState) -> Result<()> {
+        if state.init_address != self.init_address
+            || state.interrupt_address != self.interrupt_address
+        {
+            return Err("state was saved from a different song".into());
+        }
+        if state.cache_pos > state.sample_cache.len() {
+            return Err("state has an invalid sample cache position".into());
+        }
+        let mut cpu = DefaultZ80::new();
+        cpu.load_state(&state.cpu).map_err(|err| err.to_string())?;
+        if !self.bus.load_state(&state.machine) {
+            return Err("state has an invalid memory image or machine state".into());
+        }
+
+        *self.z80.cpu_mut() = cpu;
+        self.sample_cache.clone_from(&state.sample_cache);
+        self.cache_pos = state.cache_pos;
+        self.cache_len = state.sample_cache.len();
+        self.frame_counter = state.frame_counter;
+        self.init_executed = state.init_executed;
+        if self.state == PlaybackState::Stopped {
+            self.state = PlaybackState::Paused;
+        }
+        Ok(())
+    }
+
+    /// Access the underlying YM2149 chip (PSG 0).
     pub fn chip(&self) -> &ym2149::Ym2149 {
-        self.machine.chip()
+        self.bus.chip()
     }
 
-    /// Mutable access to the underlying YM2149 chip.
+    /// Mutable access to the underlying YM2149 chip (PSG 0).
     pub fn chip_mut(&mut self) -> &mut ym2149::Ym2149 {
-        self.machine.chip_mut()
+        self.bus.chip_mut()
+    }
+
+    /// Access one PSG: 0 is the machine's chip, 1 and 2 the PlayCity chips.
+    pub fn psg(&self, index: usize) -> Option<&ym2149::Ym2149> {
+        (index < self.psg_count()).then(|| self.bus.psgs().get_chip(index))
     }
 
-    /// Mute/unmute a PSG channel.
+    /// Number of PSGs the song uses: 3 once it accessed PlayCity ports.
+    pub fn psg_count(&self) -> usize {
+        self.bus.psg_count()
+    }
+
+    /// Register dumps of every PSG.
+    pub fn dump_all_registers(&self) -> Vec<[u8; 16]> {
+        self.bus.psgs().dump_all_registers()
+    }
+
+    /// Mute/unmute a PSG channel (3..9 are the PlayCity chips' channels).
     pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
-        self.machine.chip_mut().set_channel_mute(channel, mute);
+        if channel < self.psg_count() * 3 {
+            self.bus
+                .psgs_mut()
+                .get_chip_mut(channel / 3)
+                .set_channel_mute(channel % 3, mute);
+        }
     }
 
     #[cfg(feature = "trace-ports")]
     /// Retrieve and clear the recorded port log (trace-ports feature).
     pub fn take_port_log(&mut self) -> Vec<String> {
-        self.machine.take_port_log()
+        self.bus.take_port_log()
     }
 
     /// Whether the current song requires CPC firmware emulation.
     pub fn requires_cpc_firmware(&self) -> bool {
-        self.machine.requires_cpc_firmware()
+        self.bus.machine().requires_firmware()
+    }
+
+    /// The machine the song runs on.
+    pub fn machine(&self) -> &dyn AyMachine {
+        self.bus.machine()
     }
 
     /// Check mute state of a PSG channel.
     pub fn is_channel_muted(&self, channel: usize) -> bool {
-        self.machine.chip().is_channel_muted(channel)
+        self.psg(channel / 3)
+            .is_some_and(|chip| chip.is_channel_muted(channel % 3))
     }
 
     /// Enable or disable ST-style color filter.
     pub fn set_color_filter(&mut self, enabled: bool) {
-        self.machine.chip_mut().set_color_filter(enabled);
+        self.for_each_psg(|chip| chip.set_color_filter(enabled));
+    }
+
+    fn for_each_psg(&mut self, mut apply: impl FnMut(&mut ym2149::Ym2149)) {
+        let psgs = self.bus.psgs_mut();
+        for index in 0..psgs.psg_count() {
+            apply(psgs.get_chip_mut(index));
+        }
     }
 
     /// Playback position (0.0-1.0) when length is known.
@@ -319,12 +532,17 @@ impl AyPlayer {
         self.frame_counter
     }
 
+    fn song(&self) -> &AySong {
+        &self.file.songs[self.metadata.song_index]
+    }
+
     fn reset_runtime(&mut self) -> Result<()> {
-        self.machine.reset_layout();
-        for block in &self.song.data.blocks {
-            self.machine.load_block(block);
+        self.bus.reset_layout();
+        for block in &self.file.songs[self.metadata.song_index].data.blocks {
+            self.bus.load_block(block);
         }
-        self.cpu = Cpu::new();
+        self.z80.reset();
+        self.z80.set_timing(self.bus.machine().timing());
         self.apply_register_presets();
         self.frame_counter = 0;
         self.cache_pos = 0;
@@ -335,16 +553,22 @@ impl AyPlayer {
     }
 
     fn apply_register_presets(&mut self) {
-        let preset = ((self.song.data.hi_reg as u16) << 8) | self.song.data.lo_reg as u16;
-        self.cpu.registers().set16(Reg16::AF, preset);
-        self.cpu.registers().set16(Reg16::BC, preset);
-        self.cpu.registers().set16(Reg16::DE, preset);
-        self.cpu.registers().set16(Reg16::HL, preset);
-        self.cpu.registers().set16(Reg16::IX, preset);
-        self.cpu.registers().set16(Reg16::IY, preset);
-        self.cpu.registers().set16(Reg16::SP, self.points.stack);
-        self.cpu.registers().set_pc(RETURN_ADDRESS);
-        self.cpu.registers().set8(Reg8::I, 3);
+        let data = &self.song().data;
+        let preset = ((data.hi_reg as u16) << 8) | data.lo_reg as u16;
+        let cpu = self.z80.cpu_mut();
+        for pair in [
+            RegisterPair::AF,
+            RegisterPair::BC,
+            RegisterPair::DE,
+            RegisterPair::HL,
+            RegisterPair::IX,
+            RegisterPair::IY,
+        ] {
+            cpu.set_register(pair, preset);
+        }
+        cpu.set_register(RegisterPair::SP, self.points.stack);
+        cpu.set_pc(RETURN_ADDRESS);
+        cpu.set_i(3);
     }
 
     fn ensure_initialized(&mut self) -> Result<()> {
@@ -357,8 +581,9 @@ impl AyPlayer {
 
     fn render_frame(&mut self) -> Result<()> {
         self.ensure_initialized()?;
-        if self.sample_cache.len() != self.samples_per_frame {
-            self.sample_cache.resize(self.samples_per_frame, 0.0);
+        let samples = self.frame_sample_count();
+        if self.sample_cache.len() != samples {
+            self.sample_cache.resize(samples, 0.0);
         }
         let mut buffer = mem::take(&mut self.sample_cache);
         self.render_interrupt_stream(&mut buffer)?;
@@ -374,8 +599,21 @@ impl AyPlayer {
         Ok(())
     }
 
+    /// Samples in the current frame: the machine's frame length at the
+    /// output rate, rounded so the fractions carry over between frames.
+    fn frame_sample_count(&self) -> usize {
+        let cpu_clock = u64::from(self.bus.machine().cpu_clock_hz().max(1));
+        let frame_samples =
+            u64::from(self.bus.machine().timing().frame_t_states) * u64::from(SAMPLE_RATE);
+        let samples_before = |frame: u64| (frame * frame_samples + cpu_clock / 2) / cpu_clock;
+        let frame = self.frame_counter as u64;
+        (samples_before(frame + 1) - samples_before(frame)) as usize
+    }
+
     fn render_interrupt_stream(&mut self, buffer: &mut [f32]) -> Result<()> {
         self.fail_if_cpc()?;
+        self.z80.set_timing(self.bus.machine().timing());
+        self.z80.start_frame();
         self.emulate_call(self.interrupt_address);
         let mut next_sample_time = self.sample_period;
         let mut cpu_time = 0.0f64;
@@ -386,24 +624,12 @@ impl AyPlayer {
             self.fail_if_cpc()?;
             while cpu_time < next_sample_time {
                 self.fail_if_cpc()?;
-                if self.cpu.immutable_registers().pc() == RETURN_ADDRESS {
+                if self.z80.cpu().pc() == RETURN_ADDRESS {
                     cpu_time = next_sample_time;
                     break;
                 }
-                let before = self.cpu.cycle_count();
-                self.cpu.execute_instruction(&mut self.machine);
-                let after = self.cpu.cycle_count();
-                let delta_cycles =
-                    after
-                        .checked_sub(before)
-                        .ok_or_else(|| AyError::InvalidData {
-                            msg: "CPU cycle counter underflowed".to_string(),
-                        })? as f64;
-                let cpu_clock = if self.machine.is_cpc_mode() {
-                    CPC_CPU_CLOCK_HZ
-                } else {
-                    ZX_CPU_CLOCK_HZ
-                };
+                let delta_cycles = f64::from(self.z80.step(&mut self.bus));
+                let cpu_clock = f64::from(self.bus.machine().cpu_clock_hz());
                 cpu_time += delta_cycles / cpu_clock;
                 guard = guard.checked_sub(1).ok_or_else(|| AyError::InvalidData {
                     msg: format!(
@@ -413,14 +639,12 @@ impl AyPlayer {
                 })?;
             }
 
-            let chip = self.machine.chip_mut();
-            chip.clock();
-            buffer[idx] = chip.get_sample();
+            buffer[idx] = self.bus.clock_sample();
             idx += 1;
             next_sample_time += self.sample_period;
         }
 
-        if self.cpu.immutable_registers().pc() != RETURN_ADDRESS {
+        if self.z80.cpu().pc() != RETURN_ADDRESS {
             return Err(AyError::InvalidData {
                 msg: format!(
                     "Interrupt routine at 0x{:04x} did not return before frame end",
@@ -437,8 +661,8 @@ impl AyPlayer {
         let mut guard = MAX_INSTRUCTIONS_PER_CALL;
         loop {
             self.fail_if_cpc()?;
-            self.cpu.execute_instruction(&mut self.machine);
-            let pc = self.cpu.immutable_registers().pc();
+            self.z80.step(&mut self.bus);
+            let pc = self.z80.cpu().pc();
             if pc == RETURN_ADDRESS {
                 break;
             }
@@ -452,18 +676,18 @@ impl AyPlayer {
     }
 
     fn emulate_call(&mut self, entry: u16) {
-        let regs = self.cpu.registers();
-        let mut sp = regs.get16(Reg16::SP);
+        let cpu = self.z80.cpu_mut();
+        let mut sp = cpu.register(RegisterPair::SP);
         sp = sp.wrapping_sub(1);
-        self.machine.poke(sp, (RETURN_ADDRESS >> 8) as u8);
+        self.bus.poke(sp, (RETURN_ADDRESS >> 8) as u8);
         sp = sp.wrapping_sub(1);
-        self.machine.poke(sp, RETURN_ADDRESS as u8);
-        regs.set16(Reg16::SP, sp);
-        regs.set_pc(entry);
+        self.bus.poke(sp, RETURN_ADDRESS as u8);
+        cpu.set_register(RegisterPair::SP, sp);
+        cpu.set_pc(entry);
     }
 
     fn fail_if_cpc(&self) -> Result<()> {
-        if self.machine.requires_cpc_firmware() {
+        if self.requires_cpc_firmware() {
             Err(AyError::InvalidData {
                 msg: CPC_UNSUPPORTED_MSG.to_string(),
             })
@@ -473,6 +697,43 @@ impl AyPlayer {
     }
 }
 
+/// Entry points of one song in a file.
+struct SongEntry {
+    points: AyPoints,
+    init_address: u16,
+    interrupt_address: u16,
+}
+
+impl SongEntry {
+    fn resolve(file: &AyFile, song_index: usize) -> Result<Self> {
+        let song = file
+            .songs
+            .get(song_index)
+            .ok_or(AyError::SubsongOutOfRange {
+                index: song_index,
+                available: file.songs.len(),
+            })?;
+        let points = song
+            .data
+            .points
+            .clone()
+            .ok_or_else(|| AyError::InvalidData {
+                msg: "AY song is missing points data".to_string(),
+            })?;
+        let init_address = resolve_init_address(song, &points)?;
+        let interrupt_address = if points.interrupt != 0 {
+            points.interrupt
+        } else {
+            init_address
+        };
+        Ok(Self {
+            points,
+            init_address,
+            interrupt_address,
+        })
+    }
+}
+
 fn resolve_init_address(song: &AySong, points: &AyPoints) -> Result<u16> {
     if points.init != 0 {
         return Ok(points.init);
@@ -563,21 +824,82 @@ impl ChiptunePlayerBase for AyPlayer {
         AyPlayer::is_channel_muted(self, channel)
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        AyPlayer::step_frames(self, frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        AyPlayer::advance_frames(self, frames)
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.for_each_psg(|chip| chip.set_output_model(model));
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.for_each_psg(|chip| chip.set_channel_tap(capacity));
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.psg(channel / 3)
+            .and_then(|chip| chip.channel_tap())
+            .map_or(0, |tap| tap.read_channel(channel % 3, out))
+    }
+
     fn playback_position(&self) -> f32 {
         AyPlayer::playback_position(self)
     }
 
+    fn seek(&mut self, position: f32) -> bool {
+        let Some(max_frames) = self.max_frames.filter(|&frames| frames > 0) else {
+            return false;
+        };
+        let target = (position.clamp(0.0, 1.0) * max_frames as f32) as usize;
+        AyPlayer::seek_frame(self, target.min(max_frames - 1)).is_ok()
+    }
+
     fn subsong_count(&self) -> usize {
         self.metadata.song_count
     }
 
+    fn psg_count(&self) -> usize {
+        AyPlayer::psg_count(self)
+    }
+
     fn current_subsong(&self) -> usize {
         self.metadata.song_index + 1
     }
 
-    fn set_subsong(&mut self, _index: usize) -> bool {
-        // AY requires reloading from raw data to switch songs
-        false
+    fn set_subsong(&mut self, index: usize) -> bool {
+        if index == 0 || AyPlayer::set_subsong(self, index - 1).is_err() {
+            return false;
+        }
+        self.state = PlaybackState::Playing;
+        true
     }
 }
 
@@ -587,4 +909,8 @@ impl ChiptunePlayer for AyPlayer {
     fn metadata(&self) -> &Self::Metadata {
         &self.metadata
     }
+
+    fn load_report(&self) -> LoadReport {
+        LoadReport::from_player(self).with_family(ChiptuneFormat::Ay)
+    }
 }
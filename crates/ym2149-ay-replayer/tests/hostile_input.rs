@@ -0,0 +1,153 @@
+//! Mutated files must be rejected with an error, never a panic.
+
+use ym2149_ay_replayer::{AyError, AyPlayer, load_ay};
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::mutants;
+
+/// A small but complete ZXAYEMUL file: one song whose interrupt routine
+/// copies a register table to the AY through the Spectrum ports.
+fn synthetic_ay() -> Vec<u8> {
+    let mut data = b"ZXAYEMUL".to_vec();
+    // File version 3, player version 0, no special player, author at 72, no misc
+    data.extend_from_slice(&[0, 3, 0, 0, 0, 60, 0, 0]);
+    // One song (stored minus one), first song 0, song structure at 20
+    data.extend_from_slice(&[0, 0, 0, 2]);
+    // Song structure: name at 64, data at 24
+    data.extend_from_slice(&[0, 44, 0, 2]);
+    // Song data: channel map, no length or fade, registers,
+    // points at 38, blocks at 44
+    data.extend_from_slice(&[0, 1, 2, 3, 0, 0, 0, 0, 0, 0, 0, 4, 0, 8]);
+    // Points: stack 0xF000, init 0x8000, interrupt 0x8010
+    data.extend_from_slice(&[0xF0, 0x00, 0x80, 0x00, 0x80, 0x10]);
+    // Blocks: code at 0x8000 (48 bytes from 80), registers at 0x9000
+    // (14 bytes from 128)
+    data.extend_from_slice(&[0x80, 0x00, 0x00, 0x30, 0x00, 0x20]);
+    data.extend_from_slice(&[0x90, 0x00, 0x00, 0x0E, 0x00, 0x4A]);
+    data.extend_from_slice(&[0, 0]);
+    data.resize(64, 0);
+    data.extend_from_slice(b"Test\0\0\0\0Anon\0\0\0\0");
+    // 0x8000: RET (init)
+    data.extend_from_slice(&[0xC9]);
+    data.resize(96, 0);
+    // 0x8010: write R0-R13 from the table at 0x9000 plus a frame counter
+    data.extend_from_slice(&[
+        0x21, 0x00, 0x90, // ld hl,0x9000
+        0x3A, 0x00, 0x91, // ld a,(0x9100)
+        0x3C, // inc a
+        0x32, 0x00, 0x91, // ld (0x9100),a
+        0x5F, // ld e,a
+        0xAF, // xor a
+        0x01, 0xFD, 0xFF, // ld bc,0xFFFD
+        0xED, 0x79, // out (c),a
+        0x57, // ld d,a
+        0x7E, // ld a,(hl)
+        0x83, // add a,e
+        0x06, 0xBF, // ld b,0xBF
+        0xED, 0x79, // out (c),a
+        0x23, // inc hl
+        0x7A, // ld a,d
+        0x3C, // inc a
+        0xFE, 0x0E, // cp 14
+        0x20, 0xED, // jr nz,-19
+        0xC9, // ret
+    ]);
+    // 0x9000: register table
+    data.extend_from_slice(&[
+        0x10, 0x01, 0x20, 0x02, 0x30, 0x03, 0x10, 0x38, 0x0F, 0x0C, 0x08, 0x00, 0x10, 0x0E,
+    ]);
+    data
+}
+
+#[test]
+fn synthetic_ay_plays() {
+    let (mut player, _) = AyPlayer::load_from_bytes(&synthetic_ay(), 0).expect("valid AY file");
+    player.play().expect("song starts");
+    let mut buffer = [0.0f32; 882];
+    player.generate_samples_into(&mut buffer);
+    player.generate_samples_into(&mut buffer);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+}
+
+#[test]
+fn switching_songs_reuses_the_parsed_file() {
+    let mut file = load_ay(&synthetic_ay()).expect("valid AY file");
+    let mut second = file.songs[0].clone();
+    second.name = "Second".to_string();
+    file.songs.push(second);
+
+    let mut player = AyPlayer::new(file, 0).expect("first song");
+    player.play().expect("song starts");
+    let mut buffer = [0.0f32; 882];
+    player.generate_samples_into(&mut buffer);
+    assert_eq!(player.current_frame(), 1);
+
+    player.set_subsong(1).expect("second song");
+    assert_eq!(player.metadata().song_index, 1);
+    assert_eq!(player.metadata().song_name, "Second");
+    assert_eq!(player.current_frame(), 0);
+    player.generate_samples_into(&mut buffer);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+
+    assert!(matches!(
+        player.set_subsong(2),
+        Err(AyError::SubsongOutOfRange {
+            index: 2,
+            available: 2
+        })
+    ));
+    assert_eq!(player.metadata().song_index, 1);
+}
+
+#[test]
+fn playcity_ports_add_two_psgs() {
+    let mut data = synthetic_ay();
+    // Write the register table to the right PlayCity chip instead:
+    // ld bc,0xF984 (select) and ld b,0xF8 (data port 0xF884)
+    data[109] = 0x84;
+    data[110] = 0xF9;
+    data[117] = 0xF8;
+
+    let (mut player, _) = AyPlayer::load_from_bytes(&data, 0).expect("valid AY file");
+    assert_eq!(player.psg_count(), 1);
+    player.play().expect("song starts");
+    let mut buffer = [0.0f32; 882];
+    player.generate_samples_into(&mut buffer);
+    player.generate_samples_into(&mut buffer);
+
+    assert_eq!(player.psg_count(), 3);
+    assert_eq!(ChiptunePlayerBase::channel_count(&player), 9);
+    let registers = player.dump_all_registers();
+    assert_eq!(registers.len(), 3);
+    assert_eq!(registers[0][8], 0);
+    assert_ne!(registers[1][8], 0);
+    assert_eq!(registers[2][8], 0);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+
+    player.set_channel_mute(3, true);
+    assert!(player.is_channel_muted(3));
+    assert!(!player.is_channel_muted(0));
+}
+
+#[test]
+fn out_of_range_first_song_is_an_error() {
+    let mut data = synthetic_ay();
+    // Song count 1, first song 256 (1-based)
+    data[17] = 0xFF;
+    assert!(matches!(load_ay(&data), Err(AyError::InvalidData { .. })));
+}
+
+#[test]
+fn mutated_ay_files_never_panic() {
+    let original = synthetic_ay();
+    for data in mutants(&original, 2000) {
+        let _ = load_ay(&data);
+        if let Ok((mut player, _)) = AyPlayer::load_from_bytes(&data, 0)
+            && player.play().is_ok()
+        {
+            let mut buffer = [0.0f32; 882];
+            for _ in 0..4 {
+                player.generate_samples_into(&mut buffer);
+            }
+        }
+    }
+}
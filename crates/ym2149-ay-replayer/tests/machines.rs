@@ -0,0 +1,172 @@
+//! Songs running on explicitly chosen and custom machines.
+
+use ym2149_ay_replayer::{
+    AutoDetect, AyBlock, AyFile, AyHeader, AyMachine, AyPlayer, AyPoints, AySong, AySongData, Cpc,
+    FrameInterrupt, PsgBus, Zx48, Zx128,
+};
+
+const INIT_ADDRESS: u16 = 0x8000;
+const INTERRUPT_ADDRESS: u16 = 0x8010;
+
+/// Registers every test song sets: tone A on, period 0x040, full volume.
+const REGISTERS: [(u8, u8); 3] = [(7, 0x3E), (0, 0x40), (8, 0x0F)];
+
+/// A song whose interrupt routine is `routine` followed by `RET`.
+fn song(mut routine: Vec<u8>) -> AyFile {
+    routine.push(0xC9);
+    let mut code = vec![0u8; usize::from(INTERRUPT_ADDRESS - INIT_ADDRESS)];
+    code[0] = 0xC9;
+    code.extend_from_slice(&routine);
+    AyFile {
+        header: AyHeader {
+            song_count: 1,
+            ..Default::default()
+        },
+        songs: vec![AySong {
+            name: "Machine test".to_string(),
+            data: AySongData {
+                channel_map: [0, 1, 2, 3],
+                points: Some(AyPoints {
+                    stack: 0xF000,
+                    init: INIT_ADDRESS,
+                    interrupt: INTERRUPT_ADDRESS,
+                }),
+                blocks: vec![AyBlock {
+                    address: INIT_ADDRESS,
+                    length: code.len() as u16,
+                    data: code,
+                }],
+                ..Default::default()
+            },
+        }],
+    }
+}
+
+/// `ld bc,port` + `ld a,value` + `out (c),a`.
+fn out_c(port: u16, value: u8) -> [u8; 7] {
+    let [low, high] = port.to_le_bytes();
+    [0x01, low, high, 0x3E, value, 0xED, 0x79]
+}
+
+/// `ld a,value` + `out (port),a`.
+fn out_n(port: u8, value: u8) -> [u8; 4] {
+    [0x3E, value, 0xD3, port]
+}
+
+fn play_frames(player: &mut AyPlayer, frames: usize, samples_per_frame: usize) -> Vec<f32> {
+    player.play().expect("song starts");
+    let mut buffer = vec![0.0f32; samples_per_frame];
+    for _ in 0..frames {
+        player.generate_samples_into(&mut buffer);
+    }
+    buffer
+}
+
+#[test]
+fn zx128_plays_through_the_spectrum_ports() {
+    let mut routine = Vec::new();
+    for (register, value) in REGISTERS {
+        routine.extend(out_c(0xFFFD, register));
+        routine.extend(out_c(0xBFFD, value));
+    }
+    let mut player = AyPlayer::with_machine(song(routine), 0, Box::new(Zx128)).expect("song loads");
+    assert_eq!(player.machine().name(), "ZX Spectrum 128K");
+
+    let buffer = play_frames(&mut player, 2, 882);
+    assert_eq!(player.dump_all_registers()[0][8], 0x0F);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+}
+
+#[test]
+fn spectrum_frames_follow_the_ula_frame_length() {
+    let one_second = |machine: Box<dyn AyMachine>| {
+        let mut player = AyPlayer::with_machine(song(Vec::new()), 0, machine).expect("song loads");
+        play_frames(&mut player, 1, 44_100);
+        player.current_frame()
+    };
+    // 69888 T-states at 3.5 MHz: 50.08 frames per second
+    assert_eq!(one_second(Box::new(Zx48)), 51);
+    // The detected Spectrum keeps the nominal 50 Hz
+    assert_eq!(one_second(Box::<AutoDetect>::default()), 50);
+}
+
+#[test]
+fn cpc_plays_through_the_ppi() {
+    let mut routine = Vec::new();
+    for (register, value) in REGISTERS {
+        // Data bus, then BDIR/BC1 = latch address, inactive, write, inactive
+        routine.extend(out_c(0xF400, register));
+        routine.extend(out_c(0xF600, 0xC0));
+        routine.extend(out_c(0xF600, 0x00));
+        routine.extend(out_c(0xF400, value));
+        routine.extend(out_c(0xF600, 0x80));
+        routine.extend(out_c(0xF600, 0x00));
+    }
+    let mut player =
+        AyPlayer::with_machine(song(routine), 0, Box::new(Cpc::default())).expect("song loads");
+
+    let buffer = play_frames(&mut player, 2, 882);
+    assert!(!player.requires_cpc_firmware());
+    let registers = player.dump_all_registers();
+    assert_eq!(registers.len(), 1);
+    assert_eq!(registers[0][7], 0x3E);
+    assert_eq!(registers[0][8], 0x0F);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+}
+
+/// MSX: PSG at ports 0xA0 (address), 0xA1 (write), 0xA2 (read), 60 Hz.
+struct Msx;
+
+impl AyMachine for Msx {
+    fn name(&self) -> &'static str {
+        "MSX"
+    }
+
+    fn cpu_clock_hz(&self) -> u32 {
+        3_579_545
+    }
+
+    fn psg_clocks(&self) -> &[u32] {
+        &[1_789_772]
+    }
+
+    fn frame_interrupt(&self) -> FrameInterrupt {
+        FrameInterrupt {
+            rate_hz: 60,
+            divider: 1,
+        }
+    }
+
+    fn port_out(&mut self, address: u16, value: u8, psgs: &mut PsgBus<'_>) {
+        match address as u8 {
+            0xA0 => psgs.select(0, value & 0x0F),
+            0xA1 => psgs.write(0, value),
+            _ => {}
+        }
+    }
+
+    fn port_in(&mut self, address: u16, psgs: &mut PsgBus<'_>) -> u8 {
+        if address as u8 == 0xA2 {
+            psgs.read(0)
+        } else {
+            0xFF
+        }
+    }
+}
+
+#[test]
+fn custom_machine_decodes_its_own_ports_and_frame_rate() {
+    let mut routine = Vec::new();
+    for (register, value) in REGISTERS {
+        routine.extend(out_n(0xA0, register));
+        routine.extend(out_n(0xA1, value));
+    }
+    let mut player = AyPlayer::with_machine(song(routine), 0, Box::new(Msx)).expect("song loads");
+    assert_eq!(player.machine().name(), "MSX");
+
+    // 60 Hz: 735 samples per frame at 44.1 kHz
+    let buffer = play_frames(&mut player, 3, 735);
+    assert_eq!(player.current_frame(), 3);
+    assert_eq!(player.dump_all_registers()[0][8], 0x0F);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+}
@@ -0,0 +1,86 @@
+/*
+ * ym2149.h - C ABI for the ym2149-rs replayers (ym2149-capi).
+ *
+ * Loads YM, SNDH, AY and Arkos Tracker (.aks) songs from memory and renders
+ * mono float samples. Every function accepts a NULL player: queries return
+ * 0/false/NULL and commands do nothing. A replayer panic is caught: the call
+ * returns the same neutral value, generate writes silence and
+ * ym2149_last_error says what happened. A player must only be used from one
+ * thread at a time.
+ */
+
+#ifndef YM2149_H
+#define YM2149_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Opaque song player. */
+typedef struct Ym2149Player Ym2149Player;
+
+/* Playback state. */
+typedef enum Ym2149State {
+    YM2149_STATE_STOPPED = 0,
+    YM2149_STATE_PLAYING = 1,
+    YM2149_STATE_PAUSED = 2,
+} Ym2149State;
+
+/* Library version, e.g. "0.9.1" (static string). */
+const char *ym2149_version(void);
+
+/* Message of the last failed call on this thread, or NULL.
+ * Valid until the next failing call on the same thread. */
+const char *ym2149_last_error(void);
+
+/* Create a player from song data; the bytes are copied.
+ * Returns NULL on failure (see ym2149_last_error). */
+Ym2149Player *ym2149_player_new(const uint8_t *data, size_t len);
+
+/* Destroy a player. NULL is ignored. */
+void ym2149_player_free(Ym2149Player *player);
+
+/* Transport. */
+void ym2149_player_play(Ym2149Player *player);
+void ym2149_player_pause(Ym2149Player *player);
+void ym2149_player_stop(Ym2149Player *player);
+Ym2149State ym2149_player_state(const Ym2149Player *player);
+
+/* Render `count` mono samples (-1.0..1.0); silence unless playing. */
+void ym2149_player_generate(Ym2149Player *player, float *buffer, size_t count);
+
+/* Output sample rate in Hz. */
+uint32_t ym2149_player_sample_rate(const Ym2149Player *player);
+
+/* Metadata, owned by the player and valid until ym2149_player_free. */
+const char *ym2149_player_title(const Ym2149Player *player);
+const char *ym2149_player_author(const Ym2149Player *player);
+const char *ym2149_player_comments(const Ym2149Player *player);
+const char *ym2149_player_format(const Ym2149Player *player);
+
+/* Song length (0 if unknown) and playback position, in seconds. */
+float ym2149_player_duration_seconds(const Ym2149Player *player);
+float ym2149_player_elapsed_seconds(const Ym2149Player *player);
+
+/* Seek to 0.0 (start) .. 1.0 (end); false if the song can't seek. */
+bool ym2149_player_seek(Ym2149Player *player, float position);
+
+/* Subsongs, 1-based. */
+size_t ym2149_player_subsong_count(const Ym2149Player *player);
+size_t ym2149_player_current_subsong(const Ym2149Player *player);
+bool ym2149_player_set_subsong(Ym2149Player *player, size_t index);
+
+/* Channels, 0-based (3 per PSG). */
+size_t ym2149_player_channel_count(const Ym2149Player *player);
+void ym2149_player_set_channel_mute(Ym2149Player *player, size_t channel, bool mute);
+bool ym2149_player_is_channel_muted(const Ym2149Player *player, size_t channel);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* YM2149_H */
@@ -0,0 +1,586 @@
+//! C ABI for the YM2149 replayers.
+//!
+//! Exposes a single opaque player type that loads YM, SNDH, AY and Arkos
+//! Tracker songs from memory, so the emulator can be embedded in C, C++ or
+//! Swift hosts such as foobar2000 or Audacious input plugins. The matching
+//! header is `include/ym2149.h`.
+//!
+//! All functions are safe to call with a null player pointer: queries
+//! return zero/false/null and commands do nothing. A panic inside a
+//! replayer (hostile 68000 or Z80 code, say) never unwinds into the host:
+//! the call returns the same neutral value, `generate` writes silence, and
+//! [`ym2149_last_error`] says what happened. A player must only be used
+//! from one thread at a time, but may move between threads.
+//!
+//! ```c
+//! Ym2149Player *player = ym2149_player_new(data, size);
+//! if (!player) {
+//!     fprintf(stderr, "load failed: %s\n", ym2149_last_error());
+//!     return;
+//! }
+//! printf("%s - %s\n", ym2149_player_author(player), ym2149_player_title(player));
+//! ym2149_player_play(player);
+//! float buffer[1024];
+//! while (ym2149_player_state(player) == YM2149_STATE_PLAYING) {
+//!     ym2149_player_generate(player, buffer, 1024);
+//!     /* ... mono samples at ym2149_player_sample_rate(player) Hz */
+//! }
+//! ym2149_player_free(player);
+//! ```
+
+use std::cell::RefCell;
+use std::ffi::{CString, c_char};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use ym2149_arkos_replayer::{ArkosPlayer, load_aks};
+use ym2149_ay_replayer::{AyPlayer, CPC_UNSUPPORTED_MSG};
+use ym2149_common::{
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE, MetadataFields,
+    PlaybackState, load_with_detection,
+};
+use ym2149_sndh_replayer::load_sndh;
+use ym2149_ym_replayer::load_song;
+
+thread_local! {
+    /// Message of the last failed call on this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Song player created by [`ym2149_player_new`].
+///
+/// Opaque to C; owns the replayer and the metadata strings handed out by
+/// the accessors.
+pub struct Ym2149Player {
+    player: Box<dyn ChiptunePlayerBase>,
+    title: CString,
+    author: CString,
+    comments: CString,
+    format: CString,
+}
+
+impl Ym2149Player {
+    fn new<P: ChiptunePlayer + 'static>(player: P) -> Self {
+        let metadata = player.metadata();
+        Self {
+            title: c_string(metadata.title()),
+            author: c_string(metadata.author()),
+            comments: c_string(metadata.comments()),
+            format: c_string(metadata.format()),
+            player: Box::new(player),
+        }
+    }
+}
+
+/// Playback state returned by [`ym2149_player_state`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ym2149State {
+    /// Stopped (not started yet or reached the end)
+    Stopped = 0,
+    /// Generating audio
+    Playing = 1,
+    /// Paused, resumes where it left off
+    Paused = 2,
+}
+
+impl From<PlaybackState> for Ym2149State {
+    fn from(state: PlaybackState) -> Self {
+        match state {
+            PlaybackState::Stopped => Self::Stopped,
+            PlaybackState::Playing => Self::Playing,
+            PlaybackState::Paused => Self::Paused,
+        }
+    }
+}
+
+/// NUL-terminated copy of `s`, dropping interior NULs.
+fn c_string(s: &str) -> CString {
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+fn set_last_error(message: &str) {
+    LAST_ERROR.with(|error| *error.borrow_mut() = Some(c_string(message)));
+}
+
+/// Load a song of any supported format, best header match first.
+fn load_player(data: &[u8]) -> Result<Ym2149Player, String> {
+    if data.is_empty() {
+        return Err("empty file data".to_string());
+    }
+    load_with_detection(data, |format| match format {
+        ChiptuneFormat::Ym => load_song(data)
+            .map(|(player, _)| Ym2149Player::new(player))
+            .map_err(|e| e.to_string()),
+        ChiptuneFormat::Arkos => {
+            let song = load_aks(data).map_err(|e| e.to_string())?;
+            ArkosPlayer::new(song, 0)
+                .map(Ym2149Player::new)
+                .map_err(|e| e.to_string())
+        }
+        ChiptuneFormat::Ay => {
+            let (player, _) = AyPlayer::load_from_bytes(data, 0).map_err(|e| e.to_string())?;
+            if player.requires_cpc_firmware() {
+                return Err(CPC_UNSUPPORTED_MSG.to_string());
+            }
+            Ok(Ym2149Player::new(player))
+        }
+        ChiptuneFormat::Sndh => {
+            let mut player = load_sndh(data, DEFAULT_SAMPLE_RATE).map_err(|e| e.to_string())?;
+            player
+                .init_subsong(player.default_subsong())
+                .map_err(|e| e.to_string())?;
+            Ok(Ym2149Player::new(player))
+        }
+        ChiptuneFormat::Kss | ChiptuneFormat::Zip | ChiptuneFormat::Unknown => {
+            Err(format!("no {} player", format.name()))
+        }
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Run `call`, turning a replayer panic into `fallback` and a last error.
+///
+/// Panics must not unwind into the host, so every entry point that calls
+/// into a player goes through here.
+fn guard<T>(action: &str, fallback: T, call: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|_| {
+        set_last_error(&format!("replayer panicked while {action}"));
+        fallback
+    })
+}
+
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+unsafe fn player_ref<'a>(player: *const Ym2149Player) -> Option<&'a Ym2149Player> {
+    unsafe { player.as_ref() }
+}
+
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+unsafe fn player_mut<'a>(player: *mut Ym2149Player) -> Option<&'a mut Ym2149Player> {
+    unsafe { player.as_mut() }
+}
+
+/// Library version as a static NUL-terminated string (e.g. `"0.9.1"`).
+#[unsafe(no_mangle)]
+pub extern "C" fn ym2149_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Message of the last failed call on this thread, or null.
+///
+/// The string stays valid until the next failing call on the same thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn ym2149_last_error() -> *const c_char {
+    LAST_ERROR.with(|error| error.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Create a player from song data (YM, SNDH, AY or AKS), detecting the format.
+///
+/// The data is copied; the caller keeps ownership of `data`. Returns null on
+/// failure, with the reason in [`ym2149_last_error`]. Free the player with
+/// [`ym2149_player_free`].
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes (or be null with `len` 0).
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_new(data: *const u8, len: usize) -> *mut Ym2149Player {
+    let data = if data.is_null() || len == 0 {
+        &[][..]
+    } else {
+        unsafe { std::slice::from_raw_parts(data, len) }
+    };
+    let loaded = guard("loading the song", Err(None), || {
+        load_player(data).map_err(Some)
+    });
+    match loaded {
+        Ok(player) => Box::into_raw(Box::new(player)),
+        Err(error) => {
+            if let Some(error) = error {
+                set_last_error(&error);
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Destroy a player. Null is ignored.
+///
+/// # Safety
+///
+/// `player` must be null or a pointer from [`ym2149_player_new`] that has
+/// not been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_free(player: *mut Ym2149Player) {
+    if !player.is_null() {
+        drop(unsafe { Box::from_raw(player) });
+    }
+}
+
+/// Start or resume playback.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_play(player: *mut Ym2149Player) {
+    if let Some(p) = unsafe { player_mut(player) } {
+        guard("starting playback", (), || p.player.play());
+    }
+}
+
+/// Pause playback.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_pause(player: *mut Ym2149Player) {
+    if let Some(p) = unsafe { player_mut(player) } {
+        guard("pausing", (), || p.player.pause());
+    }
+}
+
+/// Stop playback and rewind to the start.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_stop(player: *mut Ym2149Player) {
+    if let Some(p) = unsafe { player_mut(player) } {
+        guard("stopping", (), || p.player.stop());
+    }
+}
+
+/// Current playback state.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_state(player: *const Ym2149Player) -> Ym2149State {
+    unsafe { player_ref(player) }.map_or(Ym2149State::Stopped, |p| {
+        guard("reading the state", Ym2149State::Stopped, || {
+            p.player.state().into()
+        })
+    })
+}
+
+/// Render `count` mono samples (-1.0 to 1.0) into `buffer`.
+///
+/// Writes silence when the player is stopped or paused, or the replayer
+/// panicked.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`], and
+/// `buffer` must point to `count` writable floats.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_generate(
+    player: *mut Ym2149Player,
+    buffer: *mut f32,
+    count: usize,
+) {
+    if buffer.is_null() || count == 0 {
+        return;
+    }
+    let buffer = unsafe { std::slice::from_raw_parts_mut(buffer, count) };
+    let rendered = unsafe { player_mut(player) }.is_some_and(|p| {
+        guard("rendering", false, || {
+            p.player.generate_samples_into(buffer);
+            true
+        })
+    });
+    if !rendered {
+        buffer.fill(0.0);
+    }
+}
+
+/// Output sample rate in Hz.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_sample_rate(player: *const Ym2149Player) -> u32 {
+    unsafe { player_ref(player) }.map_or(0, |p| {
+        guard("reading the sample rate", 0, || p.player.sample_rate())
+    })
+}
+
+/// Song title, owned by the player (empty if unknown).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_title(player: *const Ym2149Player) -> *const c_char {
+    unsafe { player_ref(player) }.map_or(ptr::null(), |p| p.title.as_ptr())
+}
+
+/// Song author, owned by the player (empty if unknown).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_author(player: *const Ym2149Player) -> *const c_char {
+    unsafe { player_ref(player) }.map_or(ptr::null(), |p| p.author.as_ptr())
+}
+
+/// Song comments, owned by the player (empty if none).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_comments(player: *const Ym2149Player) -> *const c_char {
+    unsafe { player_ref(player) }.map_or(ptr::null(), |p| p.comments.as_ptr())
+}
+
+/// File format name (e.g. `"YM6"`, `"SNDH"`, `"AY"`), owned by the player.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_format(player: *const Ym2149Player) -> *const c_char {
+    unsafe { player_ref(player) }.map_or(ptr::null(), |p| p.format.as_ptr())
+}
+
+/// Song length in seconds (0 if unknown).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_duration_seconds(player: *const Ym2149Player) -> f32 {
+    unsafe { player_ref(player) }.map_or(0.0, |p| {
+        guard("reading the duration", 0.0, || p.player.duration_seconds())
+    })
+}
+
+/// Playback position in seconds.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_elapsed_seconds(player: *const Ym2149Player) -> f32 {
+    unsafe { player_ref(player) }.map_or(0.0, |p| {
+        guard("reading the position", 0.0, || p.player.elapsed_seconds())
+    })
+}
+
+/// Seek to `position` (0.0 = start, 1.0 = end). Returns false if the song
+/// can't seek.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_seek(player: *mut Ym2149Player, position: f32) -> bool {
+    unsafe { player_mut(player) }
+        .is_some_and(|p| guard("seeking", false, || p.player.seek(position)))
+}
+
+/// Number of subsongs (1 for single-song files).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_subsong_count(player: *const Ym2149Player) -> usize {
+    unsafe { player_ref(player) }.map_or(0, |p| {
+        guard("counting subsongs", 0, || p.player.subsong_count())
+    })
+}
+
+/// Current subsong (1-based).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_current_subsong(player: *const Ym2149Player) -> usize {
+    unsafe { player_ref(player) }.map_or(0, |p| {
+        guard("reading the subsong", 0, || p.player.current_subsong())
+    })
+}
+
+/// Switch to a subsong (1-based). Returns false for an invalid index.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_set_subsong(
+    player: *mut Ym2149Player,
+    index: usize,
+) -> bool {
+    unsafe { player_mut(player) }
+        .is_some_and(|p| guard("switching subsongs", false, || p.player.set_subsong(index)))
+}
+
+/// Number of mutable channels (3 per PSG).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_channel_count(player: *const Ym2149Player) -> usize {
+    unsafe { player_ref(player) }.map_or(0, |p| {
+        guard("counting channels", 0, || p.player.channel_count())
+    })
+}
+
+/// Mute or unmute a channel (0-based).
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_set_channel_mute(
+    player: *mut Ym2149Player,
+    channel: usize,
+    mute: bool,
+) {
+    if let Some(p) = unsafe { player_mut(player) } {
+        guard("muting a channel", (), || {
+            p.player.set_channel_mute(channel, mute)
+        });
+    }
+}
+
+/// Whether a channel (0-based) is muted.
+///
+/// # Safety
+///
+/// `player` must be null or a live pointer from [`ym2149_player_new`].
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn ym2149_player_is_channel_muted(
+    player: *const Ym2149Player,
+    channel: usize,
+) -> bool {
+    unsafe { player_ref(player) }.is_some_and(|p| {
+        guard("reading a channel mute", false, || {
+            p.player.is_channel_muted(channel)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+    use ym2149_common::BasicMetadata;
+    use ym2149_common::test_support::read_asset;
+
+    /// Replayer that panics on every call that touches the emulation
+    struct PanickingPlayer(BasicMetadata);
+
+    impl ChiptunePlayerBase for PanickingPlayer {
+        fn play(&mut self) {
+            panic!("play");
+        }
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            panic!("state");
+        }
+        fn generate_samples_into(&mut self, _buffer: &mut [f32]) {
+            panic!("generate");
+        }
+        fn seek(&mut self, _position: f32) -> bool {
+            panic!("seek");
+        }
+        fn set_subsong(&mut self, _index: usize) -> bool {
+            panic!("set_subsong");
+        }
+        fn subsong_count(&self) -> usize {
+            panic!("subsong_count");
+        }
+    }
+
+    impl ChiptunePlayer for PanickingPlayer {
+        type Metadata = BasicMetadata;
+
+        fn metadata(&self) -> &BasicMetadata {
+            &self.0
+        }
+    }
+
+    #[test]
+    fn test_player_roundtrip() {
+        let Some(data) = read_asset("Ashtray.ym") else {
+            return;
+        };
+        unsafe {
+            let player = ym2149_player_new(data.as_ptr(), data.len());
+            assert!(!player.is_null());
+            assert!(
+                CStr::from_ptr(ym2149_player_format(player))
+                    .to_str()
+                    .unwrap()
+                    .starts_with("YM")
+            );
+            assert_eq!(ym2149_player_sample_rate(player), DEFAULT_SAMPLE_RATE);
+            assert_eq!(ym2149_player_subsong_count(player), 1);
+            assert!(ym2149_player_duration_seconds(player) > 0.0);
+
+            ym2149_player_play(player);
+            assert_eq!(ym2149_player_state(player), Ym2149State::Playing);
+            let mut buffer = vec![0.0f32; 4410];
+            ym2149_player_generate(player, buffer.as_mut_ptr(), buffer.len());
+            assert!(buffer.iter().any(|&s| s != 0.0));
+
+            ym2149_player_set_channel_mute(player, 1, true);
+            assert!(ym2149_player_is_channel_muted(player, 1));
+            ym2149_player_free(player);
+        }
+    }
+
+    #[test]
+    fn test_load_failure_sets_last_error() {
+        let data = b"not a song";
+        unsafe {
+            assert!(ym2149_player_new(data.as_ptr(), data.len()).is_null());
+            assert!(!ym2149_last_error().is_null());
+            assert!(ym2149_player_new(ptr::null(), 0).is_null());
+            let error = CStr::from_ptr(ym2149_last_error());
+            assert_eq!(error.to_str().unwrap(), "empty file data");
+
+            // Null players are ignored
+            ym2149_player_play(ptr::null_mut());
+            assert_eq!(ym2149_player_state(ptr::null()), Ym2149State::Stopped);
+            assert!(ym2149_player_title(ptr::null()).is_null());
+            ym2149_player_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_replayer_panic_does_not_unwind() {
+        let player = Box::into_raw(Box::new(Ym2149Player::new(PanickingPlayer(
+            BasicMetadata::default(),
+        ))));
+        unsafe {
+            ym2149_player_play(player);
+            assert_eq!(ym2149_player_state(player), Ym2149State::Stopped);
+            let mut buffer = vec![1.0f32; 64];
+            ym2149_player_generate(player, buffer.as_mut_ptr(), buffer.len());
+            assert!(buffer.iter().all(|&s| s == 0.0));
+            assert!(!ym2149_player_seek(player, 0.5));
+            assert!(!ym2149_player_set_subsong(player, 2));
+            assert_eq!(ym2149_player_subsong_count(player), 0);
+
+            let error = CStr::from_ptr(ym2149_last_error()).to_str().unwrap();
+            assert!(error.contains("panicked"), "{error}");
+            ym2149_player_free(player);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+//! Error types for catalog parsing.
+
+use thiserror::Error;
+
+use crate::version::CatalogVersion;
+
+/// Result type for catalog operations
+pub type Result<T> = std::result::Result<T, CatalogError>;
+
+/// Errors from reading a catalog
+#[derive(Debug, Error)]
+pub enum CatalogError {
+    /// Malformed JSON or a field of the wrong type
+    #[error("invalid catalog JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The catalog has no `version` field
+    #[error("catalog has no version")]
+    MissingVersion,
+
+    /// The `version` field is not `major.minor`
+    #[error("invalid catalog version: {0:?}")]
+    InvalidVersion(String),
+
+    /// The catalog was written by a newer tool, or predates 1.1
+    #[error(
+        "unsupported catalog version {found} (supported: {} to {})",
+        CatalogVersion::OLDEST,
+        CatalogVersion::CURRENT
+    )]
+    UnsupportedVersion {
+        /// Version of the catalog
+        found: CatalogVersion,
+    },
+}
@@ -0,0 +1,28 @@
+//! Track catalog schema shared by the YM2149 metadata tool and web player.
+//!
+//! `ym-metadata` writes a JSON [`Catalog`] of scanned songs (tags, waveform
+//! peaks, fingerprints, artwork) and the web player reads it. Both sides use
+//! these types, and [`Catalog::from_json`] checks the schema version and
+//! upgrades catalogs written by older tools, so the two cannot drift apart.
+//!
+//! # Example
+//!
+//! ```rust
+//! use ym2149_catalog::{CATALOG_VERSION, Catalog};
+//!
+//! let json = r#"{"version": "1.2", "generated": "2025-01-01T00:00:00Z",
+//!                "collections": [], "tracks": []}"#;
+//! let catalog = Catalog::from_json(json)?;
+//! assert_eq!(catalog.version, CATALOG_VERSION);
+//! # Ok::<(), ym2149_catalog::CatalogError>(())
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod error;
+mod schema;
+pub mod version;
+
+pub use crate::error::{CatalogError, Result};
+pub use crate::schema::{Catalog, CollectionInfo, Fingerprint, TrackMetadata};
+pub use crate::version::{CATALOG_VERSION, CatalogVersion, migrate};
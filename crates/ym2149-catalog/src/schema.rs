@@ -0,0 +1,211 @@
+//! Catalog schema.
+//!
+//! The JSON catalog lists every scanned track with its tags and, when
+//! requested, waveform peaks, fingerprint, artwork and mood tags. Optional
+//! fields are omitted when empty to keep large catalogs small.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::version::{CatalogVersion, migrate};
+
+/// Catalog entry of one track
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrackMetadata {
+    /// Path relative to the scanned base directory (forward slashes)
+    pub path: String,
+    /// Song title (file name if the file has none)
+    pub title: String,
+    /// Composer (from the directory name if the file has none)
+    pub author: String,
+    /// Format name (`SNDH`, `YM6`, `AY`, `AKS`, ...)
+    pub format: String,
+    /// Release year, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<String>,
+    /// Number of subsongs (omitted when 1)
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    pub subsongs: u32,
+    /// Number of sound channels (omitted when 3)
+    #[serde(default = "three", skip_serializing_if = "is_three")]
+    pub channels: u32,
+    /// Duration of the first subsong in seconds, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<f32>,
+    /// Collection id (`sndh`, `ym`, `ay` or `arkos`)
+    pub collection: String,
+    /// Waveform peaks as base64-encoded bytes (0-255 per bar)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub w: Option<String>,
+    /// Audio fingerprint for similarity matching
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fp: Option<Fingerprint>,
+    /// Artwork: image path relative to the base path, or a data URI
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub art: Option<String>,
+    /// Mood and style tags (`calm`, `bright`, ...)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Audio features for similarity matching
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Fingerprint {
+    /// Average amplitude (0.0-1.0)
+    pub amp: f32,
+    /// Note density (zero crossings per second) - indicates pitch/frequency content
+    pub density: u32,
+    /// Amplitude variance (0.0-1.0) - how dynamic the song is
+    pub variance: f32,
+    /// Peak-to-average ratio - how "punchy" the song is
+    pub punch: f32,
+    /// Brightness (0.0-1.0) - high vs low frequency content
+    pub brightness: f32,
+    /// Energy histogram (8 bins) - distribution of amplitude levels
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hist: Option<[u8; 8]>,
+    /// Section energies (4 quarters) - song structure fingerprint
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sections: Option<[u8; 4]>,
+    /// Tempo indicator (peaks per second) - rhythm signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempo: Option<u16>,
+    // === New spectral and rhythm features ===
+    /// Spectral centroid (0-1) - center of mass of spectrum (low=bassy, high=bright)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub centroid: Option<f32>,
+    /// Spectral flatness (0-1) - 0=tonal, 1=noise-like
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub flatness: Option<f32>,
+    /// Spectral bands [bass, low-mid, high-mid, treble] (0-255 each)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bands: Option<[u8; 4]>,
+    /// Chroma features - 12-bin pitch class histogram (C, C#, D, ..., B)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chroma: Option<[u8; 12]>,
+    /// Rhythm regularity (0-1) - how consistent the beat pattern is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhythm_reg: Option<f32>,
+    /// Rhythm strength (0-1) - how prominent/strong the beat is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rhythm_str: Option<f32>,
+    /// MFCCs - Mel-Frequency Cepstral Coefficients (13 coefficients, industry standard for timbre)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfcc: Option<[i8; 13]>,
+    /// MFCC Deltas - How timbre changes over time (13 coefficients)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfcc_d: Option<[i8; 13]>,
+    /// MFCC Delta-Deltas - Acceleration of timbre changes (13 coefficients)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mfcc_dd: Option<[i8; 13]>,
+    /// Chromagram - Pitch class distribution over 8 time segments (8 × 12 = 96 values)
+    /// Captures melodic/harmonic progression through the song
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chromagram: Option<Vec<u8>>,
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
+}
+
+fn is_three(n: &u32) -> bool {
+    *n == 3
+}
+
+fn one() -> u32 {
+    1
+}
+
+fn three() -> u32 {
+    3
+}
+
+/// Summary of one collection in a [`Catalog`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CollectionInfo {
+    /// Collection id
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// One-line description
+    pub description: String,
+    /// Format name (`SNDH`, `YM6`, `AY`, `AKS`, ...)
+    pub format: String,
+    /// Number of tracks in the catalog
+    #[serde(rename = "trackCount")]
+    pub track_count: usize,
+}
+
+/// Track catalog as read by the web player
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Catalog {
+    /// Catalog schema version ([`CATALOG_VERSION`](crate::CATALOG_VERSION))
+    pub version: String,
+    /// Generation time (RFC 3339)
+    pub generated: String,
+    /// Collections that have tracks
+    pub collections: Vec<CollectionInfo>,
+    /// All tracks
+    pub tracks: Vec<TrackMetadata>,
+}
+
+impl Catalog {
+    /// Parse a catalog, upgrading older schema versions to
+    /// [`CATALOG_VERSION`](crate::CATALOG_VERSION).
+    ///
+    /// Fails with [`CatalogError::UnsupportedVersion`](crate::CatalogError::UnsupportedVersion)
+    /// for catalogs written by a newer tool or before 1.1.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let mut value = serde_json::from_str(json)?;
+        migrate(&mut value)?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Parsed [`version`](Self::version).
+    pub fn schema_version(&self) -> Result<CatalogVersion> {
+        self.version.parse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CATALOG_VERSION, CatalogError};
+
+    #[test]
+    fn test_from_json_upgrades_old_catalog() {
+        // As written by ym-metadata 1.1: no artwork or tags, defaults omitted
+        let json = r#"{
+            "version": "1.1",
+            "generated": "2025-01-01T00:00:00Z",
+            "collections": [{"id": "ym", "name": "YM Collection",
+                "description": "YM format chiptunes", "format": "YM", "trackCount": 1}],
+            "tracks": [{"path": "ym/Ashtray.ym", "title": "Ashtray", "author": "Mad Max",
+                "format": "YM6", "collection": "ym"}]
+        }"#;
+        let catalog = Catalog::from_json(json).unwrap();
+        assert_eq!(catalog.version, CATALOG_VERSION);
+        assert_eq!(catalog.schema_version().unwrap(), CatalogVersion::CURRENT);
+        assert_eq!(catalog.collections[0].track_count, 1);
+        let track = &catalog.tracks[0];
+        assert_eq!((track.subsongs, track.channels), (1, 3));
+        assert!(track.art.is_none() && track.tags.is_empty());
+
+        let written = serde_json::to_string(&catalog).unwrap();
+        assert!(!written.contains("subsongs") && !written.contains("tags"));
+        assert_eq!(Catalog::from_json(&written).unwrap().tracks.len(), 1);
+    }
+
+    #[test]
+    fn test_from_json_rejects_newer_catalog() {
+        let json = r#"{"version": "2.0", "generated": "", "collections": [], "tracks": []}"#;
+        assert!(matches!(
+            Catalog::from_json(json),
+            Err(CatalogError::UnsupportedVersion { found }) if found.major == 2
+        ));
+        assert!(matches!(
+            Catalog::from_json("[]"),
+            Err(CatalogError::MissingVersion)
+        ));
+    }
+}
@@ -0,0 +1,160 @@
+//! Schema versions and migration of older catalogs.
+//!
+//! Minor versions only add optional fields, so older catalogs parse as they
+//! are once their version is checked:
+//!
+//! | Version | Change |
+//! |---------|--------|
+//! | 1.1 | Waveform peaks (`w`) and fingerprints (`fp`) |
+//! | 1.2 | Artwork (`art`) |
+//! | 1.3 | Mood tags (`tags`) |
+//!
+//! A schema change that renames or removes fields adds a step to
+//! `MIGRATIONS` that rewrites the JSON before it is parsed, so readers only
+//! ever see the current layout.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::error::{CatalogError, Result};
+
+/// Version written to [`Catalog::version`](crate::Catalog::version)
+pub const CATALOG_VERSION: &str = "1.3";
+
+/// Rewrite of the catalog JSON from the previous version
+type Step = fn(&mut Value);
+
+/// Migration steps in order, with the version each step upgrades to.
+const MIGRATIONS: &[(CatalogVersion, Step)] = &[
+    (CatalogVersion::new(1, 2), optional_fields_only),
+    (CatalogVersion::new(1, 3), optional_fields_only),
+];
+
+/// Step for versions that only add optional fields.
+fn optional_fields_only(_catalog: &mut Value) {}
+
+/// Catalog schema version (`major.minor`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CatalogVersion {
+    /// Incremented when fields change meaning or are removed
+    pub major: u16,
+    /// Incremented when optional fields are added
+    pub minor: u16,
+}
+
+impl CatalogVersion {
+    /// Oldest version [`migrate`] upgrades
+    pub const OLDEST: Self = Self::new(1, 1);
+    /// Version of [`CATALOG_VERSION`]
+    pub const CURRENT: Self = Self::new(1, 3);
+
+    /// Version `major.minor`.
+    pub const fn new(major: u16, minor: u16) -> Self {
+        Self { major, minor }
+    }
+
+    /// Whether [`migrate`] can upgrade catalogs of this version.
+    pub fn is_supported(self) -> bool {
+        (Self::OLDEST..=Self::CURRENT).contains(&self)
+    }
+}
+
+impl FromStr for CatalogVersion {
+    type Err = CatalogError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || CatalogError::InvalidVersion(s.to_string());
+        let (major, minor) = s.split_once('.').ok_or_else(invalid)?;
+        Ok(Self::new(
+            major.parse().map_err(|_| invalid())?,
+            minor.parse().map_err(|_| invalid())?,
+        ))
+    }
+}
+
+impl fmt::Display for CatalogVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+/// Upgrade catalog JSON to [`CATALOG_VERSION`] in place.
+///
+/// Returns the version the catalog had. Catalogs written by a newer tool are
+/// rejected rather than read with their new fields silently dropped.
+pub fn migrate(catalog: &mut Value) -> Result<CatalogVersion> {
+    let version = catalog.get("version").ok_or(CatalogError::MissingVersion)?;
+    let found: CatalogVersion = version
+        .as_str()
+        .ok_or_else(|| CatalogError::InvalidVersion(version.to_string()))?
+        .parse()?;
+    if !found.is_supported() {
+        return Err(CatalogError::UnsupportedVersion { found });
+    }
+    for (version, step) in MIGRATIONS {
+        if *version > found {
+            step(catalog);
+        }
+    }
+    catalog["version"] = Value::from(CATALOG_VERSION);
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_current_version_matches_constant() {
+        assert_eq!(
+            CATALOG_VERSION.parse::<CatalogVersion>().unwrap(),
+            CatalogVersion::CURRENT
+        );
+        assert_eq!(CatalogVersion::CURRENT.to_string(), CATALOG_VERSION);
+        assert_eq!(MIGRATIONS.last().unwrap().0, CatalogVersion::CURRENT);
+    }
+
+    #[test]
+    fn test_parse_version() {
+        assert_eq!(
+            "1.10".parse::<CatalogVersion>().unwrap(),
+            CatalogVersion::new(1, 10)
+        );
+        assert!(CatalogVersion::new(1, 10) > CatalogVersion::new(1, 9));
+        for invalid in ["", "1", "1.", "a.b", "1.2.3"] {
+            assert!(matches!(
+                invalid.parse::<CatalogVersion>(),
+                Err(CatalogError::InvalidVersion(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_migrate_stamps_current_version() {
+        let mut catalog = json!({"version": "1.1", "tracks": []});
+        assert_eq!(migrate(&mut catalog).unwrap(), CatalogVersion::OLDEST);
+        assert_eq!(catalog["version"], CATALOG_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_rejects_unsupported_versions() {
+        for version in ["1.0", "1.4", "2.0"] {
+            let mut catalog = json!({"version": version});
+            assert!(matches!(
+                migrate(&mut catalog),
+                Err(CatalogError::UnsupportedVersion { .. })
+            ));
+        }
+        assert!(matches!(
+            migrate(&mut json!({"tracks": []})),
+            Err(CatalogError::MissingVersion)
+        ));
+        assert!(matches!(
+            migrate(&mut json!({"version": 1.1})),
+            Err(CatalogError::InvalidVersion(_))
+        ));
+    }
+}
@@ -0,0 +1,95 @@
+//! Allocation counting for checking the audio hot path.
+//!
+//! Players promise that [`ChiptunePlayerBase::generate_samples_into`] does not
+//! allocate once playback is running, so it is safe to call from a real-time
+//! audio callback. [`CountingAllocator`] makes that checkable: install it as
+//! the global allocator of a test or binary and measure a player with
+//! [`allocations_per_second`].
+//!
+//! ```ignore
+//! use ym2149_common::{CountingAllocator, allocations_per_second};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator;
+//!
+//! player.play();
+//! assert_eq!(allocations_per_second(&mut player, 882, 10), 0.0);
+//! ```
+
+use crate::ChiptunePlayerBase;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+thread_local! {
+    /// Allocations made by the current thread. Per thread, so tests running
+    /// in parallel do not see each other's allocations.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // `try_with` fails only while the thread is being torn down
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+/// Global allocator that forwards to [`System`] and counts allocations per thread.
+///
+/// Reallocations count as allocations; deallocations are free.
+pub struct CountingAllocator;
+
+// SAFETY: every call is forwarded unchanged to the system allocator.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        // SAFETY: same contract as `GlobalAlloc::alloc`.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        // SAFETY: same contract as `GlobalAlloc::alloc_zeroed`.
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        // SAFETY: same contract as `GlobalAlloc::realloc`.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: same contract as `GlobalAlloc::dealloc`.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Allocations made by the current thread so far.
+///
+/// Always 0 unless [`CountingAllocator`] is the global allocator.
+pub fn thread_allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Render `seconds` of audio in blocks of `block_size` samples and return the
+/// number of allocations per second of audio.
+///
+/// The block buffer is allocated before counting starts, so the result only
+/// covers the player's own work. Requires [`CountingAllocator`] as the global
+/// allocator.
+pub fn allocations_per_second<P: ChiptunePlayerBase + ?Sized>(
+    player: &mut P,
+    block_size: usize,
+    seconds: u32,
+) -> f64 {
+    let mut buffer = vec![0.0f32; block_size.max(1)];
+    let total = player.sample_rate() as usize * seconds as usize;
+    let before = thread_allocations();
+
+    let mut rendered = 0;
+    while rendered < total {
+        let len = buffer.len().min(total - rendered);
+        player.generate_samples_into(&mut buffer[..len]);
+        rendered += len;
+    }
+
+    (thread_allocations() - before) as f64 / f64::from(seconds.max(1))
+}
@@ -3,6 +3,8 @@
 //! This module defines the core interface that all YM2149 backends must implement,
 //! whether they are cycle-accurate hardware emulations or experimental synthesizers.
 
+use crate::{ChannelTap, FilterChain, OutputModel};
+
 /// Common interface for YM2149 chip backends
 ///
 /// This trait allows different implementations to be used interchangeably:
@@ -68,7 +70,8 @@ pub trait Ym2149Backend: Send {
     ///
     /// # Returns
     ///
-    /// Current register value, or 0x00 for invalid addresses
+    /// Value read back from the register. Backends may model the hardware
+    /// read behaviour (masked unused bits, I/O ports, floating bus).
     fn read_register(&self, addr: u8) -> u8;
 
     /// Load all 16 YM2149 registers at once
@@ -190,6 +193,49 @@ pub trait Ym2149Backend: Send {
     /// * `enabled` - true to enable filter, false to disable
     fn set_color_filter(&mut self, enabled: bool);
 
+    /// Configure the post-mix output filters (DC blocker, low-pass)
+    ///
+    /// Default implementation is a no-op for backends without configurable
+    /// output filtering.
+    ///
+    /// # Arguments
+    ///
+    /// * `chain` - Filter stages to apply; [`FilterChain::raw`] disables all of them
+    fn set_filter_chain(&mut self, _chain: FilterChain) {
+        // Default: no-op for backends that don't support this
+    }
+
+    /// Get the active output filter configuration
+    ///
+    /// Default implementation reports [`FilterChain::default`].
+    fn filter_chain(&self) -> FilterChain {
+        FilterChain::default()
+    }
+
+    /// Apply an analog output stage preset
+    ///
+    /// Shorthand for `set_filter_chain(model.filter_chain())`.
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.set_filter_chain(model.filter_chain());
+    }
+
+    /// Enable or disable the per-channel output tap
+    ///
+    /// With `Some(capacity)` the backend records the last `capacity` output
+    /// samples of each channel (see [`ChannelTap`]); `None` disables
+    /// recording. Default implementation is a no-op for backends without
+    /// tap support.
+    fn set_channel_tap(&mut self, _capacity: Option<usize>) {
+        // Default: no-op for backends that don't support this
+    }
+
+    /// Get the per-channel output tap, if enabled
+    ///
+    /// Default implementation returns `None`.
+    fn channel_tap(&self) -> Option<&ChannelTap> {
+        None
+    }
+
     /// Trigger envelope restart (used by YM6 Sync Buzzer effect)
     ///
     /// This is a hardware-specific feature. Default implementation is a no-op.
@@ -198,6 +244,19 @@ pub trait Ym2149Backend: Send {
         // Default: no-op for backends that don't support this
     }
 
+    /// Trigger envelope restart part-way into the next sample
+    ///
+    /// Lets timer-driven retriggers land between output samples. Default
+    /// implementation restarts immediately via [`trigger_envelope`](Self::trigger_envelope).
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - Fraction (0.0-1.0) of the next `clock()` period that elapses before the restart
+    fn trigger_envelope_at(&mut self, offset: f32) {
+        let _ = offset;
+        self.trigger_envelope();
+    }
+
     /// Override drum sample for a channel (used by YM6 DigiDrum effect)
     ///
     /// This is a hardware-specific feature. Default implementation is a no-op.
@@ -13,12 +13,23 @@
 //!
 //! The cache also stores YM2149 channel outputs after each refill,
 //! enabling synchronized visualization without sample-accurate overhead.
+//!
+//! # Track Pre-rendering
+//!
+//! [`TrackCache`] renders a whole track in chunks on a second player
+//! instance, so players that cannot seek (Arkos, AY) can jump to any
+//! position that has already been rendered. Rendering is driven by the
+//! caller (e.g. from a browser idle callback) and can be cancelled at any
+//! time.
 
-use crate::{ChiptunePlayerBase, PlaybackState};
+use crate::{ChiptunePlayerBase, OutputGain, OutputModel, PlaybackState, RegisterDelta};
 
 /// Default cache size in samples.
 pub const DEFAULT_CACHE_SIZE: usize = 512;
 
+/// Default number of samples rendered per [`TrackCache::render_chunk`] call.
+pub const DEFAULT_PRERENDER_CHUNK: usize = 8192;
+
 // ============================================================================
 // SampleCache - Standalone cache helper
 // ============================================================================
@@ -143,6 +154,174 @@ impl Default for SampleCache {
     }
 }
 
+// ============================================================================
+// TrackCache - Pre-rendered track for instant seeking
+// ============================================================================
+
+/// A track pre-rendered from its start, for seeking players that cannot seek.
+///
+/// The cache owns a second player (the renderer) positioned at the start of
+/// the song and renders it chunk by chunk on request. Playback can then be
+/// served from any rendered position with [`seek`](Self::seek) and
+/// [`play_into`](Self::play_into); if playback overtakes the background
+/// rendering, the missing samples are rendered on the spot.
+///
+/// Cached audio is the renderer's output at the time it was rendered: output
+/// gain is applied by the owner, but channel mutes and the output model of
+/// the live player do not affect it.
+///
+/// # Example
+///
+/// ```ignore
+/// use ym2149_common::{DEFAULT_PRERENDER_CHUNK, TrackCache};
+///
+/// let mut cache = TrackCache::new(Box::new(second_player), total_samples);
+/// // From an idle callback, until it returns true
+/// cache.render_chunk(DEFAULT_PRERENDER_CHUNK);
+///
+/// if cache.seek(target_sample) {
+///     cache.play_into(&mut buffer);
+/// }
+/// ```
+pub struct TrackCache {
+    /// Player rendering the track; `None` once complete or cancelled.
+    renderer: Option<Box<dyn ChiptunePlayerBase>>,
+    samples: Vec<f32>,
+    total: usize,
+    /// Playback position while audio is served from the cache.
+    position: Option<usize>,
+}
+
+impl TrackCache {
+    /// Create a cache rendering `total_samples` samples with `renderer`.
+    ///
+    /// The renderer must be at the start of the song; it is started here.
+    pub fn new(mut renderer: Box<dyn ChiptunePlayerBase>, total_samples: usize) -> Self {
+        renderer.play();
+        Self {
+            renderer: Some(renderer),
+            samples: Vec::new(),
+            total: total_samples,
+            position: None,
+        }
+    }
+
+    /// Render up to `max_samples` more samples.
+    ///
+    /// Returns `true` once the whole track is rendered (or rendering was
+    /// cancelled), so callers can stop scheduling chunks.
+    pub fn render_chunk(&mut self, max_samples: usize) -> bool {
+        let target = self.samples.len().saturating_add(max_samples);
+        self.render_until(target);
+        self.renderer.is_none()
+    }
+
+    /// Stop rendering and release the renderer.
+    ///
+    /// Samples rendered so far stay available for seeking.
+    pub fn cancel(&mut self) {
+        self.renderer = None;
+    }
+
+    /// Whether the whole track has been rendered.
+    pub fn is_complete(&self) -> bool {
+        self.samples.len() >= self.total
+    }
+
+    /// Whether more samples can still be rendered.
+    pub fn is_rendering(&self) -> bool {
+        self.renderer.is_some()
+    }
+
+    /// Number of samples rendered so far.
+    pub fn rendered_samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Length of the track in samples.
+    pub fn total_samples(&self) -> usize {
+        self.total
+    }
+
+    /// Fraction of the track rendered (0.0 to 1.0).
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.samples.len() as f32 / self.total as f32
+        }
+    }
+
+    /// Serve playback from `sample` onwards.
+    ///
+    /// Returns `false` (and leaves playback unchanged) if that position has
+    /// not been rendered yet.
+    pub fn seek(&mut self, sample: usize) -> bool {
+        if sample >= self.samples.len() {
+            return false;
+        }
+        self.position = Some(sample);
+        true
+    }
+
+    /// Current playback position in samples, or `None` when playback is not
+    /// served from the cache.
+    pub fn position(&self) -> Option<usize> {
+        self.position
+    }
+
+    /// Stop serving playback from the cache.
+    pub fn stop_playback(&mut self) {
+        self.position = None;
+    }
+
+    /// Fill `buffer` from the playback position.
+    ///
+    /// Returns `false` when the end of the track is reached: the rest of the
+    /// buffer is silent and playback leaves the cache. Does nothing (and
+    /// returns `false`) unless [`seek`](Self::seek) succeeded before.
+    pub fn play_into(&mut self, buffer: &mut [f32]) -> bool {
+        let Some(position) = self.position else {
+            return false;
+        };
+        self.render_until(position + buffer.len());
+
+        let available = self
+            .samples
+            .len()
+            .saturating_sub(position)
+            .min(buffer.len());
+        buffer[..available].copy_from_slice(&self.samples[position..position + available]);
+        buffer[available..].fill(0.0);
+
+        if available < buffer.len() {
+            self.position = None;
+            false
+        } else {
+            self.position = Some(position + available);
+            true
+        }
+    }
+
+    /// Render until `target` samples (capped at the track length) exist.
+    fn render_until(&mut self, target: usize) {
+        let target = target.min(self.total);
+        let Some(renderer) = self.renderer.as_mut() else {
+            return;
+        };
+        if target > self.samples.len() {
+            let start = self.samples.len();
+            self.samples.resize(target, 0.0);
+            renderer.generate_samples_into(&mut self.samples[start..]);
+        }
+        if self.samples.len() >= self.total || renderer.state() == PlaybackState::Stopped {
+            // The song ended early: the track is as long as what was rendered
+            self.total = self.samples.len();
+            self.renderer = None;
+        }
+    }
+}
+
 // ============================================================================
 // CachedPlayer - Full player wrapper
 // ============================================================================
@@ -180,17 +359,29 @@ pub trait CacheablePlayer: ChiptunePlayerBase {
 /// let sample = cached.generate_sample();
 /// let channels = cached.cached_channel_outputs();
 /// ```
+///
+/// With a [`TrackCache`] attached via [`start_prerender`](Self::start_prerender),
+/// [`seek_samples`](Self::seek_samples) jumps to any pre-rendered position
+/// and playback is served from the track cache until it ends or the player
+/// is stopped.
 pub struct CachedPlayer<P: CacheablePlayer> {
     player: P,
     cache: SampleCache,
+    track: Option<TrackCache>,
+    /// Gain applied to audio served from the track cache.
+    track_gain: OutputGain,
 }
 
 impl<P: CacheablePlayer> CachedPlayer<P> {
     /// Create a new cached player with the specified cache size.
     pub fn new(player: P, cache_size: usize) -> Self {
+        let mut track_gain = OutputGain::new(player.gain());
+        track_gain.set_soft_clip(player.soft_clip());
         Self {
             player,
             cache: SampleCache::new(cache_size),
+            track: None,
+            track_gain,
         }
     }
 
@@ -238,17 +429,93 @@ impl<P: CacheablePlayer> CachedPlayer<P> {
         self.cache.reset();
     }
 
+    /// Start pre-rendering the track on `renderer`, a second instance of the
+    /// song positioned at its start and left at unity gain (the wrapper
+    /// applies its own gain to cached audio).
+    ///
+    /// Replaces any previous track cache. Drive the rendering with
+    /// [`prerender_chunk`](Self::prerender_chunk).
+    pub fn start_prerender(&mut self, renderer: Box<dyn ChiptunePlayerBase>, total_samples: usize) {
+        self.track = Some(TrackCache::new(renderer, total_samples));
+    }
+
+    /// Render the next chunk of the track cache.
+    ///
+    /// Returns `true` when there is nothing left to render.
+    pub fn prerender_chunk(&mut self, max_samples: usize) -> bool {
+        self.track
+            .as_mut()
+            .is_none_or(|track| track.render_chunk(max_samples))
+    }
+
+    /// Stop pre-rendering; already rendered audio stays seekable.
+    pub fn cancel_prerender(&mut self) {
+        if let Some(track) = self.track.as_mut() {
+            track.cancel();
+        }
+    }
+
+    /// Drop the track cache and return to the live player.
+    pub fn clear_track_cache(&mut self) {
+        self.track = None;
+        self.reset_cache();
+    }
+
+    /// Get the track cache, if pre-rendering was started.
+    pub fn track_cache(&self) -> Option<&TrackCache> {
+        self.track.as_ref()
+    }
+
+    /// Continue playback at `sample` from the track cache.
+    ///
+    /// Returns `false` if no track cache exists or the position has not been
+    /// rendered yet.
+    pub fn seek_samples(&mut self, sample: usize) -> bool {
+        let seeked = self.track.as_mut().is_some_and(|track| track.seek(sample));
+        if seeked {
+            self.reset_cache();
+        }
+        seeked
+    }
+
     /// Refill the cache from the underlying player.
     fn refill_cache(&mut self) {
         self.player.on_cache_refill();
-        self.player
-            .generate_samples_into(self.cache.sample_buffer_mut());
+        render_into(
+            &mut self.player,
+            &mut self.track,
+            &self.track_gain,
+            self.cache.sample_buffer_mut(),
+        );
         self.cache
             .fill_channel_outputs(self.player.get_channel_outputs());
         self.cache.mark_filled();
     }
 }
 
+/// Fill `buffer` from the track cache while it serves playback, otherwise
+/// from the live player.
+fn render_into<P: ChiptunePlayerBase>(
+    player: &mut P,
+    track: &mut Option<TrackCache>,
+    gain: &OutputGain,
+    buffer: &mut [f32],
+) {
+    let Some(track) = track.as_mut().filter(|track| track.position().is_some()) else {
+        player.generate_samples_into(buffer);
+        return;
+    };
+    if player.state() != PlaybackState::Playing {
+        buffer.fill(0.0);
+        return;
+    }
+    if !track.play_into(buffer) {
+        // End of the track: the live player cannot continue from here
+        player.stop();
+    }
+    gain.apply(buffer);
+}
+
 // Forward ChiptunePlayerBase methods to the inner player
 impl<P: CacheablePlayer> ChiptunePlayerBase for CachedPlayer<P> {
     fn play(&mut self) {
@@ -261,6 +528,9 @@ impl<P: CacheablePlayer> ChiptunePlayerBase for CachedPlayer<P> {
 
     fn stop(&mut self) {
         self.player.stop();
+        if let Some(track) = self.track.as_mut() {
+            track.stop_playback();
+        }
         self.reset_cache();
     }
 
@@ -269,14 +539,52 @@ impl<P: CacheablePlayer> ChiptunePlayerBase for CachedPlayer<P> {
     }
 
     fn generate_samples_into(&mut self, buffer: &mut [f32]) {
-        // For bulk generation, bypass the cache and use the player directly
-        self.player.generate_samples_into(buffer);
+        // For bulk generation, bypass the sample cache
+        render_into(&mut self.player, &mut self.track, &self.track_gain, buffer);
     }
 
     fn sample_rate(&self) -> u32 {
         self.player.sample_rate()
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.player.set_gain(gain);
+        self.track_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.player.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.player.set_soft_clip(enabled);
+        self.track_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.player.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.player.set_playback_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.player.playback_rate()
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.player.set_output_model(model);
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.player.set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.player.read_channel_tap(channel, out)
+    }
+
     fn set_channel_mute(&mut self, channel: usize, mute: bool) {
         self.player.set_channel_mute(channel, mute);
     }
@@ -285,8 +593,35 @@ impl<P: CacheablePlayer> ChiptunePlayerBase for CachedPlayer<P> {
         self.player.is_channel_muted(channel)
     }
 
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        let delta = self.player.step_frames(frames)?;
+        // Stepping moves the live player, which takes over again
+        if let Some(track) = self.track.as_mut() {
+            track.stop_playback();
+        }
+        self.reset_cache();
+        Some(delta)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        let advanced = self.player.advance_frames(frames);
+        if advanced > 0 {
+            if let Some(track) = self.track.as_mut() {
+                track.stop_playback();
+            }
+            self.reset_cache();
+        }
+        advanced
+    }
+
     fn playback_position(&self) -> f32 {
-        self.player.playback_position()
+        match self.track.as_ref().and_then(|track| {
+            let position = track.position()?;
+            Some(position as f32 / track.total_samples().max(1) as f32)
+        }) {
+            Some(position) => position,
+            None => self.player.playback_position(),
+        }
     }
 
     fn subsong_count(&self) -> usize {
@@ -299,6 +634,8 @@ impl<P: CacheablePlayer> ChiptunePlayerBase for CachedPlayer<P> {
 
     fn set_subsong(&mut self, index: usize) -> bool {
         if self.player.set_subsong(index) {
+            // The track cache holds the previous subsong
+            self.track = None;
             self.reset_cache();
             true
         } else {
@@ -427,4 +764,102 @@ mod tests {
         // After stop, cache should need refill
         assert!(cached.cache.needs_refill());
     }
+
+    #[test]
+    fn test_track_cache_renders_in_chunks() {
+        let mut track = TrackCache::new(Box::new(MockPlayer::new()), 100);
+
+        assert!(!track.render_chunk(40));
+        assert_eq!(track.rendered_samples(), 40);
+        assert!((track.progress() - 0.4).abs() < 0.0001);
+
+        assert!(!track.render_chunk(40));
+        assert!(track.render_chunk(40));
+        assert_eq!(track.rendered_samples(), 100);
+        assert!(track.is_complete());
+        assert!(!track.is_rendering());
+    }
+
+    #[test]
+    fn test_track_cache_seeks_only_into_rendered_audio() {
+        let mut track = TrackCache::new(Box::new(MockPlayer::new()), 100);
+        track.render_chunk(50);
+
+        assert!(!track.seek(60));
+        assert!(track.seek(30));
+
+        let mut buffer = [0.0; 4];
+        assert!(track.play_into(&mut buffer));
+        assert!((buffer[0] - 0.030).abs() < 0.0001);
+        assert!((buffer[3] - 0.033).abs() < 0.0001);
+        assert_eq!(track.position(), Some(34));
+    }
+
+    #[test]
+    fn test_track_cache_renders_ahead_of_playback_on_demand() {
+        let mut track = TrackCache::new(Box::new(MockPlayer::new()), 100);
+        track.render_chunk(10);
+        track.seek(8);
+
+        let mut buffer = [0.0; 6];
+        assert!(track.play_into(&mut buffer));
+        assert!((buffer[5] - 0.013).abs() < 0.0001);
+        assert_eq!(track.rendered_samples(), 14);
+    }
+
+    #[test]
+    fn test_track_cache_ends_with_the_track() {
+        let mut track = TrackCache::new(Box::new(MockPlayer::new()), 10);
+        track.render_chunk(10);
+        track.seek(8);
+
+        let mut buffer = [1.0; 4];
+        assert!(!track.play_into(&mut buffer));
+        assert_eq!(&buffer[2..], &[0.0, 0.0]);
+        assert_eq!(track.position(), None);
+    }
+
+    #[test]
+    fn test_track_cache_cancel_keeps_rendered_audio() {
+        let mut track = TrackCache::new(Box::new(MockPlayer::new()), 100);
+        track.render_chunk(20);
+        track.cancel();
+
+        assert!(track.render_chunk(20));
+        assert_eq!(track.rendered_samples(), 20);
+        assert!(track.seek(10));
+        assert!(!track.seek(20));
+    }
+
+    #[test]
+    fn test_cached_player_seeks_through_track_cache() {
+        let mut cached = CachedPlayer::new(MockPlayer::new(), 16);
+        assert!(!cached.seek_samples(10));
+
+        cached.start_prerender(Box::new(MockPlayer::new()), 1000);
+        assert!(!cached.prerender_chunk(500));
+        assert!(cached.seek_samples(400));
+        assert!((cached.playback_position() - 0.4).abs() < 0.0001);
+
+        let mut buffer = [0.0; 8];
+        cached.generate_samples_into(&mut buffer);
+        assert!((buffer[0] - 0.400).abs() < 0.0001);
+        // The live player was not advanced
+        assert_eq!(cached.inner().samples_generated, 0);
+
+        let sample = cached.generate_sample();
+        assert!((sample - 0.408).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_cached_player_stops_at_end_of_track_cache() {
+        let mut cached = CachedPlayer::new(MockPlayer::new(), 16);
+        cached.start_prerender(Box::new(MockPlayer::new()), 10);
+        cached.prerender_chunk(10);
+        cached.seek_samples(5);
+
+        let mut buffer = [0.0; 8];
+        cached.generate_samples_into(&mut buffer);
+        assert_eq!(cached.state(), PlaybackState::Stopped);
+    }
 }
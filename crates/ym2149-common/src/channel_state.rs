@@ -17,9 +17,16 @@
 //! }
 //! ```
 
+use std::collections::VecDeque;
+
 /// Standard Atari ST master clock for frequency calculations.
 const ATARI_ST_CLOCK: f32 = 2_000_000.0;
 
+/// Number of samples in an [`envelope_waveform`] (two 32-step envelope cycles).
+pub const ENVELOPE_WAVEFORM_LEN: usize = 64;
+
+static ENVELOPE_WAVEFORMS: [[f32; ENVELOPE_WAVEFORM_LEN]; 16] = build_envelope_waveforms();
+
 /// State of a single YM2149 channel extracted from registers.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ChannelState {
@@ -31,6 +38,8 @@ pub struct ChannelState {
     pub note_name: Option<&'static str>,
     /// MIDI note number (21-108 for piano range, None if out of range).
     pub midi_note: Option<u8>,
+    /// Deviation from `note_name` in cents (-50 to +50, None if out of range).
+    pub cents: Option<f32>,
     /// Raw amplitude value (0-15).
     pub amplitude: u8,
     /// Normalized amplitude (0.0-1.0) for visualization.
@@ -58,6 +67,13 @@ pub struct EnvelopeState {
     pub frequency_hz: Option<f32>,
 }
 
+impl EnvelopeState {
+    /// Waveform of the current shape, see [`envelope_waveform`].
+    pub fn waveform(&self) -> &'static [f32; ENVELOPE_WAVEFORM_LEN] {
+        envelope_waveform(self.shape)
+    }
+}
+
 /// Noise generator state.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct NoiseState {
@@ -182,7 +198,9 @@ impl ChannelStates {
         };
 
         // Convert to musical note
-        let (note_name, midi_note) = frequency_hz.map(frequency_to_note).unwrap_or((None, None));
+        let (note_name, midi_note, cents) = frequency_hz
+            .map(frequency_to_note)
+            .unwrap_or((None, None, None));
 
         // For envelope mode, use max amplitude for visualization since envelope controls volume
         let amplitude_normalized = if envelope_enabled {
@@ -196,6 +214,7 @@ impl ChannelStates {
             frequency_hz,
             note_name,
             midi_note,
+            cents,
             amplitude,
             amplitude_normalized,
             tone_enabled,
@@ -227,6 +246,176 @@ impl ChannelStates {
     }
 }
 
+impl ChannelState {
+    /// Note name with cents deviation and pitch motion, e.g. `"A4 +12c (vibrato)"`.
+    ///
+    /// The cents part is left out when the pitch is within one cent of the
+    /// note, the motion part when it is [`PitchMotion::Steady`]. Returns
+    /// `None` when the channel has no note.
+    pub fn pitch_label(&self, motion: PitchMotion) -> Option<String> {
+        let note = self.note_name?;
+        let mut label = note.to_string();
+        if let Some(cents) = self.cents.map(f32::round)
+            && cents != 0.0
+        {
+            label.push_str(&format!(" {cents:+}c"));
+        }
+        if let Some(motion) = motion.label() {
+            label.push_str(&format!(" ({motion})"));
+        }
+        Some(label)
+    }
+}
+
+/// Pitch movement detected by a [`PitchTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PitchMotion {
+    /// Pitch holds still (or there is too little history to tell).
+    #[default]
+    Steady,
+    /// Pitch oscillates around a center.
+    Vibrato,
+    /// Pitch rises steadily.
+    SlideUp,
+    /// Pitch falls steadily.
+    SlideDown,
+}
+
+impl PitchMotion {
+    /// Short lowercase label, `None` for [`PitchMotion::Steady`].
+    pub fn label(self) -> Option<&'static str> {
+        match self {
+            PitchMotion::Steady => None,
+            PitchMotion::Vibrato => Some("vibrato"),
+            PitchMotion::SlideUp => Some("slide up"),
+            PitchMotion::SlideDown => Some("slide down"),
+        }
+    }
+}
+
+/// Detects vibrato and slides on one channel from successive register frames.
+///
+/// Feed it the channel frequency once per frame. It keeps a rolling window
+/// of pitches and starts over on silence or on a jump of a semitone or more,
+/// which marks a new note rather than a bend.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::{PitchMotion, PitchTracker};
+///
+/// let mut tracker = PitchTracker::new();
+/// let mut motion = PitchMotion::Steady;
+/// for frame in 0..8 {
+///     let wobble = if frame % 2 == 0 { 1.01 } else { 0.99 };
+///     motion = tracker.push(Some(440.0 * wobble));
+/// }
+/// assert_eq!(motion, PitchMotion::Vibrato);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PitchTracker {
+    /// Recent pitches in fractional MIDI notes, oldest first.
+    pitches: VecDeque<f32>,
+    /// Frames kept in the window.
+    window: usize,
+}
+
+impl Default for PitchTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PitchTracker {
+    /// Default window: 8 frames, 160 ms at 50 Hz.
+    pub const DEFAULT_WINDOW: usize = 8;
+
+    /// Smallest pitch range that counts as movement, in semitones.
+    const MIN_RANGE: f32 = 0.1;
+
+    /// Create a tracker with the default window.
+    pub fn new() -> Self {
+        Self::with_window(Self::DEFAULT_WINDOW)
+    }
+
+    /// Create a tracker over `window` frames (at least 3).
+    pub fn with_window(window: usize) -> Self {
+        let window = window.max(3);
+        Self {
+            pitches: VecDeque::with_capacity(window),
+            window,
+        }
+    }
+
+    /// Forget all history.
+    pub fn reset(&mut self) {
+        self.pitches.clear();
+    }
+
+    /// Add the pitch of the next frame (`None` for silence) and classify the window.
+    pub fn push(&mut self, frequency_hz: Option<f32>) -> PitchMotion {
+        let Some(pitch) = frequency_hz
+            .filter(|freq| *freq > 0.0)
+            .map(|freq| (freq / 440.0).log2().mul_add(12.0, 69.0))
+        else {
+            self.pitches.clear();
+            return PitchMotion::Steady;
+        };
+
+        if self
+            .pitches
+            .back()
+            .is_some_and(|last| (pitch - last).abs() >= 1.0)
+        {
+            self.pitches.clear();
+        }
+        if self.pitches.len() == self.window {
+            self.pitches.pop_front();
+        }
+        self.pitches.push_back(pitch);
+        self.motion()
+    }
+
+    /// Classify the current window.
+    pub fn motion(&self) -> PitchMotion {
+        if self.pitches.len() < 3 {
+            return PitchMotion::Steady;
+        }
+        let (min, max) = self
+            .pitches
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(lo, hi), &p| (lo.min(p), hi.max(p)));
+        if max - min < Self::MIN_RANGE {
+            return PitchMotion::Steady;
+        }
+
+        let mut rising = false;
+        let mut falling = false;
+        let mut turns = 0;
+        let mut last_sign = 0.0f32;
+        for (a, b) in self.pitches.iter().zip(self.pitches.iter().skip(1)) {
+            let delta = b - a;
+            if delta.abs() < 0.005 {
+                continue;
+            }
+            let sign = delta.signum();
+            if last_sign != 0.0 && sign != last_sign {
+                turns += 1;
+            }
+            last_sign = sign;
+            rising |= sign > 0.0;
+            falling |= sign < 0.0;
+        }
+
+        match (rising, falling) {
+            (true, false) => PitchMotion::SlideUp,
+            (false, true) => PitchMotion::SlideDown,
+            (true, true) if turns >= 2 => PitchMotion::Vibrato,
+            _ => PitchMotion::Steady,
+        }
+    }
+}
+
 /// Get human-readable name for envelope shape.
 fn envelope_shape_name(shape: u8) -> &'static str {
     match shape & 0x0F {
@@ -244,12 +433,64 @@ fn envelope_shape_name(shape: u8) -> &'static str {
     }
 }
 
+/// Envelope level curve for an R13 shape value, normalized to 0.0-1.0.
+///
+/// Covers two envelope cycles of 32 steps each: one full period of the
+/// alternating triangle shapes, and enough for the one-shot and hold shapes
+/// to show the level they settle on. Values are envelope steps (step / 31),
+/// not the logarithmic DAC output. Only the low 4 bits of `shape` are used.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::envelope_waveform;
+///
+/// let triangle = envelope_waveform(0x0E);
+/// assert_eq!(triangle[0], 0.0);
+/// assert_eq!(triangle[31], 1.0);
+/// assert_eq!(triangle[63], 0.0);
+/// ```
+pub fn envelope_waveform(shape: u8) -> &'static [f32; ENVELOPE_WAVEFORM_LEN] {
+    &ENVELOPE_WAVEFORMS[(shape & 0x0F) as usize]
+}
+
+const fn build_envelope_waveforms() -> [[f32; ENVELOPE_WAVEFORM_LEN]; 16] {
+    let mut table = [[0.0; ENVELOPE_WAVEFORM_LEN]; 16];
+    let mut shape = 0;
+    while shape < 16 {
+        let cont = shape & 0x08 != 0;
+        let attack = shape & 0x04 != 0;
+        let alternate = shape & 0x02 != 0;
+        let hold = shape & 0x01 != 0;
+        let mut n = 0;
+        while n < ENVELOPE_WAVEFORM_LEN {
+            let cycle = n / 32;
+            let pos = (n % 32) as u8;
+            let step = if cycle == 0 {
+                if attack { pos } else { 31 - pos }
+            } else if !cont {
+                0
+            } else if hold {
+                if attack != alternate { 31 } else { 0 }
+            } else if attack != (alternate && cycle % 2 == 1) {
+                pos
+            } else {
+                31 - pos
+            };
+            table[shape][n] = step as f32 / 31.0;
+            n += 1;
+        }
+        shape += 1;
+    }
+    table
+}
+
 /// Convert frequency to musical note.
 ///
-/// Returns (note_name, midi_note) or (None, None) if out of range.
-fn frequency_to_note(freq: f32) -> (Option<&'static str>, Option<u8>) {
+/// Returns (note_name, midi_note, cents) or all `None` if out of range.
+fn frequency_to_note(freq: f32) -> (Option<&'static str>, Option<u8>, Option<f32>) {
     if !(20.0..=20000.0).contains(&freq) {
-        return (None, None);
+        return (None, None, None);
     }
 
     // MIDI note number: 69 = A4 = 440Hz
@@ -258,7 +499,7 @@ fn frequency_to_note(freq: f32) -> (Option<&'static str>, Option<u8>) {
     let midi = midi_float.round() as i32;
 
     if !(0..=127).contains(&midi) {
-        return (None, None);
+        return (None, None, None);
     }
 
     let midi_u8 = midi as u8;
@@ -278,7 +519,8 @@ fn frequency_to_note(freq: f32) -> (Option<&'static str>, Option<u8>) {
     ];
 
     let note_name = NOTE_NAMES.get(midi_u8 as usize).copied();
-    (note_name, Some(midi_u8))
+    let cents = (midi_float - midi as f32) * 100.0;
+    (note_name, Some(midi_u8), Some(cents))
 }
 
 #[cfg(test)]
@@ -322,17 +564,86 @@ mod tests {
         assert!(states.envelope.is_sustaining);
     }
 
+    #[test]
+    fn test_envelope_waveforms() {
+        let ramp_up: Vec<f32> = (0..32).map(|i| i as f32 / 31.0).collect();
+        let ramp_down: Vec<f32> = ramp_up.iter().rev().copied().collect();
+
+        // Shapes 0-3 and 9 decay once and stay silent
+        for shape in [0x00, 0x03, 0x09] {
+            let wave = envelope_waveform(shape);
+            assert_eq!(&wave[..32], &ramp_down[..]);
+            assert!(wave[32..].iter().all(|&v| v == 0.0));
+        }
+        // Sawtooth repeats, triangle alternates
+        assert_eq!(&envelope_waveform(0x0C)[32..], &ramp_up[..]);
+        assert_eq!(&envelope_waveform(0x08)[32..], &ramp_down[..]);
+        assert_eq!(&envelope_waveform(0x0A)[32..], &ramp_up[..]);
+        // Hold shapes settle high or low
+        assert!(envelope_waveform(0x0B)[32..].iter().all(|&v| v == 1.0));
+        assert!(envelope_waveform(0x0D)[32..].iter().all(|&v| v == 1.0));
+        assert!(envelope_waveform(0x0F)[32..].iter().all(|&v| v == 0.0));
+        // Upper nibble is ignored
+        assert_eq!(envelope_waveform(0xFE), envelope_waveform(0x0E));
+
+        let mut regs = [0u8; 16];
+        regs[13] = 0x0E;
+        let states = ChannelStates::from_registers(&regs);
+        assert_eq!(states.envelope.waveform(), envelope_waveform(0x0E));
+    }
+
     #[test]
     fn test_frequency_to_note_a4() {
-        let (name, midi) = frequency_to_note(440.0);
+        let (name, midi, cents) = frequency_to_note(440.0);
         assert_eq!(name, Some("A4"));
         assert_eq!(midi, Some(69));
+        assert!(cents.unwrap().abs() < 0.01);
     }
 
     #[test]
     fn test_frequency_to_note_c4() {
-        let (name, midi) = frequency_to_note(261.63);
+        let (name, midi, _) = frequency_to_note(261.63);
         assert_eq!(name, Some("C4"));
         assert_eq!(midi, Some(60));
     }
+
+    #[test]
+    fn test_cents_and_pitch_label() {
+        let mut regs = [0u8; 16];
+        regs[0] = 0x18; // Period 280: ~446.4 Hz, A4 +25c
+        regs[1] = 0x01;
+        let states = ChannelStates::from_registers(&regs);
+        let ch = &states.channels[0];
+        assert_eq!(ch.note_name, Some("A4"));
+        assert!((ch.cents.unwrap() - 25.1).abs() < 0.1);
+        assert_eq!(
+            ch.pitch_label(PitchMotion::Vibrato).as_deref(),
+            Some("A4 +25c (vibrato)")
+        );
+        assert_eq!(states.channels[1].pitch_label(PitchMotion::Steady), None);
+    }
+
+    #[test]
+    fn test_pitch_tracker() {
+        let mut tracker = PitchTracker::new();
+        for _ in 0..8 {
+            assert_eq!(tracker.push(Some(440.0)), PitchMotion::Steady);
+        }
+
+        let mut motion = PitchMotion::Steady;
+        for step in 0..6 {
+            motion = tracker.push(Some(440.0 * (1.0 + step as f32 * 0.01)));
+        }
+        assert_eq!(motion, PitchMotion::SlideUp);
+
+        // A jump to a new note starts over
+        assert_eq!(tracker.push(Some(880.0)), PitchMotion::Steady);
+        for step in 0..6 {
+            motion = tracker.push(Some(880.0 * (1.0 - step as f32 * 0.01)));
+        }
+        assert_eq!(motion, PitchMotion::SlideDown);
+
+        assert_eq!(tracker.push(None), PitchMotion::Steady);
+        assert_eq!(tracker.motion(), PitchMotion::Steady);
+    }
 }
@@ -0,0 +1,397 @@
+//! A/B comparison of two renderings of the same song.
+//!
+//! [`AbCompare`] runs two players side by side (for example the same YM file
+//! on the hardware-accurate `Ym2149` and on `SoftSynth`, or with different
+//! filter settings) and outputs one of them or their difference. Both are
+//! always rendered, so switching is instant and stays in phase; a short ramp
+//! avoids a click on each switch. Frontends that render the two sides
+//! themselves (stereo output, custom player traits) use [`AbSwitch`] directly.
+
+use std::fmt;
+
+use crate::OutputModel;
+use crate::player::{ChiptunePlayerBase, PlaybackState};
+
+/// Length of the crossfade when switching between outputs, in samples.
+pub const AB_SWITCH_SAMPLES: usize = 256;
+
+/// Which signal an A/B comparison outputs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AbMode {
+    /// The first rendering
+    #[default]
+    A,
+    /// The second rendering
+    B,
+    /// A minus B; silence means both renderings are identical
+    Difference,
+}
+
+impl AbMode {
+    /// All modes in toggle order.
+    pub const ALL: [AbMode; 3] = [AbMode::A, AbMode::B, AbMode::Difference];
+
+    /// The mode after this one, wrapping around.
+    pub fn next(self) -> Self {
+        match self {
+            Self::A => Self::B,
+            Self::B => Self::Difference,
+            Self::Difference => Self::A,
+        }
+    }
+
+    /// Parse `a`, `b` or `diff` (case-insensitive).
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "a" => Some(Self::A),
+            "b" => Some(Self::B),
+            "diff" | "difference" => Some(Self::Difference),
+            _ => None,
+        }
+    }
+
+    /// Short name ("a", "b", "diff").
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::A => "a",
+            Self::B => "b",
+            Self::Difference => "diff",
+        }
+    }
+
+    #[inline]
+    fn select(self, a: f32, b: f32, difference_gain: f32) -> f32 {
+        match self {
+            Self::A => a,
+            Self::B => b,
+            Self::Difference => (a - b) * difference_gain,
+        }
+    }
+}
+
+impl fmt::Display for AbMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Click-free selection between two rendered buffers.
+#[derive(Debug, Clone)]
+pub struct AbSwitch {
+    mode: AbMode,
+    previous: AbMode,
+    ramp_remaining: usize,
+    difference_gain: f32,
+}
+
+impl Default for AbSwitch {
+    fn default() -> Self {
+        Self::new(AbMode::A)
+    }
+}
+
+impl AbSwitch {
+    /// Create a switch starting on `mode`.
+    pub fn new(mode: AbMode) -> Self {
+        Self {
+            mode,
+            previous: mode,
+            ramp_remaining: 0,
+            difference_gain: 1.0,
+        }
+    }
+
+    /// Current output.
+    pub fn mode(&self) -> AbMode {
+        self.mode
+    }
+
+    /// Switch output, crossfading over [`AB_SWITCH_SAMPLES`].
+    pub fn set_mode(&mut self, mode: AbMode) {
+        if mode == self.mode {
+            return;
+        }
+        self.previous = self.mode;
+        self.mode = mode;
+        self.ramp_remaining = AB_SWITCH_SAMPLES;
+    }
+
+    /// Gain applied to the difference signal (1.0 by default).
+    ///
+    /// Small emulation changes give a quiet difference; raising the gain
+    /// makes them audible.
+    pub fn set_difference_gain(&mut self, gain: f32) {
+        if gain.is_finite() {
+            self.difference_gain = gain.max(0.0);
+        }
+    }
+
+    /// Gain applied to the difference signal.
+    pub fn difference_gain(&self) -> f32 {
+        self.difference_gain
+    }
+
+    /// Write the selected signal of `a` and `b` to `out`.
+    ///
+    /// Works on mono and interleaved buffers alike; all three should have
+    /// the same length (extra samples are left untouched).
+    pub fn mix(&mut self, a: &[f32], b: &[f32], out: &mut [f32]) {
+        let gain = self.difference_gain;
+        for ((out, &a), &b) in out.iter_mut().zip(a).zip(b) {
+            let current = self.mode.select(a, b, gain);
+            *out = if self.ramp_remaining > 0 {
+                let fade = self.ramp_remaining as f32 / AB_SWITCH_SAMPLES as f32;
+                self.ramp_remaining -= 1;
+                current + (self.previous.select(a, b, gain) - current) * fade
+            } else {
+                current
+            };
+        }
+    }
+}
+
+/// Two players rendered in lockstep with a switchable output.
+///
+/// Transport calls (play, seek, subsong, ...) go to both players; queries
+/// answer from player A. Both must run at the same sample rate.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut compare = AbCompare::new(Box::new(hardware), Box::new(softsynth)).unwrap();
+/// compare.play();
+/// compare.set_mode(AbMode::Difference);
+/// let mut buffer = vec![0.0; 882];
+/// compare.generate_samples_into(&mut buffer);
+/// ```
+pub struct AbCompare {
+    a: Box<dyn ChiptunePlayerBase>,
+    b: Box<dyn ChiptunePlayerBase>,
+    switch: AbSwitch,
+    scratch_a: Vec<f32>,
+    scratch_b: Vec<f32>,
+}
+
+impl AbCompare {
+    /// Compare `a` and `b`, starting on A.
+    ///
+    /// Returns `None` if they render at different sample rates.
+    pub fn new(a: Box<dyn ChiptunePlayerBase>, b: Box<dyn ChiptunePlayerBase>) -> Option<Self> {
+        if a.sample_rate() != b.sample_rate() {
+            return None;
+        }
+        Some(Self {
+            a,
+            b,
+            switch: AbSwitch::default(),
+            scratch_a: Vec::new(),
+            scratch_b: Vec::new(),
+        })
+    }
+
+    /// Current output.
+    pub fn mode(&self) -> AbMode {
+        self.switch.mode()
+    }
+
+    /// Switch output (takes effect with a short crossfade).
+    pub fn set_mode(&mut self, mode: AbMode) {
+        self.switch.set_mode(mode);
+    }
+
+    /// Access the switch, e.g. to set the difference gain.
+    pub fn switch_mut(&mut self) -> &mut AbSwitch {
+        &mut self.switch
+    }
+
+    /// Player A.
+    pub fn player_a(&mut self) -> &mut dyn ChiptunePlayerBase {
+        self.a.as_mut()
+    }
+
+    /// Player B.
+    pub fn player_b(&mut self) -> &mut dyn ChiptunePlayerBase {
+        self.b.as_mut()
+    }
+}
+
+impl ChiptunePlayerBase for AbCompare {
+    fn play(&mut self) {
+        self.a.play();
+        self.b.play();
+    }
+
+    fn pause(&mut self) {
+        self.a.pause();
+        self.b.pause();
+    }
+
+    fn stop(&mut self) {
+        self.a.stop();
+        self.b.stop();
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.a.state()
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        self.scratch_a.resize(buffer.len(), 0.0);
+        self.scratch_b.resize(buffer.len(), 0.0);
+        self.a.generate_samples_into(&mut self.scratch_a);
+        self.b.generate_samples_into(&mut self.scratch_b);
+        self.switch.mix(&self.scratch_a, &self.scratch_b, buffer);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.a.sample_rate()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.a.set_playback_rate(rate);
+        self.b.set_playback_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.a.playback_rate()
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.a.set_output_model(model);
+        self.b.set_output_model(model);
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        self.a.set_channel_mute(channel, mute);
+        self.b.set_channel_mute(channel, mute);
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.a.is_channel_muted(channel)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        self.b.advance_frames(frames);
+        self.a.advance_frames(frames)
+    }
+
+    fn playback_position(&self) -> f32 {
+        self.a.playback_position()
+    }
+
+    fn seek(&mut self, position: f32) -> bool {
+        let seeked = self.a.seek(position);
+        self.b.seek(position);
+        seeked
+    }
+
+    fn duration_seconds(&self) -> f32 {
+        self.a.duration_seconds()
+    }
+
+    fn elapsed_seconds(&self) -> f32 {
+        self.a.elapsed_seconds()
+    }
+
+    fn subsong_count(&self) -> usize {
+        self.a.subsong_count()
+    }
+
+    fn current_subsong(&self) -> usize {
+        self.a.current_subsong()
+    }
+
+    fn set_subsong(&mut self, index: usize) -> bool {
+        let switched = self.a.set_subsong(index);
+        self.b.set_subsong(index);
+        switched
+    }
+
+    fn psg_count(&self) -> usize {
+        self.a.psg_count()
+    }
+
+    fn channel_count(&self) -> usize {
+        self.a.channel_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Constant {
+        level: f32,
+        state: PlaybackState,
+    }
+
+    impl Constant {
+        fn boxed(level: f32) -> Box<dyn ChiptunePlayerBase> {
+            Box::new(Self {
+                level,
+                state: PlaybackState::Stopped,
+            })
+        }
+    }
+
+    impl ChiptunePlayerBase for Constant {
+        fn play(&mut self) {
+            self.state = PlaybackState::Playing;
+        }
+        fn pause(&mut self) {
+            self.state = PlaybackState::Paused;
+        }
+        fn stop(&mut self) {
+            self.state = PlaybackState::Stopped;
+        }
+        fn state(&self) -> PlaybackState {
+            self.state
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            let level = if self.is_playing() { self.level } else { 0.0 };
+            buffer.fill(level);
+        }
+    }
+
+    #[test]
+    fn test_switch_crossfades_between_modes() {
+        let a = [0.5; AB_SWITCH_SAMPLES];
+        let b = [0.25; AB_SWITCH_SAMPLES];
+        let mut out = [0.0; AB_SWITCH_SAMPLES];
+        let mut switch = AbSwitch::default();
+        switch.mix(&a, &b, &mut out);
+        assert_eq!(out, a);
+
+        switch.set_mode(AbMode::B);
+        switch.mix(&a, &b, &mut out);
+        assert_eq!(out[0], 0.5);
+        assert!(out.windows(2).all(|pair| pair[1] <= pair[0]));
+        switch.mix(&a, &b, &mut out);
+        assert_eq!(out, b);
+
+        switch.set_mode(AbMode::Difference);
+        switch.set_difference_gain(4.0);
+        switch.mix(&a, &b, &mut out);
+        switch.mix(&a, &b, &mut out);
+        assert_eq!(out, [1.0; AB_SWITCH_SAMPLES]);
+    }
+
+    #[test]
+    fn test_compare_drives_both_players() {
+        let mut compare = AbCompare::new(Constant::boxed(0.5), Constant::boxed(0.5)).unwrap();
+        compare.play();
+        assert_eq!(compare.player_b().state(), PlaybackState::Playing);
+
+        let mut buffer = [1.0; 8];
+        compare.set_mode(AbMode::Difference);
+        compare.generate_samples_into(&mut [0.0; AB_SWITCH_SAMPLES]);
+        compare.generate_samples_into(&mut buffer);
+        assert_eq!(buffer, [0.0; 8]);
+
+        compare.player_b().pause();
+        compare.generate_samples_into(&mut buffer);
+        assert_eq!(buffer, [0.5; 8]);
+        assert_eq!(AbMode::parse("DIFF"), Some(AbMode::Difference));
+        assert_eq!(AbMode::Difference.next(), AbMode::A);
+    }
+}
@@ -0,0 +1,140 @@
+//! Latency compensation for visualizations.
+//!
+//! Players render ahead of what is audible: samples sit in a ring buffer (or
+//! the browser's audio graph) before they reach the speakers. Visuals read
+//! straight from the player therefore lead the audio by the buffer latency.
+//! [`RegisterDelayLine`] records the chip state after each rendered block and
+//! hands back the state of the block that is playing right now.
+
+use std::collections::VecDeque;
+
+/// Delays chip state snapshots by the audio output latency.
+///
+/// Push the state after every rendered block together with the block's
+/// length in frames (samples per channel); [`delayed`](Self::delayed) returns
+/// the newest state whose block has already reached the output. The default
+/// snapshot type is one register dump per PSG, but any per-block state (for
+/// example a richer visual snapshot) works.
+#[derive(Debug, Clone)]
+pub struct RegisterDelayLine<T = Vec<[u8; 16]>> {
+    /// States tagged with the frame position at the end of their block.
+    entries: VecDeque<(u64, T)>,
+    /// Frames rendered so far.
+    written: u64,
+    /// Frames between rendering and hearing a sample.
+    latency: usize,
+}
+
+impl<T> RegisterDelayLine<T> {
+    /// Create a delay line for a fixed latency in frames.
+    pub fn new(latency_frames: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            written: 0,
+            latency: latency_frames,
+        }
+    }
+
+    /// Create a delay line matching a ring buffer that is kept full.
+    ///
+    /// `capacity_samples` counts interleaved samples, as audio ring buffers
+    /// usually do; `channels` is the number of interleaved output channels.
+    pub fn for_ring_buffer(capacity_samples: usize, channels: usize) -> Self {
+        Self::new(capacity_samples / channels.max(1))
+    }
+
+    /// Latency in frames.
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    /// Change the latency, e.g. to the measured fill of the output buffer.
+    pub fn set_latency(&mut self, latency_frames: usize) {
+        self.latency = latency_frames;
+    }
+
+    /// Record the state after rendering `frames` more frames.
+    pub fn push(&mut self, frames: usize, state: T) {
+        self.written += frames as u64;
+        self.entries.push_back((self.written, state));
+        // Keep the newest audible entry and everything after it
+        let audible = self.audible_position();
+        while self.entries.get(1).is_some_and(|&(end, _)| end <= audible) {
+            self.entries.pop_front();
+        }
+    }
+
+    /// State of the block currently being heard.
+    ///
+    /// Returns `None` until the first block has travelled through the latency
+    /// (nothing pushed so far is audible yet).
+    pub fn delayed(&self) -> Option<&T> {
+        let audible = self.audible_position();
+        self.entries
+            .iter()
+            .rev()
+            .find(|&&(end, _)| end <= audible)
+            .map(|(_, state)| state)
+    }
+
+    /// Most recently pushed state, ahead of the audio by the latency.
+    pub fn latest(&self) -> Option<&T> {
+        self.entries.back().map(|(_, state)| state)
+    }
+
+    /// Drop all recorded states, e.g. after a seek or song change.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.written = 0;
+    }
+
+    fn audible_position(&self) -> u64 {
+        self.written.saturating_sub(self.latency as u64)
+    }
+}
+
+impl<T> Default for RegisterDelayLine<T> {
+    /// A delay line without latency: `delayed` returns the latest state.
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn states_appear_after_the_latency() {
+        let mut line = RegisterDelayLine::for_ring_buffer(2000, 2);
+        assert_eq!(line.latency(), 1000);
+
+        for block in 0..4u8 {
+            line.push(400, block);
+        }
+        // 1600 frames rendered, 600 audible: block 0 ended at 400
+        assert_eq!(line.delayed(), Some(&0));
+        assert_eq!(line.latest(), Some(&3));
+
+        line.push(400, 4);
+        // 1000 audible: block 1 ended at 800, block 2 at 1200
+        assert_eq!(line.delayed(), Some(&1));
+
+        line.clear();
+        line.push(400, 5);
+        assert_eq!(line.delayed(), None);
+    }
+
+    #[test]
+    fn latency_changes_take_effect_immediately() {
+        let mut line = RegisterDelayLine::new(0);
+        line.push(100, [[1u8; 16]]);
+        assert_eq!(line.delayed(), Some(&[[1u8; 16]]));
+
+        line.set_latency(150);
+        line.push(100, [[2u8; 16]]);
+        assert_eq!(line.delayed(), None);
+        line.push(100, [[3u8; 16]]);
+        assert_eq!(line.delayed(), Some(&[[1u8; 16]]));
+    }
+}
@@ -0,0 +1,402 @@
+//! Format sniffing shared by all frontends.
+//!
+//! [`detect_format`] looks at the leading bytes of a file and returns the
+//! most likely [`ChiptuneFormat`] together with a confidence score and the
+//! signature that was matched. Loaders that fall back through several
+//! parsers use [`detect_formats`] instead, which ranks every format so that
+//! unambiguous signatures (a `ZXAYEMUL` header, a `YM6!` magic) are tried
+//! before heuristics such as "ICE!-packed data is usually SNDH".
+//!
+//! Detection only inspects headers; a guess is a hint for which parser to
+//! try first, not a guarantee that parsing succeeds. [`load_with_detection`]
+//! runs a loader over the ranked candidates and, when all of them fail,
+//! returns a [`LoadError`] recording why each parser rejected the data.
+
+use std::fmt;
+
+/// Song file formats recognized by [`detect_format`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChiptuneFormat {
+    /// ST-Sound register dumps and YM Tracker files (`.ym`), optionally LHA-packed
+    Ym,
+    /// Arkos Tracker 2/3 songs (`.aks`)
+    Arkos,
+    /// ZXAY/EMUL files (`.ay`)
+    Ay,
+    /// Atari ST SNDH files (`.sndh`), optionally ICE!-packed
+    Sndh,
+    /// MSX KSS rips (`.kss`), `KSCC` or `KSSX` header
+    Kss,
+    /// ZIP archive holding one or more songs (or a packaged `.aks`)
+    Zip,
+    /// No known signature
+    Unknown,
+}
+
+impl ChiptuneFormat {
+    /// Song formats in the order loaders try them when nothing else is known.
+    ///
+    /// KSS is left out: its header is always present, so a file without it
+    /// is never worth handing to the KSS parser.
+    pub const FALLBACK_ORDER: [ChiptuneFormat; 4] = [Self::Ym, Self::Arkos, Self::Sndh, Self::Ay];
+
+    /// Short format name ("YM", "AKS", "AY", "SNDH", "KSS", "ZIP").
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ym => "YM",
+            Self::Arkos => "AKS",
+            Self::Ay => "AY",
+            Self::Sndh => "SNDH",
+            Self::Kss => "KSS",
+            Self::Zip => "ZIP",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Result of sniffing a file header.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FormatGuess {
+    /// Detected format.
+    pub format: ChiptuneFormat,
+    /// Confidence from 0.0 (no evidence) to 1.0 (exact magic bytes).
+    pub confidence: f32,
+    /// Human-readable description of the matched signature.
+    pub reason: &'static str,
+}
+
+impl FormatGuess {
+    fn new(format: ChiptuneFormat, confidence: f32, reason: &'static str) -> Self {
+        Self {
+            format,
+            confidence,
+            reason,
+        }
+    }
+
+    /// Whether any signature was matched.
+    pub fn is_known(&self) -> bool {
+        self.format != ChiptuneFormat::Unknown && self.confidence > 0.0
+    }
+}
+
+/// Why every candidate parser rejected a file.
+///
+/// Displays as `not a valid YM (Unsupported format: ZXAY), not a valid AKS (...)`,
+/// in the order the parsers were tried.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadError {
+    attempts: Vec<(ChiptuneFormat, String)>,
+}
+
+impl LoadError {
+    /// Create an error with no recorded attempts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that the parser for `format` rejected the data.
+    pub fn push(&mut self, format: ChiptuneFormat, reason: impl Into<String>) {
+        self.attempts.push((format, reason.into()));
+    }
+
+    /// Rejected formats with their reasons, in the order they were tried.
+    pub fn attempts(&self) -> &[(ChiptuneFormat, String)] {
+        &self.attempts
+    }
+
+    /// Reason the parser for `format` gave, if it was tried.
+    pub fn reason(&self, format: ChiptuneFormat) -> Option<&str> {
+        self.attempts
+            .iter()
+            .find(|(attempted, _)| *attempted == format)
+            .map(|(_, reason)| reason.as_str())
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.attempts.is_empty() {
+            return f.write_str("unrecognized format");
+        }
+        for (index, (format, reason)) in self.attempts.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "not a valid {} ({reason})", format.name())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LoadError {}
+
+/// Exact magic bytes.
+const EXACT: f32 = 1.0;
+/// Structural match of a text format.
+const STRONG: f32 = 0.9;
+/// Compressed container typically used for one format.
+const PACKED: f32 = 0.6;
+/// Weak hint (generic XML, partial header).
+const WEAK: f32 = 0.3;
+
+const YM_MAGICS: [&[u8; 4]; 8] = [
+    b"YM2!", b"YM3!", b"YM3b", b"YM4!", b"YM5!", b"YM6!", b"YMT1", b"YMT2",
+];
+/// How far into an XML document to look for Arkos markers.
+const XML_SNIFF_LEN: usize = 512;
+
+/// Guess the format of a song file from its leading bytes.
+///
+/// Returns [`ChiptuneFormat::Unknown`] with confidence 0.0 when no signature
+/// matches.
+pub fn detect_format(data: &[u8]) -> FormatGuess {
+    signatures(data)
+        .into_iter()
+        .next()
+        .unwrap_or(FormatGuess::new(
+            ChiptuneFormat::Unknown,
+            0.0,
+            "no known signature",
+        ))
+}
+
+/// Rank every song format by how likely it is to parse `data`.
+///
+/// Matched signatures come first, strongest first. Formats without a match
+/// follow with confidence 0.0 in [`ChiptuneFormat::FALLBACK_ORDER`], so a
+/// loader can try the candidates in order and still reach every parser.
+/// A ZIP guess, if present, leads the list; callers that unpack archives
+/// handle it before the song formats.
+pub fn detect_formats(data: &[u8]) -> Vec<FormatGuess> {
+    let mut guesses = signatures(data);
+    for format in ChiptuneFormat::FALLBACK_ORDER {
+        if !guesses.iter().any(|guess| guess.format == format) {
+            guesses.push(FormatGuess::new(format, 0.0, "no signature"));
+        }
+    }
+    guesses
+}
+
+/// Run `load` for each song format from [`detect_formats`], best match first.
+///
+/// Returns the first successful result, or a [`LoadError`] listing every
+/// parser's rejection. ZIP guesses are skipped; unpack archives before
+/// calling this.
+pub fn load_with_detection<T>(
+    data: &[u8],
+    mut load: impl FnMut(ChiptuneFormat) -> Result<T, String>,
+) -> Result<T, LoadError> {
+    let mut error = LoadError::new();
+    for guess in detect_formats(data) {
+        if guess.format == ChiptuneFormat::Zip {
+            continue;
+        }
+        match load(guess.format) {
+            Ok(loaded) => return Ok(loaded),
+            Err(reason) => error.push(guess.format, reason),
+        }
+    }
+    Err(error)
+}
+
+/// All matched signatures, strongest first, at most one per format.
+fn signatures(data: &[u8]) -> Vec<FormatGuess> {
+    let mut guesses = Vec::new();
+    let mut push = |guess: FormatGuess| {
+        if !guesses
+            .iter()
+            .any(|existing: &FormatGuess| existing.format == guess.format)
+        {
+            guesses.push(guess);
+        }
+    };
+
+    if data.starts_with(b"PK\x03\x04") || data.starts_with(b"PK\x05\x06") {
+        push(FormatGuess::new(
+            ChiptuneFormat::Zip,
+            EXACT,
+            "ZIP archive header",
+        ));
+        // Arkos Tracker saves songs as a ZIP holding one XML document
+        push(FormatGuess::new(
+            ChiptuneFormat::Arkos,
+            WEAK,
+            "ZIP archive (may hold a packaged .aks song)",
+        ));
+    }
+    if data.starts_with(b"ZXAYEMUL") {
+        push(FormatGuess::new(
+            ChiptuneFormat::Ay,
+            EXACT,
+            "ZXAY/EMUL header",
+        ));
+    }
+    if data.len() >= 4 && YM_MAGICS.iter().any(|magic| data.starts_with(*magic)) {
+        push(FormatGuess::new(
+            ChiptuneFormat::Ym,
+            EXACT,
+            "YM magic bytes",
+        ));
+    }
+    if data.len() >= 16 && data[0] == 0x60 && &data[12..16] == b"SNDH" {
+        push(FormatGuess::new(
+            ChiptuneFormat::Sndh,
+            EXACT,
+            "BRA instruction and SNDH header",
+        ));
+    }
+    if data.starts_with(b"KSCC") || data.starts_with(b"KSSX") {
+        push(FormatGuess::new(
+            ChiptuneFormat::Kss,
+            EXACT,
+            "KSCC/KSSX header",
+        ));
+    }
+    if is_arkos_xml(data) {
+        push(FormatGuess::new(
+            ChiptuneFormat::Arkos,
+            STRONG,
+            "Arkos Tracker XML",
+        ));
+    }
+    if data.len() >= 7 && &data[2..5] == b"-lh" && data[6] == b'-' {
+        push(FormatGuess::new(
+            ChiptuneFormat::Ym,
+            PACKED,
+            "LHA archive (YM files are usually LHA-packed)",
+        ));
+    }
+    if data.starts_with(b"ICE!") {
+        push(FormatGuess::new(
+            ChiptuneFormat::Sndh,
+            PACKED,
+            "ICE!-packed data (usually SNDH)",
+        ));
+    }
+    if data.starts_with(b"ZXAY") {
+        push(FormatGuess::new(
+            ChiptuneFormat::Ay,
+            WEAK,
+            "ZXAY header with unsupported type",
+        ));
+    }
+    if xml_start(data).is_some_and(|text| text.starts_with(b"<?xml")) {
+        push(FormatGuess::new(
+            ChiptuneFormat::Arkos,
+            WEAK,
+            "XML document",
+        ));
+    }
+
+    guesses.sort_by(|a, b| b.confidence.total_cmp(&a.confidence));
+    guesses
+}
+
+/// Data after an optional UTF-8 BOM and leading whitespace, if it starts a tag.
+fn xml_start(data: &[u8]) -> Option<&[u8]> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let start = data.iter().position(|byte| !byte.is_ascii_whitespace())?;
+    let text = &data[start..];
+    text.starts_with(b"<").then_some(text)
+}
+
+fn is_arkos_xml(data: &[u8]) -> bool {
+    let Some(text) = xml_start(data) else {
+        return false;
+    };
+    let head = String::from_utf8_lossy(&text[..text.len().min(XML_SNIFF_LEN)]).to_lowercase();
+    head.contains("<song") || head.contains(":song") || head.contains("arkos")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sndh_header() -> Vec<u8> {
+        let mut data = vec![0x60, 0x0E, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        data.extend_from_slice(b"SNDH");
+        data.extend_from_slice(b"TITLTest\0HDNS");
+        data
+    }
+
+    #[test]
+    fn exact_magics_are_detected() {
+        let guess = detect_format(b"YM6!LeOnArD!");
+        assert_eq!(guess.format, ChiptuneFormat::Ym);
+        assert_eq!(guess.confidence, 1.0);
+
+        assert_eq!(detect_format(&sndh_header()).format, ChiptuneFormat::Sndh);
+        assert_eq!(
+            detect_format(b"ZXAYEMUL\x00\x00\x00\x00").format,
+            ChiptuneFormat::Ay
+        );
+        assert_eq!(
+            detect_format(b"\xEF\xBB\xBF<?xml version=\"1.0\"?>\n<song xmlns:aks=\"...\">").format,
+            ChiptuneFormat::Arkos
+        );
+        assert_eq!(detect_format(b"PK\x03\x04rest").format, ChiptuneFormat::Zip);
+        assert_eq!(detect_format(b"KSCC\x00\x40").format, ChiptuneFormat::Kss);
+        assert_eq!(detect_format(b"KSSX\x00\x40").format, ChiptuneFormat::Kss);
+
+        let unknown = detect_format(b"garbage");
+        assert_eq!(unknown.format, ChiptuneFormat::Unknown);
+        assert!(!unknown.is_known());
+    }
+
+    #[test]
+    fn packed_data_ranks_below_exact_magic() {
+        let guess = detect_format(b"\x1f\x00-lh5-\x00\x00");
+        assert_eq!(guess.format, ChiptuneFormat::Ym);
+        assert!(guess.confidence < 1.0);
+
+        let guess = detect_format(b"ICE!\x00\x00\x10\x00");
+        assert_eq!(guess.format, ChiptuneFormat::Sndh);
+        assert!(guess.confidence < 1.0);
+    }
+
+    #[test]
+    fn candidates_cover_every_format_once() {
+        // An AY file that also carries an SNDH-looking tag is routed to AY first
+        let mut data = b"ZXAYEMUL".to_vec();
+        data.extend_from_slice(b"\x00\x00\x00\x00SNDH");
+        let candidates = detect_formats(&data);
+        let formats: Vec<_> = candidates.iter().map(|guess| guess.format).collect();
+        assert_eq!(
+            formats,
+            vec![
+                ChiptuneFormat::Ay,
+                ChiptuneFormat::Ym,
+                ChiptuneFormat::Arkos,
+                ChiptuneFormat::Sndh
+            ]
+        );
+        assert!(candidates[1..].iter().all(|guess| guess.confidence == 0.0));
+    }
+
+    #[test]
+    fn load_error_lists_every_rejection() {
+        let error = load_with_detection(b"ZXAYEMUL", |format| -> Result<(), _> {
+            Err(match format {
+                ChiptuneFormat::Ay => "no songs".to_string(),
+                _ => "bad magic".to_string(),
+            })
+        })
+        .unwrap_err();
+
+        assert_eq!(error.attempts().len(), 4);
+        assert_eq!(error.reason(ChiptuneFormat::Ay), Some("no songs"));
+        assert_eq!(
+            error.to_string(),
+            "not a valid AY (no songs), not a valid YM (bad magic), \
+             not a valid AKS (bad magic), not a valid SNDH (bad magic)"
+        );
+
+        let loaded = load_with_detection(b"YM6!", |format| match format {
+            ChiptuneFormat::Arkos => Ok("aks"),
+            _ => Err("bad magic".to_string()),
+        });
+        assert_eq!(loaded, Ok("aks"));
+        assert_eq!(LoadError::new().to_string(), "unrecognized format");
+    }
+}
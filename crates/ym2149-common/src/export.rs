@@ -0,0 +1,620 @@
+//! Note extraction and export for piano-roll views and transcription.
+//!
+//! [`NoteRecorder`] turns per-frame register dumps into [`NoteEvent`]s
+//! (channel, note, start frame, duration, volume). This works for every
+//! format, since all of them end up writing YM2149 registers. The resulting
+//! [`NoteTimeline`] can be exported as JSON ([`to_json`]) or as a standard
+//! MIDI file ([`to_midi`]). Slides and vibrato within a held note are kept as
+//! [`PitchBend`]s instead of being split into many short notes.
+//!
+//! # Example
+//!
+//! ```
+//! use ym2149_common::export::{NoteRecorder, to_json};
+//!
+//! let mut recorder = NoteRecorder::new(50);
+//! let mut regs = [0u8; 16];
+//! regs[0] = 0x1C; // Channel A period 0x11C (~440 Hz)
+//! regs[1] = 0x01;
+//! regs[7] = 0x3E; // Tone A on
+//! regs[8] = 0x0F; // Full volume
+//! for _ in 0..25 {
+//!     recorder.record_frame(&[regs]);
+//! }
+//!
+//! let timeline = recorder.finish();
+//! assert_eq!(timeline.events.len(), 1);
+//! assert_eq!(timeline.events[0].note_name, "A4");
+//! assert_eq!(timeline.events[0].duration_frames, 25);
+//! assert!(to_json(&timeline).contains("\"note\":69"));
+//! ```
+
+use crate::channel_state::ChannelStates;
+use crate::{FRAME_RATE_PAL, PSG_MASTER_CLOCK_HZ};
+use std::fmt::Write as _;
+
+/// Volume increase (in register steps) that restarts a held note.
+///
+/// Trackers re-trigger repeated notes by jumping back to the attack volume;
+/// gradual software fades stay a single note.
+const RETRIGGER_VOLUME_STEP: u8 = 3;
+
+/// Pitch bend range used for slides, in semitones either way.
+///
+/// Slides further than this from the note's start pitch begin a new note.
+pub const PITCH_BEND_RANGE_SEMITONES: f32 = 2.0;
+
+/// Pitch deviation (in semitones) below which a channel counts as on pitch.
+const PITCH_TOLERANCE_SEMITONES: f32 = 0.05;
+
+/// Distance from a whole semitone beyond which a pitch change is a slide.
+const SLIDE_DETUNE_SEMITONES: f32 = 0.2;
+
+/// One note played on a channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NoteEvent {
+    /// Global channel index (0-2 = PSG 0, 3-5 = PSG 1, ...).
+    pub channel: usize,
+    /// MIDI note number.
+    pub midi_note: u8,
+    /// Note name (e.g. "C#5").
+    pub note_name: &'static str,
+    /// Frequency at note start in Hz.
+    pub frequency_hz: f32,
+    /// Frame the note started on.
+    pub start_frame: u32,
+    /// Length in frames.
+    pub duration_frames: u32,
+    /// Peak volume (0-15; envelope-driven notes report 15).
+    pub volume: u8,
+    /// Whether the hardware envelope drove the volume.
+    pub envelope: bool,
+}
+
+/// Pitch change of a held note (slide or vibrato).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PitchBend {
+    /// Global channel index.
+    pub channel: usize,
+    /// Frame the bend takes effect on.
+    pub frame: u32,
+    /// Offset from the held note's start frequency in semitones
+    /// (within ±[`PITCH_BEND_RANGE_SEMITONES`]; 0 returns to the start pitch).
+    pub semitones: f32,
+}
+
+/// Notes extracted from a song.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NoteTimeline {
+    /// Player frame rate in Hz.
+    pub frame_rate: u32,
+    /// Number of channels recorded (3 per PSG).
+    pub channel_count: usize,
+    /// Number of frames recorded.
+    pub frame_count: u32,
+    /// Notes ordered by start frame, then channel.
+    pub events: Vec<NoteEvent>,
+    /// Pitch bends ordered by frame, then channel.
+    pub pitch_bends: Vec<PitchBend>,
+}
+
+/// Note currently held on a channel.
+#[derive(Debug, Clone, Copy)]
+struct HeldNote {
+    event: NoteEvent,
+    /// Volume of the previous frame, for retrigger detection.
+    last_volume: u8,
+    /// Current bend in semitones.
+    bend: f32,
+    /// The note has moved off its start pitch.
+    sliding: bool,
+}
+
+impl HeldNote {
+    /// Bend needed to reach `frequency_hz`, or `None` if it is a new note.
+    fn bend_to(&self, frequency_hz: f32) -> Option<f32> {
+        let offset = 12.0 * (frequency_hz / self.event.frequency_hz).log2();
+        if offset.abs() < PITCH_TOLERANCE_SEMITONES {
+            return Some(0.0);
+        }
+        let detune = (offset - offset.round()).abs();
+        let slide = self.sliding || offset.round() == 0.0 || detune > SLIDE_DETUNE_SEMITONES;
+        (slide && offset.abs() <= PITCH_BEND_RANGE_SEMITONES).then_some(offset)
+    }
+}
+
+/// Builds a [`NoteTimeline`] from register history.
+///
+/// Call [`record_frame`](Self::record_frame) once per player frame with the
+/// register dumps of all PSGs, then [`finish`](Self::finish).
+#[derive(Debug, Clone)]
+pub struct NoteRecorder {
+    frame_rate: u32,
+    master_clock: f32,
+    frame: u32,
+    channel_count: usize,
+    open: Vec<Option<HeldNote>>,
+    events: Vec<NoteEvent>,
+    pitch_bends: Vec<PitchBend>,
+}
+
+impl NoteRecorder {
+    /// Create a recorder for a player running at `frame_rate` Hz.
+    pub fn new(frame_rate: u32) -> Self {
+        Self {
+            frame_rate: if frame_rate == 0 {
+                FRAME_RATE_PAL
+            } else {
+                frame_rate
+            },
+            master_clock: PSG_MASTER_CLOCK_HZ as f32,
+            frame: 0,
+            channel_count: 0,
+            open: Vec::new(),
+            events: Vec::new(),
+            pitch_bends: Vec::new(),
+        }
+    }
+
+    /// Use a PSG master clock other than the Atari ST's 2 MHz (e.g. 1 MHz for CPC).
+    pub fn with_master_clock(mut self, master_clock_hz: u32) -> Self {
+        self.master_clock = master_clock_hz.max(1) as f32;
+        self
+    }
+
+    /// Number of frames recorded so far.
+    pub fn frame_count(&self) -> u32 {
+        self.frame
+    }
+
+    /// Record one frame of register state for all PSGs.
+    pub fn record_frame(&mut self, register_banks: &[[u8; 16]]) {
+        let channel_count = register_banks.len() * 3;
+        if channel_count > self.open.len() {
+            self.open.resize(channel_count, None);
+        }
+        self.channel_count = self.channel_count.max(channel_count);
+
+        for (psg, registers) in register_banks.iter().enumerate() {
+            let states = ChannelStates::from_registers_with_clock(registers, self.master_clock);
+            for (local, state) in states.channels.iter().enumerate() {
+                let channel = psg * 3 + local;
+                let volume = if state.envelope_enabled {
+                    15
+                } else {
+                    state.amplitude
+                };
+                let sounding = state.tone_enabled && volume > 0;
+                let pitch = state
+                    .midi_note
+                    .zip(state.note_name)
+                    .zip(state.frequency_hz)
+                    .filter(|_| sounding);
+
+                let Some(((midi_note, note_name), frequency_hz)) = pitch else {
+                    self.close(channel);
+                    continue;
+                };
+
+                let held = self.open[channel].as_mut().and_then(|held| {
+                    let retrigger =
+                        volume >= held.last_volume.saturating_add(RETRIGGER_VOLUME_STEP);
+                    let bend = held.bend_to(frequency_hz).filter(|_| !retrigger)?;
+                    Some((held, bend))
+                });
+
+                match held {
+                    Some((held, bend)) => {
+                        held.event.duration_frames += 1;
+                        held.event.volume = held.event.volume.max(volume);
+                        held.last_volume = volume;
+                        if (bend - held.bend).abs() >= PITCH_TOLERANCE_SEMITONES {
+                            held.bend = bend;
+                            held.sliding = true;
+                            self.pitch_bends.push(PitchBend {
+                                channel,
+                                frame: self.frame,
+                                semitones: bend,
+                            });
+                        }
+                    }
+                    None => {
+                        self.close(channel);
+                        let event = NoteEvent {
+                            channel,
+                            midi_note,
+                            note_name,
+                            frequency_hz,
+                            start_frame: self.frame,
+                            duration_frames: 1,
+                            volume,
+                            envelope: state.envelope_enabled,
+                        };
+                        self.open[channel] = Some(HeldNote {
+                            event,
+                            last_volume: volume,
+                            bend: 0.0,
+                            sliding: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        // Channels of PSGs missing from this frame are silent
+        for channel in channel_count..self.open.len() {
+            self.close(channel);
+        }
+        self.frame += 1;
+    }
+
+    /// Close all held notes and return the timeline.
+    pub fn finish(mut self) -> NoteTimeline {
+        for channel in 0..self.open.len() {
+            self.close(channel);
+        }
+        self.events
+            .sort_by_key(|event| (event.start_frame, event.channel));
+        self.pitch_bends
+            .sort_by_key(|bend| (bend.frame, bend.channel));
+        NoteTimeline {
+            frame_rate: self.frame_rate,
+            channel_count: self.channel_count,
+            frame_count: self.frame,
+            events: self.events,
+            pitch_bends: self.pitch_bends,
+        }
+    }
+
+    fn close(&mut self, channel: usize) {
+        if let Some(held) = self.open.get_mut(channel).and_then(Option::take) {
+            self.events.push(held.event);
+        }
+    }
+}
+
+/// Serialize a timeline as compact JSON.
+///
+/// Layout: `{"frameRate":50,"channelCount":3,"frameCount":N,"notes":[{"channel":0,
+/// "note":69,"name":"A4","frequency":440.0,"start":0,"duration":25,"volume":15,
+/// "envelope":false}, ...],"pitchBends":[{"channel":0,"frame":12,"semitones":0.5}, ...]}`.
+pub fn to_json(timeline: &NoteTimeline) -> String {
+    let mut json =
+        String::with_capacity(96 + timeline.events.len() * 120 + timeline.pitch_bends.len() * 48);
+    let _ = write!(
+        json,
+        "{{\"frameRate\":{},\"channelCount\":{},\"frameCount\":{},\"notes\":[",
+        timeline.frame_rate, timeline.channel_count, timeline.frame_count
+    );
+    for (i, event) in timeline.events.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            "{{\"channel\":{},\"note\":{},\"name\":\"{}\",\"frequency\":{:.2},\"start\":{},\
+             \"duration\":{},\"volume\":{},\"envelope\":{}}}",
+            event.channel,
+            event.midi_note,
+            event.note_name,
+            event.frequency_hz,
+            event.start_frame,
+            event.duration_frames,
+            event.volume,
+            event.envelope
+        );
+    }
+    json.push_str("],\"pitchBends\":[");
+    for (i, bend) in timeline.pitch_bends.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            "{{\"channel\":{},\"frame\":{},\"semitones\":{:.3}}}",
+            bend.channel, bend.frame, bend.semitones
+        );
+    }
+    json.push_str("]}");
+    json
+}
+
+/// General MIDI program used for every track (81: Lead 1, square).
+const MIDI_PROGRAM_SQUARE_LEAD: u8 = 80;
+
+/// Track event; the variant order is the order within one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum MidiMessage {
+    NoteOff { note: u8 },
+    PitchBend { value: u16 },
+    NoteOn { note: u8, velocity: u8 },
+}
+
+/// Write a timeline as a standard MIDI file (format 1).
+///
+/// Track 0 holds the tempo; then each PSG channel gets its own track and
+/// MIDI channel (3×N tracks, skipping the GM drum channel 10). One tick is
+/// one player frame and a quarter note lasts one second, so the tempo follows
+/// the frame rate exactly. Volumes map linearly to note velocities and
+/// [`PitchBend`]s use a ±[`PITCH_BEND_RANGE_SEMITONES`] bend range, which is
+/// set on every channel via RPN 0.
+pub fn to_midi(timeline: &NoteTimeline) -> Vec<u8> {
+    let ticks_per_quarter = timeline.frame_rate.clamp(1, 0x7FFF) as u16;
+    let track_count = timeline.channel_count.max(1);
+
+    let mut midi = Vec::new();
+    midi.extend_from_slice(b"MThd");
+    midi.extend_from_slice(&6u32.to_be_bytes());
+    midi.extend_from_slice(&1u16.to_be_bytes());
+    midi.extend_from_slice(&(track_count as u16 + 1).to_be_bytes());
+    midi.extend_from_slice(&ticks_per_quarter.to_be_bytes());
+
+    // Tempo track: one quarter note per second (60 BPM)
+    let mut tempo = Vec::new();
+    write_var_len(&mut tempo, 0);
+    tempo.extend_from_slice(&[0xFF, 0x51, 0x03, 0x0F, 0x42, 0x40]);
+    write_end_of_track(&mut tempo);
+    write_chunk(&mut midi, &tempo);
+
+    for channel in 0..track_count {
+        let mut messages: Vec<(u32, MidiMessage)> = Vec::new();
+        for event in timeline.events.iter().filter(|e| e.channel == channel) {
+            let velocity = (event.volume.min(15) as u32 * 127 / 15).max(1) as u8;
+            let note = event.midi_note.min(127);
+            messages.push((event.start_frame, MidiMessage::NoteOn { note, velocity }));
+            messages.push((
+                event.start_frame + event.duration_frames,
+                MidiMessage::NoteOff { note },
+            ));
+        }
+        for bend in timeline.pitch_bends.iter().filter(|b| b.channel == channel) {
+            let value = pitch_bend_value(bend.semitones);
+            messages.push((bend.frame, MidiMessage::PitchBend { value }));
+        }
+        messages.sort();
+
+        // MIDI channel 10 (index 9) is reserved for drums in General MIDI
+        let midi_channel = (if channel >= 9 { channel + 1 } else { channel } % 16) as u8;
+        let mut track = Vec::new();
+        let name = format!(
+            "PSG {} {}",
+            channel / 3,
+            (b'A' + (channel % 3) as u8) as char
+        );
+        write_var_len(&mut track, 0);
+        track.extend_from_slice(&[0xFF, 0x03]);
+        write_var_len(&mut track, name.len() as u32);
+        track.extend_from_slice(name.as_bytes());
+        write_var_len(&mut track, 0);
+        track.extend_from_slice(&[0xC0 | midi_channel, MIDI_PROGRAM_SQUARE_LEAD]);
+        // RPN 0 (pitch bend sensitivity) = bend range in semitones
+        for (controller, value) in [
+            (101, 0),
+            (100, 0),
+            (6, PITCH_BEND_RANGE_SEMITONES as u8),
+            (38, 0),
+        ] {
+            write_var_len(&mut track, 0);
+            track.extend_from_slice(&[0xB0 | midi_channel, controller, value]);
+        }
+
+        let mut last_tick = 0;
+        let mut current_bend = PITCH_BEND_CENTER;
+        for (tick, message) in messages {
+            if let MidiMessage::NoteOn { .. } = message
+                && current_bend != PITCH_BEND_CENTER
+            {
+                // Notes start at their own pitch; clear a bend left by the previous one
+                write_var_len(&mut track, tick - last_tick);
+                last_tick = tick;
+                write_pitch_bend(&mut track, midi_channel, PITCH_BEND_CENTER);
+                current_bend = PITCH_BEND_CENTER;
+            }
+            write_var_len(&mut track, tick - last_tick);
+            last_tick = tick;
+            match message {
+                MidiMessage::NoteOff { note } => {
+                    track.extend_from_slice(&[0x80 | midi_channel, note, 0]);
+                }
+                MidiMessage::PitchBend { value } => {
+                    write_pitch_bend(&mut track, midi_channel, value);
+                    current_bend = value;
+                }
+                MidiMessage::NoteOn { note, velocity } => {
+                    track.extend_from_slice(&[0x90 | midi_channel, note, velocity]);
+                }
+            }
+        }
+        write_end_of_track(&mut track);
+        write_chunk(&mut midi, &track);
+    }
+    midi
+}
+
+/// Centre (no bend) value of the 14-bit MIDI pitch wheel.
+const PITCH_BEND_CENTER: u16 = 0x2000;
+
+fn pitch_bend_value(semitones: f32) -> u16 {
+    let normalized = (semitones / PITCH_BEND_RANGE_SEMITONES).clamp(-1.0, 1.0);
+    (PITCH_BEND_CENTER as f32 + normalized * 8191.0).round() as u16
+}
+
+fn write_pitch_bend(track: &mut Vec<u8>, midi_channel: u8, value: u16) {
+    track.extend_from_slice(&[
+        0xE0 | midi_channel,
+        (value & 0x7F) as u8,
+        ((value >> 7) & 0x7F) as u8,
+    ]);
+}
+
+fn write_chunk(out: &mut Vec<u8>, track: &[u8]) {
+    out.extend_from_slice(b"MTrk");
+    out.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    out.extend_from_slice(track);
+}
+
+fn write_end_of_track(track: &mut Vec<u8>) {
+    write_var_len(track, 0);
+    track.extend_from_slice(&[0xFF, 0x2F, 0x00]);
+}
+
+fn write_var_len(out: &mut Vec<u8>, mut value: u32) {
+    let mut bytes = [0u8; 4];
+    let mut len = 0;
+    loop {
+        bytes[len] = (value & 0x7F) as u8;
+        len += 1;
+        value >>= 7;
+        if value == 0 {
+            break;
+        }
+    }
+    for i in (0..len).rev() {
+        let continuation = if i > 0 { 0x80 } else { 0 };
+        out.push(bytes[i] | continuation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tone_regs(period: u16, volume: u8) -> [u8; 16] {
+        let mut regs = [0u8; 16];
+        regs[0] = (period & 0xFF) as u8;
+        regs[1] = (period >> 8) as u8;
+        regs[7] = 0x3E;
+        regs[8] = volume;
+        regs
+    }
+
+    #[test]
+    fn test_notes_split_on_pitch_silence_and_retrigger() {
+        let mut recorder = NoteRecorder::new(50);
+        let a4 = tone_regs(0x11C, 15);
+        let c5 = tone_regs(0xEE, 15);
+
+        for _ in 0..10 {
+            recorder.record_frame(&[a4]);
+        }
+        // Software decay keeps the same note
+        for volume in [12, 9] {
+            recorder.record_frame(&[tone_regs(0x11C, volume)]);
+        }
+        // Jump back to full volume re-triggers
+        recorder.record_frame(&[a4]);
+        for _ in 0..5 {
+            recorder.record_frame(&[c5]);
+        }
+        recorder.record_frame(&[tone_regs(0xEE, 0)]);
+        recorder.record_frame(&[c5]);
+
+        let timeline = recorder.finish();
+        let summary: Vec<_> = timeline
+            .events
+            .iter()
+            .map(|e| (e.note_name, e.start_frame, e.duration_frames))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("A4", 0, 12), ("A4", 12, 1), ("C5", 13, 5), ("C5", 19, 1)]
+        );
+        assert_eq!(timeline.frame_count, 20);
+    }
+
+    #[test]
+    fn test_multi_psg_channels_are_global() {
+        let mut recorder = NoteRecorder::new(50);
+        recorder.record_frame(&[[0; 16], tone_regs(0x11C, 10)]);
+        let timeline = recorder.finish();
+        assert_eq!(timeline.channel_count, 6);
+        assert_eq!(timeline.events[0].channel, 3);
+        assert_eq!(timeline.events[0].volume, 10);
+    }
+
+    #[test]
+    fn test_midi_file_structure() {
+        let mut recorder = NoteRecorder::new(50);
+        for _ in 0..50 {
+            recorder.record_frame(&[tone_regs(0x11C, 15)]);
+        }
+        let midi = to_midi(&recorder.finish());
+
+        assert_eq!(&midi[..4], b"MThd");
+        assert_eq!(u16::from_be_bytes([midi[10], midi[11]]), 4); // tempo + 3 channels
+        assert_eq!(u16::from_be_bytes([midi[12], midi[13]]), 50); // 1 tick per frame
+        assert_eq!(midi.windows(3).filter(|w| *w == [0x90, 69, 127]).count(), 1);
+        // Note-off after 50 ticks (var-len 50 = 0x32)
+        assert!(midi.windows(4).any(|w| w == [0x32, 0x80, 69, 0]));
+    }
+
+    #[test]
+    fn test_slides_become_pitch_bends() {
+        let mut recorder = NoteRecorder::new(50);
+        for period in [284, 284, 280, 276, 272, 268, 264, 260, 256, 284] {
+            recorder.record_frame(&[tone_regs(period, 15)]);
+        }
+        // Past the bend range: new note
+        recorder.record_frame(&[tone_regs(240, 15)]);
+        let timeline = recorder.finish();
+
+        assert_eq!(timeline.events.len(), 2);
+        assert_eq!(timeline.events[0].duration_frames, 10);
+        let bends: Vec<_> = timeline.pitch_bends.iter().map(|b| b.frame).collect();
+        assert_eq!(bends, vec![2, 3, 4, 5, 6, 7, 8, 9]);
+        assert!((timeline.pitch_bends[6].semitones - 1.80).abs() < 0.01);
+        assert_eq!(timeline.pitch_bends[7].semitones, 0.0);
+    }
+
+    #[test]
+    fn test_semitone_steps_stay_separate_notes() {
+        let mut recorder = NoteRecorder::new(50);
+        // Arpeggio: A4, C#5, E5
+        for period in [284, 225, 190, 284] {
+            recorder.record_frame(&[tone_regs(period, 15)]);
+        }
+        let timeline = recorder.finish();
+        assert_eq!(timeline.events.len(), 4);
+        assert!(timeline.pitch_bends.is_empty());
+    }
+
+    #[test]
+    fn test_midi_pitch_bends() {
+        let mut recorder = NoteRecorder::new(50);
+        for period in [284, 276, 284] {
+            recorder.record_frame(&[tone_regs(period, 15)]);
+        }
+        let timeline = recorder.finish();
+        let midi = to_midi(&timeline);
+
+        // Bend range RPN and square lead program on channel 0
+        assert!(midi.windows(3).any(|w| w == [0xB0, 6, 2]));
+        assert!(
+            midi.windows(2)
+                .any(|w| w == [0xC0, MIDI_PROGRAM_SQUARE_LEAD])
+        );
+        let bend = pitch_bend_value(timeline.pitch_bends[0].semitones);
+        assert!(bend > PITCH_BEND_CENTER);
+        let bend_message = [0xE0, (bend & 0x7F) as u8, (bend >> 7) as u8];
+        assert!(midi.windows(3).any(|w| w == bend_message));
+        assert!(midi.windows(3).any(|w| w == [0xE0, 0x00, 0x40]));
+        assert!(to_json(&timeline).contains("\"pitchBends\":[{\"channel\":0,\"frame\":1,"));
+    }
+
+    #[test]
+    fn test_pitch_bend_value_range() {
+        assert_eq!(pitch_bend_value(0.0), PITCH_BEND_CENTER);
+        assert_eq!(pitch_bend_value(PITCH_BEND_RANGE_SEMITONES), 0x3FFF);
+        assert_eq!(pitch_bend_value(-PITCH_BEND_RANGE_SEMITONES), 1);
+    }
+
+    #[test]
+    fn test_var_len_encoding() {
+        let mut out = Vec::new();
+        write_var_len(&mut out, 0x3FFF);
+        assert_eq!(out, [0xFF, 0x7F]);
+        out.clear();
+        write_var_len(&mut out, 0x80);
+        assert_eq!(out, [0x81, 0x00]);
+    }
+}
@@ -0,0 +1,364 @@
+//! FFT spectrum analysis of generated audio.
+//!
+//! [`FftSpectrumAnalyzer`] complements the register-based
+//! [`SpectrumAnalyzer`](crate::SpectrumAnalyzer): it analyzes the samples a
+//! player actually produced (e.g. from a [`ChannelTap`](crate::ChannelTap)),
+//! so harmonics, digidrums and DAC output show up as they sound. Bins are
+//! log-spaced; the default configuration matches the note-aligned layout of
+//! the register analyzer ([`SPECTRUM_BINS`] bins from C1).
+//!
+//! Requires the `fft` feature.
+
+use crate::visualization::{SPECTRUM_BASE_FREQ, SPECTRUM_BINS, SPECTRUM_DECAY, SPECTRUM_OCTAVES};
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner};
+use std::sync::Arc;
+
+/// Default FFT window length in samples.
+pub const DEFAULT_FFT_SIZE: usize = 2048;
+
+/// Smallest accepted FFT window length.
+const MIN_FFT_SIZE: usize = 64;
+
+/// Largest accepted FFT window length.
+const MAX_FFT_SIZE: usize = 16_384;
+
+/// Configuration of an [`FftSpectrumAnalyzer`].
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::FftSpectrumConfig;
+///
+/// // 64 bins over the full audible range, no smoothing
+/// let config = FftSpectrumConfig::default()
+///     .with_bin_count(64)
+///     .with_freq_range(20.0, 20_000.0)
+///     .with_smoothing(0.0);
+/// assert_eq!(config.bin_count, 64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FftSpectrumConfig {
+    /// FFT window length (rounded up to a power of two, 64 to 16384).
+    pub fft_size: usize,
+    /// Number of log-spaced output bins.
+    pub bin_count: usize,
+    /// Center frequency of the first bin in Hz.
+    pub min_freq_hz: f32,
+    /// Frequency at the upper edge of the bin range in Hz.
+    pub max_freq_hz: f32,
+    /// Release factor per update (0.0 = none, 0.99 = very slow decay).
+    pub smoothing: f32,
+    /// Dynamic range mapped to 0.0..=1.0, in dB below full scale.
+    pub range_db: f32,
+}
+
+impl Default for FftSpectrumConfig {
+    fn default() -> Self {
+        Self {
+            fft_size: DEFAULT_FFT_SIZE,
+            bin_count: SPECTRUM_BINS,
+            min_freq_hz: SPECTRUM_BASE_FREQ,
+            max_freq_hz: SPECTRUM_BASE_FREQ * (1 << SPECTRUM_OCTAVES) as f32,
+            smoothing: SPECTRUM_DECAY,
+            range_db: 60.0,
+        }
+    }
+}
+
+impl FftSpectrumConfig {
+    /// Set the FFT window length.
+    pub fn with_fft_size(mut self, fft_size: usize) -> Self {
+        self.fft_size = fft_size;
+        self
+    }
+
+    /// Set the number of output bins.
+    pub fn with_bin_count(mut self, bin_count: usize) -> Self {
+        self.bin_count = bin_count;
+        self
+    }
+
+    /// Set the frequency range covered by the bins.
+    pub fn with_freq_range(mut self, min_hz: f32, max_hz: f32) -> Self {
+        self.min_freq_hz = min_hz;
+        self.max_freq_hz = max_hz;
+        self
+    }
+
+    /// Set the release factor applied to falling bins.
+    pub fn with_smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
+/// FFT-based spectrum analyzer fed with generated samples.
+///
+/// Push samples as they are produced, then call [`update`](Self::update)
+/// once per display frame to analyze the most recent `fft_size` samples.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::{FftSpectrumAnalyzer, FftSpectrumConfig};
+///
+/// let mut analyzer = FftSpectrumAnalyzer::new(FftSpectrumConfig::default(), 44_100);
+/// let tone: Vec<f32> = (0..2048)
+///     .map(|i| (2.0 * std::f32::consts::PI * 440.0 * i as f32 / 44_100.0).sin())
+///     .collect();
+/// analyzer.push_samples(&tone);
+/// analyzer.update();
+///
+/// let peak = (0..analyzer.bin_count())
+///     .max_by(|&a, &b| analyzer.bins()[a].total_cmp(&analyzer.bins()[b]))
+///     .unwrap();
+/// assert!((analyzer.bin_frequency(peak) - 440.0).abs() < 40.0);
+/// ```
+#[derive(Clone)]
+pub struct FftSpectrumAnalyzer {
+    config: FftSpectrumConfig,
+    sample_rate: u32,
+    fft: Arc<dyn Fft<f32>>,
+    window: Vec<f32>,
+    window_gain: f32,
+    history: Vec<f32>,
+    history_pos: usize,
+    buffer: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+    magnitudes: Vec<f32>,
+    bins: Vec<f32>,
+}
+
+impl FftSpectrumAnalyzer {
+    /// Create an analyzer for audio at `sample_rate`.
+    ///
+    /// Out-of-range settings are clamped: the FFT size to a power of two
+    /// between 64 and 16384, at least one bin, a minimum frequency of 1 Hz
+    /// and a maximum below Nyquist.
+    #[must_use]
+    pub fn new(config: FftSpectrumConfig, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let nyquist = sample_rate as f32 / 2.0;
+        let fft_size = config
+            .fft_size
+            .clamp(MIN_FFT_SIZE, MAX_FFT_SIZE)
+            .next_power_of_two();
+        let min_freq_hz = config.min_freq_hz.clamp(1.0, nyquist / 2.0);
+        let config = FftSpectrumConfig {
+            fft_size,
+            bin_count: config.bin_count.max(1),
+            min_freq_hz,
+            max_freq_hz: config.max_freq_hz.clamp(min_freq_hz * 2.0, nyquist),
+            smoothing: config.smoothing.clamp(0.0, 0.99),
+            range_db: config.range_db.max(1.0),
+        };
+
+        let fft = FftPlanner::new().plan_fft_forward(fft_size);
+        let scratch_len = fft.get_inplace_scratch_len();
+        let window: Vec<f32> = (0..fft_size)
+            .map(|i| 0.5 * (1.0 - (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos()))
+            .collect();
+        let window_gain = window.iter().sum::<f32>();
+
+        Self {
+            config,
+            sample_rate,
+            fft,
+            window,
+            window_gain,
+            history: vec![0.0; fft_size],
+            history_pos: 0,
+            buffer: vec![Complex::default(); fft_size],
+            scratch: vec![Complex::default(); scratch_len],
+            magnitudes: vec![0.0; fft_size / 2],
+            bins: vec![0.0; config.bin_count.max(1)],
+        }
+    }
+
+    /// Effective configuration (after clamping).
+    #[must_use]
+    pub fn config(&self) -> &FftSpectrumConfig {
+        &self.config
+    }
+
+    /// Number of output bins.
+    #[must_use]
+    pub fn bin_count(&self) -> usize {
+        self.bins.len()
+    }
+
+    /// Center frequency of an output bin in Hz.
+    #[must_use]
+    pub fn bin_frequency(&self, bin: usize) -> f32 {
+        self.config.min_freq_hz * 2f32.powf(bin as f32 / self.bins_per_octave())
+    }
+
+    /// Append generated samples to the analysis window.
+    ///
+    /// Only the most recent `fft_size` samples are kept.
+    pub fn push_samples(&mut self, samples: &[f32]) {
+        let size = self.history.len();
+        let samples = &samples[samples.len().saturating_sub(size)..];
+        for &sample in samples {
+            self.history[self.history_pos] = sample;
+            self.history_pos = (self.history_pos + 1) % size;
+        }
+    }
+
+    /// Analyze the current window and update the bins.
+    ///
+    /// Rising bins follow the input immediately; falling bins release by the
+    /// configured smoothing factor.
+    pub fn update(&mut self) {
+        let size = self.history.len();
+        for (i, value) in self.buffer.iter_mut().enumerate() {
+            let sample = self.history[(self.history_pos + i) % size];
+            *value = Complex::new(sample * self.window[i], 0.0);
+        }
+        self.fft
+            .process_with_scratch(&mut self.buffer, &mut self.scratch);
+
+        // Single-sided amplitude spectrum: a full-scale sine reads 1.0
+        let scale = 2.0 / self.window_gain;
+        for (magnitude, value) in self.magnitudes.iter_mut().zip(&self.buffer) {
+            *magnitude = value.norm() * scale;
+        }
+
+        let bin_hz = self.sample_rate as f32 / size as f32;
+        let half_band = 2f32.powf(0.5 / self.bins_per_octave());
+        for bin in 0..self.bins.len() {
+            let center = self.bin_frequency(bin);
+            let lo = ((center / half_band) / bin_hz).ceil() as usize;
+            let hi = ((center * half_band) / bin_hz).floor() as usize;
+            let amplitude = if lo <= hi {
+                self.peak_magnitude(lo, hi)
+            } else {
+                // Band narrower than the FFT resolution: interpolate
+                self.interpolated_magnitude(center / bin_hz)
+            };
+
+            let level = self.level_from_amplitude(amplitude);
+            let previous = self.bins[bin];
+            self.bins[bin] = if level >= previous {
+                level
+            } else {
+                (previous * self.config.smoothing).max(level)
+            };
+        }
+    }
+
+    /// Latest bin levels (0.0 to 1.0, log-spaced from `min_freq_hz`).
+    #[must_use]
+    pub fn bins(&self) -> &[f32] {
+        &self.bins
+    }
+
+    /// Clear the sample window and bins.
+    pub fn reset(&mut self) {
+        self.history.fill(0.0);
+        self.history_pos = 0;
+        self.bins.fill(0.0);
+    }
+
+    fn bins_per_octave(&self) -> f32 {
+        let octaves = (self.config.max_freq_hz / self.config.min_freq_hz).log2();
+        self.bins.len() as f32 / octaves
+    }
+
+    fn peak_magnitude(&self, lo: usize, hi: usize) -> f32 {
+        let last = self.magnitudes.len() - 1;
+        self.magnitudes[lo.min(last)..=hi.min(last)]
+            .iter()
+            .copied()
+            .fold(0.0, f32::max)
+    }
+
+    fn interpolated_magnitude(&self, position: f32) -> f32 {
+        let last = self.magnitudes.len() - 1;
+        let index = (position.floor() as usize).min(last);
+        let next = (index + 1).min(last);
+        let frac = position - index as f32;
+        self.magnitudes[index] * (1.0 - frac) + self.magnitudes[next] * frac
+    }
+
+    fn level_from_amplitude(&self, amplitude: f32) -> f32 {
+        if amplitude <= 0.0 {
+            return 0.0;
+        }
+        let db = 20.0 * amplitude.log10();
+        ((db + self.config.range_db) / self.config.range_db).clamp(0.0, 1.0)
+    }
+}
+
+impl std::fmt::Debug for FftSpectrumAnalyzer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FftSpectrumAnalyzer")
+            .field("config", &self.config)
+            .field("sample_rate", &self.sample_rate)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visualization::freq_to_bin;
+
+    fn sine(freq: f32, amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| amplitude * (2.0 * std::f32::consts::PI * freq * i as f32 / 44_100.0).sin())
+            .collect()
+    }
+
+    fn peak_bin(analyzer: &FftSpectrumAnalyzer) -> usize {
+        let bins = analyzer.bins();
+        (0..bins.len())
+            .max_by(|&a, &b| bins[a].total_cmp(&bins[b]))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_bins_match_register_analyzer() {
+        let mut analyzer = FftSpectrumAnalyzer::new(FftSpectrumConfig::default(), 44_100);
+        for freq in [110.0, 440.0, 1760.0] {
+            analyzer.reset();
+            analyzer.push_samples(&sine(freq, 0.8, DEFAULT_FFT_SIZE));
+            analyzer.update();
+            assert_eq!(peak_bin(&analyzer), freq_to_bin(freq), "{freq} Hz");
+        }
+    }
+
+    #[test]
+    fn test_silence_and_smoothing() {
+        let config = FftSpectrumConfig::default().with_smoothing(0.5);
+        let mut analyzer = FftSpectrumAnalyzer::new(config, 44_100);
+        analyzer.update();
+        assert!(analyzer.bins().iter().all(|&level| level == 0.0));
+
+        analyzer.push_samples(&sine(440.0, 1.0, DEFAULT_FFT_SIZE));
+        analyzer.update();
+        let bin = peak_bin(&analyzer);
+        let loud = analyzer.bins()[bin];
+        assert!(
+            loud > 0.9,
+            "full-scale sine should read near 1.0, got {loud}"
+        );
+
+        analyzer.push_samples(&[0.0; DEFAULT_FFT_SIZE]);
+        analyzer.update();
+        assert!((analyzer.bins()[bin] - loud * 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_config_is_clamped() {
+        let config = FftSpectrumConfig::default()
+            .with_fft_size(1000)
+            .with_bin_count(0)
+            .with_freq_range(0.0, 1_000_000.0);
+        let analyzer = FftSpectrumAnalyzer::new(config, 44_100);
+        assert_eq!(analyzer.config().fft_size, 1024);
+        assert_eq!(analyzer.bin_count(), 1);
+        assert_eq!(analyzer.config().max_freq_hz, 22_050.0);
+    }
+}
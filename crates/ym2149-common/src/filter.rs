@@ -0,0 +1,217 @@
+//! Output filter configuration for YM2149 backends.
+//!
+//! A [`FilterChain`] describes the post-mix stages a backend applies to its
+//! output: a DC blocker followed by an optional one-pole low-pass. Both
+//! stages can be switched off to get the raw, unipolar chip signal.
+//!
+//! [`OutputModel`] provides presets approximating the analog output stage of
+//! specific machines, for A/B comparisons against hardware recordings.
+
+/// Default DC blocker cutoff in Hz.
+///
+/// Matches the 2048-sample running average the hardware-accurate backend has
+/// always used at 44.1 kHz.
+pub const DEFAULT_DC_CUTOFF_HZ: f32 = 9.5;
+
+/// Atari STF output capacitor C10 (0.1 µF) in farads.
+///
+/// Reference designators follow the Atari 520ST FM schematics, as quoted
+/// in the low-pass notes of Hatari's `src/sound.c`.
+const STF_C10_FARADS: f32 = 0.1e-6;
+
+/// Resistance C10 sees while the YM2149 output is low, in ohms:
+/// R8 = 1 kΩ ∥ R9 = 10 kΩ ∥ R10 = 5.1 kΩ ∥ R12 = 470 Ω × Q1 hFE (≈ 100).
+const STF_SOURCE_OHMS_LOW: f32 = 759.1;
+
+/// Resistance C10 sees while the YM2149 output is high, in ohms: the
+/// network of [`STF_SOURCE_OHMS_LOW`] in parallel with the chip's output
+/// resistance (1 kΩ × (65119 - 46602) / 65119).
+const STF_SOURCE_OHMS_HIGH: f32 = 206.865;
+
+/// Corner frequency of a first-order RC low-pass.
+const fn rc_cutoff_hz(ohms: f32, farads: f32) -> f32 {
+    1.0 / (2.0 * std::f32::consts::PI * ohms * farads)
+}
+
+/// Low-pass cutoff of the Atari ST(F) output stage (≈ 4895 Hz).
+///
+/// C10 forms a first-order low-pass whose corner moves with the chip's
+/// output level, from 2096.6 Hz (output low) to 7693.7 Hz (output high).
+/// The linear model uses the mean of both corners, as Hatari does.
+pub const STF_LOWPASS_CUTOFF_HZ: f32 = (rc_cutoff_hz(STF_SOURCE_OHMS_LOW, STF_C10_FARADS)
+    + rc_cutoff_hz(STF_SOURCE_OHMS_HIGH, STF_C10_FARADS))
+    / 2.0;
+
+/// Low-pass cutoff used for the Amstrad CPC output stage.
+///
+/// Not derived from the CPC schematics or fitted to a recording yet: an
+/// unsourced estimate of the amplifier roll-off, kept until the RC values
+/// of the CPC 464/6128 audio path are documented here.
+pub const CPC_LOWPASS_CUTOFF_HZ: f32 = 5_500.0;
+
+/// Post-mix filter stages of a backend.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::FilterChain;
+///
+/// // Keep the default DC blocker and roll off the top end at 8 kHz
+/// let chain = FilterChain::default().with_lowpass(Some(8_000.0));
+/// assert_eq!(chain.lowpass_cutoff_hz, Some(8_000.0));
+///
+/// // Raw chip output for analysis
+/// assert_eq!(FilterChain::raw().dc_cutoff_hz, None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterChain {
+    /// DC blocker cutoff in Hz, or `None` to keep the DC offset.
+    pub dc_cutoff_hz: Option<f32>,
+    /// One-pole low-pass cutoff in Hz, or `None` to bypass the low-pass.
+    pub lowpass_cutoff_hz: Option<f32>,
+}
+
+impl Default for FilterChain {
+    fn default() -> Self {
+        Self {
+            dc_cutoff_hz: Some(DEFAULT_DC_CUTOFF_HZ),
+            lowpass_cutoff_hz: None,
+        }
+    }
+}
+
+impl FilterChain {
+    /// Chain with every stage disabled.
+    pub fn raw() -> Self {
+        Self {
+            dc_cutoff_hz: None,
+            lowpass_cutoff_hz: None,
+        }
+    }
+
+    /// Set the DC blocker cutoff (`None` disables the stage).
+    pub fn with_dc_cutoff(mut self, cutoff_hz: Option<f32>) -> Self {
+        self.dc_cutoff_hz = cutoff_hz;
+        self
+    }
+
+    /// Set the low-pass cutoff (`None` disables the stage).
+    pub fn with_lowpass(mut self, cutoff_hz: Option<f32>) -> Self {
+        self.lowpass_cutoff_hz = cutoff_hz;
+        self
+    }
+}
+
+impl From<OutputModel> for FilterChain {
+    fn from(model: OutputModel) -> Self {
+        model.filter_chain()
+    }
+}
+
+/// Analog output stage presets.
+///
+/// Each model maps to a [`FilterChain`] with the default DC blocker and,
+/// where the machine has one, a first-order low-pass for its output RC
+/// filter ([`STF_LOWPASS_CUTOFF_HZ`], [`CPC_LOWPASS_CUTOFF_HZ`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputModel {
+    /// No output-stage low-pass (DC blocker only).
+    #[default]
+    Raw,
+    /// Atari ST/STF RC low-pass (C10, see [`STF_LOWPASS_CUTOFF_HZ`]).
+    StfFilter,
+    /// Atari STE output stage, brighter than the STF.
+    ///
+    /// C10 is only fitted on the ST/STF (per Hatari's `src/sound.c` notes),
+    /// so the STE's YM path has no RC low-pass: DC blocker only.
+    SteFilter,
+    /// Amstrad CPC amplifier roll-off.
+    CpcFilter,
+}
+
+impl OutputModel {
+    /// All models, in display order.
+    pub const ALL: [OutputModel; 4] = [
+        OutputModel::Raw,
+        OutputModel::StfFilter,
+        OutputModel::SteFilter,
+        OutputModel::CpcFilter,
+    ];
+
+    /// Parse a model from its short name (`raw`, `stf`, `ste`, `cpc`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "raw" => Some(OutputModel::Raw),
+            "stf" | "st" => Some(OutputModel::StfFilter),
+            "ste" => Some(OutputModel::SteFilter),
+            "cpc" => Some(OutputModel::CpcFilter),
+            _ => None,
+        }
+    }
+
+    /// Short name of the model.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputModel::Raw => "raw",
+            OutputModel::StfFilter => "stf",
+            OutputModel::SteFilter => "ste",
+            OutputModel::CpcFilter => "cpc",
+        }
+    }
+
+    /// Low-pass cutoff of the model, if any.
+    pub fn lowpass_cutoff_hz(&self) -> Option<f32> {
+        match self {
+            OutputModel::Raw | OutputModel::SteFilter => None,
+            OutputModel::StfFilter => Some(STF_LOWPASS_CUTOFF_HZ),
+            OutputModel::CpcFilter => Some(CPC_LOWPASS_CUTOFF_HZ),
+        }
+    }
+
+    /// Filter chain implementing the model.
+    pub fn filter_chain(&self) -> FilterChain {
+        FilterChain::default().with_lowpass(self.lowpass_cutoff_hz())
+    }
+}
+
+impl std::fmt::Display for OutputModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_output_model_names_round_trip() {
+        for model in OutputModel::ALL {
+            assert_eq!(OutputModel::from_name(model.as_str()), Some(model));
+        }
+        assert_eq!(OutputModel::from_name("STE"), Some(OutputModel::SteFilter));
+        assert_eq!(OutputModel::from_name("amiga"), None);
+    }
+
+    #[test]
+    fn test_output_model_filter_chains() {
+        assert_eq!(FilterChain::from(OutputModel::Raw), FilterChain::default());
+        let stf = OutputModel::StfFilter.filter_chain();
+        assert_eq!(stf.dc_cutoff_hz, Some(DEFAULT_DC_CUTOFF_HZ));
+        assert_eq!(stf.lowpass_cutoff_hz, Some(STF_LOWPASS_CUTOFF_HZ));
+        assert_eq!(OutputModel::SteFilter.lowpass_cutoff_hz(), None);
+    }
+
+    #[test]
+    fn test_stf_cutoff_matches_schematic_corners() {
+        let low = rc_cutoff_hz(STF_SOURCE_OHMS_LOW, STF_C10_FARADS);
+        let high = rc_cutoff_hz(STF_SOURCE_OHMS_HIGH, STF_C10_FARADS);
+        assert!((low - 2096.6).abs() < 0.1, "{low}");
+        assert!((high - 7693.7).abs() < 0.1, "{high}");
+        assert!((STF_LOWPASS_CUTOFF_HZ - 4895.1).abs() < 0.1);
+
+        // The parallel network reproduces the quoted source resistance
+        let parallel = 1.0 / (1.0 / 1_000.0 + 1.0 / 10_000.0 + 1.0 / 5_100.0 + 1.0 / 47_000.0);
+        assert!((parallel - STF_SOURCE_OHMS_LOW).abs() < 0.1, "{parallel}");
+    }
+}
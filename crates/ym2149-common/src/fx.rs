@@ -0,0 +1,709 @@
+//! Insert effects for creative playback.
+//!
+//! An [`FxRack`] runs an optional [`EffectChain`] on each PSG channel and on
+//! the master bus: simple delay, chorus and a small Schroeder reverb. These
+//! are not part of any original hardware; they are meant for people using the
+//! replayers as a playback engine rather than as a reference emulator.
+//!
+//! Channel chains work on the per-channel outputs reported by the backend
+//! (see [`Ym2149Backend::get_channel_outputs`](crate::Ym2149Backend::get_channel_outputs))
+//! and add only their difference to the mixed sample, so the dry mix is
+//! left exactly as the chip produced it.
+//!
+//! Chains can be written as short specs, e.g. `"delay:300:0.4,reverb"`; see
+//! [`EffectChain::parse`].
+//!
+//! Requires the `fx` feature.
+
+use std::f32::consts::TAU;
+
+/// Number of PSG channels with their own insert chain.
+const FX_CHANNELS: usize = 3;
+
+/// Scale applied to a channel's effect output before it joins the mix.
+///
+/// Backends mix the three channels at roughly a third of their individual level.
+const CHANNEL_MIX_SCALE: f32 = 1.0 / 3.0;
+
+/// Longest accepted delay time in milliseconds.
+pub const MAX_DELAY_MS: f32 = 2_000.0;
+
+/// Base delay of the chorus voice in milliseconds.
+const CHORUS_BASE_MS: f32 = 12.0;
+
+/// Longest accepted chorus modulation depth in milliseconds.
+const MAX_CHORUS_DEPTH_MS: f32 = 20.0;
+
+/// Reverb comb filter lengths at 44.1 kHz (Freeverb tunings).
+const REVERB_COMB_TUNING: [usize; 4] = [1116, 1188, 1277, 1356];
+
+/// Reverb all-pass filter lengths at 44.1 kHz.
+const REVERB_ALLPASS_TUNING: [usize; 2] = [556, 441];
+
+/// Input gain into the reverb combs.
+const REVERB_INPUT_GAIN: f32 = 0.03;
+
+/// One insert effect with its parameters.
+///
+/// `mix` is the wet/dry balance of every effect (0.0 = dry, 1.0 = wet only).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertEffect {
+    /// Feedback delay (echo).
+    Delay {
+        /// Delay time in milliseconds (up to [`MAX_DELAY_MS`]).
+        time_ms: f32,
+        /// Amount of the echo fed back into the line (0.0-0.95).
+        feedback: f32,
+        /// Wet/dry balance.
+        mix: f32,
+    },
+    /// Single-voice chorus with a sine LFO.
+    Chorus {
+        /// LFO rate in Hz.
+        rate_hz: f32,
+        /// Modulation depth in milliseconds.
+        depth_ms: f32,
+        /// Wet/dry balance.
+        mix: f32,
+    },
+    /// Small Schroeder reverb (four combs, two all-passes).
+    Reverb {
+        /// Decay length (0.0 = small room, 1.0 = long tail).
+        room_size: f32,
+        /// High-frequency damping of the tail (0.0-1.0).
+        damping: f32,
+        /// Wet/dry balance.
+        mix: f32,
+    },
+}
+
+impl InsertEffect {
+    /// Delay with default settings (250 ms, 0.35 feedback, 0.3 mix).
+    pub fn delay() -> Self {
+        InsertEffect::Delay {
+            time_ms: 250.0,
+            feedback: 0.35,
+            mix: 0.3,
+        }
+    }
+
+    /// Chorus with default settings (0.8 Hz, 3 ms depth, 0.5 mix).
+    pub fn chorus() -> Self {
+        InsertEffect::Chorus {
+            rate_hz: 0.8,
+            depth_ms: 3.0,
+            mix: 0.5,
+        }
+    }
+
+    /// Reverb with default settings (0.5 room size, 0.5 damping, 0.25 mix).
+    pub fn reverb() -> Self {
+        InsertEffect::Reverb {
+            room_size: 0.5,
+            damping: 0.5,
+            mix: 0.25,
+        }
+    }
+
+    /// Parse one effect spec: a name followed by optional `:`-separated parameters.
+    ///
+    /// - `delay[:time_ms[:feedback[:mix]]]`
+    /// - `chorus[:rate_hz[:depth_ms[:mix]]]`
+    /// - `reverb[:room_size[:damping[:mix]]]`
+    ///
+    /// Omitted parameters keep their defaults.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut parts = spec.trim().split(':');
+        let name = parts.next().unwrap_or_default().to_ascii_lowercase();
+        let params = parts
+            .map(|p| {
+                p.trim()
+                    .parse::<f32>()
+                    .map_err(|_| format!("invalid number '{p}' in effect '{spec}'"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if params.len() > 3 {
+            return Err(format!("too many parameters in effect '{spec}' (max 3)"));
+        }
+        let mut effect = match name.as_str() {
+            "delay" => Self::delay(),
+            "chorus" => Self::chorus(),
+            "reverb" => Self::reverb(),
+            _ => {
+                return Err(format!(
+                    "unknown effect '{name}' (expected delay, chorus or reverb)"
+                ));
+            }
+        };
+        let fields = match &mut effect {
+            InsertEffect::Delay {
+                time_ms,
+                feedback,
+                mix,
+            } => [time_ms, feedback, mix],
+            InsertEffect::Chorus {
+                rate_hz,
+                depth_ms,
+                mix,
+            } => [rate_hz, depth_ms, mix],
+            InsertEffect::Reverb {
+                room_size,
+                damping,
+                mix,
+            } => [room_size, damping, mix],
+        };
+        for (field, value) in fields.into_iter().zip(params) {
+            *field = value;
+        }
+        Ok(effect)
+    }
+}
+
+/// Ordered list of insert effects applied in series.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::{EffectChain, InsertEffect};
+///
+/// let chain = EffectChain::parse("delay:300:0.4, reverb").unwrap();
+/// assert_eq!(chain.effects().len(), 2);
+/// assert_eq!(chain.effects()[1], InsertEffect::reverb());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EffectChain {
+    effects: Vec<InsertEffect>,
+}
+
+impl EffectChain {
+    /// Empty chain (passes audio through unchanged).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an effect to the end of the chain.
+    #[must_use]
+    pub fn with(mut self, effect: InsertEffect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    /// Parse a comma-separated list of effect specs (see [`InsertEffect::parse`]).
+    ///
+    /// An empty string or `none` gives an empty chain.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if spec.is_empty() || spec.eq_ignore_ascii_case("none") {
+            return Ok(Self::new());
+        }
+        let effects = spec
+            .split(',')
+            .map(InsertEffect::parse)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { effects })
+    }
+
+    /// Effects in processing order.
+    pub fn effects(&self) -> &[InsertEffect] {
+        &self.effects
+    }
+
+    /// Whether the chain has no effects.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+}
+
+/// Insert chains for the three channels of a PSG and the master bus.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::FxConfig;
+///
+/// let mut fx = FxConfig::default();
+/// fx.apply_spec("b=chorus").unwrap();
+/// fx.apply_spec("reverb:0.7").unwrap(); // no target: master bus
+/// assert!(fx.has_channel_effects());
+/// assert!(!fx.master.is_empty());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FxConfig {
+    /// Chains for channels A, B and C (first PSG only).
+    pub channels: [EffectChain; FX_CHANNELS],
+    /// Chain applied to the mixed output.
+    pub master: EffectChain,
+}
+
+impl FxConfig {
+    /// Set one chain from a `target=chain` spec.
+    ///
+    /// `target` is `master`, `a`, `b` or `c`; without a target the chain goes
+    /// on the master bus. The chain syntax is described in [`EffectChain::parse`].
+    pub fn apply_spec(&mut self, spec: &str) -> Result<(), String> {
+        let (target, chain) = match spec.split_once('=') {
+            Some((target, chain)) => (target.trim().to_ascii_lowercase(), chain),
+            None => ("master".to_string(), spec),
+        };
+        let chain = EffectChain::parse(chain)?;
+        match target.as_str() {
+            "master" => self.master = chain,
+            "a" => self.channels[0] = chain,
+            "b" => self.channels[1] = chain,
+            "c" => self.channels[2] = chain,
+            _ => {
+                return Err(format!(
+                    "unknown effect target '{target}' (expected master, a, b or c)"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any channel has an insert chain.
+    ///
+    /// Players only need to capture per-channel outputs when this is true.
+    pub fn has_channel_effects(&self) -> bool {
+        self.channels.iter().any(|chain| !chain.is_empty())
+    }
+
+    /// Whether no chain has any effect.
+    pub fn is_empty(&self) -> bool {
+        !self.has_channel_effects() && self.master.is_empty()
+    }
+}
+
+/// Circular delay line with fractional reads.
+#[derive(Debug, Clone)]
+struct DelayLine {
+    buffer: Box<[f32]>,
+    write_pos: usize,
+}
+
+impl DelayLine {
+    fn new(len: usize) -> Self {
+        Self {
+            buffer: vec![0.0; len.max(2)].into_boxed_slice(),
+            write_pos: 0,
+        }
+    }
+
+    /// Sample written `delay` samples ago (1.0 = previous sample).
+    #[inline]
+    fn read(&self, delay: f32) -> f32 {
+        let len = self.buffer.len();
+        let delay = delay.clamp(1.0, (len - 1) as f32);
+        let whole = delay as usize;
+        let frac = delay - whole as f32;
+        let a = self.buffer[(self.write_pos + len - whole) % len];
+        let b = self.buffer[(self.write_pos + len - whole - 1) % len];
+        a + (b - a) * frac
+    }
+
+    #[inline]
+    fn write(&mut self, value: f32) {
+        self.buffer[self.write_pos] = value;
+        self.write_pos = (self.write_pos + 1) % self.buffer.len();
+    }
+}
+
+/// Feedback comb filter with a one-pole low-pass in the loop.
+#[derive(Debug, Clone)]
+struct Comb {
+    line: DelayLine,
+    len: usize,
+    store: f32,
+}
+
+/// Schroeder all-pass diffuser.
+#[derive(Debug, Clone)]
+struct AllPass {
+    line: DelayLine,
+    len: usize,
+}
+
+/// Runtime state of one insert effect.
+#[derive(Debug, Clone)]
+enum EffectState {
+    Delay {
+        line: DelayLine,
+        delay: f32,
+        feedback: f32,
+        mix: f32,
+    },
+    Chorus {
+        line: DelayLine,
+        phase: f32,
+        phase_step: f32,
+        base: f32,
+        depth: f32,
+        mix: f32,
+    },
+    Reverb {
+        combs: Vec<Comb>,
+        allpasses: Vec<AllPass>,
+        feedback: f32,
+        damping: f32,
+        mix: f32,
+    },
+}
+
+impl EffectState {
+    fn new(effect: &InsertEffect, sample_rate: u32) -> Self {
+        let samples_per_ms = sample_rate.max(1) as f32 / 1000.0;
+        match *effect {
+            InsertEffect::Delay {
+                time_ms,
+                feedback,
+                mix,
+            } => {
+                let delay = (time_ms.clamp(1.0, MAX_DELAY_MS) * samples_per_ms).max(1.0);
+                EffectState::Delay {
+                    line: DelayLine::new(delay.ceil() as usize + 2),
+                    delay,
+                    feedback: feedback.clamp(0.0, 0.95),
+                    mix: mix.clamp(0.0, 1.0),
+                }
+            }
+            InsertEffect::Chorus {
+                rate_hz,
+                depth_ms,
+                mix,
+            } => {
+                let depth = depth_ms.clamp(0.0, MAX_CHORUS_DEPTH_MS) * samples_per_ms;
+                let base = CHORUS_BASE_MS * samples_per_ms;
+                EffectState::Chorus {
+                    line: DelayLine::new((base + depth).ceil() as usize + 2),
+                    phase: 0.0,
+                    phase_step: rate_hz.max(0.0) / sample_rate.max(1) as f32,
+                    base,
+                    depth,
+                    mix: mix.clamp(0.0, 1.0),
+                }
+            }
+            InsertEffect::Reverb {
+                room_size,
+                damping,
+                mix,
+            } => {
+                let scale = sample_rate.max(1) as f32 / 44_100.0;
+                let scaled = |len: usize| ((len as f32 * scale) as usize).max(1);
+                EffectState::Reverb {
+                    combs: REVERB_COMB_TUNING
+                        .iter()
+                        .map(|&len| Comb {
+                            line: DelayLine::new(scaled(len) + 1),
+                            len: scaled(len),
+                            store: 0.0,
+                        })
+                        .collect(),
+                    allpasses: REVERB_ALLPASS_TUNING
+                        .iter()
+                        .map(|&len| AllPass {
+                            line: DelayLine::new(scaled(len) + 1),
+                            len: scaled(len),
+                        })
+                        .collect(),
+                    feedback: 0.7 + 0.28 * room_size.clamp(0.0, 1.0),
+                    damping: damping.clamp(0.0, 1.0) * 0.4,
+                    mix: mix.clamp(0.0, 1.0),
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        match self {
+            EffectState::Delay {
+                line,
+                delay,
+                feedback,
+                mix,
+            } => {
+                let echo = line.read(*delay);
+                line.write(input + echo * *feedback);
+                input + (echo - input) * *mix
+            }
+            EffectState::Chorus {
+                line,
+                phase,
+                phase_step,
+                base,
+                depth,
+                mix,
+            } => {
+                line.write(input);
+                let offset = *base + *depth * 0.5 * (1.0 + (*phase * TAU).sin());
+                *phase = (*phase + *phase_step).fract();
+                let voice = line.read(offset);
+                input + (voice - input) * *mix
+            }
+            EffectState::Reverb {
+                combs,
+                allpasses,
+                feedback,
+                damping,
+                mix,
+            } => {
+                let fed = input * REVERB_INPUT_GAIN;
+                let mut wet = 0.0;
+                for comb in combs.iter_mut() {
+                    let out = comb.line.read(comb.len as f32);
+                    comb.store = out + (comb.store - out) * *damping;
+                    comb.line.write(fed + comb.store * *feedback);
+                    wet += out;
+                }
+                for allpass in allpasses.iter_mut() {
+                    let buffered = allpass.line.read(allpass.len as f32);
+                    allpass.line.write(wet + buffered * 0.5);
+                    wet = buffered - wet;
+                }
+                input + (wet - input) * *mix
+            }
+        }
+    }
+}
+
+/// Runtime state of an [`EffectChain`].
+#[derive(Debug, Clone, Default)]
+struct ChainState {
+    effects: Vec<EffectState>,
+}
+
+impl ChainState {
+    fn new(chain: &EffectChain, sample_rate: u32) -> Self {
+        Self {
+            effects: chain
+                .effects()
+                .iter()
+                .map(|effect| EffectState::new(effect, sample_rate))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, input: f32) -> f32 {
+        self.effects
+            .iter_mut()
+            .fold(input, |sample, effect| effect.process(sample))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+}
+
+/// Processor running an [`FxConfig`] on a player's output.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::{EffectChain, FxConfig, FxRack, InsertEffect};
+///
+/// let config = FxConfig {
+///     master: EffectChain::new().with(InsertEffect::delay()),
+///     ..Default::default()
+/// };
+/// let mut rack = FxRack::new(&config, 44_100);
+/// let out = rack.process(0.5, [0.0; 3]);
+/// assert!(out.is_finite());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FxRack {
+    config: FxConfig,
+    sample_rate: u32,
+    channels: [ChainState; FX_CHANNELS],
+    master: ChainState,
+}
+
+impl FxRack {
+    /// Create a rack for `config` at the given output sample rate.
+    pub fn new(config: &FxConfig, sample_rate: u32) -> Self {
+        Self {
+            config: config.clone(),
+            sample_rate,
+            channels: std::array::from_fn(|ch| ChainState::new(&config.channels[ch], sample_rate)),
+            master: ChainState::new(&config.master, sample_rate),
+        }
+    }
+
+    /// Configuration the rack was built from.
+    pub fn config(&self) -> &FxConfig {
+        &self.config
+    }
+
+    /// Whether any channel chain is active (callers must then pass real channel outputs).
+    pub fn has_channel_effects(&self) -> bool {
+        self.channels.iter().any(|chain| !chain.is_empty())
+    }
+
+    /// Clear all delay lines and reverb tails.
+    pub fn reset(&mut self) {
+        *self = Self::new(&self.config, self.sample_rate);
+    }
+
+    /// Process one mono sample.
+    ///
+    /// `channels` holds the backend's per-channel outputs (`[A, B, C]`) for
+    /// the same sample; it is ignored when no channel chain is active.
+    #[inline]
+    pub fn process(&mut self, sample: f32, channels: [f32; FX_CHANNELS]) -> f32 {
+        let mut mixed = sample;
+        for (chain, input) in self.channels.iter_mut().zip(channels) {
+            if !chain.is_empty() {
+                mixed += (chain.process(input) - input) * CHANNEL_MIX_SCALE;
+            }
+        }
+        self.master.process(mixed)
+    }
+
+    /// Process one stereo frame.
+    ///
+    /// Effects run on the mid signal; their difference is added to both sides
+    /// so the stereo image of the dry signal is preserved.
+    #[inline]
+    pub fn process_stereo(
+        &mut self,
+        left: f32,
+        right: f32,
+        channels: [f32; FX_CHANNELS],
+    ) -> (f32, f32) {
+        let mid = (left + right) * 0.5;
+        let delta = self.process(mid, channels) - mid;
+        (left + delta, right + delta)
+    }
+
+    /// Process a mono buffer in place with matching per-channel outputs.
+    ///
+    /// Pass an empty `channels` slice when no channel chain is active.
+    pub fn process_buffer(&mut self, samples: &mut [f32], channels: &[[f32; FX_CHANNELS]]) {
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let outputs = channels.get(i).copied().unwrap_or_default();
+            *sample = self.process(*sample, outputs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RATE: u32 = 1_000;
+
+    fn rack(config: FxConfig) -> FxRack {
+        FxRack::new(&config, RATE)
+    }
+
+    #[test]
+    fn empty_rack_passes_through() {
+        let mut rack = rack(FxConfig::default());
+        for i in 0..100 {
+            let s = (i as f32 * 0.1).sin();
+            assert_eq!(rack.process(s, [0.3, -0.2, 0.1]), s);
+        }
+    }
+
+    #[test]
+    fn delay_echoes_impulse_after_delay_time() {
+        let config = FxConfig {
+            master: EffectChain::new().with(InsertEffect::Delay {
+                time_ms: 10.0,
+                feedback: 0.5,
+                mix: 1.0,
+            }),
+            ..Default::default()
+        };
+        let mut rack = rack(config);
+        let out: Vec<f32> = (0..32)
+            .map(|i| rack.process(if i == 0 { 1.0 } else { 0.0 }, [0.0; 3]))
+            .collect();
+        // 10 ms at 1 kHz = 10 samples; the echo repeats at half level
+        assert_eq!(out[0], 0.0);
+        assert!((out[10] - 1.0).abs() < 1e-6);
+        assert!((out[20] - 0.5).abs() < 1e-6);
+        assert!(out[1..10].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn channel_chain_only_adds_its_difference() {
+        let config = FxConfig {
+            channels: [
+                EffectChain::new(),
+                EffectChain::new().with(InsertEffect::Delay {
+                    time_ms: 5.0,
+                    feedback: 0.0,
+                    mix: 1.0,
+                }),
+                EffectChain::new(),
+            ],
+            ..Default::default()
+        };
+        let mut rack = rack(config);
+        assert!(rack.has_channel_effects());
+        // Channel B impulse: the dry copy is removed and an echo appears 5 samples later
+        let first = rack.process(0.5, [0.0, 1.0, 0.0]);
+        assert!((first - (0.5 - CHANNEL_MIX_SCALE)).abs() < 1e-6);
+        let later: Vec<f32> = (1..8).map(|_| rack.process(0.5, [0.0; 3])).collect();
+        assert!((later[3] - 0.5).abs() < 1e-6);
+        assert!((later[4] - (0.5 + CHANNEL_MIX_SCALE)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn stereo_processing_keeps_side_signal() {
+        let config = FxConfig {
+            master: EffectChain::new().with(InsertEffect::chorus()),
+            ..Default::default()
+        };
+        let mut rack = FxRack::new(&config, 44_100);
+        for i in 0..500 {
+            let (l, r) = (0.4 * (i as f32 * 0.05).sin(), -0.1);
+            let (out_l, out_r) = rack.process_stereo(l, r, [0.0; 3]);
+            assert!(((out_l - out_r) - (l - r)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn reverb_tail_decays() {
+        let config = FxConfig {
+            master: EffectChain::new().with(InsertEffect::Reverb {
+                room_size: 0.5,
+                damping: 0.5,
+                mix: 1.0,
+            }),
+            ..Default::default()
+        };
+        let mut rack = FxRack::new(&config, 44_100);
+        let mut buffer = vec![0.0; 44_100 * 4];
+        buffer[0] = 1.0;
+        rack.process_buffer(&mut buffer, &[]);
+        let energy = |s: &[f32]| s.iter().map(|x| x * x).sum::<f32>();
+        let early = energy(&buffer[..22_050]);
+        let late = energy(&buffer[buffer.len() - 22_050..]);
+        assert!(early > 0.0, "reverb should produce a tail");
+        assert!(late < early * 1e-3, "tail should decay: {early} -> {late}");
+        assert!(buffer.iter().all(|s| s.is_finite()));
+    }
+
+    #[test]
+    fn parse_specs() {
+        assert_eq!(
+            InsertEffect::parse("delay:120").unwrap(),
+            InsertEffect::Delay {
+                time_ms: 120.0,
+                feedback: 0.35,
+                mix: 0.3
+            }
+        );
+        assert_eq!(EffectChain::parse("none").unwrap(), EffectChain::new());
+        assert!(InsertEffect::parse("flanger").is_err());
+        assert!(InsertEffect::parse("delay:abc").is_err());
+        assert!(InsertEffect::parse("reverb:1:2:3:4").is_err());
+
+        let mut config = FxConfig::default();
+        config.apply_spec("A=delay,chorus").unwrap();
+        assert_eq!(config.channels[0].effects().len(), 2);
+        assert!(config.master.is_empty());
+        assert!(config.apply_spec("d=reverb").is_err());
+    }
+}
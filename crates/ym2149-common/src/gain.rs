@@ -0,0 +1,121 @@
+//! Output gain stage shared by all players.
+//!
+//! Players embed an [`OutputGain`] and apply it at the end of
+//! `generate_samples_into`, so every frontend (CLI, WASM, Bevy) gets the same
+//! loudness control through [`ChiptunePlayerBase::set_gain`](crate::ChiptunePlayerBase::set_gain).
+
+/// Highest accepted gain factor (+12 dB).
+pub const MAX_OUTPUT_GAIN: f32 = 4.0;
+
+/// Level above which the soft clipper starts compressing.
+const SOFT_CLIP_KNEE: f32 = 0.8;
+
+/// Gain factor with an optional soft clipper.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutputGain {
+    gain: f32,
+    soft_clip: bool,
+}
+
+impl Default for OutputGain {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            soft_clip: false,
+        }
+    }
+}
+
+impl OutputGain {
+    /// Create a gain stage with the given factor and soft clipping disabled.
+    pub fn new(gain: f32) -> Self {
+        let mut stage = Self::default();
+        stage.set_gain(gain);
+        stage
+    }
+
+    /// Current gain factor.
+    pub fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    /// Set the gain factor, clamped to `0.0..=MAX_OUTPUT_GAIN`.
+    ///
+    /// Non-finite values are ignored.
+    pub fn set_gain(&mut self, gain: f32) {
+        if gain.is_finite() {
+            self.gain = gain.clamp(0.0, MAX_OUTPUT_GAIN);
+        }
+    }
+
+    /// Whether the soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        self.soft_clip
+    }
+
+    /// Enable or disable the soft clipper.
+    ///
+    /// When enabled, samples above a knee of 0.8 are compressed smoothly
+    /// towards ±1.0 instead of exceeding full scale.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        self.soft_clip = enabled;
+    }
+
+    /// Apply gain (and soft clipping, if enabled) to a buffer in place.
+    #[inline]
+    pub fn apply(&self, buffer: &mut [f32]) {
+        if self.gain != 1.0 {
+            for sample in buffer.iter_mut() {
+                *sample *= self.gain;
+            }
+        }
+        if self.soft_clip {
+            for sample in buffer.iter_mut() {
+                *sample = soft_clip_sample(*sample);
+            }
+        }
+    }
+}
+
+/// Linear below the knee, `tanh` shaped above it; never exceeds ±1.0.
+#[inline]
+fn soft_clip_sample(sample: f32) -> f32 {
+    let magnitude = sample.abs();
+    if magnitude <= SOFT_CLIP_KNEE {
+        return sample;
+    }
+    let headroom = 1.0 - SOFT_CLIP_KNEE;
+    let shaped = SOFT_CLIP_KNEE + headroom * ((magnitude - SOFT_CLIP_KNEE) / headroom).tanh();
+    shaped.copysign(sample)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gain_scales_and_clamps() {
+        let mut buffer = [0.25, -0.5];
+        OutputGain::new(2.0).apply(&mut buffer);
+        assert_eq!(buffer, [0.5, -1.0]);
+
+        assert_eq!(OutputGain::new(-1.0).gain(), 0.0);
+        assert_eq!(OutputGain::new(100.0).gain(), MAX_OUTPUT_GAIN);
+        assert_eq!(OutputGain::new(f32::NAN).gain(), 1.0);
+    }
+
+    #[test]
+    fn test_soft_clip_is_transparent_below_knee_and_bounded() {
+        let mut stage = OutputGain::new(4.0);
+        stage.set_soft_clip(true);
+
+        let mut buffer = [0.1, -0.2, 0.25, -0.3, 10.0];
+        stage.apply(&mut buffer);
+
+        assert!((buffer[0] - 0.4).abs() < 1e-6);
+        assert!((buffer[1] + 0.8).abs() < 1e-6);
+        assert!(buffer[2] > SOFT_CLIP_KNEE && buffer[2] < 1.0);
+        assert!(buffer[3] < -SOFT_CLIP_KNEE && buffer[3] > -1.0);
+        assert!(buffer[4] <= 1.0);
+    }
+}
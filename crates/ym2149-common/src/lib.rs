@@ -8,6 +8,10 @@
 //! - [`ChiptunePlayer`] - Unified player interface for any chiptune format
 //! - [`PlaybackMetadata`] - Metadata access (title, author, duration, etc.)
 //!
+//! [`detect_format`] sniffs song files so every frontend routes them to the
+//! same parser, and [`ChiptunePlayer::load_report`] describes the loaded song
+//! the same way for every format.
+//!
 //! # Example
 //!
 //! ```ignore
@@ -27,26 +31,85 @@
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "alloc-counter")]
+mod alloc_counter;
 mod backend;
 mod cached_player;
 pub mod channel_state;
+mod compare;
+mod delay;
+mod detect;
+pub mod export;
+#[cfg(feature = "fft")]
+mod fft;
+mod filter;
+#[cfg(feature = "fx")]
+mod fx;
+mod gain;
 mod metadata;
+mod mixer;
+mod null_backend;
+mod pan;
 mod player;
+mod rate;
+mod report;
+#[cfg(feature = "rodio")]
+mod rodio_source;
+mod shared;
+mod solo;
+mod tap;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+mod timeline;
 pub mod util;
 pub mod visualization;
 
+#[cfg(feature = "alloc-counter")]
+pub use alloc_counter::{CountingAllocator, allocations_per_second, thread_allocations};
 pub use backend::Ym2149Backend;
-pub use cached_player::{CacheablePlayer, CachedPlayer, DEFAULT_CACHE_SIZE, SampleCache};
-pub use channel_state::{ChannelState, ChannelStates, EnvelopeState, NoiseState};
+pub use cached_player::{
+    CacheablePlayer, CachedPlayer, DEFAULT_CACHE_SIZE, DEFAULT_PRERENDER_CHUNK, SampleCache,
+    TrackCache,
+};
+pub use channel_state::{
+    ChannelState, ChannelStates, ENVELOPE_WAVEFORM_LEN, EnvelopeState, NoiseState, PitchMotion,
+    PitchTracker, envelope_waveform,
+};
+pub use compare::{AB_SWITCH_SAMPLES, AbCompare, AbMode, AbSwitch};
+pub use delay::RegisterDelayLine;
+pub use detect::{
+    ChiptuneFormat, FormatGuess, LoadError, detect_format, detect_formats, load_with_detection,
+};
+#[cfg(feature = "fft")]
+pub use fft::{DEFAULT_FFT_SIZE, FftSpectrumAnalyzer, FftSpectrumConfig};
+pub use filter::{
+    CPC_LOWPASS_CUTOFF_HZ, DEFAULT_DC_CUTOFF_HZ, FilterChain, OutputModel, STF_LOWPASS_CUTOFF_HZ,
+};
+#[cfg(feature = "fx")]
+pub use fx::{EffectChain, FxConfig, FxRack, InsertEffect, MAX_DELAY_MS};
+pub use gain::{MAX_OUTPUT_GAIN, OutputGain};
 pub use metadata::{BasicMetadata, MetadataFields, PlaybackMetadata};
-pub use player::{ChiptunePlayer, ChiptunePlayerBase, PlaybackState};
+pub use mixer::{Mixer, TrackId};
+pub use null_backend::{CapturingBackend, NullBackend, RegisterWrite};
+pub use pan::ChannelPanner;
+pub use player::{
+    ChiptunePlayer, ChiptunePlayerBase, PlaybackState, RegisterChange, RegisterDelta,
+};
+pub use rate::{MAX_PLAYBACK_RATE, MIN_PLAYBACK_RATE, PlaybackRate};
+pub use report::{Compression, CompressionInfo, LoadReport};
+#[cfg(feature = "rodio")]
+pub use rodio_source::YmSource;
+pub use shared::{PlayerHandle, SharedPlayer};
+pub use solo::SoloState;
+pub use tap::{ChannelTap, MAX_TAP_CAPACITY};
+pub use timeline::{TimelineBuilder, TimelineSecond};
 pub use util::{
-    channel_frequencies, channel_frequencies_with_clock, channel_period, period_to_frequency,
-    period_to_frequency_with_clock,
+    channel_frequencies, channel_frequencies_with_clock, channel_period, frequency_to_period,
+    midi_note_frequency, period_to_frequency, period_to_frequency_with_clock,
 };
 pub use visualization::{
-    MAX_CHANNEL_COUNT, MAX_PSG_COUNT, SPECTRUM_BINS, SPECTRUM_DECAY, SpectrumAnalyzer,
-    WaveformSynthesizer, freq_to_bin,
+    Chord, ChordQuality, Harmony, MAX_CHANNEL_COUNT, MAX_PSG_COUNT, SPECTRUM_BINS, SPECTRUM_DECAY,
+    SpectrumAnalyzer, WaveformSynthesizer, freq_to_bin, interval_name,
 };
 
 // ============================================================================
@@ -0,0 +1,464 @@
+//! Multi-track mixing session.
+//!
+//! A [`Mixer`] owns several players and renders them into one buffer on a
+//! shared sample clock, with per-track gain, pan and mute. Gain changes can be
+//! ramped over a number of samples, which is all a crossfade needs.
+
+use crate::gain::{MAX_OUTPUT_GAIN, OutputGain};
+use crate::player::ChiptunePlayerBase;
+
+/// Handle to a track in a [`Mixer`]
+///
+/// Ids are never reused, so a handle to a removed track stays invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TrackId(u32);
+
+/// One player with its mix settings.
+struct Track {
+    id: TrackId,
+    player: Box<dyn ChiptunePlayerBase>,
+    gain: f32,
+    target_gain: f32,
+    ramp_step: f32,
+    ramp_remaining: usize,
+    pan: f32,
+    muted: bool,
+}
+
+impl Track {
+    /// Gain for the next sample, advancing an active ramp.
+    #[inline]
+    fn next_gain(&mut self) -> f32 {
+        if self.ramp_remaining > 0 {
+            self.ramp_remaining -= 1;
+            self.gain = if self.ramp_remaining == 0 {
+                self.target_gain
+            } else {
+                self.gain + self.ramp_step
+            };
+        }
+        self.gain
+    }
+
+    /// Skip `samples` of an active ramp without rendering.
+    fn advance_ramp(&mut self, samples: usize) {
+        let steps = samples.min(self.ramp_remaining);
+        self.ramp_remaining -= steps;
+        self.gain = if self.ramp_remaining == 0 {
+            self.target_gain
+        } else {
+            self.gain + self.ramp_step * steps as f32
+        };
+    }
+
+    /// Left/right factors for the pan position (balance law: unity at center).
+    #[inline]
+    fn pan_factors(&self) -> (f32, f32) {
+        ((1.0 - self.pan).min(1.0), (1.0 + self.pan).min(1.0))
+    }
+}
+
+/// Several players rendered together on a shared clock
+///
+/// Every render call pulls the same number of samples from each track, so
+/// tracks stay in lockstep. All players must run at the mixer's sample rate;
+/// [`add_track`](Self::add_track) rejects any that do not.
+///
+/// # Example
+///
+/// ```ignore
+/// let mut mixer = Mixer::new(44_100);
+/// let music = mixer.add_track(music_player).unwrap();
+/// let next = mixer.add_track(next_player).unwrap();
+/// mixer.set_track_gain(next, 0.0);
+/// mixer.play();
+///
+/// // Two-second crossfade
+/// mixer.ramp_track_gain(music, 0.0, 88_200);
+/// mixer.ramp_track_gain(next, 1.0, 88_200);
+///
+/// let mut stereo = vec![0.0; 2 * 882];
+/// mixer.render_stereo(&mut stereo);
+/// ```
+pub struct Mixer {
+    sample_rate: u32,
+    tracks: Vec<Track>,
+    next_id: u32,
+    master: OutputGain,
+    /// Samples rendered since creation or the last [`stop`](Self::stop).
+    position: u64,
+    scratch: Vec<f32>,
+}
+
+impl Mixer {
+    /// Create an empty mixer running at `sample_rate`.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            tracks: Vec::new(),
+            next_id: 0,
+            master: OutputGain::default(),
+            position: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    /// Output sample rate in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Add a player at unity gain, centered and unmuted.
+    ///
+    /// Returns `None` (dropping the player) if it renders at a different
+    /// sample rate than the mixer.
+    pub fn add_track(&mut self, player: impl ChiptunePlayerBase + 'static) -> Option<TrackId> {
+        self.add_boxed_track(Box::new(player))
+    }
+
+    /// Add an already boxed player (see [`add_track`](Self::add_track)).
+    pub fn add_boxed_track(&mut self, player: Box<dyn ChiptunePlayerBase>) -> Option<TrackId> {
+        if player.sample_rate() != self.sample_rate {
+            return None;
+        }
+        let id = TrackId(self.next_id);
+        self.next_id += 1;
+        self.tracks.push(Track {
+            id,
+            player,
+            gain: 1.0,
+            target_gain: 1.0,
+            ramp_step: 0.0,
+            ramp_remaining: 0,
+            pan: 0.0,
+            muted: false,
+        });
+        Some(id)
+    }
+
+    /// Remove a track and hand its player back.
+    pub fn remove_track(&mut self, id: TrackId) -> Option<Box<dyn ChiptunePlayerBase>> {
+        let index = self.tracks.iter().position(|track| track.id == id)?;
+        Some(self.tracks.remove(index).player)
+    }
+
+    /// Number of tracks.
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Ids of all tracks, in the order they were added.
+    pub fn track_ids(&self) -> impl Iterator<Item = TrackId> + '_ {
+        self.tracks.iter().map(|track| track.id)
+    }
+
+    /// Access a track's player.
+    pub fn player(&self, id: TrackId) -> Option<&dyn ChiptunePlayerBase> {
+        self.track(id).map(|track| track.player.as_ref())
+    }
+
+    /// Mutable access to a track's player.
+    pub fn player_mut(&mut self, id: TrackId) -> Option<&mut dyn ChiptunePlayerBase> {
+        match self.track_mut(id) {
+            Some(track) => Some(track.player.as_mut()),
+            None => None,
+        }
+    }
+
+    /// Set a track's gain immediately, clamped to `0.0..=MAX_OUTPUT_GAIN`.
+    ///
+    /// Cancels a running ramp. Returns `false` for an unknown track.
+    pub fn set_track_gain(&mut self, id: TrackId, gain: f32) -> bool {
+        self.ramp_track_gain(id, gain, 0)
+    }
+
+    /// Move a track's gain linearly to `gain` over the next `samples` samples.
+    ///
+    /// A ramp of zero samples applies the gain immediately. Non-finite gains
+    /// are ignored. Returns `false` for an unknown track.
+    pub fn ramp_track_gain(&mut self, id: TrackId, gain: f32, samples: usize) -> bool {
+        let Some(track) = self.track_mut(id) else {
+            return false;
+        };
+        if !gain.is_finite() {
+            return true;
+        }
+        track.target_gain = gain.clamp(0.0, MAX_OUTPUT_GAIN);
+        track.ramp_remaining = samples;
+        if samples == 0 {
+            track.gain = track.target_gain;
+            track.ramp_step = 0.0;
+        } else {
+            track.ramp_step = (track.target_gain - track.gain) / samples as f32;
+        }
+        true
+    }
+
+    /// A track's current gain (mid-ramp while a ramp is running).
+    pub fn track_gain(&self, id: TrackId) -> Option<f32> {
+        self.track(id).map(|track| track.gain)
+    }
+
+    /// Whether a gain ramp is still running on the track.
+    pub fn is_track_ramping(&self, id: TrackId) -> bool {
+        self.track(id).is_some_and(|track| track.ramp_remaining > 0)
+    }
+
+    /// Set a track's pan position, clamped to `-1.0` (left) ..= `1.0` (right).
+    ///
+    /// Panning attenuates the opposite side only, so a centered track plays
+    /// at full level on both sides. Non-finite values are ignored. Returns
+    /// `false` for an unknown track.
+    pub fn set_track_pan(&mut self, id: TrackId, pan: f32) -> bool {
+        let Some(track) = self.track_mut(id) else {
+            return false;
+        };
+        if pan.is_finite() {
+            track.pan = pan.clamp(-1.0, 1.0);
+        }
+        true
+    }
+
+    /// A track's pan position.
+    pub fn track_pan(&self, id: TrackId) -> Option<f32> {
+        self.track(id).map(|track| track.pan)
+    }
+
+    /// Mute or unmute a track.
+    ///
+    /// A muted track keeps playing so it stays in sync. Returns `false` for
+    /// an unknown track.
+    pub fn set_track_mute(&mut self, id: TrackId, muted: bool) -> bool {
+        let Some(track) = self.track_mut(id) else {
+            return false;
+        };
+        track.muted = muted;
+        true
+    }
+
+    /// Whether a track is muted (`false` for an unknown track).
+    pub fn is_track_muted(&self, id: TrackId) -> bool {
+        self.track(id).is_some_and(|track| track.muted)
+    }
+
+    /// Master gain applied after mixing.
+    pub fn master_gain(&self) -> f32 {
+        self.master.gain()
+    }
+
+    /// Set the master gain, clamped to `0.0..=MAX_OUTPUT_GAIN`.
+    pub fn set_master_gain(&mut self, gain: f32) {
+        self.master.set_gain(gain);
+    }
+
+    /// Whether the master soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        self.master.soft_clip()
+    }
+
+    /// Enable or disable the master soft clipper (see [`OutputGain`]).
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        self.master.set_soft_clip(enabled);
+    }
+
+    /// Start or resume every track.
+    pub fn play(&mut self) {
+        for track in &mut self.tracks {
+            track.player.play();
+        }
+    }
+
+    /// Pause every track.
+    pub fn pause(&mut self) {
+        for track in &mut self.tracks {
+            track.player.pause();
+        }
+    }
+
+    /// Stop every track and reset the shared clock.
+    pub fn stop(&mut self) {
+        for track in &mut self.tracks {
+            track.player.stop();
+        }
+        self.position = 0;
+    }
+
+    /// Whether any track is playing.
+    pub fn is_playing(&self) -> bool {
+        self.tracks.iter().any(|track| track.player.is_playing())
+    }
+
+    /// Samples (per channel) rendered on the shared clock.
+    pub fn position_samples(&self) -> u64 {
+        self.position
+    }
+
+    /// Shared clock position in seconds.
+    pub fn position_seconds(&self) -> f64 {
+        self.position as f64 / self.sample_rate.max(1) as f64
+    }
+
+    /// Render interleaved stereo (`[L, R, L, R, ...]`).
+    ///
+    /// An odd trailing sample is left silent.
+    pub fn render_stereo(&mut self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        let frames = buffer.len() / 2;
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.resize(frames, 0.0);
+
+        for track in &mut self.tracks {
+            track.player.generate_samples_into(&mut scratch);
+            if track.muted {
+                track.advance_ramp(frames);
+                continue;
+            }
+            let (left, right) = track.pan_factors();
+            for (frame, &sample) in buffer.chunks_exact_mut(2).zip(scratch.iter()) {
+                let sample = sample * track.next_gain();
+                frame[0] += sample * left;
+                frame[1] += sample * right;
+            }
+        }
+
+        self.scratch = scratch;
+        self.master.apply(buffer);
+        self.position += frames as u64;
+    }
+
+    /// Render mono, ignoring pan.
+    pub fn render_mono(&mut self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.resize(buffer.len(), 0.0);
+
+        for track in &mut self.tracks {
+            track.player.generate_samples_into(&mut scratch);
+            if track.muted {
+                track.advance_ramp(buffer.len());
+                continue;
+            }
+            for (out, &sample) in buffer.iter_mut().zip(scratch.iter()) {
+                *out += sample * track.next_gain();
+            }
+        }
+
+        self.scratch = scratch;
+        self.master.apply(buffer);
+        self.position += buffer.len() as u64;
+    }
+
+    fn track(&self, id: TrackId) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.id == id)
+    }
+
+    fn track_mut(&mut self, id: TrackId) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlaybackState;
+
+    /// Player producing a constant level while playing.
+    struct Constant {
+        level: f32,
+        sample_rate: u32,
+        state: PlaybackState,
+    }
+
+    impl Constant {
+        fn new(level: f32) -> Self {
+            Self {
+                level,
+                sample_rate: 44_100,
+                state: PlaybackState::Stopped,
+            }
+        }
+    }
+
+    impl ChiptunePlayerBase for Constant {
+        fn play(&mut self) {
+            self.state = PlaybackState::Playing;
+        }
+        fn pause(&mut self) {
+            self.state = PlaybackState::Paused;
+        }
+        fn stop(&mut self) {
+            self.state = PlaybackState::Stopped;
+        }
+        fn state(&self) -> PlaybackState {
+            self.state
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            let level = if self.is_playing() { self.level } else { 0.0 };
+            buffer.fill(level);
+        }
+        fn sample_rate(&self) -> u32 {
+            self.sample_rate
+        }
+    }
+
+    #[test]
+    fn test_mix_applies_gain_pan_and_mute() {
+        let mut mixer = Mixer::new(44_100);
+        let a = mixer.add_track(Constant::new(0.25)).unwrap();
+        let b = mixer.add_track(Constant::new(0.5)).unwrap();
+        mixer.play();
+
+        let mut stereo = [0.0; 4];
+        mixer.render_stereo(&mut stereo);
+        assert_eq!(stereo, [0.75; 4]);
+
+        mixer.set_track_pan(a, -1.0);
+        mixer.set_track_gain(b, 0.5);
+        mixer.render_stereo(&mut stereo);
+        assert_eq!(stereo, [0.5, 0.25, 0.5, 0.25]);
+
+        mixer.set_track_mute(b, true);
+        let mut mono = [0.0; 3];
+        mixer.render_mono(&mut mono);
+        assert_eq!(mono, [0.25; 3]);
+        assert_eq!(mixer.position_samples(), 7);
+    }
+
+    #[test]
+    fn test_gain_ramp_reaches_target() {
+        let mut mixer = Mixer::new(44_100);
+        let id = mixer.add_track(Constant::new(1.0)).unwrap();
+        mixer.play();
+        mixer.ramp_track_gain(id, 0.0, 4);
+
+        let mut mono = [0.0; 6];
+        mixer.render_mono(&mut mono);
+        assert_eq!(mono, [0.75, 0.5, 0.25, 0.0, 0.0, 0.0]);
+        assert!(!mixer.is_track_ramping(id));
+
+        mixer.set_track_mute(id, true);
+        mixer.ramp_track_gain(id, 1.0, 4);
+        mixer.render_mono(&mut mono);
+        assert_eq!(mixer.track_gain(id), Some(1.0));
+    }
+
+    #[test]
+    fn test_tracks_are_managed_by_id() {
+        let mut mixer = Mixer::new(44_100);
+        let a = mixer.add_track(Constant::new(1.0)).unwrap();
+        let mut other_rate = Constant::new(1.0);
+        other_rate.sample_rate = 48_000;
+        assert!(mixer.add_track(other_rate).is_none());
+
+        assert!(mixer.remove_track(a).is_some());
+        assert!(!mixer.set_track_gain(a, 0.5));
+        let b = mixer.add_track(Constant::new(1.0)).unwrap();
+        assert_ne!(a, b);
+        assert_eq!(mixer.track_ids().collect::<Vec<_>>(), [b]);
+
+        mixer.play();
+        assert!(mixer.is_playing());
+        mixer.stop();
+        assert_eq!(mixer.player(b).unwrap().state(), PlaybackState::Stopped);
+    }
+}
@@ -0,0 +1,354 @@
+//! Silent backends for tests and analysis tools.
+//!
+//! [`NullBackend`] keeps the register file and counts writes but renders
+//! silence without running any generators, so a player driven by it runs as
+//! fast as its sequencer allows. [`CapturingBackend`] also records every write
+//! with the sample it happened on, for tools that inspect what a replayer
+//! sends to the chip (register logs, timing checks, conversions).
+//!
+//! ```ignore
+//! use ym2149_common::CapturingBackend;
+//! use ym2149_ym_replayer::YmPlayerGeneric;
+//!
+//! let mut player = YmPlayerGeneric::<CapturingBackend>::new();
+//! player.load_data(&data)?;
+//! player.play()?;
+//! player.generate_samples(44_100);
+//! for write in player.backend().writes() {
+//!     println!("{:>8} R{:<2} = {:02X}", write.sample, write.addr, write.value);
+//! }
+//! ```
+
+use crate::Ym2149Backend;
+
+/// Master clock used by [`Ym2149Backend::new`] (Atari ST).
+const DEFAULT_MASTER_CLOCK: u32 = 2_000_000;
+
+/// Sample rate used by [`Ym2149Backend::new`].
+const DEFAULT_SAMPLE_RATE: u32 = 44_100;
+
+/// Backend that counts register writes and produces silence.
+#[derive(Debug, Clone)]
+pub struct NullBackend {
+    registers: [u8; 16],
+    write_counts: [u64; 16],
+    samples: u64,
+    muted: [bool; 3],
+    master_clock: u32,
+    sample_rate: u32,
+}
+
+impl Default for NullBackend {
+    fn default() -> Self {
+        <Self as Ym2149Backend>::new()
+    }
+}
+
+impl NullBackend {
+    /// Total number of register writes (each register of
+    /// [`load_registers`](Ym2149Backend::load_registers) counts once).
+    pub fn write_count(&self) -> u64 {
+        self.write_counts.iter().sum()
+    }
+
+    /// Number of writes to one register (0 for invalid addresses).
+    pub fn register_write_count(&self, addr: u8) -> u64 {
+        self.write_counts.get(addr as usize).copied().unwrap_or(0)
+    }
+
+    /// Write counts of R0-R15.
+    pub fn write_counts(&self) -> &[u64; 16] {
+        &self.write_counts
+    }
+
+    /// Number of samples clocked so far.
+    pub fn samples_clocked(&self) -> u64 {
+        self.samples
+    }
+
+    /// Master clock the backend was created with, in Hz.
+    pub fn master_clock(&self) -> u32 {
+        self.master_clock
+    }
+
+    /// Sample rate the backend was created with, in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Reset the write counts and the sample counter, keeping the registers.
+    pub fn clear_counts(&mut self) {
+        self.write_counts = [0; 16];
+        self.samples = 0;
+    }
+}
+
+impl Ym2149Backend for NullBackend {
+    fn new() -> Self {
+        Self::with_clocks(DEFAULT_MASTER_CLOCK, DEFAULT_SAMPLE_RATE)
+    }
+
+    fn with_clocks(master_clock: u32, sample_rate: u32) -> Self {
+        Self {
+            registers: [0; 16],
+            write_counts: [0; 16],
+            samples: 0,
+            muted: [false; 3],
+            master_clock,
+            sample_rate,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.registers = [0; 16];
+    }
+
+    fn write_register(&mut self, addr: u8, value: u8) {
+        if let Some(register) = self.registers.get_mut(addr as usize) {
+            *register = value;
+            self.write_counts[addr as usize] += 1;
+        }
+    }
+
+    fn read_register(&self, addr: u8) -> u8 {
+        self.registers.get(addr as usize).copied().unwrap_or(0)
+    }
+
+    fn load_registers(&mut self, regs: &[u8; 16]) {
+        self.registers = *regs;
+        for count in &mut self.write_counts {
+            *count += 1;
+        }
+    }
+
+    fn dump_registers(&self) -> [u8; 16] {
+        self.registers
+    }
+
+    fn clock(&mut self) {
+        self.samples += 1;
+    }
+
+    fn get_sample(&self) -> f32 {
+        0.0
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        self.samples += buffer.len() as u64;
+    }
+
+    fn generate_samples_with_channels(
+        &mut self,
+        buffer: &mut [f32],
+        channel_outputs: &mut [[f32; 3]],
+    ) {
+        channel_outputs.fill([0.0; 3]);
+        self.generate_samples_into(buffer);
+    }
+
+    fn get_channel_outputs(&self) -> (f32, f32, f32) {
+        (0.0, 0.0, 0.0)
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        if let Some(muted) = self.muted.get_mut(channel) {
+            *muted = mute;
+        }
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.muted.get(channel).copied().unwrap_or(false)
+    }
+
+    fn set_color_filter(&mut self, _enabled: bool) {}
+}
+
+/// One register write seen by a [`CapturingBackend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterWrite {
+    /// Samples clocked before the write
+    pub sample: u64,
+    /// Register address (0x00-0x0F)
+    pub addr: u8,
+    /// Written value
+    pub value: u8,
+}
+
+impl RegisterWrite {
+    /// Time of the write in seconds at `sample_rate`.
+    pub fn seconds(&self, sample_rate: u32) -> f64 {
+        self.sample as f64 / f64::from(sample_rate.max(1))
+    }
+}
+
+/// Silent backend that records every register write with its timestamp.
+///
+/// Writes through [`load_registers`](Ym2149Backend::load_registers) are
+/// recorded as 16 writes in register order. The log grows without bound;
+/// use [`take_writes`](Self::take_writes) to drain it on long runs.
+#[derive(Debug, Clone, Default)]
+pub struct CapturingBackend {
+    inner: NullBackend,
+    writes: Vec<RegisterWrite>,
+}
+
+impl CapturingBackend {
+    /// Writes recorded so far, oldest first.
+    pub fn writes(&self) -> &[RegisterWrite] {
+        &self.writes
+    }
+
+    /// Remove and return the recorded writes.
+    pub fn take_writes(&mut self) -> Vec<RegisterWrite> {
+        std::mem::take(&mut self.writes)
+    }
+
+    /// Counters of the underlying [`NullBackend`].
+    pub fn counts(&self) -> &NullBackend {
+        &self.inner
+    }
+
+    /// Number of samples clocked so far.
+    pub fn samples_clocked(&self) -> u64 {
+        self.inner.samples_clocked()
+    }
+
+    /// Sample rate the backend was created with, in Hz.
+    pub fn sample_rate(&self) -> u32 {
+        self.inner.sample_rate()
+    }
+
+    fn record(&mut self, addr: u8, value: u8) {
+        self.writes.push(RegisterWrite {
+            sample: self.inner.samples_clocked(),
+            addr,
+            value,
+        });
+    }
+}
+
+impl Ym2149Backend for CapturingBackend {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_clocks(master_clock: u32, sample_rate: u32) -> Self {
+        Self {
+            inner: NullBackend::with_clocks(master_clock, sample_rate),
+            writes: Vec::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+    }
+
+    fn write_register(&mut self, addr: u8, value: u8) {
+        if addr < 16 {
+            self.record(addr, value);
+        }
+        self.inner.write_register(addr, value);
+    }
+
+    fn read_register(&self, addr: u8) -> u8 {
+        self.inner.read_register(addr)
+    }
+
+    fn load_registers(&mut self, regs: &[u8; 16]) {
+        for (addr, &value) in regs.iter().enumerate() {
+            self.record(addr as u8, value);
+        }
+        self.inner.load_registers(regs);
+    }
+
+    fn dump_registers(&self) -> [u8; 16] {
+        self.inner.dump_registers()
+    }
+
+    fn clock(&mut self) {
+        self.inner.clock();
+    }
+
+    fn get_sample(&self) -> f32 {
+        0.0
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        self.inner.generate_samples_into(buffer);
+    }
+
+    fn generate_samples_with_channels(
+        &mut self,
+        buffer: &mut [f32],
+        channel_outputs: &mut [[f32; 3]],
+    ) {
+        self.inner
+            .generate_samples_with_channels(buffer, channel_outputs);
+    }
+
+    fn get_channel_outputs(&self) -> (f32, f32, f32) {
+        (0.0, 0.0, 0.0)
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        self.inner.set_channel_mute(channel, mute);
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.inner.is_channel_muted(channel)
+    }
+
+    fn set_color_filter(&mut self, _enabled: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_null_backend_counts_writes() {
+        let mut chip = NullBackend::new();
+        chip.write_register(0x08, 0x0F);
+        chip.write_register(0x08, 0x0C);
+        chip.write_register(0x10, 0xFF);
+        chip.load_registers(&[1; 16]);
+        assert_eq!(chip.register_write_count(0x08), 3);
+        assert_eq!(chip.write_count(), 18);
+        assert_eq!(chip.read_register(0x08), 1);
+
+        let mut buffer = [1.0; 64];
+        chip.generate_samples_into(&mut buffer);
+        assert_eq!(buffer, [0.0; 64]);
+        assert_eq!(chip.samples_clocked(), 64);
+    }
+
+    #[test]
+    fn test_capturing_backend_timestamps_writes() {
+        let mut chip = CapturingBackend::with_clocks(2_000_000, 50);
+        chip.write_register(0x07, 0x3E);
+        chip.generate_samples_into(&mut [0.0; 25]);
+        chip.write_register(0x08, 0x0F);
+        chip.write_register(0x20, 0x01);
+
+        let writes = chip.take_writes();
+        assert_eq!(
+            writes,
+            [
+                RegisterWrite {
+                    sample: 0,
+                    addr: 0x07,
+                    value: 0x3E,
+                },
+                RegisterWrite {
+                    sample: 25,
+                    addr: 0x08,
+                    value: 0x0F,
+                },
+            ]
+        );
+        assert_eq!(writes[1].seconds(chip.sample_rate()), 0.5);
+        assert!(chip.writes().is_empty());
+        assert_eq!(chip.counts().write_count(), 2);
+    }
+}
@@ -0,0 +1,112 @@
+//! Per-channel stereo panning of a mono mix.
+//!
+//! PSG formats render one mono mix through the chip's output filter and gain
+//! stage. [`ChannelPanner`] turns that mix into stereo without rendering each
+//! channel separately: every sample is split by how loud each channel is at
+//! that moment (from the per-channel outputs the players already expose for
+//! oscilloscopes) and each share is placed with its channel's pan. A
+//! centered panner reproduces the mono mix on both sides.
+
+/// Stereo placement of each channel of a song.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelPanner {
+    /// Pan per channel, -1.0 (left) to 1.0 (right).
+    pans: Vec<f32>,
+}
+
+impl ChannelPanner {
+    /// Create a panner with every channel centered.
+    pub fn new(channel_count: usize) -> Self {
+        Self {
+            pans: vec![0.0; channel_count],
+        }
+    }
+
+    /// Number of channels.
+    pub fn channel_count(&self) -> usize {
+        self.pans.len()
+    }
+
+    /// Set the pan of a channel (-1.0 = left, 0.0 = center, 1.0 = right).
+    ///
+    /// Returns false if `channel` is out of range or `pan` is NaN.
+    pub fn set_pan(&mut self, channel: usize, pan: f32) -> bool {
+        match self.pans.get_mut(channel) {
+            Some(slot) if !pan.is_nan() => {
+                *slot = pan.clamp(-1.0, 1.0);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pan of a channel (0.0 for channels out of range).
+    pub fn pan(&self, channel: usize) -> f32 {
+        self.pans.get(channel).copied().unwrap_or(0.0)
+    }
+
+    /// Whether every channel is centered, so stereo equals the mono mix.
+    pub fn is_centered(&self) -> bool {
+        self.pans.iter().all(|&pan| pan == 0.0)
+    }
+
+    /// Split `mono` into interleaved stereo `out` (`2 * mono.len()` samples).
+    ///
+    /// `channels` holds `channel_count()` interleaved channel outputs per
+    /// sample, as rendered by `generate_samples_with_channels_into`. Each
+    /// side uses a balance law: a channel panned fully left keeps its full
+    /// level on the left and is silent on the right.
+    pub fn process(&self, mono: &[f32], channels: &[f32], out: &mut [f32]) {
+        let count = self.pans.len().max(1);
+        for ((sample, outputs), frame) in mono
+            .iter()
+            .zip(channels.chunks_exact(count))
+            .zip(out.chunks_exact_mut(2))
+        {
+            let (mut left, mut right, mut total) = (0.0, 0.0, 0.0);
+            for (&output, &pan) in outputs.iter().zip(&self.pans) {
+                let weight = output.abs();
+                left += weight * (1.0 - pan).min(1.0);
+                right += weight * (1.0 + pan).min(1.0);
+                total += weight;
+            }
+            if total > 0.0 {
+                frame[0] = sample * left / total;
+                frame[1] = sample * right / total;
+            } else {
+                frame[0] = *sample;
+                frame[1] = *sample;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_centered_panner_duplicates_mono() {
+        let panner = ChannelPanner::new(3);
+        assert!(panner.is_centered());
+        let mut out = [0.0; 4];
+        panner.process(&[0.5, -0.25], &[1.0, 0.5, 0.0, 0.0, 0.0, 0.0], &mut out);
+        assert_eq!(out, [0.5, 0.5, -0.25, -0.25]);
+    }
+
+    #[test]
+    fn test_pan_splits_by_channel_level() {
+        let mut panner = ChannelPanner::new(3);
+        assert!(panner.set_pan(0, -1.0));
+        assert!(panner.set_pan(1, 2.0));
+        assert!(!panner.set_pan(3, 0.0));
+        assert!(!panner.set_pan(2, f32::NAN));
+        assert_eq!(panner.pan(1), 1.0);
+
+        let mut out = [0.0; 6];
+        // Only A sounding, then only B, then A and B equally loud
+        let channels = [1.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.5, 0.5, 0.0];
+        panner.process(&[0.8, 0.8, 0.8], &channels, &mut out);
+        assert_eq!(out, [0.8, 0.0, 0.0, 0.8, 0.4, 0.4]);
+    }
+}
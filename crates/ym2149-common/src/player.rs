@@ -10,7 +10,7 @@
 //! Use `ChiptunePlayerBase` when you need trait objects (`Box<dyn ChiptunePlayerBase>`).
 //! Use `ChiptunePlayer` when you need access to the specific metadata type.
 
-use crate::PlaybackMetadata;
+use crate::{LoadReport, OutputModel, PlaybackMetadata};
 
 /// Playback state for chiptune players.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -24,6 +24,63 @@ pub enum PlaybackState {
     Paused,
 }
 
+/// A register that differs between the two sides of a [`RegisterDelta`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterChange {
+    /// PSG index (0 for single-chip players).
+    pub psg: usize,
+    /// Register number (0-15).
+    pub register: usize,
+    /// Value before the step.
+    pub old: u8,
+    /// Value after the step.
+    pub new: u8,
+}
+
+/// PSG register state before and after a frame step.
+///
+/// Returned by [`ChiptunePlayerBase::step_frames`]. Holds one `[u8; 16]` dump
+/// per PSG on each side. Since it compares dumps, rewriting a register with
+/// the same value (e.g. retriggering the envelope through R13) does not show
+/// up as a change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisterDelta {
+    /// Registers of every PSG before the step.
+    pub before: Vec<[u8; 16]>,
+    /// Registers of every PSG after the step.
+    pub after: Vec<[u8; 16]>,
+}
+
+impl RegisterDelta {
+    /// Create a delta from register dumps taken before and after a step.
+    pub fn new(before: Vec<[u8; 16]>, after: Vec<[u8; 16]>) -> Self {
+        Self { before, after }
+    }
+
+    /// Iterate over the registers whose value changed, in PSG and register order.
+    pub fn changes(&self) -> impl Iterator<Item = RegisterChange> + '_ {
+        self.before
+            .iter()
+            .zip(&self.after)
+            .enumerate()
+            .flat_map(|(psg, (before, after))| {
+                (0..16).filter_map(move |register| {
+                    (before[register] != after[register]).then_some(RegisterChange {
+                        psg,
+                        register,
+                        old: before[register],
+                        new: after[register],
+                    })
+                })
+            })
+    }
+
+    /// Whether no register changed.
+    pub fn is_empty(&self) -> bool {
+        self.changes().next().is_none()
+    }
+}
+
 /// Object-safe base trait for chiptune players.
 ///
 /// This trait provides all playback functionality without the associated
@@ -83,7 +140,73 @@ pub trait ChiptunePlayerBase: Send {
         44100
     }
 
-    /// Mute or unmute a specific channel (0-2).
+    /// Set the output gain applied by [`generate_samples_into`](Self::generate_samples_into).
+    ///
+    /// `1.0` is unity; values are clamped to `0.0..=`[`MAX_OUTPUT_GAIN`](crate::MAX_OUTPUT_GAIN).
+    /// Default implementation does nothing. Players back this with an
+    /// [`OutputGain`](crate::OutputGain).
+    fn set_gain(&mut self, _gain: f32) {}
+
+    /// Get the current output gain.
+    ///
+    /// Default returns 1.0.
+    fn gain(&self) -> f32 {
+        1.0
+    }
+
+    /// Enable or disable the soft clipper that runs after the gain stage.
+    ///
+    /// Default implementation does nothing.
+    fn set_soft_clip(&mut self, _enabled: bool) {}
+
+    /// Check whether the soft clipper is enabled.
+    ///
+    /// Default returns false.
+    fn soft_clip(&self) -> bool {
+        false
+    }
+
+    /// Set the playback rate (`1.0` = original speed).
+    ///
+    /// Speed and pitch change together (`2.0` plays twice as fast, one octave
+    /// up); values are clamped to
+    /// [`MIN_PLAYBACK_RATE`](crate::MIN_PLAYBACK_RATE)`..=`[`MAX_PLAYBACK_RATE`](crate::MAX_PLAYBACK_RATE).
+    /// Default implementation does nothing. Players back this with a
+    /// [`PlaybackRate`](crate::PlaybackRate).
+    fn set_playback_rate(&mut self, _rate: f32) {}
+
+    /// Get the current playback rate.
+    ///
+    /// Default returns 1.0.
+    fn playback_rate(&self) -> f32 {
+        1.0
+    }
+
+    /// Apply an analog output stage preset to every PSG of the player.
+    ///
+    /// See [`OutputModel`] for the available models.
+    /// Default implementation does nothing.
+    fn set_output_model(&mut self, _model: OutputModel) {}
+
+    /// Enable (`Some(capacity)`) or disable (`None`) per-channel output taps
+    /// on every PSG of the player.
+    ///
+    /// See [`ChannelTap`](crate::ChannelTap). Default implementation does nothing.
+    fn set_channel_tap(&mut self, _capacity: Option<usize>) {}
+
+    /// Copy the most recent tapped samples of a channel into `out`, oldest first.
+    ///
+    /// Uses the same global channel indices as
+    /// [`set_channel_mute`](Self::set_channel_mute) and returns the number of
+    /// samples written. Default implementation returns 0 (no tap available).
+    fn read_channel_tap(&self, _channel: usize, _out: &mut [f32]) -> usize {
+        0
+    }
+
+    /// Mute or unmute a specific channel.
+    ///
+    /// Channel indices are global across chips: 0-2 are PSG 0 (A/B/C),
+    /// 3-5 are PSG 1, and so on up to [`channel_count`](Self::channel_count).
     ///
     /// Default implementation does nothing. Override if the player
     /// supports channel muting.
@@ -96,6 +219,42 @@ pub trait ChiptunePlayerBase: Send {
         false
     }
 
+    /// Advance exactly `frames` player frames while not playing.
+    ///
+    /// A frame is one replay tick (the VBL for most songs). The player renders
+    /// the frames internally without producing output and stops right after
+    /// the last frame's registers were written, so
+    /// [`RegisterDelta::after`] shows that frame. Stepping starts where
+    /// [`play`](Self::play) would and leaves the player paused at the new
+    /// position, so `play` resumes from there.
+    ///
+    /// Returns `None` while playing. Default implementation returns `None`
+    /// (stepping not supported).
+    fn step_frames(&mut self, _frames: usize) -> Option<RegisterDelta> {
+        None
+    }
+
+    /// Advance exactly one frame while not playing.
+    ///
+    /// See [`step_frames`](Self::step_frames).
+    fn step_frame(&mut self) -> Option<RegisterDelta> {
+        self.step_frames(1)
+    }
+
+    /// Run `frames` frames of player logic without rendering audio.
+    ///
+    /// Register updates and replay code (Z80/68000) run as usual, but the
+    /// chip is not clocked per sample, which makes this much faster than
+    /// rendering and discarding output. Used for seeking, duration scans and
+    /// analysis tools. Works in any state; a song that ends on the way stops
+    /// like it would during playback.
+    ///
+    /// Returns the number of frames advanced, which is less than `frames` if
+    /// the song ended. Default implementation returns 0 (not supported).
+    fn advance_frames(&mut self, _frames: usize) -> usize {
+        0
+    }
+
     /// Get playback position as a percentage (0.0 to 1.0).
     ///
     /// Default returns 0.0. Override if position tracking is available.
@@ -206,4 +365,46 @@ pub trait ChiptunePlayer: ChiptunePlayerBase {
 
     /// Get song metadata.
     fn metadata(&self) -> &Self::Metadata;
+
+    /// Format-independent summary of the loaded song.
+    ///
+    /// The default is [`LoadReport::from_player`]; players override it to
+    /// fill in the format family and compression info.
+    fn load_report(&self) -> LoadReport {
+        LoadReport::from_player(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_delta_lists_changes_per_psg() {
+        let mut after = vec![[0u8; 16]; 2];
+        after[0][7] = 0x38;
+        after[1][13] = 0x0E;
+        let delta = RegisterDelta::new(vec![[0u8; 16]; 2], after);
+
+        let changes: Vec<_> = delta.changes().collect();
+        assert_eq!(
+            changes,
+            [
+                RegisterChange {
+                    psg: 0,
+                    register: 7,
+                    old: 0,
+                    new: 0x38
+                },
+                RegisterChange {
+                    psg: 1,
+                    register: 13,
+                    old: 0,
+                    new: 0x0E
+                },
+            ]
+        );
+        assert!(!delta.is_empty());
+        assert!(RegisterDelta::new(vec![[1; 16]], vec![[1; 16]]).is_empty());
+    }
 }
@@ -0,0 +1,192 @@
+//! Playback rate stage shared by all players.
+//!
+//! Players embed a [`PlaybackRate`] and route sample generation through it,
+//! so every frontend (CLI, WASM, Bevy) gets the same speed control through
+//! [`ChiptunePlayerBase::set_playback_rate`](crate::ChiptunePlayerBase::set_playback_rate).
+//!
+//! The rate is applied by resampling the player's native output: the song
+//! runs `rate` times faster and its pitch shifts accordingly, exactly like
+//! changing the speed of a tape. This works the same way for every format,
+//! including SNDH drivers whose timing is derived from emulated timers.
+
+/// Slowest accepted playback rate (quarter speed).
+pub const MIN_PLAYBACK_RATE: f32 = 0.25;
+
+/// Fastest accepted playback rate (4× speed).
+pub const MAX_PLAYBACK_RATE: f32 = 4.0;
+
+/// Phase that makes the next output the next source frame: two frames are
+/// pulled, and interpolation starts exactly at the first one.
+const RESTART_PHASE: f64 = 2.0;
+
+/// Linear-interpolating resampler driven by a playback rate.
+#[derive(Debug, Clone)]
+pub struct PlaybackRate {
+    rate: f32,
+    /// Position between `previous` and `current`, in source samples.
+    phase: f64,
+    previous: [f32; 2],
+    current: [f32; 2],
+    scratch: Vec<f32>,
+}
+
+impl Default for PlaybackRate {
+    fn default() -> Self {
+        Self {
+            rate: 1.0,
+            phase: RESTART_PHASE,
+            previous: [0.0; 2],
+            current: [0.0; 2],
+            scratch: Vec::new(),
+        }
+    }
+}
+
+impl PlaybackRate {
+    /// Create a stage with the given rate.
+    pub fn new(rate: f32) -> Self {
+        let mut stage = Self::default();
+        stage.set_rate(rate);
+        stage
+    }
+
+    /// Current playback rate (`1.0` = original speed).
+    pub fn rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Set the playback rate, clamped to `MIN_PLAYBACK_RATE..=MAX_PLAYBACK_RATE`.
+    ///
+    /// Non-finite values are ignored.
+    pub fn set_rate(&mut self, rate: f32) {
+        if rate.is_finite() {
+            self.rate = rate.clamp(MIN_PLAYBACK_RATE, MAX_PLAYBACK_RATE);
+        }
+    }
+
+    /// Whether the stage passes the native output through untouched.
+    pub fn is_native(&self) -> bool {
+        self.rate == 1.0
+    }
+
+    /// Fill a mono buffer, pulling native samples from `generate`.
+    ///
+    /// `generate` is called once with a buffer of however many source
+    /// samples the current rate needs (possibly none).
+    pub fn render_mono(&mut self, buffer: &mut [f32], generate: impl FnMut(&mut [f32])) {
+        self.render::<1>(buffer, generate);
+    }
+
+    /// Fill an interleaved stereo buffer (L, R, L, R, ...), pulling native
+    /// frames from `generate`.
+    ///
+    /// Like [`render_mono`](Self::render_mono), but `generate` receives an
+    /// interleaved stereo buffer.
+    pub fn render_stereo(&mut self, buffer: &mut [f32], generate: impl FnMut(&mut [f32])) {
+        self.render::<2>(buffer, generate);
+    }
+
+    fn render<const N: usize>(&mut self, buffer: &mut [f32], mut generate: impl FnMut(&mut [f32])) {
+        if self.is_native() {
+            generate(buffer);
+            // Resume seamlessly from the last native frame if the rate changes
+            if let Some(last) = buffer.rchunks_exact(N).next() {
+                self.current[..N].copy_from_slice(last);
+                self.phase = RESTART_PHASE;
+            }
+            return;
+        }
+
+        let rate = f64::from(self.rate);
+        let frames = buffer.len() / N;
+
+        // Count the source frames consumed with the same arithmetic as below
+        let mut phase = self.phase;
+        let mut needed = 0;
+        for _ in 0..frames {
+            while phase >= 1.0 {
+                phase -= 1.0;
+                needed += 1;
+            }
+            phase += rate;
+        }
+
+        self.scratch.resize(needed * N, 0.0);
+        generate(&mut self.scratch);
+
+        let mut source = self.scratch.chunks_exact(N);
+        for frame in buffer.chunks_exact_mut(N) {
+            while self.phase >= 1.0 {
+                self.previous = self.current;
+                if let Some(next) = source.next() {
+                    self.current[..N].copy_from_slice(next);
+                }
+                self.phase -= 1.0;
+            }
+            let t = self.phase as f32;
+            for (ch, out) in frame.iter_mut().enumerate() {
+                *out = self.previous[ch] + (self.current[ch] - self.previous[ch]) * t;
+            }
+            self.phase += rate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Source producing 0, 1, 2, ... so output values are source positions.
+    fn ramp(next: &mut f32) -> impl FnMut(&mut [f32]) + '_ {
+        move |buffer| {
+            for sample in buffer {
+                *sample = *next;
+                *next += 1.0;
+            }
+        }
+    }
+
+    #[test]
+    fn test_rate_clamps_and_ignores_nan() {
+        assert_eq!(PlaybackRate::new(0.0).rate(), MIN_PLAYBACK_RATE);
+        assert_eq!(PlaybackRate::new(100.0).rate(), MAX_PLAYBACK_RATE);
+        assert_eq!(PlaybackRate::new(f32::NAN).rate(), 1.0);
+    }
+
+    #[test]
+    fn test_native_rate_passes_through() {
+        let mut stage = PlaybackRate::default();
+        let mut next = 0.0;
+        let mut buffer = [0.0; 4];
+        stage.render_mono(&mut buffer, ramp(&mut next));
+        assert_eq!(buffer, [0.0, 1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn test_double_rate_consumes_twice_the_source() {
+        let mut stage = PlaybackRate::new(2.0);
+        let mut next = 0.0;
+        let mut buffer = [0.0; 100];
+        for _ in 0..10 {
+            stage.render_mono(&mut buffer, ramp(&mut next));
+        }
+        // Output k plays source sample 2k; the one after it is already pulled
+        assert_eq!(buffer[99], 1998.0);
+        assert_eq!(next, 2000.0);
+    }
+
+    #[test]
+    fn test_half_rate_interpolates_stereo() {
+        let mut stage = PlaybackRate::new(1.0);
+        let mut next = 0.0;
+        let mut buffer = [0.0; 4];
+        stage.render_stereo(&mut buffer, ramp(&mut next));
+
+        stage.set_rate(0.5);
+        let mut buffer = [0.0; 8];
+        stage.render_stereo(&mut buffer, ramp(&mut next));
+        // Last native frame was (2, 3); slowing down continues at (4, 5)
+        assert_eq!(buffer, [4.0, 5.0, 5.0, 6.0, 6.0, 7.0, 7.0, 8.0]);
+        assert_eq!(next, 10.0);
+    }
+}
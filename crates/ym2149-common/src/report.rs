@@ -0,0 +1,179 @@
+//! Format-independent summary of a loaded song.
+//!
+//! Every player returns a [`LoadReport`] from [`ChiptunePlayer::load_report`],
+//! so frontends can print file information without special-casing each
+//! format. Formats fill in what they know: frame counts are `None` for
+//! songs that are generated by code (SNDH, AY) and the compression info is
+//! only set when the file was packed.
+
+use std::fmt;
+
+use crate::{ChiptuneFormat, ChiptunePlayer, MetadataFields};
+
+/// Packing method of a song file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// LHA (LH5) archive, used by most YM files
+    Lha,
+    /// ICE! packer, used by many SNDH files
+    Ice,
+    /// ZIP archive (packaged AKS files, YM collections)
+    Zip,
+}
+
+impl Compression {
+    /// Short name ("LHA", "ICE!", "ZIP").
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Lha => "LHA",
+            Self::Ice => "ICE!",
+            Self::Zip => "ZIP",
+        }
+    }
+}
+
+/// How a packed song file was stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompressionInfo {
+    /// Packing method
+    pub method: Compression,
+    /// File size in bytes
+    pub packed_size: usize,
+    /// Size of the unpacked song data in bytes
+    pub unpacked_size: usize,
+}
+
+impl CompressionInfo {
+    /// Unpacked size divided by packed size (1.0 when either is unknown).
+    pub fn ratio(&self) -> f32 {
+        if self.packed_size == 0 || self.unpacked_size == 0 {
+            return 1.0;
+        }
+        self.unpacked_size as f32 / self.packed_size as f32
+    }
+}
+
+/// Summary of a loaded song, shared by all formats.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadReport {
+    /// Format family
+    pub family: ChiptuneFormat,
+    /// Exact format or variant name ("YM6", "SNDH", "AKS", ...)
+    pub format: String,
+    /// Number of frames of the current subsong, if the format stores them
+    pub frame_count: Option<usize>,
+    /// Player frames per second
+    pub frame_rate: u32,
+    /// Number of subsongs (1 for single-song formats)
+    pub subsongs: usize,
+    /// Number of PSG chips
+    pub chips: usize,
+    /// Duration of the current subsong in seconds, if known
+    pub duration_seconds: Option<f32>,
+    /// Packing of the file, `None` when it was stored unpacked
+    pub compression: Option<CompressionInfo>,
+}
+
+impl LoadReport {
+    /// Report built from the player's metadata and playback information.
+    ///
+    /// The family is [`ChiptuneFormat::Unknown`] and no compression is
+    /// recorded; players that know better override
+    /// [`ChiptunePlayer::load_report`] and adjust the result.
+    pub fn from_player<P: ChiptunePlayer + ?Sized>(player: &P) -> Self {
+        let metadata = player.metadata();
+        let duration = player.duration_seconds();
+        Self {
+            family: ChiptuneFormat::Unknown,
+            format: metadata.format().to_string(),
+            frame_count: metadata.frame_count(),
+            frame_rate: metadata.frame_rate(),
+            subsongs: player.subsong_count(),
+            chips: player.psg_count(),
+            duration_seconds: if duration > 0.0 {
+                Some(duration)
+            } else {
+                metadata.duration_seconds()
+            },
+            compression: None,
+        }
+    }
+
+    /// Set the format family.
+    pub fn with_family(mut self, family: ChiptuneFormat) -> Self {
+        self.family = family;
+        self
+    }
+
+    /// Set the compression info.
+    pub fn with_compression(mut self, compression: Option<CompressionInfo>) -> Self {
+        self.compression = compression;
+        self
+    }
+}
+
+impl fmt::Display for LoadReport {
+    /// One line such as `YM6, 1 PSG, 12287 frames at 50 Hz, 4:05, LHA 16384 -> 98304 bytes`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.format)?;
+        if self.subsongs > 1 {
+            write!(f, ", {} subsongs", self.subsongs)?;
+        }
+        let plural = if self.chips == 1 { "" } else { "s" };
+        write!(f, ", {} PSG{plural}", self.chips)?;
+        match self.frame_count {
+            Some(frames) => write!(f, ", {frames} frames at {} Hz", self.frame_rate)?,
+            None => write!(f, ", {} Hz", self.frame_rate)?,
+        }
+        if let Some(seconds) = self.duration_seconds {
+            let total = seconds.round() as u32;
+            write!(f, ", {}:{:02}", total / 60, total % 60)?;
+        }
+        if let Some(packing) = self.compression {
+            write!(
+                f,
+                ", {} {} -> {} bytes",
+                packing.method.name(),
+                packing.packed_size,
+                packing.unpacked_size
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_lists_known_fields() {
+        let mut report = LoadReport {
+            family: ChiptuneFormat::Ym,
+            format: "YM6".to_string(),
+            frame_count: Some(12287),
+            frame_rate: 50,
+            subsongs: 1,
+            chips: 1,
+            duration_seconds: Some(245.7),
+            compression: Some(CompressionInfo {
+                method: Compression::Lha,
+                packed_size: 16384,
+                unpacked_size: 98304,
+            }),
+        };
+        assert_eq!(
+            report.to_string(),
+            "YM6, 1 PSG, 12287 frames at 50 Hz, 4:06, LHA 16384 -> 98304 bytes"
+        );
+        assert_eq!(report.compression.unwrap().ratio(), 6.0);
+
+        report.format = "SNDH".to_string();
+        report.subsongs = 3;
+        report.chips = 2;
+        report.frame_count = None;
+        report.duration_seconds = None;
+        report.compression = None;
+        assert_eq!(report.to_string(), "SNDH, 3 subsongs, 2 PSGs, 50 Hz");
+    }
+}
@@ -0,0 +1,236 @@
+//! [`rodio::Source`] for any chiptune player.
+//!
+//! [`YmSource`] renders a player in blocks on rodio's mixer thread, so a
+//! desktop app can hand it to a `Sink` instead of running its own audio
+//! thread and ring buffer:
+//!
+//! ```ignore
+//! use rodio::{OutputStream, Sink};
+//! use ym2149_common::YmSource;
+//!
+//! let (_stream, output) = OutputStream::try_default()?;
+//! let sink = Sink::try_new(&output)?;
+//! let (player, _) = ym2149_ym_replayer::load_song(&data)?;
+//!
+//! let source = YmSource::new(player).until_end();
+//! let handle = source.handle();
+//! sink.append(source);
+//!
+//! handle.set_channel_mute(2, true);
+//! sink.sleep_until_end();
+//! ```
+//!
+//! By default the source plays until the player stops by itself, which for
+//! looping songs is never; [`until_end`](YmSource::until_end) and
+//! [`with_duration`](YmSource::with_duration) bound it. The player moves
+//! into the sink, so gain and mutes are changed through the
+//! [`PlayerHandle`] from [`handle`](YmSource::handle).
+
+use std::time::Duration;
+
+use crate::{ChiptunePlayerBase, PlaybackState, PlayerHandle, SharedPlayer};
+
+/// Samples rendered per block.
+const BLOCK_SAMPLES: usize = 1024;
+
+/// Mono [`rodio::Source`] that renders a chiptune player.
+pub struct YmSource<P: ChiptunePlayerBase + ?Sized = dyn ChiptunePlayerBase> {
+    player: SharedPlayer<P>,
+    buffer: Box<[f32]>,
+    pos: usize,
+    len: usize,
+    /// Samples left before the source ends (`None` = until the player stops)
+    remaining: Option<u64>,
+}
+
+impl<P: ChiptunePlayerBase> YmSource<P> {
+    /// Wrap a player, starting playback if it is stopped.
+    pub fn new(player: P) -> Self {
+        Self::from_box(Box::new(player))
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> YmSource<P> {
+    /// Wrap a boxed player (e.g. `Box<dyn ChiptunePlayerBase>`), starting
+    /// playback if it is stopped.
+    pub fn from_box(mut player: Box<P>) -> Self {
+        if player.state() == PlaybackState::Stopped {
+            player.play();
+        }
+        Self {
+            player: SharedPlayer::from_box(player),
+            buffer: vec![0.0; BLOCK_SAMPLES].into_boxed_slice(),
+            pos: 0,
+            len: 0,
+            remaining: None,
+        }
+    }
+
+    /// End the source after `duration` of audio.
+    pub fn with_duration(mut self, duration: Duration) -> Self {
+        let samples = duration.as_secs_f64() * f64::from(self.player.sample_rate());
+        self.remaining = Some(samples.round() as u64);
+        self
+    }
+
+    /// End the source after the song's length, if the player knows it.
+    ///
+    /// Songs without a known length keep playing until the player stops.
+    pub fn until_end(self) -> Self {
+        let seconds = self.player.duration_seconds() - self.player.elapsed_seconds();
+        if seconds > 0.0 {
+            self.with_duration(Duration::from_secs_f32(seconds))
+        } else {
+            self
+        }
+    }
+
+    /// Handle for changing gain and channel mutes while the sink plays.
+    pub fn handle(&self) -> PlayerHandle {
+        self.player.handle()
+    }
+
+    /// The wrapped player.
+    pub fn player(&self) -> &P {
+        &self.player
+    }
+
+    /// Unwrap the player.
+    pub fn into_inner(self) -> Box<P> {
+        self.player.into_inner()
+    }
+
+    /// Render the next block; false when the source has ended.
+    fn refill(&mut self) -> bool {
+        if self.remaining == Some(0) || self.player.state() == PlaybackState::Stopped {
+            return false;
+        }
+        let len = self.remaining.map_or(BLOCK_SAMPLES, |left| {
+            left.min(BLOCK_SAMPLES as u64) as usize
+        });
+        self.player.sync();
+        self.player.generate_samples_into(&mut self.buffer[..len]);
+        if let Some(left) = &mut self.remaining {
+            *left -= len as u64;
+        }
+        self.pos = 0;
+        self.len = len;
+        true
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> Iterator for YmSource<P> {
+    type Item = f32;
+
+    #[inline]
+    fn next(&mut self) -> Option<f32> {
+        if self.pos == self.len && !self.refill() {
+            return None;
+        }
+        let sample = self.buffer[self.pos];
+        self.pos += 1;
+        Some(sample)
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> rodio::Source for YmSource<P> {
+    fn current_frame_len(&self) -> Option<usize> {
+        // Channel count and sample rate never change
+        None
+    }
+
+    fn channels(&self) -> u16 {
+        1
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.player.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        let left = self.remaining? + (self.len - self.pos) as u64;
+        Some(Duration::from_secs_f64(
+            left as f64 / f64::from(self.player.sample_rate()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rodio::Source;
+
+    /// Plays a constant for a fixed number of samples, then stops
+    struct Countdown {
+        left: usize,
+        state: PlaybackState,
+        gain: f32,
+    }
+
+    impl ChiptunePlayerBase for Countdown {
+        fn play(&mut self) {
+            self.state = PlaybackState::Playing;
+        }
+        fn pause(&mut self) {
+            self.state = PlaybackState::Paused;
+        }
+        fn stop(&mut self) {
+            self.state = PlaybackState::Stopped;
+        }
+        fn state(&self) -> PlaybackState {
+            self.state
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            for sample in buffer {
+                if self.left > 0 && self.state == PlaybackState::Playing {
+                    self.left -= 1;
+                    *sample = 0.5 * self.gain;
+                } else {
+                    self.state = PlaybackState::Stopped;
+                    *sample = 0.0;
+                }
+            }
+        }
+        fn set_gain(&mut self, gain: f32) {
+            self.gain = gain;
+        }
+        fn duration_seconds(&self) -> f32 {
+            1.0
+        }
+    }
+
+    fn countdown(left: usize) -> Countdown {
+        Countdown {
+            left,
+            state: PlaybackState::Stopped,
+            gain: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_source_ends_when_player_stops() {
+        let source = YmSource::new(countdown(3000));
+        assert_eq!(source.player().state(), PlaybackState::Playing);
+        assert_eq!(source.total_duration(), None);
+        // The block in which the player stops is played out
+        assert_eq!(source.count(), 3 * BLOCK_SAMPLES);
+    }
+
+    #[test]
+    fn test_duration_bounds_source() {
+        let source = YmSource::new(countdown(usize::MAX)).with_duration(Duration::from_millis(100));
+        assert_eq!(source.total_duration(), Some(Duration::from_millis(100)));
+        assert_eq!(source.count(), 4410);
+
+        let boxed: Box<dyn ChiptunePlayerBase> = Box::new(countdown(usize::MAX));
+        let source = YmSource::from_box(boxed).until_end();
+        assert_eq!(source.count(), 44_100);
+    }
+
+    #[test]
+    fn test_handle_applies_gain() {
+        let mut source = YmSource::new(countdown(usize::MAX));
+        source.handle().set_gain(0.5);
+        assert_eq!(source.next(), Some(0.25));
+    }
+}
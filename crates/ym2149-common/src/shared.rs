@@ -0,0 +1,355 @@
+//! Lock-free parameter control for a player owned by the audio thread.
+//!
+//! Every player is `Send` ([`ChiptunePlayerBase`] requires it), so it can be
+//! moved onto an audio thread, but none of them are meant to be shared: the
+//! usual workaround, an `Arc<Mutex<player>>`, makes the audio callback wait
+//! whenever a UI thread touches the player. [`SharedPlayer`] avoids that for
+//! the parameters a UI changes most often.
+//!
+//! The audio thread owns the [`SharedPlayer`] and calls
+//! [`sync`](SharedPlayer::sync) before rendering each block. UI threads hold
+//! [`PlayerHandle`]s (`Send + Sync`, cheap to clone) that store the output
+//! gain and channel mutes in atomics; `sync` applies them when they changed
+//! and is a single atomic load otherwise.
+//!
+//! ```ignore
+//! use ym2149_common::SharedPlayer;
+//!
+//! let mut shared = SharedPlayer::new(player);
+//! let handle = shared.handle();
+//!
+//! std::thread::spawn(move || loop {
+//!     let mut buffer = [0.0f32; 1024];
+//!     shared.sync();
+//!     shared.generate_samples_into(&mut buffer);
+//!     // ... hand buffer to the audio device
+//! });
+//!
+//! handle.set_gain(0.5);
+//! handle.set_channel_mute(1, true);
+//! ```
+
+use crate::ChiptunePlayerBase;
+use crate::solo::{SoloState, apply_mute_mask, channel_mask, mute_mask};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// Parameters shared between a [`SharedPlayer`] and its handles.
+#[derive(Debug)]
+struct Controls {
+    /// Output gain as `f32` bits.
+    gain: AtomicU32,
+    /// Bit `n` set when global channel `n` is muted.
+    muted: AtomicU64,
+    /// Channels of the current player, published by the audio thread.
+    channel_count: AtomicUsize,
+    /// Bumped after every handle write so the audio thread can skip unchanged blocks.
+    version: AtomicU64,
+    /// Solo bookkeeping; only handles lock it, never the audio thread.
+    solo: Mutex<SoloState>,
+}
+
+impl Controls {
+    fn changed(&self) {
+        self.version.fetch_add(1, Ordering::Release);
+    }
+
+    fn solo(&self) -> MutexGuard<'_, SoloState> {
+        self.solo.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Thread-safe handle for changing a [`SharedPlayer`]'s volume and mutes.
+///
+/// Setters never block; the audio thread picks the new values up on its next
+/// [`SharedPlayer::sync`]. Getters return the requested state, which may be
+/// one block ahead of what is audible.
+#[derive(Debug, Clone)]
+pub struct PlayerHandle {
+    controls: Arc<Controls>,
+}
+
+impl PlayerHandle {
+    /// Set the output gain (see [`ChiptunePlayerBase::set_gain`]).
+    pub fn set_gain(&self, gain: f32) {
+        self.controls.gain.store(gain.to_bits(), Ordering::Relaxed);
+        self.controls.changed();
+    }
+
+    /// Requested output gain.
+    pub fn gain(&self) -> f32 {
+        f32::from_bits(self.controls.gain.load(Ordering::Relaxed))
+    }
+
+    /// Number of channels of the player, as last published by the audio thread.
+    pub fn channel_count(&self) -> usize {
+        self.controls.channel_count.load(Ordering::Relaxed)
+    }
+
+    /// Mute or unmute a global channel index; out-of-range channels are ignored.
+    pub fn set_channel_mute(&self, channel: usize, mute: bool) {
+        if channel >= self.channel_count() {
+            return;
+        }
+        let bit = 1u64 << channel;
+        if mute {
+            self.controls.muted.fetch_or(bit, Ordering::Relaxed);
+        } else {
+            self.controls.muted.fetch_and(!bit, Ordering::Relaxed);
+        }
+        self.controls.changed();
+    }
+
+    /// Whether a channel is (requested to be) muted.
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        channel < u64::BITS as usize
+            && self.controls.muted.load(Ordering::Relaxed) & (1u64 << channel) != 0
+    }
+
+    /// Flip a channel's mute flag.
+    pub fn toggle_channel_mute(&self, channel: usize) {
+        if channel >= self.channel_count() {
+            return;
+        }
+        self.controls
+            .muted
+            .fetch_xor(1u64 << channel, Ordering::Relaxed);
+        self.controls.changed();
+    }
+
+    /// Solo a channel, muting every other one (see [`SoloState::solo`]).
+    ///
+    /// Out-of-range channels are ignored.
+    pub fn set_channel_solo(&self, channel: usize) {
+        let mut solo = self.controls.solo();
+        let mutes = self.controls.muted.load(Ordering::Relaxed);
+        if let Some(mutes) = solo.solo(channel, self.channel_count(), mutes) {
+            self.controls.muted.store(mutes, Ordering::Relaxed);
+            self.controls.changed();
+        }
+    }
+
+    /// Leave solo mode and restore the mutes from before the solo.
+    pub fn clear_solo(&self) {
+        if let Some(mutes) = self.controls.solo().clear() {
+            let mutes = mutes & channel_mask(self.channel_count());
+            self.controls.muted.store(mutes, Ordering::Relaxed);
+            self.controls.changed();
+        }
+    }
+
+    /// The soloed channel, or `None` when solo mode is off.
+    pub fn solo_channel(&self) -> Option<usize> {
+        self.controls.solo().channel()
+    }
+}
+
+/// A player owned by the audio thread whose volume and mutes are controlled
+/// through [`PlayerHandle`]s.
+///
+/// Dereferences to the wrapped player for everything else. Gain and mutes set
+/// directly on the player are overwritten by the next handle change, so route
+/// them through a handle.
+pub struct SharedPlayer<P: ChiptunePlayerBase + ?Sized> {
+    controls: Arc<Controls>,
+    /// Last `version` applied to `player`.
+    applied: u64,
+    player: Box<P>,
+}
+
+impl<P: ChiptunePlayerBase> SharedPlayer<P> {
+    /// Wrap a player, taking its current gain and mutes as the initial state.
+    pub fn new(player: P) -> Self {
+        Self::from_box(Box::new(player))
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> SharedPlayer<P> {
+    /// Wrap a boxed (possibly unsized, e.g. `dyn ChiptunePlayerBase`) player.
+    pub fn from_box(player: Box<P>) -> Self {
+        let controls = Controls {
+            gain: AtomicU32::new(player.gain().to_bits()),
+            muted: AtomicU64::new(mute_mask(player.as_ref())),
+            channel_count: AtomicUsize::new(player.channel_count()),
+            version: AtomicU64::new(0),
+            solo: Mutex::new(SoloState::new()),
+        };
+        Self {
+            controls: Arc::new(controls),
+            applied: 0,
+            player,
+        }
+    }
+
+    /// Create another handle to this player.
+    pub fn handle(&self) -> PlayerHandle {
+        PlayerHandle {
+            controls: Arc::clone(&self.controls),
+        }
+    }
+
+    /// Apply handle changes made since the last call.
+    ///
+    /// Call on the audio thread before rendering each block.
+    pub fn sync(&mut self) {
+        let version = self.controls.version.load(Ordering::Acquire);
+        if version == self.applied {
+            return;
+        }
+        self.applied = version;
+        self.player
+            .set_gain(f32::from_bits(self.controls.gain.load(Ordering::Relaxed)));
+        apply_mute_mask(
+            self.player.as_mut(),
+            self.controls.muted.load(Ordering::Relaxed),
+        );
+    }
+
+    /// Swap in a new player and return the old one.
+    ///
+    /// The new player takes over the handles' gain; channel mutes and the
+    /// solo are cleared because its channel layout may differ.
+    pub fn replace(&mut self, player: Box<P>) -> Box<P> {
+        let previous = std::mem::replace(&mut self.player, player);
+        self.controls
+            .channel_count
+            .store(self.player.channel_count(), Ordering::Relaxed);
+        self.controls.solo().reset();
+        self.controls.muted.store(0, Ordering::Relaxed);
+        self.controls.changed();
+        self.sync();
+        previous
+    }
+
+    /// Unwrap the player; outstanding handles stop having any effect.
+    pub fn into_inner(self) -> Box<P> {
+        self.player
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> Deref for SharedPlayer<P> {
+    type Target = P;
+
+    fn deref(&self) -> &P {
+        &self.player
+    }
+}
+
+impl<P: ChiptunePlayerBase + ?Sized> DerefMut for SharedPlayer<P> {
+    fn deref_mut(&mut self) -> &mut P {
+        &mut self.player
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlaybackState;
+
+    #[derive(Default)]
+    struct TestPlayer {
+        gain: f32,
+        muted: [bool; 6],
+        gain_writes: usize,
+    }
+
+    impl ChiptunePlayerBase for TestPlayer {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Playing
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            buffer.fill(self.gain);
+        }
+        fn set_gain(&mut self, gain: f32) {
+            self.gain = gain;
+            self.gain_writes += 1;
+        }
+        fn gain(&self) -> f32 {
+            self.gain
+        }
+        fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+            self.muted[channel] = mute;
+        }
+        fn is_channel_muted(&self, channel: usize) -> bool {
+            self.muted[channel]
+        }
+        fn psg_count(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn handles_are_thread_safe() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        fn assert_send<T: Send + ?Sized>() {}
+        assert_send_sync::<PlayerHandle>();
+        assert_send::<SharedPlayer<dyn ChiptunePlayerBase>>();
+
+        let mut shared = SharedPlayer::new(TestPlayer {
+            gain: 1.0,
+            ..Default::default()
+        });
+        let handle = shared.handle();
+        std::thread::spawn(move || {
+            handle.set_gain(0.25);
+            handle.set_channel_mute(4, true);
+        })
+        .join()
+        .unwrap();
+
+        shared.sync();
+        let mut buffer = [0.0; 4];
+        shared.generate_samples_into(&mut buffer);
+        assert_eq!(buffer, [0.25; 4]);
+        assert!(shared.is_channel_muted(4));
+
+        // Nothing changed: sync leaves the player alone
+        let writes = shared.gain_writes;
+        shared.sync();
+        assert_eq!(shared.gain_writes, writes);
+    }
+
+    #[test]
+    fn solo_and_replace_follow_player_semantics() {
+        let mut shared = SharedPlayer::new(TestPlayer::default());
+        let handle = shared.handle();
+        assert_eq!(handle.channel_count(), 6);
+
+        handle.set_channel_mute(5, true);
+        handle.set_channel_solo(2);
+        handle.set_channel_mute(9, true);
+        shared.sync();
+        assert_eq!(shared.muted, [true, true, false, true, true, true]);
+        assert_eq!(handle.solo_channel(), Some(2));
+
+        // Switching the solo keeps the mutes from before the first solo
+        handle.set_channel_solo(0);
+        assert_eq!(handle.solo_channel(), Some(0));
+        handle.clear_solo();
+        shared.sync();
+        assert_eq!(shared.muted, [false, false, false, false, false, true]);
+        assert_eq!(handle.solo_channel(), None);
+
+        // Muting all but one channel by hand is not a solo
+        for channel in [0, 1, 3, 4] {
+            handle.set_channel_mute(channel, true);
+        }
+        assert_eq!(handle.solo_channel(), None);
+        handle.set_channel_solo(1);
+        handle.set_gain(0.5);
+
+        let previous = shared.replace(Box::new(TestPlayer {
+            muted: [true; 6],
+            ..Default::default()
+        }));
+        // The old player never saw the gain change made after its last sync
+        assert_eq!(previous.gain, 0.0);
+        assert_eq!(shared.gain, 0.5);
+        assert!(shared.muted.iter().all(|&muted| !muted));
+        assert_eq!(handle.solo_channel(), None);
+    }
+}
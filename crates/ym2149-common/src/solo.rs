@@ -0,0 +1,190 @@
+//! Solo-channel bookkeeping shared by every frontend.
+//!
+//! Soloing mutes every channel but one. Leaving solo mode must bring back
+//! the mutes the user had set before, not simply unmute everything, and the
+//! soloed channel cannot be told from the mute state alone (a user may mute
+//! all channels but one by hand). [`SoloState`] records both, so the owner of
+//! a player ([`PlayerHandle`](crate::PlayerHandle), the WASM player, a
+//! frontend holding a player directly) gets the same behavior everywhere.
+
+use crate::ChiptunePlayerBase;
+use crate::visualization::MAX_CHANNEL_COUNT;
+
+const _: () = assert!(MAX_CHANNEL_COUNT <= u64::BITS as usize);
+
+/// The soloed channel and the mutes to restore when the solo ends.
+///
+/// Mutes are passed around as masks with bit `n` set when global channel `n`
+/// is muted (see [`ChiptunePlayerBase::set_channel_mute`] for the channel
+/// numbering).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SoloState {
+    /// Channel currently soloed.
+    channel: Option<usize>,
+    /// Mute mask from before the first solo.
+    saved_mutes: u64,
+}
+
+impl SoloState {
+    /// Create a state with no channel soloed.
+    pub const fn new() -> Self {
+        Self {
+            channel: None,
+            saved_mutes: 0,
+        }
+    }
+
+    /// The soloed channel, or `None` when solo mode is off.
+    pub fn channel(&self) -> Option<usize> {
+        self.channel
+    }
+
+    /// Solo `channel` out of `channel_count`, given the current mute mask.
+    ///
+    /// The first solo saves `mutes`; switching the soloed channel keeps the
+    /// saved mask, so the user's own mutes survive. Returns the mask to
+    /// apply, or `None` (and changes nothing) if `channel` is out of range.
+    pub fn solo(&mut self, channel: usize, channel_count: usize, mutes: u64) -> Option<u64> {
+        let count = channel_count.min(MAX_CHANNEL_COUNT);
+        if channel >= count {
+            return None;
+        }
+        if self.channel.is_none() {
+            self.saved_mutes = mutes;
+        }
+        self.channel = Some(channel);
+        Some(channel_mask(count) & !(1u64 << channel))
+    }
+
+    /// Leave solo mode.
+    ///
+    /// Returns the mute mask from before the solo, or `None` if no channel
+    /// was soloed.
+    pub fn clear(&mut self) -> Option<u64> {
+        self.channel.take()?;
+        Some(std::mem::take(&mut self.saved_mutes))
+    }
+
+    /// Forget the solo without restoring anything, e.g. after a song change.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Solo a channel of `player` directly; returns false if out of range.
+    pub fn solo_player<P: ChiptunePlayerBase + ?Sized>(
+        &mut self,
+        player: &mut P,
+        channel: usize,
+    ) -> bool {
+        let count = player.channel_count();
+        match self.solo(channel, count, mute_mask(player)) {
+            Some(mutes) => {
+                apply_mute_mask(player, mutes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leave solo mode on `player`, restoring its earlier mutes.
+    pub fn clear_player<P: ChiptunePlayerBase + ?Sized>(&mut self, player: &mut P) {
+        if let Some(mutes) = self.clear() {
+            apply_mute_mask(player, mutes);
+        }
+    }
+}
+
+/// Mask with a bit set for each of the first `count` channels.
+pub(crate) fn channel_mask(count: usize) -> u64 {
+    if count >= u64::BITS as usize {
+        u64::MAX
+    } else {
+        (1u64 << count) - 1
+    }
+}
+
+/// Current mutes of `player` as a mask.
+pub(crate) fn mute_mask<P: ChiptunePlayerBase + ?Sized>(player: &P) -> u64 {
+    (0..player.channel_count().min(MAX_CHANNEL_COUNT))
+        .filter(|&channel| player.is_channel_muted(channel))
+        .fold(0, |mask, channel| mask | (1u64 << channel))
+}
+
+/// Set every channel of `player` from `mutes`.
+pub(crate) fn apply_mute_mask<P: ChiptunePlayerBase + ?Sized>(player: &mut P, mutes: u64) {
+    for channel in 0..player.channel_count().min(MAX_CHANNEL_COUNT) {
+        player.set_channel_mute(channel, mutes & (1u64 << channel) != 0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PlaybackState;
+
+    /// Two-PSG player that only tracks mute flags.
+    struct MutePlayer {
+        muted: [bool; 6],
+    }
+
+    impl ChiptunePlayerBase for MutePlayer {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Stopped
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            buffer.fill(0.0);
+        }
+        fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+            if let Some(flag) = self.muted.get_mut(channel) {
+                *flag = mute;
+            }
+        }
+        fn is_channel_muted(&self, channel: usize) -> bool {
+            self.muted.get(channel).copied().unwrap_or(false)
+        }
+        fn psg_count(&self) -> usize {
+            2
+        }
+    }
+
+    #[test]
+    fn test_solo_spans_all_psgs() {
+        let mut player = MutePlayer { muted: [false; 6] };
+        let mut solo = SoloState::new();
+        assert_eq!(solo.channel(), None);
+
+        assert!(solo.solo_player(&mut player, 4));
+        assert_eq!(player.muted, [true, true, true, true, false, true]);
+        assert_eq!(solo.channel(), Some(4));
+
+        assert!(solo.solo_player(&mut player, 0));
+        assert_eq!(solo.channel(), Some(0));
+
+        assert!(!solo.solo_player(&mut player, 6));
+        assert_eq!(solo.channel(), Some(0));
+        assert_eq!(player.muted, [false, true, true, true, true, true]);
+    }
+
+    #[test]
+    fn test_clear_restores_earlier_mutes() {
+        let mut player = MutePlayer {
+            muted: [false, true, false, false, false, true],
+        };
+        let mut solo = SoloState::new();
+        solo.solo_player(&mut player, 2);
+        solo.solo_player(&mut player, 3);
+        solo.clear_player(&mut player);
+        assert_eq!(player.muted, [false, true, false, false, false, true]);
+        assert_eq!(solo.channel(), None);
+
+        // Muting all but one channel by hand is not a solo
+        let mut player = MutePlayer {
+            muted: [true, true, false, true, true, true],
+        };
+        solo.clear_player(&mut player);
+        assert_eq!(player.muted, [true, true, false, true, true, true]);
+    }
+}
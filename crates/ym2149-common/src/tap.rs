@@ -0,0 +1,136 @@
+//! Per-channel sample taps for oscilloscope displays.
+//!
+//! A [`ChannelTap`] keeps the last N per-channel output samples of a PSG,
+//! recorded by the backend at the audio sample rate. Unlike
+//! [`WaveformSynthesizer`](crate::WaveformSynthesizer), which rebuilds
+//! waveforms from register values, taps show what the chip actually produced
+//! (including digidrums, sync-buzzer and SID effects).
+
+/// Number of PSG channels recorded by a tap.
+const TAP_CHANNELS: usize = 3;
+
+/// Largest accepted tap capacity in samples per channel.
+pub const MAX_TAP_CAPACITY: usize = 16_384;
+
+/// Ring buffer holding the most recent output samples of each PSG channel.
+///
+/// # Example
+///
+/// ```
+/// use ym2149_common::ChannelTap;
+///
+/// let mut tap = ChannelTap::new(4);
+/// for i in 0..6 {
+///     tap.push([i as f32, 0.0, 0.0]);
+/// }
+///
+/// let mut out = [0.0; 4];
+/// assert_eq!(tap.read_channel(0, &mut out), 4);
+/// assert_eq!(out, [2.0, 3.0, 4.0, 5.0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChannelTap {
+    buffers: [Box<[f32]>; TAP_CHANNELS],
+    write_pos: usize,
+    len: usize,
+}
+
+impl ChannelTap {
+    /// Create a tap holding `capacity` samples per channel (clamped to `1..=MAX_TAP_CAPACITY`).
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.clamp(1, MAX_TAP_CAPACITY);
+        Self {
+            buffers: std::array::from_fn(|_| vec![0.0; capacity].into_boxed_slice()),
+            write_pos: 0,
+            len: 0,
+        }
+    }
+
+    /// Samples per channel the tap can hold.
+    #[must_use]
+    pub fn capacity(&self) -> usize {
+        self.buffers[0].len()
+    }
+
+    /// Samples per channel currently held.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether no samples have been recorded yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Record one sample for each channel (`[A, B, C]`).
+    #[inline]
+    pub fn push(&mut self, outputs: [f32; TAP_CHANNELS]) {
+        for (buffer, value) in self.buffers.iter_mut().zip(outputs) {
+            buffer[self.write_pos] = value;
+        }
+        self.write_pos = (self.write_pos + 1) % self.capacity();
+        self.len = (self.len + 1).min(self.capacity());
+    }
+
+    /// Copy the most recent samples of a channel into `out`, oldest first.
+    ///
+    /// Copies `min(out.len(), len())` samples to the start of `out` and
+    /// returns that count. Returns 0 for channels outside 0..3.
+    pub fn read_channel(&self, channel: usize, out: &mut [f32]) -> usize {
+        let Some(buffer) = self.buffers.get(channel) else {
+            return 0;
+        };
+        let count = out.len().min(self.len);
+        let capacity = self.capacity();
+        let start = (self.write_pos + capacity - count) % capacity;
+        for (i, sample) in out[..count].iter_mut().enumerate() {
+            *sample = buffer[(start + i) % capacity];
+        }
+        count
+    }
+
+    /// Forget all recorded samples.
+    pub fn clear(&mut self) {
+        self.write_pos = 0;
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tap_keeps_latest_samples_per_channel() {
+        let mut tap = ChannelTap::new(3);
+        assert!(tap.is_empty());
+
+        tap.push([1.0, -1.0, 0.5]);
+        tap.push([2.0, -2.0, 0.5]);
+
+        let mut out = [0.0; 8];
+        assert_eq!(tap.read_channel(1, &mut out), 2);
+        assert_eq!(&out[..2], &[-1.0, -2.0]);
+
+        tap.push([3.0, -3.0, 0.5]);
+        tap.push([4.0, -4.0, 0.5]);
+        assert_eq!(tap.len(), 3);
+
+        let mut last_two = [0.0; 2];
+        assert_eq!(tap.read_channel(0, &mut last_two), 2);
+        assert_eq!(last_two, [3.0, 4.0]);
+        assert_eq!(tap.read_channel(3, &mut last_two), 0);
+
+        tap.clear();
+        assert_eq!(tap.read_channel(0, &mut last_two), 0);
+    }
+
+    #[test]
+    fn test_tap_capacity_is_clamped() {
+        assert_eq!(ChannelTap::new(0).capacity(), 1);
+        assert_eq!(ChannelTap::new(usize::MAX).capacity(), MAX_TAP_CAPACITY);
+    }
+}
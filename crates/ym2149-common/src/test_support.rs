@@ -0,0 +1,92 @@
+//! Fixtures for the replayer crates' tests and benchmarks.
+//!
+//! Enable the `test-support` feature from a dev-dependency:
+//!
+//! ```ignore
+//! use ym2149_common::test_support::{mutants, read_asset};
+//!
+//! let Some(song) = read_asset("Scout.ym") else {
+//!     return;
+//! };
+//! for data in mutants(&song, 100) {
+//!     let _ = load_song(&data);
+//! }
+//! ```
+
+use std::path::PathBuf;
+
+/// Read a song from the shared example assets
+/// (`crates/bevy_ym2149_examples/assets/music`).
+///
+/// Returns `None`, with a note on stderr, when the file is missing (e.g. in
+/// a published crate without the assets), so callers can skip the test.
+pub fn read_asset(name: &str) -> Option<Vec<u8>> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("../bevy_ym2149_examples/assets/music")
+        .join(name);
+    let data = std::fs::read(&path).ok();
+    if data.is_none() {
+        eprintln!("Skipping {name} - {} not found", path.display());
+    }
+    data
+}
+
+/// Deterministic mutants of a valid file: random byte changes, extreme
+/// 32-bit words (mostly in the header) and truncation.
+///
+/// The sequence is the same on every run, so a mutant that panics a parser
+/// can be found again by its index.
+pub fn mutants(original: &[u8], count: usize) -> impl Iterator<Item = Vec<u8>> + '_ {
+    const EXTREMES: [u32; 4] = [0, 1, 0x7FFF_FFFF, u32::MAX];
+    let mut state = 0x9E37_79B9u32;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize
+    };
+    (0..count).map(move |i| {
+        let mut data = original.to_vec();
+        if data.is_empty() {
+            return data;
+        }
+        match i % 3 {
+            0 => {
+                for _ in 0..1 + next() % 8 {
+                    let at = next() % data.len();
+                    data[at] = next() as u8;
+                }
+            }
+            1 => {
+                let span = data.len().min(256).saturating_sub(4).max(1);
+                let at = next() % span;
+                let word = EXTREMES[next() % EXTREMES.len()].to_be_bytes();
+                let end = (at + 4).min(data.len());
+                data[at..end].copy_from_slice(&word[..end - at]);
+            }
+            _ => data.truncate(next() % data.len()),
+        }
+        data
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutants_are_deterministic() {
+        let original: Vec<u8> = (0..=255).collect();
+        let first: Vec<Vec<u8>> = mutants(&original, 30).collect();
+        let second: Vec<Vec<u8>> = mutants(&original, 30).collect();
+        assert_eq!(first, second);
+        assert!(first.iter().all(|data| data != &original));
+        assert!(first.iter().any(|data| data.len() < original.len()));
+    }
+
+    #[test]
+    fn test_mutants_of_tiny_files() {
+        assert!(mutants(&[], 3).all(|data| data.is_empty()));
+        assert_eq!(mutants(&[7], 3).count(), 3);
+    }
+}
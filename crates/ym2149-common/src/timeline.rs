@@ -0,0 +1,198 @@
+//! Per-second song summaries for seek bars and song-structure hints.
+//!
+//! [`TimelineBuilder`] condenses per-frame register dumps into one
+//! [`TimelineSecond`] per second of music: which channels play, which effects
+//! are used, how many notes start and how loud the song is. This is cheap
+//! enough to run over a whole song ahead of time, and for register-dump
+//! formats (YM) needs no audio rendering at all.
+
+use crate::channel_state::ChannelStates;
+use crate::visualization::MAX_CHANNEL_COUNT;
+use crate::{FRAME_RATE_PAL, MAX_PSG_COUNT};
+
+/// Summary of one second of music.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimelineSecond {
+    /// Index of the second from song start.
+    pub second: u32,
+    /// Bit mask of channels that made sound (bit 0 = PSG 0 channel A).
+    pub active_channels: u16,
+    /// Most channels sounding in a single frame.
+    pub max_voices: u8,
+    /// Notes started (pitch changes or attacks on tone channels).
+    pub note_onsets: u16,
+    /// Noise generator used on a sounding channel.
+    pub noise: bool,
+    /// Hardware envelope (buzzer) used on a sounding channel.
+    pub envelope: bool,
+    /// Sample playback through the volume registers (digidrums, SID, DAC).
+    pub digi: bool,
+    /// Mean register loudness (0.0-1.0).
+    pub loudness: f32,
+    /// Loudest frame of the second (0.0-1.0).
+    pub peak: f32,
+}
+
+/// Accumulates register frames into [`TimelineSecond`] summaries.
+#[derive(Debug, Clone)]
+pub struct TimelineBuilder {
+    frame_rate: u32,
+    frame: u32,
+    current: TimelineSecond,
+    loudness_sum: f32,
+    frames_in_second: u32,
+    last_notes: [Option<u8>; MAX_CHANNEL_COUNT],
+    seconds: Vec<TimelineSecond>,
+}
+
+impl TimelineBuilder {
+    /// Create a builder for frames played at `frame_rate` Hz.
+    pub fn new(frame_rate: u32) -> Self {
+        Self {
+            frame_rate: if frame_rate == 0 {
+                FRAME_RATE_PAL
+            } else {
+                frame_rate
+            },
+            frame: 0,
+            current: TimelineSecond::default(),
+            loudness_sum: 0.0,
+            frames_in_second: 0,
+            last_notes: [None; MAX_CHANNEL_COUNT],
+            seconds: Vec::new(),
+        }
+    }
+
+    /// Frame rate the builder groups frames by.
+    pub fn frame_rate(&self) -> u32 {
+        self.frame_rate
+    }
+
+    /// Number of frames recorded so far.
+    pub fn frame_count(&self) -> u32 {
+        self.frame
+    }
+
+    /// Completed seconds so far (a trailing partial second is added by [`finish`](Self::finish)).
+    pub fn seconds(&self) -> &[TimelineSecond] {
+        &self.seconds
+    }
+
+    /// Record one frame of register state for all PSGs (at most [`MAX_PSG_COUNT`]).
+    pub fn record_frame(&mut self, register_banks: &[[u8; 16]]) {
+        let banks = &register_banks[..register_banks.len().min(MAX_PSG_COUNT)];
+        let channel_count = (banks.len() * 3).max(1);
+        let mut voices = 0u8;
+        let mut loudness = 0.0;
+
+        for (psg, registers) in banks.iter().enumerate() {
+            let states = ChannelStates::from_registers(registers);
+            for (local, state) in states.channels.iter().enumerate() {
+                let channel = psg * 3 + local;
+                let sounding = state.amplitude > 0 || state.envelope_enabled;
+                if !sounding {
+                    self.last_notes[channel] = None;
+                    continue;
+                }
+
+                voices += 1;
+                loudness += state.amplitude_normalized;
+                self.current.active_channels |= 1 << channel;
+                self.current.noise |= state.noise_enabled;
+                self.current.envelope |= state.envelope_enabled;
+                self.current.digi |= !state.tone_enabled && !state.noise_enabled;
+
+                let note = state.midi_note.filter(|_| state.tone_enabled);
+                if note.is_some() && note != self.last_notes[channel] {
+                    self.current.note_onsets = self.current.note_onsets.saturating_add(1);
+                }
+                self.last_notes[channel] = note;
+            }
+        }
+
+        let loudness = loudness / channel_count as f32;
+        self.current.max_voices = self.current.max_voices.max(voices);
+        self.current.peak = self.current.peak.max(loudness);
+        self.loudness_sum += loudness;
+        self.frames_in_second += 1;
+        self.frame += 1;
+
+        if self.frames_in_second >= self.frame_rate {
+            self.flush();
+        }
+    }
+
+    /// Close the trailing partial second and return all summaries.
+    pub fn finish(mut self) -> Vec<TimelineSecond> {
+        if self.frames_in_second > 0 {
+            self.flush();
+        }
+        self.seconds
+    }
+
+    fn flush(&mut self) {
+        let mut second = std::mem::take(&mut self.current);
+        second.second = self.seconds.len() as u32;
+        second.loudness = self.loudness_sum / self.frames_in_second.max(1) as f32;
+        self.seconds.push(second);
+        self.loudness_sum = 0.0;
+        self.frames_in_second = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regs(mixer: u8, volumes: [u8; 3], period_a: u16) -> [u8; 16] {
+        let mut regs = [0u8; 16];
+        regs[0] = (period_a & 0xFF) as u8;
+        regs[1] = (period_a >> 8) as u8;
+        regs[2] = 0x80;
+        regs[7] = mixer;
+        regs[8..11].copy_from_slice(&volumes);
+        regs
+    }
+
+    #[test]
+    fn test_seconds_summarize_channels_and_effects() {
+        let mut builder = TimelineBuilder::new(50);
+        // Second 0: channel A melody (two notes), channel B noise
+        for frame in 0..50 {
+            let period = if frame < 25 { 0x11C } else { 0xEE };
+            builder.record_frame(&[regs(0b0010_1110, [15, 15, 0], period)]);
+        }
+        // Second 1 (partial): silent except a volume-register sample on C
+        for _ in 0..10 {
+            builder.record_frame(&[regs(0b0011_1111, [0, 0, 8], 0x11C)]);
+        }
+        let seconds = builder.finish();
+
+        assert_eq!(seconds.len(), 2);
+        let first = seconds[0];
+        assert_eq!(first.active_channels, 0b011);
+        assert_eq!(first.max_voices, 2);
+        assert_eq!(first.note_onsets, 2);
+        assert!(first.noise && !first.envelope && !first.digi);
+        assert!((first.loudness - 2.0 / 3.0).abs() < 1e-5);
+
+        let second = seconds[1];
+        assert_eq!(second.second, 1);
+        assert_eq!(second.active_channels, 0b100);
+        assert_eq!(second.note_onsets, 0);
+        assert!(second.digi && !second.noise);
+    }
+
+    #[test]
+    fn test_silence_resets_note_tracking() {
+        let mut builder = TimelineBuilder::new(10);
+        let on = regs(0b0011_1110, [15, 0, 0], 0x11C);
+        let off = regs(0b0011_1110, [0, 0, 0], 0x11C);
+        for frame in [on, on, off, on, off, on, on, on, on, on] {
+            builder.record_frame(&[frame]);
+        }
+        assert_eq!(builder.seconds().len(), 1);
+        assert_eq!(builder.seconds()[0].note_onsets, 3);
+        assert!((builder.seconds()[0].peak - 1.0 / 3.0).abs() < 1e-5);
+    }
+}
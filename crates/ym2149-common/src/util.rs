@@ -36,6 +36,27 @@ pub fn period_to_frequency_with_clock(master_clock_hz: f32, period: u16) -> f32
     }
 }
 
+/// Convert a frequency into the nearest tone period for the default 2MHz master clock.
+///
+/// Clamped to the valid 12-bit range (1-4095).
+#[inline]
+#[must_use]
+pub fn frequency_to_period(frequency_hz: f32) -> u16 {
+    if frequency_hz <= 0.0 {
+        return 0x0FFF;
+    }
+    (PSG_MASTER_CLOCK_F32 / (PERIOD_DENOMINATOR * frequency_hz))
+        .round()
+        .clamp(1.0, 4095.0) as u16
+}
+
+/// Frequency of a MIDI note number in equal temperament (A4 = 69 = 440 Hz).
+#[inline]
+#[must_use]
+pub fn midi_note_frequency(note: u8) -> f32 {
+    440.0 * 2f32.powf((f32::from(note) - 69.0) / 12.0)
+}
+
 /// Convenience helper returning the three channel frequencies for the default clock.
 #[inline]
 #[must_use]
@@ -23,6 +23,9 @@
 //! let samples = waveform.get_samples();
 //! let bins = spectrum.get_bins();
 //! ```
+//!
+//! [`Harmony`] labels the chord and intervals the channels sound together,
+//! for UIs that explain the music rather than draw it.
 
 use crate::channel_state::ChannelStates;
 use std::collections::VecDeque;
@@ -586,10 +589,324 @@ impl SpectrumAnalyzer {
     }
 }
 
+// ============================================================================
+// Harmony Analysis
+// ============================================================================
+
+/// Pitch class names, C = 0.
+const PITCH_CLASS_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Name of a pitch class (0-11, wraps), e.g. `"F#"`.
+#[must_use]
+pub fn pitch_class_name(pitch_class: u8) -> &'static str {
+    PITCH_CLASS_NAMES[(pitch_class % 12) as usize]
+}
+
+/// Name of an interval in semitones, reduced to one octave.
+///
+/// ```
+/// use ym2149_common::visualization::interval_name;
+///
+/// assert_eq!(interval_name(7), "perfect 5th");
+/// assert_eq!(interval_name(16), "major 3rd");
+/// ```
+#[must_use]
+pub fn interval_name(semitones: u8) -> &'static str {
+    match semitones % 12 {
+        0 => "unison",
+        1 => "minor 2nd",
+        2 => "major 2nd",
+        3 => "minor 3rd",
+        4 => "major 3rd",
+        5 => "perfect 4th",
+        6 => "tritone",
+        7 => "perfect 5th",
+        8 => "minor 6th",
+        9 => "major 6th",
+        10 => "minor 7th",
+        _ => "major 7th",
+    }
+}
+
+/// Chord types recognized by [`Harmony`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChordQuality {
+    /// Root and fifth only.
+    Power,
+    /// Major triad.
+    Major,
+    /// Minor triad.
+    Minor,
+    /// Diminished triad.
+    Diminished,
+    /// Augmented triad.
+    Augmented,
+    /// Suspended second.
+    Sus2,
+    /// Suspended fourth.
+    Sus4,
+    /// Dominant seventh.
+    Dominant7,
+    /// Major seventh.
+    Major7,
+    /// Minor seventh.
+    Minor7,
+    /// Half-diminished seventh (m7b5).
+    HalfDiminished7,
+    /// Diminished seventh.
+    Diminished7,
+}
+
+impl ChordQuality {
+    /// All qualities with their pitch-class masks relative to the root.
+    const TEMPLATES: [(ChordQuality, u16); 12] = [
+        (ChordQuality::Major, 0b000010010001),
+        (ChordQuality::Minor, 0b000010001001),
+        (ChordQuality::Diminished, 0b000001001001),
+        (ChordQuality::Augmented, 0b000100010001),
+        (ChordQuality::Sus2, 0b000010000101),
+        (ChordQuality::Sus4, 0b000010100001),
+        (ChordQuality::Dominant7, 0b010010010001),
+        (ChordQuality::Major7, 0b100010010001),
+        (ChordQuality::Minor7, 0b010010001001),
+        (ChordQuality::HalfDiminished7, 0b010001001001),
+        (ChordQuality::Diminished7, 0b001001001001),
+        (ChordQuality::Power, 0b000010000001),
+    ];
+
+    /// Chord symbol suffix, e.g. `"m7"` (empty for major).
+    #[must_use]
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ChordQuality::Power => "5",
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::HalfDiminished7 => "m7b5",
+            ChordQuality::Diminished7 => "dim7",
+        }
+    }
+}
+
+/// A recognized chord.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chord {
+    /// Root pitch class (0-11, C = 0).
+    pub root: u8,
+    /// Chord type.
+    pub quality: ChordQuality,
+    /// Lowest sounding pitch class; differs from `root` for inversions.
+    pub bass: u8,
+}
+
+impl Chord {
+    /// Chord symbol such as `"Am"`, `"G7"` or `"C/E"` for an inversion.
+    #[must_use]
+    pub fn name(&self) -> String {
+        let mut name = format!("{}{}", pitch_class_name(self.root), self.quality.suffix());
+        if self.bass != self.root {
+            name.push('/');
+            name.push_str(pitch_class_name(self.bass));
+        }
+        name
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+/// Notes, intervals and chord sounding in one frame.
+///
+/// Build it from any number of channels (3 per PSG), once per frame.
+///
+/// ```
+/// use ym2149_common::visualization::Harmony;
+///
+/// // A minor: A3, C4, E4
+/// let harmony = Harmony::from_frequencies(&[Some(220.0), Some(261.63), Some(329.63)]);
+/// assert_eq!(harmony.chord.unwrap().name(), "Am");
+/// assert_eq!(harmony.intervals, vec![3, 7]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Harmony {
+    /// Distinct sounding MIDI notes, lowest first.
+    pub notes: Vec<u8>,
+    /// Bit mask of sounding pitch classes (bit 0 = C).
+    pub pitch_classes: u16,
+    /// Semitones from the lowest note to each higher note.
+    pub intervals: Vec<u8>,
+    /// Recognized chord, if the pitch classes form one.
+    pub chord: Option<Chord>,
+}
+
+impl Harmony {
+    /// Analyze channel frequencies in Hz (`None` or 0 for silent channels).
+    #[must_use]
+    pub fn from_frequencies(frequencies: &[Option<f32>]) -> Self {
+        Self::from_midi_notes(
+            frequencies
+                .iter()
+                .filter_map(|freq| freq.filter(|f| (20.0..=20000.0).contains(f)))
+                .map(|freq| (freq / 440.0).log2().mul_add(12.0, 69.0).round())
+                .filter(|note| (0.0..=127.0).contains(note))
+                .map(|note| note as u8),
+        )
+    }
+
+    /// Analyze the audible tone channels of one or more PSGs.
+    ///
+    /// A channel counts when its tone is enabled and it has amplitude or
+    /// envelope output.
+    #[must_use]
+    pub fn from_channel_states(states: &[ChannelStates]) -> Self {
+        Self::from_midi_notes(
+            states
+                .iter()
+                .flat_map(|psg| psg.channels.iter())
+                .filter(|ch| ch.tone_enabled && (ch.amplitude > 0 || ch.envelope_enabled))
+                .filter_map(|ch| ch.midi_note),
+        )
+    }
+
+    /// Analyze a set of MIDI notes.
+    #[must_use]
+    pub fn from_midi_notes(notes: impl IntoIterator<Item = u8>) -> Self {
+        let mut notes: Vec<u8> = notes.into_iter().collect();
+        notes.sort_unstable();
+        notes.dedup();
+
+        let Some(&lowest) = notes.first() else {
+            return Self::default();
+        };
+        let pitch_classes = notes
+            .iter()
+            .fold(0u16, |mask, note| mask | 1 << (note % 12));
+        let intervals = notes[1..].iter().map(|note| note - lowest).collect();
+
+        Self {
+            chord: Self::match_chord(pitch_classes, lowest % 12),
+            notes,
+            pitch_classes,
+            intervals,
+        }
+    }
+
+    /// Find a chord template matching the pitch classes, trying the bass first.
+    fn match_chord(pitch_classes: u16, bass: u8) -> Option<Chord> {
+        (0..12)
+            .map(|offset| (bass + offset) % 12)
+            .filter(|root| pitch_classes & (1 << root) != 0)
+            .find_map(|root| {
+                let relative = (pitch_classes >> root | pitch_classes << (12 - root)) & 0x0FFF;
+                ChordQuality::TEMPLATES
+                    .iter()
+                    .find(|(_, mask)| *mask == relative)
+                    .map(|&(quality, _)| Chord {
+                        root,
+                        quality,
+                        bass,
+                    })
+            })
+    }
+
+    /// Chord symbol, or the interval name for two pitch classes.
+    ///
+    /// Returns `None` for silence, a single pitch class, or an unrecognized cluster.
+    #[must_use]
+    pub fn label(&self) -> Option<String> {
+        if let Some(chord) = self.chord {
+            return Some(chord.name());
+        }
+        match self.intervals.as_slice() {
+            [interval] => Some(interval_name(*interval).to_string()),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_harmony_chords_and_inversions() {
+        // C major in root position: C4 E4 G4
+        let harmony = Harmony::from_midi_notes([60, 64, 67]);
+        assert_eq!(harmony.chord.unwrap().name(), "C");
+        assert_eq!(harmony.intervals, vec![4, 7]);
+
+        // First inversion: E3 C4 G4
+        assert_eq!(
+            Harmony::from_midi_notes([52, 60, 67]).label().unwrap(),
+            "C/E"
+        );
+
+        // Doubled notes collapse; G7 across two PSGs
+        let harmony = Harmony::from_midi_notes([55, 59, 62, 65, 67, 67]);
+        assert_eq!(harmony.label().unwrap(), "G7");
+
+        // Ambiguous sus chords prefer the bass as root
+        assert_eq!(
+            Harmony::from_midi_notes([60, 62, 67]).label().unwrap(),
+            "Csus2"
+        );
+        assert_eq!(
+            Harmony::from_midi_notes([55, 60, 62]).label().unwrap(),
+            "Gsus4"
+        );
+    }
+
+    #[test]
+    fn test_harmony_intervals_and_silence() {
+        let harmony = Harmony::from_midi_notes([60, 66]);
+        assert_eq!(harmony.chord, None);
+        assert_eq!(harmony.label().unwrap(), "tritone");
+        assert_eq!(Harmony::from_midi_notes([60, 67]).label().unwrap(), "C5");
+        assert_eq!(Harmony::from_midi_notes([60]).label(), None);
+        assert_eq!(
+            Harmony::from_frequencies(&[None, Some(0.0)]),
+            Harmony::default()
+        );
+    }
+
+    #[test]
+    fn test_harmony_from_channel_states() {
+        // D minor via registers: D4, F4, A4 (periods at 2 MHz)
+        let mut regs = [0u8; 16];
+        for (ch, period) in [426u16, 358, 284].into_iter().enumerate() {
+            regs[ch * 2] = period as u8;
+            regs[ch * 2 + 1] = (period >> 8) as u8;
+            regs[8 + ch] = 0x0F;
+        }
+        regs[7] = 0x38;
+        let states = ChannelStates::from_registers(&regs);
+        assert_eq!(
+            Harmony::from_channel_states(&[states.clone()])
+                .label()
+                .unwrap(),
+            "Dm"
+        );
+
+        // Muting channel C leaves the minor third
+        regs[10] = 0;
+        let states = ChannelStates::from_registers(&regs);
+        assert_eq!(
+            Harmony::from_channel_states(&[states]).label().unwrap(),
+            "minor 3rd"
+        );
+    }
+
     #[test]
     fn test_freq_to_bin_c1() {
         // C1 = 32.703 Hz should map to bin 0
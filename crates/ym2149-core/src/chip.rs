@@ -12,12 +12,14 @@
 //! and other cycle-sensitive effects.
 
 use std::collections::VecDeque;
+use std::sync::Arc;
 
-use crate::dc_filter::DcFilter;
 use crate::generators::{EnvelopeGenerator, NUM_CHANNELS, NoiseGenerator, ToneGenerator};
-use crate::mixer::Mixer;
-use crate::tables::REG_MASK;
-use ym2149_common::{MASTER_GAIN, Ym2149Backend};
+use crate::io::{IoPort, PortCallbacks, ReadConfig};
+use crate::mixer::{ChannelState, Mixer, MixerConfig};
+use crate::output_filter::{OutputFilter, OutputFilterState};
+use crate::tables::{READ_MASK, REG_MASK};
+use ym2149_common::{ChannelTap, FilterChain, MASTER_GAIN, OutputModel, Ym2149Backend};
 
 /// Default Atari ST master clock (2 MHz)
 const DEFAULT_MASTER_CLOCK: u32 = 2_000_000;
@@ -25,23 +27,61 @@ const DEFAULT_MASTER_CLOCK: u32 = 2_000_000;
 /// Default audio sample rate (44.1 kHz)
 const DEFAULT_SAMPLE_RATE: u32 = 44_100;
 
-/// Number of YM2149 registers
-const NUM_REGISTERS: usize = 14;
+/// Number of YM2149 registers, including the I/O ports R14/R15
+const NUM_REGISTERS: usize = 16;
+
+/// Number of registers that control sound generation (R0-R13)
+const NUM_SOUND_REGISTERS: usize = 14;
 
 /// CPU cycles per YM2149 master clock cycle (8 MHz / 2 MHz = 4)
 const CPU_CYCLES_PER_PSG_CYCLE: u64 = 4;
 
+/// CPU cycles per internal state machine tick (master clock / 8)
+const CPU_CYCLES_PER_INTERNAL_TICK: u64 = CPU_CYCLES_PER_PSG_CYCLE * 8;
+
 /// A pending register write with its CPU cycle timestamp
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct PendingWrite {
     /// CPU cycle when the write occurred
     cpu_cycle: u64,
-    /// Target register (0-13)
+    /// Target register (0-15)
     register: u8,
     /// Value to write
     value: u8,
 }
 
+/// Snapshot of the chip's runtime state
+///
+/// Captured by [`Ym2149::save_state`] and restored with
+/// [`Ym2149::load_state`]. It holds everything that evolves while the chip
+/// runs (registers, generator counters, envelope position, noise LFSR,
+/// pending writes, filter memory) but not its configuration: clocks, output
+/// model, read configuration, port inputs and callbacks, channel mutes and the
+/// channel tap stay as they are on the chip the state is loaded into.
+///
+/// With the `serde` feature the state can be serialized.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ym2149State {
+    cycle_accumulator: u32,
+    registers: [u8; NUM_REGISTERS],
+    selected_register: usize,
+    tone_generators: [ToneGenerator; NUM_CHANNELS],
+    noise_generator: NoiseGenerator,
+    envelope_generator: EnvelopeGenerator,
+    mixer_config: MixerConfig,
+    channels: [ChannelState; NUM_CHANNELS],
+    output_filter: OutputFilterState,
+    last_sample: f32,
+    in_timer_irq: bool,
+    pending_envelope_trigger: Option<u32>,
+    write_queue: VecDeque<PendingWrite>,
+    current_cpu_cycle: u64,
+    last_select_cycle: u64,
+    sample_start_cycle: u64,
+}
+
 /// Simple PRNG for unpredictable power-on state
 fn random_seed(seed: &mut u32) -> u16 {
     *seed = seed.wrapping_mul(214013).wrapping_add(2531011);
@@ -60,7 +100,7 @@ fn random_seed(seed: &mut u32) -> u16 {
 /// - 1 noise generator with 17-bit LFSR
 /// - 1 envelope generator with 16 shapes (10 unique patterns)
 /// - Configurable mixer for tone/noise routing
-/// - DC offset removal filter
+/// - Configurable output filters (DC removal, optional low-pass)
 /// - DigiDrum sample injection support
 ///
 /// # Example
@@ -87,9 +127,16 @@ pub struct Ym2149 {
     sample_rate: u32,
     cycle_accumulator: u32,
 
-    // Hardware registers
+    // Hardware registers (as written; unused bits are masked where used)
     registers: [u8; NUM_REGISTERS],
+    /// Address latch; 16 and above leave the chip deselected
     selected_register: usize,
+    /// Register read-back behaviour
+    read_config: ReadConfig,
+    /// Levels driven onto the I/O port pins from outside (A, B)
+    port_inputs: [u8; 2],
+    /// Host devices wired to the I/O ports
+    port_callbacks: PortCallbacks,
 
     // Generators
     tone_generators: [ToneGenerator; NUM_CHANNELS],
@@ -98,13 +145,16 @@ pub struct Ym2149 {
 
     // Output processing
     mixer: Mixer,
-    dc_filter: DcFilter,
+    output_filter: OutputFilter,
+    channel_tap: Option<ChannelTap>,
 
     // Cached output for Backend trait
     last_sample: f32,
 
     // Timer IRQ state (for sync-buzzer effects)
     in_timer_irq: bool,
+    /// Internal tick of the next sample at which to restart the envelope
+    pending_envelope_trigger: Option<u32>,
 
     // Write queue for hardware-accurate timing
     write_queue: VecDeque<PendingWrite>,
@@ -116,6 +166,8 @@ pub struct Ym2149 {
     cpu_cycles_per_sample: u64,
     /// CPU cycle at start of current sample
     sample_start_cycle: u64,
+    /// Average every internal tick instead of sampling once per output sample
+    oversampled_output: bool,
 }
 
 impl Ym2149 {
@@ -145,6 +197,9 @@ impl Ym2149 {
             cycle_accumulator: 0,
             registers: [0; NUM_REGISTERS],
             selected_register: 0,
+            read_config: ReadConfig::default(),
+            port_inputs: [0xFF; 2],
+            port_callbacks: PortCallbacks::default(),
             tone_generators: [
                 ToneGenerator::new(),
                 ToneGenerator::new(),
@@ -153,14 +208,17 @@ impl Ym2149 {
             noise_generator: NoiseGenerator::new(),
             envelope_generator: EnvelopeGenerator::new(),
             mixer: Mixer::new(),
-            dc_filter: DcFilter::new(),
+            output_filter: OutputFilter::new(FilterChain::default(), sample_rate),
+            channel_tap: None,
             last_sample: 0.0,
             in_timer_irq: false,
+            pending_envelope_trigger: None,
             write_queue: VecDeque::new(),
             current_cpu_cycle: 0,
             last_select_cycle: 0,
             cpu_cycles_per_sample,
             sample_start_cycle: 0,
+            oversampled_output: false,
         };
         chip.reset();
         chip
@@ -181,7 +239,10 @@ impl Ym2149 {
         self.noise_generator.reset();
         self.envelope_generator.reset();
         self.mixer.reset();
-        self.dc_filter.reset();
+        self.output_filter.reset();
+        if let Some(tap) = self.channel_tap.as_mut() {
+            tap.clear();
+        }
 
         // Initialize registers (R7 = 0x3F = all outputs disabled)
         self.registers = [0; NUM_REGISTERS];
@@ -190,6 +251,7 @@ impl Ym2149 {
         self.selected_register = 0;
         self.cycle_accumulator = 0;
         self.in_timer_irq = false;
+        self.pending_envelope_trigger = None;
         self.last_sample = 0.0;
 
         // Clear write queue and timing state
@@ -246,17 +308,21 @@ impl Ym2149 {
     ///
     /// Writes that occur too quickly after a register select (< 4 CPU cycles)
     /// are still queued but may not behave as expected on real hardware.
+    /// Selecting an address of 16 or above deselects the chip: data writes
+    /// are ignored until a register is selected again.
     pub fn write_port(&mut self, port: u8, value: u8) {
         if (port & 2) != 0 {
             // Data write - queue for later application
-            self.write_queue.push_back(PendingWrite {
-                cpu_cycle: self.current_cpu_cycle,
-                register: self.selected_register as u8,
-                value,
-            });
+            if self.selected_register < NUM_REGISTERS {
+                self.write_queue.push_back(PendingWrite {
+                    cpu_cycle: self.current_cpu_cycle,
+                    register: self.selected_register as u8,
+                    value,
+                });
+            }
         } else {
             // Register select - apply immediately
-            self.selected_register = (value as usize) & 0x0F;
+            self.selected_register = value as usize;
             self.last_select_cycle = self.current_cpu_cycle;
         }
     }
@@ -269,7 +335,7 @@ impl Ym2149 {
         if (port & 2) != 0 {
             self.apply_register(self.selected_register, value);
         } else {
-            self.selected_register = (value as usize) & 0x0F;
+            self.selected_register = value as usize;
         }
     }
 
@@ -313,13 +379,15 @@ impl Ym2149 {
     ///
     /// # Returns
     ///
-    /// Register value or 0xFF for invalid reads
+    /// The selected register as [`read_register`](Self::read_register)
+    /// returns it, or the floating bus value when reading the data port or
+    /// while no register is selected
     #[must_use]
     pub fn read_port(&self, port: u8) -> u8 {
         if (port & 2) == 0 && self.selected_register < NUM_REGISTERS {
-            self.registers[self.selected_register]
+            self.read_register(self.selected_register as u8)
         } else {
-            0xFF
+            self.read_config.floating_bus
         }
     }
 
@@ -335,32 +403,137 @@ impl Ym2149 {
 
     /// Read from a register
     ///
+    /// Reads follow the [`ReadConfig`]: unused bits read as 0 unless
+    /// disabled, the I/O ports R14/R15 read their pins or output latch
+    /// depending on their direction in R7, and registers 16 and above read
+    /// the floating bus value.
+    ///
     /// # Arguments
     ///
-    /// * `register` - Register number (0-13)
+    /// * `register` - Register number (0-15)
     ///
     /// # Returns
     ///
-    /// Current register value
+    /// Value the chip puts on the data bus
     #[must_use]
     pub fn read_register(&self, register: u8) -> u8 {
         let reg = register as usize;
-        if reg < NUM_REGISTERS {
-            self.registers[reg]
+        if reg >= NUM_REGISTERS {
+            return self.read_config.floating_bus;
+        }
+        if let Some(port) = IoPort::from_register(register) {
+            let pins = self.port_pins(port);
+            return if self.registers[7] & port.output_bit() != 0 {
+                // Open-collector outputs: external devices can pull low
+                self.registers[reg] & pins
+            } else {
+                pins
+            };
+        }
+        if self.read_config.mask_unused_bits {
+            self.registers[reg] & READ_MASK[reg]
         } else {
-            0
+            self.registers[reg]
+        }
+    }
+
+    /// Set how register reads behave (see [`ReadConfig`])
+    pub fn set_read_config(&mut self, config: ReadConfig) {
+        self.read_config = config;
+    }
+
+    /// Current register read behaviour
+    #[must_use]
+    pub fn read_config(&self) -> ReadConfig {
+        self.read_config
+    }
+
+    /// Set the levels an external device drives onto an I/O port's pins
+    ///
+    /// Input ports read these levels; output ports read their latch ANDed
+    /// with them. Unconnected pins are pulled up (0xFF, the default). A
+    /// callback installed with [`on_port_read`](Self::on_port_read) takes
+    /// precedence.
+    pub fn set_port_input(&mut self, port: IoPort, value: u8) {
+        self.port_inputs[port as usize] = value;
+    }
+
+    /// Call `callback` with each value an output port drives
+    ///
+    /// It fires when R14/R15 is written while the port is an output and
+    /// when R7 switches a port to output. Queued writes are reported when
+    /// the chip applies them, so at their emulated time while clocking.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::{Arc, Mutex};
+    /// use ym2149::{IoPort, Ym2149, Ym2149Backend};
+    ///
+    /// let strobes = Arc::new(Mutex::new(Vec::new()));
+    /// let sink = Arc::clone(&strobes);
+    ///
+    /// let mut chip = Ym2149::new();
+    /// chip.on_port_write(move |port, value| sink.lock().unwrap().push((port, value)));
+    /// chip.write_register(7, 0xFF); // Both ports output
+    /// chip.write_register(15, 0x41);
+    ///
+    /// assert_eq!(strobes.lock().unwrap().last(), Some(&(IoPort::B, 0x41)));
+    /// ```
+    pub fn on_port_write(&mut self, callback: impl Fn(IoPort, u8) + Send + Sync + 'static) {
+        self.port_callbacks.write = Some(Arc::new(callback));
+    }
+
+    /// Ask `callback` for the levels on an I/O port's pins on every read
+    ///
+    /// Replaces the levels set with [`set_port_input`](Self::set_port_input),
+    /// e.g. to scan a keyboard matrix row selected elsewhere in the machine.
+    pub fn on_port_read(&mut self, callback: impl Fn(IoPort) -> u8 + Send + Sync + 'static) {
+        self.port_callbacks.read = Some(Arc::new(callback));
+    }
+
+    /// Remove the callbacks installed with [`on_port_write`](Self::on_port_write)
+    /// and [`on_port_read`](Self::on_port_read)
+    pub fn clear_port_callbacks(&mut self) {
+        self.port_callbacks = PortCallbacks::default();
+    }
+
+    /// Levels on an I/O port's pins
+    fn port_pins(&self, port: IoPort) -> u8 {
+        match &self.port_callbacks.read {
+            Some(read) => read(port),
+            None => self.port_inputs[port as usize],
         }
     }
 
+    /// Report an output port's latch to the host
+    fn notify_port_output(&self, port: IoPort) {
+        if let (Some(write), Some(value)) = (&self.port_callbacks.write, self.port_output(port)) {
+            write(port, value);
+        }
+    }
+
+    /// Value an I/O port drives, or `None` while R7 configures it as input
+    #[must_use]
+    pub fn port_output(&self, port: IoPort) -> Option<u8> {
+        (self.registers[7] & port.output_bit() != 0)
+            .then(|| self.registers[port.register() as usize])
+    }
+
     /// Apply a register write and update internal state
     fn apply_register(&mut self, register: usize, value: u8) {
         if register >= NUM_REGISTERS {
             return;
         }
 
-        // Mask value to valid bits
-        let value = value & REG_MASK[register];
+        let previous = self.registers[register];
         self.registers[register] = value;
+        if let Some(port) = IoPort::from_register(register as u8) {
+            self.notify_port_output(port);
+            return;
+        }
+        // Generators only see the valid bits
+        let value = value & REG_MASK[register];
 
         match register {
             // Tone period registers (2 registers per channel)
@@ -383,6 +556,11 @@ impl Ym2149 {
             // Mixer control
             7 => {
                 self.mixer.config.set_from_register(value);
+                for port in [IoPort::A, IoPort::B] {
+                    if self.registers[7] & !previous & port.output_bit() != 0 {
+                        self.notify_port_output(port);
+                    }
+                }
             }
 
             // Envelope period (R11/R12)
@@ -404,13 +582,19 @@ impl Ym2149 {
     #[inline]
     fn read_tone_period(&self, channel: usize) -> u32 {
         let base = channel * 2;
-        ((self.registers[base + 1] as u32) << 8) | (self.registers[base] as u32)
+        ((self.sound_register(base + 1) as u32) << 8) | (self.sound_register(base) as u32)
+    }
+
+    /// Valid bits of a sound register (R0-R13)
+    #[inline]
+    fn sound_register(&self, register: usize) -> u8 {
+        self.registers[register] & REG_MASK[register]
     }
 
     /// Read 16-bit envelope period from registers
     #[inline]
     fn read_envelope_period(&self) -> u32 {
-        ((self.registers[12] as u32) << 8) | (self.registers[11] as u32)
+        ((self.sound_register(12) as u32) << 8) | (self.sound_register(11) as u32)
     }
 
     /// Tick internal state machines at 250kHz rate
@@ -443,6 +627,10 @@ impl Ym2149 {
     /// For accurate timing, call `set_cpu_cycle()` before each `write_port()` call,
     /// and ensure `sample_start_cycle` is updated between samples.
     pub fn compute_next_sample(&mut self) -> i16 {
+        if self.oversampled_output {
+            return self.compute_oversampled_sample();
+        }
+
         // Process any pending writes that should be applied before/during this sample
         let sample_end_cycle = self.sample_start_cycle + self.cpu_cycles_per_sample;
         self.process_pending_writes(sample_end_cycle);
@@ -452,24 +640,94 @@ impl Ym2149 {
 
         // Accumulate gate mask over all internal ticks
         let mut accumulated_mask: u16 = 0;
+        let mut tick = 0u32;
 
         loop {
+            if self.pending_envelope_trigger == Some(tick) {
+                self.envelope_generator.trigger();
+                self.pending_envelope_trigger = None;
+            }
             accumulated_mask |= self.tick_generators() as u16;
+            tick += 1;
             self.cycle_accumulator += self.sample_rate;
             if self.cycle_accumulator >= self.internal_clock {
                 break;
             }
         }
         self.cycle_accumulator -= self.internal_clock;
+        // Offset rounded past this sample's last tick: restart at its end
+        if self.pending_envelope_trigger.take().is_some() {
+            self.envelope_generator.trigger();
+        }
+
+        let total_output = self.mix_channels(accumulated_mask as u32);
+
+        if let Some(tap) = self.channel_tap.as_mut() {
+            let (a, b, c) = self.mixer.channel_outputs();
+            tap.push([a, b, c]);
+        }
+
+        // Apply output filters and return
+        self.output_filter.process(total_output as u16)
+    }
+
+    /// Generate a sample by averaging the output of every internal tick.
+    ///
+    /// Queued writes are applied at the tick they land on, so register
+    /// changes faster than the sample rate (timer-driven sample replay)
+    /// contribute in proportion to how long they were active.
+    fn compute_oversampled_sample(&mut self) -> i16 {
+        let sample_start_cycle = self.sample_start_cycle;
+        let sample_end_cycle = sample_start_cycle + self.cpu_cycles_per_sample;
+        self.sample_start_cycle = sample_end_cycle;
+
+        let mut total_output = 0u32;
+        let mut tick = 0u32;
 
+        loop {
+            self.process_pending_writes(
+                sample_start_cycle + u64::from(tick) * CPU_CYCLES_PER_INTERNAL_TICK,
+            );
+            if self.pending_envelope_trigger == Some(tick) {
+                self.envelope_generator.trigger();
+                self.pending_envelope_trigger = None;
+            }
+            let gate_mask = self.tick_generators();
+            total_output += self.mix_channels(gate_mask);
+            tick += 1;
+            self.cycle_accumulator += self.sample_rate;
+            if self.cycle_accumulator >= self.internal_clock {
+                break;
+            }
+        }
+        self.cycle_accumulator -= self.internal_clock;
+        self.process_pending_writes(sample_end_cycle);
+        if self.pending_envelope_trigger.take().is_some() {
+            self.envelope_generator.trigger();
+        }
+
+        if let Some(tap) = self.channel_tap.as_mut() {
+            let (a, b, c) = self.mixer.channel_outputs();
+            tap.push([a, b, c]);
+        }
+
+        self.output_filter.process((total_output / tick) as u16)
+    }
+
+    /// Mix the three channels for the given gate mask.
+    fn mix_channels(&mut self, gate_mask: u32) -> u32 {
         // Get envelope level
         let envelope_level = self.envelope_generator.level();
 
         // Build channel levels (gated and ungated for bipolar visualization)
-        let volume_regs = [self.registers[8], self.registers[9], self.registers[10]];
+        let volume_regs = [
+            self.sound_register(8),
+            self.sound_register(9),
+            self.sound_register(10),
+        ];
         let (gated_levels, ungated_levels) =
             self.mixer
-                .compute_levels(volume_regs, envelope_level, accumulated_mask as u32);
+                .compute_levels(volume_regs, envelope_level, gate_mask);
 
         // Compute individual channel outputs
         let mut total_output = 0u32;
@@ -481,9 +739,66 @@ impl Ym2149 {
                 .mixer
                 .compute_channel_output(channel, level_index, ungated_level_index, half_amplitude);
         }
+        total_output
+    }
+
+    /// Average the output over every internal tick of a sample.
+    ///
+    /// By default each output sample is computed once from the register
+    /// values at its end. With oversampling enabled, queued writes are
+    /// applied at their CPU cycle and all internal ticks are averaged,
+    /// which keeps sample replay through the volume registers at rates
+    /// near or above the host rate from aliasing. Costs roughly one mixer
+    /// pass per internal tick.
+    pub fn set_oversampled_output(&mut self, enabled: bool) {
+        self.oversampled_output = enabled;
+    }
+
+    /// Whether oversampled output is enabled.
+    #[must_use]
+    pub fn oversampled_output(&self) -> bool {
+        self.oversampled_output
+    }
+
+    /// Configure the post-mix output filters.
+    ///
+    /// The default chain removes DC with a ~9.5 Hz blocker and applies no
+    /// low-pass. Use [`FilterChain::raw`] to get the unfiltered, unipolar
+    /// chip output for analysis. Changing the chain resets the filter state.
+    pub fn set_filter_chain(&mut self, chain: FilterChain) {
+        self.output_filter = OutputFilter::new(chain, self.sample_rate);
+    }
+
+    /// Get the active output filter configuration.
+    #[must_use]
+    pub fn filter_chain(&self) -> FilterChain {
+        self.output_filter.chain()
+    }
+
+    /// Enable (`Some(capacity)`) or disable (`None`) the per-channel output tap.
+    ///
+    /// While enabled, every generated sample pushes the bipolar A/B/C channel
+    /// outputs into a [`ChannelTap`] ring buffer for oscilloscope displays.
+    /// Re-enabling with the same capacity keeps the recorded samples.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.channel_tap = match capacity {
+            Some(capacity) => match self.channel_tap.take() {
+                Some(tap) if tap.capacity() == capacity => Some(tap),
+                _ => Some(ChannelTap::new(capacity)),
+            },
+            None => None,
+        };
+    }
+
+    /// Get the per-channel output tap, if enabled.
+    #[must_use]
+    pub fn channel_tap(&self) -> Option<&ChannelTap> {
+        self.channel_tap.as_ref()
+    }
 
-        // Apply DC filter and return
-        self.dc_filter.process(total_output as u16)
+    /// Apply an analog output stage preset (see [`OutputModel`]).
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        self.set_filter_chain(model.filter_chain());
     }
 
     /// Synchronize the sample start cycle with the CPU cycle.
@@ -496,6 +811,13 @@ impl Ym2149 {
         self.current_cpu_cycle = cpu_cycle;
     }
 
+    /// CPU cycle at which the next generated sample ends.
+    #[inline]
+    #[must_use]
+    pub fn next_sample_end_cycle(&self) -> u64 {
+        self.sample_start_cycle + self.cpu_cycles_per_sample
+    }
+
     /// Signal entry/exit of timer IRQ handler
     ///
     /// This is used by sync-buzzer effects where the tone period is set to 0 or 1
@@ -516,6 +838,60 @@ impl Ym2149 {
     pub fn inside_timer_irq(&mut self, inside: bool) {
         self.set_timer_irq_state(inside);
     }
+
+    /// Capture the runtime state (see [`Ym2149State`]).
+    #[must_use]
+    pub fn save_state(&self) -> Ym2149State {
+        Ym2149State {
+            cycle_accumulator: self.cycle_accumulator,
+            registers: self.registers,
+            selected_register: self.selected_register,
+            tone_generators: self.tone_generators.clone(),
+            noise_generator: self.noise_generator.clone(),
+            envelope_generator: self.envelope_generator.clone(),
+            mixer_config: self.mixer.config.clone(),
+            channels: self.mixer.channels.clone(),
+            output_filter: self.output_filter.state(),
+            last_sample: self.last_sample,
+            in_timer_irq: self.in_timer_irq,
+            pending_envelope_trigger: self.pending_envelope_trigger,
+            write_queue: self.write_queue.clone(),
+            current_cpu_cycle: self.current_cpu_cycle,
+            last_select_cycle: self.last_select_cycle,
+            sample_start_cycle: self.sample_start_cycle,
+        }
+    }
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    ///
+    /// The chip continues exactly where the saved one was. Channel mutes,
+    /// clocks and the output model are kept; the channel tap is cleared.
+    pub fn load_state(&mut self, state: &Ym2149State) {
+        self.cycle_accumulator = state.cycle_accumulator;
+        self.registers = state.registers;
+        self.selected_register = state.selected_register;
+        self.tone_generators.clone_from(&state.tone_generators);
+        self.noise_generator.clone_from(&state.noise_generator);
+        self.envelope_generator
+            .clone_from(&state.envelope_generator);
+        self.mixer.config.clone_from(&state.mixer_config);
+        for (channel, saved) in self.mixer.channels.iter_mut().zip(&state.channels) {
+            channel.drum_override = saved.drum_override;
+            channel.last_output = saved.last_output;
+            channel.last_amplitude = saved.last_amplitude;
+        }
+        self.output_filter.restore(&state.output_filter);
+        self.last_sample = state.last_sample;
+        self.in_timer_irq = state.in_timer_irq;
+        self.pending_envelope_trigger = state.pending_envelope_trigger;
+        self.write_queue.clone_from(&state.write_queue);
+        self.current_cpu_cycle = state.current_cpu_cycle;
+        self.last_select_cycle = state.last_select_cycle;
+        self.sample_start_cycle = state.sample_start_cycle;
+        if let Some(tap) = self.channel_tap.as_mut() {
+            tap.clear();
+        }
+    }
 }
 
 impl Default for Ym2149 {
@@ -560,14 +936,17 @@ impl Ym2149Backend for Ym2149 {
     }
 
     fn load_registers(&mut self, regs: &[u8; 16]) {
-        for (i, &value) in regs.iter().take(NUM_REGISTERS).enumerate() {
+        for (i, &value) in regs.iter().take(NUM_SOUND_REGISTERS).enumerate() {
             self.write_register(i as u8, value);
         }
     }
 
     fn dump_registers(&self) -> [u8; 16] {
+        // Sound registers only; R14/R15 stay 0 like in register dumps
         let mut out = [0u8; 16];
-        out[..NUM_REGISTERS].copy_from_slice(&self.registers);
+        for (register, value) in out.iter_mut().enumerate().take(NUM_SOUND_REGISTERS) {
+            *value = self.sound_register(register);
+        }
         out
     }
 
@@ -596,8 +975,35 @@ impl Ym2149Backend for Ym2149 {
         // No post filter in this implementation
     }
 
+    fn set_filter_chain(&mut self, chain: FilterChain) {
+        Ym2149::set_filter_chain(self, chain)
+    }
+
+    fn filter_chain(&self) -> FilterChain {
+        Ym2149::filter_chain(self)
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        Ym2149::set_channel_tap(self, capacity)
+    }
+
+    fn channel_tap(&self) -> Option<&ChannelTap> {
+        Ym2149::channel_tap(self)
+    }
+
     fn trigger_envelope(&mut self) {
         self.envelope_generator.trigger();
+        self.pending_envelope_trigger = None;
+    }
+
+    fn trigger_envelope_at(&mut self, offset: f32) {
+        let ticks_per_sample = self.internal_clock as f32 / self.sample_rate.max(1) as f32;
+        let tick = (offset.clamp(0.0, 1.0) * ticks_per_sample) as u32;
+        if tick == 0 {
+            Ym2149Backend::trigger_envelope(self);
+        } else {
+            self.pending_envelope_trigger = Some(tick);
+        }
     }
 
     fn set_drum_sample_override(&mut self, channel: usize, sample: Option<f32>) {
@@ -673,6 +1079,37 @@ mod tests {
         assert!(sample.abs() > 0.0 || chip.last_sample.abs() >= 0.0);
     }
 
+    #[test]
+    fn test_load_state_replays_identically() {
+        let mut chip = Ym2149::new();
+        chip.write_register(0, 0x1C);
+        chip.write_register(6, 0x07);
+        chip.write_register(7, 0x36);
+        chip.write_register(8, 0x10);
+        chip.write_register(11, 0x40);
+        chip.write_register(13, 0x0E);
+        for _ in 0..500 {
+            chip.clock();
+        }
+
+        let state = chip.save_state();
+        let render = |chip: &mut Ym2149| -> Vec<f32> {
+            (0..500)
+                .map(|_| {
+                    chip.clock();
+                    chip.get_sample()
+                })
+                .collect()
+        };
+        let expected = render(&mut chip);
+        chip.load_state(&state);
+        assert_eq!(render(&mut chip), expected);
+
+        chip.set_channel_mute(1, true);
+        chip.load_state(&state);
+        assert!(chip.is_channel_muted(1), "mutes are not part of the state");
+    }
+
     #[test]
     fn test_channel_mute() {
         let mut chip = Ym2149::new();
@@ -709,6 +1146,112 @@ mod tests {
         assert_eq!(chip.read_register(5), 0x0A);
     }
 
+    #[test]
+    fn test_ym2149_reads_back_all_bits() {
+        let mut chip = Ym2149::new();
+        chip.write_register(8, 0xFF);
+        chip.write_register(13, 0x3E);
+        assert_eq!(chip.read_register(8), 0x1F);
+        assert_eq!(chip.read_register(13), 0x0E);
+
+        chip.set_read_config(ReadConfig::YM2149);
+        assert_eq!(chip.read_register(8), 0xFF);
+        assert_eq!(chip.read_register(13), 0x3E);
+        // Sound generation still only sees the valid bits
+        assert_eq!(chip.dump_registers()[8], 0x1F);
+    }
+
+    #[test]
+    fn test_io_port_direction() {
+        let mut chip = Ym2149::new();
+        chip.write_register(14, 0x5A);
+        chip.set_port_input(IoPort::A, 0xF0);
+
+        // Input (R7 bit 6 clear): the pins
+        assert_eq!(chip.read_register(14), 0xF0);
+        assert_eq!(chip.port_output(IoPort::A), None);
+        // Unconnected port B reads pulled-up pins
+        assert_eq!(chip.read_register(15), 0xFF);
+
+        // Output: the latch, with pins pulled low by the device
+        chip.write_register(7, 0x7F);
+        assert_eq!(chip.port_output(IoPort::A), Some(0x5A));
+        assert_eq!(chip.read_register(14), 0x50);
+        chip.set_port_input(IoPort::A, 0xFF);
+        assert_eq!(chip.read_register(14), 0x5A);
+        // Direction bits read back on the AY as well
+        assert_eq!(chip.read_register(7), 0x7F);
+    }
+
+    #[test]
+    fn test_deselected_chip_floats_bus() {
+        let mut chip = Ym2149::new();
+        chip.set_read_config(ReadConfig {
+            floating_bus: 0x00,
+            ..ReadConfig::AY_3_8910
+        });
+        chip.write_port_immediate(0, 5);
+        chip.write_port_immediate(2, 0x0A);
+
+        chip.write_port(0, 0x15);
+        assert_eq!(chip.read_port(0), 0x00);
+        // Data writes are ignored while deselected
+        chip.write_port(2, 0x03);
+        chip.flush_pending_writes();
+        assert_eq!(chip.read_register(5), 0x0A);
+        assert_eq!(chip.read_register(0x15), 0x00);
+
+        chip.write_port(0, 5);
+        assert_eq!(chip.read_port(0), 0x0A);
+    }
+
+    #[test]
+    fn test_port_write_callback_reports_outputs() {
+        use std::sync::Mutex;
+
+        let writes = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&writes);
+        let mut chip = Ym2149::new();
+        chip.on_port_write(move |port, value| sink.lock().unwrap().push((port, value)));
+
+        // Input port: latched silently
+        chip.write_register(14, 0x12);
+        assert!(writes.lock().unwrap().is_empty());
+        // Switching to output drives the latch
+        chip.write_register(7, 0x7F);
+        chip.write_register(7, 0x7E);
+        // Queued writes fire when applied
+        chip.write_port(0, 14);
+        chip.write_port(2, 0x34);
+        assert_eq!(writes.lock().unwrap().len(), 1);
+        chip.flush_pending_writes();
+        assert_eq!(
+            *writes.lock().unwrap(),
+            [(IoPort::A, 0x12), (IoPort::A, 0x34)]
+        );
+
+        chip.clear_port_callbacks();
+        chip.write_register(14, 0x56);
+        assert_eq!(writes.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_port_read_callback_supplies_pins() {
+        let mut chip = Ym2149::new();
+        chip.set_port_input(IoPort::A, 0x00);
+        // Keyboard matrix: no key pressed on port A
+        chip.on_port_read(|port| if port == IoPort::A { 0xFF } else { 0x0F });
+        assert_eq!(chip.read_register(14), 0xFF);
+        assert_eq!(chip.read_register(15), 0x0F);
+
+        chip.write_register(15, 0x3C);
+        chip.write_register(7, 0xBF);
+        assert_eq!(chip.read_register(15), 0x0C);
+
+        chip.clear_port_callbacks();
+        assert_eq!(chip.read_register(14), 0x00);
+    }
+
     #[test]
     fn test_write_queue_timing() {
         let mut chip = Ym2149::new();
@@ -730,4 +1273,107 @@ mod tests {
         assert_eq!(chip.pending_write_count(), 0);
         assert_eq!(chip.read_register(8), 0x0F);
     }
+
+    #[test]
+    fn test_oversampled_output_weights_writes_within_sample() {
+        let render = |oversampled: bool| {
+            let mut chip = Ym2149::new();
+            chip.set_filter_chain(FilterChain::raw());
+            chip.set_oversampled_output(oversampled);
+            chip.sync_sample_cycle(0);
+
+            // Full volume for the first half of the sample, silence after it
+            chip.write_port(0, 8);
+            chip.write_port(2, 0x0F);
+            chip.set_cpu_cycle(90);
+            chip.write_port(2, 0x00);
+            chip.compute_next_sample()
+        };
+
+        let point_sampled = render(false);
+        let oversampled = render(true);
+        let mut chip = Ym2149::new();
+        chip.set_filter_chain(FilterChain::raw());
+        chip.write_register(8, 0x0F);
+        let full = chip.compute_next_sample();
+
+        assert!(point_sampled < full / 8, "{point_sampled} vs {full}");
+        assert!(
+            oversampled > full / 3 && oversampled < full * 2 / 3,
+            "{oversampled} vs {full}"
+        );
+    }
+
+    #[test]
+    fn test_raw_filter_chain_outputs_unipolar_signal() {
+        let mut chip = Ym2149::new();
+        chip.set_filter_chain(FilterChain::raw());
+        assert_eq!(chip.filter_chain(), FilterChain::raw());
+
+        // Constant full volume with all outputs disabled: pure DC level
+        chip.write_register(8, 0x0F);
+        for _ in 0..4096 {
+            assert!(chip.compute_next_sample() > 0);
+        }
+
+        chip.set_filter_chain(FilterChain::default());
+        for _ in 0..8192 {
+            chip.compute_next_sample();
+        }
+        assert!(chip.compute_next_sample().abs() < 10);
+    }
+
+    #[test]
+    fn test_channel_tap_records_real_waveform() {
+        let mut chip = Ym2149::new();
+        assert!(chip.channel_tap().is_none());
+        chip.set_channel_tap(Some(512));
+
+        // Tone on A only (~440 Hz), B and C silent
+        chip.write_register(0, 0x1C);
+        chip.write_register(1, 0x01);
+        chip.write_register(8, 0x0F);
+        chip.write_register(7, 0x3E);
+        for _ in 0..1000 {
+            chip.clock();
+        }
+
+        let tap = chip.channel_tap().expect("tap enabled");
+        assert_eq!(tap.len(), 512);
+
+        let mut a = [0.0; 512];
+        let mut b = [0.0; 512];
+        assert_eq!(tap.read_channel(0, &mut a), 512);
+        tap.read_channel(1, &mut b);
+        assert!(a.iter().any(|&s| s > 0.5) && a.iter().any(|&s| s < -0.5));
+        assert!(b.iter().all(|&s| s == 0.0));
+
+        chip.set_channel_tap(None);
+        assert!(chip.channel_tap().is_none());
+    }
+
+    #[test]
+    fn test_trigger_envelope_at_restarts_mid_sample() {
+        let mut chip = Ym2149::new();
+        // Fastest falling saw: one envelope step per internal tick
+        chip.write_register(11, 0x01);
+        chip.write_register(12, 0x00);
+        chip.write_register(13, 0x08);
+
+        let mut level_after = |offset: Option<f32>| {
+            match offset {
+                Some(offset) => chip.trigger_envelope_at(offset),
+                None => Ym2149Backend::trigger_envelope(&mut chip),
+            }
+            chip.clock();
+            chip.envelope_generator.level()
+        };
+        let full = level_after(None);
+        let half = level_after(Some(0.5));
+        let late = level_after(Some(1.0));
+
+        // A later restart leaves fewer steps of decay before the sample ends
+        assert!(half > full, "{half} <= {full}");
+        assert!(late > half, "{late} <= {half}");
+    }
 }
@@ -3,10 +3,18 @@
 //! The YM2149 output has a DC offset that varies with the audio content.
 //! This filter uses a running average to remove it.
 
-/// History buffer size (2048 samples = ~20ms at 44.1kHz)
+/// Default history buffer size (2048 samples = ~20ms at 44.1kHz)
 const HISTORY_SIZE_BITS: usize = 11;
+#[cfg(test)]
 const HISTORY_SIZE: usize = 1 << HISTORY_SIZE_BITS;
 
+/// Window size limits (16 to 65536 samples)
+const MIN_HISTORY_SIZE_BITS: usize = 4;
+const MAX_HISTORY_SIZE_BITS: usize = 16;
+
+/// Cutoff of an N-sample moving average is roughly `0.443 * sample_rate / N`
+const MOVING_AVERAGE_CUTOFF_FACTOR: f32 = 0.443;
+
 /// DC offset removal filter using a running average
 ///
 /// This filter maintains a circular buffer of recent samples and subtracts
@@ -15,28 +23,50 @@ const HISTORY_SIZE: usize = 1 << HISTORY_SIZE_BITS;
 /// # Implementation Notes
 ///
 /// The `running_sum` field tracks the sum of all samples in the buffer.
-/// Maximum value: `HISTORY_SIZE × u16::MAX = 2048 × 65535 = 134,209,280`
-/// which fits comfortably in a `u32` (max ~4.29 billion).
+/// Maximum value with the largest window: `65536 × 65535 = 4,294,901,760`
+/// which still fits in a `u32` (max 4,294,967,295).
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DcFilter {
-    /// Circular buffer of recent samples
-    buffer: Box<[u16; HISTORY_SIZE]>,
+    /// Circular buffer of recent samples (power-of-two length)
+    buffer: Box<[u16]>,
+    /// log2 of the buffer length
+    window_bits: usize,
     /// Current write position in buffer
     position: usize,
-    /// Running sum of all samples in buffer (max: 2048 × 65535 = 134,209,280)
+    /// Running sum of all samples in buffer
     running_sum: u32,
 }
 
 impl DcFilter {
-    /// Create a new DC filter
+    /// Create a new DC filter with the default 2048-sample window
     pub fn new() -> Self {
+        Self::with_window_bits(HISTORY_SIZE_BITS)
+    }
+
+    /// Create a DC filter averaging over `2^window_bits` samples
+    ///
+    /// The window is clamped to 16..=65536 samples.
+    pub fn with_window_bits(window_bits: usize) -> Self {
+        let window_bits = window_bits.clamp(MIN_HISTORY_SIZE_BITS, MAX_HISTORY_SIZE_BITS);
         Self {
-            buffer: Box::new([0; HISTORY_SIZE]),
+            buffer: vec![0; 1 << window_bits].into_boxed_slice(),
+            window_bits,
             position: 0,
             running_sum: 0,
         }
     }
 
+    /// Create a DC filter whose cutoff is close to `cutoff_hz`
+    ///
+    /// The window length is rounded to the nearest power of two, so the
+    /// effective cutoff may differ from the request by up to a factor of √2.
+    pub fn for_cutoff(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let window = MOVING_AVERAGE_CUTOFF_FACTOR * sample_rate as f32 / cutoff_hz;
+        let window_bits = window.max(1.0).log2().round() as usize;
+        Self::with_window_bits(window_bits)
+    }
+
     /// Process a sample and return the DC-adjusted value
     ///
     /// # Arguments
@@ -56,10 +86,10 @@ impl DcFilter {
         self.buffer[self.position] = sample;
 
         // Advance position with wraparound
-        self.position = (self.position + 1) & (HISTORY_SIZE - 1);
+        self.position = (self.position + 1) & (self.buffer.len() - 1);
 
         // Compute DC offset as average
-        let dc_offset = self.running_sum >> HISTORY_SIZE_BITS;
+        let dc_offset = self.running_sum >> self.window_bits;
 
         // Return sample with DC removed
         (sample as i32 - dc_offset as i32) as i16
@@ -71,6 +101,14 @@ impl DcFilter {
         self.position = 0;
         self.running_sum = 0;
     }
+
+    /// Whether `other` has the same window and consistent history, so its
+    /// state can be copied into this filter
+    pub(crate) fn is_compatible(&self, other: &Self) -> bool {
+        self.window_bits == other.window_bits
+            && other.buffer.len() == 1 << other.window_bits
+            && other.position < other.buffer.len()
+    }
 }
 
 impl Default for DcFilter {
@@ -82,6 +120,7 @@ impl Default for DcFilter {
 impl std::fmt::Debug for DcFilter {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DcFilter")
+            .field("window_bits", &self.window_bits)
             .field("position", &self.position)
             .field("running_sum", &self.running_sum)
             .finish_non_exhaustive()
@@ -129,6 +168,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_dc_filter_window_from_cutoff() {
+        assert_eq!(
+            DcFilter::for_cutoff(9.5, 44_100).window_bits,
+            HISTORY_SIZE_BITS
+        );
+        assert_eq!(DcFilter::for_cutoff(40.0, 44_100).window_bits, 9);
+        assert_eq!(
+            DcFilter::for_cutoff(0.001, 44_100).window_bits,
+            MAX_HISTORY_SIZE_BITS
+        );
+        assert_eq!(
+            DcFilter::for_cutoff(20_000.0, 44_100).window_bits,
+            MIN_HISTORY_SIZE_BITS
+        );
+    }
+
     #[test]
     fn test_dc_filter_reset() {
         let mut filter = DcFilter::new();
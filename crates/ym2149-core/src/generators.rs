@@ -14,6 +14,7 @@ pub const NUM_CHANNELS: usize = 3;
 ///
 /// Each channel has a 12-bit period counter that toggles output when it reaches zero.
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ToneGenerator {
     /// Current counter value
     counter: u32,
@@ -94,8 +95,10 @@ impl ToneGenerator {
 /// Noise generator using 17-bit LFSR
 ///
 /// The noise generator runs at half the tone generator rate and produces
-/// a pseudo-random sequence using XOR feedback.
+/// a pseudo-random sequence using XOR feedback of bits 0 and 3, the layout
+/// verified on AY-3-8910 and YM2149 dies (see MAME's `ay8910.cpp`).
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NoiseGenerator {
     /// Current counter value
     counter: u32,
@@ -129,8 +132,8 @@ impl NoiseGenerator {
 
     /// Tick the generator (runs at half rate)
     ///
-    /// Uses a 17-bit Galois LFSR with taps at bits 13 and 16,
-    /// matching real YM2149/AY-3-8910 hardware.
+    /// Shifts the 17-bit register right, feeding bit 0 XOR bit 3 into bit 16;
+    /// the new bit 0 is the noise output.
     #[inline]
     pub fn tick(&mut self) -> u32 {
         self.half_tick = !self.half_tick;
@@ -140,13 +143,9 @@ impl NoiseGenerator {
             // Period 0 is treated as period 1 on real hardware
             let effective_period = self.period.max(1);
             if self.counter >= effective_period {
-                // Galois LFSR: shift right, XOR taps at bits 13 and 16 when LSB is 1
-                let lsb = self.lfsr & 1;
-                self.lfsr >>= 1;
-                if lsb != 0 {
-                    self.lfsr ^= 0x12000; // Taps at bits 13 (0x2000) and 16 (0x10000)
-                }
-                self.output_mask = if lsb != 0 { !0 } else { 0 };
+                let feedback = (self.lfsr ^ (self.lfsr >> 3)) & 1;
+                self.lfsr = (self.lfsr >> 1) | (feedback << 16);
+                self.output_mask = if self.lfsr & 1 != 0 { !0 } else { 0 };
                 self.counter = 0;
             }
         }
@@ -180,6 +179,7 @@ impl Default for NoiseGenerator {
 /// The envelope provides amplitude modulation using one of 10 unique waveforms
 /// (16 register values map to 10 patterns via SHAPE_TO_ENV).
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EnvelopeGenerator {
     /// Current counter value
     counter: u32,
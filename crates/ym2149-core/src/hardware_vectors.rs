@@ -0,0 +1,228 @@
+//! Generator test vectors from documented hardware behaviour.
+//!
+//! Each test drives one generator at the internal rate (master clock / 8)
+//! and compares it with a reference taken from the datasheet or from
+//! published die and capture analysis:
+//!
+//! - Noise: the 17-bit shift register fed with bit 0 XOR bit 3, starting at
+//!   1 after reset, as verified on AY-3-8910 and YM2149 chips for MAME's
+//!   `ay8910.cpp`. One shift every `2 * NP` ticks (clk / (16 * NP)).
+//! - Tone: square wave of clk / (16 * TP).
+//! - Envelope: the YM2149 datasheet shapes (CONT/ATT/ALT/HOLD) with 32 steps
+//!   per ramp, one step every `EP` ticks (clk / (8 * EP)), so a ramp lasts
+//!   `256 * EP` master clocks, like the 16 steps of clk / (16 * EP) on the
+//!   AY-3-8910.
+//! - Period 0 behaves as period 1 for tone, noise and envelope.
+//!
+//! Documented deviations:
+//!
+//! - Only the YM2149 envelope is emulated. The AY-3-8910 16-step envelope is
+//!   not; its staircase equals the upper four bits of the YM2149 level.
+//! - The step latched by an R13 write is shown one tick shorter than the
+//!   others: the period counter restarts at 0 and levels are read after the
+//!   tick, as in MAME. No capture of that first step is available, so
+//!   `test_envelope_first_step_after_trigger` pins the current timing.
+//! - The noise register starts at 1, MAME's reset value. The power-on state
+//!   of a real chip is not defined; only the sequence and its phase after
+//!   that reset value are checked.
+
+use crate::generators::{EnvelopeGenerator, NoiseGenerator, ToneGenerator};
+
+/// Noise output (0/1) of the first 64 shifts after reset, bit n = shift n.
+const NOISE_FIRST_64: u64 = 0x2404_1002_4001_0000;
+
+/// Noise sequence length of a maximal 17-bit shift register.
+const NOISE_PERIOD: usize = (1 << 17) - 1;
+
+/// Reference shift register: bit 0 XOR bit 3 into bit 16, output bit 0.
+fn reference_noise(state: &mut u32) -> u32 {
+    *state = (*state >> 1) | (((*state ^ (*state >> 3)) & 1) << 16);
+    *state & 1
+}
+
+/// Datasheet envelope level (0-31) `step` steps after writing `shape` to R13.
+fn reference_envelope(shape: u8, step: usize) -> u32 {
+    let (cont, attack, alternate, hold) = (
+        shape & 8 != 0,
+        shape & 4 != 0,
+        shape & 2 != 0,
+        shape & 1 != 0,
+    );
+    let ramp = |up: bool, pos: u32| if up { pos } else { 31 - pos };
+    let (cycle, pos) = (step / 32, (step % 32) as u32);
+    match cycle {
+        0 => ramp(attack, pos),
+        _ if !cont => 0,
+        _ if hold => ramp(attack != alternate, 31),
+        _ => ramp(attack != (alternate && cycle % 2 == 1), pos),
+    }
+}
+
+/// Noise outputs of `shifts` shifts at `period`, one per 2 * period ticks.
+fn noise_outputs(period: u32, shifts: usize) -> Vec<u32> {
+    let mut noise = NoiseGenerator::new();
+    noise.set_period(period);
+    let ticks_per_shift = 2 * period.max(1) as usize;
+    (0..shifts)
+        .map(|_| {
+            for _ in 0..ticks_per_shift {
+                noise.tick();
+            }
+            noise.output_mask() & 1
+        })
+        .collect()
+}
+
+/// Ticks after which the generator's output changed, over `ticks` ticks.
+fn change_ticks(ticks: usize, mut output: impl FnMut() -> u32) -> Vec<usize> {
+    let mut last = None;
+    let mut changes = Vec::new();
+    for tick in 1..=ticks {
+        let value = output();
+        if last.is_some_and(|last| last != value) {
+            changes.push(tick);
+        }
+        last = Some(value);
+    }
+    changes
+}
+
+#[test]
+fn test_noise_matches_published_sequence() {
+    let outputs = noise_outputs(1, 64);
+    let first: u64 = outputs
+        .iter()
+        .enumerate()
+        .map(|(i, &bit)| u64::from(bit) << i)
+        .sum();
+    assert_eq!(first, NOISE_FIRST_64, "{first:#x}");
+
+    // Full period against the reference register
+    let mut noise = NoiseGenerator::new();
+    noise.set_period(1);
+    let mut state = 1;
+    let mut ones = 0;
+    for shift in 0..NOISE_PERIOD {
+        noise.tick();
+        noise.tick();
+        let expected = reference_noise(&mut state);
+        assert_eq!(noise.output_mask() & 1, expected, "shift {shift}");
+        ones += expected;
+    }
+    // Maximal sequence: back at the reset state, 2^16 ones
+    assert_eq!(state, 1);
+    assert_eq!(ones, 1 << 16);
+}
+
+#[test]
+fn test_noise_shift_rate() {
+    // One shift per 2 * NP ticks: the same outputs at any period
+    let reference = noise_outputs(1, 200);
+    for period in [2, 7, 31] {
+        assert_eq!(noise_outputs(period, 200), reference, "NP = {period}");
+    }
+    // NP = 0 runs like NP = 1
+    assert_eq!(noise_outputs(0, 200), reference);
+
+    // Output only changes on shift ticks
+    let mut noise = NoiseGenerator::new();
+    noise.set_period(5);
+    let changes = change_ticks(2000, || noise.tick() & 1);
+    assert!(!changes.is_empty());
+    assert!(
+        changes.iter().all(|tick| (tick - changes[0]) % 10 == 0),
+        "{changes:?}"
+    );
+}
+
+#[test]
+fn test_tone_half_period_is_tp_ticks() {
+    for (period, expected) in [(0, 1), (1, 1), (2, 2), (100, 100), (4095, 4095)] {
+        let mut tone = ToneGenerator::new();
+        tone.set_period(period);
+        let changes = change_ticks(3 * 4095 + 1, || tone.tick(0) & 1);
+        let steps: Vec<usize> = changes.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(
+            !steps.is_empty() && steps.iter().all(|&step| step == expected),
+            "TP = {period}: {steps:?}"
+        );
+    }
+}
+
+#[test]
+fn test_envelope_shapes_match_datasheet() {
+    for shape in 0..16u8 {
+        let mut envelope = EnvelopeGenerator::new();
+        envelope.set_period(1);
+        envelope.set_shape(shape);
+        for step in 0..128 {
+            assert_eq!(
+                envelope.level(),
+                reference_envelope(shape, step),
+                "shape {shape:#x}, step {step}"
+            );
+            envelope.tick();
+        }
+    }
+}
+
+#[test]
+fn test_envelope_step_every_ep_ticks() {
+    for period in [1, 2, 5, 16] {
+        let mut envelope = EnvelopeGenerator::new();
+        envelope.set_period(period);
+        envelope.set_shape(0x0c); // Repeating rising sawtooth
+        let ep = period as usize;
+        let changes = change_ticks(130 * ep, || envelope.tick_level());
+
+        // Steps after the first one last EP ticks (clk / (8 * EP))
+        let steps: Vec<usize> = changes.windows(2).map(|w| w[1] - w[0]).collect();
+        assert!(steps.iter().all(|&step| step == ep), "EP = {period}");
+        // A ramp of 32 steps lasts 32 * EP ticks = 256 * EP master clocks
+        assert_eq!(changes[32] - changes[0], 32 * ep);
+    }
+
+    // EP = 0 runs like EP = 1
+    let mut zero = EnvelopeGenerator::new();
+    let mut one = EnvelopeGenerator::new();
+    zero.set_period(0);
+    one.set_period(1);
+    zero.set_shape(0x0e);
+    one.set_shape(0x0e);
+    for _ in 0..100 {
+        assert_eq!(zero.tick_level(), one.tick_level());
+    }
+}
+
+#[test]
+fn test_envelope_upper_bits_follow_ay_staircase() {
+    // AY-3-8910: 16 steps of 2 * EP ticks (clk / (16 * EP)) per ramp
+    let period = 3;
+    let mut envelope = EnvelopeGenerator::new();
+    envelope.set_period(period);
+    envelope.set_shape(0x08); // Repeating falling sawtooth
+    let changes = change_ticks(100 * period as usize, || envelope.tick_level() >> 1);
+    let steps: Vec<usize> = changes.windows(2).map(|w| w[1] - w[0]).collect();
+    assert!(
+        steps.iter().all(|&step| step == 2 * period as usize),
+        "{steps:?}"
+    );
+}
+
+#[test]
+fn test_envelope_first_step_after_trigger() {
+    let mut envelope = EnvelopeGenerator::new();
+    envelope.set_period(4);
+    envelope.set_shape(0x0d); // Rise and hold
+    let levels: Vec<u32> = (0..9).map(|_| envelope.tick_level()).collect();
+    // Level 0 for EP - 1 ticks after the write, then one step per EP ticks
+    assert_eq!(levels, [0, 0, 0, 1, 1, 1, 1, 2, 2]);
+}
+
+impl EnvelopeGenerator {
+    /// Tick and return the new level.
+    fn tick_level(&mut self) -> u32 {
+        self.tick();
+        self.level()
+    }
+}
@@ -0,0 +1,128 @@
+//! Register read-back and I/O port behaviour
+//!
+//! Reading the PSG back is not a plain copy of what was written:
+//!
+//! - The AY-3-8910 returns unused register bits as 0, while the YM2149
+//!   returns all 8 bits as written. Z80 players use this difference to tell
+//!   the chips apart.
+//! - R14/R15 are the I/O ports A/B. R7 bits 6/7 set their direction: an
+//!   input port reads the levels on its pins, an output port reads its
+//!   output latch. The outputs are open collector, so a device pulling a
+//!   pin low reads as 0 either way.
+//! - While no register is selected (an address with a non-zero upper
+//!   nibble), the chip does not drive the data bus and reads return
+//!   whatever the bus floats to.
+//!
+//! Machines wire devices to the ports (the CPC keyboard matrix on port A,
+//! the Atari ST printer port on port B). Hosts model them with
+//! [`Ym2149::on_port_write`](crate::Ym2149::on_port_write) and
+//! [`Ym2149::on_port_read`](crate::Ym2149::on_port_read).
+
+use std::sync::Arc;
+
+/// One of the two 8-bit I/O ports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IoPort {
+    /// Port A (R14, direction in R7 bit 6)
+    A,
+    /// Port B (R15, direction in R7 bit 7)
+    B,
+}
+
+impl IoPort {
+    /// Data register of the port
+    #[inline]
+    pub fn register(self) -> u8 {
+        match self {
+            IoPort::A => 14,
+            IoPort::B => 15,
+        }
+    }
+
+    /// Port of an I/O register (14 or 15)
+    #[inline]
+    pub fn from_register(register: u8) -> Option<Self> {
+        match register {
+            14 => Some(IoPort::A),
+            15 => Some(IoPort::B),
+            _ => None,
+        }
+    }
+
+    /// R7 bit that makes the port an output
+    #[inline]
+    pub(crate) fn output_bit(self) -> u8 {
+        match self {
+            IoPort::A => 0x40,
+            IoPort::B => 0x80,
+        }
+    }
+}
+
+/// How register reads behave
+///
+/// The default matches the AY-3-8910 with a pulled-up data bus. Reads only
+/// see these settings; sound generation always uses the valid bits.
+///
+/// # Example
+///
+/// ```
+/// use ym2149::{ReadConfig, Ym2149, Ym2149Backend};
+///
+/// let mut chip = Ym2149::new();
+/// chip.write_register(1, 0xFF);
+/// assert_eq!(chip.read_register(1), 0x0F);
+///
+/// chip.set_read_config(ReadConfig::YM2149);
+/// assert_eq!(chip.read_register(1), 0xFF);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadConfig {
+    /// Read unused register bits as 0 (AY-3-8910) instead of as written
+    /// (YM2149)
+    pub mask_unused_bits: bool,
+    /// Value read while no register is selected
+    pub floating_bus: u8,
+}
+
+impl ReadConfig {
+    /// General Instrument AY-3-8910/8912: unused bits read as 0
+    pub const AY_3_8910: Self = Self {
+        mask_unused_bits: true,
+        floating_bus: 0xFF,
+    };
+
+    /// Yamaha YM2149: registers read back all 8 bits
+    pub const YM2149: Self = Self {
+        mask_unused_bits: false,
+        floating_bus: 0xFF,
+    };
+}
+
+impl Default for ReadConfig {
+    fn default() -> Self {
+        Self::AY_3_8910
+    }
+}
+
+/// Host callback for values an output port drives
+pub type PortWriteCallback = Arc<dyn Fn(IoPort, u8) + Send + Sync>;
+
+/// Host callback for the levels on an input port's pins
+pub type PortReadCallback = Arc<dyn Fn(IoPort) -> u8 + Send + Sync>;
+
+/// Port callbacks installed by the host (shared by cloned chips)
+#[derive(Clone, Default)]
+pub(crate) struct PortCallbacks {
+    pub(crate) write: Option<PortWriteCallback>,
+    pub(crate) read: Option<PortReadCallback>,
+}
+
+impl std::fmt::Debug for PortCallbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PortCallbacks")
+            .field("write", &self.write.is_some())
+            .field("read", &self.read.is_some())
+            .finish()
+    }
+}
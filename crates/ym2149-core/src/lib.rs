@@ -35,10 +35,15 @@ mod chip;
 pub mod constants;
 mod dc_filter;
 mod generators;
+mod io;
 mod mixer;
+mod output_filter;
 pub mod psg_bank;
 mod tables;
 
+#[cfg(test)]
+mod hardware_vectors;
+
 /// Error types for YM2149 chip emulator operations
 ///
 /// This enum only contains errors that can occur in the core chip emulation.
@@ -76,7 +81,11 @@ impl From<&str> for Ym2149Error {
 pub type Result<T> = std::result::Result<T, Ym2149Error>;
 
 // Public API exports
-pub use chip::Ym2149;
+pub use chip::{Ym2149, Ym2149State};
 pub use constants::get_volume;
-pub use psg_bank::PsgBank;
-pub use ym2149_common::Ym2149Backend;
+pub use io::{IoPort, PortReadCallback, PortWriteCallback, ReadConfig};
+pub use psg_bank::{PsgBank, PsgId};
+pub use ym2149_common::{
+    CapturingBackend, DEFAULT_DC_CUTOFF_HZ, FilterChain, NullBackend, OutputModel, RegisterWrite,
+    Ym2149Backend,
+};
@@ -14,6 +14,7 @@ const DIGIDRUM_MAX_AMPLITUDE: f32 = 4.0;
 
 /// Mixer configuration from register R7
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MixerConfig {
     /// Tone enable mask (inverted in hardware: 0 = enabled)
     tone_mask: u32,
@@ -38,6 +39,7 @@ impl MixerConfig {
 
 /// Channel state for mixing
 #[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ChannelState {
     /// User mute flag
     pub muted: bool,
@@ -0,0 +1,230 @@
+//! Post-mix output filtering
+//!
+//! Applies the stages described by a [`FilterChain`] to the summed channel
+//! output: DC removal followed by an optional one-pole low-pass.
+
+use crate::dc_filter::DcFilter;
+use ym2149_common::FilterChain;
+
+/// One-pole low-pass filter (`y += a * (x - y)`)
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct OnePoleLowpass {
+    coefficient: f32,
+    state: f32,
+}
+
+impl OnePoleLowpass {
+    fn new(cutoff_hz: f32, sample_rate: u32) -> Self {
+        let coefficient =
+            1.0 - (-2.0 * std::f32::consts::PI * cutoff_hz / sample_rate as f32).exp();
+        Self {
+            coefficient: coefficient.clamp(0.0, 1.0),
+            state: 0.0,
+        }
+    }
+
+    #[inline]
+    fn process(&mut self, sample: f32) -> f32 {
+        self.state += self.coefficient * (sample - self.state);
+        self.state
+    }
+}
+
+/// Runtime state of the filter stages (see [`OutputFilter::state`])
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct OutputFilterState {
+    dc: Option<DcFilter>,
+    lowpass: Option<OnePoleLowpass>,
+}
+
+/// Output filter stages built from a [`FilterChain`]
+#[derive(Clone, Debug)]
+pub struct OutputFilter {
+    chain: FilterChain,
+    dc: Option<DcFilter>,
+    lowpass: Option<OnePoleLowpass>,
+}
+
+impl OutputFilter {
+    /// Build the filter stages for the given chain and sample rate
+    ///
+    /// Non-positive or non-finite cutoffs disable their stage.
+    pub fn new(chain: FilterChain, sample_rate: u32) -> Self {
+        let sample_rate = sample_rate.max(1);
+        let valid = |hz: &f32| hz.is_finite() && *hz > 0.0;
+        Self {
+            chain,
+            dc: chain
+                .dc_cutoff_hz
+                .filter(valid)
+                .map(|hz| DcFilter::for_cutoff(hz, sample_rate)),
+            lowpass: chain
+                .lowpass_cutoff_hz
+                .filter(valid)
+                .map(|hz| OnePoleLowpass::new(hz, sample_rate)),
+        }
+    }
+
+    /// Configuration this filter was built from
+    pub fn chain(&self) -> FilterChain {
+        self.chain
+    }
+
+    /// Filter one summed output sample
+    ///
+    /// Without a DC stage the unipolar chip output is passed through as is
+    /// (0 to 32766).
+    #[inline]
+    pub fn process(&mut self, sample: u16) -> i16 {
+        let centered = match self.dc.as_mut() {
+            Some(dc) => dc.process(sample),
+            None => sample as i16,
+        };
+        match self.lowpass.as_mut() {
+            Some(lowpass) => lowpass
+                .process(centered as f32)
+                .round()
+                .clamp(i16::MIN as f32, i16::MAX as f32) as i16,
+            None => centered,
+        }
+    }
+
+    /// Reset the filter state
+    pub fn reset(&mut self) {
+        if let Some(dc) = self.dc.as_mut() {
+            dc.reset();
+        }
+        if let Some(lowpass) = self.lowpass.as_mut() {
+            lowpass.state = 0.0;
+        }
+    }
+
+    /// Capture the filter memory
+    pub(crate) fn state(&self) -> OutputFilterState {
+        OutputFilterState {
+            dc: self.dc.clone(),
+            lowpass: self.lowpass.clone(),
+        }
+    }
+
+    /// Restore filter memory captured by [`state`](Self::state)
+    ///
+    /// Stages whose configuration changed since the capture are reset
+    /// instead, so the current filter chain is always kept.
+    pub(crate) fn restore(&mut self, state: &OutputFilterState) {
+        match (self.dc.as_mut(), &state.dc) {
+            (Some(dc), Some(saved)) if dc.is_compatible(saved) => {
+                dc.clone_from(saved);
+            }
+            (Some(dc), _) => dc.reset(),
+            (None, _) => {}
+        }
+        match (self.lowpass.as_mut(), &state.lowpass) {
+            (Some(lowpass), Some(saved)) if lowpass.coefficient == saved.coefficient => {
+                lowpass.state = saved.state;
+            }
+            (Some(lowpass), _) => lowpass.state = 0.0,
+            (None, _) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::{OutputModel, STF_LOWPASS_CUTOFF_HZ};
+
+    #[test]
+    fn test_raw_chain_keeps_dc_offset() {
+        let mut filter = OutputFilter::new(FilterChain::raw(), 44_100);
+        for _ in 0..4096 {
+            assert_eq!(filter.process(1000), 1000);
+        }
+    }
+
+    #[test]
+    fn test_default_chain_matches_dc_filter() {
+        let mut filter = OutputFilter::new(FilterChain::default(), 44_100);
+        let mut reference = DcFilter::new();
+        for i in 0..5000u32 {
+            let sample = ((i * 37) % 20_000) as u16;
+            assert_eq!(filter.process(sample), reference.process(sample));
+        }
+    }
+
+    #[test]
+    fn test_lowpass_smooths_steps() {
+        let chain = FilterChain::raw().with_lowpass(Some(1_000.0));
+        let mut filter = OutputFilter::new(chain, 44_100);
+
+        let first = filter.process(10_000);
+        assert!(
+            first > 0 && first < 10_000,
+            "step should be smoothed, got {first}"
+        );
+
+        let mut last = first;
+        for _ in 0..1000 {
+            last = filter.process(10_000);
+        }
+        assert!((last - 10_000).abs() <= 1, "should settle, got {last}");
+    }
+
+    #[test]
+    fn test_restore_resets_stages_with_other_configuration() {
+        let mut filter = OutputFilter::new(FilterChain::raw().with_lowpass(Some(1_000.0)), 44_100);
+        filter.process(10_000);
+        let state = filter.state();
+        let expected = filter.process(0);
+
+        filter.process(20_000);
+        filter.restore(&state);
+        assert_eq!(filter.process(0), expected);
+
+        let mut other = OutputFilter::new(FilterChain::raw().with_lowpass(Some(5_000.0)), 44_100);
+        other.restore(&state);
+        assert_eq!(other.process(0), 0);
+    }
+
+    /// Output RMS over input RMS of a sine at `frequency` Hz
+    fn sine_gain(chain: FilterChain, frequency: f32) -> f32 {
+        const SAMPLE_RATE: u32 = 44_100;
+        let mut filter = OutputFilter::new(chain, SAMPLE_RATE);
+        let amplitude = 10_000.0;
+        let mut sum = 0.0f64;
+        // Settle for one second, then measure a whole number of periods
+        for i in 0..2 * SAMPLE_RATE {
+            let phase = 2.0 * std::f32::consts::PI * frequency * i as f32 / SAMPLE_RATE as f32;
+            let out = filter.process((16_000.0 + amplitude * phase.sin()) as u16) as f64;
+            if i >= SAMPLE_RATE {
+                sum += out * out;
+            }
+        }
+        let rms = (sum / SAMPLE_RATE as f64).sqrt() as f32;
+        rms / (amplitude / std::f32::consts::SQRT_2)
+    }
+
+    #[test]
+    fn test_stf_response_matches_rc_reference() {
+        // First-order RC response of the STF output stage (C10 network)
+        let cutoff = STF_LOWPASS_CUTOFF_HZ;
+        let chain = OutputModel::StfFilter.filter_chain();
+        for frequency in [1_000.0, 5_000.0] {
+            let expected_db = -10.0 * (1.0 + (frequency / cutoff).powi(2)).log10();
+            let measured_db = 20.0 * sine_gain(chain, frequency).log10();
+            assert!(
+                (measured_db - expected_db).abs() < 0.3,
+                "{frequency} Hz: {measured_db:.2} dB, RC reference {expected_db:.2} dB"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_cutoff_disables_stage() {
+        let chain = FilterChain::raw().with_lowpass(Some(f32::NAN));
+        let mut filter = OutputFilter::new(chain, 44_100);
+        assert_eq!(filter.process(1234), 1234);
+    }
+}
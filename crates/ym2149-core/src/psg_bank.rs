@@ -29,6 +29,29 @@ use ym2149_common::Ym2149Backend;
 
 const DEFAULT_SAMPLE_RATE: u32 = 44_100;
 
+/// Stable handle to a chip in a [`PsgBank`].
+///
+/// Positional indices shift when a chip is removed; a `PsgId` keeps
+/// addressing the same chip until that chip itself is removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PsgId(u32);
+
+impl PsgId {
+    /// Returns the raw id value.
+    #[inline]
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+/// Per-chip mixing settings.
+#[derive(Debug, Clone, Copy)]
+struct PsgSlot {
+    id: PsgId,
+    gain: f32,
+    pan: f32,
+}
+
 /// A bank of multiple PSG chips for expanded polyphony.
 ///
 /// Each PSG provides 3 channels (A, B, C), so a bank with N PSGs
@@ -42,12 +65,23 @@ const DEFAULT_SAMPLE_RATE: u32 = 44_100;
 /// - PSG 1: Channels 3 (A), 4 (B), 5 (C)
 /// - PSG 2: Channels 6 (A), 7 (B), 8 (C)
 /// - etc.
+///
+/// Chips can be added and removed at runtime with
+/// [`add_psg`](Self::add_psg) and [`remove_psg`](Self::remove_psg).
+/// Removing a chip renumbers the chips after it; use [`PsgId`] handles to
+/// address a chip independently of its position.
 #[derive(Debug)]
 pub struct PsgBank {
     /// The individual PSG chips
     chips: Vec<Ym2149>,
     /// Clock frequency for each PSG (in Hz)
     frequencies: Vec<u32>,
+    /// Stable id, gain and pan for each PSG
+    slots: Vec<PsgSlot>,
+    /// Id handed out by the next `add_psg`
+    next_id: u32,
+    /// Output sample rate shared by all chips
+    sample_rate: u32,
     /// Scratch buffer reused between calls to avoid per-call allocations
     scratch: Vec<f32>,
 }
@@ -77,16 +111,7 @@ impl PsgBank {
     pub fn new(count: usize, frequency: u32) -> Self {
         assert!(count > 0, "PSG bank must have at least one chip");
 
-        let chips = (0..count)
-            .map(|_| Ym2149::with_clocks(frequency, DEFAULT_SAMPLE_RATE))
-            .collect();
-        let frequencies = vec![frequency; count];
-
-        Self {
-            chips,
-            frequencies,
-            scratch: Vec::new(),
-        }
+        Self::new_with_sample_rate(vec![frequency; count], DEFAULT_SAMPLE_RATE)
     }
 
     /// Creates a new PSG bank where each chip can have a different frequency.
@@ -117,6 +142,26 @@ impl PsgBank {
     /// assert_eq!(bank.channel_count(), 9);
     /// ```
     pub fn new_with_frequencies(frequencies: Vec<u32>) -> Self {
+        Self::new_with_sample_rate(frequencies, DEFAULT_SAMPLE_RATE)
+    }
+
+    /// Creates a bank with per-chip frequencies rendering at `sample_rate`.
+    ///
+    /// The other constructors render at 44.1 kHz.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frequencies` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ym2149::PsgBank;
+    ///
+    /// let bank = PsgBank::new_with_sample_rate(vec![1_000_000, 2_000_000], 48_000);
+    /// assert_eq!(bank.psg_count(), 2);
+    /// ```
+    pub fn new_with_sample_rate(frequencies: Vec<u32>, sample_rate: u32) -> Self {
         assert!(
             !frequencies.is_empty(),
             "PSG bank must have at least one chip"
@@ -124,16 +169,154 @@ impl PsgBank {
 
         let chips = frequencies
             .iter()
-            .map(|&freq| Ym2149::with_clocks(freq, DEFAULT_SAMPLE_RATE))
+            .map(|&freq| Ym2149::with_clocks(freq, sample_rate))
+            .collect();
+        let slots = (0..frequencies.len() as u32)
+            .map(|id| PsgSlot {
+                id: PsgId(id),
+                gain: 1.0,
+                pan: 0.0,
+            })
             .collect();
 
         Self {
             chips,
+            next_id: frequencies.len() as u32,
             frequencies,
+            slots,
+            sample_rate,
             scratch: Vec::new(),
         }
     }
 
+    /// Adds a chip clocked at `frequency` and returns its stable id.
+    ///
+    /// The chip renders at the bank's sample rate, starts at unity gain and
+    /// center pan, and takes the next positional index.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ym2149::PsgBank;
+    ///
+    /// let mut bank = PsgBank::new(1, 2_000_000);
+    /// let id = bank.add_psg(1_773_400);
+    /// assert_eq!(bank.psg_count(), 2);
+    /// assert_eq!(bank.psg_index(id), Some(1));
+    /// ```
+    pub fn add_psg(&mut self, frequency: u32) -> PsgId {
+        let id = PsgId(self.next_id);
+        self.next_id += 1;
+        self.chips
+            .push(Ym2149::with_clocks(frequency, self.sample_rate));
+        self.frequencies.push(frequency);
+        self.slots.push(PsgSlot {
+            id,
+            gain: 1.0,
+            pan: 0.0,
+        });
+        id
+    }
+
+    /// Removes the chip with the given id and returns it.
+    ///
+    /// Chips after it move down one positional index; their ids are
+    /// unchanged. Returns `None` if the id is unknown or if it names the
+    /// last remaining chip, since a bank always holds at least one.
+    pub fn remove_psg(&mut self, id: PsgId) -> Option<Ym2149> {
+        let index = self.psg_index(id)?;
+        if self.chips.len() == 1 {
+            return None;
+        }
+        self.frequencies.remove(index);
+        self.slots.remove(index);
+        Some(self.chips.remove(index))
+    }
+
+    /// Returns the positional index of the chip with the given id.
+    pub fn psg_index(&self, id: PsgId) -> Option<usize> {
+        self.slots.iter().position(|slot| slot.id == id)
+    }
+
+    /// Returns the id of the chip at a positional index.
+    pub fn psg_id(&self, psg_index: usize) -> Option<PsgId> {
+        self.slots.get(psg_index).map(|slot| slot.id)
+    }
+
+    /// Iterates over the ids of all chips in positional order.
+    pub fn psg_ids(&self) -> impl Iterator<Item = PsgId> + '_ {
+        self.slots.iter().map(|slot| slot.id)
+    }
+
+    /// Returns the chip with the given id.
+    pub fn chip(&self, id: PsgId) -> Option<&Ym2149> {
+        self.psg_index(id).map(|index| &self.chips[index])
+    }
+
+    /// Returns the chip with the given id mutably.
+    pub fn chip_mut(&mut self, id: PsgId) -> Option<&mut Ym2149> {
+        self.psg_index(id).map(|index| &mut self.chips[index])
+    }
+
+    /// Returns the clock frequency of the chip with the given id.
+    pub fn frequency(&self, id: PsgId) -> Option<u32> {
+        self.psg_index(id).map(|index| self.frequencies[index])
+    }
+
+    /// Returns the output sample rate shared by all chips.
+    #[inline]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Sets the mix gain of a chip (1.0 = unity). Returns `false` for an unknown id.
+    pub fn set_psg_gain(&mut self, id: PsgId, gain: f32) -> bool {
+        match self.slot_mut(id) {
+            Some(slot) => {
+                slot.gain = gain.max(0.0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the mix gain of a chip.
+    pub fn psg_gain(&self, id: PsgId) -> Option<f32> {
+        self.slot(id).map(|slot| slot.gain)
+    }
+
+    /// Sets the stereo position of a chip, from -1.0 (left) to 1.0 (right).
+    ///
+    /// Only [`generate_samples_stereo`](Self::generate_samples_stereo) uses
+    /// pan. Returns `false` for an unknown id.
+    pub fn set_psg_pan(&mut self, id: PsgId, pan: f32) -> bool {
+        match self.slot_mut(id) {
+            Some(slot) => {
+                slot.pan = pan.clamp(-1.0, 1.0);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the stereo position of a chip.
+    pub fn psg_pan(&self, id: PsgId) -> Option<f32> {
+        self.slot(id).map(|slot| slot.pan)
+    }
+
+    /// Returns the register dump of the chip with the given id.
+    pub fn dump_registers(&self, id: PsgId) -> Option<[u8; 16]> {
+        self.chip(id).map(|chip| chip.dump_registers())
+    }
+
+    fn slot(&self, id: PsgId) -> Option<&PsgSlot> {
+        self.slots.iter().find(|slot| slot.id == id)
+    }
+
+    fn slot_mut(&mut self, id: PsgId) -> Option<&mut PsgSlot> {
+        self.slots.iter_mut().find(|slot| slot.id == id)
+    }
+
     /// Returns the number of PSG chips in this bank.
     #[inline]
     pub fn psg_count(&self) -> usize {
@@ -260,6 +443,7 @@ impl PsgBank {
 
         // First chip: generate directly into buffer (avoids initial fill)
         self.chips[0].generate_samples_into(buffer);
+        let first_gain = self.slots[0].gain;
 
         // Remaining chips: generate to scratch, then add to buffer
         if psg_count > 1 {
@@ -268,25 +452,91 @@ impl PsgBank {
             }
             let scratch = &mut self.scratch[..buffer.len()];
 
-            for chip in &mut self.chips[1..] {
+            if first_gain != 1.0 {
+                for out in buffer.iter_mut() {
+                    *out *= first_gain;
+                }
+            }
+            for (chip, slot) in self.chips[1..].iter_mut().zip(&self.slots[1..]) {
                 chip.generate_samples_into(scratch);
                 for (out, sample) in buffer.iter_mut().zip(scratch.iter()) {
-                    *out += *sample;
+                    *out += *sample * slot.gain;
                 }
             }
+
+            // Normalize by PSG count to prevent clipping (single pass)
+            let scale = 1.0 / psg_count as f32;
+            for sample in buffer.iter_mut() {
+                *sample *= scale;
+            }
+        } else if first_gain != 1.0 {
+            for out in buffer.iter_mut() {
+                *out *= first_gain;
+            }
         }
+    }
 
-        // Normalize by PSG count to prevent clipping (single pass)
-        let scale = 1.0 / psg_count as f32;
+    /// Generates interleaved stereo samples (L, R) with per-chip gain and pan.
+    ///
+    /// A centered chip contributes fully to both sides; panning attenuates
+    /// the opposite side linearly down to silence at ±1.0. The mix is
+    /// normalized by the PSG count like the mono output.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ym2149::PsgBank;
+    ///
+    /// let mut bank = PsgBank::new(2, 2_000_000);
+    /// let right = bank.psg_id(1).unwrap();
+    /// bank.set_psg_pan(right, 1.0);
+    ///
+    /// let mut stereo = vec![0.0f32; 882 * 2];
+    /// bank.generate_samples_stereo(&mut stereo);
+    /// ```
+    pub fn generate_samples_stereo(&mut self, buffer: &mut [f32]) {
+        buffer.fill(0.0);
+        let frames = buffer.len() / 2;
+        if self.scratch.len() < frames {
+            self.scratch.resize(frames, 0.0);
+        }
+        let scratch = &mut self.scratch[..frames];
+
+        for (chip, slot) in self.chips.iter_mut().zip(&self.slots) {
+            chip.generate_samples_into(scratch);
+            let left = slot.gain * (1.0 - slot.pan).min(1.0);
+            let right = slot.gain * (1.0 + slot.pan).min(1.0);
+            for (out, sample) in buffer.chunks_exact_mut(2).zip(scratch.iter()) {
+                out[0] += *sample * left;
+                out[1] += *sample * right;
+            }
+        }
+
+        let scale = 1.0 / self.chips.len() as f32;
         for sample in buffer.iter_mut() {
             *sample *= scale;
         }
     }
 
+    /// Clocks every chip by one sample and returns their mix.
+    ///
+    /// Like [`generate_samples_interleaved`](Self::generate_samples_interleaved),
+    /// the mix applies per-chip gain and is normalized by the PSG count. Use
+    /// this when register writes have to land between individual samples.
+    pub fn clock_sample(&mut self) -> f32 {
+        let mut sum = 0.0;
+        for (chip, slot) in self.chips.iter_mut().zip(&self.slots) {
+            chip.clock();
+            sum += chip.get_sample() * slot.gain;
+        }
+        sum / self.chips.len() as f32
+    }
+
     /// Generates audio samples with each PSG output in a separate buffer.
     ///
     /// This is useful when you want to apply different effects or mixing to each PSG,
-    /// or when rendering to separate audio tracks.
+    /// or when rendering to separate audio tracks. Per-chip gain and pan are
+    /// not applied.
     ///
     /// # Arguments
     ///
@@ -398,6 +648,23 @@ mod tests {
         assert!(has_signal, "Expected non-zero samples");
     }
 
+    #[test]
+    fn test_clock_sample_mixes_all_chips() {
+        let mut bank = PsgBank::new_with_sample_rate(vec![1_000_000, 2_000_000], 22_050);
+        bank.write_register(1, 0x08, 0x0F);
+        let mixed: Vec<f32> = (0..64).map(|_| bank.clock_sample()).collect();
+
+        let mut first = Ym2149::with_clocks(1_000_000, 22_050);
+        let mut second = Ym2149::with_clocks(2_000_000, 22_050);
+        second.write_register(0x08, 0x0F);
+        for mixed in mixed {
+            first.clock();
+            second.clock();
+            let expected = (first.get_sample() + second.get_sample()) / 2.0;
+            assert!((mixed - expected).abs() < 1e-6);
+        }
+    }
+
     #[test]
     fn test_generate_samples_separate() {
         let mut bank = PsgBank::new(2, 2_000_000);
@@ -436,6 +703,56 @@ mod tests {
         assert_eq!(bank.read_register(1, 0x08), 0x00);
     }
 
+    #[test]
+    fn test_add_remove_keeps_ids_stable() {
+        let mut bank = PsgBank::new_with_sample_rate(vec![2_000_000], 48_000);
+        let first = bank.psg_id(0).unwrap();
+        let second = bank.add_psg(1_000_000);
+        let third = bank.add_psg(1_773_400);
+        assert_eq!(bank.psg_count(), 3);
+        assert_eq!(bank.frequency(third), Some(1_773_400));
+
+        bank.write_register(2, 0x08, 0x0C);
+        assert!(bank.remove_psg(second).is_some());
+        assert_eq!(bank.psg_count(), 2);
+        assert_eq!(bank.psg_index(third), Some(1));
+        assert_eq!(bank.dump_registers(third).unwrap()[0x08], 0x0C);
+        assert_eq!(bank.psg_index(second), None);
+        assert!(bank.remove_psg(second).is_none());
+
+        // Ids are never reused
+        assert!(bank.add_psg(2_000_000) > third);
+
+        assert!(bank.remove_psg(third).is_some());
+        let fourth = bank.psg_ids().nth(1).unwrap();
+        assert!(bank.remove_psg(fourth).is_some());
+        assert!(bank.remove_psg(first).is_none(), "last chip stays");
+        assert_eq!(bank.psg_count(), 1);
+    }
+
+    #[test]
+    fn test_gain_and_pan() {
+        let mut bank = PsgBank::new(2, 2_000_000);
+        for i in 0..2 {
+            bank.write_register(i, 0x07, 0x3E);
+            bank.write_register(i, 0x00, 0x1C);
+            bank.write_register(i, 0x08, 0x0F);
+        }
+        let left = bank.psg_id(0).unwrap();
+        let right = bank.psg_id(1).unwrap();
+        assert!(bank.set_psg_pan(left, -1.0));
+        assert!(bank.set_psg_pan(right, 2.0));
+        assert_eq!(bank.psg_pan(right), Some(1.0));
+        assert!(bank.set_psg_gain(right, 0.0));
+
+        let mut stereo = vec![0.0f32; 882 * 2];
+        bank.generate_samples_stereo(&mut stereo);
+        assert!(stereo.chunks_exact(2).any(|frame| frame[0].abs() > 0.01));
+        assert!(stereo.chunks_exact(2).all(|frame| frame[1] == 0.0));
+
+        assert!(!bank.set_psg_gain(PsgId(99), 1.0));
+    }
+
     #[test]
     #[should_panic(expected = "PSG bank must have at least one chip")]
     fn test_empty_bank_panics() {
@@ -77,6 +77,11 @@ pub static REG_MASK: [u8; 14] = [
     0xff, 0x0f, 0xff, 0x0f, 0xff, 0x0f, 0x1f, 0x3f, 0x1f, 0x1f, 0x1f, 0xff, 0xff, 0x0f,
 ];
 
+/// Bits an AY-3-8910 reads back (R7 keeps the I/O direction bits)
+pub static READ_MASK: [u8; 16] = [
+    0xff, 0x0f, 0xff, 0x0f, 0xff, 0x0f, 0x1f, 0xff, 0x1f, 0x1f, 0x1f, 0xff, 0xff, 0x0f, 0xff, 0xff,
+];
+
 /// 32-step logarithmic volume levels (pre-divided by 3 for 3-channel mixing)
 /// Computed using: 1.f / powf(sqrtf(2.f), level * 0.5f)
 #[rustfmt::skip]
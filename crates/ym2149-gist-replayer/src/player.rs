@@ -41,7 +41,10 @@ use crate::gist::TICK_RATE;
 use crate::gist::driver::GistDriver;
 use crate::gist::gist_sound::GistSound;
 use ym2149::{Ym2149, Ym2149Backend};
-use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, MetadataFields, PlaybackState};
+use ym2149_common::{
+    ChiptunePlayer, ChiptunePlayerBase, MetadataFields, OutputGain, OutputModel, PlaybackRate,
+    PlaybackState,
+};
 
 // Re-export the standard sample rate from ym2149-common
 pub use ym2149_common::DEFAULT_SAMPLE_RATE;
@@ -96,6 +99,10 @@ pub struct GistPlayer {
     sample_rate: u32,
     /// Tick accumulator for timing (fixed-point)
     tick_accumulator: u32,
+    /// Output gain applied to rendered buffers
+    output_gain: OutputGain,
+    /// Playback rate (speed and pitch) applied to rendered buffers
+    playback_rate: PlaybackRate,
 }
 
 impl Default for GistPlayer {
@@ -121,6 +128,8 @@ impl GistPlayer {
             driver: GistDriver::new(),
             sample_rate,
             tick_accumulator: 0,
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
         }
     }
 
@@ -353,6 +362,15 @@ impl GistPlayer {
     ///
     /// * `buffer` - Mutable slice to fill with samples
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| self.render_native(source));
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+    }
+
+    /// Render samples at the original driver speed.
+    fn render_native(&mut self, buffer: &mut [f32]) {
         for sample in buffer.iter_mut() {
             // Advance tick timing
             self.tick_accumulator += TICK_RATE;
@@ -516,6 +534,44 @@ impl ChiptunePlayerBase for GistPlayer {
         self.chip.is_channel_muted(channel)
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.chip.set_output_model(model);
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.chip.set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.chip
+            .channel_tap()
+            .map_or(0, |tap| tap.read_channel(channel, out))
+    }
+
     fn playback_position(&self) -> f32 {
         // GIST sounds don't have a fixed duration/position
         0.0
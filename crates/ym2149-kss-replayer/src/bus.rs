@@ -0,0 +1,186 @@
+//! MSX memory, bank mapper and PSG ports as the KSS driver sees them.
+
+use ym2149::{PsgBank, Ym2149};
+use ym2149_z80::Z80Bus;
+
+use crate::format::{BankSize, KssFile};
+
+/// Size of the Z80 address space.
+const MEMORY_SIZE: usize = 0x10000;
+
+/// PSG address latch, data write and data read ports.
+const PSG_ADDRESS_PORT: u8 = 0xA0;
+const PSG_WRITE_PORT: u8 = 0xA1;
+const PSG_READ_PORT: u8 = 0xA2;
+/// 16K bank select port.
+const BANK_PORT: u8 = 0xFE;
+/// FM-PAC (YM2413) and MSX-AUDIO (Y8950) ports.
+const FMPAC_PORTS: [u8; 2] = [0x7C, 0x7D];
+const MSX_AUDIO_PORTS: [u8; 2] = [0xC0, 0xC1];
+/// 8K bank select addresses and the window each one maps.
+const BANK_8K_SELECTS: [(u16, u16); 2] = [(0x9000, 0x8000), (0xB000, 0xA000)];
+/// SCC registers in the 0x8000 8K window.
+const SCC_REGISTERS: std::ops::RangeInclusive<u16> = 0x9800..=0x98FF;
+/// Start of the 16K bank window.
+const BANK_16K_WINDOW: u16 = 0x8000;
+
+/// MSX BIOS entries KSS drivers call, and the stubs behind them.
+const WRTPSG: u16 = 0x0093;
+const RDPSG: u16 = 0x0096;
+const WRTPSG_STUB: u16 = 0x0100;
+const RDPSG_STUB: u16 = 0x0110;
+
+/// Sound chips a song used that are not emulated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct SilentChips {
+    pub(crate) scc: bool,
+    pub(crate) fmpac: bool,
+    pub(crate) msx_audio: bool,
+}
+
+/// Memory and PSG of the emulated MSX.
+pub(crate) struct MsxBus {
+    memory: Box<[u8; MEMORY_SIZE]>,
+    psgs: PsgBank,
+    bank_size: BankSize,
+    /// Every bank of the file, indexed by bank number.
+    banks: Vec<Option<Vec<u8>>>,
+    silent: SilentChips,
+}
+
+impl MsxBus {
+    /// Create the bus with one PSG at `psg_clock`.
+    pub(crate) fn new(file: &KssFile, psg_clock: u32, sample_rate: u32) -> Self {
+        let banks = (0..=u8::MAX)
+            .map(|number| file.bank(number).map(<[u8]>::to_vec))
+            .collect();
+        Self {
+            memory: Box::new([0; MEMORY_SIZE]),
+            psgs: PsgBank::new_with_sample_rate(vec![psg_clock], sample_rate),
+            bank_size: file.header.bank_size,
+            banks,
+            silent: SilentChips::default(),
+        }
+    }
+
+    /// Lay out memory: `RET` over page 0 with the PSG BIOS entries, then
+    /// the load image; reset the PSG.
+    pub(crate) fn reset(&mut self, file: &KssFile) {
+        self.memory.fill(0);
+        self.memory[..0x4000].fill(0xC9);
+        let wrtpsg = [
+            0xD3,
+            PSG_ADDRESS_PORT, // out (0xA0),a
+            0xF5,             // push af
+            0x7B,             // ld a,e
+            0xD3,
+            PSG_WRITE_PORT, // out (0xA1),a
+            0xF1,           // pop af
+            0xC9,           // ret
+        ];
+        let rdpsg = [
+            0xD3,
+            PSG_ADDRESS_PORT, // out (0xA0),a
+            0xDB,
+            PSG_READ_PORT, // in a,(0xA2)
+            0xC9,          // ret
+        ];
+        self.install(WRTPSG_STUB, &wrtpsg);
+        self.install(RDPSG_STUB, &rdpsg);
+        for (entry, stub) in [(WRTPSG, WRTPSG_STUB), (RDPSG, RDPSG_STUB)] {
+            let [low, high] = stub.to_le_bytes();
+            self.install(entry, &[0xC3, low, high]);
+        }
+
+        let start = usize::from(file.header.load_address);
+        let end = (start + file.load_data.len()).min(MEMORY_SIZE);
+        self.memory[start..end].copy_from_slice(&file.load_data[..end - start]);
+
+        self.psgs.reset();
+        self.silent = SilentChips::default();
+    }
+
+    fn install(&mut self, address: u16, code: &[u8]) {
+        let start = usize::from(address);
+        self.memory[start..start + code.len()].copy_from_slice(code);
+    }
+
+    /// Copy bank `number` into the window at `window`; unknown banks are
+    /// ignored.
+    fn select_bank(&mut self, window: u16, number: u8) {
+        let Some(Some(bank)) = self.banks.get(usize::from(number)) else {
+            return;
+        };
+        let start = usize::from(window);
+        let size = self.bank_size.bytes();
+        self.memory[start..start + bank.len()].copy_from_slice(bank);
+        self.memory[start + bank.len()..start + size].fill(0);
+    }
+
+    /// The PSG.
+    pub(crate) fn chip(&self) -> &Ym2149 {
+        self.psgs.get_chip(0)
+    }
+
+    /// The PSG (mutable).
+    pub(crate) fn chip_mut(&mut self) -> &mut Ym2149 {
+        self.psgs.get_chip_mut(0)
+    }
+
+    /// Clock the PSG by one sample.
+    pub(crate) fn clock_sample(&mut self) -> f32 {
+        self.psgs.clock_sample()
+    }
+
+    /// Sound chips accessed since the last reset that are not emulated.
+    pub(crate) fn silent_chips(&self) -> SilentChips {
+        self.silent
+    }
+}
+
+impl Z80Bus for MsxBus {
+    fn peek(&self, address: u16) -> u8 {
+        self.memory[usize::from(address)]
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        if SCC_REGISTERS.contains(&address) {
+            self.silent.scc = true;
+        }
+        if self.bank_size == BankSize::Size8K {
+            if let Some(&(_, window)) = BANK_8K_SELECTS
+                .iter()
+                .find(|&&(select, _)| select == address)
+            {
+                self.select_bank(window, value);
+                return;
+            }
+            // Konami's 8K mapper puts the SCC registers over the bank
+            if SCC_REGISTERS.contains(&address) {
+                return;
+            }
+        }
+        self.memory[usize::from(address)] = value;
+    }
+
+    fn port_in(&mut self, address: u16) -> u8 {
+        if address as u8 == PSG_READ_PORT {
+            self.chip().read_port(0)
+        } else {
+            0xFF
+        }
+    }
+
+    fn port_out(&mut self, address: u16, value: u8) {
+        match address as u8 {
+            PSG_ADDRESS_PORT => self.chip_mut().write_port_immediate(0, value & 0x0F),
+            PSG_WRITE_PORT => self.chip_mut().write_port_immediate(2, value),
+            BANK_PORT if self.bank_size == BankSize::Size16K => {
+                self.select_bank(BANK_16K_WINDOW, value);
+            }
+            port if FMPAC_PORTS.contains(&port) => self.silent.fmpac = true,
+            port if MSX_AUDIO_PORTS.contains(&port) => self.silent.msx_audio = true,
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Error handling for the KSS replayer.
+
+use thiserror::Error;
+
+/// Convenient result alias for KSS parsing and playback.
+pub type Result<T> = std::result::Result<T, KssError>;
+
+/// Errors that may occur while parsing or replaying KSS files.
+#[derive(Debug, Error)]
+pub enum KssError {
+    /// File does not start with `KSCC` or `KSSX`.
+    #[error("KSS file must start with KSCC or KSSX")]
+    InvalidFileId,
+    /// Data ended inside the header or the load image.
+    #[error("unexpected end of file at offset 0x{offset:04x}")]
+    UnexpectedEof {
+        /// Offset of the structure that was cut short.
+        offset: usize,
+    },
+    /// The file drives an SN76489 instead of the PSG (Sega Master System
+    /// and Game Gear rips).
+    #[error("KSS files for the SN76489 (Sega) are not supported")]
+    UnsupportedSn76489,
+    /// Requested track is outside the file's track range.
+    #[error("track index {index} out of range (file has {available} tracks)")]
+    TrackOutOfRange {
+        /// Requested 0-based track index.
+        index: usize,
+        /// Number of tracks in the file.
+        available: usize,
+    },
+    /// Generic validation error.
+    #[error("{msg}")]
+    InvalidData {
+        /// Human-readable explanation of the validation failure.
+        msg: String,
+    },
+}
+
+impl From<String> for KssError {
+    fn from(s: String) -> Self {
+        KssError::InvalidData { msg: s }
+    }
+}
+
+impl From<&str> for KssError {
+    fn from(s: &str) -> Self {
+        KssError::InvalidData { msg: s.to_string() }
+    }
+}
@@ -0,0 +1,367 @@
+//! KSS (`KSCC`/`KSSX`) file structures.
+//!
+//! All values are little-endian. The 16-byte header is followed by the
+//! load image and then the ROM banks:
+//!
+//! ```text
+//! 0x00  4   "KSCC" or "KSSX"
+//! 0x04  u16 load address
+//! 0x06  u16 load size
+//! 0x08  u16 init address (called with A = track number)
+//! 0x0A  u16 play address (called once per frame)
+//! 0x0C  u8  first bank number
+//! 0x0D  u8  bank count; bit 7 set: 8K banks, clear: 16K banks
+//! 0x0E  u8  KSSX: size of the extra header (0 or 0x10)
+//! 0x0F  u8  devices: bit 0 FM-PAC, bit 1 SN76489, bit 2 RAM mode,
+//!           bit 3 MSX-AUDIO, bit 6 PAL (KSSX)
+//! ```
+//!
+//! The KSSX extra header:
+//!
+//! ```text
+//! 0x10  u32 data size
+//! 0x14  u32 reserved
+//! 0x18  u16 first track
+//! 0x1A  u16 last track
+//! 0x1C  i8  PSG volume, then SCC, MSX-MUSIC and MSX-AUDIO volumes
+//! ```
+//!
+//! 16K banks appear at 0x8000-0xBFFF when their number is written to port
+//! 0xFE; 8K banks appear at 0x8000 or 0xA000 on writes to 0x9000 or
+//! 0xB000.
+
+use crate::error::{KssError, Result};
+
+/// Size of the `KSCC`/`KSSX` header.
+pub const HEADER_SIZE: usize = 0x10;
+
+/// Size of the KSSX extra header this parser understands.
+const KSSX_EXTRA_SIZE: usize = 0x10;
+
+/// Number of tracks of a `KSCC` file (the driver gets 0-255 in A).
+const KSCC_TRACKS: usize = 256;
+
+/// Header variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KssVariant {
+    /// Original format without track range.
+    Kscc,
+    /// Extended format, optionally with a track range and volumes.
+    Kssx,
+}
+
+impl KssVariant {
+    /// Magic string ("KSCC" or "KSSX").
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Kscc => "KSCC",
+            Self::Kssx => "KSSX",
+        }
+    }
+}
+
+/// Size of the switchable ROM banks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankSize {
+    /// 16K banks at 0x8000, selected through port 0xFE.
+    Size16K,
+    /// 8K banks at 0x8000 and 0xA000, selected by writes to 0x9000/0xB000.
+    Size8K,
+}
+
+impl BankSize {
+    /// Bank size in bytes.
+    pub fn bytes(self) -> usize {
+        match self {
+            Self::Size16K => 0x4000,
+            Self::Size8K => 0x2000,
+        }
+    }
+}
+
+/// Sound devices and options flagged in the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct KssDevices(pub u8);
+
+impl KssDevices {
+    /// FM-PAC (YM2413) present.
+    pub fn fmpac(self) -> bool {
+        self.0 & 0x01 != 0
+    }
+
+    /// SN76489 (Sega) instead of the MSX PSG.
+    pub fn sn76489(self) -> bool {
+        self.0 & 0x02 != 0
+    }
+
+    /// Page 0 (0x0000-0x3FFF) is RAM instead of the BIOS.
+    pub fn ram_mode(self) -> bool {
+        !self.sn76489() && self.0 & 0x04 != 0
+    }
+
+    /// MSX-AUDIO (Y8950) present.
+    pub fn msx_audio(self) -> bool {
+        !self.sn76489() && self.0 & 0x08 != 0
+    }
+}
+
+/// Extra header of `KSSX` files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KssxHeader {
+    /// Size of the load image and banks.
+    pub data_size: u32,
+    /// First track number.
+    pub first_track: u16,
+    /// Last track number.
+    pub last_track: u16,
+    /// PSG volume adjustment.
+    pub psg_volume: i8,
+    /// SCC volume adjustment.
+    pub scc_volume: i8,
+    /// MSX-MUSIC (FM-PAC) volume adjustment.
+    pub msx_music_volume: i8,
+    /// MSX-AUDIO volume adjustment.
+    pub msx_audio_volume: i8,
+}
+
+/// Parsed KSS header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KssHeader {
+    /// Header variant.
+    pub variant: KssVariant,
+    /// Z80 address of the load image.
+    pub load_address: u16,
+    /// Size of the load image.
+    pub load_size: u16,
+    /// Init routine, called with A = track number.
+    pub init_address: u16,
+    /// Play routine, called once per frame.
+    pub play_address: u16,
+    /// Number of the first bank.
+    pub first_bank: u8,
+    /// Number of banks.
+    pub bank_count: u8,
+    /// Size of the banks.
+    pub bank_size: BankSize,
+    /// Sound devices and options.
+    pub devices: KssDevices,
+    /// Whether the song runs at 50 Hz (KSSX) instead of the MSX's 60 Hz.
+    pub pal: bool,
+    /// `KSSX` extra header, if present.
+    pub extra: Option<KssxHeader>,
+}
+
+/// Parsed KSS file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KssFile {
+    /// File header.
+    pub header: KssHeader,
+    /// Load image, copied to [`KssHeader::load_address`].
+    pub load_data: Vec<u8>,
+    /// Bank data, [`BankSize::bytes`] per bank (the last may be short).
+    pub bank_data: Vec<u8>,
+}
+
+impl KssFile {
+    /// Number of tracks the driver can play.
+    pub fn track_count(&self) -> usize {
+        match self.header.extra {
+            Some(extra) if extra.last_track >= extra.first_track && extra.last_track > 0 => {
+                usize::from(extra.last_track - extra.first_track) + 1
+            }
+            _ => KSCC_TRACKS,
+        }
+    }
+
+    /// Track number passed to the init routine for track `index` (0-based).
+    pub fn track_number(&self, index: usize) -> Option<u8> {
+        if index >= self.track_count() {
+            return None;
+        }
+        let first = self.header.extra.map_or(0, |extra| extra.first_track);
+        u8::try_from(usize::from(first) + index).ok()
+    }
+
+    /// Contents of bank `number`, `None` outside the file's bank range.
+    ///
+    /// Banks cut short by the end of the file return what is there.
+    pub fn bank(&self, number: u8) -> Option<&[u8]> {
+        let index = number.checked_sub(self.header.first_bank)?;
+        if index >= self.header.bank_count {
+            return None;
+        }
+        let size = self.header.bank_size.bytes();
+        let start = (usize::from(index) * size).min(self.bank_data.len());
+        let end = (start + size).min(self.bank_data.len());
+        Some(&self.bank_data[start..end])
+    }
+}
+
+/// Parse a KSS file.
+pub fn load_kss(data: &[u8]) -> Result<KssFile> {
+    let header = data
+        .get(..HEADER_SIZE)
+        .ok_or(KssError::UnexpectedEof { offset: 0 })?;
+    let variant = match &header[..4] {
+        b"KSCC" => KssVariant::Kscc,
+        b"KSSX" => KssVariant::Kssx,
+        _ => return Err(KssError::InvalidFileId),
+    };
+    let word = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+    let devices = KssDevices(header[0x0F]);
+    if devices.sn76489() {
+        return Err(KssError::UnsupportedSn76489);
+    }
+
+    let mut data_start = HEADER_SIZE;
+    let mut extra = None;
+    if variant == KssVariant::Kssx && header[0x0E] != 0 {
+        let extra_size = usize::from(header[0x0E]);
+        let raw =
+            data.get(HEADER_SIZE..HEADER_SIZE + extra_size)
+                .ok_or(KssError::UnexpectedEof {
+                    offset: HEADER_SIZE,
+                })?;
+        if extra_size >= KSSX_EXTRA_SIZE {
+            let dword = |offset: usize| {
+                u32::from_le_bytes([
+                    raw[offset],
+                    raw[offset + 1],
+                    raw[offset + 2],
+                    raw[offset + 3],
+                ])
+            };
+            let word = |offset: usize| u16::from_le_bytes([raw[offset], raw[offset + 1]]);
+            extra = Some(KssxHeader {
+                data_size: dword(0x00),
+                first_track: word(0x08),
+                last_track: word(0x0A),
+                psg_volume: raw[0x0C] as i8,
+                scc_volume: raw[0x0D] as i8,
+                msx_music_volume: raw[0x0E] as i8,
+                msx_audio_volume: raw[0x0F] as i8,
+            });
+        }
+        data_start += extra_size;
+    }
+
+    let load_size = word(0x06);
+    let load_end = data_start + usize::from(load_size);
+    let load_data = data
+        .get(data_start..load_end)
+        .ok_or(KssError::UnexpectedEof { offset: data_start })?
+        .to_vec();
+
+    let banks = header[0x0D];
+    let bank_size = if banks & 0x80 != 0 {
+        BankSize::Size8K
+    } else {
+        BankSize::Size16K
+    };
+    let bank_count = banks & 0x7F;
+    let bank_end = load_end + usize::from(bank_count) * bank_size.bytes();
+    let bank_data = data[load_end..bank_end.min(data.len())].to_vec();
+
+    Ok(KssFile {
+        header: KssHeader {
+            variant,
+            load_address: word(0x04),
+            load_size,
+            init_address: word(0x08),
+            play_address: word(0x0A),
+            first_bank: header[0x0C],
+            bank_count,
+            bank_size,
+            devices,
+            pal: variant == KssVariant::Kssx && devices.0 & 0x40 != 0,
+            extra,
+        },
+        load_data,
+        bank_data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(magic: &[u8; 4], load_size: u16, banks: u8, extra_size: u8) -> Vec<u8> {
+        let mut data = magic.to_vec();
+        data.extend_from_slice(&0x4000u16.to_le_bytes());
+        data.extend_from_slice(&load_size.to_le_bytes());
+        data.extend_from_slice(&0x4000u16.to_le_bytes());
+        data.extend_from_slice(&0x4001u16.to_le_bytes());
+        data.extend_from_slice(&[4, banks, extra_size, 0x01]);
+        data
+    }
+
+    #[test]
+    fn test_parse_kscc_with_banks() {
+        let mut data = header(b"KSCC", 2, 2, 0);
+        data.extend_from_slice(&[0xC9, 0xC9]);
+        data.extend(std::iter::repeat_n(0x11, 0x4000));
+        data.extend(std::iter::repeat_n(0x22, 0x100));
+
+        let file = load_kss(&data).unwrap();
+        assert_eq!(file.header.variant, KssVariant::Kscc);
+        assert_eq!(file.header.init_address, 0x4000);
+        assert_eq!(file.header.play_address, 0x4001);
+        assert_eq!(file.header.bank_size, BankSize::Size16K);
+        assert!(file.header.devices.fmpac());
+        assert!(!file.header.pal);
+        assert_eq!(file.load_data, [0xC9, 0xC9]);
+        assert_eq!(file.track_count(), 256);
+        assert_eq!(file.track_number(255), Some(255));
+        assert_eq!(file.bank(3), None);
+        assert_eq!(file.bank(4).map(<[u8]>::len), Some(0x4000));
+        // The second bank is cut short by the end of the file
+        assert_eq!(file.bank(5), Some(&[0x22; 0x100][..]));
+        assert_eq!(file.bank(6), None);
+    }
+
+    #[test]
+    fn test_parse_kssx_track_range() {
+        let mut data = header(b"KSSX", 1, 0x81, 0x10);
+        data[0x0F] = 0x40;
+        let mut extra = vec![0u8; 0x10];
+        extra[0x08..0x0A].copy_from_slice(&3u16.to_le_bytes());
+        extra[0x0A..0x0C].copy_from_slice(&7u16.to_le_bytes());
+        extra[0x0C] = 0xFE;
+        data.extend(extra);
+        data.push(0xC9);
+
+        let file = load_kss(&data).unwrap();
+        assert_eq!(file.header.variant, KssVariant::Kssx);
+        assert_eq!(file.header.bank_size, BankSize::Size8K);
+        assert!(file.header.pal);
+        assert_eq!(file.header.extra.unwrap().psg_volume, -2);
+        assert_eq!(file.load_data, [0xC9]);
+        assert_eq!(file.track_count(), 5);
+        assert_eq!(file.track_number(0), Some(3));
+        assert_eq!(file.track_number(5), None);
+        assert!(file.bank_data.is_empty());
+    }
+
+    #[test]
+    fn test_rejects_malformed_files() {
+        assert!(matches!(
+            load_kss(b"KSCC"),
+            Err(KssError::UnexpectedEof { offset: 0 })
+        ));
+        assert!(matches!(
+            load_kss(&header(b"NSFE", 0, 0, 0)),
+            Err(KssError::InvalidFileId)
+        ));
+        assert!(matches!(
+            load_kss(&header(b"KSCC", 4, 0, 0)),
+            Err(KssError::UnexpectedEof { offset: 0x10 })
+        ));
+        let mut sega = header(b"KSCC", 0, 0, 0);
+        sega[0x0F] = 0x02;
+        assert!(matches!(load_kss(&sega), Err(KssError::UnsupportedSn76489)));
+        assert!(matches!(
+            load_kss(&header(b"KSSX", 0, 0, 0x10)),
+            Err(KssError::UnexpectedEof { offset: 0x10 })
+        ));
+    }
+}
@@ -0,0 +1,47 @@
+//! KSS file parser and Z80-based PSG player.
+//!
+//! KSS files are rips of MSX game music: the game's own Z80 sound driver
+//! plus its data, with an init and a play entry point. This crate runs
+//! the driver on the workspace's Z80 core and feeds the MSX PSG (an
+//! AY-3-8910 compatible at ports 0xA0-0xA2) into the `ym2149` chip:
+//!
+//! - [`load_kss`] parses `KSCC` and `KSSX` files, see [`format`] for the
+//!   layout, including 8K and 16K ROM banks
+//! - [`KssPlayer`] calls the driver at 60 Hz (50 Hz for PAL KSSX files)
+//!   and implements [`ChiptunePlayer`]; tracks are its subsongs
+//!
+//! Only the PSG is emulated. SCC, FM-PAC and MSX-AUDIO parts stay silent
+//! ([`KssPlayer::silent_chips`] lists the ones a song uses), and files for
+//! the Sega SN76489 are rejected.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use ym2149_kss_replayer::KssPlayer;
+//!
+//! let data = std::fs::read("music.kss")?;
+//! let mut player = KssPlayer::load_from_bytes(&data, 0)?;
+//!
+//! player.play();
+//! let samples = player.generate_samples(735);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Parsing never panics on malformed data: the header and load image are
+//! bounds-checked and rejected with a [`KssError`].
+
+#![warn(missing_docs)]
+
+mod bus;
+pub mod error;
+pub mod format;
+mod player;
+
+pub use crate::error::{KssError, Result};
+pub use crate::format::{
+    BankSize, KssDevices, KssFile, KssHeader, KssVariant, KssxHeader, load_kss,
+};
+pub use crate::player::KssPlayer;
+
+// Re-export unified player traits from ym2149-common
+pub use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, PlaybackState};
@@ -0,0 +1,628 @@
+//! High-level KSS song player (Z80 + PSG).
+
+use std::mem;
+
+use crate::bus::MsxBus;
+use crate::error::{KssError, Result};
+use crate::format::KssFile;
+use ym2149::Ym2149Backend;
+use ym2149_common::{
+    BasicMetadata, ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE,
+    LoadReport, OutputGain, OutputModel, PlaybackRate, PlaybackState, RegisterDelta,
+};
+use ym2149_z80::{RegisterPair, Timing, Z80, Z80Bus, Z80Cpu};
+
+const SAMPLE_RATE: u32 = DEFAULT_SAMPLE_RATE;
+/// MSX Z80 clock (NTSC colour subcarrier).
+const CPU_CLOCK: u32 = 3_579_545;
+/// MSX PSG clock: half the Z80 clock.
+const PSG_CLOCK: u32 = 1_789_772;
+const NTSC_FRAME_RATE: u32 = 60;
+const PAL_FRAME_RATE: u32 = 50;
+const RETURN_ADDRESS: u16 = 0x0000;
+/// Stack pointer the MSX BIOS leaves for programs.
+const STACK_ADDRESS: u16 = 0xF380;
+const MAX_INSTRUCTIONS_PER_CALL: usize = 250_000;
+
+/// High-level KSS song player.
+///
+/// Runs the file's Z80 driver on an MSX with its PSG at ports 0xA0-0xA2
+/// and the BIOS `WRTPSG`/`RDPSG` entries. The init routine is called with
+/// the track number in A, then the play routine once per frame (60 Hz, or
+/// 50 Hz for PAL KSSX files).
+pub struct KssPlayer {
+    file: KssFile,
+    metadata: BasicMetadata,
+    track_index: usize,
+    bus: MsxBus,
+    z80: Z80,
+    sample_cache: Vec<f32>,
+    cache_pos: usize,
+    cache_len: usize,
+    frame_counter: usize,
+    state: PlaybackState,
+    init_executed: bool,
+    sample_period: f64,
+    output_gain: OutputGain,
+    playback_rate: PlaybackRate,
+}
+
+impl KssPlayer {
+    /// Create a player for track `track_index` (0-based) of `file`.
+    pub fn new(file: KssFile, track_index: usize) -> Result<Self> {
+        check_track(&file, track_index)?;
+        let frame_rate = if file.header.pal {
+            PAL_FRAME_RATE
+        } else {
+            NTSC_FRAME_RATE
+        };
+        let timing = Timing::uncontended((CPU_CLOCK + frame_rate / 2) / frame_rate);
+        let mut player = Self {
+            metadata: build_metadata(&file, track_index, frame_rate),
+            track_index,
+            bus: MsxBus::new(&file, PSG_CLOCK, SAMPLE_RATE),
+            file,
+            z80: Z80::new(timing),
+            sample_cache: Vec::new(),
+            cache_pos: 0,
+            cache_len: 0,
+            frame_counter: 0,
+            state: PlaybackState::Stopped,
+            init_executed: false,
+            sample_period: 1.0 / SAMPLE_RATE as f64,
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
+        };
+        player.reset_runtime();
+        Ok(player)
+    }
+
+    /// Parse a KSS file and create a player for track `track_index`.
+    pub fn load_from_bytes(data: &[u8], track_index: usize) -> Result<Self> {
+        Self::new(crate::format::load_kss(data)?, track_index)
+    }
+
+    /// Access the parsed file.
+    pub fn file(&self) -> &KssFile {
+        &self.file
+    }
+
+    /// Access metadata.
+    pub fn metadata(&self) -> &BasicMetadata {
+        &self.metadata
+    }
+
+    /// Current track (0-based).
+    pub fn track_index(&self) -> usize {
+        self.track_index
+    }
+
+    /// Switch to another track (0-based) and restart it.
+    ///
+    /// Playback state and settings (mutes, gain, rate) are kept. Fails
+    /// without changing anything if the index is out of range.
+    pub fn set_track(&mut self, track_index: usize) -> Result<()> {
+        check_track(&self.file, track_index)?;
+        self.track_index = track_index;
+        self.metadata = build_metadata(&self.file, track_index, self.metadata.frame_rate);
+        self.reset_runtime();
+        Ok(())
+    }
+
+    /// Begin playback or resume from pause.
+    pub fn play(&mut self) {
+        match self.state {
+            PlaybackState::Playing => {}
+            PlaybackState::Paused => self.state = PlaybackState::Playing,
+            PlaybackState::Stopped => {
+                self.reset_runtime();
+                self.state = PlaybackState::Playing;
+            }
+        }
+    }
+
+    /// Pause playback (keep current state).
+    pub fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+        }
+    }
+
+    /// Stop playback and reset to the beginning.
+    pub fn stop(&mut self) {
+        if self.state != PlaybackState::Stopped {
+            self.state = PlaybackState::Stopped;
+            self.reset_runtime();
+        }
+    }
+
+    /// Generate mono samples into a freshly allocated buffer.
+    pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
+        let mut output = vec![0.0; count];
+        self.generate_samples_into(&mut output);
+        output
+    }
+
+    /// Generate mono samples into the provided buffer.
+    pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| self.render_native(source));
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+    }
+
+    /// Advance exactly `frames` frames while not playing.
+    ///
+    /// Each frame runs the play routine and renders its audio into the
+    /// sample cache. A stopped player steps from the beginning and is left
+    /// paused. Returns `None` while playing.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        match self.state {
+            PlaybackState::Playing => return None,
+            PlaybackState::Paused => {}
+            PlaybackState::Stopped => self.reset_runtime(),
+        }
+
+        let before = vec![self.chip().dump_registers()];
+        self.state = PlaybackState::Paused;
+        for _ in 0..frames {
+            if let Err(err) = self.render_frame() {
+                eprintln!("KSS frame rendering error: {err}");
+                self.state = PlaybackState::Stopped;
+                break;
+            }
+        }
+        Some(RegisterDelta::new(
+            before,
+            vec![self.chip().dump_registers()],
+        ))
+    }
+
+    /// Run `frames` frames without rendering audio.
+    ///
+    /// A stopped player starts from the beginning and is left paused.
+    /// Returns the number of frames advanced, which is less than `frames`
+    /// if a routine failed (the player is then stopped).
+    pub fn advance_frames(&mut self, frames: usize) -> usize {
+        if self.state == PlaybackState::Stopped {
+            self.reset_runtime();
+            self.state = PlaybackState::Paused;
+        }
+
+        let mut advanced = 0;
+        while advanced < frames && self.state != PlaybackState::Stopped {
+            let result = self
+                .ensure_initialized()
+                .and_then(|()| self.run_subroutine(self.file.header.play_address));
+            if let Err(err) = result {
+                eprintln!("KSS frame rendering error: {err}");
+                self.state = PlaybackState::Stopped;
+                break;
+            }
+            advanced += 1;
+            self.frame_counter = self.frame_counter.saturating_add(1);
+        }
+        self.cache_pos = self.cache_len;
+        advanced
+    }
+
+    /// Current frame index (0-based).
+    pub fn current_frame(&self) -> usize {
+        self.frame_counter
+    }
+
+    /// Access the PSG.
+    pub fn chip(&self) -> &ym2149::Ym2149 {
+        self.bus.chip()
+    }
+
+    /// Mutable access to the PSG.
+    pub fn chip_mut(&mut self) -> &mut ym2149::Ym2149 {
+        self.bus.chip_mut()
+    }
+
+    /// Mute/unmute a PSG channel.
+    pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        if channel < 3 {
+            self.chip_mut().set_channel_mute(channel, mute);
+        }
+    }
+
+    /// Check mute state of a PSG channel.
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        channel < 3 && self.chip().is_channel_muted(channel)
+    }
+
+    /// Sound chips the song uses that are not emulated and stay silent:
+    /// "SCC", "FM-PAC" and "MSX-AUDIO", from the header flags and the
+    /// accesses seen since the track started.
+    pub fn silent_chips(&self) -> Vec<&'static str> {
+        let used = self.bus.silent_chips();
+        let devices = self.file.header.devices;
+        let mut chips = Vec::new();
+        if used.scc {
+            chips.push("SCC");
+        }
+        if used.fmpac || devices.fmpac() {
+            chips.push("FM-PAC");
+        }
+        if used.msx_audio || devices.msx_audio() {
+            chips.push("MSX-AUDIO");
+        }
+        chips
+    }
+
+    fn reset_runtime(&mut self) {
+        self.bus.reset(&self.file);
+        self.z80.reset();
+        let cpu = self.z80.cpu_mut();
+        cpu.set_register(RegisterPair::SP, STACK_ADDRESS);
+        cpu.set_pc(RETURN_ADDRESS);
+        self.frame_counter = 0;
+        self.cache_pos = 0;
+        self.cache_len = 0;
+        self.sample_cache.clear();
+        self.init_executed = false;
+    }
+
+    fn ensure_initialized(&mut self) -> Result<()> {
+        if !self.init_executed {
+            let track = self.file.track_number(self.track_index).unwrap_or_default();
+            let cpu = self.z80.cpu_mut();
+            let af = cpu.register(RegisterPair::AF);
+            cpu.set_register(RegisterPair::AF, (u16::from(track) << 8) | (af & 0x00FF));
+            self.run_subroutine(self.file.header.init_address)?;
+            self.init_executed = true;
+        }
+        Ok(())
+    }
+
+    /// Copy samples, rendering frames as needed.
+    fn render_native(&mut self, buffer: &mut [f32]) {
+        let mut written = 0;
+        while written < buffer.len() {
+            if self.cache_pos >= self.cache_len {
+                if self.state != PlaybackState::Playing {
+                    buffer[written..].fill(0.0);
+                    break;
+                }
+                if let Err(err) = self.render_frame() {
+                    eprintln!("KSS frame rendering error: {err}");
+                    buffer[written..].fill(0.0);
+                    self.state = PlaybackState::Stopped;
+                    break;
+                }
+            }
+
+            let available = self.cache_len - self.cache_pos;
+            let to_copy = available.min(buffer.len() - written);
+            buffer[written..written + to_copy]
+                .copy_from_slice(&self.sample_cache[self.cache_pos..self.cache_pos + to_copy]);
+            self.cache_pos += to_copy;
+            written += to_copy;
+        }
+    }
+
+    fn render_frame(&mut self) -> Result<()> {
+        self.ensure_initialized()?;
+        let samples = self.frame_sample_count();
+        let mut buffer = mem::take(&mut self.sample_cache);
+        buffer.resize(samples, 0.0);
+        let result = self.render_play_stream(&mut buffer);
+        self.sample_cache = buffer;
+        self.cache_pos = 0;
+        self.cache_len = self.sample_cache.len();
+        self.frame_counter = self.frame_counter.saturating_add(1);
+        result
+    }
+
+    /// Samples in the current frame, rounded so the fractions carry over
+    /// between frames.
+    fn frame_sample_count(&self) -> usize {
+        let frame_samples = u64::from(self.z80.timing().frame_t_states) * u64::from(SAMPLE_RATE);
+        let cpu_clock = u64::from(CPU_CLOCK);
+        let samples_before = |frame: u64| (frame * frame_samples + cpu_clock / 2) / cpu_clock;
+        let frame = self.frame_counter as u64;
+        (samples_before(frame + 1) - samples_before(frame)) as usize
+    }
+
+    /// Run the play routine interleaved with the PSG, so writes land at
+    /// the sample they happen in.
+    fn render_play_stream(&mut self, buffer: &mut [f32]) -> Result<()> {
+        let play_address = self.file.header.play_address;
+        self.z80.start_frame();
+        self.emulate_call(play_address);
+        let mut next_sample_time = self.sample_period;
+        let mut cpu_time = 0.0f64;
+        let mut guard = MAX_INSTRUCTIONS_PER_CALL;
+
+        for sample in buffer.iter_mut() {
+            while cpu_time < next_sample_time {
+                if self.z80.cpu().pc() == RETURN_ADDRESS {
+                    cpu_time = next_sample_time;
+                    break;
+                }
+                let cycles = self.z80.step(&mut self.bus);
+                cpu_time += f64::from(cycles) / f64::from(CPU_CLOCK);
+                guard = guard.checked_sub(1).ok_or_else(|| KssError::InvalidData {
+                    msg: format!(
+                        "Play routine at 0x{play_address:04x} exceeded instruction budget"
+                    ),
+                })?;
+            }
+            *sample = self.bus.clock_sample();
+            next_sample_time += self.sample_period;
+        }
+
+        if self.z80.cpu().pc() != RETURN_ADDRESS {
+            return Err(KssError::InvalidData {
+                msg: format!(
+                    "Play routine at 0x{play_address:04x} did not return before frame end"
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    fn run_subroutine(&mut self, entry: u16) -> Result<()> {
+        self.emulate_call(entry);
+        let mut guard = MAX_INSTRUCTIONS_PER_CALL;
+        while self.z80.cpu().pc() != RETURN_ADDRESS {
+            self.z80.step(&mut self.bus);
+            guard = guard.checked_sub(1).ok_or_else(|| KssError::InvalidData {
+                msg: format!(
+                    "Subroutine at 0x{entry:04x} did not return within instruction budget"
+                ),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn emulate_call(&mut self, entry: u16) {
+        let cpu = self.z80.cpu_mut();
+        let mut sp = cpu.register(RegisterPair::SP);
+        sp = sp.wrapping_sub(1);
+        self.bus.poke(sp, (RETURN_ADDRESS >> 8) as u8);
+        sp = sp.wrapping_sub(1);
+        self.bus.poke(sp, RETURN_ADDRESS as u8);
+        cpu.set_register(RegisterPair::SP, sp);
+        cpu.set_pc(entry);
+    }
+}
+
+fn check_track(file: &KssFile, track_index: usize) -> Result<()> {
+    if file.track_number(track_index).is_some() {
+        Ok(())
+    } else {
+        Err(KssError::TrackOutOfRange {
+            index: track_index,
+            available: file.track_count(),
+        })
+    }
+}
+
+fn build_metadata(file: &KssFile, track_index: usize, frame_rate: u32) -> BasicMetadata {
+    let track = file.track_number(track_index).unwrap_or_default();
+    BasicMetadata {
+        title: format!("Track {track}"),
+        format: file.header.variant.name().to_string(),
+        frame_rate,
+        ..BasicMetadata::default()
+    }
+}
+
+// ============================================================================
+// ChiptunePlayer trait implementation
+// ============================================================================
+
+impl ChiptunePlayerBase for KssPlayer {
+    fn play(&mut self) {
+        KssPlayer::play(self);
+    }
+
+    fn pause(&mut self) {
+        KssPlayer::pause(self);
+    }
+
+    fn stop(&mut self) {
+        KssPlayer::stop(self);
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        KssPlayer::generate_samples_into(self, buffer);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        SAMPLE_RATE
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        KssPlayer::set_channel_mute(self, channel, mute);
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        KssPlayer::is_channel_muted(self, channel)
+    }
+
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.chip_mut().set_output_model(model);
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.chip_mut().set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.chip()
+            .channel_tap()
+            .map_or(0, |tap| tap.read_channel(channel, out))
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        KssPlayer::step_frames(self, frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        KssPlayer::advance_frames(self, frames)
+    }
+
+    fn subsong_count(&self) -> usize {
+        self.file.track_count()
+    }
+
+    fn current_subsong(&self) -> usize {
+        self.track_index + 1
+    }
+
+    fn set_subsong(&mut self, index: usize) -> bool {
+        if index == 0 || self.set_track(index - 1).is_err() {
+            return false;
+        }
+        self.state = PlaybackState::Playing;
+        true
+    }
+}
+
+impl ChiptunePlayer for KssPlayer {
+    type Metadata = BasicMetadata;
+
+    fn metadata(&self) -> &Self::Metadata {
+        &self.metadata
+    }
+
+    fn load_report(&self) -> LoadReport {
+        LoadReport::from_player(self).with_family(ChiptuneFormat::Kss)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::load_kss;
+
+    const LOAD_ADDRESS: u16 = 0x4000;
+    const PLAY_ADDRESS: u16 = 0x4100;
+
+    /// A KSCC file whose init routine is `init` and play routine `play`
+    /// (each followed by `RET`), plus `banks` 16K banks starting at 1.
+    fn kss(init: &[u8], play: &[u8], banks: &[[u8; 0x4000]]) -> KssFile {
+        let mut image = init.to_vec();
+        image.push(0xC9);
+        image.resize(usize::from(PLAY_ADDRESS - LOAD_ADDRESS), 0);
+        image.extend_from_slice(play);
+        image.push(0xC9);
+
+        let mut data = b"KSCC".to_vec();
+        for word in [LOAD_ADDRESS, image.len() as u16, LOAD_ADDRESS, PLAY_ADDRESS] {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data.extend_from_slice(&[1, banks.len() as u8, 0, 0]);
+        data.extend(image);
+        for bank in banks {
+            data.extend_from_slice(bank);
+        }
+        load_kss(&data).expect("valid KSS")
+    }
+
+    /// `ld a,value` + `out (port),a`.
+    fn out(port: u8, value: u8) -> [u8; 4] {
+        [0x3E, value, 0xD3, port]
+    }
+
+    /// `ld a,register` + `ld e,value` + `call WRTPSG`.
+    fn wrtpsg(register: u8, value: u8) -> [u8; 7] {
+        [0x3E, register, 0x1E, value, 0xCD, 0x93, 0x00]
+    }
+
+    #[test]
+    fn test_play_routine_writes_psg_ports() {
+        let mut play = Vec::new();
+        for (register, value) in [(7, 0x3E), (0, 0x40), (8, 0x0F)] {
+            play.extend(out(0xA0, register));
+            play.extend(out(0xA1, value));
+        }
+        let mut player = KssPlayer::new(kss(&[], &play, &[]), 0).unwrap();
+        player.play();
+        // 3579545 / 60 T-states per frame: 735 samples
+        let samples = player.generate_samples(735 * 2);
+        assert_eq!(player.current_frame(), 2);
+        assert_eq!(player.chip().dump_registers()[8], 0x0F);
+        assert!(samples.iter().any(|&sample| sample != 0.0));
+        assert_eq!(player.metadata().frame_rate, 60);
+    }
+
+    #[test]
+    fn test_load_report_names_the_kss_family() {
+        let player = KssPlayer::new(kss(&[], &[], &[]), 0).unwrap();
+        let report = ChiptunePlayer::load_report(&player);
+        assert_eq!(report.family, ChiptuneFormat::Kss);
+        assert_eq!(report.format, "KSCC");
+        assert_eq!(report.frame_rate, 60);
+        assert_eq!(report.duration_seconds, None);
+    }
+
+    #[test]
+    fn test_init_gets_track_number_and_bios_writes_psg() {
+        // Store the track number as the volume through WRTPSG
+        let init = [0x5F, 0x3E, 0x08, 0xCD, 0x93, 0x00];
+        let mut player = KssPlayer::new(kss(&init, &[], &[]), 11).unwrap();
+        player.step_frames(1);
+        assert_eq!(player.chip().dump_registers()[8], 11);
+
+        player.set_track(5).unwrap();
+        player.step_frames(1);
+        assert_eq!(player.chip().dump_registers()[8], 5);
+        assert!(player.set_track(256).is_err());
+    }
+
+    #[test]
+    fn test_bank_switch_maps_rom_at_0x8000() {
+        let mut bank = [0u8; 0x4000];
+        // Bank 2 holds the tone period at 0x8000
+        bank[0] = 0x55;
+        let mut init = out(0xFE, 2).to_vec();
+        // ld a,(0x8000); ld e,a; xor a; call WRTPSG
+        init.extend([0x3A, 0x00, 0x80, 0x5F, 0xAF, 0xCD, 0x93, 0x00]);
+        let mut player = KssPlayer::new(kss(&init, &[], &[[0; 0x4000], bank]), 0).unwrap();
+        player.step_frames(1);
+        assert_eq!(player.chip().dump_registers()[0], 0x55);
+    }
+
+    #[test]
+    fn test_unemulated_chips_are_reported() {
+        let play = out(0x7C, 0x30);
+        let mut player = KssPlayer::new(kss(&wrtpsg(8, 0x0F), &play, &[]), 0).unwrap();
+        assert!(player.silent_chips().is_empty());
+        player.step_frames(1);
+        assert_eq!(player.silent_chips(), ["FM-PAC"]);
+    }
+}
@@ -0,0 +1,88 @@
+//! Mutated files must be rejected with an error, never a panic.
+
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::mutants;
+use ym2149_kss_replayer::{KssError, KssPlayer, load_kss};
+
+/// A small but complete KSSX file: tracks 1-2, an init routine that
+/// stores the track number and a play routine that copies a register
+/// table from a 16K bank to the PSG.
+fn synthetic_kss() -> Vec<u8> {
+    let mut data = b"KSSX".to_vec();
+    // Load 0x20 bytes at 0x4000, init 0x4000, play 0x4008
+    data.extend_from_slice(&[0x00, 0x40, 0x20, 0x00, 0x00, 0x40, 0x08, 0x40]);
+    // Bank 1, one 16K bank, 16-byte extra header, no extra devices
+    data.extend_from_slice(&[1, 1, 0x10, 0]);
+    // Extra header: data size, reserved, tracks 1-2, volumes
+    data.extend_from_slice(&(0x20u32 + 0x4000).to_le_bytes());
+    data.extend_from_slice(&[0; 4]);
+    data.extend_from_slice(&[1, 0, 2, 0, 0, 0, 0, 0]);
+    data.extend_from_slice(&[
+        0x32, 0x00, 0xC0, // 0x4000: ld (0xC000),a
+        0xC9, // ret
+        0, 0, 0, 0, // padding
+        0x3E, 0x01, // 0x4008: ld a,1
+        0xD3, 0xFE, // out (0xFE),a
+        0x21, 0x00, 0x80, // ld hl,0x8000
+        0xAF, // xor a
+        0xD3, 0xA0, // out (0xA0),a
+        0x5F, // ld e,a
+        0x7E, // ld a,(hl)
+        0xD3, 0xA1, // out (0xA1),a
+        0x23, // inc hl
+        0x7B, // ld a,e
+        0x3C, // inc a
+        0xFE, 0x0E, // cp 14
+        0x20, 0xF3, // jr nz,-13
+        0xC9, // ret
+        0, 0, // padding
+    ]);
+    assert_eq!(data.len(), 0x20 + 0x20);
+    // Bank 1: register table
+    let mut bank = vec![0u8; 0x4000];
+    bank[..14].copy_from_slice(&[
+        0x10, 0x01, 0x20, 0x02, 0x30, 0x03, 0x10, 0x38, 0x0F, 0x0C, 0x08, 0x00, 0x10, 0x0E,
+    ]);
+    data.extend(bank);
+    data
+}
+
+#[test]
+fn synthetic_kss_plays() {
+    let mut player = KssPlayer::load_from_bytes(&synthetic_kss(), 1).expect("valid KSS file");
+    assert_eq!(player.subsong_count(), 2);
+    assert_eq!(player.metadata().title, "Track 2");
+    player.play();
+    let mut buffer = [0.0f32; 735];
+    player.generate_samples_into(&mut buffer);
+    player.generate_samples_into(&mut buffer);
+    assert_eq!(player.chip().read_register(8), 0x0F);
+    assert!(buffer.iter().any(|&sample| sample != 0.0));
+    assert!(player.silent_chips().is_empty());
+}
+
+#[test]
+fn tracks_outside_the_kssx_range_are_errors() {
+    assert!(matches!(
+        KssPlayer::load_from_bytes(&synthetic_kss(), 2),
+        Err(KssError::TrackOutOfRange {
+            index: 2,
+            available: 2
+        })
+    ));
+}
+
+#[test]
+fn mutated_kss_files_never_panic() {
+    let original = synthetic_kss();
+    for data in mutants(&original, 2000) {
+        let _ = load_kss(&data);
+        if let Ok(mut player) = KssPlayer::load_from_bytes(&data, 0) {
+            player.play();
+            let mut buffer = [0.0f32; 735];
+            for _ in 0..4 {
+                player.generate_samples_into(&mut buffer);
+            }
+        }
+    }
+}
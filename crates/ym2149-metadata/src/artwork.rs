@@ -0,0 +1,179 @@
+//! Artwork association for catalog entries.
+//!
+//! Looks for an image next to each track: one named like the track first,
+//! then folder-level art and composer photos (`folder.png`, `cover.jpg`,
+//! `composer.png`, ...). Images are referenced by path or embedded as
+//! `data:` URIs, and tracks without an image can fall back to a thumbnail
+//! of their waveform peaks.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+
+use crate::catalog_path;
+use crate::spectrogram::Image;
+
+/// Recognized image extensions (in order of preference) and MIME types
+const IMAGE_TYPES: [(&str, &str); 5] = [
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("webp", "image/webp"),
+    ("gif", "image/gif"),
+];
+/// File stems of directory-wide images, in order of preference
+const FOLDER_NAMES: [&str; 6] = ["folder", "cover", "front", "album", "composer", "artist"];
+/// Waveform thumbnail size in pixels
+const THUMBNAIL_WIDTH: usize = 200;
+const THUMBNAIL_HEIGHT: usize = 48;
+/// Waveform thumbnail bar color
+const THUMBNAIL_COLOR: [u8; 3] = [96, 160, 255];
+
+/// How artwork is stored in the catalog
+#[derive(Debug, Clone, Copy)]
+pub struct ArtOptions {
+    /// Embed images as `data:` URIs instead of referencing their path
+    pub inline: bool,
+    /// Use a waveform thumbnail for tracks without an image
+    pub thumbnails: bool,
+}
+
+/// Catalog artwork for `track`: an image path relative to `base_path` or a
+/// `data:` URI.
+///
+/// `peaks` are the track's waveform peaks, used for the thumbnail fallback.
+pub fn artwork(
+    track: &Path,
+    base_path: &Path,
+    peaks: Option<&[u8]>,
+    options: ArtOptions,
+) -> Option<String> {
+    if let Some(image) = find_image(track) {
+        if !options.inline {
+            return Some(catalog_path(&image, base_path));
+        }
+        if let Ok(uri) = data_uri(&image) {
+            return Some(uri);
+        }
+    }
+    if options.thumbnails {
+        peaks
+            .filter(|peaks| !peaks.is_empty())
+            .map(waveform_thumbnail)
+    } else {
+        None
+    }
+}
+
+/// Image in the track's directory that belongs to it, if any.
+///
+/// An image with the track's file stem wins over folder-level art; names
+/// are compared case-insensitively.
+pub fn find_image(track: &Path) -> Option<PathBuf> {
+    let dir = track.parent()?;
+    let stem = track.file_stem()?.to_str()?.to_lowercase();
+
+    let mut best: Option<((usize, usize), PathBuf)> = None;
+    for entry in fs::read_dir(dir).ok()?.filter_map(Result::ok) {
+        let path = entry.path();
+        let Some(ext_rank) = image_type(&path).map(|(rank, _)| rank) else {
+            continue;
+        };
+        let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let name = name.to_lowercase();
+        let name_rank = if name == stem {
+            0
+        } else if let Some(rank) = FOLDER_NAMES.iter().position(|&folder| folder == name) {
+            rank + 1
+        } else {
+            continue;
+        };
+        let rank = (name_rank, ext_rank);
+        if best.as_ref().is_none_or(|(best_rank, _)| rank < *best_rank) {
+            best = Some((rank, path));
+        }
+    }
+    best.map(|(_, path)| path)
+}
+
+/// Preference rank and MIME type of an image file
+fn image_type(path: &Path) -> Option<(usize, &'static str)> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    IMAGE_TYPES
+        .iter()
+        .position(|&(known, _)| known == ext)
+        .map(|rank| (rank, IMAGE_TYPES[rank].1))
+}
+
+/// `data:` URI with the contents of an image file
+fn data_uri(path: &Path) -> std::io::Result<String> {
+    let mime = image_type(path).map_or("application/octet-stream", |(_, mime)| mime);
+    let bytes = fs::read(path)?;
+    Ok(format!("data:{mime};base64,{}", BASE64.encode(bytes)))
+}
+
+/// PNG thumbnail of waveform peaks (0-255 per bar) as a `data:` URI.
+///
+/// Bars are drawn mirrored around the vertical center.
+pub fn waveform_thumbnail(peaks: &[u8]) -> String {
+    let mut image = Image::new(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+    let center = THUMBNAIL_HEIGHT / 2;
+    for x in 0..THUMBNAIL_WIDTH {
+        let peak = peaks[x * peaks.len() / THUMBNAIL_WIDTH] as usize;
+        let half = (peak * center / 255).max(1);
+        for y in center - half..(center + half).min(THUMBNAIL_HEIGHT) {
+            image.set_pixel(x, y, THUMBNAIL_COLOR);
+        }
+    }
+
+    let mut png = Vec::new();
+    image
+        .encode_png(&mut png)
+        .expect("encoding into memory cannot fail");
+    format!("data:image/png;base64,{}", BASE64.encode(png))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_track_image_wins_over_folder_art() {
+        let dir = std::env::temp_dir().join(format!("ym-metadata-art-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["Cover.JPG", "folder.png", "notes.png", "Song.png"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+        let song = dir.join("Song.ym");
+        let other = dir.join("Other.ym");
+
+        assert_eq!(find_image(&song), Some(dir.join("Song.png")));
+        assert_eq!(find_image(&other), Some(dir.join("folder.png")));
+        assert_eq!(
+            artwork(
+                &other,
+                &dir,
+                None,
+                ArtOptions {
+                    inline: false,
+                    thumbnails: false
+                }
+            ),
+            Some("folder.png".to_string())
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_waveform_thumbnail_is_png_data_uri() {
+        let uri = waveform_thumbnail(&[0, 128, 255]);
+        let png = BASE64
+            .decode(uri.strip_prefix("data:image/png;base64,").unwrap())
+            .unwrap();
+        assert_eq!(&png[1..4], b"PNG");
+    }
+}
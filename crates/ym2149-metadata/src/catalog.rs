@@ -0,0 +1,34 @@
+//! Catalog paths and collections of scanned files.
+
+use std::path::Path;
+
+/// Collection of a file from its path: `(id, name, description, format)`
+pub fn detect_collection(
+    path: &Path,
+) -> Option<(&'static str, &'static str, &'static str, &'static str)> {
+    let path_str = path.to_string_lossy().to_lowercase();
+
+    if path_str.contains("sndh") {
+        Some((
+            "sndh",
+            "SNDH Collection",
+            "Atari ST/STE music from the SNDH archive",
+            "SNDH",
+        ))
+    } else if path_str.contains("projectay") {
+        Some(("ay", "Project AY", "ZX Spectrum AY music", "AY"))
+    } else if path_str.contains("arkos") {
+        Some(("arkos", "Arkos Tracker", "Arkos Tracker 2 songs", "AKS"))
+    } else if path_str.contains("/ym/") || path_str.contains("\\ym\\") || path_str.ends_with("/ym")
+    {
+        Some(("ym", "YM Collection", "YM format chiptunes", "YM"))
+    } else {
+        None
+    }
+}
+
+/// Path of a file as stored in the catalog
+pub fn catalog_path(path: &Path, base_path: &Path) -> String {
+    let relative_path = path.strip_prefix(base_path).unwrap_or(path);
+    relative_path.to_string_lossy().replace('\\', "/")
+}
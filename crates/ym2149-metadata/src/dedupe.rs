@@ -0,0 +1,454 @@
+//! Duplicate detection by audio fingerprint.
+//!
+//! Archives such as SNDH and Project AY hold many tracks more than once:
+//! re-rips, fixed versions and the same tune filed under another composer
+//! or converted to another format. [`find_duplicates`] compares the
+//! fingerprints and waveform peaks of a scanned catalog and groups tracks
+//! that sound alike, with a confidence score per pair for human review.
+//! Only tracks scanned with waveforms have the data to compare.
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use serde::{Deserialize, Serialize};
+use ym2149_catalog::{Fingerprint, TrackMetadata};
+
+use crate::WAVEFORM_BARS;
+
+/// Default minimum confidence for two tracks to count as duplicates
+pub const DEFAULT_DUPLICATE_THRESHOLD: f32 = 0.85;
+
+/// Tracks whose lengths differ by more than this fraction are not compared;
+/// rips of one tune differ by a fade-out or a timing fix, not by a loop
+const MAX_DURATION_RATIO: f32 = 0.25;
+
+/// Tracks in a [`DuplicateGroup`], as listed in the catalog
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateTrack {
+    /// Catalog path
+    pub path: String,
+    /// Song title
+    pub title: String,
+    /// Composer
+    pub author: String,
+    /// Format name
+    pub format: String,
+    /// Duration in seconds, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_seconds: Option<f32>,
+}
+
+/// Two tracks of a group and how alike they sound
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicatePair {
+    /// Index of the first track in [`DuplicateGroup::tracks`]
+    pub a: usize,
+    /// Index of the second track in [`DuplicateGroup::tracks`]
+    pub b: usize,
+    /// Similarity (threshold-1.0)
+    pub confidence: f32,
+}
+
+/// Tracks that are probably the same recording
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateGroup {
+    /// Highest confidence of the pairs in the group
+    pub confidence: f32,
+    /// Tracks in catalog order
+    pub tracks: Vec<DuplicateTrack>,
+    /// Pairs above the threshold, most alike first; a group can also be
+    /// chained together (A like B, B like C)
+    pub pairs: Vec<DuplicatePair>,
+}
+
+/// Result of [`find_duplicates`]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DuplicateReport {
+    /// Minimum confidence used
+    pub threshold: f32,
+    /// Tracks compared (those with waveform peaks and a fingerprint)
+    pub compared: usize,
+    /// Tracks skipped because they were scanned without waveforms (or
+    /// rendered silence)
+    pub skipped: usize,
+    /// Duplicate groups, most confident first
+    pub groups: Vec<DuplicateGroup>,
+}
+
+/// Fingerprint data of one track, prepared for many comparisons
+struct Candidate<'a> {
+    index: usize,
+    fingerprint: &'a Fingerprint,
+    duration: Option<f32>,
+    /// Waveform peaks shifted to zero mean and scaled to unit length, so the
+    /// dot product of two of them is their correlation
+    peaks: Vec<f32>,
+}
+
+impl<'a> Candidate<'a> {
+    fn new(index: usize, track: &'a TrackMetadata) -> Option<Self> {
+        Some(Self {
+            index,
+            fingerprint: track.fp.as_ref()?,
+            duration: track.duration_seconds.filter(|d| *d > 0.0),
+            peaks: normalized_peaks(&BASE64.decode(track.w.as_ref()?).ok()?)?,
+        })
+    }
+}
+
+fn normalized_peaks(peaks: &[u8]) -> Option<Vec<f32>> {
+    if peaks.len() != WAVEFORM_BARS {
+        return None;
+    }
+    let mean = peaks.iter().map(|&p| f32::from(p)).sum::<f32>() / peaks.len() as f32;
+    let centered: Vec<f32> = peaks.iter().map(|&p| f32::from(p) - mean).collect();
+    let norm = centered.iter().map(|v| v * v).sum::<f32>().sqrt();
+    // A flat waveform correlates with nothing
+    (norm > 1e-3).then(|| centered.iter().map(|v| v / norm).collect())
+}
+
+/// Weighted average of feature similarities
+#[derive(Default)]
+struct Score {
+    total: f32,
+    weight: f32,
+}
+
+impl Score {
+    fn add(&mut self, weight: f32, similarity: f32) {
+        self.total += weight * similarity.clamp(0.0, 1.0);
+        self.weight += weight;
+    }
+
+    fn add_option<T>(
+        &mut self,
+        weight: f32,
+        a: &Option<T>,
+        b: &Option<T>,
+        f: impl Fn(&T, &T) -> f32,
+    ) {
+        if let (Some(a), Some(b)) = (a, b) {
+            self.add(weight, f(a, b));
+        }
+    }
+
+    fn value(&self) -> f32 {
+        if self.weight > 0.0 {
+            self.total / self.weight
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 1 minus the mean absolute difference, relative to `range`
+fn array_similarity<T: Copy + Into<f32>>(a: &[T], b: &[T], range: f32) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let diff: f32 = a
+        .iter()
+        .zip(b)
+        .map(|(&x, &y)| (x.into() - y.into()).abs())
+        .sum();
+    1.0 - diff / (a.len() as f32 * range)
+}
+
+/// Ratio of the smaller to the larger value
+fn ratio_similarity(a: f32, b: f32) -> f32 {
+    let max = a.abs().max(b.abs());
+    if max <= f32::EPSILON {
+        1.0
+    } else {
+        a.abs().min(b.abs()) / max
+    }
+}
+
+fn mfcc_similarity(a: &[i8; 13], b: &[i8; 13]) -> f32 {
+    let a = a.map(f32::from);
+    let b = b.map(f32::from);
+    array_similarity(&a, &b, 255.0)
+}
+
+fn fingerprint_similarity(a: &Fingerprint, b: &Fingerprint) -> Score {
+    let mut score = Score::default();
+
+    // Timbre carries most of the identity of a tune
+    score.add_option(3.0, &a.mfcc, &b.mfcc, mfcc_similarity);
+    score.add_option(1.0, &a.mfcc_d, &b.mfcc_d, mfcc_similarity);
+    score.add_option(1.0, &a.mfcc_dd, &b.mfcc_dd, mfcc_similarity);
+    score.add_option(2.0, &a.chromagram, &b.chromagram, |a, b| {
+        array_similarity(a, b, 255.0)
+    });
+    score.add_option(1.0, &a.chroma, &b.chroma, |a, b| {
+        array_similarity(a, b, 255.0)
+    });
+    score.add_option(1.0, &a.bands, &b.bands, |a, b| {
+        array_similarity(a, b, 255.0)
+    });
+    score.add_option(1.0, &a.hist, &b.hist, |a, b| array_similarity(a, b, 255.0));
+    score.add_option(1.0, &a.sections, &b.sections, |a, b| {
+        array_similarity(a, b, 255.0)
+    });
+
+    score.add(0.5, 1.0 - (a.amp - b.amp).abs());
+    score.add(0.5, 1.0 - (a.variance - b.variance).abs());
+    score.add(0.5, 1.0 - (a.brightness - b.brightness).abs());
+    score.add(0.5, ratio_similarity(a.punch, b.punch));
+    score.add(0.5, ratio_similarity(a.density as f32, b.density as f32));
+    score.add_option(0.5, &a.tempo, &b.tempo, |a, b| {
+        ratio_similarity(f32::from(*a), f32::from(*b))
+    });
+    score.add_option(0.5, &a.centroid, &b.centroid, |a, b| 1.0 - (a - b).abs());
+    score.add_option(0.5, &a.flatness, &b.flatness, |a, b| 1.0 - (a - b).abs());
+    score.add_option(0.5, &a.rhythm_reg, &b.rhythm_reg, |a, b| {
+        1.0 - (a - b).abs()
+    });
+    score.add_option(0.5, &a.rhythm_str, &b.rhythm_str, |a, b| {
+        1.0 - (a - b).abs()
+    });
+    score
+}
+
+fn candidate_similarity(a: &Candidate<'_>, b: &Candidate<'_>) -> f32 {
+    // The waveform outline is what tells tunes apart: the summary features
+    // of unrelated chiptunes on the same chip are often closer than those
+    // of one tune on two emulators
+    let mut score = Score::default();
+    score.add(3.0, a.peaks.iter().zip(&b.peaks).map(|(x, y)| x * y).sum());
+    score.add_option(1.0, &a.duration, &b.duration, |a, b| {
+        ratio_similarity(*a, *b)
+    });
+    score.add(
+        1.0,
+        fingerprint_similarity(a.fingerprint, b.fingerprint).value(),
+    );
+    score.value()
+}
+
+/// How alike two catalog tracks sound (0.0-1.0), from their fingerprints,
+/// waveform peaks and durations.
+///
+/// Returns `None` unless both tracks were scanned with waveforms.
+pub fn track_similarity(a: &TrackMetadata, b: &TrackMetadata) -> Option<f32> {
+    Some(candidate_similarity(
+        &Candidate::new(0, a)?,
+        &Candidate::new(1, b)?,
+    ))
+}
+
+/// Find groups of tracks that are probably duplicates.
+///
+/// Every pair of tracks scanned with waveforms and of similar length is
+/// compared;
+/// pairs scoring at least `threshold` (see [`DEFAULT_DUPLICATE_THRESHOLD`])
+/// are joined into groups.
+pub fn find_duplicates(tracks: &[TrackMetadata], threshold: f32) -> DuplicateReport {
+    let mut candidates: Vec<Candidate<'_>> = tracks
+        .iter()
+        .enumerate()
+        .filter_map(|(index, track)| Candidate::new(index, track))
+        .collect();
+    // Sorted by duration, the tracks worth comparing with one are a window
+    // after it; unknown durations go last and are compared with everything
+    candidates.sort_by(|a, b| match (a.duration, b.duration) {
+        (Some(a), Some(b)) => a.total_cmp(&b),
+        (a, b) => b.is_some().cmp(&a.is_some()),
+    });
+
+    let first_unknown = candidates.partition_point(|c| c.duration.is_some());
+
+    let mut pairs: Vec<Pair> = Vec::new();
+    let mut compare = |a: &Candidate<'_>, b: &Candidate<'_>| {
+        let confidence = candidate_similarity(a, b);
+        if confidence >= threshold {
+            pairs.push((a.index.min(b.index), a.index.max(b.index), confidence));
+        }
+    };
+    for (i, a) in candidates.iter().enumerate() {
+        if let Some(duration) = a.duration {
+            let longest = duration * (1.0 + MAX_DURATION_RATIO);
+            for b in &candidates[i + 1..first_unknown] {
+                if b.duration.is_some_and(|d| d > longest) {
+                    break;
+                }
+                compare(a, b);
+            }
+        }
+        for b in &candidates[first_unknown.max(i + 1)..] {
+            compare(a, b);
+        }
+    }
+
+    DuplicateReport {
+        threshold,
+        compared: candidates.len(),
+        skipped: tracks.len() - candidates.len(),
+        groups: group_pairs(tracks, pairs),
+    }
+}
+
+/// Two track indices (smaller first) and their confidence
+type Pair = (usize, usize, f32);
+
+/// Join pairs sharing a track into groups (union-find over track indices)
+fn group_pairs(tracks: &[TrackMetadata], mut pairs: Vec<Pair>) -> Vec<DuplicateGroup> {
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    let mut parent: Vec<usize> = (0..tracks.len()).collect();
+    for &(a, b, _) in &pairs {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        parent[ra.max(rb)] = ra.min(rb);
+    }
+
+    // Most alike first, so groups come out ordered by their best pair
+    pairs.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)).then(a.1.cmp(&b.1)));
+    let mut roots: Vec<usize> = Vec::new();
+    let mut grouped: Vec<Vec<Pair>> = Vec::new();
+    for pair in pairs {
+        let group_root = root(&mut parent, pair.0);
+        match roots.iter().position(|&r| r == group_root) {
+            Some(index) => grouped[index].push(pair),
+            None => {
+                roots.push(group_root);
+                grouped.push(vec![pair]);
+            }
+        }
+    }
+
+    grouped
+        .into_iter()
+        .map(|pairs| {
+            let mut members: Vec<usize> = pairs.iter().flat_map(|&(a, b, _)| [a, b]).collect();
+            members.sort_unstable();
+            members.dedup();
+            let member = |track| members.binary_search(&track).unwrap_or_default();
+            DuplicateGroup {
+                confidence: pairs[0].2,
+                pairs: pairs
+                    .iter()
+                    .map(|&(a, b, confidence)| DuplicatePair {
+                        a: member(a),
+                        b: member(b),
+                        confidence,
+                    })
+                    .collect(),
+                tracks: members
+                    .iter()
+                    .map(|&i| DuplicateTrack {
+                        path: tracks[i].path.clone(),
+                        title: tracks[i].title.clone(),
+                        author: tracks[i].author.clone(),
+                        format: tracks[i].format.clone(),
+                        duration_seconds: tracks[i].duration_seconds,
+                    })
+                    .collect(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fingerprint() -> Fingerprint {
+        Fingerprint {
+            amp: 0.2,
+            density: 900,
+            variance: 0.1,
+            punch: 3.0,
+            brightness: 0.4,
+            hist: Some([10, 40, 255, 90, 20, 5, 0, 0]),
+            sections: Some([200, 255, 230, 180]),
+            tempo: Some(4),
+            centroid: None,
+            flatness: None,
+            bands: None,
+            chroma: None,
+            rhythm_reg: None,
+            rhythm_str: None,
+            mfcc: Some([127, 12, 5, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0]),
+            mfcc_d: None,
+            mfcc_dd: None,
+            chromagram: None,
+        }
+    }
+
+    /// Track whose waveform is a sine of `cycles` periods
+    fn track(path: &str, cycles: f32, duration: f32) -> TrackMetadata {
+        let peaks: Vec<u8> = (0..WAVEFORM_BARS)
+            .map(|i| {
+                let phase = i as f32 / WAVEFORM_BARS as f32 * cycles * std::f32::consts::TAU;
+                (128.0 + 100.0 * phase.sin()) as u8
+            })
+            .collect();
+        TrackMetadata {
+            path: path.to_string(),
+            title: path.to_string(),
+            author: "Mad Max".to_string(),
+            format: "SNDH".to_string(),
+            year: None,
+            subsongs: 1,
+            channels: 3,
+            duration_seconds: Some(duration),
+            collection: "sndh".to_string(),
+            w: Some(BASE64.encode(peaks)),
+            fp: Some(fingerprint()),
+            art: None,
+            tags: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_similarity_follows_waveform() {
+        let a = track("a.sndh", 3.0, 200.0);
+        let same = track("b.sndh", 3.0, 201.0);
+        let other = track("c.sndh", 7.0, 200.0);
+        assert!(track_similarity(&a, &same).unwrap() > 0.99);
+        assert!(track_similarity(&a, &other).unwrap() < DEFAULT_DUPLICATE_THRESHOLD);
+
+        let mut unscanned = track("d.sndh", 3.0, 200.0);
+        unscanned.w = None;
+        assert_eq!(track_similarity(&a, &unscanned), None);
+    }
+
+    #[test]
+    fn test_find_duplicates_groups_chains() {
+        let mut unscanned = track("e.ym", 3.0, 200.0);
+        unscanned.fp = None;
+        let mut unknown_length = track("f.ym", 3.0, 0.0);
+        unknown_length.duration_seconds = None;
+        let tracks = vec![
+            track("a.sndh", 3.0, 200.0),
+            track("b.sndh", 7.0, 200.0),
+            track("c.ym", 3.0, 204.0),
+            unknown_length,
+            // Same waveform, but a whole loop longer
+            track("d.ay", 3.0, 400.0),
+            unscanned,
+        ];
+
+        let report = find_duplicates(&tracks, DEFAULT_DUPLICATE_THRESHOLD);
+        assert_eq!((report.compared, report.skipped), (5, 1));
+        assert_eq!(report.groups.len(), 1);
+        let group = &report.groups[0];
+        let paths: Vec<&str> = group.tracks.iter().map(|t| t.path.as_str()).collect();
+        // d is only compared with the track of unknown length, which chains
+        // it into the group
+        assert_eq!(paths, ["a.sndh", "c.ym", "f.ym", "d.ay"]);
+        assert_eq!(group.pairs.len(), 4);
+        assert!(group.pairs.iter().all(|p| p.b != 3 || p.a == 2));
+        assert_eq!(group.confidence, group.pairs[0].confidence);
+        assert!(
+            group
+                .pairs
+                .windows(2)
+                .all(|w| w[0].confidence >= w[1].confidence)
+        );
+    }
+}
@@ -0,0 +1,27 @@
+//! Error handling for metadata extraction.
+
+use thiserror::Error;
+
+/// Convenient result alias for metadata extraction.
+pub type Result<T> = std::result::Result<T, ExtractError>;
+
+/// Reasons a file produces no catalog entry.
+#[derive(Debug, Error)]
+pub enum ExtractError {
+    /// The file could not be read.
+    #[error("read failed: {0}")]
+    Io(#[from] std::io::Error),
+    /// The file is empty.
+    #[error("empty file")]
+    Empty,
+    /// Not a YM, SNDH, AY or Arkos Tracker file.
+    #[error("unsupported format")]
+    Unsupported,
+    /// The replayer rejected the file.
+    #[error("{0}")]
+    Parse(String),
+    /// The waveform scan rendered only silence, which usually means the
+    /// replay code failed.
+    #[error("rendered silence")]
+    Silent,
+}
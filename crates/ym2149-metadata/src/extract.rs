@@ -0,0 +1,502 @@
+//! Catalog entry extraction.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use ym2149_arkos_replayer::load_aks;
+use ym2149_ay_replayer::AyPlayer;
+use ym2149_catalog::TrackMetadata;
+use ym2149_common::{ChiptuneFormat, ChiptunePlayerBase, detect_format};
+use ym2149_sndh_replayer::{SndhFile, SndhMetadata, load_sndh};
+use ym2149_ym_replayer::load_song;
+
+use crate::SAMPLE_RATE;
+use crate::artwork::{self, ArtOptions};
+use crate::catalog::{catalog_path, detect_collection};
+use crate::error::{ExtractError, Result};
+use crate::fingerprint::{WaveformScan, generate_waveform};
+
+// Render length for songs without a known duration (seconds)
+const DEFAULT_DURATION: f32 = 180.0;
+
+/// What [`extract`] and [`extract_bytes`] compute besides the tags
+#[derive(Debug, Clone, Default)]
+pub struct ExtractOptions {
+    /// Directory the catalog path is relative to (default: the path as given)
+    pub base_path: Option<PathBuf>,
+    /// Generate waveform peaks and a fingerprint
+    pub waveforms: Option<WaveformScan>,
+    /// Look up artwork next to the file (ignored by [`extract_bytes`])
+    pub art: Option<ArtOptions>,
+}
+
+/// Extract the catalog entry of a song file.
+///
+/// The collection comes from the path (see [`detect_collection`]) or, for
+/// files outside the known collections, from the format. Fails if the file
+/// can't be read or parsed, or if its waveform scan rendered only silence.
+pub fn extract(path: &Path, options: &ExtractOptions) -> Result<TrackMetadata> {
+    let data = fs::read(path)?;
+    if data.is_empty() {
+        return Err(ExtractError::Empty);
+    }
+    let name = catalog_path(path, options.base_path.as_deref().unwrap_or(Path::new("")));
+    let collection = detect_collection(path).map(|(id, ..)| id);
+    let mut track = extract_data(&data, name, path, collection, options.waveforms)?;
+
+    if let Some(art) = options.art {
+        let peaks = track.w.as_ref().and_then(|w| BASE64.decode(w).ok());
+        track.art = artwork::artwork(
+            path,
+            options.base_path.as_deref().unwrap_or(Path::new("")),
+            peaks.as_deref(),
+            art,
+        );
+    }
+    Ok(track)
+}
+
+/// Extract the catalog entry of a song held in memory.
+///
+/// `name` is stored as the catalog path; its extension helps format
+/// detection, and its file and directory names are the fallback title and
+/// author like for [`extract`].
+pub fn extract_bytes(data: &[u8], name: &str, options: &ExtractOptions) -> Result<TrackMetadata> {
+    if data.is_empty() {
+        return Err(ExtractError::Empty);
+    }
+    let path = Path::new(name);
+    let collection = detect_collection(path).map(|(id, ..)| id);
+    extract_data(
+        data,
+        name.replace('\\', "/"),
+        path,
+        collection,
+        options.waveforms,
+    )
+}
+
+fn extract_data(
+    data: &[u8],
+    path_str: String,
+    path: &Path,
+    collection: Option<&str>,
+    waveforms: Option<WaveformScan>,
+) -> Result<TrackMetadata> {
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+    let kind = detect_kind(data, &ext);
+    let collection_id = collection.unwrap_or(match kind.as_str() {
+        "aks" => "arkos",
+        other => other,
+    });
+
+    // Extract artist hint from directory structure
+    let artist_hint = path
+        .parent()
+        .and_then(|p| p.file_name())
+        .and_then(|n| n.to_str())
+        .map(|s| {
+            // Clean up artist names like "Hippel.Jochen (Mad Max)" -> "Mad Max"
+            if let Some(start) = s.find('(') {
+                if let Some(end) = s.find(')') {
+                    return s[start + 1..end].to_string();
+                }
+            }
+            // Or "LastName.FirstName" -> "FirstName LastName"
+            if s.contains('.') && !s.ends_with('.') {
+                let parts: Vec<&str> = s.split('.').collect();
+                if parts.len() == 2 {
+                    return format!("{} {}", parts[1], parts[0]);
+                }
+            }
+            s.to_string()
+        });
+
+    let result = match kind.as_str() {
+        "sndh" => extract_sndh_metadata(data, path_str, collection_id, artist_hint, waveforms),
+        "ym" => extract_ym_metadata(data, path_str, collection_id, artist_hint, path, waveforms),
+        "ay" => extract_ay_metadata(data, path_str, collection_id, artist_hint, waveforms),
+        "aks" => extract_aks_metadata(data, path_str, collection_id, artist_hint, waveforms),
+        _ => Err(ExtractError::Unsupported),
+    };
+
+    // Silence usually means the replay code failed (e.g. Z80 emulation)
+    match result {
+        Ok(track) if track.fp.as_ref().is_some_and(|fp| fp.amp < 0.001) => {
+            Err(ExtractError::Silent)
+        }
+        result => result,
+    }
+}
+
+/// Format of a file: `sndh`, `ym`, `ay`, `aks` or the lowercase extension.
+///
+/// Exact header magic wins over the extension, so misnamed rips still parse.
+fn detect_kind(data: &[u8], ext: &str) -> String {
+    let guess = detect_format(data);
+    match guess.format {
+        ChiptuneFormat::Ym | ChiptuneFormat::Arkos | ChiptuneFormat::Ay | ChiptuneFormat::Sndh
+            if guess.confidence >= 1.0 =>
+        {
+            guess.format.name().to_ascii_lowercase()
+        }
+        _ => ext.to_string(),
+    }
+}
+
+fn parse_error(error: impl std::fmt::Display) -> ExtractError {
+    ExtractError::Parse(error.to_string())
+}
+
+fn extract_sndh_metadata(
+    data: &[u8],
+    path: String,
+    collection: &str,
+    artist_hint: Option<String>,
+    waveforms: Option<WaveformScan>,
+) -> Result<TrackMetadata> {
+    if detect_format(data).format != ChiptuneFormat::Sndh {
+        return Err(ExtractError::Unsupported);
+    }
+
+    let sndh = SndhFile::parse(data).map_err(parse_error)?;
+    let meta = &sndh.metadata;
+
+    let title = meta
+        .title
+        .clone()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| {
+            path.rsplit('/')
+                .next()
+                .unwrap_or(&path)
+                .trim_end_matches(".sndh")
+                .trim_end_matches(".SNDH")
+                .to_string()
+        });
+
+    let author = meta
+        .author
+        .clone()
+        .filter(|s| !s.is_empty())
+        .or(artist_hint)
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let duration = sndh_duration(meta);
+
+    // Generate waveform if requested
+    let (w, fp) = if let Some(scan) = waveforms {
+        if let Ok(mut player) = load_sndh(data, SAMPLE_RATE) {
+            let _ = player.init_subsong(1);
+            player.play(); // Must start playback before generating samples
+            let dur = duration.unwrap_or(DEFAULT_DURATION);
+            let wave_data = generate_waveform(&mut player, dur, scan);
+            (Some(wave_data.waveform), Some(wave_data.fingerprint))
+        } else {
+            (None, None)
+        }
+    } else {
+        (None, None)
+    };
+
+    Ok(TrackMetadata {
+        path,
+        title,
+        author,
+        format: "SNDH".to_string(),
+        year: meta.year.clone().filter(|s| !s.is_empty()),
+        subsongs: meta.subsong_count.max(1) as u32,
+        channels: 3,
+        duration_seconds: duration,
+        collection: collection.to_string(),
+        w,
+        fp,
+        art: None,
+        tags: Vec::new(),
+    })
+}
+
+/// Duration of the first subsong from frame count and player rate
+fn sndh_duration(meta: &SndhMetadata) -> Option<f32> {
+    meta.subsong_frames
+        .first()
+        .filter(|&&f| f > 0)
+        .map(|&frames| frames as f32 / meta.player_rate as f32)
+        .or_else(|| {
+            // Fallback to TIME durations if FRMS not available
+            meta.subsong_durations.first().map(|&d| d as f32)
+        })
+}
+
+fn extract_ym_metadata(
+    data: &[u8],
+    path: String,
+    collection: &str,
+    artist_hint: Option<String>,
+    file_path: &Path,
+    waveforms: Option<WaveformScan>,
+) -> Result<TrackMetadata> {
+    // Try to load as YM file
+    let (mut player, summary) = load_song(data).map_err(parse_error)?;
+
+    let info = player.info();
+
+    let title = info
+        .map(|i| i.song_name.clone())
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| {
+            file_path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("Unknown")
+                .to_string()
+        });
+
+    let author = info
+        .map(|i| i.author.clone())
+        .filter(|s| !s.is_empty())
+        .or(artist_hint)
+        .unwrap_or_else(|| "Unknown".to_string());
+
+    let duration = player.get_duration_seconds();
+
+    // Generate waveform if requested
+    let (w, fp) = if let Some(scan) = waveforms.filter(|_| duration > 0.0) {
+        player.play(); // Must start playback before generating samples
+        let wave_data = generate_waveform(&mut player, duration, scan);
+        (Some(wave_data.waveform), Some(wave_data.fingerprint))
+    } else {
+        (None, None)
+    };
+
+    Ok(TrackMetadata {
+        path,
+        title,
+        author,
+        format: summary.format.to_string(),
+        year: None,
+        subsongs: 1,
+        channels: 3,
+        duration_seconds: if duration > 0.0 { Some(duration) } else { None },
+        collection: collection.to_string(),
+        w,
+        fp,
+        art: None,
+        tags: Vec::new(),
+    })
+}
+
+fn extract_ay_metadata(
+    data: &[u8],
+    path: String,
+    collection: &str,
+    artist_hint: Option<String>,
+    waveforms: Option<WaveformScan>,
+) -> Result<TrackMetadata> {
+    let (mut player, meta) = AyPlayer::load_from_bytes(data, 0).map_err(parse_error)?;
+
+    let title = if meta.song_name.is_empty() {
+        path.rsplit('/')
+            .next()
+            .unwrap_or(&path)
+            .trim_end_matches(".ay")
+            .trim_end_matches(".AY")
+            .to_string()
+    } else {
+        meta.song_name.clone()
+    };
+
+    let author = if meta.author.is_empty() {
+        artist_hint.unwrap_or_else(|| "Unknown".to_string())
+    } else {
+        meta.author.clone()
+    };
+
+    let duration = meta.frame_count.map(|f| f as f32 / 50.0);
+
+    // Generate waveform if requested
+    let (w, fp) = if let Some(scan) = waveforms {
+        if let Some(dur) = duration {
+            let _ = player.play(); // Must start playback before generating samples
+            let wave_data = generate_waveform(&mut player, dur, scan);
+            (Some(wave_data.waveform), Some(wave_data.fingerprint))
+        } else {
+            (None, None)
+        }
+    } else {
+        (None, None)
+    };
+
+    Ok(TrackMetadata {
+        path,
+        title,
+        author,
+        format: "AY".to_string(),
+        year: None,
+        subsongs: meta.song_count as u32,
+        channels: 3,
+        duration_seconds: duration,
+        collection: collection.to_string(),
+        w,
+        fp,
+        art: None,
+        tags: Vec::new(),
+    })
+}
+
+fn extract_aks_metadata(
+    data: &[u8],
+    path: String,
+    collection: &str,
+    artist_hint: Option<String>,
+    waveforms: Option<WaveformScan>,
+) -> Result<TrackMetadata> {
+    let song = load_aks(data).map_err(parse_error)?;
+
+    let title = if song.metadata.title.is_empty() {
+        path.rsplit('/')
+            .next()
+            .unwrap_or(&path)
+            .trim_end_matches(".aks")
+            .trim_end_matches(".AKS")
+            .to_string()
+    } else {
+        song.metadata.title.clone()
+    };
+
+    let author = if song.metadata.author.is_empty() {
+        artist_hint.unwrap_or_else(|| "Unknown".to_string())
+    } else {
+        song.metadata.author.clone()
+    };
+
+    let duration = song
+        .subsongs
+        .first()
+        .map(|s| s.end_position as f32 / s.replay_frequency_hz);
+
+    // Channel count = PSG count * 3 channels per PSG
+    let channels = song
+        .subsongs
+        .first()
+        .map(|s| (s.psgs.len() * 3) as u32)
+        .unwrap_or(3);
+
+    // Generate waveform if requested
+    let (w, fp) = if let Some(scan) = waveforms {
+        if let Some(dur) = duration {
+            if let Ok(mut player) = ym2149_arkos_replayer::ArkosPlayer::new(song.clone(), 0) {
+                let _ = player.play(); // Must start playback before generating samples
+                let wave_data = generate_waveform(&mut player, dur, scan);
+                (Some(wave_data.waveform), Some(wave_data.fingerprint))
+            } else {
+                (None, None)
+            }
+        } else {
+            (None, None)
+        }
+    } else {
+        (None, None)
+    };
+
+    Ok(TrackMetadata {
+        path,
+        title,
+        author,
+        format: "AKS".to_string(),
+        year: None,
+        subsongs: song.subsongs.len() as u32,
+        channels,
+        duration_seconds: duration,
+        collection: collection.to_string(),
+        w,
+        fp,
+        art: None,
+        tags: Vec::new(),
+    })
+}
+
+/// Load a single track and start playback, returning the player and its duration
+pub fn open_track(path: &Path) -> Result<(Box<dyn ChiptunePlayerBase>, f32)> {
+    let data = fs::read(path)?;
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+
+    let kind = detect_kind(&data, &ext);
+
+    match kind.as_str() {
+        "sndh" => {
+            let sndh = SndhFile::parse(&data).map_err(parse_error)?;
+            let duration = sndh_duration(&sndh.metadata).unwrap_or(DEFAULT_DURATION);
+            let mut player = load_sndh(&data, SAMPLE_RATE).map_err(parse_error)?;
+            player.init_subsong(1).map_err(parse_error)?;
+            player.play();
+            Ok((Box::new(player), duration))
+        }
+        "ym" => {
+            let (mut player, _) = load_song(&data).map_err(parse_error)?;
+            let duration = player.get_duration_seconds();
+            player.play();
+            Ok((Box::new(player), duration))
+        }
+        "ay" => {
+            let (mut player, meta) = AyPlayer::load_from_bytes(&data, 0).map_err(parse_error)?;
+            let duration = meta
+                .frame_count
+                .map_or(DEFAULT_DURATION, |f| f as f32 / 50.0);
+            player.play().map_err(parse_error)?;
+            Ok((Box::new(player), duration))
+        }
+        "aks" => {
+            let song = load_aks(&data).map_err(parse_error)?;
+            let mut player =
+                ym2149_arkos_replayer::ArkosPlayer::new(song, 0).map_err(parse_error)?;
+            let duration = player.estimated_total_ticks() as f32 / player.replay_frequency_hz();
+            let _ = player.play();
+            Ok((Box::new(player), duration))
+        }
+        _ => Err(ExtractError::Unsupported),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ashtray() -> Vec<u8> {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../bevy_ym2149_examples/assets/music/Ashtray.ym");
+        fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_extract_bytes_ym() {
+        let options = ExtractOptions::default();
+        let track = extract_bytes(&ashtray(), "ym/Madmax/Ashtray.ym", &options).unwrap();
+        assert_eq!(track.path, "ym/Madmax/Ashtray.ym");
+        assert_eq!(track.collection, "ym");
+        assert_eq!(track.format, "YM5");
+        assert!(track.w.is_none());
+    }
+
+    #[test]
+    fn test_extract_bytes_rejects_unknown_data() {
+        let options = ExtractOptions::default();
+        assert!(matches!(
+            extract_bytes(&[], "empty.ym", &options),
+            Err(ExtractError::Empty)
+        ));
+        assert!(matches!(
+            extract_bytes(b"not a song", "notes.txt", &options),
+            Err(ExtractError::Unsupported)
+        ));
+    }
+}
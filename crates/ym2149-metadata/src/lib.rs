@@ -0,0 +1,53 @@
+//! Metadata, waveform and fingerprint extraction for YM2149 chiptune files.
+//!
+//! [`extract`] and [`extract_bytes`] build the catalog entry
+//! ([`TrackMetadata`]) of a YM, SNDH, AY or Arkos Tracker song with the same
+//! parsers and replayers as the rest of the workspace, optionally with
+//! waveform peaks, an audio fingerprint for similarity matching and artwork.
+//! The `ym-metadata` binary (feature `cli`, on by default) scans whole
+//! directories into a [`Catalog`] (schema in `ym2149-catalog`) for the web
+//! player, and [`find_duplicates`] lists tracks of a catalog that sound alike.
+//! [`apply_tags`] adds mood tags from a [`Tagger`] for mood-based shuffle.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::path::Path;
+//! use ym2149_metadata::{ExtractOptions, WaveformScan, extract};
+//!
+//! let options = ExtractOptions {
+//!     waveforms: Some(WaveformScan::Quick),
+//!     ..ExtractOptions::default()
+//! };
+//! let track = extract(Path::new("music/Ashtray.ym"), &options)?;
+//! println!("{} - {} ({:?}s)", track.author, track.title, track.duration_seconds);
+//! # Ok::<(), ym2149_metadata::ExtractError>(())
+//! ```
+
+#![warn(missing_docs)]
+
+pub mod artwork;
+mod catalog;
+pub mod dedupe;
+pub mod error;
+mod extract;
+mod fingerprint;
+pub mod spectrogram;
+pub mod tagger;
+
+pub use crate::artwork::ArtOptions;
+pub use crate::catalog::{catalog_path, detect_collection};
+pub use crate::dedupe::{
+    DEFAULT_DUPLICATE_THRESHOLD, DuplicateReport, find_duplicates, track_similarity,
+};
+pub use crate::error::{ExtractError, Result};
+pub use crate::extract::{ExtractOptions, extract, extract_bytes, open_track};
+pub use crate::fingerprint::{WAVEFORM_BARS, WaveformData, WaveformScan, generate_waveform};
+pub use crate::tagger::{HeuristicTagger, Tagger, apply_tags};
+pub use ym2149_catalog::{
+    CATALOG_VERSION, Catalog, CatalogError, CatalogVersion, CollectionInfo, Fingerprint,
+    TrackMetadata,
+};
+
+/// Sample rate used for waveform and spectrogram rendering
+pub const SAMPLE_RATE: u32 = 44100;
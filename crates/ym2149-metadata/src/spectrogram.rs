@@ -0,0 +1,323 @@
+//! Spectrogram PNG export.
+//!
+//! Renders a track offline and draws a log-frequency spectrogram using the
+//! same Hann-windowed FFT as the catalog fingerprints. Optional activity
+//! lanes below the spectrogram show when each PSG channel is sounding, read
+//! from the player's channel taps.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use rustfft::{FftPlanner, num_complex::Complex};
+use ym2149_common::{ChiptunePlayerBase, MAX_TAP_CAPACITY};
+
+use crate::SAMPLE_RATE;
+use crate::fingerprint::{FFT_SIZE, hann_window};
+
+/// Lowest frequency shown on the spectrogram (Hz)
+const MIN_FREQ: f32 = 30.0;
+/// Power mapped to black, in dB below a full-scale sine
+const DB_FLOOR: f32 = -90.0;
+/// Height of one channel activity lane in pixels
+const LANE_HEIGHT: usize = 12;
+/// Black gap above each activity lane in pixels
+const LANE_GAP: usize = 2;
+/// Background of an activity lane while the channel is silent
+const LANE_BACKGROUND: [u8; 3] = [24, 24, 24];
+/// Lane colors for channels A, B and C (repeated for every PSG)
+const LANE_COLORS: [[u8; 3]; 3] = [[255, 96, 96], [96, 255, 128], [96, 160, 255]];
+/// Color map from silence to full scale
+const PALETTE: [[u8; 3]; 5] = [
+    [0, 0, 0],
+    [48, 12, 96],
+    [184, 40, 88],
+    [248, 148, 32],
+    [255, 252, 200],
+];
+
+/// Spectrogram image layout
+pub struct SpectrogramOptions {
+    /// Image width in pixels (one column per time slice)
+    pub width: usize,
+    /// Height of the spectrogram area in pixels
+    pub height: usize,
+    /// Draw one activity lane per channel below the spectrogram
+    pub lanes: bool,
+}
+
+/// 8-bit RGB image
+pub struct Image {
+    /// Width in pixels
+    pub width: usize,
+    /// Height in pixels
+    pub height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Black image of the given size
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0; width * height * 3],
+        }
+    }
+
+    #[cfg(test)]
+    fn pixel(&self, x: usize, y: usize) -> [u8; 3] {
+        let i = (y * self.width + x) * 3;
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]]
+    }
+
+    /// Set one pixel
+    pub fn set_pixel(&mut self, x: usize, y: usize, rgb: [u8; 3]) {
+        let i = (y * self.width + x) * 3;
+        self.pixels[i..i + 3].copy_from_slice(&rgb);
+    }
+
+    /// Write the image as an RGB PNG file
+    pub fn write_png(&self, path: &Path) -> std::io::Result<()> {
+        self.encode_png(BufWriter::new(File::create(path)?))
+    }
+
+    /// Encode the image as an RGB PNG
+    pub fn encode_png<W: Write>(&self, out: W) -> std::io::Result<()> {
+        let mut encoder = png::Encoder::new(out, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// Render `duration` seconds of a playing track into a spectrogram image.
+///
+/// Time runs left to right and frequency bottom to top on a log scale from
+/// 30 Hz to Nyquist. Each column averages the power of all FFT windows
+/// (50% overlap) that end inside its time slice.
+pub fn render<P: ChiptunePlayerBase + ?Sized>(
+    player: &mut P,
+    duration: f32,
+    options: &SpectrogramOptions,
+) -> Image {
+    let width = options.width.max(1);
+    let height = options.height.max(1);
+    let total_samples = (duration * SAMPLE_RATE as f32) as usize;
+    let hop = (total_samples / width).max(1);
+    let step = FFT_SIZE / 2;
+
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+    let hann = hann_window(FFT_SIZE);
+    let rows = row_bins(height);
+
+    let channels = if options.lanes {
+        player.channel_count()
+    } else {
+        0
+    };
+    let tap_len = hop.min(MAX_TAP_CAPACITY);
+    if channels > 0 {
+        player.set_channel_tap(Some(tap_len));
+    }
+
+    // Start with a window of silence so the first column has full context
+    let mut history = vec![0.0f32; FFT_SIZE];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); FFT_SIZE];
+    let mut power = vec![0.0f32; FFT_SIZE / 2];
+    let mut tap = vec![0.0f32; tap_len];
+    let mut image = Image::new(width, height + channels * (LANE_GAP + LANE_HEIGHT));
+    let mut activity = vec![Vec::with_capacity(width); channels];
+
+    for x in 0..width {
+        // Generate in tap-sized pieces so every sample reaches the lanes
+        let mut energy = vec![0.0f32; channels];
+        let mut remaining = hop;
+        while remaining > 0 {
+            let count = remaining.min(tap_len);
+            history.extend(player.generate_samples(count));
+            for (channel, sum) in energy.iter_mut().enumerate() {
+                let read = player.read_channel_tap(channel, &mut tap[..count]);
+                *sum += tap[..read].iter().map(|s| s * s).sum::<f32>();
+            }
+            remaining -= count;
+        }
+
+        power.fill(0.0);
+        let windows = hop.div_ceil(step);
+        for k in 0..windows {
+            let end = history.len() - k * step;
+            for ((c, &s), &w) in buffer
+                .iter_mut()
+                .zip(&history[end - FFT_SIZE..end])
+                .zip(&hann)
+            {
+                *c = Complex::new(s * w, 0.0);
+            }
+            fft.process(&mut buffer);
+            for (p, c) in power.iter_mut().zip(&buffer) {
+                *p += c.norm_sqr();
+            }
+        }
+
+        // A full-scale sine peaks at FFT_SIZE / 4 through the Hann window
+        let full_scale = windows as f32 * (FFT_SIZE as f32 / 4.0).powi(2);
+        for (y, &(lo, hi)) in rows.iter().enumerate() {
+            let peak = power[lo..hi].iter().copied().fold(0.0f32, f32::max);
+            image.set_pixel(x, y, palette(level(peak / full_scale)));
+        }
+
+        history.drain(..history.len() - FFT_SIZE);
+        for (lane, sum) in activity.iter_mut().zip(energy) {
+            lane.push((sum / hop as f32).sqrt());
+        }
+    }
+
+    if channels > 0 {
+        player.set_channel_tap(None);
+        draw_lanes(&mut image, height, &activity);
+    }
+    image
+}
+
+/// FFT bin range `lo..hi` of every image row, top row (highest frequency) first
+fn row_bins(height: usize) -> Vec<(usize, usize)> {
+    let bins = FFT_SIZE / 2;
+    let bin_hz = SAMPLE_RATE as f32 / FFT_SIZE as f32;
+    let ratio = (SAMPLE_RATE as f32 / 2.0) / MIN_FREQ;
+    let edge = |i: usize| MIN_FREQ * ratio.powf(i as f32 / height as f32) / bin_hz;
+
+    (0..height)
+        .rev()
+        .map(|i| {
+            let lo = (edge(i) as usize).min(bins - 1);
+            let hi = (edge(i + 1).ceil() as usize).clamp(lo + 1, bins);
+            (lo, hi)
+        })
+        .collect()
+}
+
+/// Map relative power to 0.0 (at `DB_FLOOR` or below) ..= 1.0 (full scale)
+fn level(power: f32) -> f32 {
+    let db = 10.0 * power.max(1e-12).log10();
+    ((db - DB_FLOOR) / -DB_FLOOR).clamp(0.0, 1.0)
+}
+
+/// Interpolate the spectrogram color map
+fn palette(value: f32) -> [u8; 3] {
+    let scaled = value.clamp(0.0, 1.0) * (PALETTE.len() - 1) as f32;
+    let index = (scaled as usize).min(PALETTE.len() - 2);
+    let t = scaled - index as f32;
+    let (a, b) = (PALETTE[index], PALETTE[index + 1]);
+    std::array::from_fn(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8)
+}
+
+/// Draw per-channel RMS lanes below the spectrogram, normalized to the loudest channel
+fn draw_lanes(image: &mut Image, top: usize, activity: &[Vec<f32>]) {
+    let loudest = activity
+        .iter()
+        .flatten()
+        .copied()
+        .fold(0.0f32, f32::max)
+        .max(1e-6);
+
+    for (channel, lane) in activity.iter().enumerate() {
+        let y0 = top + channel * (LANE_GAP + LANE_HEIGHT) + LANE_GAP;
+        let color = LANE_COLORS[channel % LANE_COLORS.len()];
+        for (x, &rms) in lane.iter().enumerate() {
+            let t = (rms / loudest).sqrt();
+            let rgb = std::array::from_fn(|i| {
+                let bg = LANE_BACKGROUND[i] as f32;
+                (bg + (color[i] as f32 - bg) * t).round() as u8
+            });
+            for y in y0..y0 + LANE_HEIGHT {
+                image.set_pixel(x, y, rgb);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::PlaybackState;
+
+    /// Plays a sine on channel A and silence on channels B and C
+    struct SinePlayer {
+        freq: f32,
+        phase: usize,
+        tap: Vec<f32>,
+    }
+
+    impl ChiptunePlayerBase for SinePlayer {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Playing
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            for s in buffer.iter_mut() {
+                let t = self.phase as f32 / SAMPLE_RATE as f32;
+                *s = (2.0 * std::f32::consts::PI * self.freq * t).sin() * 0.5;
+                self.phase += 1;
+            }
+            self.tap = buffer.to_vec();
+        }
+        fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+            if channel == 0 {
+                let n = out.len().min(self.tap.len());
+                out[..n].copy_from_slice(&self.tap[self.tap.len() - n..]);
+                n
+            } else {
+                out.fill(0.0);
+                out.len()
+            }
+        }
+    }
+
+    #[test]
+    fn test_rows_cover_spectrum_top_down() {
+        let rows = row_bins(64);
+
+        assert_eq!(rows.len(), 64);
+        assert_eq!(rows[0].1, FFT_SIZE / 2);
+        assert!(rows.windows(2).all(|w| w[0].0 >= w[1].0));
+        assert!(rows.iter().all(|&(lo, hi)| lo < hi));
+    }
+
+    #[test]
+    fn test_sine_lights_matching_row_and_lane() {
+        let mut player = SinePlayer {
+            freq: 1000.0,
+            phase: 0,
+            tap: Vec::new(),
+        };
+        let options = SpectrogramOptions {
+            width: 8,
+            height: 128,
+            lanes: true,
+        };
+        let image = render(&mut player, 1.0, &options);
+
+        assert_eq!(image.height, 128 + 3 * (LANE_GAP + LANE_HEIGHT));
+
+        // Brightest row of the last column contains the 1 kHz bin
+        let brightness = |y: usize| image.pixel(7, y).iter().map(|&c| c as u32).sum::<u32>();
+        let brightest = (0..128).max_by_key(|&y| brightness(y)).unwrap();
+        let bin = (1000.0 * FFT_SIZE as f32 / SAMPLE_RATE as f32).round() as usize;
+        let (lo, hi) = row_bins(128)[brightest];
+        assert!(
+            (lo..hi + 1).contains(&bin),
+            "row {brightest} covers bins {lo}..{hi}"
+        );
+
+        // Channel A lane is lit, channel B lane stays at the background
+        let lane_y = |channel: usize| 128 + channel * (LANE_GAP + LANE_HEIGHT) + LANE_GAP;
+        assert_eq!(image.pixel(3, lane_y(0)), LANE_COLORS[0]);
+        assert_eq!(image.pixel(3, lane_y(1)), LANE_BACKGROUND);
+    }
+}
@@ -0,0 +1,151 @@
+//! Mood and style tags from audio fingerprints.
+//!
+//! A [`Tagger`] turns the fingerprint of a track into descriptive tags that
+//! are stored in the catalog ([`TrackMetadata::tags`]), so the web player
+//! can shuffle by mood. [`HeuristicTagger`] buckets a few fingerprint
+//! features; a trained classifier can be plugged in by implementing the
+//! trait (closures implement it too):
+//!
+//! ```rust
+//! use ym2149_metadata::{Fingerprint, TrackMetadata, apply_tags};
+//!
+//! # fn tag(tracks: &mut [TrackMetadata]) {
+//! let by_length = |track: &TrackMetadata, _: &Fingerprint| match track.duration_seconds {
+//!     Some(seconds) if seconds < 60.0 => vec!["jingle".to_string()],
+//!     _ => Vec::new(),
+//! };
+//! apply_tags(tracks, &by_length);
+//! # }
+//! ```
+
+use ym2149_catalog::{Fingerprint, TrackMetadata};
+
+/// Classifies tracks by their fingerprint.
+pub trait Tagger {
+    /// Tags of a track scanned with waveforms, lowercase words such as
+    /// `calm` or `bright`.
+    fn tags(&self, track: &TrackMetadata, fingerprint: &Fingerprint) -> Vec<String>;
+}
+
+impl<F> Tagger for F
+where
+    F: Fn(&TrackMetadata, &Fingerprint) -> Vec<String>,
+{
+    fn tags(&self, track: &TrackMetadata, fingerprint: &Fingerprint) -> Vec<String> {
+        self(track, fingerprint)
+    }
+}
+
+/// Builtin tagger that buckets loudness, brightness, beat and note density.
+///
+/// Each bucket adds at most one tag, and tracks in the middle of a range get
+/// none for it:
+///
+/// | Feature | Low | High |
+/// |---------|-----|------|
+/// | Average amplitude | `calm` | `energetic` |
+/// | Brightness (high-frequency content) | `dark` | `bright` |
+/// | Rhythm strength | `floating` | `driving` |
+/// | Note density (zero crossings) | `sparse` | `busy` |
+///
+/// The thresholds split full scans of typical SNDH and YM songs roughly into
+/// thirds; quick scans have weaker rhythm and lean towards `floating`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTagger;
+
+impl HeuristicTagger {
+    const AMP: (f32, f32) = (0.16, 0.2);
+    const BRIGHTNESS: (f32, f32) = (0.075, 0.1);
+    const RHYTHM: (f32, f32) = (0.52, 0.62);
+    const DENSITY: (u32, u32) = (730, 1050);
+}
+
+/// `low` below the first threshold, `high` above the second
+fn bucket<T: PartialOrd>(
+    value: T,
+    (low_below, high_above): (T, T),
+    low: &str,
+    high: &str,
+) -> Option<String> {
+    if value < low_below {
+        Some(low.to_string())
+    } else if value > high_above {
+        Some(high.to_string())
+    } else {
+        None
+    }
+}
+
+impl Tagger for HeuristicTagger {
+    fn tags(&self, _track: &TrackMetadata, fp: &Fingerprint) -> Vec<String> {
+        [
+            bucket(fp.amp, Self::AMP, "calm", "energetic"),
+            bucket(fp.brightness, Self::BRIGHTNESS, "dark", "bright"),
+            fp.rhythm_str
+                .and_then(|strength| bucket(strength, Self::RHYTHM, "floating", "driving")),
+            bucket(fp.density, Self::DENSITY, "sparse", "busy"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+/// Replace the tags of every track that has a fingerprint with those of
+/// `tagger` (sorted, without duplicates). Tracks scanned without waveforms
+/// keep their tags.
+pub fn apply_tags(tracks: &mut [TrackMetadata], tagger: &dyn Tagger) {
+    for track in tracks {
+        let Some(fingerprint) = &track.fp else {
+            continue;
+        };
+        let mut tags = tagger.tags(track, fingerprint);
+        tags.sort();
+        tags.dedup();
+        track.tags = tags;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(amp: f32, brightness: f32, rhythm_str: f32, density: u32) -> TrackMetadata {
+        let json = format!(
+            r#"{{"path": "sndh/a.sndh", "title": "A", "author": "B", "format": "SNDH",
+                "collection": "sndh", "tags": ["old"],
+                "fp": {{"amp": {amp}, "density": {density}, "variance": 0.1, "punch": 5.0,
+                        "brightness": {brightness}, "rhythm_str": {rhythm_str}}}}}"#
+        );
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_heuristic_tags_extremes_only() {
+        let mut tracks = [
+            track(0.25, 0.13, 0.8, 1200),
+            track(0.1, 0.03, 0.4, 500),
+            track(0.18, 0.09, 0.55, 900),
+        ];
+        apply_tags(&mut tracks, &HeuristicTagger);
+        assert_eq!(tracks[0].tags, ["bright", "busy", "driving", "energetic"]);
+        assert_eq!(tracks[1].tags, ["calm", "dark", "floating", "sparse"]);
+        assert!(tracks[2].tags.is_empty());
+    }
+
+    #[test]
+    fn test_apply_tags_skips_unscanned_tracks() {
+        let mut tracks = [track(0.25, 0.13, 0.8, 1200), track(0.1, 0.03, 0.4, 500)];
+        tracks[1].fp = None;
+        let tagger = |track: &TrackMetadata, _: &Fingerprint| {
+            vec![
+                track.collection.clone(),
+                "chip".to_string(),
+                "chip".to_string(),
+            ]
+        };
+        apply_tags(&mut tracks, &tagger);
+        assert_eq!(tracks[0].tags, ["chip", "sndh"]);
+        assert_eq!(tracks[1].tags, ["old"]);
+    }
+}
@@ -0,0 +1,37 @@
+//! Python bindings for the YM2149 replayers.
+//!
+//! With the `python` feature this crate builds the `ym2149` Python module:
+//! a `Player` that loads YM, SNDH, AY and Arkos Tracker songs and renders
+//! them into NumPy arrays, and `extract`/`extract_bytes`, which return the
+//! same catalog entries (tags, waveform peaks, fingerprints) as the
+//! `ym-metadata` tool. Build and install it with [maturin]:
+//!
+//! ```bash
+//! cd crates/ym2149-py
+//! maturin develop --release
+//! ```
+//!
+//! ```python
+//! import ym2149
+//!
+//! player = ym2149.Player.from_file("Ashtray.ym")
+//! print(player.author, "-", player.title, player.duration)
+//! player.play()
+//! samples = player.generate_samples(player.sample_rate * 10)  # numpy.float32
+//!
+//! track = ym2149.extract("sndh/Hippel/Wings_Of_Death.sndh", waveforms=True)
+//! print(track["fp"]["mfcc"], track["waveform"].shape)
+//! ```
+//!
+//! Without the feature only the format-independent [`Song`] loader is
+//! built, so the workspace compiles without a Python toolchain.
+//!
+//! [maturin]: https://www.maturin.rs
+
+#![warn(missing_docs)]
+
+#[cfg(feature = "python")]
+mod python;
+mod song;
+
+pub use song::Song;
@@ -0,0 +1,299 @@
+//! The `ym2149` Python module.
+
+use std::path::PathBuf;
+use std::sync::{Mutex, MutexGuard};
+
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use numpy::PyArray1;
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use ym2149_common::PlaybackState;
+use ym2149_metadata::{ExtractError, ExtractOptions, TrackMetadata, WaveformScan};
+
+use crate::Song;
+
+/// Chiptune player for YM, SNDH, AY and Arkos Tracker songs.
+///
+/// Renders mono float32 samples at `sample_rate` Hz.
+#[pyclass(module = "ym2149")]
+struct Player {
+    song: Mutex<Song>,
+}
+
+impl Player {
+    fn song(&self) -> MutexGuard<'_, Song> {
+        // A panic while rendering leaves the player usable
+        self.song.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[pymethods]
+impl Player {
+    /// Load a song from its file contents; the format is detected.
+    #[new]
+    fn new(data: &[u8]) -> PyResult<Self> {
+        let song = Song::load(data).map_err(PyValueError::new_err)?;
+        Ok(Self {
+            song: Mutex::new(song),
+        })
+    }
+
+    /// Load a song file.
+    #[staticmethod]
+    fn from_file(path: PathBuf) -> PyResult<Self> {
+        let data = std::fs::read(&path)
+            .map_err(|e| PyOSError::new_err(format!("{}: {e}", path.display())))?;
+        Self::new(&data)
+    }
+
+    /// Song title.
+    #[getter]
+    fn title(&self) -> String {
+        self.song().title.clone()
+    }
+
+    /// Composer.
+    #[getter]
+    fn author(&self) -> String {
+        self.song().author.clone()
+    }
+
+    /// Comments (empty if none).
+    #[getter]
+    fn comments(&self) -> String {
+        self.song().comments.clone()
+    }
+
+    /// Format name, e.g. `"YM"`, `"SNDH"`, `"AY"` or `"AKS"`.
+    #[getter]
+    fn format(&self) -> String {
+        self.song().format.clone()
+    }
+
+    /// Song length in seconds (0.0 if unknown).
+    #[getter]
+    fn duration(&self) -> f32 {
+        self.song().player.duration_seconds()
+    }
+
+    /// Playback position in seconds.
+    #[getter]
+    fn elapsed(&self) -> f32 {
+        self.song().player.elapsed_seconds()
+    }
+
+    /// Output sample rate in Hz.
+    #[getter]
+    fn sample_rate(&self) -> u32 {
+        self.song().player.sample_rate()
+    }
+
+    /// Number of channels (3 per PSG).
+    #[getter]
+    fn channel_count(&self) -> usize {
+        self.song().player.channel_count()
+    }
+
+    /// Number of subsongs.
+    #[getter]
+    fn subsong_count(&self) -> usize {
+        self.song().player.subsong_count()
+    }
+
+    /// Current subsong (1-based); assign to switch.
+    #[getter]
+    fn subsong(&self) -> usize {
+        self.song().player.current_subsong()
+    }
+
+    #[setter]
+    fn set_subsong(&self, index: usize) -> PyResult<()> {
+        if self.song().player.set_subsong(index) {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!("no subsong {index}")))
+        }
+    }
+
+    /// `"stopped"`, `"playing"` or `"paused"`.
+    #[getter]
+    fn state(&self) -> &'static str {
+        match self.song().player.state() {
+            PlaybackState::Stopped => "stopped",
+            PlaybackState::Playing => "playing",
+            PlaybackState::Paused => "paused",
+        }
+    }
+
+    /// Start or resume playback.
+    fn play(&self) {
+        self.song().player.play();
+    }
+
+    /// Pause playback.
+    fn pause(&self) {
+        self.song().player.pause();
+    }
+
+    /// Stop playback and rewind.
+    fn stop(&self) {
+        self.song().player.stop();
+    }
+
+    /// Seek to `position` (0.0 = start, 1.0 = end). Returns False if the
+    /// song can't seek.
+    fn seek(&self, position: f32) -> bool {
+        self.song().player.seek(position)
+    }
+
+    /// Mute or unmute a channel (0-based).
+    #[pyo3(signature = (channel, mute = true))]
+    fn set_channel_mute(&self, channel: usize, mute: bool) {
+        self.song().player.set_channel_mute(channel, mute);
+    }
+
+    /// Whether a channel (0-based) is muted.
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.song().player.is_channel_muted(channel)
+    }
+
+    /// Render `count` mono samples as a float32 NumPy array.
+    ///
+    /// Returns silence unless the player is playing. Releases the GIL while
+    /// rendering, so several players can render in parallel threads.
+    fn generate_samples<'py>(&self, py: Python<'py>, count: usize) -> Bound<'py, PyArray1<f32>> {
+        let samples = py.detach(|| {
+            let mut samples = vec![0.0; count];
+            self.song().player.generate_samples_into(&mut samples);
+            samples
+        });
+        PyArray1::from_vec(py, samples)
+    }
+
+    fn __repr__(&self) -> String {
+        let song = self.song();
+        format!(
+            "<ym2149.Player {} - {} ({})>",
+            song.author, song.title, song.format
+        )
+    }
+}
+
+/// Load a song from its file contents (same as `Player(data)`).
+#[pyfunction]
+fn load(data: &[u8]) -> PyResult<Player> {
+    Player::new(data)
+}
+
+/// Catalog entry of a song file as a dict.
+///
+/// With `waveforms=True` the entry holds the audio fingerprint (`"fp"`) and
+/// the waveform peaks as a uint8 NumPy array (`"waveform"`); `quick=True`
+/// renders only part of the song for approximate features. `base` makes
+/// `"path"` relative.
+#[pyfunction]
+#[pyo3(signature = (path, waveforms = false, quick = false, base = None))]
+fn extract<'py>(
+    py: Python<'py>,
+    path: PathBuf,
+    waveforms: bool,
+    quick: bool,
+    base: Option<PathBuf>,
+) -> PyResult<Bound<'py, PyDict>> {
+    let options = ExtractOptions {
+        base_path: base,
+        waveforms: waveform_scan(waveforms, quick),
+        art: None,
+    };
+    let track = py
+        .detach(|| ym2149_metadata::extract(&path, &options))
+        .map_err(|e| extract_error(e, &path.display().to_string()))?;
+    track_dict(py, &track)
+}
+
+/// Catalog entry of a song held in memory; `name` is its file name or path.
+#[pyfunction]
+#[pyo3(signature = (data, name, waveforms = false, quick = false))]
+fn extract_bytes<'py>(
+    py: Python<'py>,
+    data: &[u8],
+    name: &str,
+    waveforms: bool,
+    quick: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let options = ExtractOptions {
+        waveforms: waveform_scan(waveforms, quick),
+        ..ExtractOptions::default()
+    };
+    let track = py
+        .detach(|| ym2149_metadata::extract_bytes(data, name, &options))
+        .map_err(|e| extract_error(e, name))?;
+    track_dict(py, &track)
+}
+
+fn waveform_scan(waveforms: bool, quick: bool) -> Option<WaveformScan> {
+    waveforms.then_some(if quick {
+        WaveformScan::Quick
+    } else {
+        WaveformScan::Full
+    })
+}
+
+fn extract_error(error: ExtractError, name: &str) -> PyErr {
+    match error {
+        ExtractError::Io(e) => PyOSError::new_err(format!("{name}: {e}")),
+        e => PyValueError::new_err(format!("{name}: {e}")),
+    }
+}
+
+/// Catalog entry as a dict, with the base64 waveform decoded to an array
+fn track_dict<'py>(py: Python<'py>, track: &TrackMetadata) -> PyResult<Bound<'py, PyDict>> {
+    let value = serde_json::to_value(track).map_err(|e| PyValueError::new_err(e.to_string()))?;
+    let dict = json_to_py(py, &value)?.cast_into::<PyDict>()?;
+    if let Some(peaks) = track.w.as_ref().and_then(|w| BASE64.decode(w).ok()) {
+        dict.del_item("w")?;
+        dict.set_item("waveform", PyArray1::from_vec(py, peaks))?;
+    }
+    Ok(dict)
+}
+
+fn json_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(b) => b.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into_pyobject(py)?.into_any(),
+            None => n.as_f64().unwrap_or(0.0).into_pyobject(py)?.into_any(),
+        },
+        Value::String(s) => s.into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| json_to_py(py, item))
+                .collect::<PyResult<Vec<_>>>()?;
+            PyList::new(py, items)?.into_any()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, item) in map {
+                dict.set_item(key, json_to_py(py, item)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+/// YM2149 chiptune replayers and audio fingerprints.
+#[pymodule]
+#[pyo3(name = "ym2149")]
+fn ym2149_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add("SAMPLE_RATE", ym2149_metadata::SAMPLE_RATE)?;
+    m.add_class::<Player>()?;
+    m.add_function(wrap_pyfunction!(load, m)?)?;
+    m.add_function(wrap_pyfunction!(extract, m)?)?;
+    m.add_function(wrap_pyfunction!(extract_bytes, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+//! Format-independent song loading.
+
+use ym2149_arkos_replayer::{ArkosPlayer, load_aks};
+use ym2149_ay_replayer::{AyPlayer, CPC_UNSUPPORTED_MSG};
+use ym2149_common::{
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE, MetadataFields,
+    load_with_detection,
+};
+use ym2149_sndh_replayer::load_sndh;
+use ym2149_ym_replayer::load_song;
+
+/// A loaded song of any supported format with its tags.
+pub struct Song {
+    /// Replayer for the song
+    pub player: Box<dyn ChiptunePlayerBase>,
+    /// Song title
+    pub title: String,
+    /// Composer
+    pub author: String,
+    /// Comments (empty if none)
+    pub comments: String,
+    /// Format name (`YM`, `SNDH`, `AY`, `AKS`, ...)
+    pub format: String,
+}
+
+impl Song {
+    /// Load YM, SNDH, AY or AKS data, trying the best header match first.
+    ///
+    /// On failure the error lists why each parser rejected the data.
+    pub fn load(data: &[u8]) -> Result<Self, String> {
+        if data.is_empty() {
+            return Err("empty file data".to_string());
+        }
+        load_with_detection(data, |format| match format {
+            ChiptuneFormat::Ym => load_song(data)
+                .map(|(player, _)| Self::new(player))
+                .map_err(|e| e.to_string()),
+            ChiptuneFormat::Arkos => {
+                let song = load_aks(data).map_err(|e| e.to_string())?;
+                ArkosPlayer::new(song, 0)
+                    .map(Self::new)
+                    .map_err(|e| e.to_string())
+            }
+            ChiptuneFormat::Ay => {
+                let (player, _) = AyPlayer::load_from_bytes(data, 0).map_err(|e| e.to_string())?;
+                if player.requires_cpc_firmware() {
+                    return Err(CPC_UNSUPPORTED_MSG.to_string());
+                }
+                Ok(Self::new(player))
+            }
+            ChiptuneFormat::Sndh => {
+                let mut player = load_sndh(data, DEFAULT_SAMPLE_RATE).map_err(|e| e.to_string())?;
+                player
+                    .init_subsong(player.default_subsong())
+                    .map_err(|e| e.to_string())?;
+                Ok(Self::new(player))
+            }
+            ChiptuneFormat::Kss | ChiptuneFormat::Zip | ChiptuneFormat::Unknown => {
+                Err(format!("no {} player", format.name()))
+            }
+        })
+        .map_err(|e| e.to_string())
+    }
+
+    fn new<P: ChiptunePlayer + 'static>(player: P) -> Self {
+        let metadata = player.metadata();
+        Self {
+            title: metadata.title().to_string(),
+            author: metadata.author().to_string(),
+            comments: metadata.comments().to_string(),
+            format: metadata.format().to_string(),
+            player: Box::new(player),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::PlaybackState;
+    use ym2149_common::test_support::read_asset;
+
+    #[test]
+    fn test_load_renders_audio() {
+        let Some(data) = read_asset("Ashtray.ym") else {
+            return;
+        };
+        let mut song = Song::load(&data).unwrap();
+        assert_eq!(song.author, "Jochen Hippel");
+
+        song.player.play();
+        assert_eq!(song.player.state(), PlaybackState::Playing);
+        let samples = song.player.generate_samples(4410);
+        assert!(samples.iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn test_load_rejects_garbage() {
+        assert!(Song::load(&[]).is_err());
+        assert!(Song::load(b"not a song").is_err());
+    }
+}
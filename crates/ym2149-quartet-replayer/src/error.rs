@@ -0,0 +1,55 @@
+//! Error handling for the Quartet replayer.
+
+use thiserror::Error;
+
+/// Convenient result alias for Quartet parsing and playback.
+pub type Result<T> = std::result::Result<T, QuartetError>;
+
+/// Errors that may occur while parsing or replaying Quartet files.
+#[derive(Debug, Error)]
+pub enum QuartetError {
+    /// Data ended inside a header, command or sample.
+    #[error("unexpected end of file at offset 0x{offset:04x}")]
+    UnexpectedEof {
+        /// Offset of the structure that was cut short.
+        offset: usize,
+    },
+    /// A voice stream contains a command the replayer does not know.
+    #[error("unknown command 0x{code:04x} in voice {voice} at offset 0x{offset:04x}")]
+    UnknownCommand {
+        /// Voice (0-3) whose stream contains the command.
+        voice: usize,
+        /// Offset of the command inside the song file.
+        offset: usize,
+        /// Raw command word.
+        code: u16,
+    },
+    /// A note plays an instrument the voice set does not define.
+    #[error("voice {voice} plays instrument {instrument}, but the voice set has {available}")]
+    MissingInstrument {
+        /// Voice (0-3) playing the note.
+        voice: usize,
+        /// Requested instrument index.
+        instrument: usize,
+        /// Number of instruments in the voice set.
+        available: usize,
+    },
+    /// Generic validation error.
+    #[error("{msg}")]
+    InvalidData {
+        /// Human-readable explanation of the validation failure.
+        msg: String,
+    },
+}
+
+impl From<String> for QuartetError {
+    fn from(s: String) -> Self {
+        QuartetError::InvalidData { msg: s }
+    }
+}
+
+impl From<&str> for QuartetError {
+    fn from(s: &str) -> Self {
+        QuartetError::InvalidData { msg: s.to_string() }
+    }
+}
@@ -0,0 +1,388 @@
+//! Quartet song (`.4v`/`.qts`) and voice set (`.set`) structures.
+//!
+//! All values are big-endian, as written by the Atari ST editor.
+//!
+//! A song starts with a 4-byte header followed by the command streams of
+//! the four voices, one after the other, each ended by an `F` command:
+//!
+//! ```text
+//! 0x00  u16  tempo: sequencer ticks per second
+//! 0x02  u16  replay rate in Hz that pitch steps refer to
+//! 0x04  voice 1 commands ... 'F', voice 2 commands ... 'F', voice 3, voice 4
+//! ```
+//!
+//! Every command is 12 bytes:
+//!
+//! ```text
+//! +0   u16  command: 'P' note, 'S' slide, 'R' rest, 'L' loop start,
+//!           'l' loop end, 'F' end of voice
+//! +2   u16  duration in ticks ('P', 'S', 'R')
+//! +4   u32  'P': pitch step, 16.16 fixed-point sample increment per
+//!           replay-rate sample; 'S': signed change of the step per tick
+//! +8   u8   instrument ('P')
+//! +9   u8   reserved
+//! +10  u16  extra repeats of the loop body ('l')
+//! ```
+//!
+//! A voice set holds up to [`MAX_INSTRUMENTS`] signed 8-bit samples:
+//!
+//! ```text
+//! 0x00  u16  instrument count
+//! 0x02  20 bytes per instrument: name (8 bytes, NUL padded), u32 sample
+//!       offset from the start of the file, u32 length, u32 loop start
+//!       (0xFFFFFFFF for one-shot samples)
+//! ```
+
+use crate::error::{QuartetError, Result};
+
+/// Number of software voices mixed by Quartet.
+pub const VOICE_COUNT: usize = 4;
+
+/// Largest number of instruments in a voice set.
+pub const MAX_INSTRUMENTS: usize = 20;
+
+const SONG_HEADER_SIZE: usize = 4;
+const COMMAND_SIZE: usize = 12;
+const SET_HEADER_SIZE: usize = 2;
+const INSTRUMENT_ENTRY_SIZE: usize = 20;
+const NO_LOOP: u32 = 0xFFFF_FFFF;
+
+/// One entry of a voice's command stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Start `instrument` at `step` (16.16 at the replay rate) for `ticks`.
+    Note {
+        /// Instrument index into the voice set.
+        instrument: u8,
+        /// Sample increment per replay-rate sample, 16.16 fixed point.
+        step: u32,
+        /// Duration in sequencer ticks.
+        ticks: u16,
+    },
+    /// Change the playing note's step by `delta` every tick for `ticks`.
+    Slide {
+        /// Signed step change per tick, 16.16 fixed point.
+        delta: i32,
+        /// Duration in sequencer ticks.
+        ticks: u16,
+    },
+    /// Silence the voice for `ticks`.
+    Rest {
+        /// Duration in sequencer ticks.
+        ticks: u16,
+    },
+    /// Mark the start of a loop body.
+    LoopStart,
+    /// Play the loop body `repeats` more times.
+    LoopEnd {
+        /// Extra passes through the loop body.
+        repeats: u16,
+    },
+    /// End of the voice.
+    End,
+}
+
+impl Command {
+    /// Duration of the command in ticks (0 for markers).
+    pub fn ticks(&self) -> u16 {
+        match *self {
+            Command::Note { ticks, .. }
+            | Command::Slide { ticks, .. }
+            | Command::Rest { ticks } => ticks,
+            Command::LoopStart | Command::LoopEnd { .. } | Command::End => 0,
+        }
+    }
+}
+
+/// Parsed Quartet song.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuartetSong {
+    /// Sequencer ticks per second.
+    pub tempo: u16,
+    /// Replay rate in Hz that pitch steps refer to.
+    pub replay_rate: u16,
+    /// Command streams of the four voices, each ending with [`Command::End`].
+    pub voices: [Vec<Command>; VOICE_COUNT],
+}
+
+impl QuartetSong {
+    /// Length of one pass through the song in ticks (the longest voice).
+    pub fn length_ticks(&self) -> u64 {
+        self.voices
+            .iter()
+            .map(|commands| voice_ticks(commands))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Length of one pass through the song in seconds.
+    pub fn duration_seconds(&self) -> f32 {
+        self.length_ticks() as f32 / f32::from(self.tempo)
+    }
+}
+
+/// Ticks of one voice with loops expanded.
+fn voice_ticks(commands: &[Command]) -> u64 {
+    let mut total = 0u64;
+    let mut body = 0u64;
+    for command in commands {
+        match *command {
+            Command::LoopStart => body = 0,
+            Command::LoopEnd { repeats } => {
+                total = total.saturating_add(body.saturating_mul(u64::from(repeats)));
+                body = 0;
+            }
+            Command::End => break,
+            _ => {
+                let ticks = u64::from(command.ticks());
+                total = total.saturating_add(ticks);
+                body = body.saturating_add(ticks);
+            }
+        }
+    }
+    total
+}
+
+/// One sample of a voice set.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instrument {
+    /// Instrument name.
+    pub name: String,
+    /// Signed 8-bit sample data.
+    pub data: Vec<i8>,
+    /// Sample index playback wraps to at the end, `None` for one-shot samples.
+    pub loop_start: Option<usize>,
+}
+
+/// Parsed Quartet voice set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoiceSet {
+    /// Instruments in file order.
+    pub instruments: Vec<Instrument>,
+}
+
+/// Parse a Quartet song file.
+pub fn load_song(data: &[u8]) -> Result<QuartetSong> {
+    let tempo = read_u16(data, 0)?;
+    let replay_rate = read_u16(data, 2)?;
+    if tempo == 0 || replay_rate == 0 {
+        return Err("tempo and replay rate must be non-zero".into());
+    }
+
+    let mut offset = SONG_HEADER_SIZE;
+    let mut voices: [Vec<Command>; VOICE_COUNT] = Default::default();
+    for (voice, commands) in voices.iter_mut().enumerate() {
+        loop {
+            let command = read_command(data, offset, voice)?;
+            offset += COMMAND_SIZE;
+            commands.push(command);
+            if command == Command::End {
+                break;
+            }
+        }
+    }
+
+    Ok(QuartetSong {
+        tempo,
+        replay_rate,
+        voices,
+    })
+}
+
+/// Parse a Quartet voice set file.
+pub fn load_voice_set(data: &[u8]) -> Result<VoiceSet> {
+    let count = read_u16(data, 0)? as usize;
+    if count == 0 || count > MAX_INSTRUMENTS {
+        return Err(
+            format!("voice set has {count} instruments (1-{MAX_INSTRUMENTS} allowed)").into(),
+        );
+    }
+
+    let mut instruments = Vec::with_capacity(count);
+    for index in 0..count {
+        let entry = SET_HEADER_SIZE + index * INSTRUMENT_ENTRY_SIZE;
+        let name = bytes(data, entry, 8)?;
+        let name = name.split(|&b| b == 0).next().unwrap_or_default();
+        let start = read_u32(data, entry + 8)? as usize;
+        let length = read_u32(data, entry + 12)? as usize;
+        let loop_start = read_u32(data, entry + 16)?;
+
+        let samples = bytes(data, start, length)?;
+        let loop_start = match loop_start {
+            NO_LOOP => None,
+            start if (start as usize) < length => Some(start as usize),
+            start => {
+                return Err(format!(
+                    "instrument {index} loops at {start}, past its {length} samples"
+                )
+                .into());
+            }
+        };
+        instruments.push(Instrument {
+            name: String::from_utf8_lossy(name).trim_end().to_string(),
+            data: samples.iter().map(|&b| b as i8).collect(),
+            loop_start,
+        });
+    }
+
+    Ok(VoiceSet { instruments })
+}
+
+fn read_command(data: &[u8], offset: usize, voice: usize) -> Result<Command> {
+    let raw = bytes(data, offset, COMMAND_SIZE)?;
+    let code = u16::from_be_bytes([raw[0], raw[1]]);
+    let ticks = u16::from_be_bytes([raw[2], raw[3]]);
+    let value = u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]);
+    let command = match code {
+        0x50 => Command::Note {
+            instrument: raw[8],
+            step: value,
+            ticks,
+        },
+        0x53 => Command::Slide {
+            delta: value as i32,
+            ticks,
+        },
+        0x52 => Command::Rest { ticks },
+        0x4C => Command::LoopStart,
+        0x6C => Command::LoopEnd {
+            repeats: u16::from_be_bytes([raw[10], raw[11]]),
+        },
+        0x46 => Command::End,
+        code => {
+            return Err(QuartetError::UnknownCommand {
+                voice,
+                offset,
+                code,
+            });
+        }
+    };
+    Ok(command)
+}
+
+fn bytes(data: &[u8], offset: usize, len: usize) -> Result<&[u8]> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(QuartetError::UnexpectedEof { offset })
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16> {
+    let raw = bytes(data, offset, 2)?;
+    Ok(u16::from_be_bytes([raw[0], raw[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let raw = bytes(data, offset, 4)?;
+    Ok(u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command(code: u8, ticks: u16, value: u32, instrument: u8, repeats: u16) -> Vec<u8> {
+        let mut raw = vec![0, code];
+        raw.extend_from_slice(&ticks.to_be_bytes());
+        raw.extend_from_slice(&value.to_be_bytes());
+        raw.extend_from_slice(&[instrument, 0]);
+        raw.extend_from_slice(&repeats.to_be_bytes());
+        raw
+    }
+
+    #[test]
+    fn test_parse_song_commands() {
+        let mut data = vec![0, 50, 0x2B, 0x11];
+        data.extend(command(b'L', 0, 0, 0, 0));
+        data.extend(command(b'P', 10, 0x0001_0000, 2, 0));
+        data.extend(command(b'S', 5, (-256i32) as u32, 0, 0));
+        data.extend(command(b'l', 0, 0, 0, 3));
+        data.extend(command(b'F', 0, 0, 0, 0));
+        for _ in 1..VOICE_COUNT {
+            data.extend(command(b'R', 20, 0, 0, 0));
+            data.extend(command(b'F', 0, 0, 0, 0));
+        }
+
+        let song = load_song(&data).unwrap();
+        assert_eq!(song.tempo, 50);
+        assert_eq!(song.replay_rate, 11025);
+        assert_eq!(
+            song.voices[0],
+            vec![
+                Command::LoopStart,
+                Command::Note {
+                    instrument: 2,
+                    step: 0x0001_0000,
+                    ticks: 10
+                },
+                Command::Slide {
+                    delta: -256,
+                    ticks: 5
+                },
+                Command::LoopEnd { repeats: 3 },
+                Command::End,
+            ]
+        );
+        // Voice 1 plays its 15-tick body four times
+        assert_eq!(song.length_ticks(), 60);
+        assert_eq!(song.duration_seconds(), 1.2);
+    }
+
+    #[test]
+    fn test_song_errors() {
+        assert!(matches!(
+            load_song(&[0, 50]),
+            Err(QuartetError::UnexpectedEof { offset: 2 })
+        ));
+
+        let mut data = vec![0, 50, 0x2B, 0x11];
+        data.extend(command(b'X', 0, 0, 0, 0));
+        assert!(matches!(
+            load_song(&data),
+            Err(QuartetError::UnknownCommand {
+                voice: 0,
+                offset: 4,
+                code: 0x58
+            })
+        ));
+
+        // Voice 2 is missing its end marker
+        let mut data = vec![0, 50, 0x2B, 0x11];
+        data.extend(command(b'F', 0, 0, 0, 0));
+        data.extend(command(b'R', 20, 0, 0, 0));
+        assert!(matches!(
+            load_song(&data),
+            Err(QuartetError::UnexpectedEof { offset: 28 })
+        ));
+    }
+
+    #[test]
+    fn test_parse_voice_set() {
+        let mut data = vec![0, 2];
+        data.extend_from_slice(b"BASS\0\0\0\0");
+        data.extend_from_slice(&42u32.to_be_bytes());
+        data.extend_from_slice(&4u32.to_be_bytes());
+        data.extend_from_slice(&1u32.to_be_bytes());
+        data.extend_from_slice(b"SNARE\0\0\0");
+        data.extend_from_slice(&46u32.to_be_bytes());
+        data.extend_from_slice(&2u32.to_be_bytes());
+        data.extend_from_slice(&NO_LOOP.to_be_bytes());
+        data.extend_from_slice(&[0x7F, 0x80, 0x00, 0xFF, 0x10, 0x20]);
+
+        let set = load_voice_set(&data).unwrap();
+        assert_eq!(set.instruments.len(), 2);
+        assert_eq!(set.instruments[0].name, "BASS");
+        assert_eq!(set.instruments[0].data, vec![127, -128, 0, -1]);
+        assert_eq!(set.instruments[0].loop_start, Some(1));
+        assert_eq!(set.instruments[1].name, "SNARE");
+        assert_eq!(set.instruments[1].loop_start, None);
+
+        // Sample data past the end of the file
+        data.truncate(45);
+        assert!(matches!(
+            load_voice_set(&data),
+            Err(QuartetError::UnexpectedEof { offset: 42 })
+        ));
+        assert!(load_voice_set(&[0, 0]).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! Quartet song parser and software mixing player.
+//!
+//! Quartet (Microdeal, 1989) is a 4-voice sample tracker for the Atari ST.
+//! Its replay routine mixes four 8-bit sample voices in software and plays
+//! the result on the machine's sound hardware, which is why many ST games
+//! shipped Quartet songs next to their YM music. This crate provides:
+//!
+//! - Parsers for song files (`.4v`/`.qts`) and voice sets (`.set`), see
+//!   [`format`] for the layouts
+//! - [`QuartetPlayer`], which mixes the voices at the output rate and can
+//!   add any other [`ChiptunePlayerBase`] (for example a YM or SNDH song) as
+//!   accompaniment on the same sample clock
+//!
+//! Songs loop from the start once every voice has ended. Channel indices for
+//! muting are the four voices.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use ym2149_common::ChiptunePlayerBase;
+//! use ym2149_quartet_replayer::QuartetPlayer;
+//!
+//! let song = std::fs::read("music.4v")?;
+//! let set = std::fs::read("smp.set")?;
+//! let mut player = QuartetPlayer::load_from_bytes(&song, &set)?;
+//!
+//! player.play();
+//! let samples = player.generate_samples(882);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Parsing never panics on malformed data: every command and sample is
+//! bounds-checked and rejected with a [`QuartetError`].
+
+#![warn(missing_docs)]
+
+pub mod error;
+pub mod format;
+mod player;
+
+pub use crate::error::{QuartetError, Result};
+pub use crate::format::{
+    Command, Instrument, MAX_INSTRUMENTS, QuartetSong, VOICE_COUNT, VoiceSet, load_song,
+    load_voice_set,
+};
+pub use crate::player::QuartetPlayer;
+
+// Re-export unified player traits from ym2149-common
+pub use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, PlaybackState};
@@ -0,0 +1,571 @@
+//! Software mixing player for Quartet songs.
+
+use std::sync::Arc;
+
+use crate::error::{QuartetError, Result};
+use crate::format::{Command, QuartetSong, VOICE_COUNT, VoiceSet};
+use ym2149_common::{
+    BasicMetadata, ChiptunePlayer, ChiptunePlayerBase, DEFAULT_SAMPLE_RATE, OutputGain,
+    PlaybackRate, PlaybackState,
+};
+
+/// Commands a voice may consume in one tick before it is treated as stuck.
+const MAX_COMMANDS_PER_TICK: usize = 4096;
+/// Default level of the accompaniment relative to the voices.
+const DEFAULT_ACCOMPANIMENT_LEVEL: f32 = 0.5;
+
+/// Sequencer and sample position of one voice.
+#[derive(Debug, Clone, Default)]
+struct Voice {
+    /// Index of the next command.
+    pos: usize,
+    /// Ticks left of the current command.
+    remaining: u32,
+    /// Command index the active loop jumps back to.
+    loop_start: usize,
+    /// Passes left through the active loop (`None` outside a loop).
+    loop_count: Option<u16>,
+    finished: bool,
+    /// Sounding instrument, `None` while resting.
+    instrument: Option<usize>,
+    /// Sample position, 16.16 fixed point.
+    phase: u64,
+    /// Sample increment per replay-rate sample, 16.16 fixed point.
+    step: u32,
+    /// Step change per tick while sliding.
+    slide: i32,
+    muted: bool,
+}
+
+impl Voice {
+    /// Advance the sequencer by one tick.
+    fn tick(&mut self, commands: &[Command]) {
+        let mut budget = MAX_COMMANDS_PER_TICK;
+        while self.remaining == 0 && !self.finished {
+            let Some(&command) = commands.get(self.pos) else {
+                self.finished = true;
+                break;
+            };
+            budget -= 1;
+            if budget == 0 {
+                self.finished = true;
+                break;
+            }
+            self.pos += 1;
+            match command {
+                Command::Note {
+                    instrument,
+                    step,
+                    ticks,
+                } => {
+                    self.instrument = Some(usize::from(instrument));
+                    self.phase = 0;
+                    self.step = step;
+                    self.slide = 0;
+                    self.remaining = u32::from(ticks);
+                }
+                Command::Slide { delta, ticks } => {
+                    self.slide = delta;
+                    self.remaining = u32::from(ticks);
+                }
+                Command::Rest { ticks } => {
+                    self.instrument = None;
+                    self.slide = 0;
+                    self.remaining = u32::from(ticks);
+                }
+                Command::LoopStart => {
+                    self.loop_start = self.pos;
+                    self.loop_count = None;
+                }
+                Command::LoopEnd { repeats } => {
+                    let left = self.loop_count.unwrap_or(repeats);
+                    if left > 0 {
+                        self.loop_count = Some(left - 1);
+                        self.pos = self.loop_start;
+                    } else {
+                        self.loop_count = None;
+                    }
+                }
+                Command::End => {
+                    self.instrument = None;
+                    self.finished = true;
+                }
+            }
+        }
+
+        if self.remaining > 0 {
+            self.remaining -= 1;
+            if self.slide != 0 {
+                let step = i64::from(self.step) + i64::from(self.slide);
+                self.step = step.clamp(0, i64::from(u32::MAX)) as u32;
+            }
+        }
+    }
+
+    /// Produce the next sample (-1.0..1.0) and advance by `step_scale`.
+    fn sample(&mut self, set: &VoiceSet, step_scale: u64) -> f32 {
+        let Some(instrument) = self.instrument.and_then(|i| set.instruments.get(i)) else {
+            return 0.0;
+        };
+        let len = instrument.data.len();
+        let mut index = (self.phase >> 16) as usize;
+        if index >= len {
+            match instrument.loop_start {
+                Some(start) => {
+                    index = start + (index - start) % (len - start);
+                    self.phase = ((index as u64) << 16) | (self.phase & 0xFFFF);
+                }
+                None => {
+                    self.instrument = None;
+                    return 0.0;
+                }
+            }
+        }
+        let advance = u64::from(self.step).saturating_mul(step_scale) >> 16;
+        self.phase = self.phase.saturating_add(advance);
+        if self.muted {
+            0.0
+        } else {
+            f32::from(instrument.data[index]) / 128.0
+        }
+    }
+}
+
+/// Quartet song player.
+///
+/// Mixes the four sample voices in software at the output rate, the way
+/// the ST replay routine does, and optionally adds an accompaniment player
+/// (usually a YM or SNDH song) rendered on the same sample clock.
+pub struct QuartetPlayer {
+    song: QuartetSong,
+    set: Arc<VoiceSet>,
+    metadata: BasicMetadata,
+    voices: [Voice; VOICE_COUNT],
+    sample_rate: u32,
+    /// Voice step multiplier from the replay rate to the output rate, 16.16
+    step_scale: u64,
+    /// Sequencer clock; a tick runs whenever it passes `sample_rate`
+    tick_phase: u32,
+    tick_counter: u64,
+    length_ticks: u64,
+    state: PlaybackState,
+    accompaniment: Option<Box<dyn ChiptunePlayerBase>>,
+    accompaniment_level: f32,
+    scratch: Vec<f32>,
+    output_gain: OutputGain,
+    playback_rate: PlaybackRate,
+}
+
+impl QuartetPlayer {
+    /// Create a player rendering at [`DEFAULT_SAMPLE_RATE`].
+    ///
+    /// Fails if a note plays an instrument missing from `set`.
+    pub fn new(song: QuartetSong, set: impl Into<Arc<VoiceSet>>) -> Result<Self> {
+        Self::with_sample_rate(song, set, DEFAULT_SAMPLE_RATE)
+    }
+
+    /// Create a player rendering at `sample_rate` Hz.
+    pub fn with_sample_rate(
+        song: QuartetSong,
+        set: impl Into<Arc<VoiceSet>>,
+        sample_rate: u32,
+    ) -> Result<Self> {
+        let set = set.into();
+        if sample_rate == 0 {
+            return Err("sample rate must be non-zero".into());
+        }
+        for (voice, commands) in song.voices.iter().enumerate() {
+            for command in commands {
+                if let Command::Note { instrument, .. } = *command
+                    && usize::from(instrument) >= set.instruments.len()
+                {
+                    return Err(QuartetError::MissingInstrument {
+                        voice,
+                        instrument: usize::from(instrument),
+                        available: set.instruments.len(),
+                    });
+                }
+            }
+        }
+
+        let length_ticks = song.length_ticks();
+        let metadata = BasicMetadata {
+            format: "Quartet".to_string(),
+            frame_count: usize::try_from(length_ticks).ok(),
+            frame_rate: u32::from(song.tempo),
+            loop_frame: Some(0),
+            ..Default::default()
+        };
+        let step_scale = (u64::from(song.replay_rate) << 16) / u64::from(sample_rate);
+        Ok(Self {
+            song,
+            set,
+            metadata,
+            voices: Default::default(),
+            sample_rate,
+            step_scale,
+            tick_phase: sample_rate,
+            tick_counter: 0,
+            length_ticks,
+            state: PlaybackState::Stopped,
+            accompaniment: None,
+            accompaniment_level: DEFAULT_ACCOMPANIMENT_LEVEL,
+            scratch: Vec::new(),
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
+        })
+    }
+
+    /// Parse a song and a voice set and create a player.
+    pub fn load_from_bytes(song: &[u8], voice_set: &[u8]) -> Result<Self> {
+        let song = crate::format::load_song(song)?;
+        let set = crate::format::load_voice_set(voice_set)?;
+        Self::new(song, set)
+    }
+
+    /// Access the parsed song.
+    pub fn song(&self) -> &QuartetSong {
+        &self.song
+    }
+
+    /// Access the voice set.
+    pub fn voice_set(&self) -> &VoiceSet {
+        &self.set
+    }
+
+    /// Set or remove the accompaniment mixed under the voices.
+    ///
+    /// The accompaniment follows play, pause and stop and must render at
+    /// this player's sample rate. Returns the previous accompaniment.
+    pub fn set_accompaniment(
+        &mut self,
+        accompaniment: Option<Box<dyn ChiptunePlayerBase>>,
+    ) -> Result<Option<Box<dyn ChiptunePlayerBase>>> {
+        if let Some(player) = &accompaniment
+            && player.sample_rate() != self.sample_rate
+        {
+            return Err(format!(
+                "accompaniment renders at {} Hz, player at {} Hz",
+                player.sample_rate(),
+                self.sample_rate
+            )
+            .into());
+        }
+        let mut accompaniment = accompaniment;
+        if let Some(player) = accompaniment.as_mut() {
+            match self.state {
+                PlaybackState::Playing => player.play(),
+                PlaybackState::Paused => player.pause(),
+                PlaybackState::Stopped => player.stop(),
+            }
+        }
+        Ok(std::mem::replace(&mut self.accompaniment, accompaniment))
+    }
+
+    /// Access the accompaniment player.
+    pub fn accompaniment_mut(&mut self) -> Option<&mut (dyn ChiptunePlayerBase + 'static)> {
+        self.accompaniment.as_deref_mut()
+    }
+
+    /// Set the accompaniment level relative to the voices (default 0.5).
+    pub fn set_accompaniment_level(&mut self, level: f32) {
+        self.accompaniment_level = level.max(0.0);
+    }
+
+    /// Accompaniment level relative to the voices.
+    pub fn accompaniment_level(&self) -> f32 {
+        self.accompaniment_level
+    }
+
+    /// Current sequencer tick since the start of the song.
+    pub fn current_tick(&self) -> u64 {
+        self.tick_counter
+    }
+
+    fn reset_runtime(&mut self) {
+        self.reset_sequencer();
+        // Run the first tick on the next sample
+        self.tick_phase = self.sample_rate;
+    }
+
+    fn reset_sequencer(&mut self) {
+        for voice in &mut self.voices {
+            *voice = Voice {
+                muted: voice.muted,
+                ..Voice::default()
+            };
+        }
+        self.tick_counter = 0;
+    }
+
+    fn tick(&mut self) {
+        self.advance_voices();
+        if self.voices.iter().all(|voice| voice.finished) {
+            // Quartet songs loop from the start once every voice has ended
+            self.reset_sequencer();
+            self.advance_voices();
+        }
+        self.tick_counter += 1;
+    }
+
+    fn advance_voices(&mut self) {
+        for (voice, commands) in self.voices.iter_mut().zip(&self.song.voices) {
+            voice.tick(commands);
+        }
+    }
+
+    /// Mix the voices at the song's original speed.
+    fn render_native(&mut self, buffer: &mut [f32]) {
+        if self.state != PlaybackState::Playing {
+            buffer.fill(0.0);
+            return;
+        }
+        let tempo = u32::from(self.song.tempo);
+        for out in buffer.iter_mut() {
+            if self.tick_phase >= self.sample_rate {
+                self.tick_phase -= self.sample_rate;
+                self.tick();
+            }
+            self.tick_phase += tempo;
+
+            let mut mix = 0.0;
+            for voice in &mut self.voices {
+                mix += voice.sample(&self.set, self.step_scale);
+            }
+            *out = mix / VOICE_COUNT as f32;
+        }
+    }
+
+    fn mix_accompaniment(&mut self, buffer: &mut [f32]) {
+        let Some(player) = self.accompaniment.as_mut() else {
+            return;
+        };
+        self.scratch.resize(buffer.len(), 0.0);
+        player.generate_samples_into(&mut self.scratch);
+        for (out, &sample) in buffer.iter_mut().zip(&self.scratch) {
+            *out += sample * self.accompaniment_level;
+        }
+    }
+}
+
+impl ChiptunePlayerBase for QuartetPlayer {
+    fn play(&mut self) {
+        if self.state == PlaybackState::Stopped {
+            self.reset_runtime();
+        }
+        self.state = PlaybackState::Playing;
+        if let Some(player) = self.accompaniment.as_mut() {
+            player.play();
+        }
+    }
+
+    fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+            if let Some(player) = self.accompaniment.as_mut() {
+                player.pause();
+            }
+        }
+    }
+
+    fn stop(&mut self) {
+        self.state = PlaybackState::Stopped;
+        self.reset_runtime();
+        if let Some(player) = self.accompaniment.as_mut() {
+            player.stop();
+        }
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| {
+            self.render_native(source);
+            self.mix_accompaniment(source);
+        });
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        if let Some(voice) = self.voices.get_mut(channel) {
+            voice.muted = mute;
+        }
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.voices.get(channel).is_some_and(|voice| voice.muted)
+    }
+
+    fn playback_position(&self) -> f32 {
+        if self.length_ticks == 0 {
+            return 0.0;
+        }
+        (self.tick_counter.min(self.length_ticks) as f32) / self.length_ticks as f32
+    }
+
+    fn duration_seconds(&self) -> f32 {
+        self.song.duration_seconds()
+    }
+
+    fn channel_count(&self) -> usize {
+        VOICE_COUNT
+    }
+}
+
+impl ChiptunePlayer for QuartetPlayer {
+    type Metadata = BasicMetadata;
+
+    fn metadata(&self) -> &Self::Metadata {
+        &self.metadata
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Instrument;
+
+    /// Voice 1 plays a looping square wave, the others rest
+    fn test_player(ticks: u16) -> QuartetPlayer {
+        let rest = vec![Command::Rest { ticks }, Command::End];
+        let song = QuartetSong {
+            tempo: 50,
+            replay_rate: 8000,
+            voices: [
+                vec![
+                    Command::Note {
+                        instrument: 0,
+                        step: 0x0001_0000,
+                        ticks,
+                    },
+                    Command::End,
+                ],
+                rest.clone(),
+                rest.clone(),
+                rest,
+            ],
+        };
+        let set = VoiceSet {
+            instruments: vec![Instrument {
+                name: "SQUARE".to_string(),
+                data: vec![100, 100, -100, -100],
+                loop_start: Some(0),
+            }],
+        };
+        QuartetPlayer::with_sample_rate(song, set, 8000).unwrap()
+    }
+
+    /// Plays a constant level
+    struct DcPlayer(f32);
+
+    impl ChiptunePlayerBase for DcPlayer {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Playing
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            buffer.fill(self.0);
+        }
+        fn sample_rate(&self) -> u32 {
+            8000
+        }
+    }
+
+    #[test]
+    fn test_voice_mix_and_mute() {
+        let mut player = test_player(10);
+        player.play();
+        let out = player.generate_samples(4);
+        let level = 100.0 / 128.0 / VOICE_COUNT as f32;
+        assert_eq!(out, vec![level, level, -level, -level]);
+
+        player.set_channel_mute(0, true);
+        assert!(player.is_channel_muted(0));
+        assert!(player.generate_samples(4).iter().all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_song_loops_after_all_voices_end() {
+        let mut player = test_player(2);
+        assert_eq!(player.duration_seconds(), 0.04);
+        player.play();
+
+        // Two ticks of 160 samples each, then the song starts again
+        player.generate_samples(320);
+        assert_eq!(player.current_tick(), 2);
+        assert_eq!(player.playback_position(), 1.0);
+        player.generate_samples(1);
+        assert_eq!(player.current_tick(), 1);
+        assert_eq!(player.state(), PlaybackState::Playing);
+    }
+
+    #[test]
+    fn test_accompaniment_is_mixed_in() {
+        let mut player = test_player(10);
+        player
+            .set_accompaniment(Some(Box::new(DcPlayer(0.4))))
+            .unwrap();
+        player.set_accompaniment_level(0.5);
+        player.play();
+        let out = player.generate_samples(1);
+        let level = 100.0 / 128.0 / VOICE_COUNT as f32;
+        assert!((out[0] - (level + 0.2)).abs() < 1e-6);
+
+        let mut other_rate = test_player(10);
+        other_rate.sample_rate = 44_100;
+        assert!(
+            other_rate
+                .set_accompaniment(Some(Box::new(DcPlayer(0.4))))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_missing_instrument_is_an_error() {
+        let player = test_player(1);
+        let song = player.song().clone();
+        assert!(matches!(
+            QuartetPlayer::new(song, VoiceSet::default()),
+            Err(QuartetError::MissingInstrument {
+                voice: 0,
+                instrument: 0,
+                available: 0
+            })
+        ));
+    }
+}
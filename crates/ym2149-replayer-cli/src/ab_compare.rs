@@ -0,0 +1,292 @@
+//! A/B comparison player (`--ab`).
+//!
+//! Renders the song twice, A with the normal settings and B on the
+//! SoftSynth backend or through another output model, and plays one of them
+//! or their difference. Both sides always run, so `[a]` in the TUI switches
+//! instantly without losing the position.
+
+use ym2149_common::{
+    AbMode, AbSwitch, ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta,
+};
+
+use crate::{RealtimeChip, VisualSnapshot};
+
+/// Two players rendered in lockstep with a switchable output.
+///
+/// Transport and mix controls go to both sides; queries answer from A, and
+/// the visuals follow the side being heard.
+pub struct AbPlayer {
+    a: Box<dyn RealtimeChip>,
+    b: Box<dyn RealtimeChip>,
+    /// Output model B always renders with (`None` = follow A)
+    b_output_model: Option<OutputModel>,
+    switch: AbSwitch,
+    scratch_a: Vec<f32>,
+    scratch_b: Vec<f32>,
+}
+
+impl AbPlayer {
+    /// Compare `a` with `b`, starting on `mode`.
+    ///
+    /// With `b_output_model`, B keeps that model while output model changes
+    /// only reach A.
+    pub fn new(
+        a: Box<dyn RealtimeChip>,
+        mut b: Box<dyn RealtimeChip>,
+        mode: AbMode,
+        b_output_model: Option<OutputModel>,
+    ) -> Self {
+        if let Some(model) = b_output_model {
+            b.set_output_model(model);
+        }
+        Self {
+            a,
+            b,
+            b_output_model,
+            switch: AbSwitch::new(mode),
+            scratch_a: Vec::new(),
+            scratch_b: Vec::new(),
+        }
+    }
+
+    /// The side the visuals follow.
+    fn shown(&self) -> &dyn RealtimeChip {
+        match self.switch.mode() {
+            AbMode::B => self.b.as_ref(),
+            AbMode::A | AbMode::Difference => self.a.as_ref(),
+        }
+    }
+}
+
+impl ChiptunePlayerBase for AbPlayer {
+    fn play(&mut self) {
+        self.a.play();
+        self.b.play();
+    }
+
+    fn pause(&mut self) {
+        self.a.pause();
+        self.b.pause();
+    }
+
+    fn stop(&mut self) {
+        self.a.stop();
+        self.b.stop();
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.a.state()
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        self.scratch_a.resize(buffer.len(), 0.0);
+        self.scratch_b.resize(buffer.len(), 0.0);
+        self.a.generate_samples_into(&mut self.scratch_a);
+        self.b.generate_samples_into(&mut self.scratch_b);
+        self.switch.mix(&self.scratch_a, &self.scratch_b, buffer);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.a.sample_rate()
+    }
+
+    fn set_gain(&mut self, gain: f32) {
+        self.a.set_gain(gain);
+        self.b.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.a.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.a.set_soft_clip(enabled);
+        self.b.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.a.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.a.set_playback_rate(rate);
+        self.b.set_playback_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.a.playback_rate()
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.a.set_output_model(model);
+        if self.b_output_model.is_none() {
+            self.b.set_output_model(model);
+        }
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.a.set_channel_tap(capacity);
+        self.b.set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.shown().read_channel_tap(channel, out)
+    }
+
+    fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        self.a.set_channel_mute(channel, mute);
+        self.b.set_channel_mute(channel, mute);
+    }
+
+    fn is_channel_muted(&self, channel: usize) -> bool {
+        self.a.is_channel_muted(channel)
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        self.b.step_frames(frames);
+        self.a.step_frames(frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        self.b.advance_frames(frames);
+        self.a.advance_frames(frames)
+    }
+
+    fn playback_position(&self) -> f32 {
+        self.a.playback_position()
+    }
+
+    fn seek(&mut self, position: f32) -> bool {
+        let seeked = self.a.seek(position);
+        self.b.seek(position);
+        seeked
+    }
+
+    fn duration_seconds(&self) -> f32 {
+        self.a.duration_seconds()
+    }
+
+    fn elapsed_seconds(&self) -> f32 {
+        self.a.elapsed_seconds()
+    }
+
+    fn subsong_count(&self) -> usize {
+        self.a.subsong_count()
+    }
+
+    fn current_subsong(&self) -> usize {
+        self.a.current_subsong()
+    }
+
+    fn set_subsong(&mut self, index: usize) -> bool {
+        let switched = self.a.set_subsong(index);
+        self.b.set_subsong(index);
+        switched
+    }
+
+    fn psg_count(&self) -> usize {
+        self.a.psg_count()
+    }
+
+    fn channel_count(&self) -> usize {
+        self.a.channel_count()
+    }
+}
+
+impl RealtimeChip for AbPlayer {
+    fn visual_snapshot(&self) -> VisualSnapshot {
+        self.shown().visual_snapshot()
+    }
+
+    fn set_color_filter(&mut self, enabled: bool) {
+        self.a.set_color_filter(enabled);
+        self.b.set_color_filter(enabled);
+    }
+
+    fn unsupported_reason(&self) -> Option<&'static str> {
+        self.a
+            .unsupported_reason()
+            .or_else(|| self.b.unsupported_reason())
+    }
+
+    fn generate_samples_into_stereo(&mut self, buffer: &mut [f32]) {
+        self.scratch_a.resize(buffer.len(), 0.0);
+        self.scratch_b.resize(buffer.len(), 0.0);
+        self.a.generate_samples_into_stereo(&mut self.scratch_a);
+        self.b.generate_samples_into_stereo(&mut self.scratch_b);
+        self.switch.mix(&self.scratch_a, &self.scratch_b, buffer);
+    }
+
+    fn ab_mode(&self) -> Option<AbMode> {
+        Some(self.switch.mode())
+    }
+
+    fn set_ab_mode(&mut self, mode: AbMode) {
+        self.switch.set_mode(mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::AB_SWITCH_SAMPLES;
+
+    struct Constant {
+        level: f32,
+        model: OutputModel,
+    }
+
+    impl ChiptunePlayerBase for Constant {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Playing
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            buffer.fill(self.level);
+        }
+        fn set_output_model(&mut self, model: OutputModel) {
+            self.model = model;
+        }
+    }
+
+    impl RealtimeChip for Constant {
+        fn visual_snapshot(&self) -> VisualSnapshot {
+            VisualSnapshot {
+                psg_count: (self.model == OutputModel::CpcFilter) as usize,
+                ..VisualSnapshot::default()
+            }
+        }
+        fn set_color_filter(&mut self, _enabled: bool) {}
+    }
+
+    fn constant(level: f32) -> Box<dyn RealtimeChip> {
+        Box::new(Constant {
+            level,
+            model: OutputModel::Raw,
+        })
+    }
+
+    #[test]
+    fn switches_output_and_pins_b_output_model() {
+        let mut player = AbPlayer::new(
+            constant(0.5),
+            constant(0.125),
+            AbMode::B,
+            Some(OutputModel::CpcFilter),
+        );
+        let mut stereo = [0.0; 8];
+        player.generate_samples_into_stereo(&mut stereo);
+        assert_eq!(stereo, [0.125; 8]);
+        // Visuals follow B, which keeps its own output model
+        player.set_output_model(OutputModel::SteFilter);
+        assert_eq!(player.visual_snapshot().psg_count, 1);
+
+        player.set_ab_mode(AbMode::Difference);
+        assert_eq!(player.ab_mode(), Some(AbMode::Difference));
+        player.generate_samples_into_stereo(&mut [0.0; AB_SWITCH_SAMPLES]);
+        player.generate_samples_into_stereo(&mut stereo);
+        assert_eq!(stereo, [0.375; 8]);
+    }
+}
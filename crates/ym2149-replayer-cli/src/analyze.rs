@@ -0,0 +1,176 @@
+//! Register activity report (`ym-replayer analyze`).
+//!
+//! Loads a YM song, runs [`YmPlayerGeneric::analyze_registers`] and prints
+//! per-register change counts with a text heat map over song time, followed
+//! by effect and DigiDrum usage.
+//!
+//! [`YmPlayerGeneric::analyze_registers`]: ym2149_ym_replayer::YmPlayerGeneric::analyze_registers
+
+use std::fmt::Write as _;
+
+use ym2149_ym_replayer::{LoadSummary, RegisterAnalysis, load_song};
+
+use crate::args::AnalyzeArgs;
+use crate::audio::DEFAULT_SAMPLE_RATE;
+
+/// Heat map columns used when `--columns` is not given.
+pub const DEFAULT_HEAT_MAP_COLUMNS: usize = 64;
+
+/// Register names shown next to the counts.
+const REGISTER_NAMES: [&str; 16] = [
+    "Tone A fine",
+    "Tone A coarse",
+    "Tone B fine",
+    "Tone B coarse",
+    "Tone C fine",
+    "Tone C coarse",
+    "Noise period",
+    "Mixer",
+    "Volume A",
+    "Volume B",
+    "Volume C",
+    "Env period fine",
+    "Env period coarse",
+    "Env shape",
+    "Port A / effects",
+    "Port B / effects",
+];
+
+/// Heat map cells from no change to a change on every frame.
+const HEAT_CELLS: [char; 9] = ['·', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Run the `analyze` subcommand.
+pub fn run(args: &AnalyzeArgs) -> ym2149_ym_replayer::Result<()> {
+    let Some(ref input) = args.input else {
+        return Err("analyze requires a YM file".into());
+    };
+    let data = std::fs::read(input).map_err(|e| format!("Failed to read {input}: {e}"))?;
+    let (player, summary) =
+        load_song(&data).map_err(|e| format!("{input} is not a YM song: {e}"))?;
+    let analysis = player.analyze_registers();
+    print!(
+        "{}",
+        render_report(input, &summary, &analysis, args.columns)
+    );
+    Ok(())
+}
+
+/// Format the full report.
+fn render_report(
+    name: &str,
+    summary: &LoadSummary,
+    analysis: &RegisterAnalysis,
+    columns: usize,
+) -> String {
+    let mut out = String::new();
+    let seconds = summary.total_samples() as f64 / f64::from(DEFAULT_SAMPLE_RATE);
+    let _ = writeln!(
+        out,
+        "{name}: {}, {} frames ({seconds:.1} s)\n",
+        summary.format, analysis.frame_count
+    );
+    if analysis.frame_count == 0 {
+        out.push_str("No register frames (tracker songs are not analyzed)\n");
+        return out;
+    }
+
+    let heat_map = analysis.heat_map(columns);
+    let bucket = analysis.frames_per_bucket(columns);
+    let _ = writeln!(
+        out,
+        "Reg  Name               Changes  Heat map ({bucket} frames per column)"
+    );
+    for (reg, name) in REGISTER_NAMES.iter().enumerate() {
+        let cells: String = heat_map
+            .iter()
+            .map(|row| heat_cell(row[reg], bucket))
+            .collect();
+        let _ = writeln!(
+            out,
+            "R{reg:<3} {name:<17} {:>8}  {cells}",
+            analysis.change_counts[reg]
+        );
+    }
+    let _ = writeln!(out, "\nEnvelope restarts: {}", analysis.envelope_restarts);
+    let unused = analysis.unused_registers();
+    if !unused.is_empty() {
+        let list: Vec<String> = unused.iter().map(|reg| format!("R{reg}")).collect();
+        let _ = writeln!(out, "Unchanged registers: {}", list.join(" "));
+    }
+
+    let effects = &analysis.effects;
+    if effects.is_empty() {
+        out.push_str("\nEffects: none\n");
+    } else {
+        out.push_str("\nEffects (frames per channel):\n");
+        let per_channel = |counts: [usize; 3]| {
+            format!("A {:>6}  B {:>6}  C {:>6}", counts[0], counts[1], counts[2])
+        };
+        let _ = writeln!(out, "  SID voice    {}", per_channel(effects.sid));
+        let _ = writeln!(out, "  Sinus SID    {}", per_channel(effects.sinus_sid));
+        let _ = writeln!(out, "  DigiDrum     {}", per_channel(effects.digidrum));
+        let _ = writeln!(out, "  Sync Buzzer  {:>8}", effects.sync_buzzer);
+    }
+
+    let drums = &analysis.digidrum_triggers;
+    if !drums.is_empty() {
+        let used = drums.iter().filter(|&&count| count > 0).count();
+        let _ = writeln!(out, "\nDigiDrums: {} samples, {used} used", drums.len());
+        for (index, count) in drums.iter().enumerate() {
+            let _ = writeln!(out, "  #{index:<3} {count:>6} triggers");
+        }
+    }
+    out
+}
+
+/// Heat map cell for `count` changes in a column of `frames` frames.
+fn heat_cell(count: u32, frames: usize) -> char {
+    if count == 0 {
+        return HEAT_CELLS[0];
+    }
+    let levels = HEAT_CELLS.len() - 1;
+    let level = (count as usize * levels).div_ceil(frames.max(1));
+    HEAT_CELLS[level.clamp(1, levels)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_ym_replayer::YmFileFormat;
+
+    #[test]
+    fn heat_cells_scale_with_change_density() {
+        assert_eq!(heat_cell(0, 8), '·');
+        assert_eq!(heat_cell(1, 8), '▁');
+        assert_eq!(heat_cell(4, 8), '▄');
+        assert_eq!(heat_cell(8, 8), '█');
+        assert_eq!(heat_cell(1, 1000), '▁');
+    }
+
+    #[test]
+    fn report_lists_registers_and_unchanged_ones() {
+        let frames: Vec<[u8; 16]> = (0..20u8)
+            .map(|i| {
+                let mut regs = [0u8; 16];
+                regs[0] = i;
+                regs[8] = 15;
+                regs[13] = 0xFF;
+                regs
+            })
+            .collect();
+        let analysis = RegisterAnalysis::from_frames(&frames);
+        let summary = LoadSummary {
+            format: YmFileFormat::Ym6,
+            frame_count: 20,
+            samples_per_frame: 882,
+        };
+
+        let report = render_report("song.ym", &summary, &analysis, 4);
+        assert!(report.starts_with("song.ym: YM6, 20 frames (0.4 s)"));
+        assert!(report.contains("(5 frames per column)"));
+        let r0 = report.lines().find(|line| line.starts_with("R0 ")).unwrap();
+        assert!(r0.contains("19") && r0.ends_with("▇███"), "{r0}");
+        assert!(report.contains("Unchanged registers: R1 R2"));
+        assert!(report.contains("Effects: none"));
+    }
+}
@@ -3,11 +3,25 @@
 //! This module handles parsing and validation of CLI arguments including:
 //! - File path specification
 //! - Chip backend selection (currently only ym2149)
-//! - Color filter settings
+//! - Color filter and output model settings
+//! - Insert effects (delay, chorus, reverb)
+//! - Playback rate
+//! - A/B comparison against SoftSynth or another output model
+//! - Offline note export
+//! - Headless modes (JSON status, remote control, raw PCM on stdout)
+//! - Batch conversion (`convert` subcommand)
+//! - Register activity report (`analyze` subcommand)
 //! - Help text generation
 
 use std::env;
 use std::fmt;
+use ym2149_common::{AbMode, FxConfig, MAX_PLAYBACK_RATE, MIN_PLAYBACK_RATE, OutputModel};
+use ym2149_ym_replayer::export::ExportFormat;
+
+use crate::analyze::DEFAULT_HEAT_MAP_COLUMNS;
+use crate::audio::{OutputTarget, PcmFormat};
+use crate::convert::DEFAULT_TEMPLATE;
+use crate::json_status::DEFAULT_JSON_STATUS_RATE;
 
 /// Available chip emulation backends.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -39,6 +53,77 @@ impl fmt::Display for ChipChoice {
     }
 }
 
+/// Second rendering of an A/B comparison (`--ab`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AbKind {
+    /// Same YM file on the experimental SoftSynth backend
+    #[cfg(feature = "softsynth")]
+    SoftSynth,
+    /// Same song through another output model
+    OutputModel(OutputModel),
+}
+
+impl fmt::Display for AbKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "softsynth")]
+            AbKind::SoftSynth => f.write_str("softsynth"),
+            AbKind::OutputModel(model) => write!(f, "output model {}", model.as_str()),
+        }
+    }
+}
+
+/// Options of the `convert` subcommand.
+#[derive(Debug)]
+pub struct ConvertArgs {
+    /// Directory or ZIP archive to convert
+    pub input: Option<String>,
+    /// Directory the rendered files are written to
+    pub output_dir: String,
+    /// Audio format of the rendered files
+    pub format: ExportFormat,
+    /// Output file name template (without extension)
+    pub template: String,
+    /// Number of songs rendered in parallel (None = one per CPU)
+    pub jobs: Option<usize>,
+    /// Loop passes for looping songs (None = render the song length once)
+    pub loops: Option<u32>,
+    /// Fade out length in seconds (a tail after the last loop with `loops`)
+    pub fade_secs: f32,
+}
+
+impl Default for ConvertArgs {
+    fn default() -> Self {
+        Self {
+            input: None,
+            output_dir: ".".to_string(),
+            format: ExportFormat::Wav,
+            template: DEFAULT_TEMPLATE.to_string(),
+            jobs: None,
+            loops: None,
+            fade_secs: 0.0,
+        }
+    }
+}
+
+/// Options of the `analyze` subcommand.
+#[derive(Debug)]
+pub struct AnalyzeArgs {
+    /// YM file to analyze
+    pub input: Option<String>,
+    /// Number of heat map columns across the song
+    pub columns: usize,
+}
+
+impl Default for AnalyzeArgs {
+    fn default() -> Self {
+        Self {
+            input: None,
+            columns: DEFAULT_HEAT_MAP_COLUMNS,
+        }
+    }
+}
+
 /// Parsed command-line arguments.
 #[derive(Debug)]
 pub struct CliArgs {
@@ -48,6 +133,32 @@ pub struct CliArgs {
     pub color_filter_override: Option<bool>,
     /// Selected chip backend
     pub chip_choice: ChipChoice,
+    /// Analog output stage model applied to every PSG
+    pub output_model: OutputModel,
+    /// Insert effects per channel and on the master bus
+    pub fx: FxConfig,
+    /// Playback rate (speed and pitch, 1.0 = original)
+    pub playback_rate: f32,
+    /// Render a second version to compare against (None = no A/B)
+    pub ab: Option<AbKind>,
+    /// Output selected at start of an A/B comparison
+    pub ab_mode: AbMode,
+    /// Write extracted notes to this file (JSON or MIDI) instead of playing
+    pub export_notes: Option<String>,
+    /// SNDH timing database with subsong lengths
+    pub sndh_durations: Option<String>,
+    /// TUI theme and layout file (None = default location)
+    pub tui_config: Option<String>,
+    /// Print JSON status lines at this rate (Hz) instead of the TUI
+    pub json_status: Option<f32>,
+    /// Remote control server address (port or `host:port`)
+    pub remote: Option<String>,
+    /// Audio device or raw PCM on stdout
+    pub output: OutputTarget,
+    /// Batch-convert songs instead of playing (`convert` subcommand)
+    pub convert: Option<ConvertArgs>,
+    /// Print a register activity report instead of playing (`analyze` subcommand)
+    pub analyze: Option<AnalyzeArgs>,
     /// Whether help was requested
     pub show_help: bool,
 }
@@ -58,6 +169,19 @@ impl Default for CliArgs {
             file_path: None,
             color_filter_override: None,
             chip_choice: ChipChoice::Ym2149,
+            output_model: OutputModel::Raw,
+            fx: FxConfig::default(),
+            playback_rate: 1.0,
+            ab: None,
+            ab_mode: AbMode::A,
+            export_notes: None,
+            sndh_durations: None,
+            tui_config: None,
+            json_status: None,
+            remote: None,
+            output: OutputTarget::Device,
+            convert: None,
+            analyze: None,
             show_help: false,
         }
     }
@@ -67,8 +191,19 @@ impl CliArgs {
     /// Parse arguments from command line.
     pub fn parse() -> Self {
         let mut args = Self::default();
-        let mut iter = env::args().skip(1);
+        let mut iter = env::args().skip(1).peekable();
 
+        if iter.next_if(|arg| arg == "convert").is_some() {
+            args.convert = Some(args.parse_convert(iter));
+            return args;
+        }
+        if iter.next_if(|arg| arg == "analyze").is_some() {
+            args.analyze = Some(args.parse_analyze(iter));
+            return args;
+        }
+
+        let mut pcm_format = None;
+        let mut jack_stems = false;
         while let Some(arg) = iter.next() {
             match arg.as_str() {
                 "--no-color-filter" => {
@@ -99,6 +234,136 @@ impl CliArgs {
                         args.show_help = true;
                     }
                 }
+                "--output-model" => {
+                    if let Some(value) = iter.next() {
+                        args.set_output_model(&value);
+                    } else {
+                        eprintln!("--output-model requires an argument (raw, stf, ste, cpc)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--output-model=") => {
+                    args.set_output_model(&arg["--output-model=".len()..]);
+                }
+                "--fx" => {
+                    if let Some(value) = iter.next() {
+                        args.add_fx(&value);
+                    } else {
+                        eprintln!("--fx requires an effect chain (e.g. a=chorus or reverb)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--fx=") => {
+                    args.add_fx(&arg["--fx=".len()..]);
+                }
+                "--rate" => {
+                    if let Some(value) = iter.next() {
+                        args.set_playback_rate(&value);
+                    } else {
+                        eprintln!("--rate requires a speed factor (e.g. 0.5 or 2)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--rate=") => {
+                    args.set_playback_rate(&arg["--rate=".len()..]);
+                }
+                "--ab" => {
+                    if let Some(value) = iter.next() {
+                        args.set_ab(&value);
+                    } else {
+                        eprintln!("--ab requires a comparison (softsynth, raw, stf, ste, cpc)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--ab=") => {
+                    args.set_ab(&arg["--ab=".len()..]);
+                }
+                "--ab-mode" => {
+                    if let Some(value) = iter.next() {
+                        args.set_ab_mode(&value);
+                    } else {
+                        eprintln!("--ab-mode requires an output (a, b, diff)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--ab-mode=") => {
+                    args.set_ab_mode(&arg["--ab-mode=".len()..]);
+                }
+                "--export-notes" => {
+                    if let Some(value) = iter.next() {
+                        args.export_notes = Some(value);
+                    } else {
+                        eprintln!("--export-notes requires an output file (.json or .mid)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--export-notes=") => {
+                    args.export_notes = Some(arg["--export-notes=".len()..].to_string());
+                }
+                "--sndh-durations" => {
+                    if let Some(value) = iter.next() {
+                        args.sndh_durations = Some(value);
+                    } else {
+                        eprintln!("--sndh-durations requires a timing database file");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--sndh-durations=") => {
+                    args.sndh_durations = Some(arg["--sndh-durations=".len()..].to_string());
+                }
+                "--tui-config" => {
+                    if let Some(value) = iter.next() {
+                        args.tui_config = Some(value);
+                    } else {
+                        eprintln!("--tui-config requires a config file");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--tui-config=") => {
+                    args.tui_config = Some(arg["--tui-config=".len()..].to_string());
+                }
+                "--json-status" => {
+                    args.json_status = Some(DEFAULT_JSON_STATUS_RATE);
+                }
+                _ if arg.starts_with("--json-status=") => {
+                    args.set_json_status(&arg["--json-status=".len()..]);
+                }
+                "--remote" => {
+                    if let Some(value) = iter.next() {
+                        args.remote = Some(value);
+                    } else {
+                        eprintln!("--remote requires a port or address (e.g. 7878)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--remote=") => {
+                    args.remote = Some(arg["--remote=".len()..].to_string());
+                }
+                "--output" => {
+                    if let Some(value) = iter.next() {
+                        args.set_output(&value);
+                    } else {
+                        eprintln!("--output requires a target (device, stdout or jack)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--output=") => {
+                    args.set_output(&arg["--output=".len()..]);
+                }
+                "--pcm-format" => {
+                    if let Some(value) = iter.next() {
+                        pcm_format = args.parse_pcm_format(&value);
+                    } else {
+                        eprintln!("--pcm-format requires a format (s16 or f32)");
+                        args.show_help = true;
+                    }
+                }
+                _ if arg.starts_with("--pcm-format=") => {
+                    pcm_format = args.parse_pcm_format(&arg["--pcm-format=".len()..]);
+                }
+                "--jack-stems" => {
+                    jack_stems = true;
+                }
                 _ if arg.starts_with('-') => {
                     eprintln!("Unknown flag: {arg}");
                     args.show_help = true;
@@ -109,26 +374,311 @@ impl CliArgs {
             }
         }
 
+        if let Some(format) = pcm_format {
+            match &mut args.output {
+                OutputTarget::Stdout(target) => *target = format,
+                _ => {
+                    eprintln!("--pcm-format only applies to --output stdout; ignoring it");
+                }
+            }
+        }
+
+        if jack_stems {
+            match &mut args.output {
+                #[cfg(feature = "jack")]
+                OutputTarget::Jack { stems } => *stems = true,
+                _ => eprintln!("--jack-stems only applies to --output jack; ignoring it"),
+            }
+        }
+
         args
     }
 
+    /// Parse the arguments following `convert`.
+    fn parse_convert(&mut self, mut iter: impl Iterator<Item = String>) -> ConvertArgs {
+        let mut convert = ConvertArgs::default();
+
+        while let Some(arg) = iter.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            match flag {
+                "--help" | "-h" => {
+                    self.show_help = true;
+                }
+                "--out" | "-o" | "--format" | "-f" | "--template" | "--jobs" | "-j" | "--loops"
+                | "--fade" | "--sndh-durations" => {
+                    let Some(value) = inline_value.or_else(|| iter.next()) else {
+                        eprintln!("{flag} requires an argument");
+                        self.show_help = true;
+                        continue;
+                    };
+                    match flag {
+                        "--out" | "-o" => convert.output_dir = value,
+                        "--format" | "-f" => self.set_convert_format(&mut convert, &value),
+                        "--template" => convert.template = value,
+                        "--sndh-durations" => self.sndh_durations = Some(value),
+                        "--loops" => match value.parse::<u32>() {
+                            Ok(loops) if loops > 0 => convert.loops = Some(loops),
+                            _ => {
+                                eprintln!("Invalid loop count: {value}");
+                                self.show_help = true;
+                            }
+                        },
+                        "--fade" => match value.parse::<f32>() {
+                            Ok(secs) if secs >= 0.0 && secs.is_finite() => convert.fade_secs = secs,
+                            _ => {
+                                eprintln!("Invalid fade length: {value}");
+                                self.show_help = true;
+                            }
+                        },
+                        _ => match value.parse::<usize>() {
+                            Ok(jobs) if jobs > 0 => convert.jobs = Some(jobs),
+                            _ => {
+                                eprintln!("Invalid job count: {value}");
+                                self.show_help = true;
+                            }
+                        },
+                    }
+                }
+                _ if arg.starts_with('-') => {
+                    eprintln!("Unknown convert flag: {arg}");
+                    self.show_help = true;
+                }
+                _ => {
+                    convert.input = Some(arg);
+                }
+            }
+        }
+
+        convert
+    }
+
+    /// Parse the arguments following `analyze`.
+    fn parse_analyze(&mut self, mut iter: impl Iterator<Item = String>) -> AnalyzeArgs {
+        let mut analyze = AnalyzeArgs::default();
+
+        while let Some(arg) = iter.next() {
+            let (flag, inline_value) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => (flag, Some(value.to_string())),
+                _ => (arg.as_str(), None),
+            };
+            match flag {
+                "--help" | "-h" => {
+                    self.show_help = true;
+                }
+                "--columns" => {
+                    let Some(value) = inline_value.or_else(|| iter.next()) else {
+                        eprintln!("--columns requires an argument");
+                        self.show_help = true;
+                        continue;
+                    };
+                    match value.parse::<usize>() {
+                        Ok(columns) if columns > 0 => analyze.columns = columns,
+                        _ => {
+                            eprintln!("Invalid column count: {value}");
+                            self.show_help = true;
+                        }
+                    }
+                }
+                _ if arg.starts_with('-') => {
+                    eprintln!("Unknown analyze flag: {arg}");
+                    self.show_help = true;
+                }
+                _ => {
+                    analyze.input = Some(arg);
+                }
+            }
+        }
+
+        analyze
+    }
+
+    fn set_convert_format(&mut self, convert: &mut ConvertArgs, value: &str) {
+        if let Some(format) = ExportFormat::from_name(value) {
+            convert.format = format;
+        } else if value.eq_ignore_ascii_case("opus") {
+            eprintln!("Opus output needs ym-replayer built with the `opus` feature");
+            self.show_help = true;
+        } else {
+            eprintln!("Unknown format: {value} (expected wav, flac or opus)");
+            self.show_help = true;
+        }
+    }
+
+    fn set_output_model(&mut self, value: &str) {
+        if let Some(model) = OutputModel::from_name(value) {
+            self.output_model = model;
+        } else {
+            eprintln!("Unknown output model: {value}");
+            self.show_help = true;
+        }
+    }
+
+    fn set_playback_rate(&mut self, value: &str) {
+        match value.parse::<f32>() {
+            Ok(rate) if (MIN_PLAYBACK_RATE..=MAX_PLAYBACK_RATE).contains(&rate) => {
+                self.playback_rate = rate;
+            }
+            _ => {
+                eprintln!(
+                    "Invalid rate: {value} (expected {MIN_PLAYBACK_RATE} to {MAX_PLAYBACK_RATE})"
+                );
+                self.show_help = true;
+            }
+        }
+    }
+
+    fn set_ab(&mut self, value: &str) {
+        if let Some(model) = OutputModel::from_name(value) {
+            self.ab = Some(AbKind::OutputModel(model));
+            return;
+        }
+        match value.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "softsynth")]
+            "softsynth" => self.ab = Some(AbKind::SoftSynth),
+            #[cfg(not(feature = "softsynth"))]
+            "softsynth" => {
+                eprintln!(
+                    "A/B against softsynth needs ym-replayer built with the `softsynth` feature"
+                );
+                self.show_help = true;
+            }
+            _ => {
+                eprintln!(
+                    "Unknown A/B comparison: {value} (expected softsynth, raw, stf, ste, cpc)"
+                );
+                self.show_help = true;
+            }
+        }
+    }
+
+    fn set_ab_mode(&mut self, value: &str) {
+        if let Some(mode) = AbMode::parse(value) {
+            self.ab_mode = mode;
+        } else {
+            eprintln!("Unknown A/B output: {value} (expected a, b, diff)");
+            self.show_help = true;
+        }
+    }
+
+    fn set_output(&mut self, value: &str) {
+        match value.to_ascii_lowercase().as_str() {
+            "device" => self.output = OutputTarget::Device,
+            "stdout" | "raw" => self.output = OutputTarget::Stdout(PcmFormat::default()),
+            #[cfg(feature = "jack")]
+            "jack" => self.output = OutputTarget::Jack { stems: false },
+            #[cfg(not(feature = "jack"))]
+            "jack" => {
+                eprintln!("JACK output needs ym-replayer built with the `jack` feature");
+                self.show_help = true;
+            }
+            _ => {
+                eprintln!("Unknown output: {value} (expected device, stdout or jack)");
+                self.show_help = true;
+            }
+        }
+    }
+
+    fn parse_pcm_format(&mut self, value: &str) -> Option<PcmFormat> {
+        let format = PcmFormat::from_name(value);
+        if format.is_none() {
+            eprintln!("Unknown PCM format: {value} (expected s16 or f32)");
+            self.show_help = true;
+        }
+        format
+    }
+
+    fn set_json_status(&mut self, value: &str) {
+        match value.parse::<f32>() {
+            Ok(rate) if (0.1..=100.0).contains(&rate) => self.json_status = Some(rate),
+            _ => {
+                eprintln!("Invalid --json-status rate: {value} (expected 0.1 to 100 Hz)");
+                self.show_help = true;
+            }
+        }
+    }
+
+    fn add_fx(&mut self, spec: &str) {
+        if let Err(err) = self.fx.apply_spec(spec) {
+            eprintln!("Invalid --fx value '{spec}': {err}");
+            self.show_help = true;
+        }
+    }
+
     /// Print help text to stderr.
     pub fn print_help() {
         eprintln!(
-            "Usage:\n  ym-replayer [--no-color-filter] [--chip <mode>] <file.ym|directory>\n\n\
+            "Usage:\n  ym-replayer [--no-color-filter] [--chip <mode>] [--output-model <model>] [--fx <spec>]... [--rate <factor>] [--ab <b>] [--ab-mode <out>] [--export-notes <out>] [--sndh-durations <db>] [--tui-config <file>] [--json-status[=<hz>]] [--remote <port>] [--output <target>] <file.ym|directory|archive.zip>\n\
+             \x20 ym-replayer convert [--out <dir>] [--format <fmt>] [--template <tpl>] [--loops <n>] [--fade <secs>] [--sndh-durations <db>] [--jobs <n>] <directory|archive.zip>\n\
+             \x20 ym-replayer analyze [--columns <n>] <file.ym>\n\n\
              Flags:\n\
              \x20 --no-color-filter    Disable ST-style color filter globally (default enabled)\n\
              \x20 --chip <mode>        Select synthesis engine:\n\
              \x20                        - ym2149 (default)\n\
+             \x20 --output-model <model> Analog output stage model:\n\
+             \x20                        - raw (default), stf, ste, cpc\n\
+             \x20 --fx [target=]<chain> Insert effects, repeatable per target:\n\
+             \x20                        - target: master (default), a, b, c\n\
+             \x20                        - chain: comma-separated delay[:ms:feedback:mix],\n\
+             \x20                          chorus[:rate_hz:depth_ms:mix], reverb[:room:damping:mix]\n\
+             \x20 --rate <factor>      Playback speed, pitch follows (0.25 to 4, default 1)\n\
+             \x20 --ab <b>             Also render the song as B and switch with [a]:\n\
+             \x20                        - softsynth (YM only, needs the `softsynth` feature)\n\
+             \x20                        - raw, stf, ste, cpc: B uses that output model\n\
+             \x20 --ab-mode <out>      Output at start: a (default), b or diff (A minus B)\n\
+             \x20 --export-notes <out> Write note events to <out> and exit:\n\
+             \x20                        - .mid/.midi for MIDI, anything else JSON\n\
+             \x20 --sndh-durations <db> SNDH subsong lengths (Songlengths.md5 style,\n\
+             \x20                        keyed by MD5); files without FRMS/TIME tags\n\
+             \x20                        otherwise play for 5 minutes (also for convert)\n\
+             \x20 --tui-config <file>  TUI colors and panes (default:\n\
+             \x20                        ~/.config/ym-replayer/tui.conf if present);\n\
+             \x20                        [w]/[x]/[n] toggle scope/spectrum/notes at runtime\n\
+             \x20 --json-status[=<hz>] Print one JSON status line per tick to stdout\n\
+             \x20                        instead of the TUI (default 10 Hz)\n\
+             \x20 --remote <port|addr> Accept HTTP control commands (play, pause, next,\n\
+             \x20                        seek, load, ...); a port binds 127.0.0.1 (TUI only);\n\
+             \x20                        commands need the printed session token\n\
+             \x20 --output <target>    device (default), stdout (alias raw): write\n\
+             \x20                        interleaved stereo PCM to stdout for piping,\n\
+             \x20                        or jack (needs the `jack` feature; also PipeWire)\n\
+             \x20 --pcm-format <fmt>   PCM sample format for stdout: s16 (default) or f32,\n\
+             \x20                        little-endian\n\
+             \x20 --jack-stems         Add psg_a/psg_b/psg_c JACK ports with the dry\n\
+             \x20                        channels of the first PSG\n\
              \x20 -h, --help           Show this help\n\n\
+             Convert:\n\
+             \x20 Renders every supported song of a directory or ZIP archive to audio files.\n\
+             \x20 -o, --out <dir>      Output directory (default: current directory)\n\
+             \x20 -f, --format <fmt>   wav (default), flac or opus (needs the `opus` feature)\n\
+             \x20 --template <tpl>     Output name without extension (default: {{name}});\n\
+             \x20                        placeholders {{name}}, {{title}}, {{author}}, {{format}},\n\
+             \x20                        `/` creates subdirectories\n\
+             \x20 --loops <n>          Render intro + <n> loop passes of YM/AKS songs\n\
+             \x20                        (default: song length once)\n\
+             \x20 --fade <secs>        Fade out length; a tail after the last loop with --loops\n\
+             \x20 -j, --jobs <n>       Songs rendered in parallel (default: one per CPU)\n\n\
+             Analyze:\n\
+             \x20 Prints register change counts with a heat map over song time, effect\n\
+             \x20 usage (SID, Sinus SID, DigiDrum, Sync Buzzer) and DigiDrum triggers.\n\
+             \x20 --columns <n>        Heat map columns (default: 64)\n\n\
              Supported Formats:\n\
              \x20 YM (YM2, YM3, YM5, YM6), AKS, AY, SNDH\n\n\
              Directory Mode:\n\
              \x20 When a directory is specified, all supported files are scanned recursively.\n\
+             \x20 ZIP archives are opened the same way, one playlist entry per song.\n\
              \x20 Press [p] to open the playlist overlay and select a song.\n\n\
              Examples:\n\
              \x20 ym-replayer song.ym              # Play single file\n\
-             \x20 ym-replayer ~/music/chiptunes    # Browse directory\n"
+             \x20 ym-replayer ~/music/chiptunes    # Browse directory\n\
+             \x20 ym-replayer collection.zip       # Browse ZIP archive\n\
+             \x20 ym-replayer --fx a=chorus --fx reverb song.ym  # Chorus on A, reverb on the mix\n\
+             \x20 ym-replayer --ab stf --ab-mode diff song.ym  # Hear what the STF filter removes\n\
+             \x20 ym-replayer --export-notes song.mid song.ym  # Transcribe to MIDI\n\
+             \x20 ym-replayer analyze song.ym      # Register heat map and effect usage\n\
+             \x20 ym-replayer convert -f flac -o out --template \"{{author}}/{{title}}\" ~/music\n"
         );
     }
 }
@@ -0,0 +1,116 @@
+//! Adaptive ring buffer sizing
+//!
+//! Used by [`StreamConfig::adaptive`]: playback starts at a low-latency fill
+//! limit, which doubles when underruns repeat and halves again once playback
+//! has been stable for a while. Growing needs two underruns within
+//! [`GROW_WINDOW_SECS`] and shrinking needs [`SHRINK_AFTER_SECS`] without
+//! any, so a single glitch or a short quiet spell does not make the size
+//! flap between steps.
+//!
+//! [`StreamConfig::adaptive`]: super::StreamConfig::adaptive
+
+/// Window in which a second underrun grows the buffer.
+pub const GROW_WINDOW_SECS: usize = 10;
+
+/// Stable playback needed before the buffer shrinks one step.
+pub const SHRINK_AFTER_SECS: usize = 30;
+
+/// Fill limit controller for an adaptive stream.
+#[derive(Debug, Clone)]
+pub struct AdaptiveBuffer {
+    min: usize,
+    max: usize,
+    current: usize,
+    /// Samples written since the last underrun (`None` before the first one
+    /// and after a resize)
+    since_underrun: Option<usize>,
+    /// Samples written since the last underrun or resize
+    stable: usize,
+    grow_window: usize,
+    shrink_after: usize,
+}
+
+impl AdaptiveBuffer {
+    /// Create a controller sizing between `min` and `max` samples.
+    ///
+    /// `samples_per_second` is the sample rate times the interleaved channels.
+    pub fn new(min: usize, max: usize, samples_per_second: usize) -> Self {
+        Self {
+            min,
+            max: max.max(min),
+            current: min,
+            since_underrun: None,
+            stable: 0,
+            grow_window: GROW_WINDOW_SECS * samples_per_second,
+            shrink_after: SHRINK_AFTER_SECS * samples_per_second,
+        }
+    }
+
+    /// Record an underrun; returns the new size when the buffer grows.
+    pub fn underrun(&mut self) -> Option<usize> {
+        let repeated = self
+            .since_underrun
+            .is_some_and(|samples| samples <= self.grow_window);
+        self.stable = 0;
+        if repeated && self.current < self.max {
+            self.current = (self.current * 2).min(self.max);
+            self.since_underrun = None;
+            return Some(self.current);
+        }
+        self.since_underrun = Some(0);
+        None
+    }
+
+    /// Record written samples; returns the new size when the buffer shrinks.
+    pub fn written(&mut self, samples: usize) -> Option<usize> {
+        if let Some(since) = self.since_underrun.as_mut() {
+            *since += samples;
+        }
+        self.stable += samples;
+        if self.stable >= self.shrink_after && self.current > self.min {
+            self.current = (self.current / 2).max(self.min);
+            self.stable = 0;
+            return Some(self.current);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grows_on_repeated_underruns_only() {
+        // One sample per second keeps the windows small
+        let mut buffer = AdaptiveBuffer::new(4096, 16384, 1);
+        assert_eq!(buffer.underrun(), None);
+
+        // A second underrun long after the first does not grow the buffer
+        buffer.written(GROW_WINDOW_SECS + 1);
+        assert_eq!(buffer.underrun(), None);
+
+        buffer.written(1);
+        assert_eq!(buffer.underrun(), Some(8192));
+        // The next step needs two more underruns
+        assert_eq!(buffer.underrun(), None);
+        assert_eq!(buffer.underrun(), Some(16384));
+        // Already at the largest size
+        assert_eq!(buffer.underrun(), None);
+        assert_eq!(buffer.underrun(), None);
+    }
+
+    #[test]
+    fn test_shrinks_after_stable_playback() {
+        let mut buffer = AdaptiveBuffer::new(4096, 16384, 1);
+        buffer.underrun();
+        assert_eq!(buffer.underrun(), Some(8192));
+
+        assert_eq!(buffer.written(SHRINK_AFTER_SECS - 1), None);
+        // An underrun restarts the stable period
+        buffer.underrun();
+        assert_eq!(buffer.written(SHRINK_AFTER_SECS - 1), None);
+        assert_eq!(buffer.written(1), Some(4096));
+        assert_eq!(buffer.written(SHRINK_AFTER_SECS), None);
+    }
+}
@@ -32,6 +32,8 @@ struct RingBufferSource {
     buffer: Vec<f32>,
     /// Current position in the internal buffer
     buffer_pos: usize,
+    /// Number of valid samples in the internal buffer
+    buffer_len: usize,
 }
 
 impl RingBufferSource {
@@ -48,7 +50,8 @@ impl RingBufferSource {
             channels,
             finished,
             buffer: vec![0.0f32; 4096],
-            buffer_pos: 4096, // Start by reading new batch
+            buffer_pos: 0,
+            buffer_len: 0, // Start by reading new batch
         }
     }
 }
@@ -87,21 +90,23 @@ impl Iterator for RingBufferSource {
         }
 
         // Check if we need to refill the internal buffer
-        if self.buffer_pos >= self.buffer.len() {
-            // Refill internal buffer from ring buffer (batch read)
+        if self.buffer_pos >= self.buffer_len {
+            // Refill internal buffer from ring buffer (batch read); a short
+            // read only plays what arrived
             let read = self.ring_buffer.read(&mut self.buffer);
+            self.buffer_pos = 0;
+            self.buffer_len = read;
 
-            if read > 0 {
-                self.buffer_pos = 0;
-            } else {
+            if read == 0 {
                 // Ring buffer underrun - return silence to keep stream alive
-                self.buffer_pos = 0;
+                self.ring_buffer.mark_underrun();
                 self.buffer.fill(0.0);
+                self.buffer_len = self.buffer.len();
             }
         }
 
         // Return next sample from internal buffer
-        if self.buffer_pos < self.buffer.len() {
+        if self.buffer_pos < self.buffer_len {
             let sample = self.buffer[self.buffer_pos];
             self.buffer_pos += 1;
             self.current_pos += 1;
@@ -294,6 +299,25 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_ring_buffer_source_plays_short_reads_only() {
+        let ring_buffer = Arc::new(RingBuffer::new(4096).expect("Failed to create ring buffer"));
+        let finished = Arc::new(AtomicBool::new(false));
+        let mut source = RingBufferSource::new(Arc::clone(&ring_buffer), 44100, 1, finished);
+
+        ring_buffer.write(&[0.5; 3]);
+        let samples: Vec<f32> = source.by_ref().take(3).collect();
+        assert_eq!(samples, [0.5; 3]);
+
+        // The next sample comes from a fresh read, not the rest of the batch
+        ring_buffer.write(&[0.25]);
+        assert_eq!(source.next(), Some(0.25));
+        assert_eq!(ring_buffer.underrun_count(), 0);
+
+        assert_eq!(source.next(), Some(0.0));
+        assert_eq!(ring_buffer.underrun_count(), 1);
+    }
+
     #[test]
     fn test_ring_buffer_source_finished_signal() {
         let ring_buffer = Arc::new(RingBuffer::new(4096).expect("Failed to create ring buffer"));
@@ -0,0 +1,211 @@
+//! JACK output backend (`jack` feature)
+//!
+//! Registers a `ym-replayer` client with `out_left`/`out_right` ports that are
+//! connected to the system playback ports, and optionally `psg_a`, `psg_b`
+//! and `psg_c` stem ports carrying the dry channel outputs of the first PSG
+//! for routing into a DAW session. PipeWire serves JACK clients through
+//! `pipewire-jack`, so the same backend works there.
+//!
+//! The stream keeps its own sample rate; frames are linearly resampled to the
+//! server rate in the process callback.
+
+use super::RingBuffer;
+use super::audio_device::AudioDeviceError;
+use jack::{AudioOut, Client, ClientOptions, Control, Frames, Port, PortFlags, ProcessScope};
+use std::sync::Arc;
+
+/// JACK client name, and the prefix of its port names.
+const CLIENT_NAME: &str = "ym-replayer";
+
+/// Port names in frame order: the stereo mix, then the A/B/C stems.
+const PORT_NAMES: [&str; 5] = ["out_left", "out_right", "psg_a", "psg_b", "psg_c"];
+
+/// Active JACK client streaming the ring buffer.
+pub struct JackOutput {
+    client: jack::AsyncClient<(), JackProcess>,
+}
+
+impl JackOutput {
+    /// Connect to the JACK server and start streaming.
+    ///
+    /// `channels` is the frame width of the ring buffer: 2 for the stereo mix,
+    /// 5 with stems.
+    pub fn new(
+        ring_buffer: Arc<RingBuffer>,
+        sample_rate: u32,
+        channels: u16,
+    ) -> Result<Self, AudioDeviceError> {
+        let (client, _status) = Client::new(CLIENT_NAME, ClientOptions::default())
+            .map_err(|e| AudioDeviceError(format!("Failed to connect to JACK: {e}")))?;
+
+        let width = usize::from(channels).min(PORT_NAMES.len());
+        let ports = PORT_NAMES[..width]
+            .iter()
+            .map(|name| client.register_port(name, AudioOut::default()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| AudioDeviceError(format!("Failed to register JACK port: {e}")))?;
+        let mix_ports = ports[..2]
+            .iter()
+            .filter_map(|port| port.name().ok())
+            .collect::<Vec<_>>();
+
+        let step = f64::from(sample_rate) / f64::from(client.sample_rate());
+        let process = JackProcess {
+            ports,
+            resampler: FrameResampler::new(width, step),
+            scratch: vec![0.0; client.buffer_size() as usize * width],
+            ring_buffer,
+        };
+        let client = client
+            .activate_async((), process)
+            .map_err(|e| AudioDeviceError(format!("Failed to activate JACK client: {e}")))?;
+
+        // Route the mix to the speakers; stems are left for the user to patch
+        let playback = client.as_client().ports(
+            None,
+            Some(jack::jack_sys::FLOAT_MONO_AUDIO),
+            PortFlags::IS_INPUT | PortFlags::IS_PHYSICAL,
+        );
+        for (source, destination) in mix_ports.iter().zip(&playback) {
+            let _ = client
+                .as_client()
+                .connect_ports_by_name(source, destination);
+        }
+
+        Ok(JackOutput { client })
+    }
+
+    /// Disconnect from the JACK server.
+    pub fn finish(self) {
+        let _ = self.client.deactivate();
+    }
+}
+
+/// Process callback state.
+struct JackProcess {
+    ports: Vec<Port<AudioOut>>,
+    resampler: FrameResampler,
+    /// Interleaved frames of one period
+    scratch: Vec<f32>,
+    ring_buffer: Arc<RingBuffer>,
+}
+
+impl jack::ProcessHandler for JackProcess {
+    fn process(&mut self, _: &Client, ps: &ProcessScope) -> Control {
+        let width = self.ports.len();
+        let len = ps.n_frames() as usize * width;
+        if self.scratch.len() < len {
+            // Normally sized in buffer_size() outside the realtime thread
+            self.scratch.resize(len, 0.0);
+        }
+        let frames = &mut self.scratch[..len];
+        self.resampler.render(&self.ring_buffer, frames);
+        for (channel, port) in self.ports.iter_mut().enumerate() {
+            let out = port.as_mut_slice(ps);
+            for (sample, frame) in out.iter_mut().zip(frames.chunks_exact(width)) {
+                *sample = frame[channel];
+            }
+        }
+        Control::Continue
+    }
+
+    fn buffer_size(&mut self, _: &Client, size: Frames) -> Control {
+        self.scratch.resize(size as usize * self.ports.len(), 0.0);
+        Control::Continue
+    }
+}
+
+/// Linear interpolating resampler over interleaved frames from a ring buffer.
+struct FrameResampler {
+    width: usize,
+    /// Source frames per output frame
+    step: f64,
+    /// Position between `prev` and `next` (1.0 or more: advance)
+    pos: f64,
+    prev: [f32; PORT_NAMES.len()],
+    next: [f32; PORT_NAMES.len()],
+    /// Samples read from the ring buffer, not yet consumed (`start..end`)
+    input: Vec<f32>,
+    start: usize,
+    end: usize,
+}
+
+impl FrameResampler {
+    fn new(width: usize, step: f64) -> Self {
+        Self {
+            width,
+            step,
+            pos: 1.0,
+            prev: [0.0; PORT_NAMES.len()],
+            next: [0.0; PORT_NAMES.len()],
+            input: vec![0.0; 4096],
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Fill `out` with interleaved frames; silence where the buffer ran dry.
+    fn render(&mut self, ring_buffer: &RingBuffer, out: &mut [f32]) {
+        let width = self.width;
+        for frame in out.chunks_exact_mut(width) {
+            while self.pos >= 1.0 && self.advance(ring_buffer) {
+                self.pos -= 1.0;
+            }
+            if self.pos >= 1.0 {
+                ring_buffer.mark_underrun();
+                frame.fill(0.0);
+                continue;
+            }
+            let t = self.pos as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                let (a, b) = (self.prev[channel], self.next[channel]);
+                *sample = a + (b - a) * t;
+            }
+            self.pos += self.step;
+        }
+    }
+
+    /// Move to the next source frame; false when none is buffered.
+    fn advance(&mut self, ring_buffer: &RingBuffer) -> bool {
+        let width = self.width;
+        if self.end - self.start < width {
+            // Keep a partial frame and top up behind it
+            self.input.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.start = 0;
+            self.end += ring_buffer.read(&mut self.input[self.end..]);
+            if self.end < width {
+                return false;
+            }
+        }
+        self.prev = self.next;
+        self.next[..width].copy_from_slice(&self.input[self.start..self.start + width]);
+        self.start += width;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resamples_interleaved_frames() {
+        let ring = RingBuffer::new(64).unwrap();
+        // Two-channel ramp: frame n = (n, -n)
+        let frames: Vec<f32> = (0..8).flat_map(|n| [n as f32, -(n as f32)]).collect();
+        ring.write(&frames);
+
+        // Upsample 2x: every other output frame lies halfway between inputs
+        let mut resampler = FrameResampler::new(2, 0.5);
+        let mut out = [0.0f32; 8];
+        resampler.render(&ring, &mut out);
+        assert_eq!(out, [0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5, -0.5]);
+
+        // Underrun yields silence instead of stale samples
+        let mut resampler = FrameResampler::new(2, 1.0);
+        let mut out = [1.0f32; 40];
+        resampler.render(&RingBuffer::new(64).unwrap(), &mut out);
+        assert!(out.iter().all(|&s| s == 0.0));
+    }
+}
@@ -3,12 +3,20 @@
 //! This module provides real-time audio playback with a ring buffer that allows
 //! concurrent sample generation and playback. Memory usage is limited to the ring buffer size.
 
+pub mod adaptive;
 pub mod audio_device;
+#[cfg(feature = "jack")]
+pub mod jack_output;
+pub mod pipe_output;
 pub mod realtime;
 pub mod ring_buffer;
 
+pub use adaptive::AdaptiveBuffer;
 pub use audio_device::AudioDevice;
-pub use realtime::{PlaybackStats, RealtimePlayer};
+#[cfg(feature = "jack")]
+pub use jack_output::JackOutput;
+pub use pipe_output::{PcmFormat, PipeOutput};
+pub use realtime::{PlaybackStats, RealtimePlayer, UnderrunEvent, UnderrunRecovery};
 pub use ring_buffer::RingBuffer;
 
 // Re-export sample rate from common crate
@@ -20,19 +28,71 @@ pub const VISUALIZATION_UPDATE_MS: u64 = 50;
 /// Buffer backoff time in microseconds
 pub const BUFFER_BACKOFF_MICROS: u64 = 100;
 
+/// Where the streamed samples go
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// System audio device
+    #[default]
+    Device,
+    /// Raw interleaved PCM on stdout
+    Stdout(PcmFormat),
+    /// JACK client, optionally with per-channel stem ports of the first PSG
+    #[cfg(feature = "jack")]
+    Jack { stems: bool },
+}
+
+impl OutputTarget {
+    /// Whether each frame carries the A/B/C channel stems after the stereo mix
+    pub fn has_stems(&self) -> bool {
+        match self {
+            #[cfg(feature = "jack")]
+            OutputTarget::Jack { stems } => *stems,
+            _ => false,
+        }
+    }
+}
+
+/// Running audio output of a stream
+pub enum AudioOutput {
+    Device(AudioDevice),
+    Pipe(PipeOutput),
+    #[cfg(feature = "jack")]
+    Jack(JackOutput),
+}
+
+impl AudioOutput {
+    /// Signal that no more samples will be produced
+    pub fn finish(self) {
+        match self {
+            AudioOutput::Device(device) => device.finish(),
+            AudioOutput::Pipe(pipe) => pipe.finish(),
+            #[cfg(feature = "jack")]
+            AudioOutput::Jack(jack) => jack.finish(),
+        }
+    }
+}
+
 /// Configuration for streaming playback
 #[derive(Debug, Clone, Copy)]
 pub struct StreamConfig {
     /// Size of the ring buffer (in samples)
     /// Larger buffers = more latency but less chance of underrun
     /// Typical: 4096-16384 samples (93ms-372ms at 44.1kHz)
+    /// Adaptive streams start with this size
     pub ring_buffer_size: usize,
 
+    /// Largest size an adaptive stream may grow to (equal to
+    /// `ring_buffer_size` for a fixed size)
+    pub max_ring_buffer_size: usize,
+
     /// Sample rate in Hz
     pub sample_rate: u32,
 
     /// Number of audio channels
     pub channels: u16,
+
+    /// Audio device or raw PCM on stdout
+    pub output: OutputTarget,
 }
 
 impl StreamConfig {
@@ -41,8 +101,10 @@ impl StreamConfig {
     pub fn low_latency(sample_rate: u32) -> Self {
         StreamConfig {
             ring_buffer_size: 4096,
+            max_ring_buffer_size: 4096,
             sample_rate,
             channels: 2, // Stereo output
+            output: OutputTarget::Device,
         }
     }
 
@@ -51,11 +113,36 @@ impl StreamConfig {
     pub fn stable(sample_rate: u32) -> Self {
         StreamConfig {
             ring_buffer_size: 16384,
+            max_ring_buffer_size: 16384,
             sample_rate,
             channels: 2, // Stereo output
+            output: OutputTarget::Device,
+        }
+    }
+
+    /// Create a streaming configuration that adapts to the system
+    /// Starts like [`low_latency`](Self::low_latency) and grows up to the
+    /// [`stable`](Self::stable) size when underruns repeat, shrinking back
+    /// once playback is stable
+    pub fn adaptive(sample_rate: u32) -> Self {
+        StreamConfig {
+            max_ring_buffer_size: Self::stable(sample_rate).ring_buffer_size,
+            ..Self::low_latency(sample_rate)
         }
     }
 
+    /// Whether the buffer size adapts to underruns
+    pub fn is_adaptive(&self) -> bool {
+        self.max_ring_buffer_size > self.ring_buffer_size
+    }
+
+    /// Select the output target; stems widen each frame to 5 samples
+    /// (left, right, A, B, C)
+    pub fn set_output(&mut self, output: OutputTarget) {
+        self.output = output;
+        self.channels = if output.has_stems() { 5 } else { 2 };
+    }
+
     /// Get latency in milliseconds
     pub fn latency_ms(&self) -> f32 {
         ((self.ring_buffer_size as f32) / (self.sample_rate as f32)) * 1000.0
@@ -77,5 +164,20 @@ mod tests {
         let config = StreamConfig::low_latency(44100);
         let latency = config.latency_ms();
         assert!(latency > 90.0 && latency < 95.0);
+        assert!(!config.is_adaptive());
+    }
+
+    #[test]
+    fn test_adaptive_config_spans_presets() {
+        let config = StreamConfig::adaptive(44100);
+        assert!(config.is_adaptive());
+        assert_eq!(
+            config.ring_buffer_size,
+            StreamConfig::low_latency(44100).ring_buffer_size
+        );
+        assert_eq!(
+            config.max_ring_buffer_size,
+            StreamConfig::stable(44100).ring_buffer_size
+        );
     }
 }
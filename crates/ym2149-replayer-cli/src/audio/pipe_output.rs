@@ -0,0 +1,138 @@
+//! Raw PCM output to stdout
+//!
+//! Writes the ring buffer contents to stdout as interleaved little-endian
+//! PCM instead of playing them, for piping into ffmpeg, sox or network
+//! streamers. Output runs as fast as the reader consumes it.
+
+use super::audio_device::AudioDeviceError;
+use super::{BUFFER_BACKOFF_MICROS, RingBuffer};
+use std::io::{self, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Sample encoding of the raw PCM stream.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PcmFormat {
+    /// Signed 16-bit little-endian (`s16le`)
+    #[default]
+    S16,
+    /// 32-bit float little-endian (`f32le`)
+    F32,
+}
+
+impl PcmFormat {
+    /// Parse a format name (`s16`, `s16le`, `f32`, `f32le`).
+    pub fn from_name(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "s16" | "s16le" => Some(PcmFormat::S16),
+            "f32" | "f32le" => Some(PcmFormat::F32),
+            _ => None,
+        }
+    }
+
+    /// Format name as understood by ffmpeg and sox (`-f s16le`).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PcmFormat::S16 => "s16le",
+            PcmFormat::F32 => "f32le",
+        }
+    }
+
+    /// Append `samples` to `out` in this format.
+    pub fn encode(&self, samples: &[f32], out: &mut Vec<u8>) {
+        match self {
+            PcmFormat::S16 => {
+                for &sample in samples {
+                    let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+                    out.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            PcmFormat::F32 => {
+                for &sample in samples {
+                    out.extend_from_slice(&sample.to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Writer thread streaming the ring buffer to stdout.
+pub struct PipeOutput {
+    writer: JoinHandle<()>,
+    finished: Arc<AtomicBool>,
+}
+
+impl PipeOutput {
+    /// Start writing samples from `ring_buffer` to stdout.
+    ///
+    /// Clears `running` when stdout is closed, so playback stops with the reader.
+    pub fn new(
+        ring_buffer: Arc<RingBuffer>,
+        format: PcmFormat,
+        running: Arc<AtomicBool>,
+    ) -> Result<Self, AudioDeviceError> {
+        let finished = Arc::new(AtomicBool::new(false));
+        let writer_finished = Arc::clone(&finished);
+        let writer = std::thread::Builder::new()
+            .name("pcm-output".to_string())
+            .spawn(move || write_samples(&ring_buffer, format, &writer_finished, &running))
+            .map_err(|e| AudioDeviceError(format!("Failed to start PCM output: {e}")))?;
+        Ok(PipeOutput { writer, finished })
+    }
+
+    /// Write the remaining buffered samples and stop.
+    pub fn finish(self) {
+        self.finished.store(true, Ordering::Relaxed);
+        let _ = self.writer.join();
+    }
+}
+
+fn write_samples(
+    ring_buffer: &RingBuffer,
+    format: PcmFormat,
+    finished: &AtomicBool,
+    running: &AtomicBool,
+) {
+    let mut stdout = io::stdout().lock();
+    let mut samples = vec![0.0f32; 4096];
+    let mut bytes = Vec::with_capacity(samples.len() * 4);
+    loop {
+        // Read the flag first so samples written before finish() are drained
+        let finishing = finished.load(Ordering::Relaxed);
+        let read = ring_buffer.read(&mut samples);
+        if read == 0 {
+            if finishing {
+                break;
+            }
+            std::thread::sleep(Duration::from_micros(BUFFER_BACKOFF_MICROS));
+            continue;
+        }
+        bytes.clear();
+        format.encode(&samples[..read], &mut bytes);
+        if stdout.write_all(&bytes).is_err() {
+            // Reader went away (e.g. ffmpeg quit)
+            running.store(false, Ordering::Relaxed);
+            return;
+        }
+    }
+    let _ = stdout.flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_little_endian_pcm() {
+        let mut out = Vec::new();
+        PcmFormat::S16.encode(&[1.0, -2.0, 0.0], &mut out);
+        assert_eq!(out, [0xff, 0x7f, 0x01, 0x80, 0x00, 0x00]);
+
+        out.clear();
+        PcmFormat::F32.encode(&[0.5], &mut out);
+        assert_eq!(out, 0.5f32.to_le_bytes());
+        assert_eq!(PcmFormat::from_name("F32LE"), Some(PcmFormat::F32));
+    }
+}
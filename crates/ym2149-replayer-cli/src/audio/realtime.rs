@@ -1,18 +1,44 @@
 //! Real-time audio playback with streaming
 //!
-//! Provides a simple streaming interface for real-time sample playback.
+//! Provides a simple streaming interface for real-time sample playback, and
+//! the producer's reaction to underruns: after the output runs dry, the
+//! producer renders several batches per cycle for a while and fades the
+//! resumed audio in, so a loaded system drops out softly instead of clicking.
 
 use super::ring_buffer::RingBufferError;
 use super::{BUFFER_BACKOFF_MICROS, RingBuffer, StreamConfig};
 use parking_lot::Mutex;
 use std::sync::Arc;
 
+/// Most batches the producer renders per cycle while recovering
+pub const MAX_BATCHES_PER_CYCLE: usize = 4;
+
+/// Clean batches before the producer halves its batches per cycle
+/// (about 2 seconds of 2048-frame batches at 44.1 kHz)
+const RECOVERY_BATCHES: usize = 48;
+
+/// Callback run on the producer thread after an underrun
+type UnderrunCallback = Box<dyn Fn(UnderrunEvent) + Send + Sync>;
+
 /// Real-time audio player with streaming
 pub struct RealtimePlayer {
     /// Ring buffer for sample storage
     buffer: Arc<RingBuffer>,
     /// Playback statistics
     stats: Arc<Mutex<PlaybackStats>>,
+    /// Underrun callback (see [`set_on_underrun`](Self::set_on_underrun))
+    on_underrun: Mutex<Option<UnderrunCallback>>,
+}
+
+/// Details of an underrun, passed to the underrun callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnderrunEvent {
+    /// Underruns since playback started, including this one
+    pub count: usize,
+    /// Batches the producer now renders per cycle
+    pub batches_per_cycle: usize,
+    /// Ring buffer fill limit in samples after the underrun
+    pub buffer_size: usize,
 }
 
 /// Playback statistics for monitoring overruns and buffer health
@@ -20,6 +46,10 @@ pub struct RealtimePlayer {
 pub struct PlaybackStats {
     /// Number of overrun events (producer write failed due to full buffer)
     pub overrun_count: usize,
+    /// Number of underrun gaps (audio output found the buffer empty)
+    pub underrun_count: usize,
+    /// Current ring buffer fill limit in samples
+    pub buffer_size: usize,
     /// Number of samples played
     pub samples_played: usize,
     /// Current buffer fill percentage
@@ -29,15 +59,26 @@ pub struct PlaybackStats {
 impl RealtimePlayer {
     /// Create a new real-time player with streaming
     pub fn new(config: StreamConfig) -> Result<Self, RingBufferError> {
-        let buffer = Arc::new(RingBuffer::new(config.ring_buffer_size)?);
+        let buffer = Arc::new(RingBuffer::new(
+            config.max_ring_buffer_size.max(config.ring_buffer_size),
+        )?);
+        if config.is_adaptive() {
+            buffer.set_limit(config.ring_buffer_size);
+        }
 
         let stats = Arc::new(Mutex::new(PlaybackStats {
             overrun_count: 0,
+            underrun_count: 0,
+            buffer_size: buffer.limit(),
             samples_played: 0,
             fill_percentage: 0.0,
         }));
 
-        Ok(RealtimePlayer { buffer, stats })
+        Ok(RealtimePlayer {
+            buffer,
+            stats,
+            on_underrun: Mutex::new(None),
+        })
     }
 
     /// Write samples to the playback buffer
@@ -77,11 +118,51 @@ impl RealtimePlayer {
         total_written
     }
 
+    /// Set a callback run on the producer thread after each underrun
+    ///
+    /// The callback should return quickly; the producer is recovering from
+    /// an empty buffer while it runs.
+    pub fn set_on_underrun(&self, callback: impl Fn(UnderrunEvent) + Send + Sync + 'static) {
+        *self.on_underrun.lock() = Some(Box::new(callback));
+    }
+
+    /// Check for underruns since the last call
+    ///
+    /// Returns the total underrun count when new ones occurred.
+    pub fn poll_underruns(&self) -> Option<usize> {
+        let count = self.buffer.underrun_count();
+        let mut stats = self.stats.lock();
+        if count == stats.underrun_count {
+            return None;
+        }
+        stats.underrun_count = count;
+        Some(count)
+    }
+
+    /// Pass an underrun to the callback, if one is set
+    pub fn notify_underrun(&self, event: UnderrunEvent) {
+        if let Some(callback) = self.on_underrun.lock().as_ref() {
+            callback(event);
+        }
+    }
+
+    /// Change the ring buffer fill limit (see [`StreamConfig::adaptive`])
+    pub fn set_buffer_size(&self, samples: usize) {
+        self.buffer.set_limit(samples);
+        self.stats.lock().buffer_size = self.buffer.limit();
+    }
+
     /// Get current playback statistics
     pub fn get_stats(&self) -> PlaybackStats {
         *self.stats.lock()
     }
 
+    /// Discard buffered samples that have not been played yet
+    pub fn flush(&self) {
+        self.buffer.flush();
+        self.stats.lock().fill_percentage = 0.0;
+    }
+
     /// Get buffer fill percentage (0.0 to 1.0)
     pub fn fill_percentage(&self) -> f32 {
         self.buffer.fill_percentage()
@@ -96,15 +177,80 @@ impl RealtimePlayer {
 
 impl Drop for RealtimePlayer {
     fn drop(&mut self) {
-        // Stream is automatically stopped when dropped
+        // Stream is automatically stopped when dropped; stderr keeps
+        // stdout clean for raw PCM and JSON status output
         let stats = self.stats.lock();
-        println!(
-            "Playback complete: {} samples, {} overruns",
-            stats.samples_played, stats.overrun_count
+        eprintln!(
+            "Playback complete: {} samples, {} overruns, {} underruns",
+            stats.samples_played, stats.overrun_count, stats.underrun_count
         );
     }
 }
 
+/// Producer-side recovery from underruns
+///
+/// Each underrun doubles the batches rendered per cycle (up to
+/// [`MAX_BATCHES_PER_CYCLE`]) and schedules a fade-in over the next samples;
+/// every [`RECOVERY_BATCHES`] clean batches halve it again.
+#[derive(Debug, Clone)]
+pub struct UnderrunRecovery {
+    batches_per_cycle: usize,
+    clean_batches: usize,
+    fade_frames: usize,
+    /// Frames of the pending fade-in not yet applied
+    fade_remaining: usize,
+}
+
+impl UnderrunRecovery {
+    /// Create a recovery state with a fade-in of `fade_frames` frames
+    pub fn new(fade_frames: usize) -> Self {
+        Self {
+            batches_per_cycle: 1,
+            clean_batches: 0,
+            fade_frames,
+            fade_remaining: 0,
+        }
+    }
+
+    /// React to a new underrun
+    pub fn trigger(&mut self) {
+        self.batches_per_cycle = (self.batches_per_cycle * 2).min(MAX_BATCHES_PER_CYCLE);
+        self.clean_batches = 0;
+        self.fade_remaining = self.fade_frames;
+    }
+
+    /// Batches to render before the producer checks commands again
+    pub fn batches_per_cycle(&self) -> usize {
+        self.batches_per_cycle
+    }
+
+    /// Fade the start of `samples` in if an underrun is pending
+    ///
+    /// `samples` holds interleaved frames of `width` channels.
+    pub fn apply_fade(&mut self, samples: &mut [f32], width: usize) {
+        for frame in samples.chunks_exact_mut(width) {
+            if self.fade_remaining == 0 {
+                break;
+            }
+            let gain = 1.0 - self.fade_remaining as f32 / self.fade_frames as f32;
+            frame.iter_mut().for_each(|sample| *sample *= gain);
+            self.fade_remaining -= 1;
+        }
+    }
+
+    /// Count a batch written without a new underrun
+    pub fn batch_done(&mut self) {
+        if self.batches_per_cycle == 1 {
+            return;
+        }
+        self.clean_batches += 1;
+        if self.clean_batches >= RECOVERY_BATCHES {
+            self.batches_per_cycle /= 2;
+            self.clean_batches = 0;
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -119,6 +265,8 @@ mod tests {
     fn test_playback_stats() {
         let stats = PlaybackStats {
             overrun_count: 0,
+            underrun_count: 0,
+            buffer_size: 16383,
             samples_played: 44100,
             fill_percentage: 0.5,
         };
@@ -127,4 +275,68 @@ mod tests {
         assert_eq!(stats.overrun_count, 0);
         assert!(stats.fill_percentage > 0.4 && stats.fill_percentage < 0.6);
     }
+
+    #[test]
+    fn test_underrun_recovery_grows_and_decays() {
+        let mut recovery = UnderrunRecovery::new(4);
+        assert_eq!(recovery.batches_per_cycle(), 1);
+
+        recovery.trigger();
+        recovery.trigger();
+        recovery.trigger();
+        assert_eq!(recovery.batches_per_cycle(), MAX_BATCHES_PER_CYCLE);
+
+        for _ in 0..RECOVERY_BATCHES {
+            recovery.batch_done();
+        }
+        assert_eq!(recovery.batches_per_cycle(), 2);
+        for _ in 0..RECOVERY_BATCHES {
+            recovery.batch_done();
+        }
+        assert_eq!(recovery.batches_per_cycle(), 1);
+    }
+
+    #[test]
+    fn test_underrun_recovery_fades_in() {
+        let mut recovery = UnderrunRecovery::new(4);
+        let mut samples = [1.0f32; 12];
+        recovery.apply_fade(&mut samples, 2);
+        assert_eq!(samples, [1.0; 12]);
+
+        recovery.trigger();
+        recovery.apply_fade(&mut samples[..6], 2);
+        recovery.apply_fade(&mut samples[6..], 2);
+        assert_eq!(
+            samples,
+            [
+                0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0, 1.0
+            ]
+        );
+    }
+
+    #[test]
+    fn test_poll_underruns_reports_new_gaps() {
+        let player = RealtimePlayer::new(StreamConfig::low_latency(44100)).unwrap();
+        let buffer = player.get_buffer();
+        assert_eq!(player.poll_underruns(), None);
+
+        let mut dest = [0.0; 4];
+        buffer.write(&[1.0; 4]);
+        buffer.read(&mut dest);
+        buffer.mark_underrun();
+        assert_eq!(player.poll_underruns(), Some(1));
+        assert_eq!(player.poll_underruns(), None);
+        assert_eq!(player.get_stats().underrun_count, 1);
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&events);
+        player.set_on_underrun(move |event| seen.lock().push(event));
+        let event = UnderrunEvent {
+            count: 1,
+            batches_per_cycle: 2,
+            buffer_size: 4096,
+        };
+        player.notify_underrun(event);
+        assert_eq!(*events.lock(), [event]);
+    }
 }
@@ -5,10 +5,12 @@
 //! - Consumer thread: Reads samples from buffer and outputs to audio device
 //!
 //! Memory consumption is fixed at buffer_size * sizeof(f32) regardless of duration.
+//! The producer may be held to a lower fill limit, which sets the output
+//! latency and can change while streaming (see `StreamConfig::adaptive`).
 //! Uses mutex-based synchronization with atomic position tracking for visibility.
 
 use parking_lot::Mutex;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 /// Error type for ring buffer operations
 #[derive(Debug, Clone)]
@@ -41,6 +43,12 @@ pub struct RingBuffer {
     capacity: usize,
     /// Capacity mask for fast modulo: `pos & mask == pos % capacity`
     mask: usize,
+    /// Most samples the producer may keep buffered (at most `capacity - 1`)
+    limit: AtomicUsize,
+    /// Gaps in which a real-time consumer found the buffer empty
+    underruns: AtomicUsize,
+    /// Whether samples were read since the last gap, flush or creation
+    primed: AtomicBool,
 }
 
 impl RingBuffer {
@@ -79,6 +87,9 @@ impl RingBuffer {
             read_pos: AtomicUsize::new(0),
             capacity,
             mask,
+            limit: AtomicUsize::new(capacity - 1),
+            underruns: AtomicUsize::new(0),
+            primed: AtomicBool::new(false),
         })
     }
 
@@ -115,6 +126,9 @@ impl RingBuffer {
         } else {
             (read_pos - write_pos) - 1
         };
+        // Stay within the fill limit
+        let used = self.capacity - 1 - available;
+        let available = available.min(self.limit().saturating_sub(used));
 
         let to_write = samples.len().min(available);
 
@@ -178,19 +192,52 @@ impl RingBuffer {
             dest[first_part..to_read].copy_from_slice(&buf[..to_read - first_part]);
         }
 
-        drop(buf); // Release lock before updating position
-
-        // Update read position
+        // Update read position before releasing the lock so a concurrent
+        // flush cannot be undone by a stale position
         self.read_pos.store(read_pos + to_read, Ordering::Release);
+        drop(buf);
+        self.primed.store(true, Ordering::Relaxed);
 
         to_read
     }
 
-    /// Drain and discard all samples from the buffer (used in tests)
-    #[cfg(test)]
+    /// Most samples the producer may keep buffered
+    pub fn limit(&self) -> usize {
+        self.limit.load(Ordering::Relaxed)
+    }
+
+    /// Change the fill limit, clamped to the capacity
+    ///
+    /// Lowering it below the current fill makes writes wait until the
+    /// consumer has drained the excess.
+    pub fn set_limit(&self, samples: usize) {
+        self.limit
+            .store(samples.clamp(1, self.capacity - 1), Ordering::Relaxed);
+    }
+
+    /// Record that a real-time consumer ran out of samples
+    ///
+    /// Counts each gap once, and ignores the wait for the first samples after
+    /// creation or a [`flush`](Self::flush).
+    pub fn mark_underrun(&self) {
+        if self.primed.swap(false, Ordering::Relaxed) {
+            self.underruns.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Number of underrun gaps recorded so far
+    pub fn underrun_count(&self) -> usize {
+        self.underruns.load(Ordering::Relaxed)
+    }
+
+    /// Drain and discard all samples from the buffer
+    ///
+    /// Called by the producer, e.g. after a seek so stale audio is not played.
     pub fn flush(&self) {
+        let _buf = self.buffer.lock();
         let write_pos = self.write_pos.load(Ordering::Acquire);
         self.read_pos.store(write_pos, Ordering::Release);
+        self.primed.store(false, Ordering::Relaxed);
     }
 
     /// Check if the buffer has any samples to read (used in tests)
@@ -205,9 +252,9 @@ impl RingBuffer {
         self.capacity - self.available_read() - 1 == 0
     }
 
-    /// Get fill percentage (0.0 to 1.0)
+    /// Get fill percentage of the fill limit (0.0 to 1.0)
     pub fn fill_percentage(&self) -> f32 {
-        (self.available_read() as f32) / (self.capacity as f32)
+        (self.available_read() as f32) / (self.limit() as f32)
     }
 }
 
@@ -296,6 +343,55 @@ mod tests {
         assert!(rb.is_empty());
     }
 
+    #[test]
+    fn test_fill_limit() {
+        let rb = RingBuffer::new(64).unwrap();
+        rb.set_limit(16);
+        assert_eq!(rb.write(&[1.0; 32]), 16);
+        assert_eq!(rb.fill_percentage(), 1.0);
+
+        // Raising the limit makes room right away
+        rb.set_limit(24);
+        assert_eq!(rb.write(&[1.0; 32]), 8);
+
+        // Lowering it below the fill blocks writes until drained
+        rb.set_limit(8);
+        assert_eq!(rb.write(&[1.0; 4]), 0);
+        let mut dest = [0.0; 20];
+        rb.read(&mut dest);
+        assert_eq!(rb.write(&[1.0; 8]), 4);
+
+        rb.set_limit(usize::MAX);
+        assert_eq!(rb.limit(), 63);
+    }
+
+    #[test]
+    fn test_underruns_count_once_per_gap() {
+        let rb = RingBuffer::new(16).unwrap();
+        let mut dest = [0.0; 8];
+
+        // Waiting for the first samples is not an underrun
+        rb.mark_underrun();
+        assert_eq!(rb.underrun_count(), 0);
+
+        rb.write(&[1.0; 4]);
+        rb.read(&mut dest);
+        rb.mark_underrun();
+        rb.mark_underrun();
+        assert_eq!(rb.underrun_count(), 1);
+
+        rb.write(&[1.0; 4]);
+        rb.read(&mut dest);
+        rb.flush();
+        rb.mark_underrun();
+        assert_eq!(rb.underrun_count(), 1);
+
+        rb.write(&[1.0; 4]);
+        rb.read(&mut dest);
+        rb.mark_underrun();
+        assert_eq!(rb.underrun_count(), 2);
+    }
+
     #[test]
     fn test_zero_capacity_error() {
         let result = RingBuffer::new(0);
@@ -0,0 +1,452 @@
+//! Regular audio files in chiptune playlists (`audio-files` feature).
+//!
+//! [`AudioFilePlayer`] decodes MP3, FLAC, Ogg Vorbis and WAV files with
+//! symphonia while it plays and resamples them to the output rate, so
+//! original tracks and their chiptune remixes can share one playlist. There
+//! is no PSG behind it: the register views stay empty and channel mutes do
+//! nothing.
+
+use std::collections::VecDeque;
+use std::io::Cursor;
+use std::path::Path;
+
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{
+    CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_NULL, CODEC_TYPE_VORBIS, CodecType, Decoder,
+    DecoderOptions,
+};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+use ym2149_common::{ChiptunePlayerBase, PlaybackState};
+
+use crate::{MAX_PSG_COUNT, RealtimeChip, VisualSnapshot};
+
+/// Extensions of the audio files that playlists pick up
+pub const AUDIO_FILE_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "wav"];
+
+/// Check if a path has one of the audio file extensions
+pub fn is_audio_file_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_FILE_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Display name of a codec
+fn codec_name(codec: CodecType) -> String {
+    match codec {
+        CODEC_TYPE_MP3 => "MP3".to_string(),
+        CODEC_TYPE_FLAC => "FLAC".to_string(),
+        CODEC_TYPE_VORBIS => "Ogg Vorbis".to_string(),
+        other => match symphonia::default::get_codecs().get_codec(other) {
+            Some(codec) if codec.short_name.starts_with("pcm") => "WAV".to_string(),
+            Some(codec) => codec.short_name.to_ascii_uppercase(),
+            None => "Audio".to_string(),
+        },
+    }
+}
+
+/// Tags read from the file (empty when missing).
+#[derive(Clone, Debug, Default)]
+pub struct AudioTags {
+    pub title: String,
+    pub artist: String,
+    pub comment: String,
+    /// Release year
+    pub year: String,
+}
+
+impl AudioTags {
+    fn read(revision: &MetadataRevision) -> Self {
+        let mut tags = Self::default();
+        for tag in revision.tags() {
+            let field = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut tags.title,
+                Some(StandardTagKey::Artist) => &mut tags.artist,
+                Some(StandardTagKey::Comment) => &mut tags.comment,
+                Some(StandardTagKey::Date | StandardTagKey::ReleaseDate) => &mut tags.year,
+                _ => continue,
+            };
+            if field.is_empty() {
+                *field = tag.value.to_string().trim().to_string();
+            }
+        }
+        // Dates come as years or full ISO dates
+        tags.year = tags.year.chars().take(4).collect();
+        tags
+    }
+}
+
+/// Streaming player for an MP3/FLAC/Ogg/WAV file.
+pub struct AudioFilePlayer {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    /// Codec name for display
+    codec: String,
+    tags: AudioTags,
+    source_rate: u32,
+    output_rate: u32,
+    /// Length in source frames, if the container knows it
+    total_frames: Option<u64>,
+    /// Decoded stereo frames not yet played
+    frames: VecDeque<[f32; 2]>,
+    scratch: Option<SampleBuffer<f32>>,
+    /// Read position between `frames[0]` and `frames[1]`
+    phase: f64,
+    /// Source frames per output sample
+    step: f64,
+    /// Source frames played so far
+    position: u64,
+    state: PlaybackState,
+    gain: f32,
+}
+
+impl AudioFilePlayer {
+    /// Open an audio file held in memory.
+    ///
+    /// `extension` is only a hint; the container is detected from the data.
+    pub fn new(data: Vec<u8>, extension: &str, output_rate: u32) -> Result<Self, String> {
+        let mut hint = Hint::new();
+        hint.with_extension(extension);
+        let source = MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default());
+        let mut probed = symphonia::default::get_probe()
+            .format(
+                &hint,
+                source,
+                &FormatOptions {
+                    enable_gapless: true,
+                    ..Default::default()
+                },
+                &MetadataOptions::default(),
+            )
+            .map_err(|e| format!("Unrecognised audio file: {e}"))?;
+
+        let track = probed
+            .format
+            .tracks()
+            .iter()
+            .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+            .ok_or("Audio file has no playable track")?;
+        let params = track.codec_params.clone();
+        let track_id = track.id;
+        let source_rate = params
+            .sample_rate
+            .ok_or("Audio file does not state its sample rate")?;
+        let decoder = symphonia::default::get_codecs()
+            .make(&params, &DecoderOptions::default())
+            .map_err(|e| format!("Unsupported audio codec: {e}"))?;
+        let codec = codec_name(params.codec);
+
+        // ID3 tags sit in front of the container; Vorbis comments and RIFF
+        // INFO chunks are part of it
+        let tags = probed
+            .format
+            .metadata()
+            .current()
+            .map(AudioTags::read)
+            .or_else(|| probed.metadata.get()?.current().map(AudioTags::read))
+            .unwrap_or_default();
+
+        Ok(Self {
+            format: probed.format,
+            decoder,
+            track_id,
+            codec,
+            tags,
+            source_rate,
+            output_rate,
+            total_frames: params.n_frames,
+            frames: VecDeque::new(),
+            scratch: None,
+            phase: 0.0,
+            step: f64::from(source_rate) / f64::from(output_rate),
+            position: 0,
+            state: PlaybackState::Stopped,
+            gain: 1.0,
+        })
+    }
+
+    /// Tags read from the file.
+    pub fn tags(&self) -> &AudioTags {
+        &self.tags
+    }
+
+    /// Codec name for display (`MP3`, `FLAC`, `Ogg Vorbis`, `WAV`, ...).
+    pub fn codec(&self) -> &str {
+        &self.codec
+    }
+
+    /// Sample rate of the file itself.
+    pub fn source_rate(&self) -> u32 {
+        self.source_rate
+    }
+
+    /// Decode the next packet into `frames`; false at the end of the stream.
+    fn decode_packet(&mut self) -> bool {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                // End of stream or a broken container
+                Err(_) => return false,
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // Corrupt packets are skipped
+                Err(SymphoniaError::DecodeError(_)) => continue,
+                Err(_) => return false,
+            };
+
+            let spec = *decoded.spec();
+            let channels = spec.channels.count().max(1);
+            let needed = decoded.capacity() * channels;
+            let scratch = match &mut self.scratch {
+                Some(scratch) if scratch.capacity() >= needed => scratch,
+                scratch => scratch.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+            };
+            scratch.copy_interleaved_ref(decoded);
+            self.frames.extend(
+                scratch
+                    .samples()
+                    .chunks_exact(channels)
+                    .map(|frame| [frame[0], *frame.get(1).unwrap_or(&frame[0])]),
+            );
+            return true;
+        }
+    }
+
+    /// Next output frame, linearly resampled; `None` once the file has ended.
+    fn next_frame(&mut self) -> Option<[f32; 2]> {
+        while self.phase >= 1.0 {
+            if self.frames.is_empty() && !self.decode_packet() {
+                return None;
+            }
+            if self.frames.pop_front().is_some() {
+                self.position += 1;
+                self.phase -= 1.0;
+            }
+        }
+        while self.frames.len() < 2 && self.decode_packet() {}
+
+        let a = *self.frames.front()?;
+        let b = self.frames.get(1).copied().unwrap_or(a);
+        let t = self.phase as f32;
+        self.phase += self.step;
+        Some([a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t])
+    }
+
+    /// Render stereo frames, stopping at the end of the file.
+    fn render(&mut self, out: &mut [f32], channels: usize) {
+        let mut frames = out.chunks_exact_mut(channels);
+        if self.state == PlaybackState::Playing {
+            for slot in frames.by_ref() {
+                let Some([left, right]) = self.next_frame() else {
+                    self.state = PlaybackState::Stopped;
+                    slot.fill(0.0);
+                    break;
+                };
+                if let [mono] = slot {
+                    *mono = (left + right) * 0.5 * self.gain;
+                } else {
+                    slot[0] = left * self.gain;
+                    slot[1] = right * self.gain;
+                }
+            }
+        }
+        for slot in frames {
+            slot.fill(0.0);
+        }
+    }
+
+    /// Jump to a source frame; false if the container can't seek.
+    fn seek_frame(&mut self, frame: u64) -> bool {
+        let seeked = self.format.seek(
+            SeekMode::Coarse,
+            SeekTo::TimeStamp {
+                ts: frame,
+                track_id: self.track_id,
+            },
+        );
+        match seeked {
+            Ok(seeked) => {
+                self.decoder.reset();
+                self.frames.clear();
+                self.phase = 0.0;
+                self.position = seeked.actual_ts;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl ChiptunePlayerBase for AudioFilePlayer {
+    fn play(&mut self) {
+        self.state = PlaybackState::Playing;
+    }
+
+    fn pause(&mut self) {
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+        }
+    }
+
+    fn stop(&mut self) {
+        self.state = PlaybackState::Stopped;
+        self.seek_frame(0);
+    }
+
+    fn state(&self) -> PlaybackState {
+        self.state
+    }
+
+    fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        self.render(buffer, 1);
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.output_rate
+    }
+
+    fn set_gain(&mut self, gain: f32) {
+        self.gain = gain;
+    }
+
+    fn gain(&self) -> f32 {
+        self.gain
+    }
+
+    fn playback_position(&self) -> f32 {
+        match self.total_frames {
+            Some(total) if total > 0 => (self.position as f64 / total as f64).min(1.0) as f32,
+            _ => 0.0,
+        }
+    }
+
+    fn seek(&mut self, position: f32) -> bool {
+        let Some(total) = self.total_frames else {
+            return false;
+        };
+        let frame = (f64::from(position.clamp(0.0, 1.0)) * total as f64) as u64;
+        self.seek_frame(frame)
+    }
+
+    fn duration_seconds(&self) -> f32 {
+        self.total_frames.map_or(0.0, |total| {
+            (total as f64 / f64::from(self.source_rate)) as f32
+        })
+    }
+
+    fn elapsed_seconds(&self) -> f32 {
+        (self.position as f64 / f64::from(self.source_rate)) as f32
+    }
+}
+
+impl RealtimeChip for AudioFilePlayer {
+    fn visual_snapshot(&self) -> VisualSnapshot {
+        VisualSnapshot {
+            registers: [[0u8; 16]; MAX_PSG_COUNT],
+            psg_count: 1,
+            sync_buzzer: false,
+            sid_active: [false; MAX_PSG_COUNT * 3],
+            drum_active: [false; MAX_PSG_COUNT * 3],
+        }
+    }
+
+    fn set_color_filter(&mut self, _enabled: bool) {
+        // Not applicable for recorded audio
+    }
+
+    fn generate_samples_into_stereo(&mut self, buffer: &mut [f32]) {
+        self.render(buffer, 2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16-bit stereo WAV with a ramp on the left and silence on the right
+    fn wav(sample_rate: u32, frames: u32) -> Vec<u8> {
+        let data_len = frames * 4;
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&sample_rate.to_le_bytes());
+        wav.extend_from_slice(&(sample_rate * 4).to_le_bytes());
+        wav.extend_from_slice(&4u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&data_len.to_le_bytes());
+        for i in 0..frames {
+            let left = (i % 1000) as i16 * 16;
+            wav.extend_from_slice(&left.to_le_bytes());
+            wav.extend_from_slice(&0i16.to_le_bytes());
+        }
+        wav
+    }
+
+    #[test]
+    fn plays_to_the_end_at_the_output_rate() {
+        let mut player = AudioFilePlayer::new(wav(48_000, 24_000), "wav", 44_100).unwrap();
+        assert_eq!(player.codec(), "WAV");
+        assert_eq!(player.source_rate(), 48_000);
+        assert!((player.duration_seconds() - 0.5).abs() < 1e-6);
+
+        player.play();
+        let mut rendered = 0;
+        let mut buffer = [0.0f32; 1000];
+        while player.state() == PlaybackState::Playing {
+            player.generate_samples_into(&mut buffer);
+            rendered += buffer.len();
+            assert!(rendered < 44_100, "never stopped");
+        }
+        // Half a second at 44.1 kHz, within the last block
+        assert!((22_050..23_050).contains(&rendered), "{rendered}");
+        assert!((player.elapsed_seconds() - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn stereo_output_keeps_channels_apart() {
+        let mut player = AudioFilePlayer::new(wav(44_100, 4410), "wav", 44_100).unwrap();
+        player.play();
+        let mut buffer = [0.0f32; 200];
+        player.generate_samples_into_stereo(&mut buffer);
+        assert!(buffer.chunks(2).skip(1).all(|f| f[0] > 0.0 && f[1] == 0.0));
+
+        player.set_gain(0.5);
+        let mut mono = [0.0f32; 1];
+        player.generate_samples_into(&mut mono);
+        // Frame 100 of the ramp, halved by the downmix and the gain
+        assert!(
+            (mono[0] - 1600.0 / 32768.0 / 4.0).abs() < 1e-6,
+            "{}",
+            mono[0]
+        );
+    }
+
+    #[test]
+    fn seek_and_stop_rewind() {
+        let mut player = AudioFilePlayer::new(wav(44_100, 44_100), "wav", 44_100).unwrap();
+        assert!(player.seek(0.5));
+        assert!((player.playback_position() - 0.5).abs() < 0.01);
+        player.stop();
+        assert_eq!(player.playback_position(), 0.0);
+    }
+
+    #[test]
+    fn rejects_other_data() {
+        assert!(AudioFilePlayer::new(b"YM6!LeOnArD!".to_vec(), "mp3", 44_100).is_err());
+        assert!(is_audio_file_path(Path::new("remix/Ashtray.FLAC")));
+        assert!(!is_audio_file_path(Path::new("Ashtray.ym")));
+    }
+}
@@ -0,0 +1,195 @@
+//! Commands and status shared between the UI and the audio producer thread.
+//!
+//! The producer thread owns the player. UI code sends [`PlayerCommand`]s over
+//! a single-producer, single-consumer queue and reads the [`PlayerStatus`]
+//! the producer publishes after each batch, so the UI never holds a lock the
+//! producer needs while generating samples. Volume and channel mutes skip the
+//! queue and go through the player's [`PlayerHandle`](ym2149_common::PlayerHandle).
+
+use crate::{RealtimeChip, VisualSnapshot};
+use ym2149_common::{AbMode, FxRack, OutputModel, PlaybackState, RegisterDelta};
+
+/// Request for the producer thread, applied before its next batch.
+pub enum PlayerCommand {
+    /// Pause when playing, otherwise start playback.
+    TogglePause,
+    /// Advance a number of frames while paused.
+    StepFrames(usize),
+    /// Seek forward (positive) or backward by a number of seconds.
+    SeekBy(f32),
+    /// Switch to the next subsong, wrapping to the first.
+    NextSubsong,
+    /// Switch to the previous subsong, wrapping to the last.
+    PreviousSubsong,
+    /// Set the playback rate of the current and all future players.
+    SetPlaybackRate(f32),
+    /// Set the output stage model of the current and all future players.
+    SetOutputModel(OutputModel),
+    /// Keep a per-channel tap of this capacity and publish it in the status.
+    SetChannelTap(Option<usize>),
+    /// Replace the insert effects (`None` = bypass).
+    SetFx(Option<FxRack>),
+    /// Select the output of an A/B comparison (kept for future players).
+    SetAbMode(AbMode),
+    /// Stop the current song and start playing a new player.
+    ReplacePlayer(Box<dyn RealtimeChip>),
+}
+
+/// Player state published by the producer thread after each batch.
+#[derive(Clone, Default)]
+pub struct PlayerStatus {
+    /// Number of players replaced so far; matches
+    /// [`StreamingContext::generation`](crate::streaming::StreamingContext::generation)
+    /// once the latest replacement was applied.
+    pub generation: u64,
+    /// Playback state.
+    pub state: PlaybackState,
+    /// Number of PSGs of the player.
+    pub psg_count: usize,
+    /// Number of channels of the player.
+    pub channel_count: usize,
+    /// Playback position (0.0 to 1.0).
+    pub position: f32,
+    /// Song duration in seconds (0.0 if unknown).
+    pub duration_seconds: f32,
+    /// Elapsed playback time in seconds.
+    pub elapsed_seconds: f32,
+    /// Current subsong and subsong count, for players with subsongs.
+    pub subsong: Option<(usize, usize)>,
+    /// Output of an A/B comparison, when comparing.
+    pub ab_mode: Option<AbMode>,
+    /// Chip state after the latest batch (not delayed to match the output).
+    pub snapshot: VisualSnapshot,
+    /// Registers changed by the latest frame step, until playback resumes.
+    pub last_step: Option<RegisterDelta>,
+    /// Latest channel tap samples, oldest first (empty unless requested).
+    pub taps: Vec<Vec<f32>>,
+}
+
+impl PlayerStatus {
+    /// Read everything except the taps from `player`.
+    pub fn capture(&mut self, player: &dyn RealtimeChip) {
+        self.state = player.state();
+        self.psg_count = player.psg_count();
+        self.channel_count = player.channel_count();
+        self.position = player.playback_position();
+        self.duration_seconds = player.duration_seconds();
+        self.elapsed_seconds = player.elapsed_seconds();
+        self.subsong = player
+            .has_subsongs()
+            .then(|| (player.current_subsong(), player.subsong_count()));
+        self.ab_mode = player.ab_mode();
+        self.snapshot = player.visual_snapshot();
+    }
+
+    /// Copy the player's channel taps, reusing the existing buffers.
+    pub fn capture_taps(&mut self, player: &dyn RealtimeChip, capacity: usize) {
+        self.taps.resize_with(self.channel_count, Vec::new);
+        for (ch, tap) in self.taps.iter_mut().enumerate() {
+            tap.resize(capacity, 0.0);
+            let count = player.read_channel_tap(ch, tap);
+            tap.truncate(count);
+        }
+    }
+}
+
+/// Seek by `seconds`, or by one percent per second when the duration is unknown.
+pub fn seek_by(player: &mut dyn RealtimeChip, seconds: f32) -> bool {
+    let duration = player.duration_seconds();
+    let amount = if duration > 0.0 {
+        seconds / duration
+    } else {
+        seconds / 100.0
+    };
+    let position = (player.playback_position() + amount).clamp(0.0, 1.0);
+    player.seek(position)
+}
+
+/// Switch subsong by one step forward or back, wrapping around (subsongs are 1-based).
+pub fn step_subsong(player: &mut dyn RealtimeChip, forward: bool) {
+    if !player.has_subsongs() {
+        return;
+    }
+    let current = player.current_subsong();
+    let count = player.subsong_count();
+    let index = match forward {
+        true if current >= count => 1,
+        true => current + 1,
+        false if current <= 1 => count,
+        false => current - 1,
+    };
+    player.set_subsong(index);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ym2149_common::ChiptunePlayerBase;
+
+    #[derive(Default)]
+    struct StubPlayer {
+        position: f32,
+        subsong: usize,
+    }
+
+    impl ChiptunePlayerBase for StubPlayer {
+        fn play(&mut self) {}
+        fn pause(&mut self) {}
+        fn stop(&mut self) {}
+        fn state(&self) -> PlaybackState {
+            PlaybackState::Playing
+        }
+        fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+            buffer.fill(0.0);
+        }
+        fn playback_position(&self) -> f32 {
+            self.position
+        }
+        fn seek(&mut self, position: f32) -> bool {
+            self.position = position;
+            true
+        }
+        fn duration_seconds(&self) -> f32 {
+            100.0
+        }
+        fn subsong_count(&self) -> usize {
+            3
+        }
+        fn current_subsong(&self) -> usize {
+            self.subsong
+        }
+        fn set_subsong(&mut self, index: usize) -> bool {
+            self.subsong = index;
+            true
+        }
+    }
+
+    impl RealtimeChip for StubPlayer {
+        fn visual_snapshot(&self) -> VisualSnapshot {
+            VisualSnapshot::default()
+        }
+        fn set_color_filter(&mut self, _enabled: bool) {}
+    }
+
+    #[test]
+    fn seek_and_subsong_steps_clamp_and_wrap() {
+        let mut player = StubPlayer {
+            position: 0.5,
+            subsong: 3,
+        };
+        assert!(seek_by(&mut player, 5.0));
+        assert!((player.position - 0.55).abs() < 1e-6);
+        seek_by(&mut player, -100.0);
+        assert_eq!(player.position, 0.0);
+
+        step_subsong(&mut player, true);
+        assert_eq!(player.subsong, 1);
+        step_subsong(&mut player, false);
+        assert_eq!(player.subsong, 3);
+
+        let mut status = PlayerStatus::default();
+        status.capture(&player);
+        assert_eq!(status.subsong, Some((3, 3)));
+        assert_eq!(status.duration_seconds, 100.0);
+    }
+}
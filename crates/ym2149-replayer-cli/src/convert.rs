@@ -0,0 +1,282 @@
+//! Batch conversion (`ym-replayer convert`).
+//!
+//! Scans a directory or ZIP archive with the playlist scanner, renders every
+//! song offline and encodes it through the `ym2149-ym-replayer` export
+//! module. Songs are rendered in parallel on a rayon thread pool; output
+//! names come from a template filled with each song's metadata.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::prelude::*;
+use ym2149_sndh_replayer::DurationProvider;
+use ym2149_ym_replayer::export::{ExportConfig, ExportFormat, SongTags, export_samples};
+
+use crate::args::{ChipChoice, ConvertArgs};
+use crate::audio::DEFAULT_SAMPLE_RATE;
+use crate::export::render_samples;
+use crate::player_factory::create_player;
+use crate::playlist::{Playlist, PlaylistEntry, is_archive_path};
+
+/// Output name template used when `--template` is not given.
+pub const DEFAULT_TEMPLATE: &str = "{name}";
+
+/// Placeholder value for missing metadata.
+const UNKNOWN: &str = "Unknown";
+
+/// One song to render and the file it is written to.
+struct Job<'a> {
+    entry: &'a PlaylistEntry,
+    output: PathBuf,
+}
+
+/// Run the `convert` subcommand.
+pub fn run(
+    args: &ConvertArgs,
+    sndh_durations: Option<&Arc<dyn DurationProvider>>,
+) -> ym2149_ym_replayer::Result<()> {
+    let Some(ref input) = args.input else {
+        return Err("convert requires an input directory or ZIP archive".into());
+    };
+    let input_path = Path::new(input);
+    let scanned = if input_path.is_file() && is_archive_path(input_path) {
+        Playlist::scan_archive(input_path)
+    } else {
+        Playlist::scan_directory(input_path)
+    };
+    let playlist = scanned.map_err(|e| format!("Failed to scan {input}: {e}"))?;
+    if playlist.is_empty() {
+        return Err(format!("No supported music files found in {input}").into());
+    }
+
+    let jobs = plan_jobs(
+        &playlist.entries,
+        Path::new(&args.output_dir),
+        &args.template,
+        args.format,
+    );
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(args.jobs.unwrap_or(0))
+        .build()
+        .map_err(|e| format!("Failed to start worker threads: {e}"))?;
+
+    let total = jobs.len();
+    let finished = AtomicUsize::new(0);
+    let failed = pool.install(|| {
+        jobs.par_iter()
+            .filter(|job| {
+                let result = convert_song(job, args, sndh_durations);
+                let done = finished.fetch_add(1, Ordering::Relaxed) + 1;
+                let source = job.entry.path.display();
+                match result {
+                    Ok(()) => {
+                        println!("[{done}/{total}] {source} -> {}", job.output.display());
+                        false
+                    }
+                    Err(e) => {
+                        eprintln!("[{done}/{total}] {source}: {e}");
+                        true
+                    }
+                }
+            })
+            .count()
+    });
+
+    println!("Converted {} of {total} songs", total - failed);
+    if failed > 0 {
+        return Err(format!("{failed} songs failed to convert").into());
+    }
+    Ok(())
+}
+
+/// Render one song and write it in the requested format.
+fn convert_song(
+    job: &Job<'_>,
+    args: &ConvertArgs,
+    sndh_durations: Option<&Arc<dyn DurationProvider>>,
+) -> ym2149_ym_replayer::Result<()> {
+    let path = job.entry.path.to_string_lossy();
+    let mut info = create_player(&path, ChipChoice::Ym2149, None, sndh_durations)?;
+    if let Some(reason) = info.player.unsupported_reason() {
+        return Err(reason.into());
+    }
+
+    let config = ExportConfig {
+        fade_out_duration: args.fade_secs,
+        loop_count: args.loops,
+        ..ExportConfig::default()
+    };
+    let length = config.render_length(info.total_samples, info.loop_start, DEFAULT_SAMPLE_RATE);
+    let mut samples = render_samples(info.player.as_mut(), length);
+    if let Some(parent) = job.output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tags = SongTags {
+        title: known(&info.title).to_string(),
+        artist: known(&info.author).to_string(),
+        comment: info.comment.trim().to_string(),
+        year: info.year.trim().to_string(),
+    };
+    export_samples(
+        &job.output,
+        &mut samples,
+        DEFAULT_SAMPLE_RATE,
+        args.format,
+        &config,
+        &tags,
+    )
+}
+
+/// Assign every entry a unique output path.
+///
+/// Songs that render to the same name get a ` (2)`, ` (3)`, ... suffix in
+/// playlist order.
+fn plan_jobs<'a>(
+    entries: &'a [PlaylistEntry],
+    output_dir: &Path,
+    template: &str,
+    format: ExportFormat,
+) -> Vec<Job<'a>> {
+    let mut taken = HashSet::new();
+    entries
+        .iter()
+        .map(|entry| {
+            let stem = fill_template(template, entry);
+            let mut output = output_dir.join(format!("{stem}.{}", format.extension()));
+            let mut copy = 1;
+            while !taken.insert(output.to_string_lossy().to_lowercase()) {
+                copy += 1;
+                output = output_dir.join(format!("{stem} ({copy}).{}", format.extension()));
+            }
+            Job { entry, output }
+        })
+        .collect()
+}
+
+/// Fill `{name}`, `{title}`, `{author}` and `{format}` in `template`.
+///
+/// Values are made safe for file names; `/` in the template itself starts a
+/// subdirectory. Empty results fall back to the source file name.
+fn fill_template(template: &str, entry: &PlaylistEntry) -> String {
+    let name = entry
+        .path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let title = match known(&entry.title) {
+        "" => name.as_str(),
+        title => title,
+    };
+    let author = match known(&entry.author) {
+        "" => UNKNOWN,
+        author => author,
+    };
+
+    let segments: Vec<String> = template
+        .split('/')
+        .map(|segment| {
+            let filled = segment
+                .replace("{name}", &sanitize(&name))
+                .replace("{title}", &sanitize(title))
+                .replace("{author}", &sanitize(author))
+                .replace("{format}", &sanitize(&entry.format));
+            filled.trim().trim_end_matches('.').to_string()
+        })
+        .filter(|segment| !segment.is_empty())
+        .collect();
+
+    if segments.is_empty() {
+        sanitize(&name)
+    } else {
+        segments.join("/")
+    }
+}
+
+/// Replace characters that are not allowed in file names.
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect::<String>()
+        .trim()
+        .trim_start_matches('.')
+        .to_string()
+}
+
+/// Metadata value, or an empty string for placeholders like "(unknown)".
+fn known(value: &str) -> &str {
+    let value = value.trim();
+    if value == "(unknown)" { "" } else { value }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, title: &str, author: &str) -> PlaylistEntry {
+        PlaylistEntry {
+            path: PathBuf::from(path),
+            title: title.to_string(),
+            author: author.to_string(),
+            duration_secs: None,
+            format: "YM6".to_string(),
+        }
+    }
+
+    #[test]
+    fn fills_template_from_metadata() {
+        let song = entry("music/steps.ym", "Steps", "Jess");
+
+        assert_eq!(fill_template(DEFAULT_TEMPLATE, &song), "steps");
+        assert_eq!(
+            fill_template("{author}/{title} [{format}]", &song),
+            "Jess/Steps [YM6]"
+        );
+    }
+
+    #[test]
+    fn template_values_cannot_escape_the_output_directory() {
+        let song = entry("x.ym", "../A/B: C?", "(unknown)");
+
+        assert_eq!(
+            fill_template("{author} - {title}", &song),
+            "Unknown - _A_B_ C_"
+        );
+        assert_eq!(fill_template("{title}/../{name}", &song), "_A_B_ C_/x");
+    }
+
+    #[test]
+    fn missing_title_falls_back_to_file_name() {
+        let song = entry("dir/zak.ym", "", "");
+
+        assert_eq!(fill_template("{title}", &song), "zak");
+        assert_eq!(fill_template("", &song), "zak");
+    }
+
+    #[test]
+    fn duplicate_names_get_a_suffix() {
+        let entries = [
+            entry("a/song.ym", "Song", "X"),
+            entry("b/song.ym", "Song", "X"),
+            entry("c/SONG.ym", "Song", "X"),
+        ];
+        let jobs = plan_jobs(&entries, Path::new("out"), "{name}", ExportFormat::Wav);
+        let outputs: Vec<_> = jobs.iter().map(|job| job.output.clone()).collect();
+
+        assert_eq!(
+            outputs,
+            [
+                PathBuf::from("out/song.wav"),
+                PathBuf::from("out/song (2).wav"),
+                PathBuf::from("out/SONG (3).wav"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Offline rendering and note export (`--export-notes`).
+//!
+//! Renders the song without audio output, samples the PSG registers once per
+//! frame and writes the extracted notes as JSON or MIDI (chosen by extension).
+//! [`render_samples`] renders the audio itself for `convert`.
+
+use std::path::Path;
+
+use ym2149_common::FRAME_RATE_PAL;
+use ym2149_common::export::{NoteRecorder, NoteTimeline, to_json, to_midi};
+
+use crate::RealtimeChip;
+use crate::audio::DEFAULT_SAMPLE_RATE;
+
+/// Length rendered when the song duration is unknown.
+const FALLBACK_EXPORT_SECONDS: usize = 300;
+
+/// Samples rendered per `generate_samples_into` call in [`render_samples`].
+const RENDER_CHUNK: usize = 4096;
+
+/// Song length in samples, falling back to [`FALLBACK_EXPORT_SECONDS`] when unknown.
+fn export_length(total_samples: usize) -> usize {
+    if total_samples == 0 {
+        FALLBACK_EXPORT_SECONDS * DEFAULT_SAMPLE_RATE as usize
+    } else {
+        total_samples
+    }
+}
+
+/// Render `player` to the end as mono samples at [`DEFAULT_SAMPLE_RATE`].
+pub fn render_samples(player: &mut dyn RealtimeChip, total_samples: usize) -> Vec<f32> {
+    let mut samples = vec![0.0f32; export_length(total_samples)];
+    player.play();
+    for chunk in samples.chunks_mut(RENDER_CHUNK) {
+        player.generate_samples_into(chunk);
+    }
+    samples
+}
+
+/// Render `player` to the end and record its note events.
+pub fn record_notes(player: &mut dyn RealtimeChip, total_samples: usize) -> NoteTimeline {
+    let samples_per_frame = (DEFAULT_SAMPLE_RATE / FRAME_RATE_PAL) as usize;
+    let total_samples = export_length(total_samples);
+    let frame_count = total_samples.div_ceil(samples_per_frame);
+
+    let mut recorder = NoteRecorder::new(FRAME_RATE_PAL);
+    let mut buffer = vec![0.0f32; samples_per_frame];
+    player.play();
+    for _ in 0..frame_count {
+        let snapshot = player.visual_snapshot();
+        let psg_count = snapshot.psg_count.clamp(1, snapshot.registers.len());
+        recorder.record_frame(&snapshot.registers[..psg_count]);
+        player.generate_samples_into(&mut buffer);
+    }
+    recorder.finish()
+}
+
+/// Write a timeline to `path`; `.mid`/`.midi` produce MIDI, anything else JSON.
+pub fn write_timeline(timeline: &NoteTimeline, path: &Path) -> std::io::Result<()> {
+    let is_midi = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("mid") || ext.eq_ignore_ascii_case("midi"));
+    if is_midi {
+        std::fs::write(path, to_midi(timeline))
+    } else {
+        std::fs::write(path, to_json(timeline))
+    }
+}
@@ -0,0 +1,153 @@
+//! Headless JSON status output (`--json-status`).
+//!
+//! Prints one JSON object per line to stdout while the song plays, so
+//! scripts, stream overlays and home-automation setups can follow playback:
+//!
+//! ```text
+//! {"state":"playing","title":"Scout","author":"Jochen Hippel","format":"YM6",
+//!  "elapsed":12.34,"duration":180.00,"position":0.069,"subsong":null,
+//!  "channels":[{"channel":0,"note":"A4","frequency":440.00,"volume":15,
+//!  "tone":true,"noise":false,"envelope":false,"muted":false},...]}
+//! ```
+//!
+//! (shown wrapped; each status is a single line). Channel data comes from the
+//! register snapshot delayed to match what is audible. Playback ends with
+//! the song.
+
+use std::fmt::Write as _;
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+use ym2149_common::{ChannelStates, PlaybackState};
+
+use crate::command::PlayerStatus;
+use crate::streaming::StreamingContext;
+use crate::tui::SongMetadata;
+
+/// Status lines per second when `--json-status` has no rate.
+pub const DEFAULT_JSON_STATUS_RATE: f32 = 10.0;
+
+/// Print status lines at `rate_hz` until the song ends or stdout closes.
+pub fn run(context: &StreamingContext, metadata: &SongMetadata, rate_hz: f32) {
+    let interval = Duration::from_secs_f32(1.0 / rate_hz);
+    let start = Instant::now();
+    let mut stdout = io::stdout().lock();
+    let mut line = String::with_capacity(1024);
+    // The status reads stopped until the producer thread has started the song
+    let mut started = false;
+
+    while context.running.load(std::sync::atomic::Ordering::Relaxed) {
+        let status = context.status();
+        started |= status.state != PlaybackState::Stopped;
+        // Songs of unknown length report wall-clock time
+        let elapsed = if status.duration_seconds > 0.0 {
+            status.elapsed_seconds
+        } else {
+            start.elapsed().as_secs_f32()
+        };
+        format_status(&mut line, context, &status, metadata, elapsed);
+        if writeln!(stdout, "{line}")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            // Reader went away (e.g. `| head`)
+            break;
+        }
+        if started && context.song_finished() {
+            break;
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+/// Format one status line (without newline) into `out`.
+fn format_status(
+    out: &mut String,
+    context: &StreamingContext,
+    status: &PlayerStatus,
+    metadata: &SongMetadata,
+    elapsed: f32,
+) {
+    out.clear();
+    let state = match status.state {
+        PlaybackState::Playing => "playing",
+        PlaybackState::Paused => "paused",
+        PlaybackState::Stopped => "stopped",
+    };
+    let _ = write!(out, "{{\"state\":\"{state}\",\"title\":");
+    push_json_string(out, &metadata.title);
+    out.push_str(",\"author\":");
+    push_json_string(out, &metadata.author);
+    out.push_str(",\"format\":");
+    push_json_string(out, &metadata.format);
+    let _ = write!(
+        out,
+        ",\"elapsed\":{elapsed:.2},\"duration\":{:.2},\"position\":{:.3},\"subsong\":",
+        status.duration_seconds, status.position
+    );
+    match status.subsong {
+        Some((current, count)) => {
+            let _ = write!(out, "{{\"current\":{current},\"count\":{count}}}");
+        }
+        None => out.push_str("null"),
+    }
+
+    out.push_str(",\"channels\":[");
+    let snapshot = context.get_delayed_snapshot();
+    let psg_count = status.psg_count.min(snapshot.registers.len());
+    for (psg, regs) in snapshot.registers[..psg_count].iter().enumerate() {
+        let states = ChannelStates::from_registers(regs);
+        for (local, ch) in states.channels.iter().enumerate() {
+            let channel = psg * 3 + local;
+            if channel > 0 {
+                out.push(',');
+            }
+            let _ = write!(out, "{{\"channel\":{channel},\"note\":");
+            match ch.note_name.filter(|_| ch.tone_enabled) {
+                Some(note) => push_json_string(out, note),
+                None => out.push_str("null"),
+            }
+            let _ = write!(
+                out,
+                ",\"frequency\":{:.2},\"volume\":{},\"tone\":{},\"noise\":{},\"envelope\":{},\"muted\":{}}}",
+                ch.frequency_hz.unwrap_or(0.0),
+                ch.amplitude,
+                ch.tone_enabled,
+                ch.noise_enabled,
+                ch.envelope_enabled,
+                context.controls.is_channel_muted(channel)
+            );
+        }
+    }
+    out.push_str("]}");
+}
+
+/// Append `value` as a quoted JSON string.
+fn push_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_json_strings() {
+        let mut out = String::new();
+        push_json_string(&mut out, "Mad \"Max\"\\\n\u{1}é");
+        assert_eq!(out, r#""Mad \"Max\"\\\n\u0001é""#);
+    }
+}
@@ -5,18 +5,27 @@
 //! - Terminal-based visualization
 //! - Interactive playback control
 //! - YM2149 hardware emulation
-//! - Directory playback with playlist selection
+//! - Directory and ZIP archive playback with playlist selection
 
+mod ab_compare;
+mod analyze;
 mod args;
 mod audio;
+#[cfg(feature = "audio-files")]
+mod audio_file;
+mod command;
+mod convert;
+mod export;
+mod json_status;
 mod player_factory;
 mod playlist;
+mod remote;
 mod streaming;
 mod tui;
 mod visualization;
 mod viz_helpers;
 
-use audio::{DEFAULT_SAMPLE_RATE, StreamConfig};
+use audio::{DEFAULT_SAMPLE_RATE, OutputTarget, StreamConfig};
 use parking_lot::Mutex;
 use std::path::Path;
 use std::sync::Arc;
@@ -24,15 +33,19 @@ use std::time::Instant;
 use ym2149::Ym2149Backend;
 use ym2149_arkos_replayer::ArkosPlayer;
 use ym2149_ay_replayer::{AyPlayer, CPC_UNSUPPORTED_MSG};
-use ym2149_common::ChiptunePlayerBase;
-use ym2149_sndh_replayer::SndhPlayer;
+use ym2149_common::{AbMode, ChiptunePlayerBase};
+use ym2149_kss_replayer::KssPlayer;
+use ym2149_sndh_replayer::{DurationProvider, SndhPlayer, TimingDatabase};
 use ym2149_ym_replayer::player::ym_player::YmPlayerGeneric;
 
 use args::CliArgs;
-use player_factory::{create_demo_player, create_player};
-use playlist::Playlist;
+use player_factory::{create_demo_player, create_player, load_sndh_durations, with_ab_compare};
+use playlist::{Playlist, is_archive_path};
+use remote::RemoteServer;
 use streaming::StreamingContext;
-use tui::{CaptureBuffer, SongMetadata, run_tui_loop_with_playlist, terminal_supports_tui};
+use tui::{
+    CaptureBuffer, SongMetadata, TuiConfig, run_tui_loop_with_playlist, terminal_supports_tui,
+};
 use visualization::run_visualization_loop;
 
 /// Maximum number of PSG chips supported for visualization.
@@ -129,6 +142,14 @@ pub trait RealtimeChip: ChiptunePlayerBase {
             buffer[i * 2 + 1] = sample; // Right
         }
     }
+
+    /// Current output of an A/B comparison (`None` when not comparing).
+    fn ab_mode(&self) -> Option<AbMode> {
+        None
+    }
+
+    /// Select the output of an A/B comparison; ignored when not comparing.
+    fn set_ab_mode(&mut self, _mode: AbMode) {}
 }
 
 impl<B: Ym2149Backend + 'static> RealtimeChip for YmPlayerGeneric<B> {
@@ -201,6 +222,39 @@ macro_rules! delegate_chiptune_player_base {
             fn psg_count(&self) -> usize {
                 ChiptunePlayerBase::psg_count(&self.$field)
             }
+            fn set_gain(&mut self, gain: f32) {
+                ChiptunePlayerBase::set_gain(&mut self.$field, gain);
+            }
+            fn gain(&self) -> f32 {
+                ChiptunePlayerBase::gain(&self.$field)
+            }
+            fn set_soft_clip(&mut self, enabled: bool) {
+                ChiptunePlayerBase::set_soft_clip(&mut self.$field, enabled);
+            }
+            fn soft_clip(&self) -> bool {
+                ChiptunePlayerBase::soft_clip(&self.$field)
+            }
+            fn set_playback_rate(&mut self, rate: f32) {
+                ChiptunePlayerBase::set_playback_rate(&mut self.$field, rate);
+            }
+            fn playback_rate(&self) -> f32 {
+                ChiptunePlayerBase::playback_rate(&self.$field)
+            }
+            fn step_frames(&mut self, frames: usize) -> Option<ym2149_common::RegisterDelta> {
+                ChiptunePlayerBase::step_frames(&mut self.$field, frames)
+            }
+            fn advance_frames(&mut self, frames: usize) -> usize {
+                ChiptunePlayerBase::advance_frames(&mut self.$field, frames)
+            }
+            fn set_output_model(&mut self, model: ym2149_common::OutputModel) {
+                ChiptunePlayerBase::set_output_model(&mut self.$field, model);
+            }
+            fn set_channel_tap(&mut self, capacity: Option<usize>) {
+                ChiptunePlayerBase::set_channel_tap(&mut self.$field, capacity);
+            }
+            fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+                ChiptunePlayerBase::read_channel_tap(&self.$field, channel, out)
+            }
         }
     };
 }
@@ -256,11 +310,16 @@ delegate_chiptune_player_base!(AyPlayerWrapper, player);
 
 impl RealtimeChip for AyPlayerWrapper {
     fn visual_snapshot(&self) -> VisualSnapshot {
+        let psg_count = self.player.psg_count().min(MAX_PSG_COUNT);
         let mut registers = [[0u8; 16]; MAX_PSG_COUNT];
-        registers[0] = self.player.chip().dump_registers();
+        for (i, reg) in registers.iter_mut().enumerate().take(psg_count) {
+            if let Some(chip) = self.player.psg(i) {
+                *reg = chip.dump_registers();
+            }
+        }
         VisualSnapshot {
             registers,
-            psg_count: 1,
+            psg_count,
             sync_buzzer: false,
             sid_active: [false; MAX_PSG_COUNT * 3],
             drum_active: [false; MAX_PSG_COUNT * 3],
@@ -278,6 +337,37 @@ impl RealtimeChip for AyPlayerWrapper {
     }
 }
 
+/// KSS player wrapper for CLI integration
+pub struct KssPlayerWrapper {
+    player: KssPlayer,
+}
+
+impl KssPlayerWrapper {
+    pub fn new(player: KssPlayer) -> Self {
+        Self { player }
+    }
+}
+
+delegate_chiptune_player_base!(KssPlayerWrapper, player);
+
+impl RealtimeChip for KssPlayerWrapper {
+    fn visual_snapshot(&self) -> VisualSnapshot {
+        let mut registers = [[0u8; 16]; MAX_PSG_COUNT];
+        registers[0] = self.player.chip().dump_registers();
+        VisualSnapshot {
+            registers,
+            psg_count: 1,
+            sync_buzzer: false,
+            sid_active: [false; MAX_PSG_COUNT * 3],
+            drum_active: [false; MAX_PSG_COUNT * 3],
+        }
+    }
+
+    fn set_color_filter(&mut self, _enabled: bool) {
+        // Not applicable for KSS (MSX PSG, no ST filter)
+    }
+}
+
 /// SNDH player wrapper for CLI integration
 pub struct SndhPlayerWrapper {
     player: SndhPlayer,
@@ -289,10 +379,16 @@ impl SndhPlayerWrapper {
         Self { player }
     }
 
-    /// Create a new SNDH player from raw file data
-    pub fn new(sndh_data: &[u8], sample_rate: u32) -> Result<Self, String> {
+    /// Create a new SNDH player from raw file data, taking subsong lengths
+    /// from `durations` before the file's own tags
+    pub fn new(
+        sndh_data: &[u8],
+        sample_rate: u32,
+        durations: Option<Arc<dyn DurationProvider>>,
+    ) -> Result<Self, String> {
         let mut player = SndhPlayer::new(sndh_data, sample_rate)
             .map_err(|e| format!("SNDH player init failed: {e}"))?;
+        player.set_duration_provider(durations);
 
         // Initialize first subsong
         player
@@ -307,6 +403,25 @@ impl SndhPlayerWrapper {
         use ym2149_common::ChiptunePlayer;
         ChiptunePlayer::metadata(&self.player)
     }
+
+    /// Format summary of the loaded file
+    pub fn load_report(&self) -> ym2149_common::LoadReport {
+        use ym2149_common::ChiptunePlayer;
+        self.player.load_report()
+    }
+
+    /// Year of the current subsong (empty if the file has no YEAR tag)
+    pub fn year(&self) -> String {
+        self.player
+            .get_subsong_info(self.player.current_subsong())
+            .and_then(|info| info.year)
+            .unwrap_or_default()
+    }
+
+    /// Key of this file in an SNDH timing database
+    pub fn timing_key(&self) -> String {
+        TimingDatabase::key(self.player.sndh_file())
+    }
 }
 
 delegate_chiptune_player_base!(SndhPlayerWrapper, player);
@@ -339,48 +454,113 @@ fn main() -> ym2149_ym_replayer::Result<()> {
     // Parse command-line arguments
     let args = CliArgs::parse();
 
-    // Check if we'll use TUI mode upfront (to suppress unnecessary output)
-    let will_use_tui = terminal_supports_tui();
+    if let Some(ref convert) = args.convert
+        && !args.show_help
+    {
+        let durations = args
+            .sndh_durations
+            .as_deref()
+            .map(load_sndh_durations)
+            .transpose()?;
+        return convert::run(convert, durations.as_ref());
+    }
+
+    if let Some(ref analyze) = args.analyze
+        && !args.show_help
+    {
+        return analyze::run(analyze);
+    }
+
+    // Check if we'll use TUI mode upfront (to suppress unnecessary output);
+    // JSON status mode keeps stdout for the status lines
+    let pcm_output = matches!(args.output, OutputTarget::Stdout(_));
+    if pcm_output && args.json_status.is_some() {
+        return Err("--json-status and --output stdout both write to stdout".into());
+    }
+    let will_use_tui = args.json_status.is_none() && !pcm_output && terminal_supports_tui();
+    let quiet = will_use_tui || args.json_status.is_some() || pcm_output;
 
-    if !will_use_tui {
+    if !quiet {
         println!("YM2149 PSG Emulator - Real-time Streaming Playback");
         println!("===================================================\n");
     }
 
     if args.show_help {
         CliArgs::print_help();
-        return if args.file_path.is_none() {
+        let has_input = args.file_path.is_some()
+            || args.convert.as_ref().is_some_and(|c| c.input.is_some())
+            || args.analyze.as_ref().is_some_and(|a| a.input.is_some());
+        return if !has_input {
             Ok(())
         } else {
             Err("Invalid arguments".into())
         };
     }
 
-    // Check if input is a directory
-    let is_directory = args
+    let sndh_durations = args
+        .sndh_durations
+        .as_deref()
+        .map(load_sndh_durations)
+        .transpose()?;
+
+    if let Some(ref out_path) = args.export_notes {
+        let Some(ref file_path) = args.file_path else {
+            return Err("--export-notes requires an input file".into());
+        };
+        let mut player_info = create_player(
+            file_path,
+            args.chip_choice,
+            args.color_filter_override,
+            sndh_durations.as_ref(),
+        )?;
+        let timeline = export::record_notes(player_info.player.as_mut(), player_info.total_samples);
+        export::write_timeline(&timeline, Path::new(out_path))
+            .map_err(|e| format!("Failed to write {out_path}: {e}"))?;
+        println!(
+            "Exported {} notes ({} frames) to {out_path}",
+            timeline.events.len(),
+            timeline.frame_count
+        );
+        return Ok(());
+    }
+
+    // Check if input is a directory or a ZIP archive (both open as a playlist)
+    let is_archive = args
         .file_path
         .as_ref()
-        .map(|p| Path::new(p).is_dir())
+        .map(|p| Path::new(p).is_file() && is_archive_path(Path::new(p)))
         .unwrap_or(false);
+    let is_directory = is_archive
+        || args
+            .file_path
+            .as_ref()
+            .map(|p| Path::new(p).is_dir())
+            .unwrap_or(false);
 
     // Load playlist if directory mode
     let playlist = if is_directory {
         let path = Path::new(args.file_path.as_ref().unwrap());
-        if !will_use_tui {
-            println!("Scanning directory: {}\n", path.display());
+        let source = if is_archive { "archive" } else { "directory" };
+        if !quiet {
+            println!("Scanning {source}: {}\n", path.display());
         }
-        match Playlist::scan_directory(path) {
+        let scanned = if is_archive {
+            Playlist::scan_archive(path)
+        } else {
+            Playlist::scan_directory(path)
+        };
+        match scanned {
             Ok(pl) if !pl.is_empty() => {
-                if !will_use_tui {
+                if !quiet {
                     println!("Found {} songs\n", pl.len());
                 }
                 Some(pl)
             }
             Ok(_) => {
-                return Err("No supported music files found in directory".into());
+                return Err(format!("No supported music files found in {source}").into());
             }
             Err(e) => {
-                return Err(format!("Failed to scan directory: {e}").into());
+                return Err(format!("Failed to scan {source}: {e}").into());
             }
         }
     } else {
@@ -398,29 +578,56 @@ fn main() -> ym2149_ym_replayer::Result<()> {
     };
 
     // Create player instance
-    let player_info = match initial_file {
-        Some(ref file_path) => {
-            create_player(file_path, args.chip_choice, args.color_filter_override)?
-        }
+    let mut player_info = match initial_file {
+        Some(ref file_path) => create_player(
+            file_path,
+            args.chip_choice,
+            args.color_filter_override,
+            sndh_durations.as_ref(),
+        )?,
         None => create_demo_player(args.chip_choice)?,
     };
+    if let Some(kind) = args.ab {
+        let Some(ref file_path) = initial_file else {
+            return Err("--ab needs a file to compare".into());
+        };
+        player_info = with_ab_compare(
+            player_info,
+            file_path,
+            kind,
+            args.ab_mode,
+            args.chip_choice,
+            args.color_filter_override,
+            sndh_durations.as_ref(),
+        )?;
+    }
 
     // Display file information (only in non-TUI mode)
-    if !will_use_tui {
+    if !quiet {
         println!("File Information:");
         println!("{}\n", player_info.song_info);
         println!("Selected Chip: {}\n", args.chip_choice);
     }
 
-    // Configure streaming
-    let config = StreamConfig::low_latency(DEFAULT_SAMPLE_RATE);
-    if !will_use_tui {
+    // Configure streaming: start with low latency, grow on repeated underruns
+    let mut config = StreamConfig::adaptive(DEFAULT_SAMPLE_RATE);
+    config.set_output(args.output);
+    if let OutputTarget::Stdout(format) = config.output {
+        eprintln!(
+            "Writing {} PCM to stdout: {} Hz, {} channels",
+            format.as_str(),
+            config.sample_rate,
+            config.channels
+        );
+    }
+    if !quiet {
         println!("Streaming Configuration:");
         println!("  Sample rate: {} Hz", config.sample_rate);
         println!(
-            "  Buffer size: {} samples ({:.1}ms latency)",
+            "  Buffer size: {} samples ({:.1}ms latency, adapts up to {})",
             config.ring_buffer_size,
-            config.latency_ms()
+            config.latency_ms(),
+            config.max_ring_buffer_size
         );
         println!("  Total samples: {}\n", player_info.total_samples);
     }
@@ -428,6 +635,26 @@ fn main() -> ym2149_ym_replayer::Result<()> {
     // Use TUI mode (already determined above)
     let use_tui = will_use_tui;
 
+    // Remote control server (drives the TUI)
+    let remote = match args.remote {
+        Some(ref addr) if use_tui => match RemoteServer::bind(addr) {
+            Ok(server) => {
+                println!(
+                    "Remote control on {} (token: {})",
+                    server.local_addr(),
+                    server.token()
+                );
+                Some(server)
+            }
+            Err(e) => return Err(format!("Failed to start remote control on {addr}: {e}").into()),
+        },
+        Some(_) => {
+            eprintln!("--remote is only available in the TUI; ignoring it");
+            None
+        }
+        None => None,
+    };
+
     // Extract metadata before moving player_info
     let song_metadata = SongMetadata {
         title: player_info.title.clone(),
@@ -461,14 +688,51 @@ fn main() -> ym2149_ym_replayer::Result<()> {
     } else {
         StreamingContext::start(player_info.player, config, player_info.color_filter)?
     };
+    if args.output_model != ym2149_common::OutputModel::default() {
+        context.set_output_model(args.output_model);
+    }
+    if !args.fx.is_empty() {
+        context.set_fx(&args.fx);
+    }
+    if args.playback_rate != 1.0 {
+        context.set_playback_rate(args.playback_rate);
+    }
+    if args.json_status.is_some() {
+        // Status lines own stdout; report dropouts on stderr
+        context.on_underrun(|event| {
+            eprintln!(
+                "Audio underrun #{} (rendering {} batches ahead, buffer {} samples)",
+                event.count, event.batches_per_cycle, event.buffer_size
+            );
+        });
+    }
 
-    // Create player loader closure for song switching
+    // Create player loader closure for song switching (playlist and remote `load`)
     let chip_choice = args.chip_choice;
     let color_filter_override = args.color_filter_override;
-    let player_loader: Option<tui::PlayerLoader> = if is_directory {
+    let (ab, ab_mode) = (args.ab, args.ab_mode);
+    let player_loader: Option<tui::PlayerLoader> = if is_directory || remote.is_some() {
         Some(Box::new(move |path: &std::path::Path| {
             let path_str = path.to_string_lossy().to_string();
-            match create_player(&path_str, chip_choice, color_filter_override) {
+            let loaded = create_player(
+                &path_str,
+                chip_choice,
+                color_filter_override,
+                sndh_durations.as_ref(),
+            )
+            .and_then(|info| match ab {
+                Some(kind) => with_ab_compare(
+                    info,
+                    &path_str,
+                    kind,
+                    ab_mode,
+                    chip_choice,
+                    color_filter_override,
+                    sndh_durations.as_ref(),
+                ),
+                None => Ok(info),
+            });
+            match loaded {
                 Ok(info) => Some((
                     info.player,
                     SongMetadata {
@@ -488,8 +752,10 @@ fn main() -> ym2149_ym_replayer::Result<()> {
         None
     };
 
-    // Run visualization loop (TUI or classic)
-    if use_tui
+    // Run visualization loop (JSON status, TUI or classic)
+    if let Some(rate) = args.json_status {
+        json_status::run(&context, &song_metadata, rate);
+    } else if use_tui
         && let Some(ref capture) = context.capture
         && let Err(e) = run_tui_loop_with_playlist(
             &context,
@@ -497,9 +763,13 @@ fn main() -> ym2149_ym_replayer::Result<()> {
             song_metadata,
             playlist,
             player_loader,
+            TuiConfig::load(args.tui_config.as_deref()),
+            remote,
         )
     {
         eprintln!("TUI error: {e}");
+    } else if pcm_output {
+        context.wait_until_finished();
     } else if !use_tui {
         run_visualization_loop(&context);
     }
@@ -509,16 +779,21 @@ fn main() -> ym2149_ym_replayer::Result<()> {
     let final_stats = context.streamer.get_stats();
     context.shutdown();
 
-    // Only print stats if not using TUI (TUI already shows them)
-    if !use_tui {
+    // Only print stats in classic mode (TUI already shows them)
+    if !quiet {
         println!("\n=== Playback Statistics ===");
         println!("Duration:          {:.2} seconds", total_time.as_secs_f32());
         println!("Samples played:    {}", final_stats.samples_played);
         println!("Overrun events:    {}", final_stats.overrun_count);
-        println!("Buffer latency:    {:.1} ms", config.latency_ms());
+        println!("Underrun events:   {}", final_stats.underrun_count);
+        println!(
+            "Buffer size:       {} samples ({:.1} ms)",
+            final_stats.buffer_size,
+            final_stats.buffer_size as f32 / config.sample_rate as f32 * 1000.0
+        );
         println!(
             "Memory used:       {} bytes (ring buffer)",
-            config.ring_buffer_size * std::mem::size_of::<f32>()
+            config.max_ring_buffer_size.next_power_of_two() * std::mem::size_of::<f32>()
         );
         println!("\nPlayback complete!");
     }
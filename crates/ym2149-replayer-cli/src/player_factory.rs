@@ -5,18 +5,30 @@
 //! - Creating appropriate player instances
 //! - Setting up demo mode when no file is provided
 //! - Configuring chip-specific settings
+//! - Pairing a player with a second rendering for A/B comparison
 
 use crate::audio::DEFAULT_SAMPLE_RATE;
+use crate::playlist::read_song_data;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 use ym2149::Ym2149Backend;
 use ym2149_arkos_replayer::{ArkosPlayer, load_aks};
 use ym2149_ay_replayer::{AyPlayer, CPC_UNSUPPORTED_MSG};
-use ym2149_sndh_replayer::is_sndh_data;
+use ym2149_common::{AbMode, ChiptuneFormat, ChiptunePlayer, MetadataFields, detect_format};
+use ym2149_kss_replayer::KssPlayer;
+use ym2149_sndh_replayer::{DurationProvider, TimingDatabase};
+#[cfg(feature = "softsynth")]
+use ym2149_softsynth::SoftSynth;
+#[cfg(feature = "softsynth")]
+use ym2149_ym_replayer::player::ym_player::YmPlayerGeneric;
 use ym2149_ym_replayer::{Player, load_song};
 
-use crate::args::ChipChoice;
-use crate::{ArkosPlayerWrapper, AyPlayerWrapper, RealtimeChip, SndhPlayerWrapper};
+use crate::ab_compare::AbPlayer;
+use crate::args::{AbKind, ChipChoice};
+use crate::{
+    ArkosPlayerWrapper, AyPlayerWrapper, KssPlayerWrapper, RealtimeChip, SndhPlayerWrapper,
+};
 
 /// Information about a loaded player.
 pub struct PlayerInfo {
@@ -24,6 +36,8 @@ pub struct PlayerInfo {
     pub player: Box<dyn RealtimeChip>,
     /// Total samples in the song
     pub total_samples: usize,
+    /// Sample the song loops back to (None if unknown or not looping)
+    pub loop_start: Option<usize>,
     /// Human-readable song information
     pub song_info: String,
     /// Whether to run the ST-style post filter
@@ -32,6 +46,10 @@ pub struct PlayerInfo {
     pub title: String,
     /// Song author/composer
     pub author: String,
+    /// Song comment (empty if the format has none)
+    pub comment: String,
+    /// Release year (empty if unknown)
+    pub year: String,
     /// File format (YM5, SNDH, AKS, etc.)
     pub format: String,
 }
@@ -55,60 +73,77 @@ fn load_arkos_file(
     }
 
     // Extract metadata before moving song into player
-    let estimated_duration = subsong.end_position as f32 / subsong.replay_frequency_hz;
-    let total_samples = (estimated_duration * DEFAULT_SAMPLE_RATE as f32) as usize;
-
-    let info_str = format!(
-        "File: {}\nFormat: Arkos Tracker 3 (AKS)\n\
-         Title: {}\nAuthor: {}\nComposer: {}\n\
-         Subsongs: {}\nInstruments: {}\n\
-         PSG Count: {} ({} channels)\n\
-         Tracks: {}\nReplay Freq: {} Hz",
-        file_path,
-        song.metadata.title,
-        song.metadata.author,
-        if song.metadata.composer.is_empty() {
-            "-"
-        } else {
-            &song.metadata.composer
-        },
-        song.subsongs.len(),
-        song.instruments.len(),
-        subsong.psgs.len(),
-        subsong.psgs.len() * 3,
-        subsong.tracks.len(),
-        subsong.replay_frequency_hz,
+    let loop_lines: usize = subsong
+        .positions
+        .iter()
+        .take(subsong.loop_start_position)
+        .map(|pos| pos.height)
+        .sum();
+    let loop_ticks = loop_lines.saturating_mul(subsong.initial_speed.max(1) as usize);
+    let loop_start = Some(
+        (loop_ticks as f32 / subsong.replay_frequency_hz * DEFAULT_SAMPLE_RATE as f32) as usize,
     );
 
     // Extract title/author before creating player (song still available here)
     let title = song.metadata.title.clone();
     let author = song.metadata.author.clone();
+    let comment = song.metadata.comments.clone();
+    let year = song.metadata.creation_date.chars().take(4).collect();
+    let composer = if song.metadata.composer.is_empty() {
+        "-".to_string()
+    } else {
+        song.metadata.composer.clone()
+    };
+    let instruments = song.instruments.len();
+    let tracks = subsong.tracks.len();
 
     // Create player - song is moved, player owns Arc<AksSong>
     let player =
         ArkosPlayer::new(song, 0).map_err(|e| format!("Failed to create Arkos player: {e}"))?;
 
+    let info_str = format!(
+        "File: {file_path}\nFormat: {}\n\
+         Title: {title}\nAuthor: {author}\nComposer: {composer}\n\
+         Instruments: {instruments}\nTracks: {tracks}",
+        player.load_report(),
+    );
+
+    // One tick per replay period, lines at the subsong's initial speed
+    let estimated_duration = player.estimated_total_ticks() as f32 / player.replay_frequency_hz();
+    let total_samples = (estimated_duration * DEFAULT_SAMPLE_RATE as f32) as usize;
+
     let color_filter = color_filter_override.unwrap_or(true);
 
     Ok(PlayerInfo {
         player: Box::new(ArkosPlayerWrapper::new(player)) as Box<dyn RealtimeChip>,
         total_samples,
+        loop_start,
         song_info: info_str,
         color_filter,
         title,
         author,
+        comment,
+        year,
         format: "Arkos Tracker 3 (AKS)".to_string(),
     })
 }
 
+/// Load an SNDH timing database (`--sndh-durations`) to share between players.
+pub fn load_sndh_durations(path: &str) -> ym2149_ym_replayer::Result<Arc<dyn DurationProvider>> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Failed to read '{path}': {e}"))?;
+    let database = TimingDatabase::parse(&text).map_err(|e| format!("{path}: {e}"))?;
+    Ok(Arc::new(database))
+}
+
 /// Load an SNDH (Atari ST) file using ym2149-sndh-replayer for accurate playback.
 fn load_sndh_file(
     file_data: &[u8],
     file_path: &str,
     color_filter_override: Option<bool>,
+    durations: Option<&Arc<dyn DurationProvider>>,
 ) -> ym2149_ym_replayer::Result<PlayerInfo> {
     // Create player using ym2149-sndh-replayer (handles ICE! decompression internally)
-    let player = SndhPlayerWrapper::new(file_data, DEFAULT_SAMPLE_RATE)
+    let player = SndhPlayerWrapper::new(file_data, DEFAULT_SAMPLE_RATE, durations.cloned())
         .map_err(|e| format!("SNDH player init failed: {e}"))?;
 
     // Get metadata from the player (which already parsed the SNDH file)
@@ -123,7 +158,7 @@ fn load_sndh_file(
     } else {
         metadata.author.to_string()
     };
-    let player_rate = metadata.frame_rate;
+    let year = player.year();
 
     // Get duration from FRMS/TIME metadata (use trait method)
     use ym2149_common::ChiptunePlayerBase;
@@ -143,8 +178,10 @@ fn load_sndh_file(
         "unknown".to_string()
     };
 
+    let timing_key = player.timing_key();
+    let report = player.load_report();
     let info_str = format!(
-        "File: {file_path}\nFormat: SNDH (Atari ST)\nTitle: {title}\nAuthor: {author}\nPlayer rate: {player_rate} Hz\nDuration: {duration_str}"
+        "File: {file_path}\nFormat: {report}\nTitle: {title}\nAuthor: {author}\nDuration: {duration_str}\nTiming key: {timing_key}"
     );
 
     let color_filter = color_filter_override.unwrap_or(false);
@@ -152,10 +189,13 @@ fn load_sndh_file(
     Ok(PlayerInfo {
         player: Box::new(player) as Box<dyn RealtimeChip>,
         total_samples,
+        loop_start: None,
         song_info: info_str,
         color_filter,
         title,
         author,
+        comment: String::new(),
+        year,
         format: "SNDH (Atari ST)".to_string(),
     })
 }
@@ -184,8 +224,9 @@ fn load_ay_file(
         .unwrap_or(DEFAULT_SAMPLE_RATE as usize * 180);
 
     let info_str = format!(
-        "File: {}\nFormat: AY/EMUL\nTitle: {}\nAuthor: {}\nSongs: {}/{}\nFrame length: {}\n",
+        "File: {}\nFormat: {}\nTitle: {}\nAuthor: {}\nSong: {}/{}\n",
         file_path,
+        player.load_report(),
         metadata.song_name,
         if metadata.author.is_empty() {
             "(unknown)"
@@ -194,10 +235,6 @@ fn load_ay_file(
         },
         metadata.song_index + 1,
         metadata.song_count,
-        metadata
-            .frame_count
-            .map(|f| f.to_string())
-            .unwrap_or_else(|| "unknown".into()),
     );
 
     let color_filter = color_filter_override.unwrap_or(true);
@@ -205,17 +242,101 @@ fn load_ay_file(
     Ok(PlayerInfo {
         player: Box::new(AyPlayerWrapper::new(player)) as Box<dyn RealtimeChip>,
         total_samples,
+        loop_start: None,
         song_info: info_str,
         color_filter,
         title: metadata.song_name.clone(),
         author: metadata.author.clone(),
+        comment: metadata.misc.clone(),
+        year: String::new(),
         format: "AY/EMUL".to_string(),
     })
 }
 
+/// Load a KSS (MSX) file.
+fn load_kss_file(file_data: &[u8], file_path: &str) -> ym2149_ym_replayer::Result<PlayerInfo> {
+    let player =
+        KssPlayer::load_from_bytes(file_data, 0).map_err(|e| format!("KSS load failed: {e}"))?;
+
+    let silent = player.silent_chips();
+    let info_str = format!(
+        "File: {file_path}\nFormat: {}\nTrack: {}/{}\nSilent chips: {}",
+        player.load_report(),
+        player.track_index() + 1,
+        player.file().track_count(),
+        if silent.is_empty() {
+            "none".to_string()
+        } else {
+            silent.join(", ")
+        },
+    );
+    let title = player.metadata().title.clone();
+    let format = format!("KSS ({})", player.file().header.variant.name());
+
+    Ok(PlayerInfo {
+        player: Box::new(KssPlayerWrapper::new(player)) as Box<dyn RealtimeChip>,
+        // KSS files carry no length; play for 3 minutes like other open-ended formats
+        total_samples: DEFAULT_SAMPLE_RATE as usize * 180,
+        loop_start: None,
+        song_info: info_str,
+        color_filter: false,
+        title,
+        author: String::new(),
+        comment: String::new(),
+        year: String::new(),
+        format,
+    })
+}
+
+/// Load an MP3/FLAC/Ogg/WAV file to play between chiptunes.
+#[cfg(feature = "audio-files")]
+fn load_audio_file(
+    file_data: Vec<u8>,
+    file_path: &str,
+    extension: &str,
+) -> ym2149_ym_replayer::Result<PlayerInfo> {
+    use crate::audio_file::AudioFilePlayer;
+    use ym2149_common::ChiptunePlayerBase;
+
+    let player = AudioFilePlayer::new(file_data, extension, DEFAULT_SAMPLE_RATE)?;
+    let tags = player.tags().clone();
+
+    let duration_secs = player.duration_seconds();
+    let total_samples = if duration_secs > 0.0 {
+        (duration_secs * DEFAULT_SAMPLE_RATE as f32) as usize
+    } else {
+        // Fallback: 3 minutes if the container has no length
+        DEFAULT_SAMPLE_RATE as usize * 180
+    };
+
+    let info_str = format!(
+        "File: {file_path}\nFormat: {} audio, {} Hz\nTitle: {}\nArtist: {}",
+        player.codec(),
+        player.source_rate(),
+        tags.title,
+        tags.artist,
+    );
+    let format = player.codec().to_string();
+
+    Ok(PlayerInfo {
+        player: Box::new(player) as Box<dyn RealtimeChip>,
+        total_samples,
+        loop_start: None,
+        song_info: info_str,
+        color_filter: false,
+        title: tags.title,
+        author: tags.artist,
+        comment: tags.comment,
+        year: tags.year,
+        format,
+    })
+}
+
 /// Create a player instance from a file path.
 ///
-/// Loads the YM file, detects its format, and creates an appropriate player.
+/// Loads the file, detects its format, and creates an appropriate player
+/// (with the `audio-files` feature also for MP3/FLAC/Ogg/WAV files).
+/// Paths may point into a ZIP archive (`archive.zip/entry.ym`).
 ///
 /// # Arguments
 /// * `file_path` - Path to the YM file
@@ -228,30 +349,46 @@ pub fn create_player(
     file_path: &str,
     chip_choice: ChipChoice,
     color_filter_override: Option<bool>,
+    sndh_durations: Option<&Arc<dyn DurationProvider>>,
 ) -> ym2149_ym_replayer::Result<PlayerInfo> {
     // Note: No println! here - TUI mode handles its own display
+    let path = Path::new(file_path);
     let file_data =
-        fs::read(file_path).map_err(|e| format!("Failed to read file '{file_path}': {e}"))?;
+        read_song_data(path).map_err(|e| format!("Failed to read file '{file_path}': {e}"))?;
 
     // Check file extension
-    let path = Path::new(file_path);
     let extension = path
         .extension()
         .and_then(|ext| ext.to_str())
         .map(|ext| ext.to_ascii_lowercase())
         .unwrap_or_default();
 
+    #[cfg(feature = "audio-files")]
+    if crate::audio_file::AUDIO_FILE_EXTENSIONS.contains(&extension.as_str()) {
+        return load_audio_file(file_data, file_path, &extension);
+    }
+
     if extension == "aks" {
         return load_arkos_file(&file_data, file_path, chip_choice, color_filter_override);
     } else if extension == "ay" {
         return load_ay_file(&file_data, file_path, color_filter_override);
     } else if extension == "sndh" {
-        return load_sndh_file(&file_data, file_path, color_filter_override);
+        return load_sndh_file(&file_data, file_path, color_filter_override, sndh_durations);
+    } else if extension == "kss" {
+        return load_kss_file(&file_data, file_path);
     }
 
-    // Header-based detection for SNDH data even if the extension is missing
-    if is_sndh_data(&file_data) {
-        return load_sndh_file(&file_data, file_path, color_filter_override);
+    // Header-based detection for files with a missing or unknown extension
+    match detect_format(&file_data).format {
+        ChiptuneFormat::Sndh => {
+            return load_sndh_file(&file_data, file_path, color_filter_override, sndh_durations);
+        }
+        ChiptuneFormat::Ay => return load_ay_file(&file_data, file_path, color_filter_override),
+        ChiptuneFormat::Arkos => {
+            return load_arkos_file(&file_data, file_path, chip_choice, color_filter_override);
+        }
+        ChiptuneFormat::Kss => return load_kss_file(&file_data, file_path),
+        ChiptuneFormat::Ym | ChiptuneFormat::Zip | ChiptuneFormat::Unknown => {}
     }
 
     let (mut ym_player, summary) = load_song(&file_data)?;
@@ -264,34 +401,90 @@ pub fn create_player(
             }
 
             // Extract metadata
-            let (title, author) = if let Some(info) = ym_player.info() {
-                (info.song_name.clone(), info.author.clone())
+            let (title, author, comment) = if let Some(info) = ym_player.info() {
+                (
+                    info.song_name.clone(),
+                    info.author.clone(),
+                    info.comment.clone(),
+                )
             } else {
-                (String::new(), String::new())
+                (String::new(), String::new(), String::new())
             };
 
             let info_str = format!(
-                "File: {} ({})\n{}",
+                "File: {}\nFormat: {}\n{}",
                 file_path,
-                summary.format,
+                ChiptunePlayer::load_report(&ym_player),
                 ym_player.format_info()
             );
 
             let total_samples = summary.total_samples();
+            let loop_start = ChiptunePlayer::metadata(&ym_player)
+                .loop_frame()
+                .map(|frame| frame * summary.samples_per_frame as usize);
 
             Ok(PlayerInfo {
                 player: Box::new(ym_player) as Box<dyn RealtimeChip>,
                 total_samples,
+                loop_start,
                 song_info: info_str,
                 color_filter,
                 title,
                 author,
+                comment,
+                year: String::new(),
                 format: summary.format.to_string(),
             })
         }
     }
 }
 
+/// Pair a loaded player with a second rendering of the same file.
+///
+/// The original player becomes side A; B is the file on the SoftSynth
+/// backend (YM only) or the same player through another output model.
+pub fn with_ab_compare(
+    info: PlayerInfo,
+    file_path: &str,
+    kind: AbKind,
+    mode: AbMode,
+    chip_choice: ChipChoice,
+    color_filter_override: Option<bool>,
+    sndh_durations: Option<&Arc<dyn DurationProvider>>,
+) -> ym2149_ym_replayer::Result<PlayerInfo> {
+    let (b, b_output_model) = match kind {
+        #[cfg(feature = "softsynth")]
+        AbKind::SoftSynth => {
+            let data = read_song_data(Path::new(file_path))
+                .map_err(|e| format!("Failed to read file '{file_path}': {e}"))?;
+            let mut player = YmPlayerGeneric::<SoftSynth>::new();
+            player
+                .load_data(&data)
+                .map_err(|e| format!("A/B against softsynth needs a YM file: {e}"))?;
+            if let Some(cf) = color_filter_override {
+                player.set_color_filter(cf);
+            }
+            (Box::new(player) as Box<dyn RealtimeChip>, None)
+        }
+        AbKind::OutputModel(model) => {
+            let b = create_player(
+                file_path,
+                chip_choice,
+                color_filter_override,
+                sndh_durations,
+            )?;
+            (b.player, Some(model))
+        }
+    };
+
+    let song_info = format!("{}\nA/B: {kind} as B", info.song_info);
+    Ok(PlayerInfo {
+        player: Box::new(AbPlayer::new(info.player, b, mode, b_output_model)),
+        song_info,
+        ..info
+    })
+}
+
 /// Create a demo player with silence when no file is provided.
 ///
 /// # Arguments
@@ -314,10 +507,13 @@ pub fn create_demo_player(chip_choice: ChipChoice) -> ym2149_ym_replayer::Result
             Ok(PlayerInfo {
                 player: Box::new(demo_player) as Box<dyn RealtimeChip>,
                 total_samples,
+                loop_start: None,
                 song_info: info_str,
                 color_filter: true,
                 title: "Demo Mode".to_string(),
                 author: String::new(),
+                comment: String::new(),
+                year: String::new(),
                 format: "Demo".to_string(),
             })
         }
@@ -2,19 +2,30 @@
 //!
 //! This module provides:
 //! - Recursive directory scanning for music files
+//! - ZIP archive scanning (entries become playlist items)
 //! - Metadata extraction for playlist display
 //! - Song selection and loading
+//!
+//! Songs inside a ZIP archive are addressed by a virtual path made of the
+//! archive path followed by the entry name (e.g. `dumps/mad_max.zip/Lethal.ym`);
+//! [`read_song_data`] resolves such paths back to the entry contents.
 
 use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
 use ym2149_arkos_replayer::load_aks;
 use ym2149_ay_replayer::AyPlayer;
-use ym2149_sndh_replayer::{SndhPlayer, is_sndh_data};
-use ym2149_ym_replayer::load_song;
+use ym2149_common::{ChiptuneFormat, detect_format};
+use ym2149_kss_replayer::KssPlayer;
+use ym2149_sndh_replayer::SndhPlayer;
+use ym2149_ym_replayer::{extract_zip_entry, is_zip_archive, load_song, zip_entry_names};
 
 /// Supported file extensions
-const SUPPORTED_EXTENSIONS: &[&str] = &["ym", "aks", "ay", "sndh"];
+const SUPPORTED_EXTENSIONS: &[&str] = &["ym", "aks", "ay", "sndh", "kss"];
+
+/// Extension of archives that are opened as playlists
+const ARCHIVE_EXTENSION: &str = "zip";
 
 /// Entry in the playlist with metadata
 #[derive(Clone, Debug)]
@@ -88,6 +99,20 @@ impl Playlist {
         })
     }
 
+    /// Scan a ZIP archive for music files
+    ///
+    /// Entries keep their archive order.
+    pub fn scan_archive(path: &Path) -> io::Result<Self> {
+        let mut entries = Vec::new();
+        scan_archive_entries(path, &mut entries)?;
+
+        Ok(Self {
+            entries,
+            selected: 0,
+            search_query: String::new(),
+        })
+    }
+
     /// Check if playlist is empty
     pub fn is_empty(&self) -> bool {
         self.entries.is_empty()
@@ -294,14 +319,16 @@ fn scan_directory_recursive(path: &Path, entries: &mut Vec<PlaylistEntry>) -> st
             // Recurse into subdirectory
             scan_directory_recursive(&path, entries)?;
         } else if path.is_file() {
-            // Check if it's a supported file
-            if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                let ext_lower = ext.to_ascii_lowercase();
-                if SUPPORTED_EXTENSIONS.contains(&ext_lower.as_str()) {
-                    // Try to extract metadata
-                    if let Some(entry) = extract_metadata(&path) {
-                        entries.push(entry);
-                    }
+            if is_archive_path(&path) {
+                // Unreadable or corrupt archives are skipped like unparsable songs
+                let _ = scan_archive_entries(&path, entries);
+            } else if is_supported_path(&path) {
+                // Try to extract metadata
+                if let Some(entry) = fs::read(&path)
+                    .ok()
+                    .and_then(|data| extract_metadata(&path, &data))
+                {
+                    entries.push(entry);
                 }
             }
         }
@@ -310,27 +337,109 @@ fn scan_directory_recursive(path: &Path, entries: &mut Vec<PlaylistEntry>) -> st
     Ok(())
 }
 
-/// Extract metadata from a music file
-fn extract_metadata(path: &Path) -> Option<PlaylistEntry> {
-    let file_data = fs::read(path).ok()?;
-    let extension = path
-        .extension()
+/// Add all supported entries of a ZIP archive to the playlist
+fn scan_archive_entries(path: &Path, entries: &mut Vec<PlaylistEntry>) -> io::Result<()> {
+    let archive_data = fs::read(path)?;
+    let names = zip_entry_names(&archive_data).map_err(io::Error::other)?;
+
+    for name in names {
+        let entry_path = path.join(&name);
+        if !is_supported_path(&entry_path) {
+            continue;
+        }
+        if let Some(entry) = extract_zip_entry(&archive_data, &name)
+            .ok()
+            .and_then(|data| extract_metadata(&entry_path, &data))
+        {
+            entries.push(entry);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check if a path has the ZIP archive extension
+pub fn is_archive_path(path: &Path) -> bool {
+    lowercase_extension(path) == ARCHIVE_EXTENSION
+}
+
+/// Check if a path has one of the supported song extensions
+fn is_supported_path(path: &Path) -> bool {
+    #[cfg(feature = "audio-files")]
+    if crate::audio_file::is_audio_file_path(path) {
+        return true;
+    }
+    SUPPORTED_EXTENSIONS.contains(&lowercase_extension(path).as_str())
+}
+
+fn lowercase_extension(path: &Path) -> String {
+    path.extension()
         .and_then(|e| e.to_str())
         .map(|e| e.to_ascii_lowercase())
-        .unwrap_or_default();
+        .unwrap_or_default()
+}
+
+/// Read a song file, resolving paths that point into ZIP archives
+///
+/// Accepts regular files, virtual `archive.zip/entry` paths produced by the
+/// playlist scanner, and plain archive paths (the first supported entry is used).
+pub fn read_song_data(path: &Path) -> io::Result<Vec<u8>> {
+    if path.is_file() {
+        let data = fs::read(path)?;
+        if !(is_archive_path(path) && is_zip_archive(&data)) {
+            return Ok(data);
+        }
+        let names = zip_entry_names(&data).map_err(io::Error::other)?;
+        let first = names
+            .iter()
+            .find(|name| is_supported_path(Path::new(name)))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "archive contains no supported songs",
+                )
+            })?;
+        return extract_zip_entry(&data, first).map_err(io::Error::other);
+    }
+
+    // Virtual path: find the enclosing archive and extract the remainder
+    for archive in path.ancestors().skip(1) {
+        if archive.is_file() && is_archive_path(archive) {
+            let entry_name = path
+                .strip_prefix(archive)
+                .map_err(io::Error::other)?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            let archive_data = fs::read(archive)?;
+            return extract_zip_entry(&archive_data, &entry_name).map_err(io::Error::other);
+        }
+    }
+
+    fs::read(path)
+}
+
+/// Extract metadata from a music file
+fn extract_metadata(path: &Path, file_data: &[u8]) -> Option<PlaylistEntry> {
+    let extension = lowercase_extension(path);
 
     let (title, author, duration_secs, format) = match extension.as_str() {
-        "aks" => extract_aks_metadata(&file_data)?,
-        "ay" => extract_ay_metadata(&file_data)?,
-        "sndh" => extract_sndh_metadata(&file_data)?,
-        _ => {
-            // Try YM format (also handles header-based SNDH detection)
-            if is_sndh_data(&file_data) {
-                extract_sndh_metadata(&file_data)?
-            } else {
-                extract_ym_metadata(&file_data)?
-            }
+        #[cfg(feature = "audio-files")]
+        ext if crate::audio_file::AUDIO_FILE_EXTENSIONS.contains(&ext) => {
+            extract_audio_file_metadata(file_data, ext)?
         }
+        "aks" => extract_aks_metadata(file_data)?,
+        "ay" => extract_ay_metadata(file_data)?,
+        "sndh" => extract_sndh_metadata(file_data)?,
+        "kss" => extract_kss_metadata(file_data)?,
+        _ => match detect_format(file_data).format {
+            ChiptuneFormat::Sndh => extract_sndh_metadata(file_data)?,
+            ChiptuneFormat::Ay => extract_ay_metadata(file_data)?,
+            ChiptuneFormat::Arkos => extract_aks_metadata(file_data)?,
+            ChiptuneFormat::Kss => extract_kss_metadata(file_data)?,
+            _ => extract_ym_metadata(file_data)?,
+        },
     };
 
     Some(PlaylistEntry {
@@ -410,6 +519,47 @@ fn extract_sndh_metadata(data: &[u8]) -> Option<(String, String, Option<f32>, St
     Some((title, author, None, "SNDH".to_string()))
 }
 
+/// Extract metadata from KSS file
+fn extract_kss_metadata(data: &[u8]) -> Option<(String, String, Option<f32>, String)> {
+    let player = KssPlayer::load_from_bytes(data, 0).ok()?;
+
+    // KSS rips have no title, author or length tags
+    Some((
+        player.metadata().title.clone(),
+        "(unknown)".to_string(),
+        None,
+        "KSS".to_string(),
+    ))
+}
+
+/// Extract metadata from an MP3/FLAC/Ogg/WAV file
+#[cfg(feature = "audio-files")]
+fn extract_audio_file_metadata(
+    data: &[u8],
+    extension: &str,
+) -> Option<(String, String, Option<f32>, String)> {
+    use ym2149_common::ChiptunePlayerBase;
+
+    let player = crate::audio_file::AudioFilePlayer::new(data.to_vec(), extension, 44100).ok()?;
+    let tags = player.tags();
+
+    let title = if tags.title.is_empty() {
+        "(unknown)".to_string()
+    } else {
+        tags.title.clone()
+    };
+
+    let author = if tags.artist.is_empty() {
+        "(unknown)".to_string()
+    } else {
+        tags.artist.clone()
+    };
+
+    let duration = Some(player.duration_seconds()).filter(|d| *d > 0.0);
+
+    Some((title, author, duration, player.codec().to_string()))
+}
+
 /// Extract metadata from YM file
 fn extract_ym_metadata(data: &[u8]) -> Option<(String, String, Option<f32>, String)> {
     let (player, summary) = load_song(data).ok()?;
@@ -0,0 +1,485 @@
+//! Remote control server (`--remote <port|addr>`).
+//!
+//! A minimal HTTP/1.1 server so other processes (stream deck plugins, web
+//! remotes, shell scripts) can drive the TUI player:
+//!
+//! ```text
+//! curl -X POST -H 'Authorization: Bearer <token>' localhost:7878/pause
+//! curl -X POST 'localhost:7878/seek?by=-30&token=<token>'
+//! curl -X POST -H 'Authorization: Bearer <token>' 'localhost:7878/load?path=/music/Scout.sndh'
+//! ```
+//!
+//! Every command must carry the session token, either as a bearer
+//! `Authorization` header or as a `token` query parameter. The token is
+//! random per session (printed at startup and shown in the TUI footer)
+//! unless `YM_REPLAYER_REMOTE_TOKEN` sets it. Web pages the user visits can
+//! still send requests to a loopback port, so requests with a non-local
+//! `Origin`, or a `Host` other than the loopback or listening address (DNS
+//! rebinding), are refused as well.
+//!
+//! Connections are served one at a time on a background thread. Each command
+//! is handed to the player loop, and the response reports whether it was
+//! applied (`200 ok`) or why not (`409` with a message).
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+/// How long a connection waits for the player loop to apply its command.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Read/write timeout for client sockets.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest accepted request or header line, in bytes.
+const MAX_LINE_LEN: usize = 8 * 1024;
+
+/// Most header lines accepted per request.
+const MAX_HEADERS: usize = 64;
+
+/// Environment variable that fixes the session token.
+pub const TOKEN_ENV: &str = "YM_REPLAYER_REMOTE_TOKEN";
+
+/// Random bytes in a generated session token.
+const TOKEN_BYTES: usize = 16;
+
+/// Command received from a remote client.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RemoteCommand {
+    /// Start or resume playback.
+    Play,
+    /// Pause playback.
+    Pause,
+    /// Pause when playing, otherwise play.
+    TogglePause,
+    /// Play the next playlist entry.
+    Next,
+    /// Play the previous playlist entry.
+    Previous,
+    /// Seek forward (positive) or backward by a number of seconds.
+    SeekBy(f32),
+    /// Seek to an absolute position in seconds.
+    SeekTo(f32),
+    /// Load and play a file.
+    Load(PathBuf),
+    /// Quit the player.
+    Quit,
+}
+
+/// A command waiting to be applied by the player loop.
+pub struct RemoteRequest {
+    pub command: RemoteCommand,
+    reply: Sender<Result<(), String>>,
+}
+
+impl RemoteRequest {
+    /// Report the outcome to the waiting client.
+    pub fn reply(self, result: Result<(), String>) {
+        // The client may have timed out already
+        let _ = self.reply.send(result);
+    }
+}
+
+/// Background HTTP server forwarding commands to the player loop.
+pub struct RemoteServer {
+    requests: Receiver<RemoteRequest>,
+    token: String,
+    local_addr: SocketAddr,
+}
+
+impl RemoteServer {
+    /// Bind to `addr` and start serving; a bare port number binds `127.0.0.1`.
+    ///
+    /// The session token comes from [`TOKEN_ENV`] when set, otherwise it is
+    /// generated randomly.
+    pub fn bind(addr: &str) -> io::Result<Self> {
+        let listener = match addr.parse::<u16>() {
+            Ok(port) => TcpListener::bind((Ipv4Addr::LOCALHOST, port))?,
+            Err(_) => TcpListener::bind(addr)?,
+        };
+        let token = match std::env::var(TOKEN_ENV) {
+            Ok(token) if !token.is_empty() => token,
+            _ => random_token()?,
+        };
+        let local_addr = listener.local_addr()?;
+        let access = Access {
+            token: token.clone(),
+            local_ip: local_addr.ip(),
+        };
+        let (tx, requests) = mpsc::channel();
+        thread::Builder::new()
+            .name("remote-control".to_string())
+            .spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    // A misbehaving client only affects its own connection
+                    let _ = handle_connection(stream, &tx, &access);
+                }
+            })?;
+        Ok(Self {
+            requests,
+            token,
+            local_addr,
+        })
+    }
+
+    /// Token clients must send with every command.
+    pub fn token(&self) -> &str {
+        &self.token
+    }
+
+    /// Address the server listens on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Next pending command, if any.
+    pub fn try_recv(&self) -> Option<RemoteRequest> {
+        self.requests.try_recv().ok()
+    }
+}
+
+/// Hex-encoded random session token.
+fn random_token() -> io::Result<String> {
+    let mut bytes = [0u8; TOKEN_BYTES];
+    getrandom::fill(&mut bytes).map_err(io::Error::other)?;
+    Ok(bytes.iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// What a request must match to be accepted.
+struct Access {
+    token: String,
+    /// Listening address, accepted as `Host` besides the loopback names.
+    local_ip: IpAddr,
+}
+
+/// Request line and headers of an HTTP request.
+#[derive(Debug)]
+struct Request {
+    line: String,
+    /// Header names lowercased, values trimmed.
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    tx: &Sender<RemoteRequest>,
+    access: &Access,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))?;
+    stream.set_write_timeout(Some(IO_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+
+    let command = read_request(&mut reader).and_then(|request| {
+        authorize(&request, access)?;
+        parse_request(&request.line)
+    });
+    let (status, body) = match command {
+        Ok(command) => {
+            let (reply, outcome) = mpsc::channel();
+            if tx.send(RemoteRequest { command, reply }).is_err() {
+                (503, "player is shutting down".to_string())
+            } else {
+                match outcome.recv_timeout(REPLY_TIMEOUT) {
+                    Ok(Ok(())) => (200, "ok".to_string()),
+                    Ok(Err(e)) => (409, e),
+                    Err(_) => (503, "player did not respond".to_string()),
+                }
+            }
+        }
+        Err(error) => error,
+    };
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        431 => "Request Header Fields Too Large",
+        _ => "Service Unavailable",
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}\n",
+        body.len() + 1
+    )?;
+    stream.flush()
+}
+
+/// Read the request line and headers, refusing overlong lines and too many
+/// headers.
+fn read_request(reader: &mut impl BufRead) -> Result<Request, (u16, String)> {
+    let line = read_line(reader)?;
+    let mut headers = Vec::new();
+    loop {
+        let header = read_line(reader)?;
+        if header.is_empty() {
+            break;
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err((431, "too many headers".to_string()));
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err((400, "malformed header".to_string()));
+        };
+        headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    }
+    Ok(Request { line, headers })
+}
+
+/// One line without its line ending; empty at the end of the headers or
+/// of the stream.
+fn read_line(reader: &mut impl BufRead) -> Result<String, (u16, String)> {
+    let mut line = Vec::new();
+    reader
+        .by_ref()
+        .take(MAX_LINE_LEN as u64 + 1)
+        .read_until(b'\n', &mut line)
+        .map_err(|_| (400, "malformed request".to_string()))?;
+    if line.len() > MAX_LINE_LEN {
+        return Err((431, "line too long".to_string()));
+    }
+    let line = String::from_utf8(line).map_err(|_| (400, "malformed request".to_string()))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Check the token and that the request was addressed to this machine by a
+/// local client, not by a web page or a rebound DNS name.
+fn authorize(request: &Request, access: &Access) -> Result<(), (u16, String)> {
+    match request.header("host") {
+        Some(host) if is_local_host(host, access.local_ip) => {}
+        _ => return Err((403, "host not allowed".to_string())),
+    }
+    if let Some(origin) = request.header("origin") {
+        let host = origin
+            .strip_prefix("http://")
+            .or_else(|| origin.strip_prefix("https://"));
+        if !host.is_some_and(|host| is_local_host(host, access.local_ip)) {
+            return Err((403, "origin not allowed".to_string()));
+        }
+    }
+
+    let target = request.line.split_whitespace().nth(1).unwrap_or_default();
+    let query = target.split_once('?').map_or("", |(_, query)| query);
+    let token = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string())
+        .or_else(|| query_param(query, "token"));
+    match token {
+        Some(token) if token == access.token => Ok(()),
+        Some(_) => Err((401, "invalid token".to_string())),
+        None => Err((401, "missing token".to_string())),
+    }
+}
+
+/// Whether a `Host`-style `name[:port]` names this machine.
+fn is_local_host(host: &str, local_ip: IpAddr) -> bool {
+    let name = match host.strip_prefix('[') {
+        Some(rest) => rest.split_once(']').map_or(rest, |(ip, _)| ip),
+        None => host.split_once(':').map_or(host, |(name, _)| name),
+    };
+    if name.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+    name.parse::<IpAddr>()
+        .is_ok_and(|ip| ip.is_loopback() || (ip == local_ip && !ip.is_unspecified()))
+}
+
+/// Percent-decoded value of the first `name=` pair in a query string.
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| percent_decode(value))
+}
+
+/// Parse an HTTP request line such as `POST /seek?to=90 HTTP/1.1`.
+fn parse_request(line: &str) -> Result<RemoteCommand, (u16, String)> {
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err((400, "malformed request".to_string()));
+    };
+    if method != "POST" {
+        return Err((405, "use POST".to_string()));
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let param = |name: &str| query_param(query, name);
+    let seconds = |name: &str| {
+        param(name).map(|value| {
+            value
+                .parse::<f32>()
+                .ok()
+                .filter(|s| s.is_finite())
+                .ok_or((400, format!("invalid seconds `{value}`")))
+        })
+    };
+
+    match path {
+        "/play" => Ok(RemoteCommand::Play),
+        "/pause" => Ok(RemoteCommand::Pause),
+        "/toggle" => Ok(RemoteCommand::TogglePause),
+        "/next" => Ok(RemoteCommand::Next),
+        "/prev" | "/previous" => Ok(RemoteCommand::Previous),
+        "/quit" => Ok(RemoteCommand::Quit),
+        "/seek" => match (seconds("by"), seconds("to")) {
+            (Some(by), None) => Ok(RemoteCommand::SeekBy(by?)),
+            (None, Some(to)) => Ok(RemoteCommand::SeekTo(to?.max(0.0))),
+            _ => Err((400, "seek takes either `by` or `to`".to_string())),
+        },
+        "/load" => match param("path") {
+            Some(path) if !path.is_empty() => Ok(RemoteCommand::Load(PathBuf::from(path))),
+            _ => Err((400, "load requires `path`".to_string())),
+        },
+        _ => Err((404, format!("unknown command `{path}`"))),
+    }
+}
+
+/// Decode `%XX` escapes and `+` in a query value.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+                match hex {
+                    Some(byte) => {
+                        out.push(byte);
+                        i += 2;
+                    }
+                    None => out.push(b'%'),
+                }
+            }
+            byte => out.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        assert_eq!(
+            parse_request("POST /pause HTTP/1.1\r\n"),
+            Ok(RemoteCommand::Pause)
+        );
+        assert_eq!(
+            parse_request("POST /seek?by=-30 HTTP/1.1"),
+            Ok(RemoteCommand::SeekBy(-30.0))
+        );
+        assert_eq!(
+            parse_request("POST /load?path=%2Fmusic%2FMad+Max.sndh HTTP/1.1"),
+            Ok(RemoteCommand::Load(PathBuf::from("/music/Mad Max.sndh")))
+        );
+        assert_eq!(parse_request("GET /play HTTP/1.1").unwrap_err().0, 405);
+        assert_eq!(
+            parse_request("POST /seek?by=x HTTP/1.1").unwrap_err().0,
+            400
+        );
+        assert_eq!(parse_request("POST /rewind HTTP/1.1").unwrap_err().0, 404);
+    }
+
+    fn access() -> Access {
+        Access {
+            token: "secret".to_string(),
+            local_ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
+        }
+    }
+
+    fn request(text: &str) -> Result<Request, (u16, String)> {
+        read_request(&mut io::Cursor::new(text.as_bytes()))
+    }
+
+    fn status(text: &str) -> u16 {
+        match request(text).and_then(|request| authorize(&request, &access())) {
+            Ok(()) => 200,
+            Err((status, _)) => status,
+        }
+    }
+
+    #[test]
+    fn requires_the_session_token() {
+        let with_header =
+            "POST /next HTTP/1.1\r\nHost: localhost:7878\r\nAuthorization: Bearer secret\r\n\r\n";
+        assert_eq!(status(with_header), 200);
+        assert_eq!(
+            status("POST /next?token=secret HTTP/1.1\r\nHost: 127.0.0.1:7878\r\n\r\n"),
+            200
+        );
+        assert_eq!(
+            status("POST /next HTTP/1.1\r\nHost: localhost:7878\r\n\r\n"),
+            401
+        );
+        assert_eq!(
+            status("POST /next?token=guess HTTP/1.1\r\nHost: localhost\r\n\r\n"),
+            401
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_hosts_and_origins() {
+        // DNS rebinding: the page's own name arrives as Host
+        assert_eq!(
+            status("POST /next?token=secret HTTP/1.1\r\nHost: evil.example:7878\r\n\r\n"),
+            403
+        );
+        assert_eq!(status("POST /next?token=secret HTTP/1.1\r\n\r\n"), 403);
+        // Cross-origin form posts and fetches carry the page's Origin
+        let from = |origin: &str| {
+            status(&format!(
+                "POST /next?token=secret HTTP/1.1\r\nHost: localhost:7878\r\nOrigin: {origin}\r\n\r\n"
+            ))
+        };
+        assert_eq!(from("https://evil.example"), 403);
+        assert_eq!(from("null"), 403);
+        assert_eq!(from("http://localhost:7878"), 200);
+        assert_eq!(from("http://[::1]:7878"), 200);
+    }
+
+    #[test]
+    fn limits_header_size() {
+        let long = format!(
+            "POST /next HTTP/1.1\r\nX-Pad: {}\r\n\r\n",
+            "a".repeat(MAX_LINE_LEN)
+        );
+        assert_eq!(request(&long).unwrap_err().0, 431);
+        let many = format!(
+            "POST /next HTTP/1.1\r\n{}\r\n",
+            "X-Pad: a\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert_eq!(request(&many).unwrap_err().0, 431);
+        let request = request("POST /next HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+        assert_eq!(request.line, "POST /next HTTP/1.1");
+        assert_eq!(request.header("host"), Some("localhost"));
+    }
+
+    #[test]
+    fn keeps_malformed_escapes() {
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%41"), "%zzA");
+    }
+}
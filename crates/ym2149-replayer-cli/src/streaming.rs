@@ -1,76 +1,33 @@
 //! Real-time audio streaming and playback control.
 //!
 //! This module manages:
-//! - Audio device initialization
+//! - Audio output initialization (device or raw PCM on stdout)
 //! - Producer thread for sample generation
 //! - Real-time buffer management
-//! - Playback state synchronization
+//! - Playback state synchronization (commands in, status out)
+//! - Optional insert effects (delay, chorus, reverb)
 //! - Visualization delay compensation (syncs visuals with audio output)
-
-use crate::audio::{AudioDevice, BUFFER_BACKOFF_MICROS, RealtimePlayer, StreamConfig};
+//! - Underrun recovery (larger producer cycles and a fade-in after a gap)
+//! - Adaptive buffer sizing (see [`StreamConfig::adaptive`])
+
+#[cfg(feature = "jack")]
+use crate::audio::JackOutput;
+use crate::audio::{
+    AdaptiveBuffer, AudioDevice, AudioOutput, BUFFER_BACKOFF_MICROS, OutputTarget, PipeOutput,
+    RealtimePlayer, StreamConfig, UnderrunEvent, UnderrunRecovery, VISUALIZATION_UPDATE_MS,
+};
+use crate::command::{self, PlayerCommand, PlayerStatus};
 use crate::tui::CaptureBuffer;
 use crate::{RealtimeChip, VisualSnapshot};
 use parking_lot::Mutex;
-use std::collections::VecDeque;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
-
-/// Delay buffer for visual snapshots to sync visualization with audio output.
-///
-/// The audio ring buffer introduces latency between when samples are generated
-/// and when they're actually played. This buffer delays the visual snapshots
-/// by the same amount so visualization matches the audible output.
-pub struct SnapshotDelayBuffer {
-    /// Ring buffer of snapshots
-    snapshots: VecDeque<VisualSnapshot>,
-    /// Target delay in number of snapshots (based on audio buffer size)
-    target_delay: usize,
-    /// Current delayed snapshot for TUI to read
-    current_delayed: VisualSnapshot,
-}
-
-impl SnapshotDelayBuffer {
-    /// Create a new delay buffer.
-    ///
-    /// # Arguments
-    /// * `audio_buffer_samples` - Size of the audio ring buffer in samples
-    /// * `batch_size` - Number of samples generated per batch (snapshot interval)
-    pub fn new(audio_buffer_samples: usize, batch_size: usize) -> Self {
-        // Calculate how many batches fit in the audio buffer
-        // This is the delay we need to compensate for
-        let target_delay = (audio_buffer_samples / batch_size).max(1);
-
-        Self {
-            snapshots: VecDeque::with_capacity(target_delay + 2),
-            target_delay,
-            current_delayed: VisualSnapshot::default(),
-        }
-    }
-
-    /// Push a new snapshot (called from producer thread after generating samples).
-    /// Updates the current_delayed snapshot that the TUI reads.
-    pub fn push(&mut self, snapshot: VisualSnapshot) {
-        self.snapshots.push_back(snapshot);
-
-        // Update delayed snapshot if we have enough buffered
-        if self.snapshots.len() > self.target_delay
-            && let Some(delayed) = self.snapshots.pop_front()
-        {
-            self.current_delayed = delayed;
-        }
-    }
-
-    /// Get the current delayed snapshot (called from TUI thread).
-    pub fn get_delayed(&self) -> VisualSnapshot {
-        self.current_delayed
-    }
-
-    /// Clear the buffer (e.g., when switching songs).
-    pub fn clear(&mut self) {
-        self.snapshots.clear();
-        self.current_delayed = VisualSnapshot::default();
-    }
-}
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use ym2149_common::{
+    AbMode, DEFAULT_FFT_SIZE, FxConfig, FxRack, OutputModel, PlaybackState, PlayerHandle,
+    RegisterDelayLine, RegisterDelta, SharedPlayer,
+};
+use ym2149_ym_replayer::ReplayerError;
 
 /// ST color filter for stereo audio.
 ///
@@ -123,24 +80,49 @@ impl ColorFilter {
 /// With stereo, this is 2048 frames = 4096 samples (interleaved L/R).
 const SAMPLE_BATCH_SIZE: usize = 2048;
 
+/// Channel tap capacity used to feed per-channel effects.
+///
+/// Shares the TUI's oscilloscope tap (one FFT window), so both can be
+/// enabled without recreating the tap every batch.
+const FX_TAP_CAPACITY: usize = DEFAULT_FFT_SIZE;
+const _: () = assert!(SAMPLE_BATCH_SIZE <= FX_TAP_CAPACITY);
+
+/// Length of the fade-in after an underrun, in milliseconds.
+const UNDERRUN_FADE_MS: u32 = 10;
+
+/// Player owned by the producer thread; volume and mutes go through [`PlayerHandle`].
+type SharedChip = SharedPlayer<dyn RealtimeChip>;
+
 /// Audio streaming context with device and producer thread.
+///
+/// The producer thread owns the player. Control it with [`send`](Self::send)
+/// and read its state with [`status`](Self::status); neither waits for the
+/// producer to finish a batch.
 pub struct StreamingContext {
-    /// Audio device handle
-    pub audio_device: AudioDevice,
+    /// Audio device or raw PCM writer
+    pub audio_output: AudioOutput,
     /// Producer thread handle
     pub producer_thread: std::thread::JoinHandle<()>,
     /// Flag to signal shutdown
     pub running: Arc<AtomicBool>,
-    /// Shared player instance
-    pub player: Arc<Mutex<Box<dyn RealtimeChip>>>,
+    /// Lock-free volume and channel mute controls of the player
+    pub controls: PlayerHandle,
     /// Streaming engine
     pub streamer: Arc<RealtimePlayer>,
     /// Capture buffer for TUI visualization (optional)
     pub capture: Option<Arc<Mutex<CaptureBuffer>>>,
-    /// Master volume (0-100 as percentage, stored as atomic for thread safety)
-    pub volume: Arc<AtomicU32>,
-    /// Delay buffer for syncing visuals with audio output
-    pub snapshot_delay: Arc<Mutex<SnapshotDelayBuffer>>,
+    /// Delay line syncing visuals with audio output
+    pub snapshot_delay: Arc<Mutex<RegisterDelayLine<VisualSnapshot>>>,
+    /// Command queue consumed by the producer thread
+    commands: Sender<PlayerCommand>,
+    /// Latest player state published by the producer thread
+    status: Arc<Mutex<PlayerStatus>>,
+    /// Number of players replaced so far (see [`PlayerStatus::generation`])
+    generation: AtomicU64,
+    /// Output model applied to every player (kept across song switches)
+    output_model: Mutex<OutputModel>,
+    /// Output sample rate, used to build effect racks
+    sample_rate: u32,
 }
 
 impl StreamingContext {
@@ -182,7 +164,8 @@ impl StreamingContext {
 
     /// Initialize audio streaming paused (for playlist mode).
     ///
-    /// The player will not start automatically - call `player.play()` to begin playback.
+    /// The player will not start automatically - send
+    /// [`PlayerCommand::TogglePause`] or replace the player to begin playback.
     pub fn start_paused(
         player: Box<dyn RealtimeChip>,
         config: StreamConfig,
@@ -203,78 +186,188 @@ impl StreamingContext {
             RealtimePlayer::new(config)
                 .map_err(|e| format!("Failed to create realtime player: {e}"))?,
         );
-        let audio_device =
-            AudioDevice::new(config.sample_rate, config.channels, streamer.get_buffer())
-                .map_err(|e| format!("Failed to create audio device: {e}"))?;
-
-        let player = Arc::new(Mutex::new(player));
         let running = Arc::new(AtomicBool::new(true));
-        let volume = Arc::new(AtomicU32::new(100)); // 100% default
+        let audio_output = match config.output {
+            OutputTarget::Device => {
+                AudioDevice::new(config.sample_rate, config.channels, streamer.get_buffer())
+                    .map(AudioOutput::Device)
+            }
+            OutputTarget::Stdout(format) => {
+                PipeOutput::new(streamer.get_buffer(), format, Arc::clone(&running))
+                    .map(AudioOutput::Pipe)
+            }
+            #[cfg(feature = "jack")]
+            OutputTarget::Jack { .. } => {
+                JackOutput::new(streamer.get_buffer(), config.sample_rate, config.channels)
+                    .map(AudioOutput::Jack)
+            }
+        }
+        .map_err(|e| ReplayerError::DeviceLost(e.to_string()))?;
+
+        let player = SharedPlayer::from_box(player);
+        let controls = player.handle();
+        let (commands, receiver) = mpsc::channel();
 
-        // Create delay buffer to sync visuals with audio output
-        let snapshot_delay = Arc::new(Mutex::new(SnapshotDelayBuffer::new(
+        // Publish the initial state so the UI can lay itself out before the
+        // first batch
+        let mut initial = PlayerStatus::default();
+        initial.capture(&*player);
+        let status = Arc::new(Mutex::new(initial));
+
+        // Delay visual snapshots by the ring buffer latency so they match
+        // what is audible rather than what was just generated
+        let snapshot_delay = Arc::new(Mutex::new(RegisterDelayLine::for_ring_buffer(
             config.ring_buffer_size,
-            SAMPLE_BATCH_SIZE,
+            usize::from(config.channels),
         )));
 
-        let running_clone = Arc::clone(&running);
-        let player_clone = Arc::clone(&player);
-        let streamer_clone = Arc::clone(&streamer);
-        let volume_clone = Arc::clone(&volume);
-        let snapshot_delay_clone = Arc::clone(&snapshot_delay);
-
-        let producer_thread = std::thread::spawn(move || {
-            run_producer_loop(
-                player_clone,
-                streamer_clone,
-                running_clone,
-                ColorFilter::new(color_filter_enabled),
-                auto_start,
-                volume_clone,
-                snapshot_delay_clone,
-            );
-        });
+        let producer = Producer {
+            player,
+            commands: receiver,
+            status: Arc::clone(&status),
+            streamer: Arc::clone(&streamer),
+            running: Arc::clone(&running),
+            color_filter: ColorFilter::new(color_filter_enabled),
+            snapshot_delay: Arc::clone(&snapshot_delay),
+            fx: None,
+            output_model: OutputModel::default(),
+            ab_mode: None,
+            tap_capacity: None,
+            stems: config.output.has_stems(),
+            generation: 0,
+            last_step: None,
+            recovery: UnderrunRecovery::new(
+                (config.sample_rate * UNDERRUN_FADE_MS / 1000) as usize,
+            ),
+            adaptive: config.is_adaptive().then(|| {
+                AdaptiveBuffer::new(
+                    config.ring_buffer_size,
+                    config.max_ring_buffer_size,
+                    config.sample_rate as usize * usize::from(config.channels),
+                )
+            }),
+        };
+        let producer_thread = std::thread::spawn(move || producer.run(auto_start));
 
         Ok(StreamingContext {
-            audio_device,
+            audio_output,
             producer_thread,
             running,
-            player,
+            controls,
             streamer,
             capture,
-            volume,
             snapshot_delay,
+            commands,
+            status,
+            generation: AtomicU64::new(0),
+            output_model: Mutex::new(OutputModel::default()),
+            sample_rate: config.sample_rate,
         })
     }
 
-    /// Set the master volume (0.0 to 1.0)
+    /// Queue a command for the producer thread.
+    ///
+    /// Commands apply in order before the next batch; they are dropped once
+    /// the producer thread has exited.
+    pub fn send(&self, command: PlayerCommand) {
+        let _ = self.commands.send(command);
+    }
+
+    /// Latest player state published by the producer thread.
+    pub fn status(&self) -> PlayerStatus {
+        self.status.lock().clone()
+    }
+
+    /// Number of players replaced so far.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    /// Whether the current song has stopped on its own.
+    ///
+    /// Stays false until a pending [`replace_player`](Self::replace_player)
+    /// has been applied, so a song switch is never mistaken for its end.
+    pub fn song_finished(&self) -> bool {
+        let status = self.status.lock();
+        status.generation == self.generation() && status.state == PlaybackState::Stopped
+    }
+
+    /// Block until the current song has played to its end or playback stops.
+    pub fn wait_until_finished(&self) {
+        // The status reads stopped until the producer thread has started the
+        // song; a short song written to a file can also end between two
+        // polls, which its elapsed time still shows
+        let mut started = false;
+        while self.running.load(Ordering::Relaxed) {
+            let status = self.status();
+            started |= status.state != PlaybackState::Stopped || status.elapsed_seconds > 0.0;
+            if started && self.song_finished() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(VISUALIZATION_UPDATE_MS));
+        }
+    }
+
+    /// Run `callback` on the producer thread after each audio output underrun.
+    ///
+    /// Device and JACK outputs report underruns; raw PCM output does not.
+    pub fn on_underrun(&self, callback: impl Fn(UnderrunEvent) + Send + Sync + 'static) {
+        self.streamer.set_on_underrun(callback);
+    }
+
+    /// Set the master volume (0.0 to 1.0) through the player's output gain.
+    ///
+    /// Does not wait for the producer thread; the change applies to the next batch.
     pub fn set_volume(&self, vol: f32) {
-        let percentage = (vol.clamp(0.0, 1.0) * 100.0) as u32;
-        self.volume.store(percentage, Ordering::Relaxed);
+        self.controls.set_gain(vol.clamp(0.0, 1.0));
+    }
+
+    /// Set the playback rate (speed and pitch) of the current and all future players.
+    pub fn set_playback_rate(&self, rate: f32) {
+        self.send(PlayerCommand::SetPlaybackRate(rate));
+    }
+
+    /// Set the analog output stage model of the current and all future players.
+    pub fn set_output_model(&self, model: OutputModel) {
+        *self.output_model.lock() = model;
+        self.send(PlayerCommand::SetOutputModel(model));
+    }
+
+    /// Get the active output stage model.
+    pub fn output_model(&self) -> OutputModel {
+        *self.output_model.lock()
+    }
+
+    /// Select the output of an A/B comparison for the current and all future players.
+    pub fn set_ab_mode(&self, mode: AbMode) {
+        self.send(PlayerCommand::SetAbMode(mode));
+    }
+
+    /// Set the insert effects applied to the output (an empty config bypasses them).
+    pub fn set_fx(&self, config: &FxConfig) {
+        let rack = (!config.is_empty()).then(|| FxRack::new(config, self.sample_rate));
+        self.send(PlayerCommand::SetFx(rack));
     }
 
     /// Replace the current player with a new one.
     ///
     /// This allows switching songs without restarting the audio stream.
-    /// The new player will start playing immediately.
+    /// The new player starts playing with the next batch.
     pub fn replace_player(&self, new_player: Box<dyn RealtimeChip>) {
-        let mut guard = self.player.lock();
-        // Stop old player
-        guard.stop();
-        // Replace with new player
-        *guard = new_player;
-        // Start new player
-        guard.play();
-        // Clear the snapshot delay buffer for fresh start
-        self.snapshot_delay.lock().clear();
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        self.send(PlayerCommand::ReplacePlayer(new_player));
     }
 
     /// Get a delayed visual snapshot that's synced with audio output.
     ///
-    /// Call this instead of directly reading from the player to get
-    /// visualization that matches what's currently being heard.
+    /// Call this instead of [`PlayerStatus::snapshot`] to get visualization
+    /// that matches what's currently being heard.
     pub fn get_delayed_snapshot(&self) -> VisualSnapshot {
-        self.snapshot_delay.lock().get_delayed()
+        self.snapshot_delay
+            .lock()
+            .delayed()
+            .copied()
+            .unwrap_or_default()
     }
 
     /// Signal shutdown and wait for producer thread to finish.
@@ -287,74 +380,286 @@ impl StreamingContext {
             // Log but don't panic - we need to clean up the audio device
             eprintln!("Warning: Producer thread panicked during shutdown: {e:?}");
         }
-        self.audio_device.finish();
+        self.audio_output.finish();
     }
 }
 
-/// Producer loop that generates samples and feeds them to the streamer.
-///
-/// Runs in a dedicated thread, continuously generating stereo audio samples
-/// from the player and writing them to the ring buffer. Also captures
-/// visual snapshots and pushes them to the delay buffer for sync.
-fn run_producer_loop(
-    player: Arc<Mutex<Box<dyn RealtimeChip>>>,
+/// State owned by the producer thread.
+struct Producer {
+    player: SharedChip,
+    commands: Receiver<PlayerCommand>,
+    status: Arc<Mutex<PlayerStatus>>,
     streamer: Arc<RealtimePlayer>,
     running: Arc<AtomicBool>,
-    mut color_filter: ColorFilter,
-    auto_start: bool,
-    volume: Arc<AtomicU32>,
-    snapshot_delay: Arc<Mutex<SnapshotDelayBuffer>>,
-) {
-    // Stereo buffer: 2048 frames * 2 channels = 4096 samples (interleaved L/R)
-    let mut sample_buffer = [0.0f32; 4096];
-
-    // Start playback (unless in paused mode for playlist)
-    if auto_start {
-        let mut player = player.lock();
-        player.play();
-    }
+    color_filter: ColorFilter,
+    snapshot_delay: Arc<Mutex<RegisterDelayLine<VisualSnapshot>>>,
+    /// Insert effects (`None` = bypass)
+    fx: Option<FxRack>,
+    /// Output model applied to every player
+    output_model: OutputModel,
+    /// A/B output selected by the UI, applied to every comparing player
+    ab_mode: Option<AbMode>,
+    /// Channel tap requested by the UI
+    tap_capacity: Option<usize>,
+    /// Append the first PSG's A/B/C outputs to every stereo frame
+    stems: bool,
+    /// Number of players replaced so far
+    generation: u64,
+    /// Registers changed by the latest frame step
+    last_step: Option<RegisterDelta>,
+    /// Batch count and fade-in after underruns
+    recovery: UnderrunRecovery,
+    /// Buffer size controller of an adaptive stream
+    adaptive: Option<AdaptiveBuffer>,
+}
+
+/// Scratch buffers of the producer loop.
+struct BatchBuffers {
+    /// Stereo buffer: 2048 frames * 2 channels = 4096 samples (interleaved L/R)
+    samples: [f32; SAMPLE_BATCH_SIZE * 2],
+    /// Per-channel outputs of the batch, for channel insert effects
+    channel_outputs: Vec<[f32; 3]>,
+    tap: Vec<f32>,
+    /// Stereo mix plus A/B/C stems per frame, when stems are enabled
+    stems: Vec<f32>,
+}
 
-    while running.load(Ordering::Relaxed) {
-        let batch_size = sample_buffer.len();
+impl Producer {
+    /// Producer loop that generates samples and feeds them to the streamer.
+    ///
+    /// Runs in a dedicated thread, continuously generating stereo audio samples
+    /// from the player and writing them to the ring buffer. Also captures
+    /// visual snapshots and pushes them to the delay buffer for sync.
+    fn run(mut self, auto_start: bool) {
+        let mut buffers = BatchBuffers {
+            samples: [0.0; SAMPLE_BATCH_SIZE * 2],
+            channel_outputs: vec![[0.0; 3]; SAMPLE_BATCH_SIZE],
+            tap: vec![0.0; SAMPLE_BATCH_SIZE],
+            stems: vec![0.0; if self.stems { SAMPLE_BATCH_SIZE * 5 } else { 0 }],
+        };
 
-        // Generate stereo samples and capture snapshot
-        let snapshot = {
-            let mut player = player.lock();
+        // Start playback (unless in paused mode for playlist)
+        if auto_start {
+            self.player.play();
+        }
+
+        while self.running.load(Ordering::Relaxed) {
+            while let Ok(command) = self.commands.try_recv() {
+                self.apply(command);
+            }
+            self.player.sync();
+
+            let channel_fx = self.fx.as_ref().is_some_and(FxRack::has_channel_effects);
+            let read_channels = channel_fx || self.stems;
+            let tap_capacity = if read_channels {
+                Some(FX_TAP_CAPACITY.max(self.tap_capacity.unwrap_or(0)))
+            } else {
+                self.tap_capacity
+            };
+            if tap_capacity.is_some() {
+                self.player.set_channel_tap(tap_capacity);
+            }
 
             // Check for unsupported format
-            if let Some(reason) = player.unsupported_reason() {
+            if let Some(reason) = self.player.unsupported_reason() {
                 eprintln!("{reason}");
-                running.store(false, Ordering::Relaxed);
+                self.running.store(false, Ordering::Relaxed);
                 break;
             }
 
-            // Generate stereo samples (produces silence when stopped/paused)
-            player.generate_samples_into_stereo(&mut sample_buffer);
+            // Render further ahead for a while after the output ran dry
+            if let Some(count) = self.streamer.poll_underruns() {
+                self.recovery.trigger();
+                if let Some(size) = self.adaptive.as_mut().and_then(AdaptiveBuffer::underrun) {
+                    self.resize_buffer(size);
+                }
+                self.streamer.notify_underrun(UnderrunEvent {
+                    count,
+                    batches_per_cycle: self.recovery.batches_per_cycle(),
+                    buffer_size: self.streamer.get_stats().buffer_size,
+                });
+            }
+            for _ in 0..self.recovery.batches_per_cycle() {
+                self.render_batch(&mut buffers, read_channels);
+            }
+        }
+    }
 
-            // Capture visual snapshot AFTER generating samples
-            // This is the state that corresponds to the audio we just generated
-            player.visual_snapshot()
-        };
+    /// Generate one batch, process it and write it to the ring buffer.
+    fn render_batch(&mut self, buffers: &mut BatchBuffers, read_channels: bool) {
+        // Generate stereo samples (produces silence when stopped/paused)
+        self.player
+            .generate_samples_into_stereo(&mut buffers.samples);
+
+        if read_channels {
+            read_channel_outputs(
+                &*self.player,
+                &mut buffers.tap,
+                &mut buffers.channel_outputs,
+            );
+        }
+
+        // Capture visual snapshot AFTER generating samples
+        // This is the state that corresponds to the audio we just generated
+        let snapshot = self.player.visual_snapshot();
+        self.publish_status();
 
         // Push snapshot to delay buffer (syncs visualization with audio output)
-        snapshot_delay.lock().push(snapshot);
+        self.snapshot_delay.lock().push(SAMPLE_BATCH_SIZE, snapshot);
 
         // Apply color filter to stereo samples
-        color_filter.process_stereo(&mut sample_buffer[..batch_size]);
-
-        // Apply master volume
-        let vol = volume.load(Ordering::Relaxed) as f32 / 100.0;
-        if vol < 1.0 {
-            for sample in sample_buffer[..batch_size].iter_mut() {
-                *sample *= vol;
+        self.color_filter.process_stereo(&mut buffers.samples);
+
+        // Apply insert effects after the analog output stage
+        if let Some(rack) = self.fx.as_mut() {
+            // Channel outputs are only read when channel_fx (or stems) is set
+            for (frame, &channels) in buffers
+                .samples
+                .chunks_exact_mut(2)
+                .zip(&buffers.channel_outputs)
+            {
+                (frame[0], frame[1]) = rack.process_stereo(frame[0], frame[1], channels);
             }
         }
 
+        let (output, width) = if self.stems {
+            for ((frame, stereo), channels) in buffers
+                .stems
+                .chunks_exact_mut(5)
+                .zip(buffers.samples.chunks_exact(2))
+                .zip(&buffers.channel_outputs)
+            {
+                frame[..2].copy_from_slice(stereo);
+                frame[2..].copy_from_slice(channels);
+            }
+            (&mut buffers.stems[..], 5)
+        } else {
+            (&mut buffers.samples[..], 2)
+        };
+        self.recovery.apply_fade(output, width);
+
         // Write to ring buffer
-        let written = streamer.write_blocking(&sample_buffer[..batch_size]);
-        if written < batch_size {
+        let written = self.streamer.write_blocking(output);
+        if written < output.len() {
             // Buffer full, back off briefly
             std::thread::sleep(std::time::Duration::from_micros(BUFFER_BACKOFF_MICROS));
         }
+        self.recovery.batch_done();
+        if let Some(size) = self.adaptive.as_mut().and_then(|a| a.written(written)) {
+            self.resize_buffer(size);
+        }
+    }
+
+    /// Set a new ring buffer fill limit and match the visual delay to it.
+    fn resize_buffer(&mut self, samples: usize) {
+        let width = if self.stems { 5 } else { 2 };
+        self.streamer.set_buffer_size(samples);
+        self.snapshot_delay.lock().set_latency(samples / width);
+    }
+
+    fn apply(&mut self, command: PlayerCommand) {
+        let player = &mut *self.player;
+        match command {
+            PlayerCommand::TogglePause => match player.state() {
+                PlaybackState::Playing => player.pause(),
+                _ => {
+                    self.last_step = None;
+                    player.play();
+                }
+            },
+            PlayerCommand::StepFrames(frames) => {
+                if let Some(delta) = player.step_frames(frames) {
+                    self.last_step = Some(delta);
+                }
+            }
+            PlayerCommand::SeekBy(seconds) => {
+                if command::seek_by(player, seconds) {
+                    // Drop audio and visuals from before the seek, and show
+                    // the new position without waiting for the next batch
+                    self.streamer.flush();
+                    self.snapshot_delay.lock().clear();
+                    self.publish_status();
+                }
+            }
+            PlayerCommand::NextSubsong => command::step_subsong(player, true),
+            PlayerCommand::PreviousSubsong => command::step_subsong(player, false),
+            PlayerCommand::SetPlaybackRate(rate) => player.set_playback_rate(rate),
+            PlayerCommand::SetOutputModel(model) => {
+                self.output_model = model;
+                player.set_output_model(model);
+            }
+            PlayerCommand::SetChannelTap(capacity) => {
+                self.tap_capacity = capacity;
+                player.set_channel_tap(capacity);
+            }
+            PlayerCommand::SetFx(rack) => self.fx = rack,
+            PlayerCommand::SetAbMode(mode) => {
+                self.ab_mode = Some(mode);
+                player.set_ab_mode(mode);
+            }
+            PlayerCommand::ReplacePlayer(new_player) => self.replace_player(new_player),
+        }
+    }
+
+    fn replace_player(&mut self, new_player: Box<dyn RealtimeChip>) {
+        // Stop old player, keeping its soft clip and rate settings
+        // (the shared controls carry the output gain over)
+        self.player.stop();
+        let soft_clip = self.player.soft_clip();
+        let playback_rate = self.player.playback_rate();
+        // Replace with new player
+        self.player.replace(new_player);
+        self.player.set_soft_clip(soft_clip);
+        self.player.set_playback_rate(playback_rate);
+        self.player.set_output_model(self.output_model);
+        if let Some(mode) = self.ab_mode {
+            self.player.set_ab_mode(mode);
+        }
+        if self.tap_capacity.is_some() {
+            self.player.set_channel_tap(self.tap_capacity);
+        }
+        // Start new player
+        self.player.play();
+        self.generation += 1;
+        self.last_step = None;
+        // Drop echoes and reverb tails of the previous song
+        if let Some(rack) = self.fx.as_mut() {
+            rack.reset();
+        }
+        // Clear the snapshot delay buffer for fresh start
+        self.snapshot_delay.lock().clear();
+    }
+
+    /// Publish the player state unless the UI is reading it right now.
+    ///
+    /// Skipping a batch is harmless (the next one publishes again), whereas
+    /// waiting here would stall the audio output behind a slow UI frame.
+    fn publish_status(&mut self) {
+        let Some(mut status) = self.status.try_lock() else {
+            return;
+        };
+        status.capture(&*self.player);
+        status.generation = self.generation;
+        status.last_step.clone_from(&self.last_step);
+        match self.tap_capacity {
+            Some(capacity) => status.capture_taps(&*self.player, capacity),
+            None => status.taps.clear(),
+        }
+    }
+}
+
+/// Copy the first PSG's tapped channel outputs of the last batch into `out`.
+///
+/// The tap returns the most recent samples oldest first; if it holds fewer
+/// than a batch (e.g. right after being enabled), the missing leading frames
+/// are zero.
+fn read_channel_outputs(player: &dyn RealtimeChip, scratch: &mut [f32], out: &mut [[f32; 3]]) {
+    for ch in 0..3 {
+        let count = player.read_channel_tap(ch, scratch);
+        let missing = out.len() - count;
+        let values = std::iter::repeat_n(0.0, missing).chain(scratch[..count].iter().copied());
+        for (frame, value) in out.iter_mut().zip(values) {
+            frame[ch] = value;
+        }
     }
 }
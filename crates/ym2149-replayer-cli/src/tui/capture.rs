@@ -6,10 +6,15 @@
 //! Supports up to 4 PSGs (12 channels) for multi-PSG configurations like AKS.
 //! Also tracks SID voice and DigiDrum effects for special visualization.
 //! Provides smoothed data and velocity tracking for dynamic visualization.
+//! Waveforms and spectra come from the backend channel taps (FFT analysis)
+//! when available, with the register-based ones as fallback.
 
+use std::collections::VecDeque;
 use ym2149_common::visualization::{
-    MAX_CHANNEL_COUNT, MAX_PSG_COUNT, SPECTRUM_BINS, SpectrumAnalyzer, WaveformSynthesizer,
+    MAX_CHANNEL_COUNT, MAX_PSG_COUNT, SPECTRUM_BINS, SpectrumAnalyzer, WAVEFORM_SIZE,
+    WaveformSynthesizer,
 };
+use ym2149_common::{DEFAULT_SAMPLE_RATE, FftSpectrumAnalyzer, FftSpectrumConfig};
 
 /// Smoothing factor for spectrum velocity (0.0-1.0, higher = more smoothing)
 const VELOCITY_SMOOTHING: f32 = 0.7;
@@ -25,8 +30,14 @@ const VELOCITY_SMOOTHING: f32 = 0.7;
 pub struct CaptureBuffer {
     /// Waveform synthesizer from shared library.
     waveform: WaveformSynthesizer,
+    /// Real per-channel samples read from the backend channel taps.
+    tapped: [VecDeque<f32>; MAX_CHANNEL_COUNT],
     /// Spectrum analyzer from shared library.
     spectrum: SpectrumAnalyzer,
+    /// FFT analyzers fed with the tapped channel samples.
+    fft: Vec<FftSpectrumAnalyzer>,
+    /// Latest FFT spectrum per channel (`None` without tap data).
+    fft_spectrum: [Option<[f32; SPECTRUM_BINS]>; MAX_CHANNEL_COUNT],
     /// Current PSG count.
     psg_count: usize,
     /// SID voice effect active per channel (bypasses normal waveform).
@@ -46,7 +57,14 @@ impl CaptureBuffer {
     pub fn new() -> Self {
         Self {
             waveform: WaveformSynthesizer::new(),
+            tapped: std::array::from_fn(|_| VecDeque::new()),
             spectrum: SpectrumAnalyzer::new(),
+            fft: (0..MAX_CHANNEL_COUNT)
+                .map(|_| {
+                    FftSpectrumAnalyzer::new(FftSpectrumConfig::default(), DEFAULT_SAMPLE_RATE)
+                })
+                .collect(),
+            fft_spectrum: [None; MAX_CHANNEL_COUNT],
             psg_count: 1,
             sid_active: [false; MAX_CHANNEL_COUNT],
             drum_active: [false; MAX_CHANNEL_COUNT],
@@ -93,7 +111,7 @@ impl CaptureBuffer {
         // Calculate spectrum velocity (rate of change) for dynamic brightness
         let channel_count = count * 3;
         for ch in 0..channel_count {
-            let current = self.spectrum.channel_spectrum(ch);
+            let current = *self.spectrum_channel(ch);
             for (bin, &cur_val) in current.iter().enumerate() {
                 // Calculate absolute change
                 let delta = (cur_val - self.prev_spectrum[ch][bin]).abs();
@@ -107,14 +125,49 @@ impl CaptureBuffer {
         }
     }
 
+    /// Update waveforms and FFT spectra from samples read from the backend.
+    ///
+    /// `taps[ch]` holds the latest samples of global channel `ch` (up to one
+    /// FFT window); channels with no tap data fall back to the synthesized
+    /// waveform and the register-based spectrum.
+    pub fn update_from_taps(&mut self, taps: &[Vec<f32>]) {
+        for (ch, tapped) in self.tapped.iter_mut().enumerate() {
+            tapped.clear();
+            self.fft_spectrum[ch] = None;
+            let Some(samples) = taps.get(ch).filter(|samples| !samples.is_empty()) else {
+                continue;
+            };
+            tapped.extend(&samples[samples.len().saturating_sub(WAVEFORM_SIZE)..]);
+
+            let analyzer = &mut self.fft[ch];
+            analyzer.push_samples(samples);
+            analyzer.update();
+            let mut bins = [0.0; SPECTRUM_BINS];
+            bins.copy_from_slice(analyzer.bins());
+            self.fft_spectrum[ch] = Some(bins);
+        }
+    }
+
     /// Get waveform samples for a channel (0-11 for multi-PSG).
-    pub fn waveform(&self, channel: usize) -> &std::collections::VecDeque<f32> {
-        self.waveform.channel_waveform(channel)
+    ///
+    /// Returns the tapped backend output if available, otherwise the
+    /// waveform synthesized from register values.
+    pub fn waveform(&self, channel: usize) -> &VecDeque<f32> {
+        match self.tapped.get(channel) {
+            Some(tapped) if !tapped.is_empty() => tapped,
+            _ => self.waveform.channel_waveform(channel),
+        }
     }
 
     /// Get spectrum for a specific channel (0-11 for multi-PSG).
+    ///
+    /// Returns the FFT spectrum of the tapped output if available, otherwise
+    /// the register-based spectrum.
     pub fn spectrum_channel(&self, channel: usize) -> &[f32; SPECTRUM_BINS] {
-        self.spectrum.channel_spectrum(channel)
+        match self.fft_spectrum.get(channel) {
+            Some(Some(bins)) => bins,
+            _ => self.spectrum.channel_spectrum(channel),
+        }
     }
 
     /// Get the current channel count.
@@ -157,7 +210,7 @@ impl CaptureBuffer {
 
         // Get the length of the shortest waveform
         let len = (0..channel_count)
-            .map(|ch| self.waveform.channel_waveform(ch).len())
+            .map(|ch| self.waveform(ch).len())
             .min()
             .unwrap_or(0);
 
@@ -169,13 +222,7 @@ impl CaptureBuffer {
         let mut mono = std::collections::VecDeque::with_capacity(len);
         for i in 0..len {
             let sum: f32 = (0..channel_count)
-                .map(|ch| {
-                    self.waveform
-                        .channel_waveform(ch)
-                        .get(i)
-                        .copied()
-                        .unwrap_or(0.0)
-                })
+                .map(|ch| self.waveform(ch).get(i).copied().unwrap_or(0.0))
                 .sum();
             // Normalize by channel count to prevent clipping
             mono.push_back(sum / channel_count as f32);
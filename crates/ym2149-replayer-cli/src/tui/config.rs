@@ -0,0 +1,263 @@
+//! TUI theme and layout configuration.
+//!
+//! Loaded from `--tui-config <file>`, or from `ym-replayer/tui.conf` in the
+//! user's config directory (`$XDG_CONFIG_HOME`, else `~/.config`) when it
+//! exists. The file holds `key = value` lines; comments start with `#` at
+//! the beginning of a line or with ` # ` after a value:
+//!
+//! ```text
+//! # Panes (on/off) and their share of the screen in percent
+//! oscilloscope = on
+//! spectrum = off
+//! notes = on
+//! visuals_height = 45
+//! oscilloscope_width = 60
+//! channels_width = 50
+//!
+//! # Colors: names (red, lightblue, ...), #rrggbb or 0-255 indices
+//! color.title = cyan
+//! color.time = yellow
+//! color.status = green
+//! color.channels = red, green, blue, yellow, cyan, magenta
+//! ```
+//!
+//! Unknown keys and invalid values are reported and skipped, so a typo never
+//! keeps the player from starting.
+
+use ratatui::style::Color;
+use std::path::{Path, PathBuf};
+
+/// Colors used across the TUI.
+#[derive(Clone, Debug)]
+pub struct Theme {
+    /// Song title in the header and info panel
+    pub title: Color,
+    /// Elapsed / total time
+    pub time: Color,
+    /// Playing / paused status
+    pub status: Color,
+    /// Per-channel colors (oscilloscope, gauges, note history); 3 per PSG
+    pub channels: [Color; 12],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            title: Color::Cyan,
+            time: Color::Yellow,
+            status: Color::Green,
+            channels: [
+                Color::Red,
+                Color::Green,
+                Color::Blue,
+                Color::Yellow,
+                Color::Cyan,
+                Color::Magenta,
+                Color::LightRed,
+                Color::LightGreen,
+                Color::LightBlue,
+                Color::LightYellow,
+                Color::LightCyan,
+                Color::LightMagenta,
+            ],
+        }
+    }
+}
+
+/// Which panes are shown and how the screen is split between them.
+#[derive(Clone, Debug)]
+pub struct LayoutConfig {
+    /// Oscilloscope and mono output pane
+    pub show_oscilloscope: bool,
+    /// Spectrum pane
+    pub show_spectrum: bool,
+    /// Song info pane with the note history table
+    pub show_notes: bool,
+    /// Height of the visualization row in percent of the content area
+    pub visuals_height: u16,
+    /// Width of the oscilloscope in percent of the visualization row
+    pub oscilloscope_width: u16,
+    /// Width of the channel gauges in percent of the bottom row
+    pub channels_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            show_oscilloscope: true,
+            show_spectrum: true,
+            show_notes: true,
+            visuals_height: 55,
+            oscilloscope_width: 60,
+            channels_width: 50,
+        }
+    }
+}
+
+/// Complete TUI configuration.
+#[derive(Clone, Debug, Default)]
+pub struct TuiConfig {
+    pub theme: Theme,
+    pub layout: LayoutConfig,
+}
+
+impl TuiConfig {
+    /// Load the configuration from `path`, or from the default location.
+    ///
+    /// Falls back to the defaults when no file is given and none exists at
+    /// the default location. Problems are printed to stderr.
+    pub fn load(path: Option<&str>) -> Self {
+        let path = match path {
+            Some(path) => PathBuf::from(path),
+            None => match default_path().filter(|path| path.exists()) {
+                Some(path) => path,
+                None => return Self::default(),
+            },
+        };
+        match std::fs::read_to_string(&path) {
+            Ok(text) => {
+                let (config, errors) = Self::parse(&text);
+                for error in errors {
+                    eprintln!("{}: {error}", path.display());
+                }
+                config
+            }
+            Err(e) => {
+                eprintln!("Failed to read TUI config {}: {e}", path.display());
+                Self::default()
+            }
+        }
+    }
+
+    /// Parse configuration text, returning the config and one message per bad line.
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let mut config = Self::default();
+        let mut errors = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let result = match line.split_once('=') {
+                Some((key, value)) => config.set(key.trim(), value.trim()),
+                None => Err("expected `key = value`".to_string()),
+            };
+            if let Err(e) = result {
+                errors.push(format!("line {}: {e}", number + 1));
+            }
+        }
+        (config, errors)
+    }
+
+    fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let layout = &mut self.layout;
+        let theme = &mut self.theme;
+        match key {
+            "oscilloscope" => layout.show_oscilloscope = parse_switch(value)?,
+            "spectrum" => layout.show_spectrum = parse_switch(value)?,
+            "notes" => layout.show_notes = parse_switch(value)?,
+            "visuals_height" => layout.visuals_height = parse_percent(value)?,
+            "oscilloscope_width" => layout.oscilloscope_width = parse_percent(value)?,
+            "channels_width" => layout.channels_width = parse_percent(value)?,
+            "color.title" => theme.title = parse_color(value)?,
+            "color.time" => theme.time = parse_color(value)?,
+            "color.status" => theme.status = parse_color(value)?,
+            "color.channels" => {
+                let colors = value
+                    .split(',')
+                    .map(|color| parse_color(color.trim()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if colors.is_empty() || colors.len() > 12 {
+                    return Err("color.channels takes 1 to 12 colors".to_string());
+                }
+                // Fewer colors than channels repeat
+                for (ch, slot) in theme.channels.iter_mut().enumerate() {
+                    *slot = colors[ch % colors.len()];
+                }
+            }
+            _ => return Err(format!("unknown key `{key}`")),
+        }
+        Ok(())
+    }
+}
+
+/// Default config file location, if a config directory can be determined.
+fn default_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+    Some(base.join("ym-replayer").join("tui.conf"))
+}
+
+/// Remove a comment: a line starting with `#`, or ` # ...` after a value
+/// (so `#rrggbb` colors survive).
+fn strip_comment(line: &str) -> &str {
+    if line.trim_start().starts_with('#') {
+        return "";
+    }
+    let bytes = line.as_bytes();
+    for (i, &b) in bytes.iter().enumerate() {
+        let after = bytes.get(i + 1).is_none_or(u8::is_ascii_whitespace);
+        if b == b'#' && i > 0 && bytes[i - 1].is_ascii_whitespace() && after {
+            return &line[..i];
+        }
+    }
+    line
+}
+
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "yes" | "1" => Ok(true),
+        "off" | "false" | "no" | "0" => Ok(false),
+        _ => Err(format!("expected on or off, got `{value}`")),
+    }
+}
+
+fn parse_percent(value: &str) -> Result<u16, String> {
+    match value.trim_end_matches('%').parse::<u16>() {
+        Ok(percent) if (10..=90).contains(&percent) => Ok(percent),
+        _ => Err(format!(
+            "expected a percentage from 10 to 90, got `{value}`"
+        )),
+    }
+}
+
+fn parse_color(value: &str) -> Result<Color, String> {
+    value
+        .parse::<Color>()
+        .map_err(|_| format!("unknown color `{value}`"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_layout_and_colors() {
+        let (config, errors) = TuiConfig::parse(
+            "# small terminal\n\
+             spectrum = off\n\
+             visuals_height = 40%\n\
+             color.title = #ff8000\n\
+             color.channels = red, lightblue  # repeats\n",
+        );
+        assert!(errors.is_empty(), "{errors:?}");
+        assert!(!config.layout.show_spectrum);
+        assert!(config.layout.show_oscilloscope);
+        assert_eq!(config.layout.visuals_height, 40);
+        assert_eq!(config.theme.title, Color::Rgb(0xff, 0x80, 0x00));
+        assert_eq!(config.theme.channels[2], Color::Red);
+        assert_eq!(config.theme.channels[3], Color::LightBlue);
+    }
+
+    #[test]
+    fn reports_bad_lines_and_keeps_defaults() {
+        let (config, errors) =
+            TuiConfig::parse("notes = maybe\nvisuals_height = 99\nfoo = 1\nbar\n");
+        assert_eq!(errors.len(), 4);
+        assert!(errors[0].starts_with("line 1:"));
+        assert!(config.layout.show_notes);
+        assert_eq!(config.layout.visuals_height, 55);
+    }
+}
@@ -6,8 +6,10 @@
 //! - Spectrum analyzer with frequency bars
 //! - Real-time playback status and controls
 //! - Playlist overlay for directory playback
+//! - Configurable colors and panes (see [`TuiConfig`])
 
 mod capture;
+mod config;
 mod mono_output;
 mod note_history;
 mod oscilloscope;
@@ -15,14 +17,17 @@ mod playlist_overlay;
 mod spectrum;
 
 pub use capture::CaptureBuffer;
+pub use config::TuiConfig;
 use note_history::NoteHistory;
 
 use crate::VisualSnapshot;
+use crate::command::PlayerCommand;
 use crate::playlist::Playlist;
+use crate::remote::{RemoteCommand, RemoteServer};
 use crate::streaming::StreamingContext;
 
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
 };
@@ -39,7 +44,18 @@ use std::io::{self, stdout};
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
 use std::time::{Duration, Instant};
-use ym2149_common::PlaybackState;
+use ym2149_common::{
+    AbMode, DEFAULT_FFT_SIZE, OutputModel, PitchMotion, PlaybackState, RegisterDelta,
+};
+
+/// Frames advanced by the large step key (one second at 50Hz)
+const LARGE_STEP_FRAMES: usize = 50;
+
+/// Seconds skipped by the seek keys (5% of the song if its duration is unknown)
+const SEEK_SECONDS: f32 = 5.0;
+
+/// Seconds skipped by the seek keys with Shift (30% if the duration is unknown)
+const LARGE_SEEK_SECONDS: f32 = 30.0;
 
 /// Minimum terminal size for TUI mode
 pub const MIN_COLS: u16 = 80;
@@ -60,6 +76,8 @@ pub struct App {
     pub capture: Arc<Mutex<CaptureBuffer>>,
     /// Current channel mute states
     pub mute_states: Vec<bool>,
+    /// Channel that is currently soloed (only audible channel)
+    pub solo_channel: Option<usize>,
     /// Song metadata
     pub title: String,
     pub author: String,
@@ -82,10 +100,20 @@ pub struct App {
     pub has_started_playback: bool,
     /// Master volume (0.0 - 1.0)
     pub volume: f32,
+    /// Analog output stage model
+    pub output_model: OutputModel,
+    /// A/B comparison output (None when not comparing)
+    pub ab_mode: Option<AbMode>,
     /// Note history for scrolling display
     pub note_history: NoteHistory,
     /// Last seek time for throttling (prevents stuttering when holding arrow keys)
     pub last_seek_time: Option<Instant>,
+    /// Register changes of the last frame step (cleared when playback resumes)
+    pub last_step: Option<RegisterDelta>,
+    /// Colors and panes (panes can be toggled at runtime)
+    pub config: TuiConfig,
+    /// Remote control address and token (None without `--remote`)
+    pub remote: Option<String>,
 }
 
 impl App {
@@ -93,6 +121,7 @@ impl App {
         Self {
             capture,
             mute_states: vec![false; 12],
+            solo_channel: None,
             title: String::new(),
             author: String::new(),
             format: String::new(),
@@ -112,8 +141,13 @@ impl App {
             show_playlist: false,
             has_started_playback: false,
             volume: 1.0,
+            output_model: OutputModel::default(),
+            ab_mode: None,
             note_history: NoteHistory::new(),
             last_seek_time: None,
+            last_step: None,
+            config: TuiConfig::default(),
+            remote: None,
         }
     }
 
@@ -174,38 +208,43 @@ impl App {
         // Get delayed snapshot for visualization (synced with audio output)
         let delayed_snapshot = context.get_delayed_snapshot();
 
-        let guard = context.player.lock();
-        self.is_playing = guard.state() == PlaybackState::Playing;
-        self.psg_count = guard.psg_count();
+        self.output_model = context.output_model();
+        let status = context.status();
+        self.is_playing = status.state == PlaybackState::Playing;
+        self.psg_count = status.psg_count;
+        self.last_step = status.last_step;
+        self.ab_mode = status.ab_mode;
 
         // Use player's elapsed_seconds if duration is known (supports seeking),
         // otherwise fallback to wallclock elapsed time
-        let player_duration = guard.duration_seconds();
+        let player_duration = status.duration_seconds;
         if player_duration > 0.0 {
-            self.elapsed = guard.elapsed_seconds();
+            self.elapsed = status.elapsed_seconds;
             self.duration = player_duration;
         } else {
             self.elapsed = elapsed;
         }
 
-        // Update mute states
-        let channel_count = guard.channel_count();
+        // Update mute states from the controls, which show toggles before
+        // the producer thread applies them
+        let channel_count = context.controls.channel_count();
         self.mute_states.resize(channel_count, false);
         for (ch, muted) in self.mute_states.iter_mut().enumerate() {
-            *muted = guard.is_channel_muted(ch);
+            *muted = context.controls.is_channel_muted(ch);
         }
+        self.solo_channel = context.controls.solo_channel();
 
         // Update subsong info
-        if guard.has_subsongs() {
-            self.subsong = Some((guard.current_subsong(), guard.subsong_count()));
+        if status.subsong.is_some() {
+            self.subsong = status.subsong;
         }
-        drop(guard);
 
         // Use delayed snapshot for visualization (syncs with audio output)
         self.snapshot = delayed_snapshot;
 
         // Update spectrum and waveforms from delayed register states
         let mut capture = self.capture.lock();
+        capture.update_from_taps(&status.taps);
         capture.update_from_registers(
             &self.snapshot.registers,
             self.psg_count,
@@ -302,6 +341,8 @@ pub fn run_tui_loop_with_playlist(
     metadata: SongMetadata,
     playlist: Option<Playlist>,
     player_loader: Option<PlayerLoader>,
+    config: TuiConfig,
+    remote: Option<RemoteServer>,
 ) -> io::Result<()> {
     // Setup terminal
     enable_raw_mode()?;
@@ -320,6 +361,10 @@ pub fn run_tui_loop_with_playlist(
 
     // Create app state
     let mut app = App::new(capture);
+    app.config = config;
+    app.remote = remote
+        .as_ref()
+        .map(|server| format!("{} token {}", server.local_addr(), server.token()));
 
     // Set metadata from player info
     app.title = metadata.title;
@@ -339,10 +384,10 @@ pub fn run_tui_loop_with_playlist(
     }
 
     // Get initial player state
-    {
-        let guard = context.player.lock();
-        app.psg_count = guard.psg_count();
-    }
+    app.psg_count = context.status().psg_count;
+    // Real channel output (one FFT window) for the oscilloscope and spectrum;
+    // the producer thread keeps the tap on players swapped in from the playlist
+    context.send(PlayerCommand::SetChannelTap(Some(DEFAULT_FFT_SIZE)));
 
     let mut playback_start = Instant::now();
     let frame_duration = Duration::from_millis(33); // ~30 FPS
@@ -460,27 +505,59 @@ pub fn run_tui_loop_with_playlist(
                                 app.toggle_playlist();
                             }
                             KeyCode::Char(' ') => {
-                                let mut guard = context.player.lock();
-                                match guard.state() {
-                                    PlaybackState::Playing => guard.pause(),
-                                    _ => guard.play(),
-                                }
+                                context.send(PlayerCommand::TogglePause);
+                            }
+                            // Frame stepping while paused: f = 1 frame, F = 1 second
+                            KeyCode::Char(c @ ('f' | 'F')) => {
+                                let frames = if c == 'f' { 1 } else { LARGE_STEP_FRAMES };
+                                context.send(PlayerCommand::StepFrames(frames));
                             }
                             KeyCode::Char(c @ '1'..='9') => {
                                 let ch = (c as u8 - b'1') as usize;
-                                let mut guard = context.player.lock();
-                                if ch < guard.channel_count() {
-                                    let muted = guard.is_channel_muted(ch);
-                                    guard.set_channel_mute(ch, !muted);
-                                }
+                                context.controls.toggle_channel_mute(ch);
                             }
                             KeyCode::Char('0') => {
-                                let mut guard = context.player.lock();
-                                if guard.channel_count() > 9 {
-                                    let muted = guard.is_channel_muted(9);
-                                    guard.set_channel_mute(9, !muted);
+                                context.controls.toggle_channel_mute(9);
+                            }
+                            // Solo: cycle through all channels, then back to all audible
+                            KeyCode::Char('s') | KeyCode::Char('S') => {
+                                let controls = &context.controls;
+                                let next = controls.solo_channel().map_or(0, |ch| ch + 1);
+                                if next < controls.channel_count() {
+                                    controls.set_channel_solo(next);
+                                } else {
+                                    controls.clear_solo();
+                                }
+                            }
+                            // Output model: cycle raw -> stf -> ste -> cpc
+                            KeyCode::Char('o') | KeyCode::Char('O') => {
+                                let current = context.output_model();
+                                let index = OutputModel::ALL
+                                    .iter()
+                                    .position(|&model| model == current)
+                                    .unwrap_or(0);
+                                let next = OutputModel::ALL[(index + 1) % OutputModel::ALL.len()];
+                                context.set_output_model(next);
+                            }
+                            // A/B comparison: cycle a -> b -> diff
+                            KeyCode::Char('a') | KeyCode::Char('A') => {
+                                if let Some(mode) = app.ab_mode {
+                                    context.set_ab_mode(mode.next());
                                 }
                             }
+                            // Pane toggles: w = oscilloscope, x = spectrum, n = notes
+                            KeyCode::Char('w') => {
+                                let layout = &mut app.config.layout;
+                                layout.show_oscilloscope = !layout.show_oscilloscope;
+                            }
+                            KeyCode::Char('x') => {
+                                let layout = &mut app.config.layout;
+                                layout.show_spectrum = !layout.show_spectrum;
+                            }
+                            KeyCode::Char('n') => {
+                                let layout = &mut app.config.layout;
+                                layout.show_notes = !layout.show_notes;
+                            }
                             // Volume control: Up/Down arrows
                             KeyCode::Up => {
                                 app.volume_up();
@@ -490,51 +567,37 @@ pub fn run_tui_loop_with_playlist(
                                 app.volume_down();
                                 context.set_volume(app.volume);
                             }
-                            // Seeking: Left/Right arrows (±5 seconds, throttled)
-                            KeyCode::Left => {
+                            // Seeking: Left/Right or h/l (±5 seconds), with
+                            // Shift (or H/L) ±30 seconds; throttled
+                            KeyCode::Left
+                            | KeyCode::Right
+                            | KeyCode::Char('h' | 'l' | 'H' | 'L') => {
                                 if app.can_seek() {
-                                    let mut guard = context.player.lock();
-                                    let current_pos = guard.playback_position();
-                                    let duration = guard.duration_seconds();
-                                    let seek_amount =
-                                        if duration > 0.0 { 5.0 / duration } else { 0.05 };
-                                    let new_pos = (current_pos - seek_amount).max(0.0);
-                                    if guard.seek(new_pos) {
-                                        app.mark_seek();
-                                    }
-                                }
-                            }
-                            KeyCode::Right => {
-                                if app.can_seek() {
-                                    let mut guard = context.player.lock();
-                                    let current_pos = guard.playback_position();
-                                    let duration = guard.duration_seconds();
-                                    let seek_amount =
-                                        if duration > 0.0 { 5.0 / duration } else { 0.05 };
-                                    let new_pos = (current_pos + seek_amount).min(1.0);
-                                    if guard.seek(new_pos) {
-                                        app.mark_seek();
-                                    }
+                                    let large = key.modifiers.contains(KeyModifiers::SHIFT)
+                                        || matches!(key.code, KeyCode::Char('H' | 'L'));
+                                    let backward = matches!(
+                                        key.code,
+                                        KeyCode::Left | KeyCode::Char('h' | 'H')
+                                    );
+                                    let seconds = if large {
+                                        LARGE_SEEK_SECONDS
+                                    } else {
+                                        SEEK_SECONDS
+                                    };
+                                    context.send(PlayerCommand::SeekBy(if backward {
+                                        -seconds
+                                    } else {
+                                        seconds
+                                    }));
+                                    app.mark_seek();
                                 }
                             }
                             // Subsong navigation: +/= for next, -/_ for previous
                             KeyCode::Char('+') | KeyCode::Char('=') => {
-                                let mut guard = context.player.lock();
-                                if guard.has_subsongs() {
-                                    let current = guard.current_subsong();
-                                    let count = guard.subsong_count();
-                                    let next = if current >= count { 1 } else { current + 1 };
-                                    guard.set_subsong(next);
-                                }
+                                context.send(PlayerCommand::NextSubsong);
                             }
                             KeyCode::Char('-') | KeyCode::Char('_') => {
-                                let mut guard = context.player.lock();
-                                if guard.has_subsongs() {
-                                    let current = guard.current_subsong();
-                                    let count = guard.subsong_count();
-                                    let prev = if current <= 1 { count } else { current - 1 };
-                                    guard.set_subsong(prev);
-                                }
+                                context.send(PlayerCommand::PreviousSubsong);
                             }
                             // Next/Previous song in playlist
                             KeyCode::Char(']') | KeyCode::Char('>') | KeyCode::Char('.') => {
@@ -572,6 +635,16 @@ pub fn run_tui_loop_with_playlist(
             }
         }
 
+        // Apply commands from the remote control server
+        while let Some(request) = remote.as_ref().and_then(RemoteServer::try_recv) {
+            let result =
+                apply_remote_command(&request.command, context, &mut app, player_loader.as_ref());
+            if matches!(result, Ok(true)) {
+                playback_start = Instant::now();
+            }
+            request.reply(result.map(|_| ()));
+        }
+
         // Check if we should exit
         if !context.running.load(Ordering::Relaxed) {
             break;
@@ -582,23 +655,19 @@ pub fn run_tui_loop_with_playlist(
 
         // Auto-advance to next song when current song ends (playlist mode only)
         // Only auto-advance if user has already selected and played a song
-        if app.has_playlist() && !app.show_playlist && app.has_started_playback {
-            let is_stopped = {
-                let guard = context.player.lock();
-                guard.state() == PlaybackState::Stopped
-            };
-
-            if is_stopped
-                && let Some(ref mut pl) = app.playlist
-                && let Some(path) = pl.selected_path()
-                && let Some(ref loader) = player_loader
-                && let Some((new_player, new_meta)) = loader(path)
-            {
-                pl.select_next();
-                context.replace_player(new_player);
-                app.update_from_metadata(new_meta);
-                playback_start = Instant::now();
-            }
+        if app.has_playlist()
+            && !app.show_playlist
+            && app.has_started_playback
+            && context.song_finished()
+            && let Some(ref mut pl) = app.playlist
+            && let Some(path) = pl.selected_path()
+            && let Some(ref loader) = player_loader
+            && let Some((new_player, new_meta)) = loader(path)
+        {
+            pl.select_next();
+            context.replace_player(new_player);
+            app.update_from_metadata(new_meta);
+            playback_start = Instant::now();
         }
 
         // Draw UI
@@ -619,6 +688,76 @@ pub fn run_tui_loop_with_playlist(
     Ok(())
 }
 
+/// Apply a remote control command; `Ok(true)` when a new song was loaded.
+fn apply_remote_command(
+    command: &RemoteCommand,
+    context: &StreamingContext,
+    app: &mut App,
+    player_loader: Option<&PlayerLoader>,
+) -> Result<bool, String> {
+    let state = context.status().state;
+    match command {
+        RemoteCommand::Play | RemoteCommand::Pause => {
+            let play = *command == RemoteCommand::Play;
+            if (state == PlaybackState::Playing) != play {
+                context.send(PlayerCommand::TogglePause);
+            }
+            Ok(false)
+        }
+        RemoteCommand::TogglePause => {
+            context.send(PlayerCommand::TogglePause);
+            Ok(false)
+        }
+        RemoteCommand::SeekBy(seconds) => {
+            context.send(PlayerCommand::SeekBy(*seconds));
+            Ok(false)
+        }
+        RemoteCommand::SeekTo(seconds) => {
+            let elapsed = context.status().elapsed_seconds;
+            context.send(PlayerCommand::SeekBy(seconds - elapsed));
+            Ok(false)
+        }
+        RemoteCommand::Quit => {
+            context.running.store(false, Ordering::Relaxed);
+            Ok(false)
+        }
+        RemoteCommand::Next | RemoteCommand::Previous => {
+            let pl = app.playlist.as_mut().ok_or("no playlist")?;
+            if *command == RemoteCommand::Next {
+                pl.select_next();
+            } else {
+                pl.select_previous();
+            }
+            let path = pl.selected_path().ok_or("playlist is empty")?.to_path_buf();
+            load_remote_song(context, app, player_loader, &path)
+        }
+        RemoteCommand::Load(path) => {
+            // Keep the playlist cursor on the song when it is part of the playlist
+            if let Some(pl) = app.playlist.as_mut()
+                && let Some(index) = pl.entries.iter().position(|entry| entry.path == *path)
+            {
+                pl.selected = index;
+            }
+            load_remote_song(context, app, player_loader, path)
+        }
+    }
+}
+
+fn load_remote_song(
+    context: &StreamingContext,
+    app: &mut App,
+    player_loader: Option<&PlayerLoader>,
+    path: &std::path::Path,
+) -> Result<bool, String> {
+    let loader = player_loader.ok_or("loading songs is not available")?;
+    let (new_player, new_meta) =
+        loader(path).ok_or_else(|| format!("failed to load {}", path.display()))?;
+    context.replace_player(new_player);
+    app.update_from_metadata(new_meta);
+    app.show_playlist = false;
+    Ok(true)
+}
+
 /// Draw the main UI
 fn draw_ui(f: &mut Frame, app: &App) {
     let area = f.area();
@@ -661,17 +800,18 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
 
     let elapsed_str = format_time(app.elapsed);
     let duration_str = format_time(app.duration);
+    let theme = &app.config.theme;
 
     let header_text = vec![Line::from(vec![
         Span::styled(" ", Style::default()),
-        Span::styled(&title, Style::default().fg(Color::Cyan).bold()),
+        Span::styled(&title, Style::default().fg(theme.title).bold()),
         Span::raw("  "),
         Span::styled(
             format!("{elapsed_str} / {duration_str}"),
-            Style::default().fg(Color::Yellow),
+            Style::default().fg(theme.time),
         ),
         Span::raw("  "),
-        Span::styled(status, Style::default().fg(Color::Green)),
+        Span::styled(status, Style::default().fg(theme.status)),
     ])];
 
     let header = Paragraph::new(header_text).block(
@@ -684,57 +824,85 @@ fn draw_header(f: &mut Frame, area: Rect, app: &App) {
 }
 
 /// Draw main content with oscilloscope, mono output, spectrum, channels, and song info
+///
+/// Hidden panes give their space to the remaining ones.
 fn draw_content(f: &mut Frame, area: Rect, app: &App) {
+    let layout = &app.config.layout;
+    let show_visuals = layout.show_oscilloscope || layout.show_spectrum;
+
     // Split vertically: visualizations on top, channels + info on bottom
+    let visuals_height = if show_visuals {
+        layout.visuals_height
+    } else {
+        0
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage(55), // Oscilloscope + Mono + Spectrum
-            Constraint::Percentage(45), // Channels + Song Info
+            Constraint::Percentage(visuals_height), // Oscilloscope + Mono + Spectrum
+            Constraint::Percentage(100 - visuals_height), // Channels + Song Info
         ])
         .split(area);
 
-    // Split top section: oscilloscope/mono left, spectrum right
-    let top_chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(60), // Oscilloscope + Mono Output
-            Constraint::Percentage(40), // Spectrum
-        ])
-        .split(chunks[0]);
-
-    // Split left section: oscilloscope on top, mono output below
-    let left_chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(75), // Oscilloscope (per-channel)
-            Constraint::Percentage(25), // Mono Output (mixed)
-        ])
-        .split(top_chunks[0]);
-
-    // Draw oscilloscope
-    oscilloscope::draw_oscilloscope(f, left_chunks[0], app);
-
-    // Draw mono output
-    mono_output::draw_mono_output(f, left_chunks[1], app);
+    if show_visuals {
+        // Split top section: oscilloscope/mono left, spectrum right
+        let scope_width = match (layout.show_oscilloscope, layout.show_spectrum) {
+            (true, true) => layout.oscilloscope_width,
+            (true, false) => 100,
+            _ => 0,
+        };
+        let top_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([
+                Constraint::Percentage(scope_width), // Oscilloscope + Mono Output
+                Constraint::Percentage(100 - scope_width), // Spectrum
+            ])
+            .split(chunks[0]);
+
+        if layout.show_oscilloscope {
+            // Split left section: oscilloscope on top, mono output below
+            let left_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Percentage(75), // Oscilloscope (per-channel)
+                    Constraint::Percentage(25), // Mono Output (mixed)
+                ])
+                .split(top_chunks[0]);
+
+            // Draw oscilloscope
+            oscilloscope::draw_oscilloscope(f, left_chunks[0], app);
+
+            // Draw mono output
+            mono_output::draw_mono_output(f, left_chunks[1], app);
+        }
 
-    // Draw spectrum
-    spectrum::draw_spectrum(f, top_chunks[1], app);
+        if layout.show_spectrum {
+            // Draw spectrum
+            spectrum::draw_spectrum(f, top_chunks[1], app);
+        }
+    }
 
     // Split bottom section: channels left, song info right
+    let channels_width = if layout.show_notes {
+        layout.channels_width
+    } else {
+        100
+    };
     let bottom_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(50), // Channels
-            Constraint::Percentage(50), // Song Info
+            Constraint::Percentage(channels_width),       // Channels
+            Constraint::Percentage(100 - channels_width), // Song Info
         ])
         .split(chunks[1]);
 
     // Draw channel info
     draw_channels(f, bottom_chunks[0], app);
 
-    // Draw song info
-    draw_song_info(f, bottom_chunks[1], app);
+    if layout.show_notes {
+        // Draw song info
+        draw_song_info(f, bottom_chunks[1], app);
+    }
 }
 
 /// Draw channel volume bars and info
@@ -752,7 +920,7 @@ fn draw_channels(f: &mut Frame, area: Rect, app: &App) {
     };
 
     let channel_names = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"];
-    let colors = [Color::Red, Color::Green, Color::Blue];
+    let colors = &app.config.theme.channels;
 
     for psg_idx in 0..app.psg_count {
         let regs = &app.snapshot.registers[psg_idx];
@@ -770,11 +938,18 @@ fn draw_channels(f: &mut Frame, area: Rect, app: &App) {
 
             let amplitude = (regs[8 + local_ch] & 0x0F) as f64 / 15.0;
             let muted = app.mute_states.get(global_ch).copied().unwrap_or(false);
+            let marker = if app.solo_channel == Some(global_ch) {
+                "(S)"
+            } else if muted {
+                "(M)"
+            } else {
+                "   "
+            };
 
             let label = format!(
                 " {}{} ",
                 channel_names.get(global_ch).unwrap_or(&"?"),
-                if muted { "(M)" } else { "   " }
+                marker
             );
 
             let gauge = Gauge::default()
@@ -784,7 +959,7 @@ fn draw_channels(f: &mut Frame, area: Rect, app: &App) {
                         .fg(if muted {
                             Color::DarkGray
                         } else {
-                            colors[local_ch % 3]
+                            colors[global_ch % 12]
                         })
                         .bg(Color::Black),
                 )
@@ -834,7 +1009,7 @@ fn draw_song_metadata(f: &mut Frame, area: Rect, app: &App) {
     if !app.title.is_empty() {
         let mut spans = vec![Span::styled(
             &app.title,
-            Style::default().fg(Color::Cyan).bold(),
+            Style::default().fg(app.config.theme.title).bold(),
         )];
         if !app.author.is_empty() {
             spans.push(Span::raw(" by "));
@@ -879,20 +1054,7 @@ fn draw_note_history_table(f: &mut Frame, area: Rect, app: &App) {
 
     // Channel labels and colors
     let channel_labels = ["A", "B", "C", "D", "E", "F", "G", "H", "I", "J", "K", "L"];
-    let channel_colors = [
-        Color::Red,
-        Color::Green,
-        Color::Blue,
-        Color::Yellow,
-        Color::Cyan,
-        Color::Magenta,
-        Color::LightRed,
-        Color::LightGreen,
-        Color::LightBlue,
-        Color::LightYellow,
-        Color::LightCyan,
-        Color::LightMagenta,
-    ];
+    let channel_colors = &app.config.theme.channels;
 
     // Fixed column width: "NOTE FREQ" = 4 + 1 + 5 = 10 chars per column
     let col_width = 10;
@@ -964,7 +1126,14 @@ fn draw_note_history_table(f: &mut Frame, area: Rect, app: &App) {
                     } else {
                         format!("{:>5}", format!("{:.0}", note.freq))
                     };
-                    format!("{note_str} {freq_str}")
+                    // Separator doubles as vibrato/slide marker
+                    let marker = match note.motion {
+                        PitchMotion::Steady => ' ',
+                        PitchMotion::Vibrato => '~',
+                        PitchMotion::SlideUp => '↗',
+                        PitchMotion::SlideDown => '↘',
+                    };
+                    format!("{note_str}{marker}{freq_str}")
                 } else {
                     format!("{:^width$}", "---", width = col_width)
                 }
@@ -996,7 +1165,12 @@ fn draw_note_history_table(f: &mut Frame, area: Rect, app: &App) {
 /// Draw footer with controls help
 fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
     // Build controls string based on available features
-    let mut controls = String::from("[1-9] Mute  [Space] Pause  [↑↓] Vol  [←→] Seek");
+    let mut controls =
+        String::from("[1-9] Mute  [s] Solo  [Space] Pause  [↑↓] Vol  [←→/hl] Seek  [⇧] ±30s");
+
+    if !app.is_playing {
+        controls.push_str("  [f/F] Step");
+    }
 
     if app.has_playlist() {
         controls.push_str("  [,/.] Prev/Next  [p] Playlist");
@@ -1006,9 +1180,20 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
         controls.push_str("  [+/-] Subsong");
     }
 
-    controls.push_str("  [q] Quit");
+    if app.ab_mode.is_some() {
+        controls.push_str("  [a] A/B");
+    }
+
+    controls.push_str("  [w/x/n] Panes  [q] Quit");
 
-    let volume_info = format!("  Vol: {}%", (app.volume * 100.0) as u32);
+    let mut volume_info = format!(
+        "  Vol: {}%  Out: {}",
+        (app.volume * 100.0) as u32,
+        app.output_model
+    );
+    if let Some(mode) = app.ab_mode {
+        volume_info.push_str(&format!("  A/B: {mode}"));
+    }
 
     let subsong_info = app
         .subsong
@@ -1021,17 +1206,52 @@ fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
         .map(|pl| format!("  [{} songs]", pl.len()))
         .unwrap_or_default();
 
+    let step_info = app.last_step.as_ref().map(format_step).unwrap_or_default();
+
+    let remote_info = app
+        .remote
+        .as_ref()
+        .map(|remote| format!("  Remote: {remote}"))
+        .unwrap_or_default();
+
     let footer = Paragraph::new(Line::from(vec![
         Span::styled(controls, Style::default().fg(Color::DarkGray)),
         Span::styled(volume_info, Style::default().fg(Color::Green)),
         Span::styled(subsong_info, Style::default().fg(Color::Yellow)),
         Span::styled(playlist_info, Style::default().fg(Color::Cyan)),
+        Span::styled(step_info, Style::default().fg(Color::Magenta)),
+        Span::styled(remote_info, Style::default().fg(Color::DarkGray)),
     ]))
     .block(Block::default().borders(Borders::ALL));
 
     f.render_widget(footer, area);
 }
 
+/// Format the register changes of a frame step (e.g. "  Step: R0 1C→2A R8 0F→0C")
+fn format_step(delta: &RegisterDelta) -> String {
+    let multi_psg = delta.after.len() > 1;
+    let changes: Vec<String> = delta
+        .changes()
+        .map(|change| {
+            let psg = if multi_psg {
+                format!("{}:", change.psg)
+            } else {
+                String::new()
+            };
+            format!(
+                "{psg}R{} {:02X}→{:02X}",
+                change.register, change.old, change.new
+            )
+        })
+        .collect();
+
+    if changes.is_empty() {
+        "  Step: no register changes".to_string()
+    } else {
+        format!("  Step: {}", changes.join(" "))
+    }
+}
+
 /// Format seconds as MM:SS
 fn format_time(seconds: f32) -> String {
     // Guard against NaN, infinity, or negative values
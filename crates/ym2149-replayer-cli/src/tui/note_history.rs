@@ -4,6 +4,7 @@
 //! visualization in the Song Info panel.
 
 use std::collections::VecDeque;
+use ym2149_common::{PitchMotion, PitchTracker};
 
 /// Number of notes to show (4 before + 1 current + 4 after = 9 visible)
 pub const HISTORY_SIZE: usize = 9;
@@ -15,12 +16,18 @@ pub struct NoteEntry {
     pub note: String,
     /// Frequency in Hz (0.0 for silence)
     pub freq: f32,
+    /// Vibrato or slide seen while the note played
+    pub motion: PitchMotion,
 }
 
 impl NoteEntry {
     /// Create a new note entry.
     pub fn new(note: String, freq: f32) -> Self {
-        Self { note, freq }
+        Self {
+            note,
+            freq,
+            motion: PitchMotion::Steady,
+        }
     }
 
     /// Create a silence entry.
@@ -28,6 +35,7 @@ impl NoteEntry {
         Self {
             note: "---".to_string(),
             freq: 0.0,
+            motion: PitchMotion::Steady,
         }
     }
 }
@@ -43,6 +51,8 @@ pub struct ChannelHistory {
     last_freq: f32,
     /// Last seen envelope shape (kept until a new one is set)
     last_envelope_shape: Option<String>,
+    /// Vibrato/slide detector over recent frames
+    tracker: PitchTracker,
 }
 
 impl Default for ChannelHistory {
@@ -64,11 +74,15 @@ impl ChannelHistory {
             current_idx: HISTORY_SIZE / 2, // Middle position
             last_freq: 0.0,
             last_envelope_shape: None,
+            tracker: PitchTracker::new(),
         }
     }
 
     /// Update with a new note. Only adds if frequency changed significantly.
     ///
+    /// Pitch changes that form a vibrato or slide mark the current note
+    /// instead of adding new ones.
+    ///
     /// `has_output` should be true if the channel is producing sound (amplitude > 0 OR envelope_enabled).
     /// `envelope_shape` should be Some("shape") if envelope is enabled for this note.
     pub fn update(
@@ -93,8 +107,13 @@ impl ChannelHistory {
         };
 
         let is_note_on = has_output && freq > 0.0;
+        let motion = self.tracker.push(is_note_on.then_some(freq));
 
-        if freq_changed && is_note_on {
+        if motion != PitchMotion::Steady {
+            if let Some(current) = self.notes.back_mut() {
+                current.motion = motion;
+            }
+        } else if freq_changed && is_note_on {
             // Push new note
             self.notes.push_back(NoteEntry::new(note.to_string(), freq));
 
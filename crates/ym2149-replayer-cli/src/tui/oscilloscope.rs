@@ -15,30 +15,6 @@ use ratatui::{
     },
 };
 
-/// Channel colors - cycles through for multi-PSG
-/// PSG 0: Red, Green, Blue
-/// PSG 1: Yellow, Cyan, Magenta
-/// PSG 2: LightRed, LightGreen, LightBlue
-/// PSG 3: LightYellow, LightCyan, LightMagenta
-const CHANNEL_COLORS: [Color; 12] = [
-    // PSG 0
-    Color::Red,
-    Color::Green,
-    Color::Blue,
-    // PSG 1
-    Color::Yellow,
-    Color::Cyan,
-    Color::Magenta,
-    // PSG 2
-    Color::LightRed,
-    Color::LightGreen,
-    Color::LightBlue,
-    // PSG 3
-    Color::LightYellow,
-    Color::LightCyan,
-    Color::LightMagenta,
-];
-
 /// Channel labels for multi-PSG
 const CHANNEL_LABELS: [&str; 12] = [
     "A", "B", "C", // PSG 0
@@ -166,7 +142,7 @@ pub fn draw_oscilloscope(f: &mut Frame, area: Rect, app: &App) {
         .y_bounds([0.0, y_bounds])
         .paint(|ctx| {
             for (ch, (centered, _)) in processed.iter().enumerate() {
-                let color = CHANNEL_COLORS[ch % 12];
+                let color = app.config.theme.channels[ch % 12];
                 // Channels from top to bottom (reversed index)
                 let y_base = (channel_count - 1 - ch) as f64 + 0.5;
 
@@ -8,16 +8,14 @@
 
 use crate::audio::VISUALIZATION_UPDATE_MS;
 use crate::viz_helpers::{create_channel_status, create_volume_bar};
-use parking_lot::Mutex;
 use std::io::{self, Read, Write};
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Instant;
-use ym2149_common::{channel_period, period_to_frequency};
-use ym2149_ym_replayer::PlaybackState;
+use ym2149_common::{PlayerHandle, channel_period, period_to_frequency};
 
+use crate::command::{PlayerCommand, PlayerStatus};
 use crate::streaming::StreamingContext;
-use crate::{RealtimeChip, VisualSnapshot};
 
 const NOTE_NAMES: [&str; 12] = [
     "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
@@ -126,14 +124,12 @@ fn restore_terminal_mode() {}
 /// - Restores terminal on exit
 pub fn run_visualization_loop(context: &StreamingContext) {
     // Check if player has subsongs and get PSG count
-    let (has_subsongs, psg_count, channel_count) = {
-        let guard = context.player.lock();
-        (
-            guard.has_subsongs(),
-            guard.psg_count(),
-            guard.channel_count(),
-        )
-    };
+    let status = context.status();
+    let (has_subsongs, psg_count, channel_count) = (
+        status.subsong.is_some(),
+        status.psg_count,
+        status.channel_count,
+    );
 
     // Build mute keys help based on channel count
     let mute_keys = if channel_count <= 3 {
@@ -182,37 +178,28 @@ pub fn run_visualization_loop(context: &StreamingContext) {
         // Process keyboard input
         while let Ok(byte) = rx.try_recv() {
             if let Some(event) = escape_state.process(byte) {
-                handle_key_press(event, &context.player, &context.running);
+                handle_key_press(event, context);
             }
         }
 
         // Get current state
         let stats = context.streamer.get_stats();
-        let (elapsed, snapshot, subsong_info) = {
-            let guard = context.player.lock();
-            // Use player's elapsed_seconds if duration is known (supports seeking),
-            // otherwise fallback to wallclock elapsed time
-            let elapsed = if guard.duration_seconds() > 0.0 {
-                guard.elapsed_seconds()
-            } else {
-                playback_start.elapsed().as_secs_f32()
-            };
-            let ss_info = if guard.has_subsongs() {
-                Some((guard.current_subsong(), guard.subsong_count()))
-            } else {
-                None
-            };
-            (elapsed, guard.visual_snapshot(), ss_info)
+        let status = context.status();
+        // Use player's elapsed_seconds if duration is known (supports seeking),
+        // otherwise fallback to wallclock elapsed time
+        let elapsed = if status.duration_seconds > 0.0 {
+            status.elapsed_seconds
+        } else {
+            playback_start.elapsed().as_secs_f32()
         };
 
         // Display visualization
         display_frame(
-            &snapshot,
-            &context.player,
+            &status,
+            &context.controls,
             &stats,
             elapsed,
             context.streamer.fill_percentage(),
-            subsong_info,
         );
 
         if !context.running.load(Ordering::Relaxed) {
@@ -312,58 +299,30 @@ enum KeyEvent {
 }
 
 /// Handle keyboard input.
-fn handle_key_press(
-    event: KeyEvent,
-    player: &Arc<Mutex<Box<dyn RealtimeChip>>>,
-    running: &Arc<AtomicBool>,
-) {
+fn handle_key_press(event: KeyEvent, context: &StreamingContext) {
+    let controls = &context.controls;
     match event {
         KeyEvent::Regular(key) => match key {
             // Channel mute: 1-9 for channels 0-8, 0 for channel 9
             b'1'..=b'9' => {
-                let ch = (key - b'1') as usize;
-                let mut guard = player.lock();
-                if ch < guard.channel_count() {
-                    let muted = guard.is_channel_muted(ch);
-                    guard.set_channel_mute(ch, !muted);
-                }
+                controls.toggle_channel_mute((key - b'1') as usize);
             }
             b'0' => {
                 // Channel 10 (index 9)
-                let mut guard = player.lock();
-                if guard.channel_count() > 9 {
-                    let muted = guard.is_channel_muted(9);
-                    guard.set_channel_mute(9, !muted);
-                }
+                controls.toggle_channel_mute(9);
             }
             b' ' => {
-                let mut guard = player.lock();
-                match guard.state() {
-                    PlaybackState::Playing => guard.pause(),
-                    PlaybackState::Paused | PlaybackState::Stopped => guard.play(),
-                }
+                context.send(PlayerCommand::TogglePause);
             }
             b'q' | b'Q' => {
-                running.store(false, Ordering::Relaxed);
+                context.running.store(false, Ordering::Relaxed);
             }
             // Subsong navigation: + or = for next, - or _ for previous
             b'+' | b'=' => {
-                let mut guard = player.lock();
-                if guard.has_subsongs() {
-                    let current = guard.current_subsong();
-                    let count = guard.subsong_count();
-                    let next = if current >= count { 1 } else { current + 1 };
-                    guard.set_subsong(next);
-                }
+                context.send(PlayerCommand::NextSubsong);
             }
             b'-' | b'_' => {
-                let mut guard = player.lock();
-                if guard.has_subsongs() {
-                    let current = guard.current_subsong();
-                    let count = guard.subsong_count();
-                    let prev = if current <= 1 { count } else { current - 1 };
-                    guard.set_subsong(prev);
-                }
+                context.send(PlayerCommand::PreviousSubsong);
             }
             _ => {}
         },
@@ -377,29 +336,11 @@ fn handle_key_press(
         // Seeking: Left/Right arrows
         KeyEvent::ArrowLeft => {
             // Seek backward 5 seconds (or 5% if duration unknown)
-            let mut guard = player.lock();
-            let current_pos = guard.playback_position();
-            let duration = guard.duration_seconds();
-            let seek_amount = if duration > 0.0 {
-                5.0 / duration // 5 seconds as fraction
-            } else {
-                0.05 // 5%
-            };
-            let new_pos = (current_pos - seek_amount).max(0.0);
-            guard.seek(new_pos);
+            context.send(PlayerCommand::SeekBy(-5.0));
         }
         KeyEvent::ArrowRight => {
             // Seek forward 5 seconds (or 5% if duration unknown)
-            let mut guard = player.lock();
-            let current_pos = guard.playback_position();
-            let duration = guard.duration_seconds();
-            let seek_amount = if duration > 0.0 {
-                5.0 / duration // 5 seconds as fraction
-            } else {
-                0.05 // 5%
-            };
-            let new_pos = (current_pos + seek_amount).min(1.0);
-            guard.seek(new_pos);
+            context.send(PlayerCommand::SeekBy(5.0));
         }
     }
 }
@@ -462,30 +403,25 @@ fn extract_channel_data(regs: &[u8; 16], channel: usize) -> ChannelData {
 
 /// Display a single visualization frame.
 fn display_frame(
-    snapshot: &VisualSnapshot,
-    player: &Arc<Mutex<Box<dyn RealtimeChip>>>,
+    status: &PlayerStatus,
+    controls: &PlayerHandle,
     stats: &crate::audio::PlaybackStats,
     elapsed: f32,
     fill_pct: f32,
-    subsong_info: Option<(usize, usize)>,
 ) {
+    let subsong_info = status.subsong;
     // Clone and detect effects from registers if not already set
-    let mut snapshot = *snapshot;
+    let mut snapshot = status.snapshot;
     snapshot.detect_effects_from_registers();
 
     let psg_count = snapshot.psg_count;
     let sync_buzzer_active = snapshot.sync_buzzer;
 
     // Get mute states and position
-    let (mute_states, pos_pct) = {
-        let guard = player.lock();
-        let channel_count = guard.channel_count();
-        let mutes: Vec<bool> = (0..channel_count)
-            .map(|ch| guard.is_channel_muted(ch))
-            .collect();
-        let pos = (guard.playback_position() * 100.0).clamp(0.0, 100.0);
-        (mutes, pos)
-    };
+    let mute_states: Vec<bool> = (0..status.channel_count)
+        .map(|ch| controls.is_channel_muted(ch))
+        .collect();
+    let pos_pct = (status.position * 100.0).clamp(0.0, 100.0);
 
     // Move cursor up: 1 status line + 3 lines per PSG + separators between PSGs
     let separator_lines = psg_count.saturating_sub(1);
@@ -506,11 +442,12 @@ fn display_frame(
     };
 
     print!(
-        "\x1B[2K\r[{:.1}s] Progress: {:>5.1}% | Buffer: {:.1}% | Overruns: {}{}{}\n",
+        "\x1B[2K\r[{:.1}s] Progress: {:>5.1}% | Buffer: {:.1}% | Overruns: {} | Underruns: {}{}{}\n",
         elapsed,
         pos_pct,
         fill_pct * 100.0,
         stats.overrun_count,
+        stats.underrun_count,
         subsong_str,
         psg_str,
     );
@@ -0,0 +1,65 @@
+//! Benchmarks for SNDH playback on the emulated 68000
+//!
+//! Run with: cargo bench --bench sndh_frames -p ym2149-sndh-replayer
+//!
+//! The song is read from the Bevy example assets; benchmarks are skipped when
+//! the asset is not available.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::read_asset;
+use ym2149_sndh_replayer::load_sndh;
+
+const SONG: &str = "Wings_Of_Death.sndh";
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Samples per VBL frame at 44.1kHz and 50Hz.
+const SAMPLES_PER_FRAME: usize = 882;
+
+fn bench_load(c: &mut Criterion) {
+    let Some(data) = read_asset(SONG) else {
+        return;
+    };
+
+    // Includes ICE! depacking and running the init routine
+    c.bench_function("sndh_load", |b| {
+        b.iter(|| black_box(load_sndh(black_box(&data), SAMPLE_RATE).expect("song loads")));
+    });
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let Some(data) = read_asset(SONG) else {
+        return;
+    };
+    let mut player = load_sndh(&data, SAMPLE_RATE).expect("song loads");
+    player
+        .init_subsong(player.default_subsong())
+        .expect("subsong initializes");
+    player.play();
+    let mut mono = vec![0.0f32; SAMPLES_PER_FRAME];
+    let mut stereo = vec![0.0f32; SAMPLES_PER_FRAME * 2];
+
+    let mut group = c.benchmark_group("sndh_frames");
+    group.throughput(Throughput::Elements(SAMPLES_PER_FRAME as u64));
+
+    group.bench_function("mono", |b| {
+        b.iter(|| {
+            player.generate_samples_into(&mut mono);
+            black_box(&mono);
+        });
+    });
+
+    group.bench_function("stereo", |b| {
+        b.iter(|| {
+            black_box(player.render_f32_stereo(&mut stereo));
+            black_box(&stereo);
+        });
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_frames);
+criterion_main!(benches);
@@ -10,6 +10,38 @@ pub use r68k_impl::R68kBackend;
 /// Default CPU backend type alias.
 pub type DefaultCpu = R68kBackend;
 
+/// Register-level 68000 state, independent of the backend.
+///
+/// Captured between instructions for player state snapshots.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CpuState {
+    /// Program counter.
+    pub pc: u32,
+    /// Instruction register.
+    pub ir: u16,
+    /// D0-D7 followed by A0-A7 (A7 is the active stack pointer).
+    pub dar: [u32; 16],
+    /// Status register.
+    pub sr: u16,
+    /// User stack pointer.
+    pub usp: u32,
+    /// Supervisor stack pointer.
+    pub ssp: u32,
+    /// Prefetch buffer address.
+    pub prefetch_addr: u32,
+    /// Prefetch buffer data.
+    pub prefetch_data: u32,
+    /// Interrupt request level being processed.
+    pub irq_level: u8,
+    /// STOP instruction executed.
+    pub stopped: bool,
+    /// Halted after a double fault.
+    pub halted: bool,
+    /// Total cycles executed since reset.
+    pub total_cycles: u64,
+}
+
 /// Memory access trait for CPU backends.
 ///
 /// This trait abstracts the memory interface used by the CPU during execution.
@@ -71,4 +103,10 @@ pub trait Cpu68k {
     /// Add cycles to the total cycle count (for exception processing overhead).
     /// Used when emulating exception entry that bypasses normal instruction execution.
     fn add_cycles(&mut self, cycles: u64);
+
+    /// Capture the register-level state.
+    fn save_state(&self) -> CpuState;
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    fn load_state(&mut self, state: &CpuState);
 }
@@ -25,7 +25,7 @@
 //! Since we need external memory that varies per-step, we use a raw pointer in thread-local
 //! storage. The alternative would be to fork r68k or use a different 68k emulator.
 
-use super::{Cpu68k, CpuMemory};
+use super::{Cpu68k, CpuMemory, CpuState};
 use r68k::cpu::{ConfiguredCore, ProcessingState};
 use r68k::interrupts::AutoInterruptController;
 use r68k::ram::AddressBus;
@@ -176,7 +176,11 @@ impl Cpu68k for R68kBackend {
         // the duration of this function. The pointer is stored in thread-local storage
         // and is only accessed by the ProxyBus during `execute1()`. We clear it before
         // returning, ensuring the pointer never outlives the borrow.
-        let ptr = memory as *mut M as *mut dyn CpuMemoryDyn;
+        let ptr: *mut (dyn CpuMemoryDyn + '_) = memory;
+        // SAFETY: Only the trait object's lifetime bound is erased; see above for
+        // why the pointer is never used after `memory`'s borrow ends. (Newer
+        // compilers reject extending it with a plain `as` cast.)
+        let ptr: *mut dyn CpuMemoryDyn = unsafe { std::mem::transmute(ptr) };
 
         MEMORY_CONTEXT.with(|ctx| {
             // SAFETY: We have exclusive access to the thread-local cell.
@@ -247,4 +251,46 @@ impl Cpu68k for R68kBackend {
     fn add_cycles(&mut self, cycles: u64) {
         self.total_cycles += cycles;
     }
+
+    fn save_state(&self) -> CpuState {
+        CpuState {
+            pc: self.cpu.pc,
+            ir: self.cpu.ir,
+            dar: self.cpu.dar,
+            sr: self.cpu.status_register(),
+            usp: self.cpu.usp(),
+            ssp: self.cpu.ssp(),
+            prefetch_addr: self.cpu.prefetch_addr,
+            prefetch_data: self.cpu.prefetch_data,
+            irq_level: self.cpu.irq_level,
+            stopped: self.cpu.processing_state == ProcessingState::Stopped,
+            halted: self.cpu.processing_state == ProcessingState::Halted,
+            total_cycles: self.total_cycles,
+        }
+    }
+
+    fn load_state(&mut self, state: &CpuState) {
+        // Set flags first: a supervisor bit change swaps the stack pointers,
+        // which are all overwritten below
+        self.cpu.sr_to_flags(state.sr);
+        self.cpu.dar = state.dar;
+        if self.cpu.s_flag > 0 {
+            self.cpu.inactive_usp = state.usp;
+        } else {
+            self.cpu.inactive_ssp = state.ssp;
+        }
+        self.cpu.pc = state.pc;
+        self.cpu.ir = state.ir;
+        self.cpu.prefetch_addr = state.prefetch_addr;
+        self.cpu.prefetch_data = state.prefetch_data;
+        self.cpu.irq_level = state.irq_level;
+        self.cpu.processing_state = if state.halted {
+            ProcessingState::Halted
+        } else if state.stopped {
+            ProcessingState::Stopped
+        } else {
+            ProcessingState::Normal
+        };
+        self.total_cycles = state.total_cycles;
+    }
 }
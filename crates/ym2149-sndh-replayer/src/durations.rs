@@ -0,0 +1,213 @@
+//! Subsong durations from tags or external timing databases.
+//!
+//! Many SNDH files carry no `FRMS`/`TIME` tags, so a player cannot tell
+//! when a subsong ends. A [`DurationProvider`] supplies those lengths from
+//! another source; [`SndhPlayer::set_duration_provider`] consults it before
+//! the file's own tags.
+//!
+//! [`TimingDatabase`] reads song length lists in the HVSC `Songlengths.md5`
+//! style:
+//!
+//! ```text
+//! ; comment lines start with ';', section headers like [Database] are skipped
+//! ; /Hubbard_Rob/Goldrunner.sndh
+//! 0f343b0931126a20f133d67c2b018a3b=3:12 0:45.500
+//! ```
+//!
+//! Each entry maps the MD5 of the unpacked SNDH data to one `m:ss[.mmm]`
+//! length per subsong, so ICE! packed and unpacked copies share an entry.
+//!
+//! [`SndhPlayer::set_duration_provider`]: crate::SndhPlayer::set_duration_provider
+
+use std::collections::HashMap;
+
+use md5::{Digest, Md5};
+
+use crate::error::{Result, SndhError};
+use crate::parser::SndhFile;
+
+/// Source of subsong lengths for SNDH files.
+pub trait DurationProvider: Send + Sync {
+    /// Length of `subsong_id` (1-based) in player ticks, or `None` if unknown.
+    fn subsong_frames(&self, sndh: &SndhFile, subsong_id: usize) -> Option<u32>;
+}
+
+/// Durations from the file's own `FRMS` and `TIME` tags.
+///
+/// This is what [`SndhPlayer`](crate::SndhPlayer) uses when no provider is
+/// set, and what it falls back to when a provider has no entry.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TagDurations;
+
+impl DurationProvider for TagDurations {
+    fn subsong_frames(&self, sndh: &SndhFile, subsong_id: usize) -> Option<u32> {
+        sndh.tagged_frames(subsong_id)
+    }
+}
+
+/// Subsong lengths loaded from an external timing database.
+#[derive(Debug, Clone, Default)]
+pub struct TimingDatabase {
+    /// Lengths in milliseconds per subsong, keyed by lowercase MD5 hex
+    entries: HashMap<String, Vec<u32>>,
+}
+
+impl TimingDatabase {
+    /// Create an empty database.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse a database in `Songlengths.md5` style (see the module docs).
+    ///
+    /// Later entries for the same MD5 replace earlier ones.
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut db = Self::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') || line.starts_with('[') {
+                continue;
+            }
+            let invalid = |msg: String| SndhError::InvalidDurationDatabase {
+                line: index + 1,
+                msg,
+            };
+
+            let (key, times) = line
+                .split_once('=')
+                .ok_or_else(|| invalid("expected <md5>=<lengths>".to_string()))?;
+            let key = key.trim().to_ascii_lowercase();
+            if key.len() != 32 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(invalid(format!("invalid MD5 '{key}'")));
+            }
+            let lengths = times
+                .split_whitespace()
+                .map(|time| {
+                    parse_length_ms(time).ok_or_else(|| invalid(format!("invalid length '{time}'")))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            db.entries.insert(key, lengths);
+        }
+        Ok(db)
+    }
+
+    /// Add or replace the lengths (in milliseconds, one per subsong) of a file.
+    pub fn insert(&mut self, sndh: &SndhFile, lengths_ms: Vec<u32>) {
+        self.entries.insert(Self::key(sndh), lengths_ms);
+    }
+
+    /// Lengths in milliseconds of every subsong of `sndh`, if listed.
+    pub fn lengths_ms(&self, sndh: &SndhFile) -> Option<&[u32]> {
+        self.entries.get(&Self::key(sndh)).map(Vec::as_slice)
+    }
+
+    /// Number of files in the database.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the database has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Database key of a file: the lowercase MD5 hex of its unpacked data.
+    pub fn key(sndh: &SndhFile) -> String {
+        Md5::digest(sndh.raw_data())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+impl DurationProvider for TimingDatabase {
+    fn subsong_frames(&self, sndh: &SndhFile, subsong_id: usize) -> Option<u32> {
+        let ms = *self.lengths_ms(sndh)?.get(subsong_id.checked_sub(1)?)?;
+        let rate = u64::from(sndh.metadata.player_rate.max(1));
+        let frames = (u64::from(ms) * rate).div_ceil(1000);
+        (frames > 0).then(|| frames.min(u64::from(u32::MAX)) as u32)
+    }
+}
+
+/// Parse `m:ss`, `m:ss.mmm` or `ss` into milliseconds, ignoring a
+/// trailing attribute like `(L)`.
+fn parse_length_ms(time: &str) -> Option<u32> {
+    let time = time.split('(').next()?;
+    let (minutes, seconds) = time.split_once(':').unwrap_or(("0", time));
+    let (seconds, fraction) = seconds.split_once('.').unwrap_or((seconds, ""));
+    if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let minutes: u32 = minutes.parse().ok()?;
+    let seconds: u32 = seconds.parse().ok()?;
+    let millis = format!("{fraction:0<3}").parse::<u32>().ok()?;
+    minutes
+        .checked_mul(60_000)?
+        .checked_add(seconds.checked_mul(1000)?)?
+        .checked_add(millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sndh_with_tags(tags: &[u8]) -> SndhFile {
+        let total_size = 16 + tags.len() + 4;
+        let mut data = vec![0u8; total_size];
+        data[0] = 0x60; // BRA.s
+        data[1] = (total_size - 2) as u8; // offset past all tags
+        data[12..16].copy_from_slice(b"SNDH");
+        data[16..16 + tags.len()].copy_from_slice(tags);
+        data[16 + tags.len()..].copy_from_slice(b"HDNS");
+        SndhFile::parse(&data).unwrap()
+    }
+
+    #[test]
+    fn test_parse_length() {
+        assert_eq!(parse_length_ms("3:12"), Some(192_000));
+        assert_eq!(parse_length_ms("0:45.5"), Some(45_500));
+        assert_eq!(parse_length_ms("1:00.250(L)"), Some(60_250));
+        assert_eq!(parse_length_ms("90"), Some(90_000));
+        assert_eq!(parse_length_ms("1:xx"), None);
+        assert_eq!(parse_length_ms("1:00.1234"), None);
+    }
+
+    #[test]
+    fn test_database_lookup_by_md5() {
+        let sndh = sndh_with_tags(b"##02");
+        let text = format!(
+            "; SNDH timing database\n[Database]\n; /Test/song.sndh\n{}=1:00 0:02.5\n",
+            TimingDatabase::key(&sndh).to_uppercase()
+        );
+        let db = TimingDatabase::parse(&text).unwrap();
+
+        assert_eq!(db.len(), 1);
+        assert_eq!(db.subsong_frames(&sndh, 1), Some(3000));
+        assert_eq!(db.subsong_frames(&sndh, 2), Some(125));
+        assert_eq!(db.subsong_frames(&sndh, 3), None);
+        assert_eq!(db.subsong_frames(&sndh, 0), None);
+    }
+
+    #[test]
+    fn test_database_reports_bad_lines() {
+        let err = TimingDatabase::parse("; ok\nnot-an-entry\n").unwrap_err();
+        assert!(matches!(
+            err,
+            SndhError::InvalidDurationDatabase { line: 2, .. }
+        ));
+
+        let err = TimingDatabase::parse("0123456789abcdef0123456789abcdef=1:zz").unwrap_err();
+        assert!(matches!(
+            err,
+            SndhError::InvalidDurationDatabase { line: 1, .. }
+        ));
+    }
+
+    #[test]
+    fn test_tag_durations() {
+        let sndh = sndh_with_tags(b"##02TIME\x00\x3C\x00\x00");
+        assert_eq!(TagDurations.subsong_frames(&sndh, 1), Some(3000));
+        assert_eq!(TagDurations.subsong_frames(&sndh, 2), None);
+    }
+}
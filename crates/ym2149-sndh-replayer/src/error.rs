@@ -16,6 +16,10 @@ pub enum SndhError {
     #[error("ICE decompression failed: {0}")]
     IceDepackError(String),
 
+    /// ICE compression failed
+    #[error("ICE compression failed: {0}")]
+    IcePackError(String),
+
     /// Invalid subsong index
     #[error("Invalid subsong index {index} (available: 1-{available})")]
     InvalidSubsong {
@@ -53,6 +57,19 @@ pub enum SndhError {
         /// Number of frames before timeout
         frames: u32,
     },
+
+    /// Malformed line in a subsong timing database
+    #[error("Invalid duration database at line {line}: {msg}")]
+    InvalidDurationDatabase {
+        /// 1-based line number
+        line: usize,
+        /// Error description
+        msg: String,
+    },
+
+    /// State snapshot does not match the loaded file or player
+    #[error("Incompatible state snapshot: {0}")]
+    IncompatibleState(String),
 }
 
 impl From<String> for SndhError {
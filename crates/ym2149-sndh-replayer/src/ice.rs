@@ -1,8 +1,9 @@
-//! ICE! 2.4 depacker implementation.
+//! ICE! 2.4 packer and depacker implementation.
 //!
 //! ICE! was a popular data packer for the Atari ST. Many SNDH files are
 //! compressed with ICE! to reduce file size. This module provides
-//! decompression support.
+//! decompression support, plus a packer so tools can re-emit compressed
+//! files (e.g. after fixing SNDH header metadata).
 //!
 //! ICE! packed data can be recognized by the magic bytes "ICE!" at the
 //! start of the file.
@@ -14,6 +15,12 @@ use crate::error::{Result, SndhError};
 /// ICE! magic header bytes
 const ICE_MAGIC: u32 = 0x49434521; // "ICE!"
 
+/// Size of the ICE! header (magic, packed size, original size)
+const ICE_HEADER_SIZE: usize = 12;
+
+/// Largest original size accepted by the packer and depacker (16 MB)
+const MAX_ORIGINAL_SIZE: usize = 16 * 1024 * 1024;
+
 /// Check if data is ICE! 2.4 packed.
 ///
 /// Returns true if the data starts with the "ICE!" magic header.
@@ -64,7 +71,7 @@ pub fn ice_depack(src: &[u8]) -> Result<Vec<u8>> {
         )));
     }
 
-    if orig_size == 0 || orig_size > 16 * 1024 * 1024 {
+    if orig_size == 0 || orig_size > MAX_ORIGINAL_SIZE {
         return Err(SndhError::IceDepackError(format!(
             "Invalid original size: {orig_size}"
         )));
@@ -77,6 +84,44 @@ pub fn ice_depack(src: &[u8]) -> Result<Vec<u8>> {
     Ok(dst)
 }
 
+/// Pack data with ICE! 2.4 compression.
+///
+/// The output uses the standard ICE! 2.4 layout ("ICE!" header followed by a
+/// backwards-read bitstream), so it can be depacked by [`ice_depack`] as well
+/// as by the original Atari ST depack routines. The trailing "picture" flag
+/// is always cleared.
+///
+/// # Arguments
+///
+/// * `data` - Unpacked data (e.g. a raw SNDH file)
+///
+/// # Returns
+///
+/// ICE! compressed data, or error if the input cannot be packed.
+pub fn ice_pack(data: &[u8]) -> Result<Vec<u8>> {
+    if data.is_empty() || data.len() > MAX_ORIGINAL_SIZE {
+        return Err(SndhError::IcePackError(format!(
+            "Invalid original size: {}",
+            data.len()
+        )));
+    }
+
+    // The depacker rebuilds the output from its last byte backwards, so
+    // matches are searched on the reversed input.
+    let reversed: Vec<u8> = data.iter().rev().copied().collect();
+    let stream = IcePacker::new(&reversed).pack()?;
+
+    let packed_size = ICE_HEADER_SIZE + stream.len();
+    let mut packed = Vec::with_capacity(packed_size);
+    packed.extend_from_slice(&ICE_MAGIC.to_be_bytes());
+    packed.extend_from_slice(&(packed_size as u32).to_be_bytes());
+    packed.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    // The stream is consumed from the end of the file towards the header.
+    packed.extend(stream.iter().rev());
+
+    Ok(packed)
+}
+
 /// Read big-endian u32 from byte slice.
 fn get_u32_be(data: &[u8], offset: usize) -> u32 {
     ((data[offset] as u32) << 24)
@@ -119,7 +164,13 @@ impl<'a> IceState<'a> {
         // Initialize: read first bit to load cmd
         self.get_bits(1)?;
 
-        // Fix reload: skip to valid bit position
+        // Fix reload: skip to valid bit position. The last byte always
+        // holds an end marker bit; without one the loop below never ends.
+        if self.cmd == 0 {
+            return Err(SndhError::IceDepackError(
+                "Missing bitstream end marker".to_string(),
+            ));
+        }
         self.mask = 0x80;
         while (self.cmd & 1) == 0 {
             self.cmd >>= 1;
@@ -277,6 +328,243 @@ impl<'a> IceState<'a> {
     }
 }
 
+/// Longest literal run the length encoding can express.
+const MAX_LITERAL_RUN: usize = 33037;
+
+/// Longest sliding dictionary copy the length encoding can express.
+const MAX_MATCH_LEN: usize = 1033;
+
+/// Largest distance for two-byte copies.
+const MAX_SHORT_DISTANCE: usize = 576;
+
+/// Largest `distance - length` gap for copies of three or more bytes.
+const MAX_LONG_GAP: usize = 4382;
+
+/// Number of hash chain candidates examined per position.
+const MAX_CHAIN_DEPTH: usize = 256;
+
+/// Marker for an empty hash chain slot.
+const NO_POSITION: u32 = u32::MAX;
+
+/// ICE packer state: greedy LZ77 parse emitting the depacker's bitstream.
+///
+/// Works on reversed input and produces the byte stream in the order the
+/// depacker consumes it; command bytes are reserved in the stream at the
+/// point where the depacker reloads them, interleaved with literal bytes.
+struct IcePacker<'a> {
+    /// Reversed input data
+    data: &'a [u8],
+    /// Output stream in depacker read order
+    stream: Vec<u8>,
+    /// Index of the command byte currently being filled
+    cmd_pos: usize,
+    /// Bit mask for the next bit in the current command byte
+    mask: u8,
+    /// Most recent position for each two-byte prefix
+    head: Vec<u32>,
+    /// Previous position with the same prefix
+    prev: Vec<u32>,
+}
+
+impl<'a> IcePacker<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            // The first command byte only holds the end marker bit, so every
+            // following command byte carries a full 8 bits.
+            stream: vec![0x80],
+            cmd_pos: 0,
+            mask: 0,
+            head: vec![NO_POSITION; 1 << 16],
+            prev: vec![NO_POSITION; data.len()],
+        }
+    }
+
+    /// Main pack loop, returning the stream in depacker read order.
+    fn pack(mut self) -> Result<Vec<u8>> {
+        let len = self.data.len();
+        let mut pos = 0;
+        let mut literal_start = 0;
+
+        while pos < len {
+            if let Some((match_len, distance)) = self.find_match(pos) {
+                self.put_literals(literal_start, pos);
+                self.put_sld(match_len, distance);
+                for p in pos..pos + match_len {
+                    self.insert(p);
+                }
+                pos += match_len;
+                literal_start = pos;
+            } else {
+                if pos - literal_start == MAX_LITERAL_RUN {
+                    return Err(SndhError::IcePackError(format!(
+                        "Incompressible run of more than {MAX_LITERAL_RUN} bytes"
+                    )));
+                }
+                self.insert(pos);
+                pos += 1;
+            }
+        }
+
+        if literal_start < len {
+            self.put_bits(1, 1);
+            self.put_literal_length(len - literal_start);
+            self.stream
+                .extend_from_slice(&self.data[literal_start..len]);
+        }
+
+        // "Picture" flag read by the original depacker after the last byte
+        self.put_bits(0, 1);
+
+        Ok(self.stream)
+    }
+
+    /// Find the longest encodable copy for `pos` as `(length, distance)`.
+    fn find_match(&self, pos: usize) -> Option<(usize, usize)> {
+        let available = (self.data.len() - pos).min(MAX_MATCH_LEN);
+        if available < 2 {
+            return None;
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        let mut candidate = self.head[self.hash(pos)];
+        let mut depth = 0;
+
+        while candidate != NO_POSITION && depth < MAX_CHAIN_DEPTH {
+            let start = candidate as usize;
+            let distance = pos - start;
+            if distance > MAX_LONG_GAP + MAX_MATCH_LEN {
+                break;
+            }
+
+            let mut len = 0;
+            while len < available && self.data[start + len] == self.data[pos + len] {
+                len += 1;
+            }
+
+            // Overlapping copies are only encodable as byte repeats.
+            if distance > 1 {
+                len = len.min(distance);
+            }
+            let encodable = match len {
+                0 | 1 => false,
+                2 => distance <= MAX_SHORT_DISTANCE,
+                _ => distance == 1 || distance - len <= MAX_LONG_GAP,
+            };
+            if encodable && best.is_none_or(|(best_len, _)| len > best_len) {
+                best = Some((len, distance));
+                if len == available {
+                    break;
+                }
+            }
+
+            candidate = self.prev[start];
+            depth += 1;
+        }
+
+        best
+    }
+
+    /// Record `pos` in the hash chains.
+    fn insert(&mut self, pos: usize) {
+        if pos + 1 < self.data.len() {
+            let hash = self.hash(pos);
+            self.prev[pos] = self.head[hash];
+            self.head[hash] = pos as u32;
+        }
+    }
+
+    fn hash(&self, pos: usize) -> usize {
+        ((self.data[pos] as usize) << 8) | self.data[pos + 1] as usize
+    }
+
+    /// Put `len` bits of `value` (most significant first).
+    fn put_bits(&mut self, value: u32, len: u32) {
+        for bit in (0..len).rev() {
+            if self.mask == 0 {
+                self.cmd_pos = self.stream.len();
+                self.stream.push(0);
+                self.mask = 0x80;
+            }
+            if (value >> bit) & 1 != 0 {
+                self.stream[self.cmd_pos] |= self.mask;
+            }
+            self.mask >>= 1;
+        }
+    }
+
+    /// Emit the literal run `start..end` (if any) ahead of a dictionary copy.
+    fn put_literals(&mut self, start: usize, end: usize) {
+        if start == end {
+            self.put_bits(0, 1);
+            return;
+        }
+        self.put_bits(1, 1);
+        self.put_literal_length(end - start);
+        self.stream.extend_from_slice(&self.data[start..end]);
+    }
+
+    /// Inverse of `IceState::get_literal_length`.
+    fn put_literal_length(&mut self, len: usize) {
+        const LEN_BITS: [u32; 6] = [1, 2, 2, 3, 8, 15];
+        const OFFSET: [usize; 6] = [1, 2, 5, 8, 15, 270];
+
+        let table_pos = OFFSET
+            .iter()
+            .rposition(|&offset| len >= offset)
+            .unwrap_or(0);
+        for &bits in &LEN_BITS[..table_pos] {
+            self.put_bits((1 << bits) - 1, bits);
+        }
+        self.put_bits((len - OFFSET[table_pos]) as u32, LEN_BITS[table_pos]);
+    }
+
+    /// Inverse of `IceState::get_sld_params`.
+    fn put_sld(&mut self, len: usize, distance: usize) {
+        const EXTRA_BITS: [u32; 5] = [0, 0, 1, 2, 10];
+        const OFFSET: [usize; 5] = [0, 1, 2, 4, 8];
+
+        // Length
+        let len = len - 2;
+        let table_pos = OFFSET
+            .iter()
+            .rposition(|&offset| len >= offset)
+            .unwrap_or(0);
+        for _ in 0..table_pos {
+            self.put_bits(1, 1);
+        }
+        if table_pos < 4 {
+            self.put_bits(0, 1);
+        }
+        self.put_bits((len - OFFSET[table_pos]) as u32, EXTRA_BITS[table_pos]);
+
+        // Position
+        let pos = distance - 1;
+        if len == 0 {
+            if pos >= 64 {
+                self.put_bits(1, 1);
+                self.put_bits((pos - 64) as u32, 9);
+            } else {
+                self.put_bits(0, 1);
+                self.put_bits(pos as u32, 6);
+            }
+        } else {
+            // Non-zero positions are stored relative to the length.
+            let pos = if pos == 0 { 0 } else { pos - len };
+            if pos < 32 {
+                self.put_bits(0b10, 2);
+                self.put_bits(pos as u32, 5);
+            } else if pos < 288 {
+                self.put_bits(0, 1);
+                self.put_bits((pos - 32) as u32, 8);
+            } else {
+                self.put_bits(0b11, 2);
+                self.put_bits((pos - 288) as u32, 12);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -302,4 +590,71 @@ mod tests {
         assert_eq!(ice_packed_size(ice_data), 0x100);
         assert_eq!(ice_original_size(ice_data), 0x200);
     }
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let packed = ice_pack(data).unwrap();
+        assert!(is_ice_packed(&packed));
+        assert_eq!(ice_packed_size(&packed) as usize, packed.len());
+        assert_eq!(ice_original_size(&packed) as usize, data.len());
+        assert_eq!(ice_depack(&packed).unwrap(), data);
+        packed
+    }
+
+    /// Deterministic pseudo-random bytes (LCG).
+    fn noise(len: usize, mut seed: u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ice_pack_round_trip_small() {
+        round_trip(b"A");
+        round_trip(b"AB");
+        round_trip(b"AAA");
+        round_trip(b"ABABABABAB");
+    }
+
+    #[test]
+    fn test_ice_pack_compresses_repetitive_data() {
+        let mut data = b"SNDH".to_vec();
+        data.extend_from_slice(b"TITLThe Fixed Title\0COMMSomebody\0HDNS");
+        for i in 0..4000u32 {
+            data.extend_from_slice(&(i % 37).to_be_bytes());
+        }
+        data.extend(std::iter::repeat_n(0u8, 5000));
+
+        let packed = round_trip(&data);
+        assert!(packed.len() < data.len() / 4);
+    }
+
+    #[test]
+    fn test_ice_pack_round_trip_noise() {
+        // Long literal runs and far, short matches
+        round_trip(&noise(50_000, 1));
+
+        // Noise mixed with repeated blocks at various distances
+        let block = noise(3000, 7);
+        let mut data = Vec::new();
+        for i in 0..12 {
+            data.extend_from_slice(&block[..300 * (i % 5) + 2]);
+            data.extend_from_slice(&noise(100 * i, i as u32));
+        }
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_ice_pack_rejects_empty_input() {
+        assert!(ice_pack(&[]).is_err());
+    }
+
+    #[test]
+    fn test_ice_depack_rejects_missing_end_marker() {
+        let mut packed = ice_pack(b"ABABABABAB").unwrap();
+        *packed.last_mut().unwrap() = 0;
+        assert!(ice_depack(&packed).is_err());
+    }
 }
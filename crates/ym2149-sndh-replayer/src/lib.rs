@@ -6,7 +6,8 @@
 //! Atari ST chiptune music. It includes:
 //!
 //! - **SNDH Parser**: Parses SNDH file headers and metadata
-//! - **ICE Depacker**: Decompresses ICE! 2.4 packed SNDH files
+//! - **ICE Packer/Depacker**: Decompresses ICE! 2.4 packed SNDH files and
+//!   re-packs edited files
 //! - **68000 CPU Emulation**: Via the `m68000` crate for executing SNDH drivers
 //! - **MFP68901 Timer Emulation**: For accurate timer-based effects (SID voice, etc.)
 //! - **Atari ST Machine**: Memory-mapped I/O emulation for YM2149 and timers
@@ -40,10 +41,20 @@
 //! - Entry point + 8: Play one frame (called at player rate, typically 50Hz)
 //!
 //! Many SNDH files are ICE! packed for smaller file sizes.
+//!
+//! ## Untrusted input
+//!
+//! Loading never panics on malformed data: ICE! streams and SNDH headers are
+//! bounds-checked and rejected with [`SndhError`]. The embedded 68000 code
+//! runs against a sandboxed machine whose memory accesses are masked to its
+//! address space, so hostile replay code can misbehave but not crash the
+//! host. `tests/hostile_input.rs` and the `sndh_parse`/`ice_depack` fuzz
+//! targets check this.
 
 #![warn(missing_docs)]
 
 mod cpu_backend;
+mod durations;
 mod error;
 mod ice;
 mod lmc1992;
@@ -51,12 +62,15 @@ mod machine;
 mod mfp68901;
 mod parser;
 mod player;
+#[cfg(feature = "serde")]
+mod serde_array;
 mod ste_dac;
 
+pub use durations::{DurationProvider, TagDurations, TimingDatabase};
 pub use error::{Result, SndhError};
-pub use ice::{ice_depack, is_ice_packed};
+pub use ice::{ice_depack, ice_pack, is_ice_packed};
 pub use parser::{DmaSampleRate, SndhFile, SndhFlags, SndhMetadata, SubsongInfo};
-pub use player::SndhPlayer;
+pub use player::{SndhPlayer, SndhPlayerState};
 
 // Re-export common traits for convenience
 pub use ym2149_common::{BasicMetadata, ChiptunePlayer, PlaybackMetadata, PlaybackState};
@@ -19,6 +19,7 @@
 /// We cascade two first-order sections to achieve the full ±12dB range
 /// with proper frequency response.
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct ShelvingFilter {
     // First-order IIR coefficients: y = b0*x + b1*x1 - a1*y1
     b0: f32,
@@ -171,6 +172,7 @@ impl Lmc1992Function {
 
 /// LMC1992 STE Audio Mixer emulation.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Lmc1992 {
     /// Microwire data register ($FF8922)
     mw_data: u16,
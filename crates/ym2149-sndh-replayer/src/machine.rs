@@ -17,12 +17,12 @@
 //! - 0xFF8922 - 0xFF8925: LMC1992 Microwire interface
 //! - 0xFFFA00 - 0xFFFA25: MFP 68901
 
-use crate::cpu_backend::{Cpu68k, CpuMemory, DefaultCpu};
+use crate::cpu_backend::{Cpu68k, CpuMemory, CpuState, DefaultCpu};
 use crate::error::{Result, SndhError};
 use crate::lmc1992::Lmc1992;
 use crate::mfp68901::{Mfp68901, TimerId};
 use crate::ste_dac::SteDac;
-use ym2149::Ym2149;
+use ym2149::{Ym2149, Ym2149State};
 use ym2149_common::MASTER_GAIN;
 
 /// Map timer index to TimerId for interrupt acknowledgment.
@@ -248,6 +248,8 @@ impl AtariMemory {
 
         // MFP 68901
         if (MFP_START..MFP_WRITE_END).contains(&addr) {
+            // Timer restarts count from this cycle
+            self.mfp.set_cpu_cycle(self.cpu_cycles);
             self.mfp.write8((addr - MFP_START) as u8, value);
         }
     }
@@ -349,6 +351,24 @@ impl CpuMemory for AtariMemory {
     }
 }
 
+/// CPU, RAM and peripheral state of an [`AtariMachine`], for player snapshots.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct AtariMachineState {
+    cpu: CpuState,
+    ram: Vec<u8>,
+    ym2149: Ym2149State,
+    mfp: Mfp68901,
+    ste_dac: SteDac,
+    lmc1992: Lmc1992,
+    next_malloc_addr: u32,
+    reset_triggered: bool,
+    cpu_cycles: u64,
+    interrupt_nesting_depth: u8,
+    current_interrupt_priority: u8,
+    next_gemdos_malloc: u32,
+}
+
 /// Atari ST machine emulation for SNDH playback.
 pub struct AtariMachine {
     /// 68000 CPU (backend selected via features)
@@ -363,6 +383,8 @@ pub struct AtariMachine {
     next_gemdos_malloc: u32,
     /// Enable cycle-accurate timer interrupts (disable during seek for performance)
     cycle_accurate_timers: bool,
+    /// Dispatch timers at their exact cycle between samples (see [`Self::set_timer_accurate`])
+    timer_accurate: bool,
 }
 
 impl AtariMachine {
@@ -376,6 +398,7 @@ impl AtariMachine {
             next_gemdos_malloc: GEMDOS_MALLOC_START,
             // MFP cycle-accurate timers enabled (seek-compatible implementation)
             cycle_accurate_timers: true,
+            timer_accurate: false,
         };
         machine.reset();
         machine
@@ -390,6 +413,53 @@ impl AtariMachine {
         self.next_gemdos_malloc = GEMDOS_MALLOC_START;
     }
 
+    /// Capture CPU, RAM and peripheral state.
+    pub(crate) fn save_state(&self) -> AtariMachineState {
+        AtariMachineState {
+            cpu: self.cpu.save_state(),
+            ram: self.memory.ram.clone(),
+            ym2149: self.memory.ym2149.save_state(),
+            mfp: self.memory.mfp.clone(),
+            ste_dac: self.memory.ste_dac.clone(),
+            lmc1992: self.memory.lmc1992.clone(),
+            next_malloc_addr: self.memory.next_malloc_addr,
+            reset_triggered: self.memory.reset_triggered,
+            cpu_cycles: self.memory.cpu_cycles,
+            interrupt_nesting_depth: self.interrupt_nesting_depth,
+            current_interrupt_priority: self.current_interrupt_priority,
+            next_gemdos_malloc: self.next_gemdos_malloc,
+        }
+    }
+
+    /// Restore a capture from [`save_state`](Self::save_state).
+    ///
+    /// DAC mutes are kept. Returns `false` (leaving the machine untouched)
+    /// if the RAM image has the wrong size.
+    pub(crate) fn load_state(&mut self, state: &AtariMachineState) -> bool {
+        if state.ram.len() != RAM_SIZE {
+            return false;
+        }
+        let mute_left = self.is_dac_left_muted();
+        let mute_right = self.is_dac_right_muted();
+
+        self.cpu.load_state(&state.cpu);
+        self.memory.ram.copy_from_slice(&state.ram);
+        self.memory.ym2149.load_state(&state.ym2149);
+        self.memory.mfp.clone_from(&state.mfp);
+        self.memory.ste_dac.clone_from(&state.ste_dac);
+        self.memory.lmc1992.clone_from(&state.lmc1992);
+        self.memory.next_malloc_addr = state.next_malloc_addr;
+        self.memory.reset_triggered = state.reset_triggered;
+        self.memory.cpu_cycles = state.cpu_cycles;
+        self.interrupt_nesting_depth = state.interrupt_nesting_depth;
+        self.current_interrupt_priority = state.current_interrupt_priority;
+        self.next_gemdos_malloc = state.next_gemdos_malloc;
+
+        self.set_dac_mute_left(mute_left);
+        self.set_dac_mute_right(mute_right);
+        true
+    }
+
     /// Upload data to RAM.
     pub fn upload(&mut self, data: &[u8], addr: u32) -> Result<()> {
         let addr = addr as usize;
@@ -442,6 +512,65 @@ impl AtariMachine {
         Ok(self.memory.reset_triggered)
     }
 
+    /// Enable or disable timer-accurate sample generation.
+    ///
+    /// The legacy path ticks the MFP once per host sample, so a timer can
+    /// fire at most once per sample and every register write inside the
+    /// sample collapses to its last value. Sample replayers that stream
+    /// through the YM volume registers from a fast Timer A (TAO, Digital
+    /// Muzak and similar drivers) then sound crunchy, or lose their
+    /// interrupts entirely at low host rates.
+    ///
+    /// In timer-accurate mode the CPU clock advances with the audio clock,
+    /// every timer fire inside a sample is dispatched at its own cycle and
+    /// the YM2149 averages its output over the resulting writes.
+    pub fn set_timer_accurate(&mut self, enabled: bool) {
+        if self.timer_accurate == enabled {
+            return;
+        }
+        self.timer_accurate = enabled;
+        self.memory.ym2149.set_oversampled_output(enabled);
+        if enabled {
+            self.sync_timing();
+        }
+    }
+
+    /// Whether timer-accurate sample generation is enabled.
+    pub fn timer_accurate(&self) -> bool {
+        self.timer_accurate
+    }
+
+    /// Fastest interrupt rate in Hz among the running MFP timers.
+    pub fn fastest_timer_rate(&self) -> Option<u32> {
+        self.memory.mfp.fastest_timer_rate()
+    }
+
+    /// Let the CPU idle until `cycle` (no-op if it is already past it).
+    fn advance_cpu_to(&mut self, cycle: u64) {
+        let now = self.cpu.total_cycles();
+        if now < cycle {
+            self.cpu.add_cycles(cycle - now);
+        }
+        self.memory.cpu_cycles = self.cpu.total_cycles();
+    }
+
+    /// Dispatch every counter-mode timer interrupt due before `end_cycle`,
+    /// each at its own CPU cycle, then idle the CPU up to `end_cycle`.
+    fn run_timers_until(&mut self, end_cycle: u64) {
+        while let Some(next_fire) = self.memory.mfp.next_timer_fire_cycle() {
+            let dispatch_cycle = next_fire + MFP_INTERRUPT_LATENCY_CYCLES;
+            if dispatch_cycle >= end_cycle {
+                break;
+            }
+            self.advance_cpu_to(dispatch_cycle);
+            let cpu_cycle = self.cpu.total_cycles();
+            if let Some(timer_id) = self.memory.mfp.check_timers_at_cycle(cpu_cycle) {
+                self.dispatch_timer_interrupt(timer_id);
+            }
+        }
+        self.advance_cpu_to(end_cycle);
+    }
+
     /// Tick all MFP timers and dispatch their interrupts (legacy sample-based mode).
     fn tick_timers(&mut self) {
         let fired = self.memory.mfp.tick();
@@ -453,6 +582,16 @@ impl AtariMachine {
         }
     }
 
+    /// Tick the event-count timers only (timer-accurate mode).
+    fn tick_event_timers(&mut self) {
+        let fired = self.memory.mfp.tick_event_timers();
+        for (timer_idx, active) in fired.into_iter().enumerate() {
+            if active {
+                self.dispatch_timer_interrupt(TIMER_ID_MAP[timer_idx]);
+            }
+        }
+    }
+
     /// Dispatch a timer interrupt with nested interrupt support.
     ///
     /// Nested interrupts are allowed if:
@@ -651,6 +790,13 @@ impl AtariMachine {
     /// Compute the next stereo audio sample.
     /// Returns (left, right) samples.
     pub fn compute_sample_stereo(&mut self) -> (i16, i16) {
+        // Timer-accurate mode: run this sample's timer interrupts first so
+        // their writes land inside it
+        if self.timer_accurate {
+            let end_cycle = self.memory.ym2149.next_sample_end_cycle();
+            self.run_timers_until(end_cycle);
+        }
+
         // Get YM2149 sample (mono, duplicated to both channels)
         // Only if LMC1992 mix is enabled
         let ym_sample = if self.memory.lmc1992.should_mix_ym() {
@@ -679,7 +825,11 @@ impl AtariMachine {
         let out_right = ((lmc_right as f32 * MASTER_GAIN) as i32).clamp(-32768, 32767) as i16;
 
         // Tick timers after mixing
-        self.tick_timers();
+        if self.timer_accurate {
+            self.tick_event_timers();
+        } else {
+            self.tick_timers();
+        }
 
         (out_left, out_right)
     }
@@ -903,3 +1053,81 @@ impl AtariMachine {
         Ok(self.memory.reset_triggered)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HANDLER_ADDR: u32 = 0x3000;
+    const COUNTER_ADDR: u32 = 0x2004;
+
+    /// Machine with a Timer A sample replayer at 25.6 kHz: every interrupt
+    /// steps channel A's volume through 0..=15, giving a 1.6 kHz sawtooth.
+    fn sample_replay_machine(sample_rate: u32, timer_accurate: bool) -> AtariMachine {
+        let mut machine = AtariMachine::new(sample_rate);
+        let handler: [u16; 13] = [
+            0x11FC, 0x0008, 0x8800, // move.b #8,$ffff8800.w
+            0x5238, 0x2000, // addq.b #1,$2000.w
+            0x0238, 0x000F, 0x2000, // andi.b #$0f,$2000.w
+            0x11F8, 0x2000, 0x8802, // move.b $2000.w,$ffff8802.w
+            0x52B8, 0x2004, // addq.l #1,$2004.w
+        ];
+        for (i, word) in handler.iter().chain(&[0x4E73]).enumerate() {
+            machine
+                .memory
+                .write_word(HANDLER_ADDR + 2 * i as u32, *word); // ..., rte
+        }
+        machine
+            .memory
+            .write_long(IVECTOR[TimerId::TimerA as usize], HANDLER_ADDR);
+
+        // Timer A: 2.4576 MHz / 4 / 24 = 25.6 kHz, enabled and unmasked
+        machine.memory.write_byte(0xFFFA1F, 24);
+        machine.memory.write_byte(0xFFFA19, 0x01);
+        machine.memory.write_byte(0xFFFA07, 0x20);
+        machine.memory.write_byte(0xFFFA13, 0x20);
+
+        machine.set_timer_accurate(timer_accurate);
+        machine
+    }
+
+    fn render(machine: &mut AtariMachine, samples: usize) -> Vec<i16> {
+        (0..samples)
+            .map(|_| machine.compute_sample_stereo().0)
+            .collect()
+    }
+
+    fn zero_crossings(samples: &[i16]) -> usize {
+        samples
+            .windows(2)
+            .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+            .count()
+    }
+
+    #[test]
+    fn test_timer_accurate_dispatches_every_fast_timer_irq() {
+        let sample_rate = 22_050;
+        let mut legacy = sample_replay_machine(sample_rate, false);
+        let mut accurate = sample_replay_machine(sample_rate, true);
+        render(&mut legacy, sample_rate as usize);
+        render(&mut accurate, sample_rate as usize);
+
+        // The legacy path fires at most once per host sample
+        assert!(legacy.memory.read_long(COUNTER_ADDR) <= sample_rate);
+        let fired = accurate.memory.read_long(COUNTER_ADDR);
+        assert!(fired.abs_diff(25_600) < 256, "{fired} interrupts");
+    }
+
+    #[test]
+    fn test_timer_accurate_keeps_sample_pitch() {
+        let sample_rate = 44_100;
+        let mut machine = sample_replay_machine(sample_rate, true);
+        assert!(machine.timer_accurate());
+        render(&mut machine, 4410);
+
+        // One second of the 1.6 kHz sawtooth, two crossings per period
+        let output = render(&mut machine, sample_rate as usize);
+        let crossings = zero_crossings(&output);
+        assert!(crossings.abs_diff(3200) < 64, "{crossings} zero crossings");
+    }
+}
@@ -141,7 +141,8 @@ const CPU_CYCLES_PER_PRESCALER_TICK_FP16: [u64; 8] = [
 /// We model this as 100 cycles (midpoint of valid range 1-200).
 const PRESCALE_SWITCH_DELAY_CYCLES: u64 = 100;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct Timer {
     // === CONFIGURATION (stable, only changed by register writes) ===
     enable: bool,
@@ -199,13 +200,14 @@ impl Timer {
         self.in_service = false;
     }
 
-    fn restart(&mut self) {
+    fn restart(&mut self, cpu_cycle: u64) {
         // Reset legacy state
         self.inner_clock = 0;
         self.legacy_counter = self.data_register_init;
 
-        // Reset cycle-accurate state
+        // Reset cycle-accurate state, counting from the restart
         self.cycles_until_fire = self.calc_cycles_for_period();
+        self.last_check_cycle = cpu_cycle;
 
         // Reset phase accumulator (timer reconfigured, phase tracking starts fresh)
         self.virtual_cycles_accumulated = 0;
@@ -215,6 +217,19 @@ impl Timer {
         (self.control_register & 7) != 0 && (self.control_register & 8) == 0
     }
 
+    /// Interrupt rate in Hz while counting with the interrupt enabled.
+    fn interrupt_rate(&self) -> Option<u32> {
+        if !self.enable || !self.mask || !self.is_counter_mode() {
+            return None;
+        }
+        // A data register of 0 counts 256 prescaler ticks
+        let count = match self.data_register_init {
+            0 => 256,
+            data => u32::from(data),
+        };
+        Some(PRESCALE[(self.control_register & 7) as usize] / count)
+    }
+
     fn is_event_mode(&self) -> bool {
         (self.control_register & 8) != 0
     }
@@ -273,21 +288,22 @@ impl Timer {
         self.in_service = false;
     }
 
-    fn set_er(&mut self, enable: bool) {
-        if (self.enable ^ enable) && enable && self.is_counter_mode() {
-            self.restart();
-        }
+    fn set_er(&mut self, enable: bool, cpu_cycle: u64) {
+        let was_enabled = self.enable;
         self.enable = enable;
+        if !was_enabled && enable && self.is_counter_mode() {
+            self.restart(cpu_cycle);
+        }
     }
 
-    fn set_dr(&mut self, data: u8) {
+    fn set_dr(&mut self, data: u8, cpu_cycle: u64) {
         self.data_register_init = data;
         if self.control_register == 0 {
-            self.restart();
+            self.restart(cpu_cycle);
         }
     }
 
-    fn set_cr(&mut self, data: u8) {
+    fn set_cr(&mut self, data: u8, cpu_cycle: u64) {
         let old_prescaler = self.control_register & 7;
         let new_prescaler = data & 7;
 
@@ -323,6 +339,15 @@ impl Timer {
             }
         }
 
+        // Stopping the counter (or switching to event mode) halts the
+        // cycle-accurate countdown; starting it counts from this write
+        if !self.enable || !self.is_counter_mode() {
+            self.cycles_until_fire = None;
+        } else if self.cycles_until_fire.is_none() {
+            self.cycles_until_fire = self.calc_cycles_for_period();
+            self.last_check_cycle = cpu_cycle;
+        }
+
         self.previous_prescaler = new_prescaler;
     }
 
@@ -415,8 +440,17 @@ impl Timer {
             if elapsed >= remaining {
                 // Timer fires
                 self.pending = true;
-                // Reload from configuration (not legacy state!)
-                self.cycles_until_fire = self.calc_cycles_for_period();
+                // Reload from configuration (not legacy state!), keeping the
+                // cycles already elapsed in the new period so late checks
+                // don't stretch the timer
+                let overshoot = elapsed - remaining;
+                self.cycles_until_fire = self.calc_cycles_for_period().map(|period| {
+                    if period == 0 {
+                        0
+                    } else {
+                        period - overshoot % period
+                    }
+                });
                 return self.mask; // Return true only if masked (interrupt enabled)
             } else {
                 self.cycles_until_fire = Some(remaining - elapsed);
@@ -489,8 +523,11 @@ impl Timer {
 }
 
 /// MFP68901 (MC68901) Multi-Function Peripheral emulation
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Mfp68901 {
     host_replay_rate: u32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
     regs: [u8; 256],
     timers: [Timer; 5],
     /// GPIP - General Purpose I/O register (directly readable/writable)
@@ -676,6 +713,7 @@ impl Mfp68901 {
 
     pub fn write8(&mut self, port: u8, data: u8) {
         let port = port as usize & 255;
+        let cycle = self.current_cpu_cycle;
 
         if (port & 1) != 0 {
             match port {
@@ -690,13 +728,18 @@ impl Mfp68901 {
                     self.ddr = data;
                 }
                 REG_IERA => {
-                    self.timers[TimerId::TimerA as usize].set_er((data & (1 << INT_TIMER_A)) != 0);
-                    self.timers[TimerId::TimerB as usize].set_er((data & (1 << INT_TIMER_B)) != 0);
-                    self.timers[TimerId::Gpi7 as usize].set_er((data & (1 << INT_GPI7)) != 0);
+                    self.timers[TimerId::TimerA as usize]
+                        .set_er((data & (1 << INT_TIMER_A)) != 0, cycle);
+                    self.timers[TimerId::TimerB as usize]
+                        .set_er((data & (1 << INT_TIMER_B)) != 0, cycle);
+                    self.timers[TimerId::Gpi7 as usize]
+                        .set_er((data & (1 << INT_GPI7)) != 0, cycle);
                 }
                 REG_IERB => {
-                    self.timers[TimerId::TimerC as usize].set_er((data & (1 << INT_TIMER_C)) != 0);
-                    self.timers[TimerId::TimerD as usize].set_er((data & (1 << INT_TIMER_D)) != 0);
+                    self.timers[TimerId::TimerC as usize]
+                        .set_er((data & (1 << INT_TIMER_C)) != 0, cycle);
+                    self.timers[TimerId::TimerD as usize]
+                        .set_er((data & (1 << INT_TIMER_D)) != 0, cycle);
                 }
                 REG_IPRA => {
                     self.apply_ipra(data);
@@ -723,18 +766,18 @@ impl Mfp68901 {
                     self.vr = data;
                 }
                 REG_TACR => {
-                    self.timers[TimerId::TimerA as usize].set_cr(data & 0x0f);
+                    self.timers[TimerId::TimerA as usize].set_cr(data & 0x0f, cycle);
                 }
                 REG_TBCR => {
-                    self.timers[TimerId::TimerB as usize].set_cr(data & 0x0f);
+                    self.timers[TimerId::TimerB as usize].set_cr(data & 0x0f, cycle);
                 }
                 REG_TCDCR => {
-                    self.timers[TimerId::TimerC as usize].set_cr((data >> 4) & 7);
-                    self.timers[TimerId::TimerD as usize].set_cr(data & 7);
+                    self.timers[TimerId::TimerC as usize].set_cr((data >> 4) & 7, cycle);
+                    self.timers[TimerId::TimerD as usize].set_cr(data & 7, cycle);
                 }
                 REG_TADR | REG_TBDR | REG_TCDR | REG_TDDR => {
                     let timer_id = (port - REG_TADR) >> 1;
-                    self.timers[timer_id].set_dr(data);
+                    self.timers[timer_id].set_dr(data, cycle);
                 }
                 _ => {}
             }
@@ -805,6 +848,19 @@ impl Mfp68901 {
         fired
     }
 
+    /// Tick only the timers in event count mode (e.g. Timer A counting DMA
+    /// frame ends). Used when counter-mode timers are driven by
+    /// [`check_timers_at_cycle`](Self::check_timers_at_cycle).
+    pub fn tick_event_timers(&mut self) -> [bool; 5] {
+        let mut fired = [false; 5];
+        for (i, timer) in self.timers.iter_mut().enumerate() {
+            if timer.is_event_mode() {
+                fired[i] = timer.tick(self.host_replay_rate);
+            }
+        }
+        fired
+    }
+
     /// Synchronize all timer states after seek or time discontinuity.
     /// Resets both cycle-accurate and legacy states for clean continuation.
     pub fn sync_cpu_cycle(&mut self, cpu_cycle: u64) {
@@ -839,6 +895,14 @@ impl Mfp68901 {
         None
     }
 
+    /// Fastest interrupt rate in Hz among the counting timers A-D.
+    pub fn fastest_timer_rate(&self) -> Option<u32> {
+        self.timers[0..4]
+            .iter()
+            .filter_map(Timer::interrupt_rate)
+            .max()
+    }
+
     /// Get the next CPU cycle at which any timer will fire.
     /// Returns None if no timers are active.
     pub fn next_timer_fire_cycle(&self) -> Option<u64> {
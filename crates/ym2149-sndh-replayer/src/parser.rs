@@ -35,6 +35,7 @@ pub struct SndhFile {
 
 /// SNDH feature flags (from FLAG tag, SNDH v2.2)
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SndhFlags {
     /// Uses Timer A
     pub timer_a: bool,
@@ -78,6 +79,7 @@ pub struct SndhFlags {
 
 /// DMA sample rates for STE and Falcon
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DmaSampleRate {
     /// 6.25 kHz (STE)
     Rate6258,
@@ -105,6 +107,7 @@ pub enum DmaSampleRate {
 
 /// SNDH file metadata
 #[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SndhMetadata {
     /// Song title
     pub title: Option<String>,
@@ -137,6 +140,7 @@ pub struct SndhMetadata {
 
 /// Information about a specific subsong
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SubsongInfo {
     /// Total number of subsongs
     pub subsong_count: usize,
@@ -154,6 +158,16 @@ pub struct SubsongInfo {
     pub year: Option<String>,
     /// Subtune name (from #!SN tag, if available)
     pub subtune_name: Option<String>,
+    /// Replay timer ('A' to 'D' from the TA..TD tags), `None` for VBL or unknown
+    pub timer: Option<char>,
+}
+
+impl SubsongInfo {
+    /// Subsong length in seconds, or `None` if unknown.
+    pub fn duration_seconds(&self) -> Option<f32> {
+        (self.player_tick_count > 0 && self.player_tick_rate > 0)
+            .then(|| self.player_tick_count as f32 / self.player_tick_rate as f32)
+    }
 }
 
 impl SndhFile {
@@ -556,17 +570,7 @@ impl SndhFile {
         }
 
         let idx = subsong_id - 1;
-
-        // Prefer FRMS (frame count, SNDH v2.2) over TIME (seconds, legacy)
-        let tick_count = if let Some(&frames) = self.metadata.subsong_frames.get(idx) {
-            // FRMS provides exact frame count (0 = endless loop)
-            frames
-        } else if let Some(&duration) = self.metadata.subsong_durations.get(idx) {
-            // TIME fallback: convert seconds to frames
-            (duration as u32) * self.metadata.player_rate
-        } else {
-            0 // Unknown duration
-        };
+        let tick_count = self.tagged_frames(subsong_id).unwrap_or(0);
 
         let samples_per_tick = sample_rate / self.metadata.player_rate;
 
@@ -582,9 +586,31 @@ impl SndhFile {
             author: self.metadata.author.clone(),
             year: self.metadata.year.clone(),
             subtune_name,
+            timer: self.metadata.timer_used,
         })
     }
 
+    /// Length of a subsong in player ticks from its `FRMS` or `TIME` tag.
+    ///
+    /// Returns `None` when neither tag gives a length (0 in `FRMS` marks an
+    /// endless loop).
+    pub fn tagged_frames(&self, subsong_id: usize) -> Option<u32> {
+        let idx = subsong_id.checked_sub(1)?;
+
+        // Prefer FRMS (frame count, SNDH v2.2) over TIME (seconds, legacy)
+        let frames = self.metadata.subsong_frames.get(idx).copied().unwrap_or(0);
+        if frames > 0 {
+            return Some(frames);
+        }
+        let seconds = self
+            .metadata
+            .subsong_durations
+            .get(idx)
+            .copied()
+            .unwrap_or(0);
+        (seconds > 0).then(|| u32::from(seconds) * self.metadata.player_rate)
+    }
+
     /// Get raw data for uploading to Atari machine memory.
     pub fn raw_data(&self) -> &[u8] {
         &self.data
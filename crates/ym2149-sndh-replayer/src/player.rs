@@ -3,11 +3,41 @@
 //! This module provides the main `SndhPlayer` struct that handles SNDH
 //! file playback using the Atari ST machine emulation.
 
+use std::sync::Arc;
+
+use crate::durations::DurationProvider;
 use crate::error::{Result, SndhError};
-use crate::machine::AtariMachine;
+use crate::ice::is_ice_packed;
+use crate::machine::{AtariMachine, AtariMachineState};
 use crate::parser::{SndhFile, SndhFlags, SubsongInfo};
 use ym2149::Ym2149Backend;
-use ym2149_common::{BasicMetadata, ChiptunePlayer, ChiptunePlayerBase, PlaybackState};
+use ym2149_common::{
+    BasicMetadata, ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, Compression,
+    CompressionInfo, LoadReport, OutputGain, OutputModel, PlaybackRate, PlaybackState,
+    RegisterDelta,
+};
+
+/// Snapshot of an [`SndhPlayer`]'s emulation state
+///
+/// Captured by [`SndhPlayer::save_state`] and restored with
+/// [`SndhPlayer::load_state`] into a player for the same file and sample
+/// rate. Holds the 68000, RAM, YM2149, MFP, STE DAC, LMC1992 and the tick
+/// position, so playback continues sample-exactly. Playback settings
+/// (mutes, gain, rate, output model) are not included.
+///
+/// With the `serde` feature the state can be serialized.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SndhPlayerState {
+    data_len: usize,
+    sample_rate: u32,
+    machine: AtariMachineState,
+    current_subsong: usize,
+    frame: u32,
+    frame_count: u32,
+    loop_count: u32,
+    inner_sample_pos: i32,
+}
 
 /// SNDH file player.
 ///
@@ -56,8 +86,19 @@ pub struct SndhPlayer {
     play_cycle_budget: usize,
     /// Disable warmup/prime phase (env flag)
     warmup_enabled: bool,
+    /// Switch to timer-accurate mode when a fast timer starts (off after
+    /// an explicit [`Self::set_timer_accurate`])
+    timer_accurate_auto: bool,
     /// Reusable stereo buffer for mono conversion (avoids allocation in hot path)
     stereo_scratch: Vec<f32>,
+    /// Output gain applied to f32 output
+    output_gain: OutputGain,
+    /// Playback rate (speed and pitch) applied to f32 output
+    playback_rate: PlaybackRate,
+    /// External subsong lengths, consulted before the file's tags
+    duration_provider: Option<Arc<dyn DurationProvider>>,
+    /// ICE! packing of the loaded file
+    compression: Option<CompressionInfo>,
 }
 
 impl SndhPlayer {
@@ -73,6 +114,11 @@ impl SndhPlayer {
     /// A new player ready for subsong initialization.
     pub fn new(data: &[u8], sample_rate: u32) -> Result<Self> {
         let sndh = SndhFile::parse(data)?;
+        let compression = is_ice_packed(data).then_some(CompressionInfo {
+            method: Compression::Ice,
+            packed_size: data.len(),
+            unpacked_size: sndh.data.len(),
+        });
 
         let metadata = BasicMetadata {
             title: sndh.metadata.title.clone().unwrap_or_default(),
@@ -112,10 +158,45 @@ impl SndhPlayer {
             current_subsong: 0,
             play_cycle_budget,
             warmup_enabled,
+            timer_accurate_auto: true,
             stereo_scratch: Vec::new(),
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
+            duration_provider: None,
+            compression,
         })
     }
 
+    /// Set an external source of subsong lengths, or `None` to use only the
+    /// file's `FRMS`/`TIME` tags.
+    ///
+    /// The provider is asked first; subsongs it does not know fall back to
+    /// the tags and then to the 5-minute default. The length of the current
+    /// subsong is updated immediately. Share one provider (e.g. a
+    /// [`TimingDatabase`](crate::TimingDatabase)) between players with an `Arc`.
+    pub fn set_duration_provider(&mut self, provider: Option<Arc<dyn DurationProvider>>) {
+        self.duration_provider = provider;
+        if self.current_subsong > 0 {
+            self.frame_count = self.subsong_frames(self.current_subsong);
+        }
+    }
+
+    /// Length of a subsong in player ticks from the duration provider or the
+    /// file's tags, if known.
+    pub fn known_subsong_frames(&self, subsong_id: usize) -> Option<u32> {
+        self.duration_provider
+            .as_ref()
+            .and_then(|provider| provider.subsong_frames(&self.sndh, subsong_id))
+            .or_else(|| self.sndh.tagged_frames(subsong_id))
+    }
+
+    /// Length of a subsong in player ticks, defaulting to 5 minutes so that
+    /// seeking works for files without duration info.
+    fn subsong_frames(&self, subsong_id: usize) -> u32 {
+        self.known_subsong_frames(subsong_id)
+            .unwrap_or(self.sndh.metadata.player_rate * 300)
+    }
+
     /// Initialize a specific subsong.
     ///
     /// # Arguments
@@ -133,29 +214,20 @@ impl SndhPlayer {
             });
         }
 
-        // Calculate frame count from duration (FRMS or TIME tag) FIRST
-        // This enables seeking even if later init steps have issues
-        // If neither is available, use a default of 5 minutes to enable seeking
-        let duration_frames = self
-            .sndh
-            .get_subsong_info(subsong_id, self.sample_rate)
-            .map(|info| info.player_tick_count)
-            .unwrap_or(0);
-
-        if duration_frames > 0 {
-            self.frame_count = duration_frames;
-        } else {
-            // Default: 5 minutes at player rate (enables seeking for older SNDH files)
-            self.frame_count = self.sndh.metadata.player_rate * 300;
-        }
+        // Calculate frame count from the duration provider or FRMS/TIME tags
+        // FIRST. This enables seeking even if later init steps have issues
+        self.frame_count = self.subsong_frames(subsong_id);
 
         // Setup playback state
         self.current_subsong = subsong_id;
         self.frame = 0;
         self.loop_count = 0;
 
-        // Reset machine
+        // Reset machine; the automatic mode is chosen again for this subsong
         self.machine.reset();
+        if self.timer_accurate_auto {
+            self.machine.set_timer_accurate(false);
+        }
 
         // Upload SNDH data
         let upload_addr = self.machine.sndh_upload_addr();
@@ -169,11 +241,21 @@ impl SndhPlayer {
             ));
         }
 
+        // Timer-accurate mode clocks the MFP and YM2149 from the CPU cycle
+        // counter, which the init routine has already advanced: align them
+        // so playback starts at cycle 0 of the first sample. The legacy path
+        // counts host samples and is left as it was.
+        if self.machine.timer_accurate() {
+            self.machine.sync_timing();
+        }
+        self.detect_fast_timers();
+
         // CRITICAL: Call play routine IMMEDIATELY after init to set up registers
         // before any samples are generated. The timer fires immediately after init.
         let _ = self
             .machine
             .jsr_limited(upload_addr + 8, 0, self.play_cycle_budget);
+        self.detect_fast_timers();
         self.frame += 1;
 
         // Set inner_sample_pos to full tick so we don't call play again immediately
@@ -191,11 +273,36 @@ impl SndhPlayer {
         Ok(())
     }
 
-    /// Get information about a specific subsong.
+    /// Get information about a specific subsong from the file's tags.
+    ///
+    /// See [`subsong_info`](Self::subsong_info) for lengths that include the
+    /// duration provider.
     pub fn get_subsong_info(&self, subsong_id: usize) -> Option<SubsongInfo> {
         self.sndh.get_subsong_info(subsong_id, self.sample_rate)
     }
 
+    /// Get name, length and replay timer of a subsong (1-based).
+    ///
+    /// The length comes from the duration provider or the `FRMS`/`TIME`
+    /// tags, like playback; it is 0 ticks when unknown.
+    pub fn subsong_info(&self, subsong_id: usize) -> Option<SubsongInfo> {
+        let mut info = self.get_subsong_info(subsong_id)?;
+        info.player_tick_count = self.known_subsong_frames(subsong_id).unwrap_or(0);
+        Some(info)
+    }
+
+    /// Get [`subsong_info`](Self::subsong_info) for every subsong, in order.
+    pub fn subsongs(&self) -> Vec<SubsongInfo> {
+        (1..=self.subsong_count())
+            .filter_map(|id| self.subsong_info(id))
+            .collect()
+    }
+
+    /// Get the parsed SNDH file.
+    pub fn sndh_file(&self) -> &SndhFile {
+        &self.sndh
+    }
+
     /// Get the number of subsongs.
     pub fn subsong_count(&self) -> usize {
         self.sndh.metadata.subsong_count
@@ -248,7 +355,10 @@ impl SndhPlayer {
     /// Runtime detection is important for older SNDH files without FLAG tags.
     pub fn uses_ste_features(&self) -> bool {
         let flags = &self.sndh.metadata.flags;
-        flags.ste || flags.lmc || flags.stereo || flags.dma_rate.is_some()
+        flags.ste
+            || flags.lmc
+            || flags.stereo
+            || flags.dma_rate.is_some()
             || self.machine.was_ste_dac_used()
     }
 
@@ -262,6 +372,46 @@ impl SndhPlayer {
         self.machine.was_ste_dac_used()
     }
 
+    /// Force timer-accurate playback on or off.
+    ///
+    /// TAO, Digital Muzak and other drivers that play samples through the
+    /// YM volume registers from a fast Timer A need every interrupt at its
+    /// own cycle. In this mode the timers are dispatched cycle-accurately
+    /// between samples and the YM2149 output is averaged over the writes
+    /// inside each sample, at a moderate CPU cost.
+    ///
+    /// By default the player switches to this mode by itself as soon as an
+    /// MFP timer interrupts faster than the replay rate; calling this
+    /// method turns that detection off.
+    pub fn set_timer_accurate(&mut self, enabled: bool) {
+        self.timer_accurate_auto = false;
+        self.machine.set_timer_accurate(enabled);
+    }
+
+    /// Switch to timer-accurate mode if a timer interrupts faster than the
+    /// play routine is called: such timers only drive samples or effects.
+    fn detect_fast_timers(&mut self) {
+        if !self.timer_accurate_auto || self.machine.timer_accurate() {
+            return;
+        }
+        let replay_rate = match self.sndh.metadata.player_rate {
+            0 => 50,
+            rate => rate,
+        };
+        if self
+            .machine
+            .fastest_timer_rate()
+            .is_some_and(|rate| rate > replay_rate)
+        {
+            self.machine.set_timer_accurate(true);
+        }
+    }
+
+    /// Whether timer-accurate playback is enabled.
+    pub fn timer_accurate(&self) -> bool {
+        self.machine.timer_accurate()
+    }
+
     /// Mute or unmute the STE DAC left channel.
     pub fn set_dac_mute_left(&mut self, mute: bool) {
         self.machine.set_dac_mute_left(mute);
@@ -337,6 +487,56 @@ impl SndhPlayer {
         self.machine.lmc1992_treble_raw()
     }
 
+    /// Capture the emulation state (see [`SndhPlayerState`]).
+    pub fn save_state(&self) -> SndhPlayerState {
+        SndhPlayerState {
+            data_len: self.sndh.raw_data().len(),
+            sample_rate: self.sample_rate,
+            machine: self.machine.save_state(),
+            current_subsong: self.current_subsong,
+            frame: self.frame,
+            frame_count: self.frame_count,
+            loop_count: self.loop_count,
+            inner_sample_pos: self.inner_sample_pos,
+        }
+    }
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    ///
+    /// A stopped player is left paused so that `play` resumes from the
+    /// restored position. Fails without changing anything if the state was
+    /// saved from a different file or sample rate.
+    pub fn load_state(&mut self, state: &SndhPlayerState) -> Result<()> {
+        if state.data_len != self.sndh.raw_data().len()
+            || state.current_subsong > self.sndh.metadata.subsong_count
+        {
+            return Err(SndhError::IncompatibleState(
+                "saved from a different file".to_string(),
+            ));
+        }
+        if state.sample_rate != self.sample_rate {
+            return Err(SndhError::IncompatibleState(format!(
+                "saved at {} Hz, player runs at {} Hz",
+                state.sample_rate, self.sample_rate
+            )));
+        }
+        if !self.machine.load_state(&state.machine) {
+            return Err(SndhError::IncompatibleState(
+                "invalid RAM image".to_string(),
+            ));
+        }
+
+        self.current_subsong = state.current_subsong;
+        self.frame = state.frame;
+        self.frame_count = state.frame_count;
+        self.loop_count = state.loop_count;
+        self.inner_sample_pos = state.inner_sample_pos;
+        if self.state == PlaybackState::Stopped && self.current_subsong > 0 {
+            self.state = PlaybackState::Paused;
+        }
+        Ok(())
+    }
+
     /// Get the current frame position (0-based).
     pub fn current_frame(&self) -> u32 {
         self.frame
@@ -350,14 +550,12 @@ impl SndhPlayer {
         self.frame_count
     }
 
-    /// Check if the current subsong has accurate duration info (FRMS or TIME tag).
+    /// Check if the current subsong has accurate duration info (from the
+    /// duration provider or an FRMS/TIME tag).
     ///
     /// Returns false if using the default 5-minute estimate.
     pub fn has_duration_info(&self) -> bool {
-        self.sndh
-            .get_subsong_info(self.current_subsong, self.sample_rate)
-            .map(|info| info.player_tick_count > 0)
-            .unwrap_or(false)
+        self.known_subsong_frames(self.current_subsong).is_some()
     }
 
     /// Get playback progress as a fraction (0.0 to 1.0).
@@ -407,32 +605,8 @@ impl SndhPlayer {
         }
 
         // Fast-forward by calling play routine for each frame
-        // Minimal hardware simulation for performance (1 sample per tick instead of 882)
         self.frame = 0;
-        let play_addr = upload_addr + 8;
-
-        // Use reduced cycle budget during seek for speed
-        let seek_cycle_budget = self.play_cycle_budget.min(100_000);
-
-        for _ in 0..target_frame {
-            if seek_cycle_budget == 0 {
-                let _ = self.machine.jsr(play_addr, 0);
-            } else {
-                let _ = self.machine.jsr_limited(play_addr, 0, seek_cycle_budget);
-            }
-            self.frame += 1;
-
-            // Minimal hardware update (1 sample instead of full tick)
-            // This keeps basic timer state without the full audio generation overhead
-            let _ = self.machine.compute_sample_stereo();
-        }
-
-        // Reset inner sample position
-        self.inner_sample_pos = self.samples_per_tick as i32;
-
-        // Synchronize YM2149 timing after seek - flushes write queue and
-        // aligns sample_start_cycle with current CPU cycles
-        self.machine.sync_timing();
+        self.advance_frames(target_frame as usize);
 
         // Restore playback state
         if was_playing {
@@ -459,6 +633,73 @@ impl SndhPlayer {
         self.seek_to_frame(target_frame)
     }
 
+    /// Run `frames` play routine ticks without rendering audio.
+    ///
+    /// The play routine runs with a reduced cycle budget and the hardware is
+    /// clocked for one sample per tick instead of a full tick, which keeps
+    /// the timer state alive at a fraction of the rendering cost. Works in
+    /// any state; SNDH songs never end, so this returns `frames` (or 0
+    /// before a subsong was initialized).
+    pub fn advance_frames(&mut self, frames: usize) -> usize {
+        if self.current_subsong == 0 {
+            return 0;
+        }
+
+        let play_addr = self.machine.sndh_upload_addr() + 8;
+        // Use reduced cycle budget for speed
+        let cycle_budget = self.play_cycle_budget.min(100_000);
+
+        for _ in 0..frames {
+            if cycle_budget == 0 {
+                let _ = self.machine.jsr(play_addr, 0);
+            } else {
+                let _ = self.machine.jsr_limited(play_addr, 0, cycle_budget);
+            }
+            self.frame += 1;
+            if self.frame_count > 0 && self.frame >= self.frame_count {
+                self.loop_count += 1;
+            }
+
+            // Minimal hardware update (1 sample instead of full tick)
+            let _ = self.machine.compute_sample_stereo();
+        }
+
+        // Next sample calls the play routine again
+        self.inner_sample_pos = self.samples_per_tick as i32;
+
+        // Synchronize YM2149 timing - flushes write queue and aligns
+        // sample_start_cycle with current CPU cycles
+        self.machine.sync_timing();
+
+        frames
+    }
+
+    /// Advance exactly `frames` player ticks while not playing.
+    ///
+    /// Renders silently until the play routine has run `frames` times, then
+    /// pauses, so the returned delta shows the registers written by the last
+    /// tick. Returns `None` while playing or before a subsong was initialized.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        if self.state == PlaybackState::Playing || self.current_subsong == 0 {
+            return None;
+        }
+
+        let before = vec![self.machine.ym2149().dump_registers()];
+        let target = self
+            .frame
+            .saturating_add(u32::try_from(frames).unwrap_or(u32::MAX));
+        let mut frame = [0i16; 2];
+        self.state = PlaybackState::Playing;
+        while self.frame < target {
+            self.render_into_stereo(&mut frame, 0i16, |left, right| (left, right));
+        }
+        self.state = PlaybackState::Paused;
+        Some(RegisterDelta::new(
+            before,
+            vec![self.machine.ym2149().dump_registers()],
+        ))
+    }
+
     /// Render audio into a buffer of interleaved stereo i16 samples.
     ///
     /// Buffer length must be even (pairs of left/right samples).
@@ -470,11 +711,19 @@ impl SndhPlayer {
     /// Render audio into a buffer of interleaved stereo f32 samples.
     ///
     /// Buffer length must be even (pairs of left/right samples).
+    /// The player's playback rate and output gain are applied to the result.
     /// Returns loop count.
     pub fn render_f32_stereo(&mut self, buffer: &mut [f32]) -> u32 {
-        self.render_into_stereo(buffer, 0.0f32, |left, right| {
-            (left as f32 / 32768.0, right as f32 / 32768.0)
-        })
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_stereo(buffer, |source| {
+            self.render_into_stereo(source, 0.0f32, |left, right| {
+                (left as f32 / 32768.0, right as f32 / 32768.0)
+            });
+        });
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
+        self.loop_count
     }
 
     fn render_into_stereo<T: Copy>(
@@ -504,6 +753,7 @@ impl SndhPlayer {
                         .machine
                         .jsr_limited(upload_addr + 8, 0, self.play_cycle_budget);
                 }
+                self.detect_fast_timers();
                 self.inner_sample_pos = self.samples_per_tick as i32;
                 self.frame += 1;
 
@@ -581,6 +831,53 @@ impl ChiptunePlayerBase for SndhPlayer {
         self.machine.ym2149().is_channel_muted(channel)
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        SndhPlayer::step_frames(self, frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        SndhPlayer::advance_frames(self, frames)
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.machine.ym2149_mut().set_output_model(model);
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.machine.ym2149_mut().set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.machine
+            .ym2149()
+            .channel_tap()
+            .map_or(0, |tap| tap.read_channel(channel, out))
+    }
+
     fn playback_position(&self) -> f32 {
         // Return progress as fraction (0.0 to 1.0) based on FRMS/TIME duration
         self.progress()
@@ -626,6 +923,17 @@ impl ChiptunePlayer for SndhPlayer {
     fn metadata(&self) -> &Self::Metadata {
         &self.metadata
     }
+
+    fn load_report(&self) -> LoadReport {
+        let mut report = LoadReport::from_player(self)
+            .with_family(ChiptuneFormat::Sndh)
+            .with_compression(self.compression);
+        if !self.has_duration_info() {
+            // duration_seconds() reports the 5-minute fallback
+            report.duration_seconds = None;
+        }
+        report
+    }
 }
 
 #[cfg(test)]
@@ -658,6 +966,70 @@ mod tests {
         assert_eq!(player.subsong_count(), 1);
     }
 
+    #[test]
+    fn test_load_report_records_ice_packing() {
+        let data = make_minimal_sndh();
+        let player = SndhPlayer::new(&data, 44100).unwrap();
+        let report = player.load_report();
+        assert_eq!(report.family, ChiptuneFormat::Sndh);
+        assert_eq!(report.format, "SNDH");
+        assert_eq!(report.subsongs, 1);
+        assert_eq!(report.duration_seconds, None);
+        assert_eq!(report.compression, None);
+
+        let packed = crate::ice_pack(&data).unwrap();
+        let report = SndhPlayer::new(&packed, 44100).unwrap().load_report();
+        let packing = report.compression.unwrap();
+        assert_eq!(packing.method, Compression::Ice);
+        assert_eq!(packing.packed_size, packed.len());
+        assert_eq!(packing.unpacked_size, data.len());
+    }
+
+    #[test]
+    fn test_subsong_list() {
+        let mut data = vec![0u8; 16];
+        data[0] = 0x60; // BRA.s
+        data[12..16].copy_from_slice(b"SNDH");
+        data.extend_from_slice(b"##02TC100\0#!SN");
+        data.extend_from_slice(&[0x00, 0x04, 0x00, 0x0A]); // name offsets
+        data.extend_from_slice(b"Intro\0Main\0TIME\x00\x1E\x00\x00HDNS");
+        data[1] = (data.len() - 2) as u8;
+        let mut player = SndhPlayer::new(&data, 44100).unwrap();
+
+        let mut db = crate::TimingDatabase::new();
+        db.insert(player.sndh_file(), vec![0, 45_000]);
+        player.set_duration_provider(Some(Arc::new(db)));
+
+        let subsongs = player.subsongs();
+        assert_eq!(subsongs.len(), 2);
+        assert_eq!(subsongs[0].subtune_name.as_deref(), Some("Intro"));
+        assert_eq!(subsongs[1].subtune_name.as_deref(), Some("Main"));
+        assert_eq!(subsongs[0].timer, Some('C'));
+        assert_eq!(subsongs[0].player_tick_rate, 100);
+        assert_eq!(subsongs[0].duration_seconds(), Some(30.0));
+        assert_eq!(subsongs[1].duration_seconds(), Some(45.0));
+        assert!(player.subsong_info(3).is_none());
+    }
+
+    #[test]
+    fn test_duration_provider_overrides_fallback() {
+        let data = make_minimal_sndh();
+        let mut player = SndhPlayer::new(&data, 44100).unwrap();
+        let _ = player.init_subsong(1);
+        assert!(!player.has_duration_info());
+
+        let mut db = crate::TimingDatabase::new();
+        db.insert(&SndhFile::parse(&data).unwrap(), vec![90_000]);
+        player.set_duration_provider(Some(Arc::new(db)));
+
+        assert!(player.has_duration_info());
+        assert_eq!(player.total_frames(), 4500);
+        assert!((player.duration_seconds() - 90.0).abs() < 0.1);
+
+        player.set_duration_provider(None);
+        assert_eq!(player.total_frames(), 15000);
+    }
+
     #[test]
     fn test_fallback_duration_for_old_sndh() {
         // Minimal SNDH without FRMS/TIME should get fallback duration
@@ -691,4 +1063,48 @@ mod tests {
             "Should report no duration info (using fallback)"
         );
     }
+
+    /// SNDH whose play routine adds 7 to a RAM counter and writes it to the
+    /// channel A fine period, with channel A tone enabled at full volume.
+    fn make_counting_sndh() -> Vec<u8> {
+        let mut data = make_minimal_sndh();
+        data[4..6].copy_from_slice(&[0x60, 0x3A]); // exit: BRA.s to RTS
+        data[8..10].copy_from_slice(&[0x60, 0x38]); // play: BRA.s to byte 66
+        let code: [u16; 23] = [
+            0x4E75, // init/exit: RTS
+            0x5E78, 0x1000, // ADDQ.W #7,$1000.w
+            0x11FC, 0x0000, 0x8800, // MOVE.B #0,$FF8800.w
+            0x11F8, 0x1001, 0x8802, // MOVE.B $1001.w,$FF8802.w
+            0x11FC, 0x0008, 0x8800, // MOVE.B #8,$FF8800.w
+            0x11FC, 0x000F, 0x8802, // MOVE.B #15,$FF8802.w
+            0x11FC, 0x0007, 0x8800, // MOVE.B #7,$FF8800.w
+            0x11FC, 0x003E, 0x8802, // MOVE.B #$3E,$FF8802.w
+            0x4E75, // play: RTS
+            0x0000,
+        ];
+        data.extend(code.iter().flat_map(|word| word.to_be_bytes()));
+        data
+    }
+
+    #[test]
+    fn test_load_state_resumes_identically() {
+        let data = make_counting_sndh();
+        let mut player = SndhPlayer::new(&data, 44100).unwrap();
+        player.init_subsong(1).unwrap();
+        ChiptunePlayerBase::play(&mut player);
+        player.generate_samples(5_000);
+        let state = player.save_state();
+        let expected = player.generate_samples(5_000);
+        assert!(expected.iter().any(|&sample| sample != expected[0]));
+
+        let mut other = SndhPlayer::new(&data, 44100).unwrap();
+        other.load_state(&state).unwrap();
+        assert_eq!(other.state(), PlaybackState::Paused);
+        ChiptunePlayerBase::play(&mut other);
+        assert_eq!(other.generate_samples(5_000), expected);
+        assert_eq!(other.current_frame(), player.current_frame());
+
+        let other_rate = SndhPlayer::new(&data, 48000).unwrap().load_state(&state);
+        assert!(other_rate.is_err());
+    }
 }
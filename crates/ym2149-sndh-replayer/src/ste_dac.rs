@@ -45,6 +45,8 @@ const DAC_FREQ: [u32; 4] = [
 ];
 
 /// STE DMA Sound emulation
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SteDac {
     host_replay_rate: u32,
     sample_ptr: u32,
@@ -53,6 +55,7 @@ pub struct SteDac {
     microwire_mask: u16,
     microwire_data: u16,
     microwire_shift: i32,
+    #[cfg_attr(feature = "serde", serde(with = "crate::serde_array"))]
     regs: [u8; 256],
     master_volume: i32,
     /// 50kHz to 25kHz averaging toggle
@@ -0,0 +1,34 @@
+//! Mutated real files must be rejected with an error, never a panic.
+
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::{mutants, read_asset};
+use ym2149_sndh_replayer::{SndhFile, ice_depack, load_sndh};
+
+#[test]
+fn mutated_sndh_files_never_panic() {
+    let Some(packed) = read_asset("Wings_Of_Death.sndh") else {
+        return;
+    };
+    // The ICE layer first, then the unpacked song
+    for data in mutants(&packed, 30) {
+        let _ = ice_depack(&data);
+    }
+
+    let song = ice_depack(&packed).expect("asset unpacks");
+    for (index, data) in mutants(&song, 300).enumerate() {
+        let _ = SndhFile::parse(&data);
+        // Running the 68000 code is slow in debug builds; play every tenth mutant
+        if index % 10 != 0 {
+            continue;
+        }
+        if let Ok(mut player) = load_sndh(&data, 44_100)
+            && player.init_subsong(player.default_subsong()).is_ok()
+        {
+            player.play();
+            let mut buffer = [0.0f32; 882];
+            for _ in 0..2 {
+                player.generate_samples_into(&mut buffer);
+            }
+        }
+    }
+}
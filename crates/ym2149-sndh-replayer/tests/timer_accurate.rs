@@ -0,0 +1,73 @@
+//! Songs that drive samples or effects from a fast MFP timer switch to
+//! timer-accurate playback by themselves.
+
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::read_asset;
+use ym2149_sndh_replayer::{SndhPlayer, load_sndh};
+
+const SONG: &str = "Wings_Of_Death.sndh";
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// Subsong 1 plays on the replay tick only; subsong 2 also runs a timer
+/// far above the 50 Hz replay rate.
+const FRAME_TIMED_SUBSONG: usize = 1;
+const FAST_TIMER_SUBSONG: usize = 2;
+
+fn player(subsong: usize) -> Option<SndhPlayer> {
+    let data = read_asset(SONG)?;
+    let mut player = load_sndh(&data, SAMPLE_RATE).expect("song loads");
+    player.init_subsong(subsong).expect("subsong starts");
+    Some(player)
+}
+
+/// Render `seconds` of audio and return the peak level.
+fn render(player: &mut SndhPlayer, seconds: f32) -> f32 {
+    player.play();
+    let mut buffer = vec![0.0f32; (SAMPLE_RATE as f32 * seconds) as usize];
+    player.generate_samples_into(&mut buffer);
+    assert!(buffer.iter().all(|sample| sample.is_finite()));
+    buffer
+        .iter()
+        .fold(0.0, |peak, sample| peak.max(sample.abs()))
+}
+
+#[test]
+fn fast_timer_songs_play_timer_accurate() {
+    let Some(mut player) = player(FAST_TIMER_SUBSONG) else {
+        return;
+    };
+    assert!(player.timer_accurate());
+    let peak = render(&mut player, 1.0);
+    assert!(peak > 0.01 && peak <= 1.0, "peak {peak}");
+    assert!(player.timer_accurate());
+}
+
+#[test]
+fn frame_timed_songs_keep_sample_timing() {
+    let Some(mut player) = player(FRAME_TIMED_SUBSONG) else {
+        return;
+    };
+    let peak = render(&mut player, 1.0);
+    assert!(peak > 0.01, "peak {peak}");
+    assert!(!player.timer_accurate());
+}
+
+#[test]
+fn explicit_mode_overrides_detection() {
+    let Some(mut player) = player(FRAME_TIMED_SUBSONG) else {
+        return;
+    };
+    player.set_timer_accurate(false);
+    player
+        .init_subsong(FAST_TIMER_SUBSONG)
+        .expect("subsong starts");
+    render(&mut player, 0.2);
+    assert!(!player.timer_accurate());
+
+    player.set_timer_accurate(true);
+    player
+        .init_subsong(FRAME_TIMED_SUBSONG)
+        .expect("subsong starts");
+    assert!(player.timer_accurate());
+}
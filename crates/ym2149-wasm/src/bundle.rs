@@ -0,0 +1,49 @@
+//! Song formats included in this build.
+//!
+//! YM playback is always built in. The SNDH (68000), AY and KSS (Z80) and
+//! Arkos (XML) players sit behind the `sndh`, `ay`, `kss` and `arkos`
+//! features, so a page that only plays YM files can ship a much smaller
+//! module and load the full bundle once another format shows up.
+
+use wasm_bindgen::prelude::*;
+use ym2149_common::ChiptuneFormat;
+
+/// Cargo feature of each song format and whether it is compiled in.
+const FORMAT_FEATURES: [(ChiptuneFormat, &str, bool); 5] = [
+    (ChiptuneFormat::Ym, "ym", true),
+    (ChiptuneFormat::Arkos, "arkos", cfg!(feature = "arkos")),
+    (ChiptuneFormat::Ay, "ay", cfg!(feature = "ay")),
+    (ChiptuneFormat::Sndh, "sndh", cfg!(feature = "sndh")),
+    (ChiptuneFormat::Kss, "kss", cfg!(feature = "kss")),
+];
+
+/// Names of the song formats this build can play (`"YM"`, `"AKS"`, `"AY"`,
+/// `"SNDH"`, `"KSS"`).
+///
+/// ```javascript
+/// import init, { supportedFormats } from './ym-only/ym2149_wasm.js';
+/// await init();
+/// if (!supportedFormats().includes('SNDH')) {
+///     // fall back to the full bundle for this song
+/// }
+/// ```
+#[wasm_bindgen(js_name = supportedFormats)]
+pub fn supported_formats() -> Vec<String> {
+    FORMAT_FEATURES
+        .iter()
+        .filter(|(_, _, included)| *included)
+        .map(|(format, _, _)| format.name().to_string())
+        .collect()
+}
+
+/// Error for a song whose format was left out of this build.
+pub(crate) fn format_not_included(format: ChiptuneFormat) -> String {
+    let feature = FORMAT_FEATURES
+        .iter()
+        .find(|(known, _, _)| *known == format)
+        .map_or("?", |(_, feature, _)| feature);
+    format!(
+        "{} support is not included in this build (enable the `{feature}` feature)",
+        format.name()
+    )
+}
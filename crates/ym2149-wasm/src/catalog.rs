@@ -0,0 +1,34 @@
+//! Track catalogs written by `ym-metadata`.
+//!
+//! `parseCatalog` reads a catalog with the same schema types as the metadata
+//! tool (`ym2149-catalog`), so the front-end fails loudly on catalogs it does
+//! not understand instead of reading missing fields.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+use ym2149_catalog::{CATALOG_VERSION, Catalog};
+
+/// Parse a catalog JSON, upgrading older schema versions to the current one.
+///
+/// Returns the catalog as a plain object (`{ version, generated, collections,
+/// tracks }`, tracks with the field names of the JSON). Throws for catalogs
+/// written by a newer `ym-metadata` or with malformed fields.
+///
+/// ```javascript
+/// const response = await fetch('catalog.json');
+/// const catalog = parseCatalog(await response.text());
+/// const calm = catalog.tracks.filter(track => track.tags?.includes('calm'));
+/// ```
+#[wasm_bindgen(js_name = parseCatalog)]
+pub fn parse_catalog(json: &str) -> Result<JsValue, JsValue> {
+    let catalog = Catalog::from_json(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    catalog
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(JsValue::from)
+}
+
+/// Catalog schema version this build reads (e.g. `"1.3"`).
+#[wasm_bindgen(js_name = catalogVersion)]
+pub fn catalog_version() -> String {
+    CATALOG_VERSION.to_string()
+}
@@ -0,0 +1,254 @@
+//! HTTP loading for the browser player.
+//!
+//! Fetches song files with the browser `fetch()` API so JavaScript callers
+//! only need to pass a URL. Large files are downloaded in ranged chunks when
+//! the server supports `Range` requests, which lets the player report progress
+//! for big SNDH archives. Gzip payloads (e.g. `.ym.gz` or `.sndh.gz` files
+//! served without `Content-Encoding`) are decompressed transparently.
+
+use std::io::Read;
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response};
+
+/// Size of each ranged request (256 KiB).
+const RANGE_CHUNK_SIZE: u64 = 256 * 1024;
+
+/// Upper bound for downloaded and decompressed payloads (64 MiB).
+///
+/// Chiptune files are at most a few megabytes; this guards against
+/// misconfigured URLs and gzip bombs.
+const MAX_PAYLOAD_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Gzip member magic bytes.
+const GZIP_MAGIC: [u8; 2] = [0x1F, 0x8B];
+
+/// How the rest of a file is downloaded after the first ranged request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Transfer {
+    /// The first response already carried the whole file (plain `200`).
+    Complete,
+    /// `206` with `Content-Range: bytes a-b/total`: ranges up to `total`.
+    Ranged { total: u64 },
+    /// `206` without a usable total (`bytes a-b/*`): ranges until a short or
+    /// empty chunk.
+    RangedUnknownSize,
+}
+
+impl Transfer {
+    fn from_response(status: u16, content_range: Option<&str>) -> Self {
+        if status != 206 {
+            return Self::Complete;
+        }
+        match content_range.and_then(parse_content_range_total) {
+            Some(total) => Self::Ranged { total },
+            None => Self::RangedUnknownSize,
+        }
+    }
+
+    /// Total size as reported by the server.
+    fn total(self) -> Option<u64> {
+        match self {
+            Self::Ranged { total } => Some(total),
+            Self::Complete | Self::RangedUnknownSize => None,
+        }
+    }
+
+    /// Whether another range is needed after `loaded` bytes, the last
+    /// chunk being `last_chunk` bytes long.
+    fn needs_more(self, loaded: u64, last_chunk: u64) -> bool {
+        match self {
+            Self::Complete => false,
+            Self::Ranged { total } => loaded < total,
+            Self::RangedUnknownSize => last_chunk >= RANGE_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Download a song file and return its (decompressed) bytes.
+///
+/// `on_progress` is invoked as `on_progress(loaded, total)` after every chunk.
+/// `total` is `undefined` while the size is unknown; the last call always
+/// reports the final size.
+pub async fn fetch_song_bytes(
+    url: &str,
+    on_progress: Option<&js_sys::Function>,
+) -> Result<Vec<u8>, String> {
+    let first = fetch_range(url, 0, RANGE_CHUNK_SIZE - 1).await?;
+    if !first.ok() {
+        return Err(format!("HTTP {} fetching {url}", first.status()));
+    }
+    let transfer = Transfer::from_response(first.status(), content_range(&first).as_deref());
+    let total = transfer.total();
+    if let Some(total) = total
+        && total > MAX_PAYLOAD_SIZE
+    {
+        return Err(format!("file too large ({total} bytes)"));
+    }
+    let mut bytes = response_bytes(&first).await?;
+    let mut last_chunk = bytes.len() as u64;
+
+    // A 206 means the server honours range requests, so the rest of the
+    // file is pulled in chunks. A plain 200 already carries the whole body.
+    while transfer.needs_more(bytes.len() as u64, last_chunk) {
+        if bytes.len() as u64 > MAX_PAYLOAD_SIZE {
+            return Err(format!("file too large (over {MAX_PAYLOAD_SIZE} bytes)"));
+        }
+        report_progress(on_progress, bytes.len() as u64, total);
+        let start = bytes.len() as u64;
+        let end = match total {
+            Some(total) => (start + RANGE_CHUNK_SIZE).min(total) - 1,
+            None => start + RANGE_CHUNK_SIZE - 1,
+        };
+        let chunk = fetch_range(url, start, end).await?;
+        // Without a total the file may end exactly on a chunk boundary
+        if total.is_none() && chunk.status() == 416 {
+            break;
+        }
+        if chunk.status() != 206 {
+            return Err(format!("range request failed with HTTP {}", chunk.status()));
+        }
+        let data = response_bytes(&chunk).await?;
+        if data.is_empty() && total.is_some() {
+            return Err("server returned an empty range".to_string());
+        }
+        last_chunk = data.len() as u64;
+        bytes.extend_from_slice(&data);
+    }
+
+    if bytes.len() as u64 > MAX_PAYLOAD_SIZE {
+        return Err(format!("file too large ({} bytes)", bytes.len()));
+    }
+    report_progress(on_progress, bytes.len() as u64, Some(bytes.len() as u64));
+
+    decode_payload(bytes)
+}
+
+/// Undo transport-level packing that the browser did not already remove.
+///
+/// Browsers decode `Content-Encoding: gzip` themselves; this handles gzip
+/// files that were served verbatim.
+pub fn decode_payload(bytes: Vec<u8>) -> Result<Vec<u8>, String> {
+    if bytes.starts_with(&GZIP_MAGIC) {
+        let mut decoded = Vec::new();
+        flate2::read::MultiGzDecoder::new(bytes.as_slice())
+            .take(MAX_PAYLOAD_SIZE + 1)
+            .read_to_end(&mut decoded)
+            .map_err(|e| format!("gzip decompression failed: {e}"))?;
+        if decoded.len() as u64 > MAX_PAYLOAD_SIZE {
+            return Err("gzip payload exceeds size limit".to_string());
+        }
+        return Ok(decoded);
+    }
+    Ok(bytes)
+}
+
+/// Issue a GET request for `start..=end`; the caller checks the status.
+async fn fetch_range(url: &str, start: u64, end: u64) -> Result<Response, String> {
+    let window = web_sys::window().ok_or("no window object available")?;
+
+    let init = RequestInit::new();
+    init.set_method("GET");
+    let request = Request::new_with_str_and_init(url, &init).map_err(js_error)?;
+    request
+        .headers()
+        .set("Range", &format!("bytes={start}-{end}"))
+        .map_err(js_error)?;
+
+    let response: Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(js_error)?
+        .dyn_into()
+        .map_err(js_error)?;
+    Ok(response)
+}
+
+/// Read the complete body of a response.
+async fn response_bytes(response: &Response) -> Result<Vec<u8>, String> {
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?)
+        .await
+        .map_err(js_error)?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// The `Content-Range` header of a response.
+fn content_range(response: &Response) -> Option<String> {
+    response.headers().get("Content-Range").ok().flatten()
+}
+
+/// Extract the full resource length from a `Content-Range: bytes a-b/total`
+/// header; `None` for an unknown (`*`) or malformed total.
+fn parse_content_range_total(header: &str) -> Option<u64> {
+    header.rsplit_once('/')?.1.trim().parse().ok()
+}
+
+fn report_progress(callback: Option<&js_sys::Function>, loaded: u64, total: Option<u64>) {
+    if let Some(callback) = callback {
+        let total = total.map_or(JsValue::UNDEFINED, |t| JsValue::from_f64(t as f64));
+        let _ = callback.call2(&JsValue::NULL, &JsValue::from_f64(loaded as f64), &total);
+    }
+}
+
+fn js_error(value: JsValue) -> String {
+    value
+        .as_string()
+        .or_else(|| {
+            value
+                .dyn_ref::<js_sys::Error>()
+                .map(|e| String::from(e.message()))
+        })
+        .unwrap_or_else(|| format!("{value:?}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_range_total() {
+        assert_eq!(
+            parse_content_range_total("bytes 0-262143/1048576"),
+            Some(1_048_576)
+        );
+        assert_eq!(parse_content_range_total("bytes 0-262143/*"), None);
+        assert_eq!(parse_content_range_total("bytes 0-262143"), None);
+        assert_eq!(parse_content_range_total("bytes 0-262143/12ab"), None);
+        assert_eq!(parse_content_range_total(""), None);
+    }
+
+    #[test]
+    fn test_transfer_from_response() {
+        assert_eq!(Transfer::from_response(200, None), Transfer::Complete);
+        assert_eq!(
+            Transfer::from_response(200, Some("bytes 0-99/100")),
+            Transfer::Complete
+        );
+        assert_eq!(
+            Transfer::from_response(206, Some("bytes 0-262143/300000")),
+            Transfer::Ranged { total: 300_000 }
+        );
+        assert_eq!(
+            Transfer::from_response(206, Some("bytes 0-262143/*")),
+            Transfer::RangedUnknownSize
+        );
+        assert_eq!(
+            Transfer::from_response(206, None),
+            Transfer::RangedUnknownSize
+        );
+    }
+
+    #[test]
+    fn test_unknown_size_ranges_until_short_chunk() {
+        let transfer = Transfer::RangedUnknownSize;
+        assert!(transfer.needs_more(RANGE_CHUNK_SIZE, RANGE_CHUNK_SIZE));
+        assert!(!transfer.needs_more(RANGE_CHUNK_SIZE + 10, 10));
+        assert!(!transfer.needs_more(RANGE_CHUNK_SIZE, 0));
+
+        let transfer = Transfer::Ranged { total: 300_000 };
+        assert!(transfer.needs_more(RANGE_CHUNK_SIZE, RANGE_CHUNK_SIZE));
+        assert!(!transfer.needs_more(300_000, 300_000 - RANGE_CHUNK_SIZE));
+        assert!(!Transfer::Complete.needs_more(100, 100));
+    }
+}
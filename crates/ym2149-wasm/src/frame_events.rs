@@ -0,0 +1,170 @@
+//! Register writes and effect changes per rendered frame.
+//!
+//! Once `getFrameEvents` has been called, the player renders audio in slices
+//! of a quarter frame and records the PSG registers whenever the song moves
+//! to another frame. Each call hands the recorded frames to JS with their
+//! register changes and, for YM songs, the effects that started or stopped
+//! on them, so visualizers can animate effects on the frame they happen.
+
+use std::collections::VecDeque;
+
+use wasm_bindgen::prelude::*;
+use ym2149_common::{DEFAULT_SAMPLE_RATE, RegisterDelta};
+use ym2149_ym_replayer::{EffectEvent, EffectEventKind};
+
+use crate::players::BrowserSongPlayer;
+use crate::set_js_prop;
+
+/// Recorded frames kept when JS stops polling (10 seconds at 50 Hz).
+const MAX_PENDING_FRAMES: usize = 500;
+
+/// Rendering slices per song frame while recording.
+const SLICES_PER_FRAME: u32 = 4;
+
+/// A frame reached since the previous recording.
+struct RecordedFrame {
+    /// Frame recorded before this one.
+    previous: usize,
+    /// Frame the song is on.
+    frame: usize,
+    /// Registers of every PSG before and after.
+    delta: RegisterDelta,
+}
+
+/// Frames rendered since the last `getFrameEvents` call.
+pub(crate) struct FrameEventLog {
+    /// Effect changes of the whole song, ordered by frame (YM only).
+    effects: Vec<EffectEvent>,
+    /// Samples rendered between recordings.
+    slice: usize,
+    last_frame: usize,
+    last_registers: Vec<[u8; 16]>,
+    pending: VecDeque<RecordedFrame>,
+}
+
+impl FrameEventLog {
+    /// Start recording at the player's current frame.
+    pub fn new(player: &BrowserSongPlayer, frame_rate: u32) -> Self {
+        let slice = DEFAULT_SAMPLE_RATE / (frame_rate.max(1) * SLICES_PER_FRAME);
+        Self {
+            effects: player.effect_events(),
+            slice: slice.max(1) as usize,
+            last_frame: player.frame_position(),
+            last_registers: player.dump_all_registers(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Samples to render before the next [`record`](Self::record).
+    pub fn slice(&self) -> usize {
+        self.slice
+    }
+
+    /// Record the player's registers if it moved to another frame.
+    pub fn record(&mut self, player: &BrowserSongPlayer) {
+        let frame = player.frame_position();
+        if frame == self.last_frame {
+            return;
+        }
+        let registers = player.dump_all_registers();
+        if self.pending.len() == MAX_PENDING_FRAMES {
+            self.pending.pop_front();
+        }
+        self.pending.push_back(RecordedFrame {
+            previous: self.last_frame,
+            frame,
+            delta: RegisterDelta::new(
+                std::mem::replace(&mut self.last_registers, registers.clone()),
+                registers,
+            ),
+        });
+        self.last_frame = frame;
+    }
+
+    /// Hand the recorded frames to JS and clear them.
+    pub fn drain(&mut self) -> js_sys::Array {
+        let frames = js_sys::Array::new();
+        for recorded in self.pending.drain(..) {
+            let registers: Vec<u8> = recorded
+                .delta
+                .changes()
+                .flat_map(|change| {
+                    [
+                        change.psg as u8,
+                        change.register as u8,
+                        change.old,
+                        change.new,
+                    ]
+                })
+                .collect();
+
+            // Effects of every frame skipped since the previous recording;
+            // after a seek or loop only those of the new frame
+            let first = if recorded.frame > recorded.previous {
+                recorded.previous + 1
+            } else {
+                recorded.frame
+            };
+            let start = self.effects.partition_point(|event| event.frame < first);
+            let effects = js_sys::Array::new();
+            for event in self.effects[start..]
+                .iter()
+                .take_while(|event| event.frame <= recorded.frame)
+            {
+                effects.push(&effect_to_js(event.kind));
+            }
+
+            let obj = js_sys::Object::new();
+            set_js_prop(&obj, "frame", recorded.frame as u32);
+            set_js_prop(
+                &obj,
+                "registers",
+                js_sys::Uint8Array::from(registers.as_slice()),
+            );
+            set_js_prop(&obj, "effects", &effects);
+            frames.push(&obj);
+        }
+        frames
+    }
+}
+
+/// JS object for an effect change.
+fn effect_to_js(kind: EffectEventKind) -> JsValue {
+    let obj = js_sys::Object::new();
+    match kind {
+        EffectEventKind::SidStart {
+            channel,
+            freq,
+            volume,
+            sinus,
+        } => {
+            set_js_prop(&obj, "type", "sidStart");
+            set_js_prop(&obj, "channel", channel as u32);
+            set_js_prop(&obj, "frequency", freq);
+            set_js_prop(&obj, "volume", volume);
+            set_js_prop(&obj, "sinus", sinus);
+        }
+        EffectEventKind::SidStop { channel } => {
+            set_js_prop(&obj, "type", "sidStop");
+            set_js_prop(&obj, "channel", channel as u32);
+        }
+        EffectEventKind::DigiDrum {
+            channel,
+            drum,
+            sample_rate_hz,
+        } => {
+            set_js_prop(&obj, "type", "digidrum");
+            set_js_prop(&obj, "channel", channel as u32);
+            set_js_prop(&obj, "drum", drum as u32);
+            set_js_prop(&obj, "sampleRate", sample_rate_hz);
+        }
+        EffectEventKind::SyncBuzzerStart { freq } => {
+            set_js_prop(&obj, "type", "syncBuzzerStart");
+            set_js_prop(&obj, "frequency", freq);
+        }
+        EffectEventKind::SyncBuzzerStop => {
+            set_js_prop(&obj, "type", "syncBuzzerStop");
+        }
+    }
+    obj.into()
+}
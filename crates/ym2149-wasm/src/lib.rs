@@ -8,11 +8,20 @@
 //! - Load and play YM2-YM6 format files
 //! - Load and play Arkos Tracker (.aks) files
 //! - Load and play AY format files
+//! - Open ZIP collections and enumerate their songs
 //! - Playback control (play, pause, stop, seek)
+//! - Background pre-rendering for seeking Arkos and AY songs
 //! - Volume control
 //! - Metadata extraction (title, author, comments)
 //! - Channel muting/solo
+//! - Stereo output with per-channel panning (`setChannelPan`)
 //! - Real-time waveform data for visualization
+//! - Register writes and effect changes per frame (`getFrameEvents`)
+//! - Per-second song timeline for seek bars (`EventTimeline`)
+//! - Waveform peaks for songs outside the catalog (`computeWaveform`)
+//! - Fast metadata probing for playlists (`probeMetadata`)
+//! - Versioned catalog parsing shared with `ym-metadata` (`parseCatalog`)
+//! - Feature-gated format players for smaller YM-only bundles (`supportedFormats`)
 //!
 //! # Example Usage (JavaScript)
 //!
@@ -30,27 +39,74 @@
 //! }
 //! ```
 //!
+//! Files can also be loaded straight from a URL:
+//!
+//! ```javascript
+//! const player = await Ym2149Player.fromUrl('music/song.sndh', (loaded, total) => {
+//!     console.log(`Downloaded ${loaded} of ${total ?? '?'} bytes`);
+//! });
+//! ```
+//!
 //! # Module Organization
 //!
 //! Internal modules handle:
 //!
+//! - Catalog parsing
+//! - HTTP fetching with ranged downloads and gzip decoding
 //! - Metadata types and conversion functions
 //! - Player wrappers for different file formats
+//! - Song timeline summaries
+//! - Waveform peak rendering
 
 #![warn(missing_docs)]
 
+mod bundle;
+mod catalog;
+mod fetch;
+mod frame_events;
 mod metadata;
+mod panning;
 mod players;
+mod probe;
+mod timeline;
+mod waveform;
 
 use wasm_bindgen::prelude::*;
+#[cfg(feature = "arkos")]
 use ym2149_arkos_replayer::{ArkosPlayer, load_aks};
+#[cfg(feature = "ay")]
 use ym2149_ay_replayer::{AyPlayer, CPC_UNSUPPORTED_MSG};
-use ym2149_sndh_replayer::is_sndh_data;
-use ym2149_ym_replayer::{PlaybackState, load_song};
-
-use metadata::{YmMetadata, metadata_from_summary};
-use players::{BrowserSongPlayer, arkos::ArkosWasmPlayer, ay::AyWasmPlayer, sndh::SndhWasmPlayer};
-use ym2149_common::DEFAULT_SAMPLE_RATE;
+#[cfg(feature = "kss")]
+use ym2149_kss_replayer::KssPlayer;
+use ym2149_ym_replayer::{PlaybackState, extract_zip_entry, is_zip_archive, zip_entry_names};
+
+use bundle::format_not_included;
+use frame_events::FrameEventLog;
+use metadata::YmMetadata;
+use panning::StereoPanning;
+#[cfg(feature = "arkos")]
+use players::arkos::ArkosWasmPlayer;
+#[cfg(feature = "ay")]
+use players::ay::AyWasmPlayer;
+#[cfg(feature = "kss")]
+use players::kss::KssWasmPlayer;
+#[cfg(feature = "sndh")]
+use players::sndh::SndhWasmPlayer;
+use players::{
+    BrowserSongPlayer, mono_to_stereo_in_place,
+    ym::{YmChip, YmWasmPlayer},
+};
+use ym2149_common::{
+    ChiptuneFormat, DEFAULT_PRERENDER_CHUNK, DEFAULT_SAMPLE_RATE, MAX_CHANNEL_COUNT,
+    MAX_TAP_CAPACITY, OutputGain, OutputModel, PitchTracker, RegisterDelayLine, SoloState,
+    TrackCache, load_with_detection,
+};
+
+pub use bundle::supported_formats;
+pub use catalog::{catalog_version, parse_catalog};
+pub use probe::probe_metadata;
+pub use timeline::EventTimeline;
+pub use waveform::compute_waveform;
 
 /// Sample rate used for audio generation.
 pub const YM_SAMPLE_RATE_F32: f32 = DEFAULT_SAMPLE_RATE as f32;
@@ -61,6 +117,15 @@ pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// Envelope level curve for an R13 shape value (0-15), normalized to 0.0-1.0.
+///
+/// Returns two 32-step envelope cycles so UIs can draw the real shape of
+/// the `shape` reported by `getChannelStates`.
+#[wasm_bindgen(js_name = envelopeWaveform)]
+pub fn envelope_waveform(shape: u8) -> Vec<f32> {
+    ym2149_common::envelope_waveform(shape).to_vec()
+}
+
 /// Log to browser console.
 macro_rules! console_log {
     ($($t:tt)*) => {
@@ -68,16 +133,6 @@ macro_rules! console_log {
     }
 }
 
-/// Apply volume scaling to audio samples.
-#[inline]
-fn apply_volume(samples: &mut [f32], volume: f32) {
-    if volume != 1.0 {
-        for sample in samples.iter_mut() {
-            *sample *= volume;
-        }
-    }
-}
-
 /// Set a property on a JavaScript object (ignores errors).
 #[inline]
 fn set_js_prop(obj: &js_sys::Object, key: &str, value: impl Into<JsValue>) {
@@ -92,7 +147,20 @@ fn set_js_prop(obj: &js_sys::Object, key: &str, value: impl Into<JsValue>) {
 pub struct Ym2149Player {
     player: BrowserSongPlayer,
     metadata: YmMetadata,
-    volume: f32,
+    /// Channel soloed via `setChannelSolo` and the mutes to restore.
+    solo: SoloState,
+    /// Register dumps delayed by the output latency for `getChannelStates`.
+    register_delay: RegisterDelayLine,
+    /// Vibrato/slide detectors per channel, fed by `getChannelStates` polls.
+    pitch_trackers: Vec<PitchTracker>,
+    /// Song file, kept to create a second player for pre-rendering.
+    source: Vec<u8>,
+    /// Pre-rendered audio used to seek songs that cannot seek natively.
+    track_cache: Option<TrackCache>,
+    /// Frames recorded for `getFrameEvents`, once it has been called.
+    frame_events: Option<FrameEventLog>,
+    /// Channel pans applied by the stereo outputs.
+    panning: StereoPanning,
 }
 
 #[wasm_bindgen]
@@ -104,15 +172,27 @@ impl Ym2149Player {
     /// # Arguments
     ///
     /// * `data` - File data as Uint8Array
+    /// * `chip` - Optional chip backend for YM songs: `"hardware"` (default,
+    ///   cycle-accurate) or `"softsynth"` (experimental). Other formats always
+    ///   use their own hardware emulation.
     ///
     /// # Returns
     ///
     /// Result containing the player or an error message.
     #[wasm_bindgen(constructor)]
-    pub fn new(data: &[u8]) -> Result<Ym2149Player, JsValue> {
+    pub fn new(data: &[u8], chip: Option<String>) -> Result<Ym2149Player, JsValue> {
         console_log!("Loading file ({} bytes)...", data.len());
 
-        let (player, metadata) = load_browser_player(data).map_err(|e| {
+        let chip = match chip.as_deref() {
+            None => YmChip::default(),
+            Some(name) => YmChip::from_name(name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Unknown chip '{name}' (expected 'hardware' or 'softsynth')"
+                ))
+            })?,
+        };
+
+        let (player, metadata) = load_browser_player(data, chip).map_err(|e| {
             JsValue::from_str(&format!(
                 "Failed to load chiptune file ({} bytes): {}",
                 data.len(),
@@ -124,13 +204,71 @@ impl Ym2149Player {
         console_log!("  Title: {}", metadata.title);
         console_log!("  Format: {}", metadata.format);
 
+        let panning = StereoPanning::new(player.channel_count());
         Ok(Ym2149Player {
             player,
             metadata,
-            volume: 1.0,
+            solo: SoloState::new(),
+            register_delay: RegisterDelayLine::default(),
+            pitch_trackers: Vec::new(),
+            source: data.to_vec(),
+            track_cache: None,
+            frame_events: None,
+            panning,
         })
     }
 
+    /// Fetch a file from a URL and create a player for it.
+    ///
+    /// Large files are downloaded with HTTP range requests when the server
+    /// supports them. Gzip-compressed files are decompressed automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Location of the song file
+    /// * `on_progress` - Optional callback invoked as `(loaded, total)` after each chunk;
+    ///   `total` is `undefined` while the size is unknown, and the last call
+    ///   reports the final size
+    ///
+    /// # Returns
+    ///
+    /// Promise resolving to the player, or rejecting with an error message.
+    #[wasm_bindgen(js_name = fromUrl)]
+    pub async fn from_url(
+        url: String,
+        on_progress: Option<js_sys::Function>,
+    ) -> Result<Ym2149Player, JsValue> {
+        console_log!("Fetching {}...", url);
+
+        let data = fetch::fetch_song_bytes(&url, on_progress.as_ref())
+            .await
+            .map_err(|e| JsValue::from_str(&format!("Failed to fetch '{url}': {e}")))?;
+
+        Self::new(&data, None)
+    }
+
+    /// List the playable songs inside a ZIP archive.
+    ///
+    /// Returns entry names (YM, AKS, AY and SNDH files) in archive order, suitable
+    /// for building a playlist. Pass a name to `fromArchiveEntry` to load it.
+    #[wasm_bindgen(js_name = archiveEntries)]
+    pub fn archive_entries(data: &[u8]) -> Result<Vec<String>, JsValue> {
+        playable_archive_entries(data).map_err(|e| JsValue::from_str(&e))
+    }
+
+    /// Create a player from one entry of a ZIP archive.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - ZIP archive data as Uint8Array
+    /// * `name` - Entry name as returned by `archiveEntries`
+    #[wasm_bindgen(js_name = fromArchiveEntry)]
+    pub fn from_archive_entry(data: &[u8], name: &str) -> Result<Ym2149Player, JsValue> {
+        let entry = extract_zip_entry(data, name)
+            .map_err(|e| JsValue::from_str(&format!("Failed to open '{name}': {e}")))?;
+        Self::new(&entry, None)
+    }
+
     /// Get metadata about the loaded file.
     #[wasm_bindgen(getter)]
     pub fn metadata(&self) -> YmMetadata {
@@ -150,12 +288,16 @@ impl Ym2149Player {
     /// Stop playback and reset to beginning.
     pub fn stop(&mut self) {
         self.player.stop();
+        self.leave_track_cache();
+        self.register_delay.clear();
     }
 
     /// Restart playback from the beginning.
     pub fn restart(&mut self) {
         self.player.stop();
         self.player.play();
+        self.leave_track_cache();
+        self.register_delay.clear();
     }
 
     /// Get current playback state.
@@ -168,19 +310,73 @@ impl Ym2149Player {
         format!("{:?}", self.player.state())
     }
 
-    /// Set volume (0.0 to 1.0). Applied to generated samples.
+    /// Set volume (0.0 to 1.0). Applied to generated samples through the player's output gain.
     pub fn set_volume(&mut self, volume: f32) {
-        self.volume = volume.clamp(0.0, 1.0);
+        self.player.set_gain(volume.clamp(0.0, 1.0));
     }
 
     /// Get current volume (0.0 to 1.0).
     pub fn volume(&self) -> f32 {
-        self.volume
+        self.player.gain()
+    }
+
+    /// Set the playback rate (1.0 = original speed, clamped to 0.25 to 4.0).
+    ///
+    /// Speed and pitch change together, e.g. `0.5` plays at half speed one
+    /// octave lower.
+    #[wasm_bindgen(js_name = setPlaybackRate)]
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        self.player.set_playback_rate(rate);
+    }
+
+    /// Get the current playback rate.
+    #[wasm_bindgen(js_name = playbackRate)]
+    pub fn playback_rate(&self) -> f32 {
+        self.player.playback_rate()
+    }
+
+    /// Enable or disable the output soft clipper.
+    ///
+    /// When enabled, peaks above 0.8 are compressed smoothly instead of
+    /// exceeding full scale.
+    #[wasm_bindgen(js_name = setSoftClip)]
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        self.player.set_soft_clip(enabled);
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    #[wasm_bindgen(js_name = softClip)]
+    pub fn soft_clip(&self) -> bool {
+        self.player.soft_clip()
+    }
+
+    /// Set the output latency in frames (samples per channel).
+    ///
+    /// `getChannelStates` then reports the registers of the audio that is
+    /// audible now rather than of the block generated last. Pass the frames
+    /// buffered between generation and the speakers, e.g.
+    /// `(ctx.baseLatency + ctx.outputLatency) * ctx.sampleRate` plus any
+    /// queued worklet buffers. Defaults to 0 (no compensation).
+    #[wasm_bindgen(js_name = setOutputLatency)]
+    pub fn set_output_latency(&mut self, frames: usize) {
+        self.register_delay.set_latency(frames);
+        if frames == 0 {
+            self.register_delay.clear();
+        }
+    }
+
+    /// Get the output latency in frames.
+    #[wasm_bindgen(js_name = outputLatency)]
+    pub fn output_latency(&self) -> usize {
+        self.register_delay.latency()
     }
 
     /// Get current frame position.
     pub fn frame_position(&self) -> u32 {
-        self.player.frame_position() as u32
+        match self.track_position() {
+            Some(position) => (position * self.player.frame_count() as f32) as u32,
+            None => self.player.frame_position() as u32,
+        }
     }
 
     /// Get total frame count.
@@ -195,19 +391,76 @@ impl Ym2149Player {
 
     /// Get playback position as percentage (0.0 to 1.0).
     pub fn position_percentage(&self) -> f32 {
-        self.player.playback_position()
+        self.track_position()
+            .unwrap_or_else(|| self.player.playback_position())
     }
 
-    /// Seek to a specific frame (silently ignored for Arkos/AY backends).
+    /// Seek to a specific frame.
+    ///
+    /// Arkos/AY songs only seek into audio pre-rendered by `startPrerender`;
+    /// other positions are silently ignored.
     pub fn seek_to_frame(&mut self, frame: u32) {
-        let _ = self.player.seek_frame(frame as usize);
+        if !self.player.seek_frame(frame as usize) {
+            let frame_count = self.player.frame_count().max(1);
+            self.seek_track_cache(frame as f32 / frame_count as f32);
+        }
+        self.register_delay.clear();
     }
 
     /// Seek to a percentage of the song (0.0 to 1.0).
     ///
     /// Returns true if seek succeeded. Works for all SNDH files (uses fallback duration for older files).
+    /// Arkos/AY songs seek into audio pre-rendered by `startPrerender`.
     pub fn seek_to_percentage(&mut self, percentage: f32) -> bool {
-        self.player.seek_percentage(percentage)
+        self.register_delay.clear();
+        self.player.seek_percentage(percentage) || self.seek_track_cache(percentage)
+    }
+
+    /// Start pre-rendering the song so it can be seeked instantly.
+    ///
+    /// Arkos and AY songs cannot seek natively. Pre-rendering plays a second
+    /// copy of the song into memory; seeks to any position rendered so far
+    /// then continue playback from there. Call `prerenderChunk` repeatedly
+    /// (e.g. from `requestIdleCallback`) until it returns true.
+    ///
+    /// Returns false for YM and SNDH songs, which seek without it, and for
+    /// KSS songs, which have no known length.
+    #[wasm_bindgen(js_name = startPrerender)]
+    pub fn start_prerender(&mut self) -> bool {
+        let renderer = load_browser_player(&self.source, YmChip::default())
+            .ok()
+            .and_then(|(player, _)| player.into_renderer());
+        let Some(renderer) = renderer else {
+            return false;
+        };
+        let total_samples = (self.player.duration_seconds() * YM_SAMPLE_RATE_F32) as usize;
+        self.track_cache = Some(TrackCache::new(renderer, total_samples));
+        true
+    }
+
+    /// Pre-render the next `maxSamples` samples (default 8192).
+    ///
+    /// Returns true when the whole song is rendered, pre-rendering was
+    /// cancelled, or `startPrerender` was not called.
+    #[wasm_bindgen(js_name = prerenderChunk)]
+    pub fn prerender_chunk(&mut self, max_samples: Option<usize>) -> bool {
+        self.track_cache
+            .as_mut()
+            .is_none_or(|track| track.render_chunk(max_samples.unwrap_or(DEFAULT_PRERENDER_CHUNK)))
+    }
+
+    /// Fraction of the song pre-rendered (0.0 to 1.0).
+    #[wasm_bindgen(js_name = prerenderProgress)]
+    pub fn prerender_progress(&self) -> f32 {
+        self.track_cache.as_ref().map_or(0.0, TrackCache::progress)
+    }
+
+    /// Stop pre-rendering. Positions rendered so far stay seekable.
+    #[wasm_bindgen(js_name = cancelPrerender)]
+    pub fn cancel_prerender(&mut self) {
+        if let Some(track) = self.track_cache.as_mut() {
+            track.cancel();
+        }
     }
 
     /// Get duration in seconds.
@@ -225,18 +478,58 @@ impl Ym2149Player {
         self.player.has_duration_info()
     }
 
-    /// Mute or unmute a channel (0-2 for YM2149, 3-4 for STE DAC L/R).
+    /// Mute or unmute a channel.
+    ///
+    /// Channels are numbered globally: 0-2 are PSG 0 (A/B/C), 3-5 are PSG 1 for
+    /// multi-PSG Arkos songs, and so on. For SNDH, 3-4 are the STE DAC L/R.
+    /// Use `channelCount()` for the valid range; other indices are ignored.
     #[wasm_bindgen(js_name = setChannelMute)]
     pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
-        self.player.set_channel_mute(channel, mute);
+        if channel < self.player.channel_count() {
+            self.player.set_channel_mute(channel, mute);
+        }
     }
 
-    /// Check if a channel is muted.
+    /// Check if a channel is muted (global channel index, see `setChannelMute`).
     #[wasm_bindgen(js_name = isChannelMuted)]
     pub fn is_channel_muted(&self, channel: usize) -> bool {
         self.player.is_channel_muted(channel)
     }
 
+    /// Solo a channel: mute every other channel of every PSG.
+    ///
+    /// The mute state from before the first solo is restored by `clearSolo()`,
+    /// so switching the soloed channel does not lose the user's own mutes.
+    /// Returns false if the channel index is out of range.
+    #[wasm_bindgen(js_name = setChannelSolo)]
+    pub fn set_channel_solo(&mut self, channel: usize) -> bool {
+        let channel_count = self.player.channel_count();
+        let mutes = (0..channel_count.min(MAX_CHANNEL_COUNT))
+            .filter(|&ch| self.player.is_channel_muted(ch))
+            .fold(0u64, |mask, ch| mask | (1 << ch));
+        match self.solo.solo(channel, channel_count, mutes) {
+            Some(mutes) => {
+                self.apply_mutes(mutes);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Leave solo mode and restore the previous mute state.
+    #[wasm_bindgen(js_name = clearSolo)]
+    pub fn clear_solo(&mut self) {
+        if let Some(mutes) = self.solo.clear() {
+            self.apply_mutes(mutes);
+        }
+    }
+
+    /// Get the soloed channel, or `undefined` when solo mode is off.
+    #[wasm_bindgen(js_name = soloChannel)]
+    pub fn solo_channel(&self) -> Option<usize> {
+        self.solo.channel()
+    }
+
     /// Generate audio samples.
     ///
     /// Returns a Float32Array containing mono samples.
@@ -245,8 +538,8 @@ impl Ym2149Player {
     /// For 44.1kHz at 50Hz frame rate: 882 samples per frame.
     #[wasm_bindgen(js_name = generateSamples)]
     pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        let mut samples = self.player.generate_samples(count);
-        apply_volume(&mut samples, self.volume);
+        let mut samples = vec![0.0; count];
+        self.generate_samples_into(&mut samples);
         samples
     }
 
@@ -255,43 +548,165 @@ impl Ym2149Player {
     /// This is more efficient than `generate_samples` as it reuses the same buffer.
     #[wasm_bindgen(js_name = generateSamplesInto)]
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
-        self.player.generate_samples_into(buffer);
-        apply_volume(buffer, self.volume);
+        if !self.play_from_track_cache(buffer) {
+            self.render_live(buffer, 1);
+        }
+        self.record_registers(buffer.len());
     }
 
     /// Generate stereo audio samples (interleaved L/R).
     ///
-    /// Returns frame_count * 2 samples. SNDH uses native stereo output,
-    /// other formats duplicate mono to stereo.
+    /// Returns frame_count * 2 samples. While every channel is centered, SNDH
+    /// uses native stereo output and other formats duplicate mono to stereo;
+    /// `setChannelPan` places channels in the stereo field.
     #[wasm_bindgen(js_name = generateSamplesStereo)]
     pub fn generate_samples_stereo(&mut self, frame_count: usize) -> Vec<f32> {
-        let mut samples = self.player.generate_samples_stereo(frame_count);
-        apply_volume(&mut samples, self.volume);
+        let mut samples = vec![0.0; frame_count * 2];
+        self.generate_samples_into_stereo(&mut samples);
         samples
     }
 
     /// Generate stereo samples into a pre-allocated buffer (zero-allocation).
     ///
-    /// Buffer length must be even (frame_count * 2). Interleaved L/R format.
-    /// SNDH uses native stereo output, other formats duplicate mono to stereo.
+    /// Buffer length must be even (frame_count * 2). Interleaved L/R format,
+    /// panned like `generateSamplesStereo`.
     #[wasm_bindgen(js_name = generateSamplesIntoStereo)]
     pub fn generate_samples_into_stereo(&mut self, buffer: &mut [f32]) {
-        self.player.generate_samples_into_stereo(buffer);
-        apply_volume(buffer, self.volume);
+        let frame_count = buffer.len() / 2;
+        if self.play_from_track_cache(&mut buffer[..frame_count]) {
+            mono_to_stereo_in_place(&mut buffer[..frame_count * 2]);
+        } else {
+            self.render_live(buffer, 2);
+        }
+        self.record_registers(frame_count);
+    }
+
+    /// Place a channel in the stereo field of `generateSamplesStereo`.
+    ///
+    /// `pan` ranges from -1.0 (left) through 0.0 (center, the default) to
+    /// 1.0 (right). Channels use the numbering of `setChannelMute`, so SNDH
+    /// songs pan their DAC left/right outputs as channels 3 and 4. Each
+    /// output sample is split between the channels by their current level,
+    /// which keeps the chip's mixing and filtering. Panning any channel
+    /// replaces SNDH native stereo with this split; audio served from the
+    /// pre-rendered track stays centered. Returns false if the channel does
+    /// not exist or `pan` is not a number.
+    #[wasm_bindgen(js_name = setChannelPan)]
+    pub fn set_channel_pan(&mut self, channel: usize, pan: f32) -> bool {
+        self.panning.set_pan(channel, pan)
+    }
+
+    /// Get the pan of a channel (-1.0 left to 1.0 right).
+    #[wasm_bindgen(js_name = channelPan)]
+    pub fn channel_pan(&self, channel: usize) -> f32 {
+        self.panning.pan(channel)
+    }
+
+    /// Advance exactly one frame while paused (debugger-style single step).
+    ///
+    /// Returns the changed registers as flat `[psg, register, old, new]`
+    /// quadruples, or `undefined` while playing. See `stepFrames`.
+    #[wasm_bindgen(js_name = stepFrame)]
+    pub fn step_frame(&mut self) -> Option<Vec<u8>> {
+        self.step_frames(1)
+    }
+
+    /// Advance exactly `frames` frames while paused.
+    ///
+    /// The player stays paused at the new position, so `play()` resumes from
+    /// there. Returns the registers that differ from before the step as flat
+    /// `[psg, register, old, new]` quadruples, or `undefined` while playing
+    /// or for songs without PSG registers (YM Tracker).
+    #[wasm_bindgen(js_name = stepFrames)]
+    pub fn step_frames(&mut self, frames: usize) -> Option<Vec<u8>> {
+        let delta = self.player.step_frames(frames)?;
+        // Stepping moves the live player, which takes over again
+        self.leave_track_cache();
+        Some(
+            delta
+                .changes()
+                .flat_map(|change| {
+                    [
+                        change.psg as u8,
+                        change.register as u8,
+                        change.old,
+                        change.new,
+                    ]
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the register writes and effect changes of the frames rendered
+    /// since the last call.
+    ///
+    /// The first call starts recording and returns an empty array; poll it
+    /// once per animation frame after that. Returns an array of frames:
+    ///
+    /// ```json
+    /// [
+    ///   {
+    ///     "frame": 1234,
+    ///     "registers": [0, 8, 12, 15, ...],
+    ///     "effects": [
+    ///       { "type": "sidStart", "channel": 0, "frequency": 5000, "volume": 15, "sinus": false },
+    ///       { "type": "digidrum", "channel": 2, "drum": 3, "sampleRate": 8000 }
+    ///     ]
+    ///   }
+    /// ]
+    /// ```
+    ///
+    /// `registers` is a Uint8Array of `[psg, register, old, new]` quadruples
+    /// like `stepFrames`. Effect types are `sidStart`, `sidStop`, `digidrum`,
+    /// `syncBuzzerStart` and `syncBuzzerStop`; only YM songs report effects.
+    /// Frames are recorded in quarter-frame slices of rendered audio, so they
+    /// run ahead of the speakers by the output latency. At most 500 frames
+    /// are kept between calls.
+    #[wasm_bindgen(js_name = getFrameEvents)]
+    pub fn get_frame_events(&mut self) -> js_sys::Array {
+        match &mut self.frame_events {
+            Some(log) => log.drain(),
+            None => {
+                self.frame_events =
+                    Some(FrameEventLog::new(&self.player, self.metadata.frame_rate));
+                js_sys::Array::new()
+            }
+        }
     }
 
     /// Get the current register values (for visualization).
+    ///
+    /// Returns the registers of the first PSG; use `getPsgRegisters` or
+    /// `getAllRegisters` for multi-PSG songs.
     pub fn get_registers(&self) -> Vec<u8> {
         self.player.dump_registers().to_vec()
     }
 
+    /// Get the 16 register values of one PSG chip.
+    ///
+    /// Returns an empty array if `psg` is out of range (see `psgCount()`).
+    #[wasm_bindgen(js_name = getPsgRegisters)]
+    pub fn get_psg_registers(&self, psg: usize) -> Vec<u8> {
+        self.player
+            .dump_all_registers()
+            .get(psg)
+            .map(|regs| regs.to_vec())
+            .unwrap_or_default()
+    }
+
+    /// Get the registers of all PSG chips, 16 bytes per chip in PSG order.
+    #[wasm_bindgen(js_name = getAllRegisters)]
+    pub fn get_all_registers(&self) -> Vec<u8> {
+        self.player.dump_all_registers().concat()
+    }
+
     /// Get channel states for visualization (frequency, amplitude, note, effects).
     ///
     /// Returns a JsValue containing an object with channel data for all PSG chips:
     /// ```json
     /// {
     ///   "channels": [
-    ///     { "frequency": 440.0, "note": "A4", "amplitude": 0.8, "toneEnabled": true, "noiseEnabled": false, "envelopeEnabled": false },
+    ///     { "psg": 0, "frequency": 440.0, "note": "A4", "cents": 0.0, "pitchMotion": "", "amplitude": 0.8, "toneEnabled": true, "noiseEnabled": false, "envelopeEnabled": false },
     ///     ...
     ///   ],
     ///   "envelopes": [
@@ -300,11 +715,19 @@ impl Ym2149Player {
     ///   ]
     /// }
     /// ```
+    ///
+    /// `cents` is the deviation from `note`. `pitchMotion` is `"vibrato"`,
+    /// `"slide up"`, `"slide down"` or empty, detected over the last 8 calls,
+    /// so poll once per animation frame.
     #[wasm_bindgen(js_name = getChannelStates)]
-    pub fn get_channel_states(&self) -> JsValue {
+    pub fn get_channel_states(&mut self) -> JsValue {
         use ym2149_common::ChannelStates;
 
-        let all_regs = self.player.dump_all_registers();
+        let all_regs = self
+            .register_delay
+            .delayed()
+            .cloned()
+            .unwrap_or_else(|| self.player.dump_all_registers());
 
         // Build JavaScript-friendly object
         let obj = js_sys::Object::new();
@@ -314,13 +737,22 @@ impl Ym2149Player {
         // Envelopes array (one per PSG)
         let envelopes = js_sys::Array::new();
 
-        for regs in &all_regs {
+        self.pitch_trackers
+            .resize_with(all_regs.len() * 3, PitchTracker::new);
+
+        for (psg, regs) in all_regs.iter().enumerate() {
             let states = ChannelStates::from_registers(regs);
 
-            for ch in &states.channels {
+            for (local, ch) in states.channels.iter().enumerate() {
+                let sounding = ch.amplitude > 0 || ch.envelope_enabled;
+                let motion =
+                    self.pitch_trackers[psg * 3 + local].push(ch.frequency_hz.filter(|_| sounding));
                 let ch_obj = js_sys::Object::new();
+                set_js_prop(&ch_obj, "psg", psg as u32);
                 set_js_prop(&ch_obj, "frequency", ch.frequency_hz.unwrap_or(0.0));
                 set_js_prop(&ch_obj, "note", ch.note_name.unwrap_or("--"));
+                set_js_prop(&ch_obj, "cents", ch.cents.unwrap_or(0.0));
+                set_js_prop(&ch_obj, "pitchMotion", motion.label().unwrap_or(""));
                 set_js_prop(&ch_obj, "amplitude", ch.amplitude_normalized);
                 set_js_prop(&ch_obj, "toneEnabled", ch.tone_enabled);
                 set_js_prop(&ch_obj, "noiseEnabled", ch.noise_enabled);
@@ -337,6 +769,7 @@ impl Ym2149Player {
         }
 
         // For SNDH with STE features, add DAC channels (L/R)
+        #[cfg(feature = "sndh")]
         if let BrowserSongPlayer::Sndh(sndh_player) = &self.player {
             if sndh_player.uses_ste_features() {
                 let (dac_left, dac_right) = sndh_player.get_dac_levels();
@@ -397,6 +830,7 @@ impl Ym2149Player {
     /// Returns null for non-SNDH formats.
     #[wasm_bindgen(js_name = getLmc1992State)]
     pub fn get_lmc1992_state(&self) -> JsValue {
+        #[cfg(feature = "sndh")]
         if let BrowserSongPlayer::Sndh(sndh_player) = &self.player {
             let obj = js_sys::Object::new();
             // dB values
@@ -411,10 +845,9 @@ impl Ym2149Player {
             set_js_prop(&obj, "rightVolumeRaw", sndh_player.lmc1992_right_volume_raw() as i32);
             set_js_prop(&obj, "bassRaw", sndh_player.lmc1992_bass_raw() as i32);
             set_js_prop(&obj, "trebleRaw", sndh_player.lmc1992_treble_raw() as i32);
-            obj.into()
-        } else {
-            JsValue::NULL
+            return obj.into();
         }
+        JsValue::NULL
     }
 
     /// Get current per-channel audio outputs for oscilloscope visualization.
@@ -442,14 +875,15 @@ impl Ym2149Player {
     /// This enables accurate per-sample oscilloscope visualization at the full audio sample rate.
     #[wasm_bindgen(js_name = generateSamplesWithChannels)]
     pub fn generate_samples_with_channels(&mut self, count: usize) -> JsValue {
-        let (mut mono, channels) = self.player.generate_samples_with_channels(count);
-
-        // Apply volume
-        if self.volume != 1.0 {
-            for sample in &mut mono {
-                *sample *= self.volume;
-            }
-        }
+        let (mono, channels) = if self.track_position().is_some() {
+            // Channel outputs are not pre-rendered
+            let mut mono = vec![0.0; count];
+            self.play_from_track_cache(&mut mono);
+            (mono, vec![0.0; count * self.player.channel_count()])
+        } else {
+            self.player.generate_samples_with_channels(count)
+        };
+        self.record_registers(count);
 
         // Create JS object with both arrays
         let obj = js_sys::Object::new();
@@ -463,12 +897,79 @@ impl Ym2149Player {
         obj.into()
     }
 
+    /// Generate one buffer of channel output per channel.
+    ///
+    /// Returns an array of `channelCount()` Float32Arrays with `count`
+    /// samples each (A, B, C per PSG; SNDH adds DAC left and right). These
+    /// are the raw channel outputs (-1.0 to 1.0) before the output filter
+    /// and volume, for per-channel scopes or mixing in Web Audio. Advances
+    /// playback like `generateSamples`, whose audio is discarded. Buffers
+    /// are silent while playback is served from the pre-rendered track.
+    #[wasm_bindgen(js_name = generateChannelBuffers)]
+    pub fn generate_channel_buffers(&mut self, count: usize) -> js_sys::Array {
+        let channel_count = self.player.channel_count();
+        let mut mono = vec![0.0; count];
+        let mut channels = vec![0.0; count * channel_count];
+        if !self.play_from_track_cache(&mut mono) {
+            self.player
+                .generate_samples_with_channels_into(&mut mono, &mut channels);
+        }
+        self.record_registers(count);
+
+        let buffers = js_sys::Array::new();
+        for channel in 0..channel_count {
+            let samples: Vec<f32> = channels
+                .iter()
+                .skip(channel)
+                .step_by(channel_count)
+                .copied()
+                .collect();
+            buffers.push(&js_sys::Float32Array::from(samples.as_slice()));
+        }
+        buffers
+    }
+
     /// Enable or disable the ST color filter.
     pub fn set_color_filter(&mut self, enabled: bool) {
         self.player.set_color_filter(enabled);
     }
 
-    /// Get the number of subsongs (1 for most formats, >1 for multi-song SNDH files).
+    /// Select the analog output stage model: "raw", "stf", "ste" or "cpc".
+    ///
+    /// Returns false (and leaves the current model active) for unknown names.
+    #[wasm_bindgen(js_name = setOutputModel)]
+    pub fn set_output_model(&mut self, model: &str) -> bool {
+        match OutputModel::from_name(model) {
+            Some(model) => {
+                self.player.set_output_model(model);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Record the last `capacity` output samples of every channel for oscilloscopes.
+    ///
+    /// Pass 0 to disable recording. Read the samples with `channelTap()`.
+    #[wasm_bindgen(js_name = setChannelTap)]
+    pub fn set_channel_tap(&mut self, capacity: usize) {
+        self.player
+            .set_channel_tap((capacity > 0).then_some(capacity));
+    }
+
+    /// Get the most recent tapped output samples of a channel, oldest first.
+    ///
+    /// Returns a Float32Array of up to `count` bipolar samples (-1.0 to 1.0),
+    /// empty if taps are disabled. Channel indices match `setChannelMute()`.
+    #[wasm_bindgen(js_name = channelTap)]
+    pub fn channel_tap(&self, channel: usize, count: usize) -> Vec<f32> {
+        let mut samples = vec![0.0; count.min(MAX_TAP_CAPACITY)];
+        let written = self.player.read_channel_tap(channel, &mut samples);
+        samples.truncate(written);
+        samples
+    }
+
+    /// Get the number of subsongs (1 for most formats, >1 for multi-song AY and SNDH files).
     #[wasm_bindgen(js_name = subsongCount)]
     pub fn subsong_count(&self) -> usize {
         self.player.subsong_count()
@@ -482,61 +983,245 @@ impl Ym2149Player {
         self.player.channel_count()
     }
 
+    /// Get the number of PSG chips (1 except for multi-PSG Arkos songs and
+    /// AY songs using PlayCity).
+    #[wasm_bindgen(js_name = psgCount)]
+    pub fn psg_count(&self) -> usize {
+        self.player.psg_count()
+    }
+
     /// Get the current subsong index (1-based).
     #[wasm_bindgen(js_name = currentSubsong)]
     pub fn current_subsong(&self) -> usize {
         self.player.current_subsong()
     }
 
+    /// Get information about a subsong (1-based index) for subsong lists.
+    ///
+    /// Returns `{ index, name, durationSeconds, tickRate, timer }` for SNDH
+    /// subsongs; `name`, `durationSeconds` and `timer` ('A' to 'D') are
+    /// `null` when the file does not provide them. Returns `null` for other
+    /// formats and out-of-range indices.
+    #[wasm_bindgen(js_name = subsongInfo)]
+    pub fn subsong_info(&self, index: usize) -> JsValue {
+        #[cfg(feature = "sndh")]
+        if let Some(info) = self.player.subsong_info(index) {
+            let or_null = |value: Option<JsValue>| value.unwrap_or(JsValue::NULL);
+            let name = or_null(info.subtune_name.clone().map(Into::into));
+            let duration = or_null(info.duration_seconds().map(Into::into));
+            let timer = or_null(info.timer.map(|t| t.to_string().into()));
+
+            let obj = js_sys::Object::new();
+            set_js_prop(&obj, "index", index as u32);
+            set_js_prop(&obj, "name", name);
+            set_js_prop(&obj, "durationSeconds", duration);
+            set_js_prop(&obj, "tickRate", info.player_tick_rate);
+            set_js_prop(&obj, "timer", timer);
+            return obj.into();
+        }
+        let _ = index;
+        JsValue::NULL
+    }
+
     /// Set the current subsong (1-based index). Returns true on success.
     #[wasm_bindgen(js_name = setSubsong)]
     pub fn set_subsong(&mut self, index: usize) -> bool {
-        self.player.set_subsong(index)
+        self.register_delay.clear();
+        let changed = self.player.set_subsong(index);
+        if changed {
+            // The pre-render holds the previous subsong
+            self.track_cache = None;
+            self.panning.reset(self.player.channel_count());
+        }
+        changed
     }
 }
 
-/// Load a file and create the appropriate player.
-fn load_browser_player(data: &[u8]) -> Result<(BrowserSongPlayer, YmMetadata), String> {
-    if data.is_empty() {
-        return Err("empty file data".to_string());
+impl Ym2149Player {
+    /// Set every channel's mute flag from a mask (bit `n` = channel `n`).
+    fn apply_mutes(&mut self, mutes: u64) {
+        for ch in 0..self.player.channel_count().min(MAX_CHANNEL_COUNT) {
+            self.player.set_channel_mute(ch, mutes & (1 << ch) != 0);
+        }
+    }
+
+    /// Playback position (0.0 to 1.0) while audio is served from the track cache.
+    fn track_position(&self) -> Option<f32> {
+        let track = self.track_cache.as_ref()?;
+        let position = track.position()?;
+        Some(position as f32 / track.total_samples().max(1) as f32)
     }
 
-    // SNDH needs to be detected first to avoid falling back to AY/other formats
-    // when the header already looks like a packed SNDH.
-    if is_sndh_data(data) {
-        let (wrapper, metadata) = SndhWasmPlayer::new(data)?;
-        return Ok((BrowserSongPlayer::Sndh(Box::new(wrapper)), metadata));
+    /// Continue playback from the pre-rendered audio at `fraction` of the song.
+    fn seek_track_cache(&mut self, fraction: f32) -> bool {
+        let Some(track) = self.track_cache.as_mut() else {
+            return false;
+        };
+        let sample = (fraction.clamp(0.0, 1.0) * track.total_samples() as f32) as usize;
+        track.seek(sample)
+    }
+
+    /// Hand playback back to the live player.
+    fn leave_track_cache(&mut self) {
+        if let Some(track) = self.track_cache.as_mut() {
+            track.stop_playback();
+        }
+    }
+
+    /// Fill `buffer` from the track cache while it serves playback.
+    ///
+    /// Returns false (leaving `buffer` untouched) when the live player
+    /// should render instead.
+    fn play_from_track_cache(&mut self, buffer: &mut [f32]) -> bool {
+        let Some(track) = self
+            .track_cache
+            .as_mut()
+            .filter(|track| track.position().is_some())
+        else {
+            return false;
+        };
+        if self.player.state() != PlaybackState::Playing {
+            buffer.fill(0.0);
+            return true;
+        }
+        if !track.play_into(buffer) {
+            // End of the song: the live player restarts from the beginning
+            self.player.stop();
+        }
+        let mut gain = OutputGain::new(self.player.gain());
+        gain.set_soft_clip(self.player.soft_clip());
+        gain.apply(buffer);
+        true
+    }
+
+    /// Render `buffer` with the live player (`channels` interleaved),
+    /// recording frame events in slices while `getFrameEvents` is in use.
+    fn render_live(&mut self, buffer: &mut [f32], channels: usize) {
+        let slice = match &self.frame_events {
+            Some(log) => log.slice() * channels,
+            None => buffer.len(),
+        };
+        for slice in buffer.chunks_mut(slice.max(1)) {
+            if channels == 2 {
+                self.panning.render(&mut self.player, slice);
+            } else {
+                self.player.generate_samples_into(slice);
+            }
+            if let Some(log) = &mut self.frame_events {
+                log.record(&self.player);
+            }
+        }
     }
 
-    // Try YM format first
-    if let Ok((player, summary)) = load_song(data) {
-        let metadata = metadata_from_summary(&player, &summary);
-        return Ok((BrowserSongPlayer::Ym(Box::new(player)), metadata));
+    /// Record the registers after generating `frames` frames.
+    ///
+    /// Skipped without latency: `getChannelStates` then reads the player directly.
+    fn record_registers(&mut self, frames: usize) {
+        if self.register_delay.latency() > 0 {
+            self.register_delay
+                .push(frames, self.player.dump_all_registers());
+        }
     }
+}
 
-    // Try Arkos format
-    if let Ok(song) = load_aks(data) {
-        let psg_count = song.subsongs.first().map(|s| s.psgs.len()).unwrap_or(0);
-        console_log!("Arkos: loaded song with {} PSGs ({} channels)", psg_count, psg_count * 3);
-        let arkos_player =
-            ArkosPlayer::new(song, 0).map_err(|e| format!("Arkos player init failed: {e}"))?;
-        let (wrapper, metadata) = ArkosWasmPlayer::new(arkos_player);
-        return Ok((BrowserSongPlayer::Arkos(Box::new(wrapper)), metadata));
+/// File extensions recognised inside ZIP archives.
+const ARCHIVE_SONG_EXTENSIONS: &[&str] = &[".ym", ".aks", ".ay", ".sndh", ".kss"];
+
+/// List archive entries with a playable file extension.
+fn playable_archive_entries(data: &[u8]) -> Result<Vec<String>, String> {
+    let names = zip_entry_names(data).map_err(|e| e.to_string())?;
+    Ok(names
+        .into_iter()
+        .filter(|name| {
+            let lower = name.to_ascii_lowercase();
+            ARCHIVE_SONG_EXTENSIONS
+                .iter()
+                .any(|ext| lower.ends_with(ext))
+        })
+        .collect())
+}
+
+/// Load a file and create the appropriate player.
+///
+/// `chip` selects the backend for YM songs; other formats ignore it.
+fn load_browser_player(
+    data: &[u8],
+    chip: YmChip,
+) -> Result<(BrowserSongPlayer, YmMetadata), String> {
+    if data.is_empty() {
+        return Err("empty file data".to_string());
     }
 
-    // Try SNDH format (Atari ST) even if the heuristic didn't match
-    if let Ok((wrapper, metadata)) = SndhWasmPlayer::new(data) {
-        return Ok((BrowserSongPlayer::Sndh(Box::new(wrapper)), metadata));
+    // ZIP collections: play the first song. Packaged Arkos files (a ZIP holding
+    // one .aks document) also take this path and load from the extracted XML.
+    if is_zip_archive(data)
+        && let Ok(entries) = playable_archive_entries(data)
+        && let Some(first) = entries.first()
+    {
+        let entry = extract_zip_entry(data, first).map_err(|e| e.to_string())?;
+        if !is_zip_archive(&entry) {
+            return load_browser_player(&entry, chip);
+        }
     }
 
-    // Try AY format as last resort
-    let (player, meta) = AyPlayer::load_from_bytes(data, 0)
-        .map_err(|e| format!("unrecognized format (AY parse error: {e})"))?;
-    if player.requires_cpc_firmware() {
-        return Err(CPC_UNSUPPORTED_MSG.to_string());
+    // Try parsers in order of header evidence, so a file is never handed to a
+    // fallback parser while a matching signature points elsewhere. On failure
+    // the error lists every parser's reason, not just the last fallback's.
+    load_with_detection(data, |format| load_browser_player_as(data, format, chip))
+        .map_err(|e| e.to_string())
+}
+
+/// Create the player for a song of a known `format`.
+fn load_browser_player_as(
+    data: &[u8],
+    format: ChiptuneFormat,
+    chip: YmChip,
+) -> Result<(BrowserSongPlayer, YmMetadata), String> {
+    match format {
+        ChiptuneFormat::Ym => {
+            let (player, metadata) = YmWasmPlayer::new(data, chip)?;
+            Ok((BrowserSongPlayer::Ym(Box::new(player)), metadata))
+        }
+        #[cfg(feature = "arkos")]
+        ChiptuneFormat::Arkos => {
+            let song = load_aks(data).map_err(|e| e.to_string())?;
+            let psg_count = song.subsongs.first().map(|s| s.psgs.len()).unwrap_or(0);
+            console_log!(
+                "Arkos: loaded song with {} PSGs ({} channels)",
+                psg_count,
+                psg_count * 3
+            );
+            let arkos_player =
+                ArkosPlayer::new(song, 0).map_err(|e| format!("Arkos player init failed: {e}"))?;
+            let (wrapper, metadata) = ArkosWasmPlayer::new(arkos_player);
+            Ok((BrowserSongPlayer::Arkos(Box::new(wrapper)), metadata))
+        }
+        #[cfg(feature = "sndh")]
+        ChiptuneFormat::Sndh => {
+            let (wrapper, metadata) = SndhWasmPlayer::new(data)?;
+            Ok((BrowserSongPlayer::Sndh(Box::new(wrapper)), metadata))
+        }
+        #[cfg(feature = "ay")]
+        ChiptuneFormat::Ay => {
+            let (player, meta) = AyPlayer::load_from_bytes(data, 0).map_err(|e| e.to_string())?;
+            if player.requires_cpc_firmware() {
+                return Err(CPC_UNSUPPORTED_MSG.to_string());
+            }
+            let (wrapper, metadata) = AyWasmPlayer::new(player, &meta);
+            Ok((BrowserSongPlayer::Ay(Box::new(wrapper)), metadata))
+        }
+        #[cfg(feature = "kss")]
+        ChiptuneFormat::Kss => {
+            let player = KssPlayer::load_from_bytes(data, 0).map_err(|e| e.to_string())?;
+            let (wrapper, metadata) = KssWasmPlayer::new(player);
+            Ok((BrowserSongPlayer::Kss(Box::new(wrapper)), metadata))
+        }
+        ChiptuneFormat::Zip | ChiptuneFormat::Unknown => {
+            Err(format!("no {} player", format.name()))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(format_not_included(format)),
     }
-    let (wrapper, metadata) = AyWasmPlayer::new(player, &meta);
-    Ok((BrowserSongPlayer::Ay(Box::new(wrapper)), metadata))
 }
 
 // Re-export for wasm-pack
@@ -4,7 +4,11 @@
 //! and conversion functions from various player metadata formats.
 
 use wasm_bindgen::prelude::*;
+use ym2149::Ym2149Backend;
+#[cfg(feature = "ay")]
 use ym2149_ay_replayer::AyMetadata as AyFileMetadata;
+#[cfg(feature = "kss")]
+use ym2149_kss_replayer::KssPlayer;
 use ym2149_ym_replayer::LoadSummary;
 
 /// YM file metadata exposed to JavaScript.
@@ -66,8 +70,8 @@ impl YmMetadata {
 }
 
 /// Convert YM player info to metadata.
-pub fn metadata_from_summary(
-    player: &ym2149_ym_replayer::YmPlayer,
+pub fn metadata_from_summary<B: Ym2149Backend>(
+    player: &ym2149_ym_replayer::YmPlayerGeneric<B>,
     summary: &LoadSummary,
 ) -> YmMetadata {
     let (title, author, comments, frame_rate) = if let Some(info) = player.info() {
@@ -98,6 +102,7 @@ pub fn metadata_from_summary(
 }
 
 /// Convert AY file metadata to common metadata format.
+#[cfg(feature = "ay")]
 pub fn metadata_from_ay(meta: &AyFileMetadata) -> YmMetadata {
     let frame_count = meta.frame_count.unwrap_or(0);
     let duration_seconds = meta
@@ -114,3 +119,21 @@ pub fn metadata_from_ay(meta: &AyFileMetadata) -> YmMetadata {
         duration_seconds,
     }
 }
+
+/// Build common metadata for the current track of a KSS player.
+///
+/// KSS rips carry no tags or lengths, so only the track name, variant and
+/// frame rate are filled in.
+#[cfg(feature = "kss")]
+pub fn metadata_from_kss(player: &KssPlayer) -> YmMetadata {
+    let metadata = player.metadata();
+    YmMetadata {
+        title: metadata.title.clone(),
+        author: String::new(),
+        comments: String::new(),
+        format: metadata.format.clone(),
+        frame_count: 0,
+        frame_rate: metadata.frame_rate,
+        duration_seconds: 0.0,
+    }
+}
@@ -0,0 +1,57 @@
+//! Stereo output with per-channel panning.
+//!
+//! While every channel is centered, stereo rendering is the player's own:
+//! native stereo for SNDH, the mono mix on both sides otherwise. Once JS pans
+//! a channel with `setChannelPan`, the mono mix is rendered together with
+//! the channel outputs and split with a [`ChannelPanner`].
+
+use ym2149_common::ChannelPanner;
+
+use crate::players::BrowserSongPlayer;
+
+/// Channel pans and the scratch buffers used to apply them.
+pub(crate) struct StereoPanning {
+    panner: ChannelPanner,
+    mono: Vec<f32>,
+    channels: Vec<f32>,
+}
+
+impl StereoPanning {
+    /// Every channel of the song centered.
+    pub fn new(channel_count: usize) -> Self {
+        Self {
+            panner: ChannelPanner::new(channel_count),
+            mono: Vec::new(),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Set the pan of a channel; false if `channel` is out of range.
+    pub fn set_pan(&mut self, channel: usize, pan: f32) -> bool {
+        self.panner.set_pan(channel, pan)
+    }
+
+    /// Pan of a channel.
+    pub fn pan(&self, channel: usize) -> f32 {
+        self.panner.pan(channel)
+    }
+
+    /// Center every channel of a song with `channel_count` channels.
+    pub fn reset(&mut self, channel_count: usize) {
+        self.panner = ChannelPanner::new(channel_count);
+    }
+
+    /// Render interleaved stereo into `buffer` (`2 * frames` samples).
+    pub fn render(&mut self, player: &mut BrowserSongPlayer, buffer: &mut [f32]) {
+        if self.panner.is_centered() {
+            player.generate_samples_into_stereo(buffer);
+            return;
+        }
+        let frames = buffer.len() / 2;
+        self.mono.resize(frames, 0.0);
+        self.channels
+            .resize(frames * self.panner.channel_count(), 0.0);
+        player.generate_samples_with_channels_into(&mut self.mono, &mut self.channels);
+        self.panner.process(&self.mono, &self.channels, buffer);
+    }
+}
@@ -6,7 +6,7 @@ use crate::YM_SAMPLE_RATE_F32;
 use crate::metadata::YmMetadata;
 use ym2149::Ym2149Backend;
 use ym2149_arkos_replayer::ArkosPlayer;
-use ym2149_common::{ChiptunePlayerBase, PlaybackState};
+use ym2149_common::{ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta};
 
 /// Arkos player wrapper for WebAssembly.
 pub struct ArkosWasmPlayer {
@@ -57,6 +57,11 @@ impl ArkosWasmPlayer {
         self.duration_secs
     }
 
+    /// Unwrap the player for pre-rendering the track.
+    pub fn into_renderer(self) -> Box<dyn ChiptunePlayerBase> {
+        Box::new(self.player)
+    }
+
     /// Start playback.
     pub fn play(&mut self) {
         ChiptunePlayerBase::play(&mut self.player);
@@ -92,11 +97,6 @@ impl ArkosWasmPlayer {
         ChiptunePlayerBase::playback_position(&self.player)
     }
 
-    /// Generate audio samples.
-    pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        ChiptunePlayerBase::generate_samples(&mut self.player, count)
-    }
-
     /// Generate audio samples into a pre-allocated buffer.
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
         ChiptunePlayerBase::generate_samples_into(&mut self.player, buffer);
@@ -112,6 +112,41 @@ impl ArkosWasmPlayer {
         ChiptunePlayerBase::is_channel_muted(&self.player, channel)
     }
 
+    /// Set the output gain (0.0 to 4.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        ChiptunePlayerBase::set_gain(&mut self.player, gain);
+    }
+
+    /// Get the output gain.
+    pub fn gain(&self) -> f32 {
+        ChiptunePlayerBase::gain(&self.player)
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        ChiptunePlayerBase::set_playback_rate(&mut self.player, rate);
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        ChiptunePlayerBase::playback_rate(&self.player)
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        ChiptunePlayerBase::step_frames(&mut self.player, frames)
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        ChiptunePlayerBase::set_soft_clip(&mut self.player, enabled);
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        ChiptunePlayerBase::soft_clip(&self.player)
+    }
+
     /// Dump current PSG register values.
     pub fn dump_registers(&self) -> [u8; 16] {
         self.player
@@ -127,11 +162,31 @@ impl ArkosWasmPlayer {
         }
     }
 
+    /// Apply an analog output stage model.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        ChiptunePlayerBase::set_output_model(&mut self.player, model);
+    }
+
+    /// Enable or disable the per-channel output taps.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        ChiptunePlayerBase::set_channel_tap(&mut self.player, capacity);
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        ChiptunePlayerBase::read_channel_tap(&self.player, channel, out)
+    }
+
     /// Get number of channels (3 per PSG chip).
     pub fn channel_count(&self) -> usize {
         self.player.channel_count()
     }
 
+    /// Get number of PSG chips.
+    pub fn psg_count(&self) -> usize {
+        self.player.psg_count()
+    }
+
     /// Dump registers for all PSG chips.
     pub fn dump_all_registers(&self) -> Vec<[u8; 16]> {
         (0..self.player.psg_count())
@@ -5,7 +5,7 @@
 use crate::metadata::{YmMetadata, metadata_from_ay};
 use ym2149::Ym2149Backend;
 use ym2149_ay_replayer::{AyMetadata as AyFileMetadata, AyPlayer, CPC_UNSUPPORTED_MSG};
-use ym2149_common::{ChiptunePlayerBase, PlaybackState};
+use ym2149_common::{ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta};
 
 /// AY player wrapper for WebAssembly.
 pub struct AyWasmPlayer {
@@ -37,6 +37,11 @@ impl AyWasmPlayer {
         self.duration_secs
     }
 
+    /// Unwrap the player for pre-rendering the track.
+    pub fn into_renderer(self) -> Box<dyn ChiptunePlayerBase> {
+        Box::new(self.player)
+    }
+
     /// Start playback.
     pub fn play(&mut self) -> Result<(), String> {
         if self.unsupported {
@@ -76,18 +81,6 @@ impl AyWasmPlayer {
         ChiptunePlayerBase::playback_position(&self.player)
     }
 
-    /// Generate audio samples.
-    pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        if self.unsupported {
-            return vec![0.0; count];
-        }
-        let mut samples = ChiptunePlayerBase::generate_samples(&mut self.player, count);
-        if self.check_support().is_err() {
-            samples.fill(0.0);
-        }
-        samples
-    }
-
     /// Generate audio samples into a pre-allocated buffer.
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
         ChiptunePlayerBase::generate_samples_into(&mut self.player, buffer);
@@ -106,11 +99,87 @@ impl AyWasmPlayer {
         ChiptunePlayerBase::is_channel_muted(&self.player, channel)
     }
 
+    /// Set the output gain (0.0 to 4.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        ChiptunePlayerBase::set_gain(&mut self.player, gain);
+    }
+
+    /// Get the output gain.
+    pub fn gain(&self) -> f32 {
+        ChiptunePlayerBase::gain(&self.player)
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        ChiptunePlayerBase::set_playback_rate(&mut self.player, rate);
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        ChiptunePlayerBase::playback_rate(&self.player)
+    }
+
+    /// Get the number of songs in the file.
+    pub fn subsong_count(&self) -> usize {
+        ChiptunePlayerBase::subsong_count(&self.player)
+    }
+
+    /// Get the current song (1-based).
+    pub fn current_subsong(&self) -> usize {
+        ChiptunePlayerBase::current_subsong(&self.player)
+    }
+
+    /// Switch to another song of the file (1-based). Returns true on success.
+    pub fn set_subsong(&mut self, index: usize) -> bool {
+        if !ChiptunePlayerBase::set_subsong(&mut self.player, index) {
+            return false;
+        }
+        let metadata = metadata_from_ay(self.player.metadata());
+        self.frame_count = metadata.frame_count as usize;
+        self.duration_secs = metadata.duration_seconds;
+        true
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        if self.unsupported {
+            return None;
+        }
+        let delta = ChiptunePlayerBase::step_frames(&mut self.player, frames);
+        self.check_support().ok()?;
+        delta
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        ChiptunePlayerBase::set_soft_clip(&mut self.player, enabled);
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        ChiptunePlayerBase::soft_clip(&self.player)
+    }
+
     /// Dump current PSG register values.
     pub fn dump_registers(&self) -> [u8; 16] {
         self.player.chip().dump_registers()
     }
 
+    /// Get number of PSG chips (3 for PlayCity songs).
+    pub fn psg_count(&self) -> usize {
+        self.player.psg_count()
+    }
+
+    /// Get number of channels (3 per PSG chip).
+    pub fn channel_count(&self) -> usize {
+        self.player.psg_count() * 3
+    }
+
+    /// Dump registers for all PSG chips.
+    pub fn dump_all_registers(&self) -> Vec<[u8; 16]> {
+        self.player.dump_all_registers()
+    }
+
     /// Get current per-channel audio outputs.
     ///
     /// Returns the actual audio output values (A, B, C) updated at sample rate.
@@ -123,10 +192,26 @@ impl AyWasmPlayer {
         self.player.set_color_filter(enabled);
     }
 
+    /// Apply an analog output stage model.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        ChiptunePlayerBase::set_output_model(&mut self.player, model);
+    }
+
+    /// Enable or disable the per-channel output taps.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        ChiptunePlayerBase::set_channel_tap(&mut self.player, capacity);
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        ChiptunePlayerBase::read_channel_tap(&self.player, channel, out)
+    }
+
     /// Generate samples with per-sample channel outputs for visualization.
     ///
     /// Fills the mono buffer with mixed samples and channels buffer with
-    /// per-sample channel outputs: [A, B, C, A, B, C, ...].
+    /// per-sample channel outputs for all PSG chips: [A0, B0, C0, A1, ...]
+    /// per sample.
     ///
     /// Note: AY player generates samples in frame-sized batches internally,
     /// so channel outputs are captured after each sample but may reflect
@@ -139,14 +224,20 @@ impl AyWasmPlayer {
         }
 
         // Generate samples one at a time to capture channel outputs
+        let channel_count = self.channel_count();
         let mut sample_buf = [0.0f32; 1];
-        for i in 0..mono.len() {
+        for (i, mono_sample) in mono.iter_mut().enumerate() {
             self.player.generate_samples_into(&mut sample_buf);
-            mono[i] = sample_buf[0];
-            let (a, b, c) = self.player.chip().get_channel_outputs();
-            channels[i * 3] = a;
-            channels[i * 3 + 1] = b;
-            channels[i * 3 + 2] = c;
+            *mono_sample = sample_buf[0];
+            for psg_idx in 0..self.player.psg_count() {
+                if let Some(chip) = self.player.psg(psg_idx) {
+                    let (a, b, c) = chip.get_channel_outputs();
+                    let offset = i * channel_count + psg_idx * 3;
+                    channels[offset] = a;
+                    channels[offset + 1] = b;
+                    channels[offset + 2] = c;
+                }
+            }
         }
 
         if self.check_support().is_err() {
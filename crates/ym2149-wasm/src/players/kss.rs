@@ -0,0 +1,174 @@
+//! KSS file WASM player wrapper.
+//!
+//! Wraps `KssPlayer` to provide a consistent interface for the browser player.
+
+use crate::metadata::{YmMetadata, metadata_from_kss};
+use ym2149::Ym2149Backend;
+use ym2149_common::{ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta};
+use ym2149_kss_replayer::KssPlayer;
+
+/// KSS player wrapper for WebAssembly.
+pub struct KssWasmPlayer {
+    player: KssPlayer,
+}
+
+impl KssWasmPlayer {
+    /// Create a new KSS WASM player wrapper.
+    pub fn new(player: KssPlayer) -> (Self, YmMetadata) {
+        let metadata = metadata_from_kss(&player);
+        (Self { player }, metadata)
+    }
+
+    /// Get duration in seconds (0 when unknown, which is always for KSS).
+    pub fn duration_seconds(&self) -> f32 {
+        ChiptunePlayerBase::duration_seconds(&self.player)
+    }
+
+    /// Start playback.
+    pub fn play(&mut self) {
+        self.player.play();
+    }
+
+    /// Pause playback.
+    pub fn pause(&mut self) {
+        self.player.pause();
+    }
+
+    /// Stop playback and reset.
+    pub fn stop(&mut self) {
+        self.player.stop();
+    }
+
+    /// Get current playback state.
+    pub fn state(&self) -> PlaybackState {
+        ChiptunePlayerBase::state(&self.player)
+    }
+
+    /// Get current frame position.
+    pub fn frame_position(&self) -> usize {
+        self.player.current_frame()
+    }
+
+    /// Get total frame count (KSS files store no length).
+    pub fn frame_count(&self) -> usize {
+        0
+    }
+
+    /// Get playback position as percentage (0.0 to 1.0).
+    pub fn playback_position(&self) -> f32 {
+        ChiptunePlayerBase::playback_position(&self.player)
+    }
+
+    /// Generate audio samples into a pre-allocated buffer.
+    pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        self.player.generate_samples_into(buffer);
+    }
+
+    /// Mute or unmute a channel.
+    pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        self.player.set_channel_mute(channel, mute);
+    }
+
+    /// Check if a channel is muted.
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        self.player.is_channel_muted(channel)
+    }
+
+    /// Set the output gain (0.0 to 4.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        ChiptunePlayerBase::set_gain(&mut self.player, gain);
+    }
+
+    /// Get the output gain.
+    pub fn gain(&self) -> f32 {
+        ChiptunePlayerBase::gain(&self.player)
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        ChiptunePlayerBase::set_playback_rate(&mut self.player, rate);
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        ChiptunePlayerBase::playback_rate(&self.player)
+    }
+
+    /// Get the number of tracks in the file.
+    pub fn subsong_count(&self) -> usize {
+        ChiptunePlayerBase::subsong_count(&self.player)
+    }
+
+    /// Get the current track (1-based).
+    pub fn current_subsong(&self) -> usize {
+        ChiptunePlayerBase::current_subsong(&self.player)
+    }
+
+    /// Switch to another track of the file (1-based). Returns true on success.
+    pub fn set_subsong(&mut self, index: usize) -> bool {
+        ChiptunePlayerBase::set_subsong(&mut self.player, index)
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        self.player.step_frames(frames)
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        ChiptunePlayerBase::set_soft_clip(&mut self.player, enabled);
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        ChiptunePlayerBase::soft_clip(&self.player)
+    }
+
+    /// Dump current PSG register values.
+    pub fn dump_registers(&self) -> [u8; 16] {
+        self.player.chip().dump_registers()
+    }
+
+    /// Get current per-channel audio outputs.
+    ///
+    /// Returns the actual audio output values (A, B, C) updated at sample rate.
+    pub fn get_channel_outputs(&self) -> (f32, f32, f32) {
+        self.player.chip().get_channel_outputs()
+    }
+
+    /// Enable or disable the color filter.
+    pub fn set_color_filter(&mut self, _enabled: bool) {
+        // Not applicable for KSS (MSX PSG, no ST filter)
+    }
+
+    /// Apply an analog output stage model.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        ChiptunePlayerBase::set_output_model(&mut self.player, model);
+    }
+
+    /// Enable or disable the per-channel output taps.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        ChiptunePlayerBase::set_channel_tap(&mut self.player, capacity);
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        ChiptunePlayerBase::read_channel_tap(&self.player, channel, out)
+    }
+
+    /// Generate samples with per-sample channel outputs for visualization.
+    ///
+    /// Fills the mono buffer with mixed samples and the channels buffer with
+    /// [A, B, C] per sample.
+    pub fn generate_samples_with_channels_into(&mut self, mono: &mut [f32], channels: &mut [f32]) {
+        let mut sample_buf = [0.0f32; 1];
+        for (i, mono_sample) in mono.iter_mut().enumerate() {
+            self.player.generate_samples_into(&mut sample_buf);
+            *mono_sample = sample_buf[0];
+            let (a, b, c) = self.player.chip().get_channel_outputs();
+            channels[i * 3] = a;
+            channels[i * 3 + 1] = b;
+            channels[i * 3 + 2] = c;
+        }
+    }
+}
@@ -1,53 +1,100 @@
 //! Player wrapper types for different file formats.
 //!
-//! This module provides unified access to YM, Arkos, AY, and SNDH players
+//! This module provides unified access to YM, Arkos, AY, SNDH and KSS players
 //! through the `BrowserSongPlayer` enum.
 
+#[cfg(feature = "arkos")]
 pub mod arkos;
+#[cfg(feature = "ay")]
 pub mod ay;
+#[cfg(feature = "kss")]
+pub mod kss;
+#[cfg(feature = "sndh")]
 pub mod sndh;
+pub mod ym;
 
+#[cfg(feature = "arkos")]
 use arkos::ArkosWasmPlayer;
+#[cfg(feature = "ay")]
 use ay::AyWasmPlayer;
+#[cfg(feature = "kss")]
+use kss::KssWasmPlayer;
+#[cfg(feature = "sndh")]
 use sndh::SndhWasmPlayer;
-use ym2149::Ym2149Backend;
-use ym2149_common::{ChiptunePlayerBase, PlaybackState};
-
-/// Convert mono samples to interleaved stereo (duplicate L/R).
+use ym::YmWasmPlayer;
+use ym2149_common::{ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta};
+#[cfg(feature = "sndh")]
+use ym2149_sndh_replayer::SubsongInfo;
+use ym2149_ym_replayer::EffectEvent;
+
+/// Spread mono samples held in the first half of `buffer` into interleaved
+/// stereo (duplicate L/R) in place.
 #[inline]
-fn mono_to_stereo(mono: &[f32], stereo: &mut [f32]) {
-    for (i, &sample) in mono.iter().enumerate() {
-        stereo[i * 2] = sample;
-        stereo[i * 2 + 1] = sample;
+pub(crate) fn mono_to_stereo_in_place(buffer: &mut [f32]) {
+    // Backwards, so no mono sample is overwritten before it is read
+    for i in (0..buffer.len() / 2).rev() {
+        let sample = buffer[i];
+        buffer[i * 2] = sample;
+        buffer[i * 2 + 1] = sample;
     }
 }
 
 /// Unified player enum for all supported formats.
 pub enum BrowserSongPlayer {
     /// YM format player (YM2-YM6).
-    Ym(Box<ym2149_ym_replayer::YmPlayer>),
+    Ym(Box<YmWasmPlayer>),
     /// Arkos Tracker format player (.aks).
+    #[cfg(feature = "arkos")]
     Arkos(Box<ArkosWasmPlayer>),
     /// AY format player (.ay).
+    #[cfg(feature = "ay")]
     Ay(Box<AyWasmPlayer>),
     /// SNDH format player (Atari ST).
+    #[cfg(feature = "sndh")]
     Sndh(Box<SndhWasmPlayer>),
+    /// KSS format player (MSX).
+    #[cfg(feature = "kss")]
+    Kss(Box<KssWasmPlayer>),
 }
 
 impl BrowserSongPlayer {
     /// Seek to a specific frame.
     ///
     /// Returns `true` if seek is supported and successful, `false` otherwise.
-    /// Supported for YM and SNDH formats. Arkos and AY do not support seeking.
+    /// Supported for YM and SNDH formats. Arkos, AY and KSS do not support seeking.
     pub fn seek_frame(&mut self, frame: usize) -> bool {
         match self {
             BrowserSongPlayer::Ym(player) => {
                 player.seek_frame(frame);
                 true
             }
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => false,
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(_) => false,
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.seek_frame(frame),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => false,
+        }
+    }
+
+    /// Turn a player for a format without native seeking into a track
+    /// pre-renderer.
+    ///
+    /// Returns `None` for YM and SNDH, which seek natively, and for KSS,
+    /// whose files store no length to render up to.
+    pub fn into_renderer(self) -> Option<Box<dyn ChiptunePlayerBase>> {
+        match self {
+            BrowserSongPlayer::Ym(_) => None,
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(_) => None,
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => Some(player.into_renderer()),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => Some(player.into_renderer()),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => None,
         }
     }
 
@@ -57,10 +104,15 @@ impl BrowserSongPlayer {
     /// Uses ChiptunePlayerBase::seek() which handles fallback duration for older SNDH.
     pub fn seek_percentage(&mut self, position: f32) -> bool {
         match self {
-            BrowserSongPlayer::Ym(player) => ChiptunePlayerBase::seek(player.as_mut(), position),
+            BrowserSongPlayer::Ym(player) => player.seek_percentage(position),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => false,
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(_) => false,
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.seek_percentage(position),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => false,
         }
     }
 
@@ -69,23 +121,33 @@ impl BrowserSongPlayer {
     /// For SNDH < 2.2 without FRMS/TIME, returns 300 (5 minute fallback).
     pub fn duration_seconds(&self) -> f32 {
         match self {
-            BrowserSongPlayer::Ym(player) => ChiptunePlayerBase::duration_seconds(player.as_ref()),
+            BrowserSongPlayer::Ym(player) => player.duration_seconds(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.duration_seconds(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.duration_seconds(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.duration_seconds(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.duration_seconds(),
         }
     }
 
     /// Check if the duration is from actual metadata or estimated.
     ///
-    /// Returns false for older SNDH files using the 5-minute fallback.
-    /// Always returns true for YM/Arkos/AY (they always have duration info).
+    /// Returns false for older SNDH files using the 5-minute fallback and for
+    /// KSS files, which store no length. Always returns true for YM/Arkos/AY.
     pub fn has_duration_info(&self) -> bool {
         match self {
             BrowserSongPlayer::Ym(_) => true,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => true,
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(_) => true,
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.has_duration_info(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => false,
         }
     }
 
@@ -93,11 +155,16 @@ impl BrowserSongPlayer {
     pub fn play(&mut self) {
         match self {
             BrowserSongPlayer::Ym(player) => player.play(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.play(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => {
                 let _ = player.play();
             }
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.play(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.play(),
         }
     }
 
@@ -105,9 +172,14 @@ impl BrowserSongPlayer {
     pub fn pause(&mut self) {
         match self {
             BrowserSongPlayer::Ym(player) => player.pause(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.pause(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.pause(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.pause(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.pause(),
         }
     }
 
@@ -115,9 +187,14 @@ impl BrowserSongPlayer {
     pub fn stop(&mut self) {
         match self {
             BrowserSongPlayer::Ym(player) => player.stop(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.stop(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.stop(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.stop(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.stop(),
         }
     }
 
@@ -125,19 +202,38 @@ impl BrowserSongPlayer {
     pub fn state(&self) -> PlaybackState {
         match self {
             BrowserSongPlayer::Ym(player) => player.state(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.state(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.state(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.state(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.state(),
+        }
+    }
+
+    /// Effect starts and stops of the song (YM only; empty for other formats).
+    pub fn effect_events(&self) -> Vec<EffectEvent> {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.effect_events(),
+            #[allow(unreachable_patterns)]
+            _ => Vec::new(),
         }
     }
 
     /// Get current frame position.
     pub fn frame_position(&self) -> usize {
         match self {
-            BrowserSongPlayer::Ym(player) => player.get_current_frame(),
+            BrowserSongPlayer::Ym(player) => player.frame_position(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.frame_position(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.frame_position(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.frame_position(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.frame_position(),
         }
     }
 
@@ -145,9 +241,14 @@ impl BrowserSongPlayer {
     pub fn frame_count(&self) -> usize {
         match self {
             BrowserSongPlayer::Ym(player) => player.frame_count(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.frame_count(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.frame_count(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.frame_count(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.frame_count(),
         }
     }
 
@@ -155,19 +256,14 @@ impl BrowserSongPlayer {
     pub fn playback_position(&self) -> f32 {
         match self {
             BrowserSongPlayer::Ym(player) => player.playback_position(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.playback_position(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.playback_position(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.playback_position(),
-        }
-    }
-
-    /// Generate audio samples.
-    pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        match self {
-            BrowserSongPlayer::Ym(player) => player.generate_samples(count),
-            BrowserSongPlayer::Arkos(player) => player.generate_samples(count),
-            BrowserSongPlayer::Ay(player) => player.generate_samples(count),
-            BrowserSongPlayer::Sndh(player) => player.generate_samples(count),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.playback_position(),
         }
     }
 
@@ -175,25 +271,14 @@ impl BrowserSongPlayer {
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
         match self {
             BrowserSongPlayer::Ym(player) => player.generate_samples_into(buffer),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.generate_samples_into(buffer),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.generate_samples_into(buffer),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.generate_samples_into(buffer),
-        }
-    }
-
-    /// Generate stereo audio samples (interleaved L/R).
-    ///
-    /// Returns frame_count * 2 samples. SNDH uses native stereo output,
-    /// other formats duplicate mono to stereo.
-    pub fn generate_samples_stereo(&mut self, frame_count: usize) -> Vec<f32> {
-        match self {
-            BrowserSongPlayer::Sndh(player) => player.generate_samples_stereo(frame_count),
-            _ => {
-                let mono = self.generate_samples(frame_count);
-                let mut stereo = vec![0.0; frame_count * 2];
-                mono_to_stereo(&mono, &mut stereo);
-                stereo
-            }
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.generate_samples_into(buffer),
         }
     }
 
@@ -203,22 +288,29 @@ impl BrowserSongPlayer {
     /// other formats duplicate mono to stereo.
     pub fn generate_samples_into_stereo(&mut self, buffer: &mut [f32]) {
         match self {
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.generate_samples_into_stereo(buffer),
+            #[allow(unreachable_patterns)]
             _ => {
                 let frame_count = buffer.len() / 2;
-                let mono = self.generate_samples(frame_count);
-                mono_to_stereo(&mono, buffer);
+                self.generate_samples_into(&mut buffer[..frame_count]);
+                mono_to_stereo_in_place(&mut buffer[..frame_count * 2]);
             }
         }
     }
 
-    /// Mute or unmute a channel.
+    /// Mute or unmute a channel (global index, 3 channels per PSG).
     pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
         match self {
             BrowserSongPlayer::Ym(player) => player.set_channel_mute(channel, mute),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.set_channel_mute(channel, mute),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.set_channel_mute(channel, mute),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.set_channel_mute(channel, mute),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_channel_mute(channel, mute),
         }
     }
 
@@ -226,39 +318,209 @@ impl BrowserSongPlayer {
     pub fn is_channel_muted(&self, channel: usize) -> bool {
         match self {
             BrowserSongPlayer::Ym(player) => player.is_channel_muted(channel),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.is_channel_muted(channel),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.is_channel_muted(channel),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.is_channel_muted(channel),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.is_channel_muted(channel),
+        }
+    }
+
+    /// Set the output gain applied by the player.
+    pub fn set_gain(&mut self, gain: f32) {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.set_gain(gain),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.set_gain(gain),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_gain(gain),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.set_gain(gain),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_gain(gain),
+        }
+    }
+
+    /// Get the output gain applied by the player.
+    pub fn gain(&self) -> f32 {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.gain(),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.gain(),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.gain(),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.gain(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.gain(),
+        }
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.set_playback_rate(rate),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.set_playback_rate(rate),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_playback_rate(rate),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.set_playback_rate(rate),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_playback_rate(rate),
+        }
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.playback_rate(),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.playback_rate(),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.playback_rate(),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.playback_rate(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.playback_rate(),
+        }
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.step_frames(frames),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.step_frames(frames),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.step_frames(frames),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.step_frames(frames),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.step_frames(frames),
+        }
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.set_soft_clip(enabled),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.set_soft_clip(enabled),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_soft_clip(enabled),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.set_soft_clip(enabled),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_soft_clip(enabled),
+        }
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.soft_clip(),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.soft_clip(),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.soft_clip(),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.soft_clip(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.soft_clip(),
         }
     }
 
     /// Dump current PSG register values.
     pub fn dump_registers(&self) -> [u8; 16] {
         match self {
-            BrowserSongPlayer::Ym(player) => player.get_chip().dump_registers(),
+            BrowserSongPlayer::Ym(player) => player.dump_registers(),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.dump_registers(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.dump_registers(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.dump_registers(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.dump_registers(),
         }
     }
 
     /// Enable or disable the color filter.
     pub fn set_color_filter(&mut self, enabled: bool) {
         match self {
-            BrowserSongPlayer::Ym(player) => player.get_chip_mut().set_color_filter(enabled),
+            BrowserSongPlayer::Ym(player) => player.set_color_filter(enabled),
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.set_color_filter(enabled),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => player.set_color_filter(enabled),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.set_color_filter(enabled),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_color_filter(enabled),
+        }
+    }
+
+    /// Apply an analog output stage model to every PSG.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.set_output_model(model),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.set_output_model(model),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_output_model(model),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.set_output_model(model),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_output_model(model),
+        }
+    }
+
+    /// Enable (`Some(capacity)`) or disable (`None`) the per-channel output taps.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.set_channel_tap(capacity),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.set_channel_tap(capacity),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_channel_tap(capacity),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.set_channel_tap(capacity),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_channel_tap(capacity),
+        }
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`, oldest first.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        match self {
+            BrowserSongPlayer::Ym(player) => player.read_channel_tap(channel, out),
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.read_channel_tap(channel, out),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.read_channel_tap(channel, out),
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(player) => player.read_channel_tap(channel, out),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.read_channel_tap(channel, out),
         }
     }
 
-    /// Get the number of subsongs (1 for most formats, >1 for multi-song SNDH files).
+    /// Get the number of subsongs (1 for most formats, more for AY, SNDH and KSS files).
     pub fn subsong_count(&self) -> usize {
         match self {
             BrowserSongPlayer::Ym(_) => 1,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => 1,
-            BrowserSongPlayer::Ay(_) => 1,
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.subsong_count(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.subsong_count(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.subsong_count(),
         }
     }
 
@@ -266,9 +528,23 @@ impl BrowserSongPlayer {
     pub fn current_subsong(&self) -> usize {
         match self {
             BrowserSongPlayer::Ym(_) => 1,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => 1,
-            BrowserSongPlayer::Ay(_) => 1,
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.current_subsong(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.current_subsong(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.current_subsong(),
+        }
+    }
+
+    /// Get name, length and replay timer of a subsong (1-based, SNDH only).
+    #[cfg(feature = "sndh")]
+    pub fn subsong_info(&self, index: usize) -> Option<SubsongInfo> {
+        match self {
+            BrowserSongPlayer::Sndh(player) => player.subsong_info(index),
+            _ => None,
         }
     }
 
@@ -276,24 +552,50 @@ impl BrowserSongPlayer {
     pub fn set_subsong(&mut self, index: usize) -> bool {
         match self {
             BrowserSongPlayer::Ym(_) => index == 1,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => index == 1,
-            BrowserSongPlayer::Ay(_) => index == 1,
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.set_subsong(index),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.set_subsong(index),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => player.set_subsong(index),
         }
     }
 
     /// Get the number of audio channels.
     ///
     /// Returns:
-    /// - 3 for YM/AY (single PSG chip)
-    /// - 6/9/12 for Arkos (multi-chip)
+    /// - 3 for YM/AY/KSS (single PSG chip)
+    /// - 6/9/12 for Arkos (multi-chip), 9 for AY PlayCity songs
     /// - 5 for SNDH (3 YM channels + 2 DAC L/R)
     pub fn channel_count(&self) -> usize {
         match self {
             BrowserSongPlayer::Ym(_) => 3,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.channel_count(),
-            BrowserSongPlayer::Ay(_) => 3,
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.channel_count(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.channel_count(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => 3,
+        }
+    }
+
+    /// Get the number of PSG chips (more than one only for multi-PSG Arkos
+    /// songs and AY PlayCity songs).
+    pub fn psg_count(&self) -> usize {
+        match self {
+            #[cfg(feature = "arkos")]
+            BrowserSongPlayer::Arkos(player) => player.psg_count(),
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.psg_count(),
+            BrowserSongPlayer::Ym(_) => 1,
+            #[cfg(feature = "sndh")]
+            BrowserSongPlayer::Sndh(_) => 1,
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => 1,
         }
     }
 
@@ -302,10 +604,15 @@ impl BrowserSongPlayer {
     /// Returns an array of register dumps, one per PSG chip.
     pub fn dump_all_registers(&self) -> Vec<[u8; 16]> {
         match self {
-            BrowserSongPlayer::Ym(player) => vec![player.get_chip().dump_registers()],
+            BrowserSongPlayer::Ym(player) => vec![player.dump_registers()],
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.dump_all_registers(),
-            BrowserSongPlayer::Ay(player) => vec![player.dump_registers()],
+            #[cfg(feature = "ay")]
+            BrowserSongPlayer::Ay(player) => player.dump_all_registers(),
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => vec![player.dump_registers()],
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => vec![player.dump_registers()],
         }
     }
 
@@ -315,9 +622,14 @@ impl BrowserSongPlayer {
     pub fn loop_count(&self) -> u32 {
         match self {
             BrowserSongPlayer::Ym(_) => 0,
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(_) => 0,
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(_) => 0,
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => player.loop_count(),
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(_) => 0,
         }
     }
 
@@ -330,18 +642,26 @@ impl BrowserSongPlayer {
     pub fn get_channel_outputs(&self) -> Vec<[f32; 3]> {
         match self {
             BrowserSongPlayer::Ym(player) => {
-                let (a, b, c) = player.get_chip().get_channel_outputs();
+                let (a, b, c) = player.get_channel_outputs();
                 vec![[a, b, c]]
             }
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => player.get_channel_outputs(),
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => {
                 let (a, b, c) = player.get_channel_outputs();
                 vec![[a, b, c]]
             }
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => {
                 let (a, b, c) = player.get_channel_outputs();
                 vec![[a, b, c]]
             }
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => {
+                let (a, b, c) = player.get_channel_outputs();
+                vec![[a, b, c]]
+            }
         }
     }
 
@@ -354,31 +674,34 @@ impl BrowserSongPlayer {
     /// accurate oscilloscope visualization of the individual channel waveforms.
     pub fn generate_samples_with_channels(&mut self, count: usize) -> (Vec<f32>, Vec<f32>) {
         let mut mono = vec![0.0f32; count];
-        let channel_count = self.channel_count();
-        let mut channels = vec![0.0f32; count * channel_count];
+        let mut channels = vec![0.0f32; count * self.channel_count()];
+        self.generate_samples_with_channels_into(&mut mono, &mut channels);
+        (mono, channels)
+    }
 
+    /// Generate samples and per-sample channel outputs into pre-allocated
+    /// buffers (`channels` holds `channel_count()` values per mono sample).
+    pub fn generate_samples_with_channels_into(&mut self, mono: &mut [f32], channels: &mut [f32]) {
         match self {
             BrowserSongPlayer::Ym(player) => {
-                use ym2149::Ym2149Backend;
-                for i in 0..count {
-                    mono[i] = player.generate_sample();
-                    let (a, b, c) = player.get_chip().get_channel_outputs();
-                    channels[i * 3] = a;
-                    channels[i * 3 + 1] = b;
-                    channels[i * 3 + 2] = c;
-                }
+                player.generate_samples_with_channels_into(mono, channels)
             }
+            #[cfg(feature = "arkos")]
             BrowserSongPlayer::Arkos(player) => {
-                player.generate_samples_with_channels_into(&mut mono, &mut channels);
+                player.generate_samples_with_channels_into(mono, channels)
             }
+            #[cfg(feature = "ay")]
             BrowserSongPlayer::Ay(player) => {
-                player.generate_samples_with_channels_into(&mut mono, &mut channels);
+                player.generate_samples_with_channels_into(mono, channels)
             }
+            #[cfg(feature = "sndh")]
             BrowserSongPlayer::Sndh(player) => {
-                player.generate_samples_with_channels_into(&mut mono, &mut channels);
+                player.generate_samples_with_channels_into(mono, channels)
+            }
+            #[cfg(feature = "kss")]
+            BrowserSongPlayer::Kss(player) => {
+                player.generate_samples_with_channels_into(mono, channels)
             }
         }
-
-        (mono, channels)
     }
 }
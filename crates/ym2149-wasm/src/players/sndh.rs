@@ -3,8 +3,10 @@
 //! Wraps `SndhPlayer` to provide a consistent interface for the browser player.
 
 use ym2149::Ym2149Backend;
-use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, MetadataFields, PlaybackState};
-use ym2149_sndh_replayer::{SndhPlayer, load_sndh};
+use ym2149_common::{
+    ChiptunePlayer, ChiptunePlayerBase, MetadataFields, OutputModel, PlaybackState, RegisterDelta,
+};
+use ym2149_sndh_replayer::{SndhPlayer, SubsongInfo, load_sndh};
 
 use crate::YM_SAMPLE_RATE_F32;
 use crate::metadata::YmMetadata;
@@ -119,25 +121,11 @@ impl SndhWasmPlayer {
         self.player.has_duration_info()
     }
 
-    /// Generate mono audio samples.
-    pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        ChiptunePlayerBase::generate_samples(&mut self.player, count)
-    }
-
     /// Generate mono audio samples into a pre-allocated buffer.
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
         ChiptunePlayerBase::generate_samples_into(&mut self.player, buffer);
     }
 
-    /// Generate stereo audio samples (interleaved L/R).
-    ///
-    /// Returns stereo samples with STE DAC and LMC1992 audio processing.
-    pub fn generate_samples_stereo(&mut self, frame_count: usize) -> Vec<f32> {
-        let mut buffer = vec![0.0f32; frame_count * 2];
-        self.player.render_f32_stereo(&mut buffer);
-        buffer
-    }
-
     /// Generate stereo audio samples into a pre-allocated buffer (interleaved L/R).
     ///
     /// Buffer length must be even (frame_count * 2).
@@ -184,6 +172,41 @@ impl SndhWasmPlayer {
         }
     }
 
+    /// Set the output gain (0.0 to 4.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        ChiptunePlayerBase::set_gain(&mut self.player, gain);
+    }
+
+    /// Get the output gain.
+    pub fn gain(&self) -> f32 {
+        ChiptunePlayerBase::gain(&self.player)
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        ChiptunePlayerBase::set_playback_rate(&mut self.player, rate);
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        ChiptunePlayerBase::playback_rate(&self.player)
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        ChiptunePlayerBase::step_frames(&mut self.player, frames)
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        ChiptunePlayerBase::set_soft_clip(&mut self.player, enabled);
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        ChiptunePlayerBase::soft_clip(&self.player)
+    }
+
     /// Get the number of channels.
     ///
     /// Always returns 5 for SNDH (3 YM2149 + 2 DAC).
@@ -257,6 +280,21 @@ impl SndhWasmPlayer {
         // Not applicable for SNDH (uses real 68000 code)
     }
 
+    /// Apply an analog output stage model.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        ChiptunePlayerBase::set_output_model(&mut self.player, model);
+    }
+
+    /// Enable or disable the per-channel output taps.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        ChiptunePlayerBase::set_channel_tap(&mut self.player, capacity);
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        ChiptunePlayerBase::read_channel_tap(&self.player, channel, out)
+    }
+
     /// Get number of subsongs.
     pub fn subsong_count(&self) -> usize {
         self.player.subsong_count()
@@ -267,6 +305,11 @@ impl SndhWasmPlayer {
         self.player.current_subsong()
     }
 
+    /// Get name, length and replay timer of a subsong (1-based).
+    pub fn subsong_info(&self, index: usize) -> Option<SubsongInfo> {
+        self.player.subsong_info(index)
+    }
+
     /// Set subsong (1-based). Returns true on success.
     ///
     /// Valid range: 1 to `subsong_count()`.
@@ -0,0 +1,224 @@
+//! YM file WASM player wrapper.
+//!
+//! Wraps `YmPlayerGeneric` so the browser player can render YM songs through
+//! either the hardware-accurate YM2149 emulation or the experimental softsynth.
+
+use crate::metadata::{YmMetadata, metadata_from_summary};
+use ym2149::Ym2149Backend;
+use ym2149_common::{ChiptunePlayerBase, OutputModel, PlaybackState, RegisterDelta};
+use ym2149_softsynth::SoftSynth;
+use ym2149_ym_replayer::{EffectEvent, YmPlayer, YmPlayerGeneric};
+
+/// Chip backend used to render YM songs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YmChip {
+    /// Cycle-accurate YM2149 emulation (full YM6 effect support).
+    #[default]
+    Hardware,
+    /// Experimental software synthesizer (tone and volume only).
+    SoftSynth,
+}
+
+impl YmChip {
+    /// Parse a chip name (`"hardware"`/`"ym2149"` or `"softsynth"`, case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "hardware" | "ym2149" => Some(Self::Hardware),
+            "softsynth" => Some(Self::SoftSynth),
+            _ => None,
+        }
+    }
+}
+
+/// YM player wrapper for WebAssembly.
+pub enum YmWasmPlayer {
+    /// Player driving the hardware-accurate emulation.
+    Hardware(YmPlayer),
+    /// Player driving the experimental softsynth.
+    SoftSynth(YmPlayerGeneric<SoftSynth>),
+}
+
+/// Forward a call to the wrapped player, whichever backend it uses.
+macro_rules! with_player {
+    ($self:expr, $player:ident => $body:expr) => {
+        match $self {
+            YmWasmPlayer::Hardware($player) => $body,
+            YmWasmPlayer::SoftSynth($player) => $body,
+        }
+    };
+}
+
+impl YmWasmPlayer {
+    /// Load YM data with the requested chip backend.
+    pub fn new(data: &[u8], chip: YmChip) -> Result<(Self, YmMetadata), String> {
+        match chip {
+            YmChip::Hardware => {
+                let mut player = YmPlayer::new();
+                let summary = player.load_data(data).map_err(|e| e.to_string())?;
+                let metadata = metadata_from_summary(&player, &summary);
+                Ok((Self::Hardware(player), metadata))
+            }
+            YmChip::SoftSynth => {
+                let mut player = YmPlayerGeneric::<SoftSynth>::new();
+                let summary = player.load_data(data).map_err(|e| e.to_string())?;
+                let metadata = metadata_from_summary(&player, &summary);
+                Ok((Self::SoftSynth(player), metadata))
+            }
+        }
+    }
+
+    /// Get duration in seconds.
+    pub fn duration_seconds(&self) -> f32 {
+        with_player!(self, player => ChiptunePlayerBase::duration_seconds(player))
+    }
+
+    /// Start playback.
+    pub fn play(&mut self) {
+        with_player!(self, player => ChiptunePlayerBase::play(player))
+    }
+
+    /// Pause playback.
+    pub fn pause(&mut self) {
+        with_player!(self, player => ChiptunePlayerBase::pause(player))
+    }
+
+    /// Stop playback and reset.
+    pub fn stop(&mut self) {
+        with_player!(self, player => ChiptunePlayerBase::stop(player))
+    }
+
+    /// Get current playback state.
+    pub fn state(&self) -> PlaybackState {
+        with_player!(self, player => ChiptunePlayerBase::state(player))
+    }
+
+    /// Seek to a specific frame.
+    pub fn seek_frame(&mut self, frame: usize) {
+        with_player!(self, player => player.seek_frame(frame))
+    }
+
+    /// Seek to a percentage position (0.0 to 1.0).
+    pub fn seek_percentage(&mut self, position: f32) -> bool {
+        with_player!(self, player => ChiptunePlayerBase::seek(player, position))
+    }
+
+    /// Get current frame position.
+    pub fn frame_position(&self) -> usize {
+        with_player!(self, player => player.get_current_frame())
+    }
+
+    /// Get total frame count.
+    pub fn frame_count(&self) -> usize {
+        with_player!(self, player => player.frame_count())
+    }
+
+    /// Get playback position as percentage (0.0 to 1.0).
+    pub fn playback_position(&self) -> f32 {
+        with_player!(self, player => ChiptunePlayerBase::playback_position(player))
+    }
+
+    /// Generate audio samples into a pre-allocated buffer.
+    pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
+        with_player!(self, player => ChiptunePlayerBase::generate_samples_into(player, buffer))
+    }
+
+    /// Mute or unmute a channel.
+    pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
+        with_player!(self, player => player.set_channel_mute(channel, mute))
+    }
+
+    /// Check if a channel is muted.
+    pub fn is_channel_muted(&self, channel: usize) -> bool {
+        with_player!(self, player => player.is_channel_muted(channel))
+    }
+
+    /// Set the output gain (0.0 to 4.0).
+    pub fn set_gain(&mut self, gain: f32) {
+        with_player!(self, player => ChiptunePlayerBase::set_gain(player, gain))
+    }
+
+    /// Get the output gain.
+    pub fn gain(&self) -> f32 {
+        with_player!(self, player => ChiptunePlayerBase::gain(player))
+    }
+
+    /// Set the playback rate (speed and pitch).
+    pub fn set_playback_rate(&mut self, rate: f32) {
+        with_player!(self, player => ChiptunePlayerBase::set_playback_rate(player, rate))
+    }
+
+    /// Get the playback rate.
+    pub fn playback_rate(&self) -> f32 {
+        with_player!(self, player => ChiptunePlayerBase::playback_rate(player))
+    }
+
+    /// Advance `frames` frames while paused and return the register delta.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        with_player!(self, player => ChiptunePlayerBase::step_frames(player, frames))
+    }
+
+    /// Enable or disable the output soft clipper.
+    pub fn set_soft_clip(&mut self, enabled: bool) {
+        with_player!(self, player => ChiptunePlayerBase::set_soft_clip(player, enabled))
+    }
+
+    /// Check whether the output soft clipper is enabled.
+    pub fn soft_clip(&self) -> bool {
+        with_player!(self, player => ChiptunePlayerBase::soft_clip(player))
+    }
+
+    /// Dump current PSG register values.
+    pub fn dump_registers(&self) -> [u8; 16] {
+        with_player!(self, player => player.dump_registers())
+    }
+
+    /// Enable or disable the color filter.
+    pub fn set_color_filter(&mut self, enabled: bool) {
+        with_player!(self, player => player.set_color_filter(enabled))
+    }
+
+    /// Apply an analog output stage model.
+    pub fn set_output_model(&mut self, model: OutputModel) {
+        with_player!(self, player => ChiptunePlayerBase::set_output_model(player, model))
+    }
+
+    /// Enable (`Some(capacity)`) or disable (`None`) the per-channel output tap.
+    pub fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        with_player!(self, player => ChiptunePlayerBase::set_channel_tap(player, capacity))
+    }
+
+    /// Copy the most recent tapped samples of a channel into `out`, oldest first.
+    pub fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        with_player!(self, player => ChiptunePlayerBase::read_channel_tap(player, channel, out))
+    }
+
+    /// Get current channel outputs.
+    pub fn get_channel_outputs(&self) -> (f32, f32, f32) {
+        with_player!(self, player => player.backend().get_channel_outputs())
+    }
+
+    /// Effect starts and stops of the song, ordered by frame.
+    pub fn effect_events(&self) -> Vec<EffectEvent> {
+        with_player!(self, player => player.effect_events())
+    }
+
+    /// Copy of the decoded register frames (`None` for YM Tracker songs).
+    pub fn frames_clone(&self) -> Option<Vec<[u8; 16]>> {
+        with_player!(self, player => player.frames_clone())
+    }
+
+    /// Generate samples with per-sample channel outputs.
+    ///
+    /// `channels` receives interleaved `[A, B, C]` values for every sample.
+    pub fn generate_samples_with_channels_into(&mut self, mono: &mut [f32], channels: &mut [f32]) {
+        with_player!(self, player => {
+            for (i, sample) in mono.iter_mut().enumerate() {
+                ChiptunePlayerBase::generate_samples_into(player, std::slice::from_mut(sample));
+                let (a, b, c) = player.backend().get_channel_outputs();
+                channels[i * 3] = a;
+                channels[i * 3 + 1] = b;
+                channels[i * 3 + 2] = c;
+            }
+        })
+    }
+}
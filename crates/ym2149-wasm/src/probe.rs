@@ -0,0 +1,195 @@
+//! Metadata-only probing for playlists.
+//!
+//! `probeMetadata` reads what a playlist row needs (title, author, format,
+//! subsongs, duration) without creating a player: SNDH and AY headers are
+//! parsed without booting the 68000/Z80, and Arkos durations come from the
+//! song structure. KSS files only name their tracks; the player built for
+//! them maps the load image but calls no driver code. YM files are register dumps, so probing them loads the
+//! frames but runs no emulation.
+
+use wasm_bindgen::prelude::*;
+#[cfg(feature = "arkos")]
+use ym2149_arkos_replayer::load_aks;
+#[cfg(feature = "ay")]
+use ym2149_ay_replayer::{AyMetadata, load_ay};
+#[cfg(feature = "sndh")]
+use ym2149_common::DEFAULT_SAMPLE_RATE;
+use ym2149_common::{ChiptuneFormat, load_with_detection};
+#[cfg(feature = "kss")]
+use ym2149_kss_replayer::KssPlayer;
+#[cfg(feature = "sndh")]
+use ym2149_sndh_replayer::SndhFile;
+use ym2149_ym_replayer::{extract_zip_entry, is_zip_archive};
+
+use crate::bundle::format_not_included;
+use crate::metadata::YmMetadata;
+#[cfg(feature = "ay")]
+use crate::metadata::metadata_from_ay;
+#[cfg(feature = "kss")]
+use crate::metadata::metadata_from_kss;
+use crate::players::ym::{YmChip, YmWasmPlayer};
+use crate::{playable_archive_entries, set_js_prop};
+
+/// Metadata of a probed file.
+struct ProbedMetadata {
+    metadata: YmMetadata,
+    subsong_count: usize,
+}
+
+impl ProbedMetadata {
+    fn single(metadata: YmMetadata) -> Self {
+        Self {
+            metadata,
+            subsong_count: 1,
+        }
+    }
+}
+
+/// Read song metadata without constructing a player.
+///
+/// Returns an object:
+///
+/// ```json
+/// { "title": "...", "author": "...", "comments": "", "format": "SNDH",
+///   "subsongCount": 3, "frameCount": 9000, "frameRate": 50,
+///   "durationSeconds": 180 }
+/// ```
+///
+/// Values describe the default subsong. `durationSeconds` is 0 when the file
+/// carries no length (older SNDH, AY without a declared length); the player
+/// falls back to an estimate in that case. ZIP collections report their first
+/// song, like `Ym2149Player`.
+#[wasm_bindgen(js_name = probeMetadata)]
+pub fn probe_metadata(data: &[u8]) -> Result<JsValue, JsValue> {
+    let probed = probe(data)
+        .map_err(|e| JsValue::from_str(&format!("Failed to probe chiptune file: {e}")))?;
+    let metadata = probed.metadata;
+
+    let obj = js_sys::Object::new();
+    set_js_prop(&obj, "title", metadata.title);
+    set_js_prop(&obj, "author", metadata.author);
+    set_js_prop(&obj, "comments", metadata.comments);
+    set_js_prop(&obj, "format", metadata.format);
+    set_js_prop(&obj, "subsongCount", probed.subsong_count as u32);
+    set_js_prop(&obj, "frameCount", metadata.frame_count);
+    set_js_prop(&obj, "frameRate", metadata.frame_rate);
+    set_js_prop(&obj, "durationSeconds", metadata.duration_seconds);
+    Ok(obj.into())
+}
+
+/// Detect the format and read its metadata.
+fn probe(data: &[u8]) -> Result<ProbedMetadata, String> {
+    if data.is_empty() {
+        return Err("empty file data".to_string());
+    }
+
+    if is_zip_archive(data)
+        && let Ok(entries) = playable_archive_entries(data)
+        && let Some(first) = entries.first()
+    {
+        let entry = extract_zip_entry(data, first).map_err(|e| e.to_string())?;
+        if !is_zip_archive(&entry) {
+            return probe(&entry);
+        }
+    }
+
+    load_with_detection(data, |format| probe_as(data, format)).map_err(|e| e.to_string())
+}
+
+fn probe_as(data: &[u8], format: ChiptuneFormat) -> Result<ProbedMetadata, String> {
+    match format {
+        ChiptuneFormat::Ym => YmWasmPlayer::new(data, YmChip::Hardware)
+            .map(|(_, metadata)| ProbedMetadata::single(metadata)),
+        #[cfg(feature = "arkos")]
+        ChiptuneFormat::Arkos => probe_arkos(data),
+        #[cfg(feature = "sndh")]
+        ChiptuneFormat::Sndh => probe_sndh(data),
+        #[cfg(feature = "ay")]
+        ChiptuneFormat::Ay => probe_ay(data),
+        #[cfg(feature = "kss")]
+        ChiptuneFormat::Kss => probe_kss(data),
+        ChiptuneFormat::Zip | ChiptuneFormat::Unknown => {
+            Err(format!("no {} parser", format.name()))
+        }
+        #[allow(unreachable_patterns)]
+        _ => Err(format_not_included(format)),
+    }
+}
+
+#[cfg(feature = "arkos")]
+fn probe_arkos(data: &[u8]) -> Result<ProbedMetadata, String> {
+    let song = load_aks(data).map_err(|e| e.to_string())?;
+    let subsong = song
+        .subsongs
+        .first()
+        .ok_or_else(|| "Arkos song has no subsongs".to_string())?;
+    let frame_rate = subsong.replay_frequency_hz.round().max(1.0);
+    let lines: usize = subsong.positions.iter().map(|pos| pos.height).sum();
+    let frame_count = lines
+        .saturating_mul(subsong.initial_speed.max(1) as usize)
+        .max(1);
+    let info = song.metadata;
+    Ok(ProbedMetadata {
+        metadata: YmMetadata {
+            title: info.title,
+            author: if info.author.is_empty() {
+                info.composer
+            } else {
+                info.author
+            },
+            comments: info.comments,
+            format: "AKS".to_string(),
+            frame_count: frame_count as u32,
+            frame_rate: frame_rate as u32,
+            duration_seconds: frame_count as f32 / frame_rate,
+        },
+        subsong_count: song.subsongs.len(),
+    })
+}
+
+#[cfg(feature = "ay")]
+fn probe_ay(data: &[u8]) -> Result<ProbedMetadata, String> {
+    let file = load_ay(data).map_err(|e| e.to_string())?;
+    let meta = AyMetadata::from_file(&file, 0).ok_or_else(|| "AY file has no songs".to_string())?;
+    Ok(ProbedMetadata {
+        metadata: metadata_from_ay(&meta),
+        subsong_count: meta.song_count,
+    })
+}
+
+#[cfg(feature = "sndh")]
+fn probe_sndh(data: &[u8]) -> Result<ProbedMetadata, String> {
+    let file = SndhFile::parse(data).map_err(|e| e.to_string())?;
+    let meta = &file.metadata;
+    let frame_rate = meta.player_rate.max(1);
+    let frame_count = file
+        .get_subsong_info(meta.default_subsong, DEFAULT_SAMPLE_RATE)
+        .map(|info| info.player_tick_count)
+        .unwrap_or(0);
+    let or_unknown = |value: &Option<String>| match value {
+        Some(value) if !value.is_empty() => value.clone(),
+        _ => "(unknown)".to_string(),
+    };
+
+    Ok(ProbedMetadata {
+        metadata: YmMetadata {
+            title: or_unknown(&meta.title),
+            author: or_unknown(&meta.author),
+            comments: String::new(),
+            format: "SNDH".to_string(),
+            frame_count,
+            frame_rate,
+            duration_seconds: frame_count as f32 / frame_rate as f32,
+        },
+        subsong_count: meta.subsong_count,
+    })
+}
+
+#[cfg(feature = "kss")]
+fn probe_kss(data: &[u8]) -> Result<ProbedMetadata, String> {
+    let player = KssPlayer::load_from_bytes(data, 0).map_err(|e| e.to_string())?;
+    Ok(ProbedMetadata {
+        metadata: metadata_from_kss(&player),
+        subsong_count: player.file().track_count(),
+    })
+}
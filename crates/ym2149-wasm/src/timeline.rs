@@ -0,0 +1,198 @@
+//! Precomputed song timeline for seek bars.
+//!
+//! `EventTimeline` loads its own copy of a song and summarizes it per second
+//! (active channels, effects, note density, loudness). YM files are summarized
+//! straight from their register frames; other formats are emulated in
+//! `advance` slices so the page can build the timeline in the background.
+
+use wasm_bindgen::prelude::*;
+use ym2149_common::{FRAME_RATE_PAL, TimelineBuilder, TimelineSecond};
+
+use crate::players::{BrowserSongPlayer, ym::YmChip};
+use crate::{YM_SAMPLE_RATE_F32, load_browser_player, set_js_prop};
+
+/// Where timeline frames come from.
+enum TimelineSource {
+    /// Register frames of a YM file (no emulation needed).
+    Frames {
+        frames: Vec<[u8; 16]>,
+        position: usize,
+    },
+    /// A player emulated frame by frame.
+    Player {
+        player: Box<BrowserSongPlayer>,
+        total_frames: usize,
+        buffer: Vec<f32>,
+    },
+}
+
+/// Per-second song summary for rendering a seek bar with structure hints.
+///
+/// ```javascript
+/// const timeline = new EventTimeline(fileData);
+/// while (!timeline.advance(30)) {
+///     await new Promise(requestAnimationFrame); // keep the page responsive
+/// }
+/// for (const s of timeline.summaries()) {
+///     drawSecond(s.second, s.loudness, s.activeChannels, s.noise, s.envelope);
+/// }
+/// ```
+#[wasm_bindgen]
+pub struct EventTimeline {
+    source: TimelineSource,
+    builder: TimelineBuilder,
+    seconds: Vec<TimelineSecond>,
+    complete: bool,
+}
+
+#[wasm_bindgen]
+impl EventTimeline {
+    /// Create a timeline for a song file (any format `Ym2149Player` accepts).
+    ///
+    /// Nothing is computed until `advance` is called.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<EventTimeline, JsValue> {
+        let (player, metadata) = load_browser_player(data, YmChip::Hardware)
+            .map_err(|e| JsValue::from_str(&format!("Failed to load chiptune file: {e}")))?;
+
+        let frames = match &player {
+            BrowserSongPlayer::Ym(ym) => ym.frames_clone(),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        };
+        let (source, frame_rate) = match frames {
+            Some(frames) => (
+                TimelineSource::Frames {
+                    frames,
+                    position: 0,
+                },
+                metadata.frame_rate(),
+            ),
+            None => {
+                let mut player = Box::new(player);
+                player.play();
+                let total_frames =
+                    (player.duration_seconds() * FRAME_RATE_PAL as f32).ceil() as usize;
+                let samples_per_frame = (YM_SAMPLE_RATE_F32 / FRAME_RATE_PAL as f32) as usize;
+                (
+                    TimelineSource::Player {
+                        player,
+                        total_frames,
+                        buffer: vec![0.0; samples_per_frame],
+                    },
+                    FRAME_RATE_PAL,
+                )
+            }
+        };
+
+        Ok(EventTimeline {
+            source,
+            builder: TimelineBuilder::new(frame_rate),
+            seconds: Vec::new(),
+            complete: false,
+        })
+    }
+
+    /// Summarize up to `max_seconds` more of the song.
+    ///
+    /// YM files complete in one call. Returns true once the whole song is done.
+    pub fn advance(&mut self, max_seconds: f32) -> bool {
+        if self.complete {
+            return true;
+        }
+
+        let frame_budget = (max_seconds.max(0.0) * self.builder.frame_rate() as f32) as usize;
+        let done = match &mut self.source {
+            TimelineSource::Frames { frames, position } => {
+                for registers in &frames[*position..] {
+                    self.builder.record_frame(std::slice::from_ref(registers));
+                }
+                *position = frames.len();
+                true
+            }
+            TimelineSource::Player {
+                player,
+                total_frames,
+                buffer,
+            } => {
+                let end = (self.builder.frame_count() as usize + frame_budget).min(*total_frames);
+                while (self.builder.frame_count() as usize) < end {
+                    self.builder.record_frame(&player.dump_all_registers());
+                    player.generate_samples_into(buffer);
+                }
+                end >= *total_frames
+            }
+        };
+
+        if done {
+            let builder = std::mem::replace(&mut self.builder, TimelineBuilder::new(0));
+            self.seconds = builder.finish();
+            self.complete = true;
+        } else {
+            self.seconds = self.builder.seconds().to_vec();
+        }
+        self.complete
+    }
+
+    /// Whether the whole song has been summarized.
+    #[wasm_bindgen(js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Fraction of the song summarized so far (0.0 to 1.0).
+    pub fn progress(&self) -> f32 {
+        if self.complete {
+            return 1.0;
+        }
+        let (done, total) = match &self.source {
+            TimelineSource::Frames { frames, position } => (*position, frames.len()),
+            TimelineSource::Player { total_frames, .. } => {
+                (self.builder.frame_count() as usize, *total_frames)
+            }
+        };
+        if total == 0 {
+            1.0
+        } else {
+            done as f32 / total as f32
+        }
+    }
+
+    /// Number of seconds summarized so far.
+    #[wasm_bindgen(js_name = secondCount)]
+    pub fn second_count(&self) -> usize {
+        self.seconds.len()
+    }
+
+    /// Per-second summaries as an array of objects:
+    ///
+    /// ```json
+    /// { "second": 0, "activeChannels": 7, "maxVoices": 3, "noteOnsets": 12,
+    ///   "noise": true, "envelope": false, "digi": false,
+    ///   "loudness": 0.62, "peak": 0.81 }
+    /// ```
+    ///
+    /// `activeChannels` is a bit mask (bit 0 = channel A of the first PSG).
+    pub fn summaries(&self) -> JsValue {
+        let array = js_sys::Array::new();
+        for second in &self.seconds {
+            let obj = js_sys::Object::new();
+            set_js_prop(&obj, "second", second.second);
+            set_js_prop(&obj, "activeChannels", second.active_channels);
+            set_js_prop(&obj, "maxVoices", second.max_voices);
+            set_js_prop(&obj, "noteOnsets", second.note_onsets);
+            set_js_prop(&obj, "noise", second.noise);
+            set_js_prop(&obj, "envelope", second.envelope);
+            set_js_prop(&obj, "digi", second.digi);
+            set_js_prop(&obj, "loudness", second.loudness);
+            set_js_prop(&obj, "peak", second.peak);
+            array.push(&obj);
+        }
+        array.into()
+    }
+
+    /// Mean loudness per second (0.0 to 1.0), for drawing a waveform-style seek bar.
+    pub fn loudness(&self) -> Vec<f32> {
+        self.seconds.iter().map(|s| s.loudness).collect()
+    }
+}
@@ -0,0 +1,60 @@
+//! Waveform peaks for files outside the catalog.
+//!
+//! The catalog ships peaks precomputed by the metadata tool; `computeWaveform`
+//! produces the same data in the browser by rendering the song without audio
+//! output. A whole song takes a noticeable moment to emulate, so call it from
+//! a Web Worker.
+
+use wasm_bindgen::prelude::*;
+
+use crate::players::ym::YmChip;
+use crate::{YM_SAMPLE_RATE_F32, load_browser_player};
+
+/// Samples rendered per `generate_samples_into` call.
+const RENDER_CHUNK: usize = 4096;
+
+/// Render a whole song and return one peak per bar (0-255).
+///
+/// Peaks are the largest absolute sample of each bar, scaled like the
+/// catalog's `w` field (base64 there). `bars` is capped at one bar per sample.
+/// Returns an empty array for songs without a known duration or when `bars`
+/// is 0.
+///
+/// ```javascript
+/// // waveform-worker.js
+/// import init, { computeWaveform } from 'ym2149-wasm';
+/// self.onmessage = async ({ data }) => {
+///     await init();
+///     self.postMessage(computeWaveform(new Uint8Array(data), 400));
+/// };
+/// ```
+#[wasm_bindgen(js_name = computeWaveform)]
+pub fn compute_waveform(data: &[u8], bars: usize) -> Result<Vec<u8>, JsValue> {
+    let (mut player, _) = load_browser_player(data, YmChip::Hardware)
+        .map_err(|e| JsValue::from_str(&format!("Failed to load chiptune file: {e}")))?;
+
+    let total_samples = (player.duration_seconds() * YM_SAMPLE_RATE_F32) as usize;
+    if bars == 0 || total_samples == 0 {
+        return Ok(Vec::new());
+    }
+    let bars = bars.min(total_samples);
+    let samples_per_bar = total_samples / bars;
+
+    player.play();
+    let mut buffer = vec![0.0f32; RENDER_CHUNK];
+    let mut peaks = Vec::with_capacity(bars);
+    for _ in 0..bars {
+        let mut peak: f32 = 0.0;
+        let mut remaining = samples_per_bar;
+        while remaining > 0 {
+            let chunk = &mut buffer[..remaining.min(RENDER_CHUNK)];
+            player.generate_samples_into(chunk);
+            peak = chunk
+                .iter()
+                .fold(peak, |peak, sample| peak.max(sample.abs()));
+            remaining -= chunk.len();
+        }
+        peaks.push((peak.min(1.0) * 255.0) as u8);
+    }
+    Ok(peaks)
+}
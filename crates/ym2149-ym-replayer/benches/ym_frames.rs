@@ -0,0 +1,65 @@
+//! Benchmarks for YM frame playback
+//!
+//! Run with: cargo bench --bench ym_frames -p ym2149-ym-replayer
+//!
+//! Songs are read from the Bevy example assets; benchmarks are skipped when
+//! the assets are not available.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use std::hint::black_box;
+use ym2149_common::test_support::read_asset;
+use ym2149_common::{ChiptunePlayerBase, PlaybackState};
+use ym2149_ym_replayer::load_song;
+
+/// Samples per VBL frame at 44.1kHz and 50Hz.
+const SAMPLES_PER_FRAME: usize = 882;
+
+/// A plain YM song and one using Mad Max digidrums.
+const SONGS: &[&str] = &["Steps.ym", "Iceage (digi).ym"];
+
+fn bench_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ym_load");
+
+    for &name in SONGS {
+        let Some(data) = read_asset(name) else {
+            continue;
+        };
+        // Includes LHA decompression for packed files
+        group.bench_with_input(BenchmarkId::from_parameter(name), &data, |b, data| {
+            b.iter(|| black_box(load_song(black_box(data)).expect("song loads")));
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_frames(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ym_frames");
+
+    for &name in SONGS {
+        let Some(data) = read_asset(name) else {
+            continue;
+        };
+        let (mut player, _) = load_song(&data).expect("song loads");
+        player.play();
+        let mut buffer = vec![0.0f32; SAMPLES_PER_FRAME];
+
+        group.throughput(Throughput::Elements(SAMPLES_PER_FRAME as u64));
+        group.bench_function(BenchmarkId::from_parameter(name), |b| {
+            b.iter(|| {
+                // Songs stop at their end; keep measuring real frames
+                if player.state() != PlaybackState::Playing {
+                    player.stop();
+                    player.play();
+                }
+                player.generate_samples_into(&mut buffer);
+                black_box(&buffer);
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_load, bench_frames);
+criterion_main!(benches);
@@ -0,0 +1,574 @@
+//! LHA (`-lh5-`) archive writer
+//!
+//! Produces single-file LHA archives in the layout used by the original YM
+//! distribution: a level 0 header followed by `-lh5-` compressed data
+//! (LZSS with an 8 KB window and per-block canonical Huffman trees).
+//!
+//! The output is readable by [`super::decompress_if_needed`] as well as by
+//! classic LHA/LHarc tools.
+
+use crate::{ReplayerError, Result};
+
+/// Sliding window size for `-lh5-` (8 KB)
+const WINDOW_SIZE: usize = 1 << 13;
+
+/// Shortest and longest match lengths encodable by a command code
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 256;
+
+/// Number of command codes (256 literals + 254 match lengths)
+const NUM_COMMANDS: usize = 510;
+
+/// Number of offset codes (offset bit lengths 0..=13)
+const NUM_OFFSETS: usize = 14;
+
+/// Number of codes in the tree used to transmit command code lengths
+const NUM_TEMP_CODES: usize = 19;
+
+/// Bit widths of the tree-size fields
+const COMMAND_BITS: u32 = 9;
+const OFFSET_BITS: u32 = 4;
+const TEMP_BITS: u32 = 5;
+
+/// Maximum Huffman code length
+const MAX_CODE_LENGTH: u8 = 16;
+
+/// Maximum number of commands per block (16-bit counter)
+const MAX_BLOCK_COMMANDS: usize = u16::MAX as usize;
+
+/// Number of hash chain candidates examined per position
+const MAX_CHAIN_DEPTH: usize = 128;
+
+/// Level 0 header size excluding the two leading size/checksum bytes
+const LEVEL0_HEADER_BASE: usize = 22;
+
+/// MS-DOS timestamp written to headers (1980-01-01 00:00:00)
+const DOS_EPOCH_TIMESTAMP: u32 = 0x0021_0000;
+
+/// MS-DOS "archive" attribute
+const MSDOS_ATTR_ARCHIVE: u8 = 0x20;
+
+/// Marker for an empty hash chain slot
+const NO_POSITION: u32 = u32::MAX;
+
+/// Compress `data` into a single-entry `-lh5-` LHA archive
+///
+/// # Arguments
+/// * `file_name` - Name stored for the archive entry (e.g. `"song.ym"`)
+/// * `data` - Uncompressed file contents
+///
+/// # Returns
+/// The complete archive, including the end-of-archive marker.
+pub fn compress_lha(file_name: &str, data: &[u8]) -> Result<Vec<u8>> {
+    let name = file_name.as_bytes();
+    if name.len() > u8::MAX as usize - LEVEL0_HEADER_BASE {
+        return Err(ReplayerError::ConfigError(format!(
+            "LHA entry name too long ({} bytes)",
+            name.len()
+        )));
+    }
+    let original_size = u32::try_from(data.len()).map_err(|_| {
+        ReplayerError::ConfigError(format!("LHA entry too large ({} bytes)", data.len()))
+    })?;
+
+    let packed = encode_lh5(data);
+    let packed_size = u32::try_from(packed.len()).map_err(|_| {
+        ReplayerError::ConfigError(format!("LHA entry too large ({} bytes)", packed.len()))
+    })?;
+
+    let mut header = Vec::with_capacity(LEVEL0_HEADER_BASE + name.len());
+    header.extend_from_slice(b"-lh5-");
+    header.extend_from_slice(&packed_size.to_le_bytes());
+    header.extend_from_slice(&original_size.to_le_bytes());
+    header.extend_from_slice(&DOS_EPOCH_TIMESTAMP.to_le_bytes());
+    header.push(MSDOS_ATTR_ARCHIVE);
+    header.push(0); // header level
+    header.push(name.len() as u8);
+    header.extend_from_slice(name);
+    header.extend_from_slice(&crc16(data).to_le_bytes());
+
+    let checksum = header.iter().fold(0u8, |sum, &b| sum.wrapping_add(b));
+
+    let mut archive = Vec::with_capacity(header.len() + packed.len() + 3);
+    archive.push(header.len() as u8);
+    archive.push(checksum);
+    archive.extend_from_slice(&header);
+    archive.extend_from_slice(&packed);
+    archive.push(0); // end of archive
+    Ok(archive)
+}
+
+/// CRC-16 (ARC polynomial) as stored in LHA headers
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0u16, |crc, &byte| {
+        (0..8).fold(crc ^ byte as u16, |crc, _| {
+            if crc & 1 != 0 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            }
+        })
+    })
+}
+
+/// A single LZSS command
+#[derive(Clone, Copy)]
+enum Command {
+    Literal(u8),
+    Match { length: usize, distance: usize },
+}
+
+impl Command {
+    fn code(self) -> usize {
+        match self {
+            Command::Literal(byte) => byte as usize,
+            Command::Match { length, .. } => 256 + length - MIN_MATCH,
+        }
+    }
+}
+
+/// Offset code for a match distance: the bit length of `distance - 1`
+fn offset_code(distance: usize) -> usize {
+    let offset = distance - 1;
+    (usize::BITS - offset.leading_zeros()) as usize
+}
+
+fn encode_lh5(data: &[u8]) -> Vec<u8> {
+    let commands = parse_commands(data);
+    let mut writer = BitWriter::default();
+    for block in commands.chunks(MAX_BLOCK_COMMANDS) {
+        write_block(&mut writer, block);
+    }
+    writer.finish()
+}
+
+/// Greedy LZSS parse with hash chains over three-byte prefixes
+fn parse_commands(data: &[u8]) -> Vec<Command> {
+    let mut chains = HashChains::new(data.len());
+    let mut commands = Vec::new();
+    let mut pos = 0;
+
+    while pos < data.len() {
+        let (length, distance) = chains.longest_match(data, pos);
+        if length >= MIN_MATCH {
+            commands.push(Command::Match { length, distance });
+            for p in pos..pos + length {
+                chains.insert(data, p);
+            }
+            pos += length;
+        } else {
+            commands.push(Command::Literal(data[pos]));
+            chains.insert(data, pos);
+            pos += 1;
+        }
+    }
+    commands
+}
+
+/// Match finder state
+struct HashChains {
+    /// Most recent position for each prefix hash
+    head: Vec<u32>,
+    /// Previous position with the same prefix hash
+    prev: Vec<u32>,
+}
+
+impl HashChains {
+    fn new(len: usize) -> Self {
+        Self {
+            head: vec![NO_POSITION; 1 << 16],
+            prev: vec![NO_POSITION; len],
+        }
+    }
+
+    fn hash(data: &[u8], pos: usize) -> usize {
+        (((data[pos] as usize) << 8) ^ ((data[pos + 1] as usize) << 4) ^ data[pos + 2] as usize)
+            & 0xFFFF
+    }
+
+    fn insert(&mut self, data: &[u8], pos: usize) {
+        if pos + MIN_MATCH <= data.len() {
+            let hash = Self::hash(data, pos);
+            self.prev[pos] = self.head[hash];
+            self.head[hash] = pos as u32;
+        }
+    }
+
+    /// Longest match for `pos` within the window as `(length, distance)`
+    fn longest_match(&self, data: &[u8], pos: usize) -> (usize, usize) {
+        let available = (data.len() - pos).min(MAX_MATCH);
+        let mut best = (0, 0);
+        if available < MIN_MATCH {
+            return best;
+        }
+
+        let mut candidate = self.head[Self::hash(data, pos)];
+        let mut depth = 0;
+        while candidate != NO_POSITION && depth < MAX_CHAIN_DEPTH {
+            let start = candidate as usize;
+            let distance = pos - start;
+            if distance > WINDOW_SIZE {
+                break;
+            }
+            let mut length = 0;
+            while length < available && data[start + length] == data[pos + length] {
+                length += 1;
+            }
+            if length > best.0 {
+                best = (length, distance);
+                if length == available {
+                    break;
+                }
+            }
+            candidate = self.prev[start];
+            depth += 1;
+        }
+        best
+    }
+}
+
+fn write_block(writer: &mut BitWriter, block: &[Command]) {
+    let mut command_freq = [0u32; NUM_COMMANDS];
+    let mut offset_freq = [0u32; NUM_OFFSETS];
+    for &command in block {
+        command_freq[command.code()] += 1;
+        if let Command::Match { distance, .. } = command {
+            offset_freq[offset_code(distance)] += 1;
+        }
+    }
+
+    let command_lengths = huffman_lengths(&command_freq);
+    let offset_lengths = huffman_lengths(&offset_freq);
+
+    writer.put_bits(block.len() as u32, 16);
+
+    // Command tree, transmitted through the temporary code-length tree
+    let command_codes = match single_symbol(&command_freq) {
+        Some(symbol) => {
+            writer.put_bits(0, TEMP_BITS);
+            writer.put_bits(0, TEMP_BITS);
+            writer.put_bits(0, COMMAND_BITS);
+            writer.put_bits(symbol as u32, COMMAND_BITS);
+            None
+        }
+        None => {
+            write_command_lengths(writer, &command_lengths);
+            Some(canonical_codes(&command_lengths))
+        }
+    };
+
+    // Offset tree
+    let offset_codes = match single_symbol(&offset_freq) {
+        Some(symbol) => {
+            writer.put_bits(0, OFFSET_BITS);
+            writer.put_bits(symbol as u32, OFFSET_BITS);
+            None
+        }
+        None if offset_freq.iter().all(|&f| f == 0) => {
+            writer.put_bits(0, OFFSET_BITS);
+            writer.put_bits(0, OFFSET_BITS);
+            None
+        }
+        None => {
+            let count = used_length(&offset_lengths);
+            writer.put_bits(count as u32, OFFSET_BITS);
+            for &length in &offset_lengths[..count] {
+                write_code_length(writer, length);
+            }
+            Some(canonical_codes(&offset_lengths))
+        }
+    };
+
+    for &command in block {
+        let code = command.code();
+        if let Some(codes) = &command_codes {
+            writer.put_bits(codes[code], command_lengths[code] as u32);
+        }
+        if let Command::Match { distance, .. } = command {
+            let symbol = offset_code(distance);
+            if let Some(codes) = &offset_codes {
+                writer.put_bits(codes[symbol], offset_lengths[symbol] as u32);
+            }
+            if symbol > 1 {
+                let extra_bits = symbol as u32 - 1;
+                writer.put_bits((distance - 1) as u32 & ((1 << extra_bits) - 1), extra_bits);
+            }
+        }
+    }
+}
+
+/// Code-length symbols for the temporary tree: 0-2 encode zero runs, 3+ encode `length + 2`
+fn write_command_lengths(writer: &mut BitWriter, lengths: &[u8]) {
+    let count = used_length(lengths);
+
+    // (temp symbol, extra bits, extra bit count)
+    let mut symbols: Vec<(usize, u32, u32)> = Vec::new();
+    let mut i = 0;
+    while i < count {
+        if lengths[i] != 0 {
+            symbols.push((lengths[i] as usize + 2, 0, 0));
+            i += 1;
+            continue;
+        }
+        let run = lengths[i..count].iter().take_while(|&&l| l == 0).count();
+        match run {
+            1 | 2 => symbols.extend(std::iter::repeat_n((0, 0, 0), run)),
+            3..=18 => symbols.push((1, run as u32 - 3, 4)),
+            19 => {
+                symbols.push((0, 0, 0));
+                symbols.push((1, 15, 4));
+            }
+            _ => symbols.push((2, run as u32 - 20, COMMAND_BITS)),
+        }
+        i += run;
+    }
+
+    let mut temp_freq = [0u32; NUM_TEMP_CODES];
+    for &(symbol, _, _) in &symbols {
+        temp_freq[symbol] += 1;
+    }
+
+    let temp_codes = match single_symbol(&temp_freq) {
+        Some(symbol) => {
+            writer.put_bits(0, TEMP_BITS);
+            writer.put_bits(symbol as u32, TEMP_BITS);
+            None
+        }
+        None => {
+            let temp_lengths = huffman_lengths(&temp_freq);
+            // Readers always expect the 2-bit zero-run field after the third length
+            let temp_count = used_length(&temp_lengths).max(3);
+            writer.put_bits(temp_count as u32, TEMP_BITS);
+            let mut j = 0;
+            while j < temp_count {
+                write_code_length(writer, temp_lengths[j]);
+                j += 1;
+                if j == 3 {
+                    let skip = temp_lengths[3..temp_count.min(6)]
+                        .iter()
+                        .take_while(|&&l| l == 0)
+                        .count();
+                    writer.put_bits(skip as u32, 2);
+                    j += skip;
+                }
+            }
+            Some((canonical_codes(&temp_lengths), temp_lengths))
+        }
+    };
+
+    writer.put_bits(count as u32, COMMAND_BITS);
+    for (symbol, extra, extra_bits) in symbols {
+        if let Some((codes, temp_lengths)) = &temp_codes {
+            writer.put_bits(codes[symbol], temp_lengths[symbol] as u32);
+        }
+        writer.put_bits(extra, extra_bits);
+    }
+}
+
+/// Code lengths 0-6 use three bits; longer ones continue in unary
+fn write_code_length(writer: &mut BitWriter, length: u8) {
+    if length < 7 {
+        writer.put_bits(length as u32, 3);
+    } else {
+        writer.put_bits(7, 3);
+        for _ in 7..length {
+            writer.put_bits(1, 1);
+        }
+        writer.put_bits(0, 1);
+    }
+}
+
+/// Number of entries up to and including the last non-zero length
+fn used_length(lengths: &[u8]) -> usize {
+    lengths.iter().rposition(|&l| l != 0).map_or(0, |i| i + 1)
+}
+
+/// Return the symbol if exactly one symbol has a non-zero frequency
+fn single_symbol(freq: &[u32]) -> Option<usize> {
+    let mut used = freq.iter().enumerate().filter(|(_, f)| **f != 0);
+    match (used.next(), used.next()) {
+        (Some((symbol, _)), None) => Some(symbol),
+        _ => None,
+    }
+}
+
+/// Huffman code lengths limited to [`MAX_CODE_LENGTH`]
+///
+/// Frequencies are flattened and the tree rebuilt until it fits the limit.
+fn huffman_lengths(freq: &[u32]) -> Vec<u8> {
+    let mut freq = freq.to_vec();
+    loop {
+        let lengths = unlimited_huffman_lengths(&freq);
+        if lengths.iter().all(|&l| l <= MAX_CODE_LENGTH) {
+            return lengths;
+        }
+        for f in freq.iter_mut().filter(|f| **f != 0) {
+            *f = (*f >> 1) | 1;
+        }
+    }
+}
+
+fn unlimited_huffman_lengths(freq: &[u32]) -> Vec<u8> {
+    use std::cmp::Reverse;
+    use std::collections::BinaryHeap;
+
+    let mut lengths = vec![0u8; freq.len()];
+    let mut parent: Vec<usize> = Vec::new();
+    let mut heap = BinaryHeap::new();
+    let mut leaves = Vec::new();
+
+    for (symbol, &f) in freq.iter().enumerate() {
+        if f != 0 {
+            heap.push(Reverse((f as u64, parent.len())));
+            leaves.push((symbol, parent.len()));
+            parent.push(usize::MAX);
+        }
+    }
+    if leaves.len() < 2 {
+        for (symbol, _) in leaves {
+            lengths[symbol] = 1;
+        }
+        return lengths;
+    }
+
+    while let (Some(Reverse((fa, a))), Some(Reverse((fb, b)))) = (heap.pop(), heap.pop()) {
+        let node = parent.len();
+        parent.push(usize::MAX);
+        parent[a] = node;
+        parent[b] = node;
+        heap.push(Reverse((fa + fb, node)));
+    }
+
+    for (symbol, mut node) in leaves {
+        let mut depth = 0u8;
+        while parent[node] != usize::MAX {
+            node = parent[node];
+            depth = depth.saturating_add(1);
+        }
+        lengths[symbol] = depth;
+    }
+    lengths
+}
+
+/// Canonical codes: shorter codes first, ties broken by symbol order
+fn canonical_codes(lengths: &[u8]) -> Vec<u32> {
+    let mut count = [0u32; MAX_CODE_LENGTH as usize + 1];
+    for &l in lengths {
+        count[l as usize] += 1;
+    }
+    count[0] = 0;
+
+    let mut next = [0u32; MAX_CODE_LENGTH as usize + 2];
+    let mut code = 0u32;
+    for bits in 1..=MAX_CODE_LENGTH as usize {
+        code = (code + count[bits - 1]) << 1;
+        next[bits] = code;
+    }
+
+    lengths
+        .iter()
+        .map(|&l| {
+            if l == 0 {
+                0
+            } else {
+                let code = next[l as usize];
+                next[l as usize] += 1;
+                code
+            }
+        })
+        .collect()
+}
+
+/// MSB-first bit writer
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    fn put_bits(&mut self, value: u32, count: u32) {
+        for bit in (0..count).rev() {
+            self.buffer = (self.buffer << 1) | ((value >> bit) & 1);
+            self.bit_count += 1;
+            if self.bit_count == 8 {
+                self.bytes.push(self.buffer as u8);
+                self.buffer = 0;
+                self.bit_count = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bit_count > 0 {
+            self.bytes.push((self.buffer << (8 - self.bit_count)) as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(data: &[u8]) -> Vec<u8> {
+        let archive = compress_lha("test.ym", data).unwrap();
+        assert!(super::super::is_lha_compressed(&archive));
+        assert_eq!(super::super::decompress_if_needed(&archive).unwrap(), data);
+
+        let mut reader = delharc::LhaDecodeReader::new(archive.as_slice()).unwrap();
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        assert!(reader.crc_is_ok());
+        archive
+    }
+
+    /// Deterministic pseudo-random bytes (LCG)
+    fn noise(len: usize, mut seed: u32) -> Vec<u8> {
+        (0..len)
+            .map(|_| {
+                seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                (seed >> 16) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_crc16_arc() {
+        assert_eq!(crc16(b"123456789"), 0xBB3D);
+    }
+
+    #[test]
+    fn test_lha_round_trip_tiny_inputs() {
+        round_trip(b"Y");
+        round_trip(b"YM");
+        round_trip(b"YMYMYMYMYMYM");
+    }
+
+    #[test]
+    fn test_lha_round_trip_compresses_register_dump() {
+        let mut data = b"YM6!LeOnArD!".to_vec();
+        for frame in 0..3000u32 {
+            data.extend_from_slice(&[(frame % 64) as u8, 1, 0x20, 0, 0, 2, 0, 0x38, 15, 12, 10]);
+        }
+        let archive = round_trip(&data);
+        assert!(archive.len() < data.len() / 10);
+    }
+
+    #[test]
+    fn test_lha_round_trip_noise_and_multiple_blocks() {
+        round_trip(&noise(20_000, 3));
+
+        // More than one block of commands, with long runs and far matches
+        let mut data = noise(70_000, 9);
+        data.extend(std::iter::repeat_n(0xAA, 40_000));
+        data.extend_from_within(1000..9000);
+        round_trip(&data);
+    }
+
+    #[test]
+    fn test_lha_rejects_long_names() {
+        let name = "x".repeat(300);
+        assert!(compress_lha(&name, b"data").is_err());
+    }
+}
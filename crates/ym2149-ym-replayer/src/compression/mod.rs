@@ -2,7 +2,8 @@
 //!
 //! Automatically detects and decompresses LHA-compressed YM files using the `delharc` crate.
 //! Most YM files in the wild are compressed with LHA (Lossless Hamming Archive),
-//! typically using the LH5 algorithm.
+//! typically using the LH5 algorithm. ZIP collections are handled by the [`zip`]
+//! submodule, and [`lha`] writes `-lh5-` archives for YM export.
 //!
 //! Decompression is transparent - simply load any YM file, and this module handles
 //! compression automatically. Uncompressed files pass through unchanged.
@@ -19,6 +20,12 @@
 //! - **Backward Compatibility**: Uncompressed files work unchanged
 //! - **Robustness**: Errors provide clear guidance for troubleshooting
 
+pub mod lha;
+pub mod zip;
+
+pub use lha::compress_lha;
+pub use zip::{extract_zip_entry, is_zip_archive, zip_entry_names};
+
 use crate::Result;
 use std::io::Read;
 #[cfg(not(target_arch = "wasm32"))]
@@ -50,6 +57,8 @@ const LHA_MAX_VALID_LEVEL: u8 = b'7';
 ///
 /// This function provides **transparent decompression**:
 /// - If data is LHA-compressed: extracts the first file from the archive
+/// - If data is a ZIP archive: extracts the first `.ym` entry (or the only entry),
+///   decompressing it further if it is LHA-packed
 /// - If data is uncompressed: returns a copy unchanged
 ///
 /// Includes safety guards against decompression bombs (enforces size limits).
@@ -70,6 +79,17 @@ const LHA_MAX_VALID_LEVEL: u8 = b'7';
 /// // Works with both compressed (LH5) and uncompressed files
 /// ```
 pub fn decompress_if_needed(data: &[u8]) -> Result<Vec<u8>> {
+    if is_zip_archive(data) {
+        let entry = zip::default_ym_entry(data)?;
+        let contents = extract_zip_entry(data, &entry)?;
+        // Entries of YM collections are usually LHA-packed themselves
+        return if is_zip_archive(&contents) {
+            Ok(contents)
+        } else {
+            decompress_if_needed(&contents)
+        };
+    }
+
     if !is_lha_compressed(data) {
         // Not compressed - return copy of original data
         return Ok(data.to_vec());
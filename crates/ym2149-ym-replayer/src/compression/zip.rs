@@ -0,0 +1,168 @@
+//! ZIP archive support for song collections
+//!
+//! Many chiptune dumps ship as `.zip` collections. This module lets loaders
+//! open such archives directly: entries can be listed (for playlists) and
+//! extracted by name, and [`super::decompress_if_needed`] transparently picks
+//! the first YM entry when handed a whole archive.
+//!
+//! Arkos Tracker `.aks` files are themselves ZIP archives containing a single
+//! XML document; they still work because the extracted entry is handed back
+//! to the normal format detection.
+
+use crate::{ReplayerError, Result};
+use std::io::{Cursor, Read};
+
+use super::MAX_DECOMPRESSED_SIZE;
+
+/// Local file header signature (`PK\x03\x04`)
+const ZIP_LOCAL_HEADER_MAGIC: &[u8; 4] = b"PK\x03\x04";
+
+/// End-of-central-directory signature (`PK\x05\x06`), used by empty archives
+const ZIP_EMPTY_ARCHIVE_MAGIC: &[u8; 4] = b"PK\x05\x06";
+
+/// Detect if data is a ZIP archive by checking magic bytes
+pub fn is_zip_archive(data: &[u8]) -> bool {
+    data.starts_with(ZIP_LOCAL_HEADER_MAGIC) || data.starts_with(ZIP_EMPTY_ARCHIVE_MAGIC)
+}
+
+/// List the file entries of a ZIP archive in archive order
+///
+/// Directory entries are skipped. Names use `/` as separator, exactly as
+/// stored in the archive, and can be passed to [`extract_zip_entry`].
+pub fn zip_entry_names(data: &[u8]) -> Result<Vec<String>> {
+    let archive = open_archive(data)?;
+    Ok((0..archive.len())
+        .filter_map(|index| archive.name_for_index(index))
+        .filter(|name| !name.ends_with('/'))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Extract a single entry from a ZIP archive by name
+///
+/// Includes the same decompression-bomb guard as LHA decompression.
+pub fn extract_zip_entry(data: &[u8], name: &str) -> Result<Vec<u8>> {
+    let mut archive = open_archive(data)?;
+    let entry = archive.by_name(name).map_err(|e| {
+        ReplayerError::DecompressionError(format!("ZIP entry '{name}' not found: {e}"))
+    })?;
+
+    let mut contents = Vec::new();
+    entry
+        .take(MAX_DECOMPRESSED_SIZE as u64)
+        .read_to_end(&mut contents)
+        .map_err(|e| {
+            ReplayerError::DecompressionError(format!("Failed to extract ZIP entry '{name}': {e}"))
+        })?;
+
+    if contents.len() >= MAX_DECOMPRESSED_SIZE {
+        return Err(ReplayerError::DecompressionError(format!(
+            "ZIP entry '{name}' exceeded maximum safe size (100MB)"
+        )));
+    }
+
+    Ok(contents)
+}
+
+/// Pick the entry [`super::decompress_if_needed`] should use for a YM loader
+///
+/// Prefers the first `.ym` entry; single-file archives fall back to their
+/// only entry regardless of its name.
+pub(super) fn default_ym_entry(data: &[u8]) -> Result<String> {
+    let names = zip_entry_names(data)?;
+    names
+        .iter()
+        .find(|name| name.to_ascii_lowercase().ends_with(".ym"))
+        .or_else(|| {
+            if names.len() == 1 {
+                names.first()
+            } else {
+                None
+            }
+        })
+        .cloned()
+        .ok_or_else(|| {
+            ReplayerError::DecompressionError(format!(
+                "ZIP archive contains no YM file ({} entries)",
+                names.len()
+            ))
+        })
+}
+
+fn open_archive(data: &[u8]) -> Result<zip::ZipArchive<Cursor<&[u8]>>> {
+    zip::ZipArchive::new(Cursor::new(data))
+        .map_err(|e| ReplayerError::DecompressionError(format!("Failed to read ZIP archive: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            if name.ends_with('/') {
+                writer
+                    .add_directory(*name, SimpleFileOptions::default())
+                    .unwrap();
+            } else {
+                writer
+                    .start_file(*name, SimpleFileOptions::default())
+                    .unwrap();
+                writer.write_all(contents).unwrap();
+            }
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_zip_magic_detection() {
+        let archive = build_zip(&[("a.ym", b"YM3!")]);
+        assert!(is_zip_archive(&archive));
+        assert!(!is_zip_archive(b"YM6!LeOnArD!"));
+        assert!(!is_zip_archive(b"PK"));
+    }
+
+    #[test]
+    fn test_zip_entry_names_preserve_order_and_skip_dirs() {
+        let archive = build_zip(&[
+            ("songs/", b""),
+            ("songs/zeta.sndh", b"SNDH"),
+            ("songs/alpha.ym", b"YM3!"),
+            ("readme.txt", b"hello"),
+        ]);
+        let names = zip_entry_names(&archive).unwrap();
+        assert_eq!(names, ["songs/zeta.sndh", "songs/alpha.ym", "readme.txt"]);
+    }
+
+    #[test]
+    fn test_extract_zip_entry() {
+        let archive = build_zip(&[("a.txt", b"first"), ("b.ym", b"YM3!payload")]);
+        assert_eq!(extract_zip_entry(&archive, "b.ym").unwrap(), b"YM3!payload");
+        assert!(extract_zip_entry(&archive, "missing.ym").is_err());
+    }
+
+    #[test]
+    fn test_default_ym_entry_selection() {
+        let archive = build_zip(&[("info.txt", b"x"), ("Song.YM", b"YM3!")]);
+        assert_eq!(default_ym_entry(&archive).unwrap(), "Song.YM");
+
+        let single = build_zip(&[("track.bin", b"YM3!")]);
+        assert_eq!(default_ym_entry(&single).unwrap(), "track.bin");
+
+        let none = build_zip(&[("a.sndh", b"x"), ("b.ay", b"y")]);
+        assert!(default_ym_entry(&none).is_err());
+    }
+
+    #[test]
+    fn test_decompress_if_needed_extracts_ym_entry() {
+        let mut frame_data = b"YM3!".to_vec();
+        frame_data.extend_from_slice(&[0u8; 14]);
+        let archive = build_zip(&[("notes.txt", b"x"), ("tune.ym", &frame_data)]);
+
+        let data = super::super::decompress_if_needed(&archive).unwrap();
+        assert_eq!(data, frame_data);
+    }
+}
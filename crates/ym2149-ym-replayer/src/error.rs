@@ -25,6 +25,18 @@ pub enum ReplayerError {
     #[error("Invalid configuration: {0}")]
     ConfigError(String),
 
+    /// Data is not in a supported YM format (holds the detected magic or version)
+    #[error("Unsupported format: {0}")]
+    UnsupportedFormat(String),
+
+    /// Operation does not apply to YM Tracker (YMT1/YMT2) songs
+    #[error("{0} is not supported for YM Tracker songs")]
+    TrackerModeUnsupported(&'static str),
+
+    /// Audio output device could not be opened or went away
+    #[error("Audio device lost: {0}")]
+    DeviceLost(String),
+
     /// Generic error
     #[error("{0}")]
     Other(String),
@@ -0,0 +1,117 @@
+//! FLAC export functionality
+
+use super::{ExportConfig, ExportFormat, SongTags, export_player};
+use crate::{LoadSummary, Result, YmPlayer};
+use flacenc::bitsink::ByteSink;
+use flacenc::component::{BitRepr, MetadataBlockData};
+use flacenc::error::Verify;
+use flacenc::source::MemSource;
+use std::io::Write;
+use std::path::Path;
+
+/// FLAC metadata block type of a Vorbis comment
+const VORBIS_COMMENT_BLOCK: u8 = 4;
+
+/// Export YM playback to a FLAC file
+///
+/// # Examples
+///
+/// ```no_run
+/// use ym2149_ym_replayer::export::export_to_flac;
+/// use ym2149_ym_replayer::load_song;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("song.ym")?;
+/// let (mut player, info) = load_song(&data)?;
+///
+/// export_to_flac(&mut player, info, "output.flac")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_to_flac<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    info: LoadSummary,
+    output_path: P,
+) -> Result<()> {
+    export_to_flac_with_config(player, output_path, info, ExportConfig::default())
+}
+
+/// Export YM playback to a FLAC file with custom configuration
+///
+/// `config.sample_rate` is ignored; the file is encoded at the player's rate.
+/// The song title, author and comment are written as Vorbis comment tags.
+pub fn export_to_flac_with_config<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    output_path: P,
+    info: LoadSummary,
+    config: ExportConfig,
+) -> Result<()> {
+    export_player(
+        player,
+        &info,
+        output_path.as_ref(),
+        ExportFormat::Flac,
+        &config,
+    )
+}
+
+/// Encode mono samples as a 16-bit FLAC stream into `sink`
+///
+/// With `channels == 2` the mono signal is duplicated to both channels.
+pub(super) fn write_flac<W: Write>(
+    mut sink: W,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    tags: &SongTags,
+) -> Result<W> {
+    let channel_count = if channels == 2 { 2 } else { 1 };
+    let pcm: Vec<i32> = samples
+        .iter()
+        .flat_map(|&sample| {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i32;
+            std::iter::repeat_n(value, channel_count)
+        })
+        .collect();
+
+    let config = flacenc::config::Encoder::default()
+        .into_verified()
+        .map_err(|(_, e)| format!("Invalid FLAC encoder config: {e}"))?;
+    let source = MemSource::from_samples(&pcm, channel_count, 16, sample_rate as usize);
+    let mut stream = flacenc::encode_with_fixed_block_size(&config, source, config.block_size)
+        .map_err(|e| format!("Failed to encode FLAC stream: {e}"))?;
+
+    let comments =
+        MetadataBlockData::new_unknown(VORBIS_COMMENT_BLOCK, &tags.vorbis_comment_block())
+            .map_err(|e| format!("Invalid FLAC metadata: {e}"))?;
+    stream.add_metadata_block(comments);
+
+    let mut bytes = ByteSink::new();
+    stream
+        .write(&mut bytes)
+        .map_err(|e| format!("Failed to write FLAC stream: {e}"))?;
+    sink.write_all(bytes.as_slice())?;
+    Ok(sink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_flac_stream() {
+        let samples: Vec<f32> = (0..44_100)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 44_100.0).sin() * 0.5)
+            .collect();
+        let tags = SongTags {
+            title: "Sine".into(),
+            ..Default::default()
+        };
+        let data = write_flac(Vec::new(), &samples, 44_100, 2, &tags).unwrap();
+
+        assert!(data.starts_with(b"fLaC"));
+        assert!(data.windows(10).any(|w| w == b"TITLE=Sine"));
+        // Lossless, but well below the 176 KB of 16-bit stereo PCM
+        assert!(data.len() < 120_000);
+    }
+}
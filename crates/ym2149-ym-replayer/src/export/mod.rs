@@ -1,30 +1,341 @@
 //! Audio export functionality for YM2149 playback
 //!
 //! This module provides utilities to export YM file playback to various audio formats:
-//! - WAV (uncompressed PCM)
+//! - WAV (uncompressed PCM, feature `export-wav`)
+//! - FLAC (lossless, feature `export-flac`)
+//! - Ogg Opus (feature `export-opus`)
+//! - Ogg Vorbis (feature `export-vorbis`)
+//!
+//! Every export is tagged with the song title, author, comment and year: WAV
+//! files get a RIFF `INFO` chunk, FLAC and Ogg files Vorbis comments (see
+//! [`SongTags`]). [`export_samples`] encodes samples rendered by any player,
+//! e.g. AKS, AY or SNDH songs.
 //!
 //! # Examples
 //!
 //! ## Export to WAV
 //!
 //! ```no_run
-//! use ym2149::export::export_to_wav;
-//! use ym2149::replayer::load_song;
-//!
+//! # #[cfg(feature = "export-wav")]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use ym2149_ym_replayer::export::export_to_wav;
+//! use ym2149_ym_replayer::load_song;
+//!
 //! let data = std::fs::read("song.ym")?;
-//! let (mut player, _) = load_song(&data)?;
+//! let (mut player, info) = load_song(&data)?;
 //!
-//! export_to_wav(&mut player, "output.wav")?;
+//! export_to_wav(&mut player, info, "output.wav")?;
 //! # Ok(())
 //! # }
+//! # #[cfg(not(feature = "export-wav"))]
+//! # fn main() {}
 //! ```
 //!
+//! ## Export to Opus
+//!
+//! Opus encodes at 48 kHz; songs rendered at other rates are resampled, so
+//! loading the song at 48 kHz avoids that step:
+//!
+//! ```no_run
+//! # #[cfg(feature = "export-opus")]
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use ym2149_ym_replayer::export::export_to_opus;
+//! use ym2149_ym_replayer::load_song_with_rate;
+//!
+//! let data = std::fs::read("song.ym")?;
+//! let (mut player, info) = load_song_with_rate(&data, 48_000)?;
+//!
+//! export_to_opus(&mut player, info, "output.opus")?;
+//! # Ok(())
+//! # }
+//! # #[cfg(not(feature = "export-opus"))]
+//! # fn main() {}
+//! ```
+
 #[cfg(feature = "export-wav")]
 mod wav;
 #[cfg(feature = "export-wav")]
 pub use wav::*;
 
+#[cfg(feature = "export-flac")]
+mod flac;
+#[cfg(feature = "export-flac")]
+pub use flac::*;
+
+#[cfg(feature = "export-opus")]
+mod opus;
+#[cfg(feature = "export-opus")]
+pub use opus::*;
+
+#[cfg(feature = "export-vorbis")]
+mod vorbis;
+#[cfg(feature = "export-vorbis")]
+pub use vorbis::*;
+
+#[cfg(any(
+    feature = "export-flac",
+    feature = "export-opus",
+    feature = "export-vorbis"
+))]
+use crate::PlaybackController;
+use crate::{LoadSummary, Result, YmPlayer};
+use std::path::Path;
+
+/// Serial number of the logical stream in exported Ogg files
+#[cfg(any(feature = "export-opus", feature = "export-vorbis"))]
+const OGG_STREAM_SERIAL: u32 = 0x5945_3231;
+
+/// Vendor string written into Vorbis comment headers
+#[cfg(any(feature = "export-flac", feature = "export-opus"))]
+const VENDOR: &str = concat!("ym2149-rs ", env!("CARGO_PKG_VERSION"));
+
+/// Audio file format for [`export_samples`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// 16-bit PCM WAV
+    #[cfg(feature = "export-wav")]
+    Wav,
+    /// 16-bit FLAC (lossless)
+    #[cfg(feature = "export-flac")]
+    Flac,
+    /// Ogg Opus
+    #[cfg(feature = "export-opus")]
+    Opus,
+    /// Ogg Vorbis
+    #[cfg(feature = "export-vorbis")]
+    Vorbis,
+}
+
+impl ExportFormat {
+    /// Parse a format name or file extension (`wav`, `flac`, `opus`, `ogg`/`vorbis`)
+    ///
+    /// Returns `None` for unknown names and formats whose feature is disabled.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "export-wav")]
+            "wav" => Some(Self::Wav),
+            #[cfg(feature = "export-flac")]
+            "flac" => Some(Self::Flac),
+            #[cfg(feature = "export-opus")]
+            "opus" => Some(Self::Opus),
+            #[cfg(feature = "export-vorbis")]
+            "ogg" | "vorbis" => Some(Self::Vorbis),
+            _ => None,
+        }
+    }
+
+    /// File extension for this format (without the dot)
+    pub fn extension(self) -> &'static str {
+        match self {
+            #[cfg(feature = "export-wav")]
+            Self::Wav => "wav",
+            #[cfg(feature = "export-flac")]
+            Self::Flac => "flac",
+            #[cfg(feature = "export-opus")]
+            Self::Opus => "opus",
+            #[cfg(feature = "export-vorbis")]
+            Self::Vorbis => "ogg",
+        }
+    }
+}
+
+/// Song metadata written into exported files
+///
+/// FLAC, Opus and Vorbis store it as Vorbis comments (`TITLE`, `ARTIST`,
+/// `COMMENT`, `DATE`), WAV as RIFF `INFO` entries (`INAM`, `IART`, `ICMT`,
+/// `ICRD`). Empty fields are left out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SongTags {
+    /// Song title
+    pub title: String,
+    /// Author or composer
+    pub artist: String,
+    /// Free-form comment
+    pub comment: String,
+    /// Release year (or full date)
+    pub year: String,
+}
+
+impl SongTags {
+    /// Tags from the song info of a loaded YM file
+    ///
+    /// YM headers have no release date, so `year` stays empty.
+    pub fn from_player(player: &YmPlayer) -> Self {
+        player
+            .info()
+            .map(|info| Self {
+                title: info.song_name.clone(),
+                artist: info.author.clone(),
+                comment: info.comment.clone(),
+                year: String::new(),
+            })
+            .unwrap_or_default()
+    }
+
+    /// Vorbis comment fields, leaving out empty ones
+    #[cfg(any(
+        feature = "export-flac",
+        feature = "export-opus",
+        feature = "export-vorbis"
+    ))]
+    fn vorbis_comments(&self) -> Vec<(&'static str, &str)> {
+        [
+            ("TITLE", self.title.as_str()),
+            ("ARTIST", self.artist.as_str()),
+            ("COMMENT", self.comment.as_str()),
+            ("DATE", self.year.as_str()),
+        ]
+        .into_iter()
+        .map(|(key, value)| (key, value.trim()))
+        .filter(|(_, value)| !value.is_empty())
+        .collect()
+    }
+
+    /// Vorbis comment header body: vendor string and `KEY=value` fields, all
+    /// lengths little-endian (shared by the FLAC and Opus headers)
+    #[cfg(any(feature = "export-flac", feature = "export-opus"))]
+    fn vorbis_comment_block(&self) -> Vec<u8> {
+        let comments = self.vorbis_comments();
+        let mut block = Vec::new();
+        block.extend_from_slice(&(VENDOR.len() as u32).to_le_bytes());
+        block.extend_from_slice(VENDOR.as_bytes());
+        block.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for (key, value) in comments {
+            let comment = format!("{key}={value}");
+            block.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            block.extend_from_slice(comment.as_bytes());
+        }
+        block
+    }
+}
+
+/// Encode rendered mono samples to a file
+///
+/// Normalization and fade out from `config` are applied to `samples` in
+/// place; `config.sample_rate` is ignored in favour of `sample_rate`. This is
+/// the entry point for songs rendered by other players (AKS, AY, SNDH).
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "export-wav")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use ym2149_ym_replayer::export::{ExportConfig, ExportFormat, SongTags, export_samples};
+///
+/// let mut samples = vec![0.0f32; 44_100];
+/// let tags = SongTags {
+///     title: "Silence".into(),
+///     ..Default::default()
+/// };
+/// export_samples(
+///     "silence.wav",
+///     &mut samples,
+///     44_100,
+///     ExportFormat::Wav,
+///     &ExportConfig::default(),
+///     &tags,
+/// )?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "export-wav"))]
+/// # fn main() {}
+/// ```
+pub fn export_samples<P: AsRef<Path>>(
+    output_path: P,
+    samples: &mut [f32],
+    sample_rate: u32,
+    format: ExportFormat,
+    config: &ExportConfig,
+    tags: &SongTags,
+) -> Result<()> {
+    if config.normalize {
+        normalize_samples(samples);
+    }
+    apply_fade_out(samples, config.fade_out_duration, sample_rate);
+
+    let path = output_path.as_ref();
+    match format {
+        #[cfg(feature = "export-wav")]
+        ExportFormat::Wav => {
+            wav::write_wav_samples(path, samples, sample_rate, config.channels, tags)
+        }
+        #[cfg(feature = "export-flac")]
+        ExportFormat::Flac => write_file(path, |sink| {
+            flac::write_flac(sink, samples, sample_rate, config.channels, tags)
+        }),
+        #[cfg(feature = "export-opus")]
+        ExportFormat::Opus => write_file(path, |sink| {
+            opus::write_opus(
+                sink,
+                samples,
+                sample_rate,
+                config.channels,
+                config.bitrate,
+                tags,
+            )
+        }),
+        #[cfg(feature = "export-vorbis")]
+        ExportFormat::Vorbis => write_file(path, |sink| {
+            vorbis::write_vorbis(
+                sink,
+                samples,
+                sample_rate,
+                config.channels,
+                config.bitrate,
+                tags,
+            )
+        }),
+    }
+}
+
+/// Create `path` and hand a buffered writer to `encode`
+#[cfg(any(
+    feature = "export-flac",
+    feature = "export-opus",
+    feature = "export-vorbis"
+))]
+fn write_file(
+    path: &Path,
+    encode: impl FnOnce(std::io::BufWriter<std::fs::File>) -> Result<std::io::BufWriter<std::fs::File>>,
+) -> Result<()> {
+    use std::io::Write;
+
+    let file = std::fs::File::create(path)?;
+    let mut sink = encode(std::io::BufWriter::new(file))?;
+    sink.flush()?;
+    Ok(())
+}
+
+/// Render the whole song at the player's sample rate and encode it
+#[cfg(any(
+    feature = "export-flac",
+    feature = "export-opus",
+    feature = "export-vorbis"
+))]
+fn export_player(
+    player: &mut YmPlayer,
+    info: &LoadSummary,
+    output_path: &Path,
+    format: ExportFormat,
+    config: &ExportConfig,
+) -> Result<()> {
+    use ym2149_common::ChiptunePlayerBase;
+
+    PlaybackController::play(player)?;
+    let sample_rate = ChiptunePlayerBase::sample_rate(player);
+    let length = ym_render_length(player, info, config, sample_rate);
+    let mut samples = player.generate_samples(length);
+    let tags = SongTags::from_player(player);
+    export_samples(
+        output_path,
+        &mut samples,
+        sample_rate,
+        format,
+        config,
+        &tags,
+    )
+}
+
 /// Export configuration options
 #[derive(Debug, Clone)]
 pub struct ExportConfig {
@@ -36,6 +347,14 @@ pub struct ExportConfig {
     pub normalize: bool,
     /// Fade out duration in seconds (0 = no fade)
     pub fade_out_duration: f32,
+    /// Passes through the loop section of looping songs (None = render the
+    /// song length once)
+    ///
+    /// With a loop count the fade out is rendered as an extra tail after the
+    /// last pass instead of fading the end of the song.
+    pub loop_count: Option<u32>,
+    /// Target bitrate in bits per second for lossy formats (Opus, Vorbis)
+    pub bitrate: u32,
 }
 
 impl Default for ExportConfig {
@@ -45,6 +364,8 @@ impl Default for ExportConfig {
             channels: 1,
             normalize: true,
             fade_out_duration: 0.0,
+            loop_count: None,
+            bitrate: 96_000,
         }
     }
 }
@@ -77,6 +398,60 @@ impl ExportConfig {
         self.fade_out_duration = duration_seconds;
         self
     }
+
+    /// Set the target bitrate for lossy formats
+    pub fn bitrate(mut self, bits_per_second: u32) -> Self {
+        self.bitrate = bits_per_second;
+        self
+    }
+
+    /// Render the intro, `count` passes of the loop section and the fade out
+    /// tail (at least one pass)
+    pub fn loops(mut self, count: u32) -> Self {
+        self.loop_count = Some(count.max(1));
+        self
+    }
+
+    /// Number of samples to render for a song
+    ///
+    /// `total_samples` is one play through the song and `loop_start` the
+    /// sample it loops back to (`None` if the song does not loop). Without a
+    /// loop count or loop point this is `total_samples`; otherwise the intro,
+    /// [`loop_count`](Self::loop_count) loop passes and a tail of
+    /// `fade_out_duration` seconds.
+    pub fn render_length(
+        &self,
+        total_samples: usize,
+        loop_start: Option<usize>,
+        sample_rate: u32,
+    ) -> usize {
+        match (self.loop_count, loop_start) {
+            (Some(count), Some(loop_start)) if loop_start < total_samples => {
+                let loop_length = total_samples - loop_start;
+                let tail = (self.fade_out_duration.max(0.0) * sample_rate as f32) as usize;
+                loop_start
+                    .saturating_add(loop_length.saturating_mul(count.max(1) as usize))
+                    .saturating_add(tail)
+            }
+            _ => total_samples,
+        }
+    }
+}
+
+/// Samples to render for a loaded YM song, honouring its loop frame
+fn ym_render_length(
+    player: &YmPlayer,
+    info: &LoadSummary,
+    config: &ExportConfig,
+    sample_rate: u32,
+) -> usize {
+    use ym2149_common::{ChiptunePlayer, MetadataFields};
+
+    let samples_per_frame = info.samples_per_frame as usize;
+    let loop_start = ChiptunePlayer::metadata(player)
+        .loop_frame()
+        .map(|frame| frame.saturating_mul(samples_per_frame));
+    config.render_length(info.total_samples(), loop_start, sample_rate)
 }
 
 /// Apply normalization to audio samples
@@ -107,8 +482,9 @@ fn apply_fade_out(samples: &mut [f32], fade_duration: f32, sample_rate: u32) {
         return;
     }
 
-    let fade_samples = (fade_duration * sample_rate as f32) as usize;
-    let start_fade = samples.len().saturating_sub(fade_samples);
+    // A fade longer than the song spans the whole song, still ending silent
+    let fade_samples = ((fade_duration * sample_rate as f32) as usize).min(samples.len());
+    let start_fade = samples.len() - fade_samples;
 
     for (i, sample) in samples.iter_mut().enumerate().skip(start_fade) {
         let progress = (i - start_fade) as f32 / fade_samples as f32;
@@ -149,5 +525,47 @@ mod tests {
         assert_eq!(config.channels, 2);
         assert!(!config.normalize);
         assert_eq!(config.fade_out_duration, 2.0);
+        assert_eq!(ExportConfig::default().bitrate(64_000).bitrate, 64_000);
+        assert_eq!(ExportConfig::default().loops(0).loop_count, Some(1));
+    }
+
+    #[test]
+    fn test_render_length_with_loops() {
+        // 1 s intro, 4 s loop section, 2 s fade tail at 1 kHz
+        let config = ExportConfig::default().loops(3).fade_out(2.0);
+        assert_eq!(
+            config.render_length(5_000, Some(1_000), 1_000),
+            1_000 + 3 * 4_000 + 2_000
+        );
+
+        // Songs without a loop point keep their length and fade inside it
+        assert_eq!(config.render_length(5_000, None, 1_000), 5_000);
+        assert_eq!(config.render_length(5_000, Some(5_000), 1_000), 5_000);
+        assert_eq!(
+            ExportConfig::default()
+                .fade_out(2.0)
+                .render_length(5_000, Some(0), 1_000),
+            5_000
+        );
+    }
+
+    #[test]
+    fn test_ym_render_length_follows_loop_frame() {
+        let bytes = crate::YmWriter::new(vec![[0u8; 16]; 100])
+            .loop_frame(40)
+            .to_bytes()
+            .unwrap();
+        let (player, info) = crate::load_song(&bytes).unwrap();
+        let samples_per_frame = info.samples_per_frame as usize;
+
+        let looped = ExportConfig::default().loops(2);
+        assert_eq!(
+            ym_render_length(&player, &info, &looped, 44_100),
+            (100 + 60) * samples_per_frame
+        );
+        assert_eq!(
+            ym_render_length(&player, &info, &ExportConfig::default(), 44_100),
+            100 * samples_per_frame
+        );
     }
 }
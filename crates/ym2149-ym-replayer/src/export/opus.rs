@@ -0,0 +1,273 @@
+//! Ogg Opus export functionality
+
+use super::{ExportConfig, ExportFormat, OGG_STREAM_SERIAL, SongTags, export_player};
+use crate::{LoadSummary, Result, YmPlayer};
+use ogg::{PacketWriteEndInfo, PacketWriter};
+use std::borrow::Cow;
+use std::io::Write;
+use std::path::Path;
+
+/// Input sample rates accepted by the Opus encoder
+///
+/// Samples at any other rate are resampled to 48 kHz before encoding.
+pub const OPUS_SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
+
+/// Ogg Opus granule positions always count 48 kHz samples (RFC 7845)
+const GRANULE_RATE: u32 = 48_000;
+
+/// Opus frames per second (20 ms frames)
+const FRAMES_PER_SECOND: u32 = 50;
+
+/// Largest Opus packet we ask the encoder for (recommended by libopus)
+const MAX_PACKET_SIZE: usize = 4000;
+
+/// Export YM playback to an Ogg Opus file
+///
+/// Players running at a rate Opus does not accept (such as the default
+/// 44.1 kHz) are resampled to 48 kHz; load the song with
+/// [`load_song_with_rate`](crate::load_song_with_rate) at 48 kHz to skip that.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ym2149_ym_replayer::export::export_to_opus;
+/// use ym2149_ym_replayer::load_song_with_rate;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("song.ym")?;
+/// let (mut player, info) = load_song_with_rate(&data, 48_000)?;
+///
+/// export_to_opus(&mut player, info, "output.opus")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_to_opus<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    info: LoadSummary,
+    output_path: P,
+) -> Result<()> {
+    export_to_opus_with_config(player, output_path, info, ExportConfig::default())
+}
+
+/// Export YM playback to an Ogg Opus file with custom configuration
+///
+/// `config.sample_rate` is ignored; the file is encoded from the player's rate.
+/// The song title, author and comment are written as Opus comment tags.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ym2149_ym_replayer::export::{export_to_opus_with_config, ExportConfig};
+/// use ym2149_ym_replayer::load_song_with_rate;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("song.ym")?;
+/// let (mut player, info) = load_song_with_rate(&data, 48_000)?;
+///
+/// let config = ExportConfig::default().bitrate(64_000).fade_out(2.0);
+///
+/// export_to_opus_with_config(&mut player, "output.opus", info, config)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_to_opus_with_config<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    output_path: P,
+    info: LoadSummary,
+    config: ExportConfig,
+) -> Result<()> {
+    export_player(
+        player,
+        &info,
+        output_path.as_ref(),
+        ExportFormat::Opus,
+        &config,
+    )
+}
+
+/// Encode mono samples as an Ogg Opus stream into `sink`
+///
+/// With `channels == 2` the mono signal is duplicated to both channels.
+pub(super) fn write_opus<W: Write>(
+    sink: W,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    bitrate: u32,
+    tags: &SongTags,
+) -> Result<W> {
+    let input_sample_rate = sample_rate;
+    let (samples, sample_rate) = if OPUS_SAMPLE_RATES.contains(&sample_rate) {
+        (Cow::Borrowed(samples), sample_rate)
+    } else {
+        let resampled = resample_linear(samples, sample_rate, GRANULE_RATE);
+        (Cow::Owned(resampled), GRANULE_RATE)
+    };
+
+    let opus_channels = if channels == 2 {
+        opus::Channels::Stereo
+    } else {
+        opus::Channels::Mono
+    };
+    let channel_count = opus_channels as usize;
+
+    let mut encoder = opus::Encoder::new(sample_rate, opus_channels, opus::Application::Audio)
+        .map_err(|e| format!("Failed to create Opus encoder: {e}"))?;
+    encoder
+        .set_bitrate(opus::Bitrate::Bits(bitrate.min(i32::MAX as u32) as i32))
+        .map_err(|e| format!("Failed to set Opus bitrate: {e}"))?;
+    let lookahead = encoder
+        .get_lookahead()
+        .map_err(|e| format!("Failed to query Opus lookahead: {e}"))?;
+
+    // Granule positions and pre-skip are in 48 kHz units regardless of input rate
+    let granule_scale = u64::from(GRANULE_RATE / sample_rate);
+    let pre_skip = lookahead.max(0) as u64 * granule_scale;
+
+    let mut writer = PacketWriter::new(sink);
+    let header = opus_head_packet(channel_count as u8, pre_skip as u16, input_sample_rate);
+    write_packet(&mut writer, header, PacketWriteEndInfo::EndPage, 0)?;
+    write_packet(
+        &mut writer,
+        opus_tags_packet(tags),
+        PacketWriteEndInfo::EndPage,
+        0,
+    )?;
+
+    // Pad the input with the encoder delay so the final samples are flushed
+    let frame_size = (sample_rate / FRAMES_PER_SECOND) as usize;
+    let padded_len = (samples.len() + lookahead.max(0) as usize).div_ceil(frame_size) * frame_size;
+    let mut frame = vec![0.0f32; frame_size * channel_count];
+    let mut packet = vec![0u8; MAX_PACKET_SIZE];
+    let end_granule = pre_skip + samples.len() as u64 * granule_scale;
+
+    let mut offset = 0;
+    while offset < padded_len {
+        frame.fill(0.0);
+        let available = samples.len().saturating_sub(offset).min(frame_size);
+        for (i, &sample) in samples[offset..offset + available].iter().enumerate() {
+            frame[i * channel_count..(i + 1) * channel_count].fill(sample);
+        }
+
+        let len = encoder
+            .encode_float(&frame, &mut packet)
+            .map_err(|e| format!("Failed to encode Opus frame: {e}"))?;
+        offset += frame_size;
+
+        let (end_info, granule) = if offset >= padded_len {
+            (PacketWriteEndInfo::EndStream, end_granule)
+        } else {
+            (
+                PacketWriteEndInfo::NormalPacket,
+                offset as u64 * granule_scale,
+            )
+        };
+        write_packet(&mut writer, packet[..len].to_vec(), end_info, granule)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+fn write_packet<W: Write>(
+    writer: &mut PacketWriter<'_, W>,
+    packet: Vec<u8>,
+    end_info: PacketWriteEndInfo,
+    granule: u64,
+) -> Result<()> {
+    writer
+        .write_packet(packet, OGG_STREAM_SERIAL, end_info, granule)
+        .map_err(|e| format!("Failed to write Ogg page: {e}"))?;
+    Ok(())
+}
+
+/// Linear interpolation resampler for rates Opus cannot encode directly
+fn resample_linear(samples: &[f32], from_rate: u32, to_rate: u32) -> Vec<f32> {
+    let out_len = (samples.len() as u64 * u64::from(to_rate) / u64::from(from_rate)) as usize;
+    let step = f64::from(from_rate) / f64::from(to_rate);
+    (0..out_len)
+        .map(|i| {
+            let position = i as f64 * step;
+            let index = position as usize;
+            let frac = (position - index as f64) as f32;
+            let current = samples[index];
+            let next = samples.get(index + 1).copied().unwrap_or(current);
+            current + (next - current) * frac
+        })
+        .collect()
+}
+
+/// Identification header (RFC 7845, section 5.1)
+fn opus_head_packet(channels: u8, pre_skip: u16, input_sample_rate: u32) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(19);
+    packet.extend_from_slice(b"OpusHead");
+    packet.push(1); // Version
+    packet.push(channels);
+    packet.extend_from_slice(&pre_skip.to_le_bytes());
+    packet.extend_from_slice(&input_sample_rate.to_le_bytes());
+    packet.extend_from_slice(&0i16.to_le_bytes()); // Output gain
+    packet.push(0); // Mapping family: mono/stereo
+    packet
+}
+
+/// Comment header (RFC 7845, section 5.2)
+fn opus_tags_packet(tags: &SongTags) -> Vec<u8> {
+    let mut packet = b"OpusTags".to_vec();
+    packet.extend_from_slice(&tags.vorbis_comment_block());
+    packet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(sample_rate: u32) -> Vec<f32> {
+        (0..sample_rate)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / sample_rate as f32).sin() * 0.5)
+            .collect()
+    }
+
+    #[test]
+    fn test_opus_tags_packet() {
+        let tags = SongTags {
+            title: "Steps".into(),
+            artist: "Jess".into(),
+            comment: String::new(),
+            year: "1990".into(),
+        };
+        let packet = opus_tags_packet(&tags);
+
+        assert!(packet.starts_with(b"OpusTags"));
+        let vendor_len = u32::from_le_bytes(packet[8..12].try_into().unwrap()) as usize;
+        let count_at = 12 + vendor_len;
+        assert_eq!(
+            u32::from_le_bytes(packet[count_at..count_at + 4].try_into().unwrap()),
+            3
+        );
+        assert_eq!(&packet[count_at + 8..count_at + 19], b"TITLE=Steps");
+        assert!(packet.ends_with(b"DATE=1990"));
+    }
+
+    #[test]
+    fn test_resample_linear() {
+        let resampled = resample_linear(&[0.0, 1.0, 0.0, -1.0], 2, 4);
+
+        assert_eq!(resampled, vec![0.0, 0.5, 1.0, 0.5, 0.0, -0.5, -1.0, -1.0]);
+    }
+
+    #[test]
+    fn test_write_opus_stream() {
+        let tags = SongTags {
+            title: "Sine".into(),
+            ..Default::default()
+        };
+        let data = write_opus(Vec::new(), &sine(44_100), 44_100, 2, 64_000, &tags).unwrap();
+
+        assert!(data.starts_with(b"OggS"));
+        assert_eq!(&data[28..36], b"OpusHead");
+        assert_eq!(data[37], 2);
+        assert_eq!(u32::from_le_bytes(data[40..44].try_into().unwrap()), 44_100);
+        assert!(data.windows(10).any(|w| w == b"TITLE=Sine"));
+        // One second at 64 kbit/s is roughly 8 KB
+        assert!(data.len() < 16_000);
+    }
+}
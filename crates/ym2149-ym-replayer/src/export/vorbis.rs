@@ -0,0 +1,141 @@
+//! Ogg Vorbis export functionality
+
+use super::{ExportConfig, ExportFormat, OGG_STREAM_SERIAL, SongTags, export_player};
+use crate::{LoadSummary, Result, YmPlayer};
+use std::io::Write;
+use std::num::{NonZeroU8, NonZeroU32};
+use std::path::Path;
+use vorbis_rs::{VorbisBitrateManagementStrategy, VorbisEncoderBuilder};
+
+/// Samples handed to the encoder per block (recommended by libvorbis)
+const BLOCK_SIZE: usize = 1024;
+
+/// Export YM playback to an Ogg Vorbis file
+///
+/// # Examples
+///
+/// ```no_run
+/// use ym2149_ym_replayer::export::export_to_vorbis;
+/// use ym2149_ym_replayer::load_song;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("song.ym")?;
+/// let (mut player, info) = load_song(&data)?;
+///
+/// export_to_vorbis(&mut player, info, "output.ogg")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_to_vorbis<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    info: LoadSummary,
+    output_path: P,
+) -> Result<()> {
+    export_to_vorbis_with_config(player, output_path, info, ExportConfig::default())
+}
+
+/// Export YM playback to an Ogg Vorbis file with custom configuration
+///
+/// `config.sample_rate` is ignored; the file is encoded at the player's rate.
+/// The song title, author and comment are written as Vorbis comment tags.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ym2149_ym_replayer::export::{export_to_vorbis_with_config, ExportConfig};
+/// use ym2149_ym_replayer::load_song;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data = std::fs::read("song.ym")?;
+/// let (mut player, info) = load_song(&data)?;
+///
+/// let config = ExportConfig::stereo().bitrate(128_000);
+///
+/// export_to_vorbis_with_config(&mut player, "output.ogg", info, config)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn export_to_vorbis_with_config<P: AsRef<Path>>(
+    player: &mut YmPlayer,
+    output_path: P,
+    info: LoadSummary,
+    config: ExportConfig,
+) -> Result<()> {
+    export_player(
+        player,
+        &info,
+        output_path.as_ref(),
+        ExportFormat::Vorbis,
+        &config,
+    )
+}
+
+/// Encode mono samples as an Ogg Vorbis stream into `sink`
+///
+/// With `channels == 2` the mono signal is duplicated to both channels.
+pub(super) fn write_vorbis<W: Write>(
+    sink: W,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    bitrate: u32,
+    tags: &SongTags,
+) -> Result<W> {
+    let channel_count = if channels == 2 { 2 } else { 1 };
+    let sample_rate =
+        NonZeroU32::new(sample_rate).ok_or("Vorbis cannot encode a 0 Hz sample rate")?;
+    let target_bitrate = NonZeroU32::new(bitrate).ok_or("Vorbis bitrate must not be 0")?;
+
+    let mut builder = VorbisEncoderBuilder::new_with_serial(
+        sample_rate,
+        NonZeroU8::new(channel_count).unwrap_or(NonZeroU8::MIN),
+        sink,
+        OGG_STREAM_SERIAL as i32,
+    );
+    builder
+        .bitrate_management_strategy(VorbisBitrateManagementStrategy::Vbr { target_bitrate })
+        .comment_tags(tags.vorbis_comments())
+        .map_err(|e| format!("Invalid Vorbis comment: {e}"))?;
+    let mut encoder = builder
+        .build()
+        .map_err(|e| format!("Failed to create Vorbis encoder: {e}"))?;
+
+    for block in samples.chunks(BLOCK_SIZE) {
+        let planes = [block; 2];
+        encoder
+            .encode_audio_block(&planes[..channel_count as usize])
+            .map_err(|e| format!("Failed to encode Vorbis block: {e}"))?;
+    }
+
+    let sink = encoder
+        .finish()
+        .map_err(|e| format!("Failed to finalize Vorbis stream: {e}"))?;
+    Ok(sink)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_vorbis_stream() {
+        let samples: Vec<f32> = (0..44_100)
+            .map(|i| (i as f32 * 440.0 * std::f32::consts::TAU / 44_100.0).sin() * 0.5)
+            .collect();
+        let tags = SongTags {
+            title: "Sine".into(),
+            artist: "Test".into(),
+            comment: String::new(),
+            year: "1990".into(),
+        };
+        let data = write_vorbis(Vec::new(), &samples, 44_100, 1, 96_000, &tags).unwrap();
+
+        assert!(data.starts_with(b"OggS"));
+        assert_eq!(&data[29..35], b"vorbis");
+        assert!(data.windows(10).any(|w| w == b"TITLE=Sine"));
+        assert!(data.windows(11).any(|w| w == b"ARTIST=Test"));
+        assert!(data.windows(9).any(|w| w == b"DATE=1990"));
+        // Far smaller than the 88 KB of 16-bit PCM
+        assert!(data.len() < 32_000);
+    }
+}
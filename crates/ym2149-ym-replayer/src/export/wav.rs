@@ -1,8 +1,10 @@
 //! WAV file export functionality
 
-use super::{ExportConfig, apply_fade_out, normalize_samples};
+use super::{ExportConfig, SongTags, apply_fade_out, normalize_samples, ym_render_length};
 use crate::Result;
 use crate::{LoadSummary, PlaybackController, YmPlayer};
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
 use std::path::Path;
 
 /// Export YM playback to WAV file
@@ -17,14 +19,14 @@ use std::path::Path;
 /// # Examples
 ///
 /// ```no_run
-/// use ym2149::export::export_to_wav;
-/// use ym2149::replayer::load_song;
+/// use ym2149_ym_replayer::export::export_to_wav;
+/// use ym2149_ym_replayer::load_song;
 ///
 /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let data = std::fs::read("song.ym")?;
-/// let (mut player, _) = load_song(&data)?;
+/// let (mut player, info) = load_song(&data)?;
 ///
-/// export_to_wav(&mut player, "output.wav")?;
+/// export_to_wav(&mut player, info, "output.wav")?;
 /// # Ok(())
 /// # }
 /// ```
@@ -38,6 +40,10 @@ pub fn export_to_wav<P: AsRef<Path>>(
 
 /// Export YM playback to WAV file with custom configuration
 ///
+/// The song title, author and comment are written as a RIFF `INFO` chunk.
+/// With [`ExportConfig::loops`] the file holds the intro, the requested loop
+/// passes and a fade out tail.
+///
 /// # Arguments
 ///
 /// * `player` - YM player instance
@@ -47,18 +53,20 @@ pub fn export_to_wav<P: AsRef<Path>>(
 /// # Examples
 ///
 /// ```no_run
-/// use ym2149::export::{export_to_wav_with_config, ExportConfig};
-/// use ym2149::replayer::load_song;
+/// use ym2149_ym_replayer::export::{export_to_wav_with_config, ExportConfig};
+/// use ym2149_ym_replayer::load_song;
 ///
 /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// let data = std::fs::read("song.ym")?;
-/// let (mut player, _) = load_song(&data)?;
+/// let (mut player, info) = load_song(&data)?;
 ///
+/// // Intro, two passes of the loop, then a 5 second fade
 /// let config = ExportConfig::stereo()
 ///     .normalize(true)
-///     .fade_out(2.0);
+///     .loops(2)
+///     .fade_out(5.0);
 ///
-/// export_to_wav_with_config(&mut player, "output.wav", config)?;
+/// export_to_wav_with_config(&mut player, "output.wav", info, config)?;
 /// # Ok(())
 /// # }
 /// ```
@@ -70,13 +78,14 @@ pub fn export_to_wav_with_config<P: AsRef<Path>>(
 ) -> Result<()> {
     // Ensure player is playing
     player.play()?;
+    let tags = SongTags::from_player(player);
 
-    // Calculate total samples needed
-    let total_samples = info.total_samples();
+    // Calculate total samples needed (intro, loop passes and fade tail)
+    let total_samples = ym_render_length(player, &info, &config, config.sample_rate);
 
     println!(
         "Rendering {} frames ({:.1}s) to WAV...",
-        info.frame_count,
+        total_samples / info.samples_per_frame.max(1) as usize,
         total_samples as f32 / config.sample_rate as f32
     );
 
@@ -122,6 +131,8 @@ pub fn export_to_wav_with_config<P: AsRef<Path>>(
         )?;
     }
 
+    append_info_chunk(output_path.as_ref(), &tags)?;
+
     println!("Export complete!");
     Ok(())
 }
@@ -136,6 +147,77 @@ fn mono_to_stereo(mono: &[f32]) -> Vec<f32> {
     stereo
 }
 
+/// Write mono samples and tags to a WAV file, duplicating the samples when
+/// `channels == 2`
+pub(super) fn write_wav_samples(
+    path: &Path,
+    samples: &[f32],
+    sample_rate: u32,
+    channels: u16,
+    tags: &SongTags,
+) -> Result<()> {
+    if channels == 2 {
+        write_wav_file(path, &mono_to_stereo(samples), sample_rate, channels)?;
+    } else {
+        write_wav_file(path, samples, sample_rate, 1)?;
+    }
+    append_info_chunk(path, tags)
+}
+
+/// RIFF `LIST` chunk of type `INFO` holding the non-empty tags
+fn info_chunk(tags: &SongTags) -> Option<Vec<u8>> {
+    let fields = [
+        (b"INAM", &tags.title),
+        (b"IART", &tags.artist),
+        (b"ICMT", &tags.comment),
+        (b"ICRD", &tags.year),
+    ];
+
+    let mut info = b"INFO".to_vec();
+    for (id, value) in fields {
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        // NUL-terminated text, padded to an even length
+        let size = value.len() + 1;
+        info.extend_from_slice(id);
+        info.extend_from_slice(&(size as u32).to_le_bytes());
+        info.extend_from_slice(value.as_bytes());
+        info.push(0);
+        if size % 2 == 1 {
+            info.push(0);
+        }
+    }
+    if info.len() == 4 {
+        return None;
+    }
+
+    let mut chunk = b"LIST".to_vec();
+    chunk.extend_from_slice(&(info.len() as u32).to_le_bytes());
+    chunk.extend_from_slice(&info);
+    Some(chunk)
+}
+
+/// Append the tags to a finished WAV file and update the RIFF size
+///
+/// hound cannot write extra chunks, so the `INFO` list goes after the data
+/// chunk; 16-bit samples keep that chunk word-aligned.
+fn append_info_chunk(path: &Path, tags: &SongTags) -> Result<()> {
+    let Some(chunk) = info_chunk(tags) else {
+        return Ok(());
+    };
+
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let end = file.seek(SeekFrom::End(0))?;
+    let riff_size =
+        u32::try_from(end + chunk.len() as u64 - 8).map_err(|_| "WAV file too large for tags")?;
+    file.write_all(&chunk)?;
+    file.seek(SeekFrom::Start(4))?;
+    file.write_all(&riff_size.to_le_bytes())?;
+    Ok(())
+}
+
 /// Write samples to WAV file
 fn write_wav_file(path: &Path, samples: &[f32], sample_rate: u32, channels: u16) -> Result<()> {
     let spec = hound::WavSpec {
@@ -146,19 +228,19 @@ fn write_wav_file(path: &Path, samples: &[f32], sample_rate: u32, channels: u16)
     };
 
     let mut writer = hound::WavWriter::create(path, spec)
-        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+        .map_err(|e| format!("Failed to create WAV file: {e}"))?;
 
     // Convert f32 samples to i16
     for &sample in samples {
         let sample_i16 = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
         writer
             .write_sample(sample_i16)
-            .map_err(|e| format!("Failed to write sample: {}", e))?;
+            .map_err(|e| format!("Failed to write sample: {e}"))?;
     }
 
     writer
         .finalize()
-        .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
+        .map_err(|e| format!("Failed to finalize WAV file: {e}"))?;
 
     Ok(())
 }
@@ -179,7 +261,7 @@ fn write_wav_file_streaming(
     };
 
     let mut writer = hound::WavWriter::create(path, spec)
-        .map_err(|e| format!("Failed to create WAV file: {}", e))?;
+        .map_err(|e| format!("Failed to create WAV file: {e}"))?;
 
     // Generate and write samples in chunks (zero-allocation hot path)
     const SAMPLES_PER_CHUNK: usize = 4096;
@@ -201,7 +283,7 @@ fn write_wav_file_streaming(
             for _ in 0..channels {
                 writer
                     .write_sample(sample_i16)
-                    .map_err(|e| format!("Failed to write sample: {}", e))?;
+                    .map_err(|e| format!("Failed to write sample: {e}"))?;
             }
         }
 
@@ -210,7 +292,7 @@ fn write_wav_file_streaming(
 
     writer
         .finalize()
-        .map_err(|e| format!("Failed to finalize WAV file: {}", e))?;
+        .map_err(|e| format!("Failed to finalize WAV file: {e}"))?;
 
     Ok(())
 }
@@ -227,4 +309,33 @@ mod tests {
         assert_eq!(stereo.len(), 6);
         assert_eq!(stereo, vec![0.1, 0.1, 0.2, 0.2, 0.3, 0.3]);
     }
+
+    #[test]
+    fn test_wav_info_tags() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let tags = SongTags {
+            title: "Steps".into(),
+            artist: "Jess".into(),
+            comment: String::new(),
+            year: "1990".into(),
+        };
+        write_wav_samples(file.path(), &[0.0, 0.5, -0.5], 44_100, 2, &tags).unwrap();
+
+        let data = std::fs::read(file.path()).unwrap();
+        let riff_size = u32::from_le_bytes(data[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_size, data.len() - 8);
+        assert!(data.windows(13).any(|w| w == b"INAM\x06\0\0\0Steps"));
+        assert!(data.windows(4).any(|w| w == b"ICRD"));
+        assert!(!data.windows(4).any(|w| w == b"ICMT"));
+
+        // Players still read the samples behind the tags
+        let reader = hound::WavReader::open(file.path()).unwrap();
+        assert_eq!(reader.spec().channels, 2);
+        assert_eq!(reader.len(), 6);
+    }
+
+    #[test]
+    fn test_no_info_chunk_without_tags() {
+        assert!(info_chunk(&SongTags::default()).is_none());
+    }
 }
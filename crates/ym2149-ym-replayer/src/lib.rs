@@ -6,12 +6,22 @@
 //! # Features
 //!
 //! - YM2/3/5/6 file format parsing with LHA decompression
+//! - YM6 writing with optional LHA compression ([`YmWriter`])
+//! - ZIP collection support (entry listing and extraction)
 //! - Generic over YM2149 backend (hardware-accurate or experimental)
 //! - Tracker mode support (YMT1/YMT2)
 //! - Mad Max digi-drums
 //! - YM6 effects (SID voice, Sync Buzzer)
+//! - Register activity analysis ([`RegisterAnalysis`])
 //! - Optional streaming audio output
-//! - Optional WAV/MP3 export
+//! - Optional WAV, FLAC, Ogg Opus and Ogg Vorbis export
+//!
+//! # Untrusted input
+//!
+//! Parsing and playback never panic on malformed data: truncated frames,
+//! out-of-range digidrum offsets and corrupt LHA streams all surface as
+//! errors from [`load_song`] and the format parsers. `tests/hostile_input.rs`
+//! and the `ym_parse`/`lha_decompress` fuzz targets check this.
 //!
 //! # Example
 //!
@@ -36,24 +46,37 @@ pub use error::{ReplayerError, Result};
 
 // Core modules
 pub mod compression;
+#[cfg(any(
+    feature = "export-wav",
+    feature = "export-flac",
+    feature = "export-opus",
+    feature = "export-vorbis"
+))]
+pub mod export;
 pub mod loader;
 pub mod parser;
+pub mod writer;
 
 // Re-export commonly used types
-pub use compression::decompress_if_needed;
+pub use compression::{
+    compress_lha, decompress_if_needed, extract_zip_entry, is_zip_archive, zip_entry_names,
+};
 pub use loader::{load_bytes, load_file};
 pub use parser::{
     EffectCommand, RawParser, Ym6EffectDecoder, Ym6Parser, YmMetadata, YmParser, decode_effects_ym5,
 };
+pub use writer::YmWriter;
 
 // Player module - YM music playback engine
 pub mod player;
 
 // Re-export player types
 pub use player::{
-    CycleCounter, EffectsManager, LoadSummary, PlaybackController, PlaybackState, Player,
-    TimingConfig, VblSync, Ym6Info, Ym6Metadata, Ym6Player, YmFileFormat, YmPlayer,
-    YmPlayerGeneric, load_song, load_song_with_rate,
+    CycleCounter, DigiDrumSample, DigiDrumTrigger, EffectEvent, EffectEventKind, EffectPrecision,
+    EffectUsage, EffectsManager, LoadSummary, MAX_CUSTOM_DIGIDRUM_LEN, PlaybackController,
+    PlaybackState, Player, RegisterAnalysis, TimingConfig, TrackerVoice, VblSync, Ym6Info,
+    Ym6Metadata, Ym6Player, YmFileFormat, YmPlayer, YmPlayerGeneric, YmPlayerState, load_song,
+    load_song_with_rate,
 };
 
 // Re-export unified player trait from ym2149-common
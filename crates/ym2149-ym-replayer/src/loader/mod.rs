@@ -4,7 +4,7 @@
 //! including format auto-detection and compression handling.
 
 use crate::parser::FormatParser;
-use crate::{Result, compression, parser};
+use crate::{ReplayerError, Result, compression, parser};
 use std::fs;
 
 /// Loads YM files from disk
@@ -38,11 +38,10 @@ impl YmFileLoader {
                 let parser = parser::Ym6Parser;
                 parser.parse(&data)
             }
-            _ => Err(
-                "Unsupported file format. Supported: YM3, YM3b, YM4, YM5, YM6"
-                    .to_string()
-                    .into(),
-            ),
+            _ => Err(ReplayerError::UnsupportedFormat(format!(
+                "{} (supported: YM3, YM3b, YM4, YM5, YM6)",
+                parser::magic_name(&data)
+            ))),
         }
     }
 
@@ -204,8 +204,11 @@ impl Default for Ym6EffectDecoder {
 /// - R3\[5:4\]: DigiDrum voice selector (1=A,2=B,3=C)
 ///   - Drum index from R8+voice low 5 bits
 ///   - Timer prediv from R8\[7:5\], counter from R15
-pub fn decode_effects_ym5(registers: &[u8; 16]) -> Vec<EffectCommand> {
-    let mut out = Vec::new();
+///
+/// Like [`Ym6EffectDecoder::decode_effects`], returns one command per slot
+/// (SID, then DigiDrum) so decoding a frame never allocates.
+pub fn decode_effects_ym5(registers: &[u8; 16]) -> [EffectCommand; 2] {
+    let mut out = [EffectCommand::None; 2];
 
     // SID
     let sid_code = (registers[1] >> 4) & 0x03; // 1..3 => voices A..C
@@ -217,11 +220,11 @@ pub fn decode_effects_ym5(registers: &[u8; 16]) -> Vec<EffectCommand> {
         if prediv != 0 && count != 0 {
             let freq = ATARI_MFP_CLOCK_HZ / (prediv * count);
             let volume = registers[8 + voice as usize] & 0x0F;
-            out.push(EffectCommand::SidStart {
+            out[0] = EffectCommand::SidStart {
                 voice,
                 freq,
                 volume,
-            });
+            };
         }
     }
 
@@ -235,11 +238,11 @@ pub fn decode_effects_ym5(registers: &[u8; 16]) -> Vec<EffectCommand> {
         let prediv = MFP_PREDIV[prediv_idx];
         if prediv != 0 && count != 0 {
             let freq = ATARI_MFP_CLOCK_HZ / (prediv * count);
-            out.push(EffectCommand::DigiDrumStart {
+            out[1] = EffectCommand::DigiDrumStart {
                 voice,
                 drum_num,
                 freq,
-            });
+            };
         }
     }
 
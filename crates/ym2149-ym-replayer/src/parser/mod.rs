@@ -44,3 +44,8 @@ pub(crate) fn decode_4bit_digidrum(data: &[u8]) -> Vec<u8> {
         .map(|byte| DIGIDRUM_4BIT_TABLE[(byte & 0x0F) as usize])
         .collect()
 }
+
+/// Leading magic bytes as text, for [`ReplayerError::UnsupportedFormat`](crate::ReplayerError::UnsupportedFormat)
+pub(crate) fn magic_name(data: &[u8]) -> String {
+    String::from_utf8_lossy(&data[..data.len().min(4)]).into_owned()
+}
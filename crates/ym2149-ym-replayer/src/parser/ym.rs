@@ -11,8 +11,8 @@
 
 use std::sync::Arc;
 
-use super::{ATTR_DRUM_4BIT, FormatParser, decode_4bit_digidrum};
-use crate::Result;
+use super::{ATTR_DRUM_4BIT, FormatParser, decode_4bit_digidrum, magic_name};
+use crate::{ReplayerError, Result};
 use ym2149_common::MetadataFields;
 
 /// Type alias for full YM parse result: frames, header, metadata, digidrums
@@ -188,7 +188,7 @@ impl YmParser {
             b"YM3!" => Ok("YM3"),
             b"YM4!" => Ok("YM4"),
             b"YM5!" => Ok("YM5"),
-            _ => Err("Not a recognized YM format".into()),
+            _ => Err(ReplayerError::UnsupportedFormat(magic_name(data))),
         }
     }
 
@@ -196,7 +196,7 @@ impl YmParser {
     /// Returns error for YM3/YM3b (which don't have metadata)
     pub fn parse_full(&self, data: &[u8]) -> Result<(Vec<[u8; 16]>, YmMetadata)> {
         if !Self::is_ym_format(data) {
-            return Err("Not a valid YM file format".into());
+            return Err(ReplayerError::UnsupportedFormat(magic_name(data)));
         }
 
         let version = Self::detect_version(data)?;
@@ -207,7 +207,7 @@ impl YmParser {
             }
             "YM4" => Self::parse_ym4_full(data),
             "YM5" => Self::parse_ym5_full(data),
-            _ => Err(format!("Unsupported YM version: {version}").into()),
+            _ => Err(ReplayerError::UnsupportedFormat(version.to_string())),
         }
     }
 
@@ -556,7 +556,7 @@ impl Default for YmParser {
 impl FormatParser for YmParser {
     fn parse(&self, data: &[u8]) -> Result<Vec<[u8; 16]>> {
         if !Self::is_ym_format(data) {
-            return Err("Not a valid YM file format".into());
+            return Err(ReplayerError::UnsupportedFormat(magic_name(data)));
         }
 
         let version = Self::detect_version(data)?;
@@ -581,7 +581,7 @@ impl FormatParser for YmParser {
             }
             "YM4" => Self::parse_ym4(data),
             "YM5" => Self::parse_ym5(data),
-            _ => Err(format!("Unsupported YM version: {version}").into()),
+            _ => Err(ReplayerError::UnsupportedFormat(version.to_string())),
         }
     }
 
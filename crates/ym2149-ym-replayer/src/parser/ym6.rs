@@ -54,7 +54,7 @@ pub struct Ym6Parser;
 
 impl Ym6Parser {
     /// Maximum reasonable frame count (100,000 frames ≈ 33 minutes at 50Hz)
-    const MAX_REASONABLE_FRAMES: u32 = 100_000;
+    pub(crate) const MAX_REASONABLE_FRAMES: u32 = 100_000;
 
     /// Parse YM6 header from data
     fn parse_header(data: &[u8]) -> Result<Ym6Header> {
@@ -277,7 +277,11 @@ impl FormatParser for Ym6Parser {
                 data[offset + 2],
                 data[offset + 3],
             ]);
-            offset += 4 + sample_size as usize;
+            // Checked: `usize` is 32 bits on wasm32
+            offset = offset
+                .checked_add(4)
+                .and_then(|o| o.checked_add(sample_size as usize))
+                .ok_or("Digidrum offset overflow")?;
 
             if offset > data.len() {
                 return Err("Incomplete digidrum sample data".into());
@@ -285,7 +289,9 @@ impl FormatParser for Ym6Parser {
         }
 
         // Skip extra data if present
-        offset += header.extra_data_size as usize;
+        offset = offset
+            .checked_add(header.extra_data_size as usize)
+            .ok_or("Extra data offset overflow")?;
         if offset > data.len() {
             return Err("Extra data extends beyond file".into());
         }
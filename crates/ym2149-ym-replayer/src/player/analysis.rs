@@ -0,0 +1,245 @@
+//! Register activity analysis for research and test selection.
+//!
+//! [`YmPlayerGeneric::analyze_registers`] walks every frame of the loaded
+//! song without rendering audio and records which registers change, which
+//! YM5/YM6 effects are used and how often each DigiDrum is triggered.
+
+use ym2149::Ym2149Backend;
+
+use super::format_profile::{FormatMode, create_profile};
+use super::ym_player::YmPlayerGeneric;
+use crate::parser::effects::EffectCommand;
+
+/// Frames on which each special effect is active.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EffectUsage {
+    /// SID voice frames per channel.
+    pub sid: [usize; 3],
+    /// Sinus SID frames per channel.
+    pub sinus_sid: [usize; 3],
+    /// DigiDrum frames per channel.
+    pub digidrum: [usize; 3],
+    /// Sync Buzzer frames.
+    pub sync_buzzer: usize,
+}
+
+impl EffectUsage {
+    /// Whether the song uses no effect at all.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Register heat map and effect statistics of a whole song.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegisterAnalysis {
+    /// Number of frames analyzed.
+    pub frame_count: usize,
+    /// Registers changed by each frame, bit `n` for R`n`.
+    ///
+    /// The first frame is compared against the all-zero reset state. R13 is
+    /// only compared when written (0xFF marks "no write" in YM5/YM6).
+    pub change_masks: Vec<u16>,
+    /// Frames on which each register changed.
+    pub change_counts: [usize; 16],
+    /// Frames writing R13, each of which restarts the envelope.
+    pub envelope_restarts: usize,
+    /// Effect usage decoded from the frames (YM2 drums count as DigiDrum frames).
+    pub effects: EffectUsage,
+    /// Triggers per DigiDrum sample, indexed like
+    /// [`YmPlayerGeneric::get_digidrums`].
+    pub digidrum_triggers: Vec<usize>,
+}
+
+impl RegisterAnalysis {
+    /// Build the analysis from raw frames, without effect or drum data.
+    pub fn from_frames(frames: &[[u8; 16]]) -> Self {
+        let mut analysis = Self {
+            frame_count: frames.len(),
+            change_masks: Vec::with_capacity(frames.len()),
+            ..Self::default()
+        };
+        let mut previous = [0u8; 16];
+        for regs in frames {
+            let mut mask = 0u16;
+            for (reg, (&value, last)) in regs.iter().zip(previous.iter_mut()).enumerate() {
+                if reg == 13 && value == 0xFF {
+                    continue;
+                }
+                if value != *last {
+                    mask |= 1 << reg;
+                    analysis.change_counts[reg] += 1;
+                    *last = value;
+                }
+            }
+            if regs[13] != 0xFF {
+                analysis.envelope_restarts += 1;
+            }
+            analysis.change_masks.push(mask);
+        }
+        analysis
+    }
+
+    /// Change counts per register for `buckets` equal slices of the song.
+    ///
+    /// Rows follow song time; fewer rows are returned for songs shorter than
+    /// `buckets` frames.
+    pub fn heat_map(&self, buckets: usize) -> Vec<[u32; 16]> {
+        if buckets == 0 || self.change_masks.is_empty() {
+            return Vec::new();
+        }
+        let frames_per_bucket = self.change_masks.len().div_ceil(buckets);
+        self.change_masks
+            .chunks(frames_per_bucket)
+            .map(|chunk| {
+                let mut row = [0u32; 16];
+                for mask in chunk {
+                    for (reg, count) in row.iter_mut().enumerate() {
+                        *count += u32::from(mask >> reg & 1);
+                    }
+                }
+                row
+            })
+            .collect()
+    }
+
+    /// Number of frames covered by each row of [`heat_map`](Self::heat_map).
+    pub fn frames_per_bucket(&self, buckets: usize) -> usize {
+        self.change_masks.len().div_ceil(buckets.max(1)).max(1)
+    }
+
+    /// Registers that never change over the song.
+    pub fn unused_registers(&self) -> Vec<u8> {
+        (0..16u8)
+            .filter(|&reg| self.change_counts[reg as usize] == 0)
+            .collect()
+    }
+}
+
+impl<B: Ym2149Backend> YmPlayerGeneric<B> {
+    /// Analyze register activity, effects and DigiDrum usage of the loaded song.
+    ///
+    /// Works on the frame data only, so it is cheap even for long songs and
+    /// leaves the playback position untouched. Tracker songs (YMT1/YMT2)
+    /// have no register frames and produce an empty analysis.
+    pub fn analyze_registers(&self) -> RegisterAnalysis {
+        let frames = self.sequencer.frames();
+        let mut analysis = RegisterAnalysis::from_frames(frames);
+
+        // Fresh profile: the YM6 decoder carries state between frames
+        let mut profile = create_profile(self.format_profile.mode());
+        for regs in frames {
+            for cmd in profile.decode_effects(regs) {
+                let usage = &mut analysis.effects;
+                match cmd {
+                    EffectCommand::None => {}
+                    EffectCommand::SidStart { voice, .. } => {
+                        if let Some(count) = usage.sid.get_mut(voice as usize) {
+                            *count += 1;
+                        }
+                    }
+                    EffectCommand::SinusSidStart { voice, .. } => {
+                        if let Some(count) = usage.sinus_sid.get_mut(voice as usize) {
+                            *count += 1;
+                        }
+                    }
+                    EffectCommand::DigiDrumStart { voice, .. } => {
+                        if let Some(count) = usage.digidrum.get_mut(voice as usize) {
+                            *count += 1;
+                        }
+                    }
+                    EffectCommand::SyncBuzzerStart { .. } => usage.sync_buzzer += 1,
+                }
+            }
+        }
+
+        analysis.digidrum_triggers = vec![0; self.digidrums.len()];
+        for trigger in &self.digidrum_triggers {
+            if let Some(count) = analysis.digidrum_triggers.get_mut(trigger.drum) {
+                *count += 1;
+            }
+        }
+        if profile.mode() == FormatMode::Ym2 {
+            // YM2 drums are flagged in R10 rather than encoded as effects
+            analysis.effects.digidrum[2] = self
+                .digidrum_triggers
+                .iter()
+                .filter(|trigger| trigger.channel == 2)
+                .count();
+        }
+        analysis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YmWriter;
+    use crate::player::YmPlayer;
+
+    #[test]
+    fn test_change_masks_and_counts() {
+        let mut frames = vec![[0u8; 16]; 4];
+        for frame in &mut frames {
+            frame[13] = 0xFF;
+        }
+        frames[1][0] = 0x10;
+        frames[2][0] = 0x10;
+        frames[2][8] = 0x0F;
+        frames[2][13] = 0x0A;
+        frames[3] = frames[2];
+        frames[3][13] = 0xFF;
+
+        let analysis = RegisterAnalysis::from_frames(&frames);
+        assert_eq!(analysis.change_masks, [0, 1, 1 << 8 | 1 << 13, 0]);
+        assert_eq!(analysis.change_counts[0], 1);
+        assert_eq!(analysis.change_counts[8], 1);
+        assert_eq!(analysis.envelope_restarts, 1);
+        assert!(!analysis.unused_registers().contains(&13));
+        assert!(analysis.unused_registers().contains(&7));
+    }
+
+    #[test]
+    fn test_heat_map_buckets() {
+        let frames: Vec<[u8; 16]> = (0..10u8)
+            .map(|i| {
+                let mut regs = [0u8; 16];
+                regs[0] = i;
+                regs[13] = 0xFF;
+                regs
+            })
+            .collect();
+        let analysis = RegisterAnalysis::from_frames(&frames);
+
+        let map = analysis.heat_map(3);
+        assert_eq!(analysis.frames_per_bucket(3), 4);
+        assert_eq!(map.len(), 3);
+        assert_eq!([map[0][0], map[1][0], map[2][0]], [3, 4, 2]);
+        assert!(map.iter().all(|row| row[1..].iter().all(|&c| c == 0)));
+    }
+
+    #[test]
+    fn test_analyze_effects_and_drums() {
+        let mut frames = vec![[0u8; 16]; 4];
+        for frame in &mut frames[1..3] {
+            frame[1] = 0x50; // DigiDrum on voice A
+            frame[8] = 1;
+            frame[6] = 0b0010_0000;
+            frame[14] = 96;
+            frame[13] = 0xFF;
+        }
+        let data = YmWriter::new(frames)
+            .digidrums(vec![vec![0x80; 4], vec![0x10, 0x20, 0x30]])
+            .to_bytes()
+            .unwrap();
+        let mut player = YmPlayer::new();
+        player.load_data(&data).unwrap();
+
+        let analysis = player.analyze_registers();
+        assert_eq!(analysis.frame_count, 4);
+        assert_eq!(analysis.effects.digidrum, [2, 0, 0]);
+        assert_eq!(analysis.effects.sid, [0; 3]);
+        assert_eq!(analysis.digidrum_triggers, [0, 1]);
+        assert!(!analysis.effects.is_empty());
+    }
+}
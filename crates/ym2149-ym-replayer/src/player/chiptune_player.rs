@@ -7,7 +7,10 @@ use super::PlaybackState;
 use super::ym_player::YmPlayerGeneric;
 use super::ym6::Ym6Info;
 use ym2149::Ym2149Backend;
-use ym2149_common::{ChiptunePlayer, ChiptunePlayerBase, MetadataFields};
+use ym2149_common::{
+    ChiptuneFormat, ChiptunePlayer, ChiptunePlayerBase, LoadReport, MetadataFields, OutputModel,
+    RegisterDelta,
+};
 
 /// Metadata wrapper for YM6 files.
 ///
@@ -122,6 +125,52 @@ impl<B: Ym2149Backend> ChiptunePlayerBase for YmPlayerGeneric<B> {
         YmPlayerGeneric::is_channel_muted(self, channel)
     }
 
+    fn set_gain(&mut self, gain: f32) {
+        self.output_gain.set_gain(gain);
+    }
+
+    fn gain(&self) -> f32 {
+        self.output_gain.gain()
+    }
+
+    fn set_soft_clip(&mut self, enabled: bool) {
+        self.output_gain.set_soft_clip(enabled);
+    }
+
+    fn soft_clip(&self) -> bool {
+        self.output_gain.soft_clip()
+    }
+
+    fn set_playback_rate(&mut self, rate: f32) {
+        self.playback_rate.set_rate(rate);
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.playback_rate.rate()
+    }
+
+    fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        YmPlayerGeneric::step_frames(self, frames)
+    }
+
+    fn advance_frames(&mut self, frames: usize) -> usize {
+        YmPlayerGeneric::advance_frames(self, frames)
+    }
+
+    fn set_output_model(&mut self, model: OutputModel) {
+        self.chip.set_output_model(model);
+    }
+
+    fn set_channel_tap(&mut self, capacity: Option<usize>) {
+        self.chip.set_channel_tap(capacity);
+    }
+
+    fn read_channel_tap(&self, channel: usize, out: &mut [f32]) -> usize {
+        self.chip
+            .channel_tap()
+            .map_or(0, |tap| tap.read_channel(channel, out))
+    }
+
     fn seek(&mut self, position: f32) -> bool {
         let frame_count = self.frame_count();
         if frame_count == 0 {
@@ -146,4 +195,14 @@ impl<B: Ym2149Backend> ChiptunePlayer for YmPlayerGeneric<B> {
     fn metadata(&self) -> &Self::Metadata {
         &self.cached_metadata
     }
+
+    fn load_report(&self) -> LoadReport {
+        let mut report = LoadReport::from_player(self)
+            .with_family(ChiptuneFormat::Ym)
+            .with_compression(self.compression);
+        if let Some(format) = self.file_format {
+            report.format = format.to_string();
+        }
+        report
+    }
 }
@@ -0,0 +1,322 @@
+//! DigiDrum sample bank and trigger inspection
+//!
+//! Exposes the samples a YM2/YM5/YM6 song carries and the frames that start
+//! them, so tools can extract and analyze the drums without playing the song,
+//! and lets callers swap in their own PCM for individual drums.
+
+use std::sync::Arc;
+
+use super::format_profile::{FormatMode, create_profile};
+use super::madmax_digidrums::MADMAX_SAMPLE_RATE_BASE;
+use super::ym_player::YmPlayerGeneric;
+use crate::parser::EffectCommand;
+use ym2149::Ym2149Backend;
+
+/// Longest custom DigiDrum in sample frames; longer PCM is truncated.
+///
+/// Keeps the 17.15 fixed-point playback position from overflowing.
+pub const MAX_CUSTOM_DIGIDRUM_LEN: usize = 1 << 16;
+
+/// A DigiDrum sample embedded in (or, for YM2, implied by) a YM file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DigiDrumSample {
+    /// Unsigned 8-bit PCM as played back (4-bit source samples are expanded).
+    pub data: Arc<[u8]>,
+    /// Bit depth of the sample as stored in the file (4 or 8).
+    pub source_bits: u8,
+    /// Replay rate of the first trigger in the song, if the song uses the sample.
+    ///
+    /// YM samples have no fixed rate: each trigger sets it through the MFP timer
+    /// (see [`DigiDrumTrigger::sample_rate_hz`]).
+    pub sample_rate_hz: Option<u32>,
+}
+
+impl DigiDrumSample {
+    /// Wrap decoded sample data.
+    pub fn new(data: Arc<[u8]>, source_bits: u8) -> Self {
+        Self {
+            data,
+            source_bits,
+            sample_rate_hz: None,
+        }
+    }
+
+    /// Number of sample frames.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Whether the sample holds no data.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+/// User PCM replacing a file DigiDrum.
+#[derive(Debug, Clone)]
+pub(in crate::player) struct CustomDigiDrum {
+    /// Unsigned 8-bit PCM, like file samples.
+    data: Arc<[u8]>,
+    /// Rate the PCM was recorded at.
+    sample_rate_hz: u32,
+}
+
+/// A DigiDrum start on one channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DigiDrumTrigger {
+    /// Frame the drum starts on.
+    pub frame: usize,
+    /// Channel (0 = A, 1 = B, 2 = C).
+    pub channel: usize,
+    /// Index into [`YmPlayerGeneric::get_digidrums`].
+    pub drum: usize,
+    /// Replay rate in Hz.
+    pub sample_rate_hz: u32,
+}
+
+impl<B: Ym2149Backend> YmPlayerGeneric<B> {
+    /// DigiDrum samples of the loaded song (empty for formats without drums).
+    pub fn get_digidrums(&self) -> &[DigiDrumSample] {
+        &self.digidrums
+    }
+
+    /// Every DigiDrum start in the song, ordered by frame and channel.
+    ///
+    /// Mirrors playback: a held drum only counts again when its sample or
+    /// rate changes (YM5/YM6), while YM2 restarts on every flagged frame.
+    pub fn digidrum_triggers(&self) -> &[DigiDrumTrigger] {
+        &self.digidrum_triggers
+    }
+
+    /// Replace DigiDrum `index` with custom PCM (-1.0..=1.0) recorded at `sample_rate_hz`.
+    ///
+    /// The replacement plays at its own rate when triggered at the original
+    /// sample's usual rate; triggers at other rates scale it by the same ratio,
+    /// so pitched drums keep their melody. Drums that are already playing
+    /// switch on their next trigger. Returns `false` if the song has no drum
+    /// `index` or the rate is zero. Loading another song clears replacements.
+    pub fn set_custom_digidrum(&mut self, index: usize, pcm: &[f32], sample_rate_hz: u32) -> bool {
+        if index >= self.digidrums.len() || sample_rate_hz == 0 {
+            return false;
+        }
+        let data: Arc<[u8]> = pcm
+            .iter()
+            .take(MAX_CUSTOM_DIGIDRUM_LEN)
+            .map(|&s| ((s.clamp(-1.0, 1.0) * 0.5 + 0.5) * 255.0).round() as u8)
+            .collect();
+        if self.custom_digidrums.len() < self.digidrums.len() {
+            self.custom_digidrums.resize(self.digidrums.len(), None);
+        }
+        self.custom_digidrums[index] = Some(CustomDigiDrum {
+            data,
+            sample_rate_hz,
+        });
+        true
+    }
+
+    /// Restore the file's sample for DigiDrum `index`.
+    pub fn clear_custom_digidrum(&mut self, index: usize) {
+        if let Some(slot) = self.custom_digidrums.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    /// Restore all file DigiDrum samples.
+    pub fn clear_custom_digidrums(&mut self) {
+        self.custom_digidrums.clear();
+    }
+
+    /// Whether DigiDrum `index` is currently replaced.
+    pub fn has_custom_digidrum(&self, index: usize) -> bool {
+        self.custom_digidrums
+            .get(index)
+            .is_some_and(|slot| slot.is_some())
+    }
+
+    /// Sample data and replay rate for a drum triggered at `freq`, honoring replacements.
+    pub(in crate::player) fn digidrum_playback(
+        &self,
+        index: usize,
+        freq: u32,
+    ) -> Option<(Arc<[u8]>, u32)> {
+        let sample = self.digidrums.get(index)?;
+        match self.custom_digidrums.get(index).and_then(Option::as_ref) {
+            Some(custom) => {
+                let reference = sample.sample_rate_hz.unwrap_or(freq).max(1) as u64;
+                let rate = (custom.sample_rate_hz as u64 * freq as u64 / reference).max(1);
+                Some((Arc::clone(&custom.data), rate.min(u32::MAX as u64) as u32))
+            }
+            None => Some((Arc::clone(&sample.data), freq)),
+        }
+    }
+
+    /// Decode all frames for DigiDrum starts and record each sample's first rate.
+    pub(in crate::player) fn scan_digidrum_triggers(&mut self, format_mode: FormatMode) {
+        self.digidrum_triggers = match format_mode {
+            FormatMode::Ym2 => self.scan_ym2_triggers(),
+            FormatMode::Ym5 | FormatMode::Ym6 => self.scan_ymx_triggers(format_mode),
+            FormatMode::Basic => Vec::new(),
+        };
+
+        for trigger in &self.digidrum_triggers {
+            if let Some(sample) = self.digidrums.get_mut(trigger.drum) {
+                sample.sample_rate_hz.get_or_insert(trigger.sample_rate_hz);
+            }
+        }
+    }
+
+    fn scan_ym2_triggers(&self) -> Vec<DigiDrumTrigger> {
+        let mut triggers = Vec::new();
+        for (frame, regs) in self.sequencer.frames().iter().enumerate() {
+            let drum = (regs[10] & 0x7F) as usize;
+            let timer = regs[12] as u32;
+            if regs[10] & 0x80 == 0 || timer == 0 || drum >= self.digidrums.len() {
+                continue;
+            }
+            let freq = (MADMAX_SAMPLE_RATE_BASE / 4) / timer;
+            if freq > 0 {
+                triggers.push(DigiDrumTrigger {
+                    frame,
+                    channel: 2,
+                    drum,
+                    sample_rate_hz: freq,
+                });
+            }
+        }
+        triggers
+    }
+
+    fn scan_ymx_triggers(&self, format_mode: FormatMode) -> Vec<DigiDrumTrigger> {
+        // Fresh profile: the YM6 decoder carries state between frames
+        let mut profile = create_profile(format_mode);
+        let mut playing: [Option<(usize, u32)>; 3] = [None; 3];
+        let mut triggers = Vec::new();
+
+        for (frame, regs) in self.sequencer.frames().iter().enumerate() {
+            let mut intent: [Option<(usize, u32)>; 3] = [None; 3];
+            for cmd in profile.decode_effects(regs) {
+                if let EffectCommand::DigiDrumStart {
+                    voice,
+                    drum_num,
+                    freq,
+                } = cmd
+                    && (voice as usize) < 3
+                {
+                    intent[voice as usize] = Some((drum_num as usize, freq));
+                }
+            }
+
+            for (channel, intent) in intent.into_iter().enumerate() {
+                match intent {
+                    Some((drum, freq)) if drum < self.digidrums.len() => {
+                        if playing[channel] != Some((drum, freq)) {
+                            triggers.push(DigiDrumTrigger {
+                                frame,
+                                channel,
+                                drum,
+                                sample_rate_hz: freq,
+                            });
+                            playing[channel] = Some((drum, freq));
+                        }
+                    }
+                    // Unknown sample numbers leave the current drum running
+                    Some(_) => {}
+                    None => playing[channel] = None,
+                }
+            }
+        }
+        triggers
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::YmWriter;
+    use crate::player::{PlaybackController, YmPlayer};
+
+    /// YM6 song with two drums; voice A starts drum 1 at frame 1 and holds it.
+    fn ym6_with_drums() -> Vec<u8> {
+        let mut frames = vec![[0u8; 16]; 4];
+        for frame in &mut frames[1..3] {
+            frame[1] = 0x50; // effect code 0101: DigiDrum on voice A
+            frame[8] = 1; // drum number
+            frame[6] = 0b0010_0000; // timer predivisor 4
+            frame[14] = 96; // timer count -> 2457600 / 4 / 96 = 6400 Hz
+        }
+        YmWriter::new(frames)
+            .digidrums(vec![vec![0x80; 4], vec![0x10, 0x20, 0x30]])
+            .to_bytes()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_get_digidrums_and_triggers() {
+        let mut player = YmPlayer::new();
+        player.load_data(&ym6_with_drums()).unwrap();
+
+        let drums = player.get_digidrums();
+        assert_eq!(drums.len(), 2);
+        assert_eq!(&*drums[1].data, &[0x10, 0x20, 0x30]);
+        assert_eq!(drums[1].source_bits, 8);
+        assert_eq!(drums[0].sample_rate_hz, None);
+
+        let triggers = player.digidrum_triggers();
+        assert_eq!(triggers.len(), 1);
+        assert_eq!(triggers[0].frame, 1);
+        assert_eq!(triggers[0].channel, 0);
+        assert_eq!(triggers[0].drum, 1);
+        assert_eq!(triggers[0].sample_rate_hz, 6400);
+        assert_eq!(drums[1].sample_rate_hz, Some(triggers[0].sample_rate_hz));
+    }
+
+    #[test]
+    fn test_custom_digidrum_replaces_playback_data() {
+        let mut player = YmPlayer::new();
+        player.load_data(&ym6_with_drums()).unwrap();
+
+        assert!(!player.set_custom_digidrum(2, &[0.0], 22_050));
+        assert!(player.set_custom_digidrum(1, &[-1.0, 0.0, 1.0, 2.0], 22_050));
+        assert!(player.has_custom_digidrum(1) && !player.has_custom_digidrum(0));
+
+        // At the original rate the custom PCM plays at its own rate
+        let (data, rate) = player.digidrum_playback(1, 6400).unwrap();
+        assert_eq!(&*data, &[0, 128, 255, 255]);
+        assert_eq!(rate, 22_050);
+        // An octave up stays an octave up
+        assert_eq!(player.digidrum_playback(1, 12_800).unwrap().1, 44_100);
+        // File samples are untouched
+        assert_eq!(&*player.get_digidrums()[1].data, &[0x10, 0x20, 0x30]);
+
+        player.clear_custom_digidrum(1);
+        let (data, rate) = player.digidrum_playback(1, 6400).unwrap();
+        assert_eq!(&*data, &[0x10, 0x20, 0x30]);
+        assert_eq!(rate, 6400);
+    }
+
+    #[test]
+    fn test_custom_digidrum_is_audible() {
+        let mut player = YmPlayer::new();
+        player.load_data(&ym6_with_drums()).unwrap();
+        player.play().unwrap();
+        let mut original = vec![0.0f32; 882 * 3];
+        player.generate_samples_into(&mut original);
+
+        player.load_data(&ym6_with_drums()).unwrap();
+        player.set_custom_digidrum(1, &[1.0; 4000], 44_100);
+        player.play().unwrap();
+        let mut custom = vec![0.0f32; 882 * 3];
+        player.generate_samples_into(&mut custom);
+
+        // The replacement is longer and louder than the 3-byte original
+        let energy = |s: &[f32]| s[882 * 2..].iter().map(|x| x.abs()).sum::<f32>();
+        assert!(energy(&custom) > energy(&original));
+    }
+
+    #[test]
+    fn test_songs_without_drums_have_empty_bank() {
+        let mut player = YmPlayer::new();
+        player.load_frames(vec![[0u8; 16]; 10]);
+        assert!(player.get_digidrums().is_empty());
+        assert!(player.digidrum_triggers().is_empty());
+    }
+}
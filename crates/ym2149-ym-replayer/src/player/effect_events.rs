@@ -0,0 +1,209 @@
+//! Effect start/stop timeline for visualizers.
+//!
+//! [`YmPlayerGeneric::effect_events`] decodes the YM5/YM6 effect slots of
+//! every frame, like playback does, and reports only the frames on which an
+//! effect switches on or off, so a UI can animate SID voices, Sync Buzzer
+//! and DigiDrum hits without decoding registers itself.
+
+use ym2149::Ym2149Backend;
+
+use super::format_profile::create_profile;
+use super::ym_player::YmPlayerGeneric;
+use crate::parser::effects::EffectCommand;
+
+/// What happened on an [`EffectEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EffectEventKind {
+    /// SID voice switched on (or between square and sinus modulation).
+    SidStart {
+        /// Channel (0 = A, 1 = B, 2 = C).
+        channel: usize,
+        /// Timer frequency in Hz at the start.
+        freq: u32,
+        /// Modulation volume (0-15) at the start.
+        volume: u8,
+        /// Sinus SID rather than square-wave SID.
+        sinus: bool,
+    },
+    /// SID voice switched off.
+    SidStop {
+        /// Channel (0 = A, 1 = B, 2 = C).
+        channel: usize,
+    },
+    /// DigiDrum (re)started, as in [`YmPlayerGeneric::digidrum_triggers`].
+    DigiDrum {
+        /// Channel (0 = A, 1 = B, 2 = C).
+        channel: usize,
+        /// Index into [`YmPlayerGeneric::get_digidrums`].
+        drum: usize,
+        /// Replay rate in Hz.
+        sample_rate_hz: u32,
+    },
+    /// Sync Buzzer switched on.
+    SyncBuzzerStart {
+        /// Timer frequency in Hz.
+        freq: u32,
+    },
+    /// Sync Buzzer switched off.
+    SyncBuzzerStop,
+}
+
+/// An effect switching on or off at a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EffectEvent {
+    /// Frame the change takes effect on.
+    pub frame: usize,
+    /// The change.
+    pub kind: EffectEventKind,
+}
+
+impl<B: Ym2149Backend> YmPlayerGeneric<B> {
+    /// Every effect start and stop in the song, ordered by frame.
+    ///
+    /// Within a frame, SID and Sync Buzzer changes come before DigiDrum
+    /// starts. Parameter changes of a running SID are not reported. The
+    /// timeline covers one pass through the frames; effects still running
+    /// when the song loops are not stopped at the loop point. Tracker songs
+    /// and formats without effects produce no events (YM2 drums are
+    /// reported).
+    pub fn effect_events(&self) -> Vec<EffectEvent> {
+        let mut events = Vec::new();
+        // Fresh profile: the YM6 decoder carries state between frames
+        let mut profile = create_profile(self.format_profile.mode());
+        // Running SID per channel: sinus flag
+        let mut sid: [Option<bool>; 3] = [None; 3];
+        let mut sync_buzzer = false;
+
+        for (frame, regs) in self.sequencer.frames().iter().enumerate() {
+            let mut sid_intent: [Option<(u32, u8, bool)>; 3] = [None; 3];
+            let mut sync_intent = None;
+            for cmd in profile.decode_effects(regs) {
+                match cmd {
+                    EffectCommand::SidStart {
+                        voice,
+                        freq,
+                        volume,
+                    } => {
+                        // Sinus SID wins when both slots address a voice
+                        if let Some(intent @ None) = sid_intent.get_mut(voice as usize) {
+                            *intent = Some((freq, volume, false));
+                        }
+                    }
+                    EffectCommand::SinusSidStart {
+                        voice,
+                        freq,
+                        volume,
+                    } => {
+                        if let Some(intent) = sid_intent.get_mut(voice as usize) {
+                            *intent = Some((freq, volume, true));
+                        }
+                    }
+                    EffectCommand::SyncBuzzerStart { freq, .. } => sync_intent = Some(freq),
+                    EffectCommand::None | EffectCommand::DigiDrumStart { .. } => {}
+                }
+            }
+
+            match (sync_intent, sync_buzzer) {
+                (Some(freq), false) => events.push(EffectEvent {
+                    frame,
+                    kind: EffectEventKind::SyncBuzzerStart { freq },
+                }),
+                (None, true) => events.push(EffectEvent {
+                    frame,
+                    kind: EffectEventKind::SyncBuzzerStop,
+                }),
+                _ => {}
+            }
+            sync_buzzer = sync_intent.is_some();
+
+            for (channel, (intent, running)) in sid_intent.into_iter().zip(&mut sid).enumerate() {
+                let kind = match intent {
+                    Some((freq, volume, sinus)) if *running != Some(sinus) => {
+                        EffectEventKind::SidStart {
+                            channel,
+                            freq,
+                            volume,
+                            sinus,
+                        }
+                    }
+                    None if running.is_some() => EffectEventKind::SidStop { channel },
+                    _ => continue,
+                };
+                events.push(EffectEvent { frame, kind });
+                *running = intent.map(|(_, _, sinus)| sinus);
+            }
+        }
+
+        events.extend(self.digidrum_triggers.iter().map(|trigger| EffectEvent {
+            frame: trigger.frame,
+            kind: EffectEventKind::DigiDrum {
+                channel: trigger.channel,
+                drum: trigger.drum,
+                sample_rate_hz: trigger.sample_rate_hz,
+            },
+        }));
+        // Stable: drum starts stay after the SID changes of their frame
+        events.sort_by_key(|event| event.frame);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::YmWriter;
+    use crate::player::YmPlayer;
+
+    #[test]
+    fn test_sid_and_drum_events() {
+        let mut frames = vec![[0u8; 16]; 5];
+        for frame in &mut frames {
+            frame[13] = 0xFF;
+        }
+        for frame in &mut frames[1..3] {
+            frame[1] = 0x10; // SID on voice A
+            frame[6] = 0b0010_0000;
+            frame[14] = 96;
+            frame[8] = 12;
+        }
+        frames[2][3] = 0x70; // DigiDrum on voice C
+        frames[2][10] = 0;
+        frames[2][8] |= 0b0010_0000;
+        frames[2][15] = 96;
+
+        let data = YmWriter::new(frames)
+            .digidrums(vec![vec![0x80; 4]])
+            .to_bytes()
+            .unwrap();
+        let mut player = YmPlayer::new();
+        player.load_data(&data).unwrap();
+
+        let events = player.effect_events();
+        let kinds: Vec<_> = events.iter().map(|e| (e.frame, e.kind)).collect();
+        assert!(matches!(
+            kinds[0],
+            (
+                1,
+                EffectEventKind::SidStart {
+                    channel: 0,
+                    volume: 12,
+                    sinus: false,
+                    ..
+                }
+            )
+        ));
+        assert!(matches!(
+            kinds[1],
+            (
+                2,
+                EffectEventKind::DigiDrum {
+                    channel: 2,
+                    drum: 0,
+                    ..
+                }
+            )
+        ));
+        assert_eq!(kinds[2], (3, EffectEventKind::SidStop { channel: 0 }));
+        assert_eq!(kinds.len(), 3);
+    }
+}
@@ -6,13 +6,32 @@
 //! Effects are managed separately from the core PSG emulation to maintain clean separation
 //! of concerns: the chip is pure hardware emulation, effects are format-specific playback tricks.
 
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use ym2149::Ym2149Backend;
 
 const DRUM_PREC: u32 = 15;
 
+/// Timing precision of timer-driven effects (Sync Buzzer, SID voices)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EffectPrecision {
+    /// Update effect timers once per output sample, like ST-Sound
+    ///
+    /// Envelope retriggers land on sample boundaries and SID gates take the
+    /// level at the start of each sample.
+    #[default]
+    PerSample,
+    /// Follow every timer tick
+    ///
+    /// Envelope retriggers land at their sub-sample position and SID gates take
+    /// the level that holds over most of each sample, so timer rates close to or
+    /// above the output rate keep their pitch instead of jittering to the sample grid.
+    PerTimerTick,
+}
+
 /// Waveform modes for SID-style amplitude gating
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 enum SidMode {
     /// Square wave gating (amplitude on/off based on bit 31)
     Square,
@@ -22,6 +41,7 @@ enum SidMode {
 
 /// Per-voice SID state for amplitude gating
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct SidState {
     /// Whether this SID voice is currently active
     active: bool,
@@ -49,6 +69,7 @@ impl Default for SidState {
 
 /// Per-voice DigiDrum state for sample playback
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct DrumState {
     /// Whether this DigiDrum is currently playing
     active: bool,
@@ -60,17 +81,74 @@ struct DrumState {
     step: u32,
 }
 
+/// Empty sample shared by idle voices; `Arc::from([])` would allocate on
+/// every `digidrum_stop`, which runs on the audio thread.
+static NO_SAMPLE: LazyLock<Arc<[u8]>> = LazyLock::new(|| Arc::from([]));
+
 impl Default for DrumState {
     fn default() -> Self {
         Self {
             active: false,
-            data: Arc::from([]),
+            data: Arc::clone(&NO_SAMPLE),
             pos: 0,
             step: 0,
         }
     }
 }
 
+impl SidState {
+    /// Gate volume for the next sample at the given precision
+    fn volume(&self, precision: EffectPrecision) -> u8 {
+        let level = match (self.mode, precision) {
+            (SidMode::Square, EffectPrecision::PerSample) => {
+                if self.pos & 0x8000_0000 != 0 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            (SidMode::Square, EffectPrecision::PerTimerTick) => {
+                if square_gate_duty(self.pos, self.step) >= 0.5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            (SidMode::Sinus, EffectPrecision::PerSample) => {
+                // Compute sinusoidal amplitude in [0..vol]
+                let phase = (self.pos as f32) * (std::f32::consts::TAU / (u32::MAX as f32));
+                0.5f32 * (1.0 + phase.sin())
+            }
+            (SidMode::Sinus, EffectPrecision::PerTimerTick) => sinus_gate_mean(self.pos, self.step),
+        };
+        let a = (level * (self.vol as f32)).round() as i32;
+        a.clamp(0, 15) as u8
+    }
+}
+
+/// Fraction of `[pos, pos + step)` during which the square gate (bit 31) is on
+fn square_gate_duty(pos: u32, step: u32) -> f32 {
+    if step == 0 {
+        return if pos & 0x8000_0000 != 0 { 1.0 } else { 0.0 };
+    }
+    // Gate-on time from phase 0 up to `x`: the upper half of every 2^32 period
+    let on_time = |x: u64| (x >> 32) * (1 << 31) + (x & 0xFFFF_FFFF).saturating_sub(1 << 31);
+    let start = pos as u64;
+    let end = start + step as u64;
+    (on_time(end) - on_time(start)) as f32 / step as f32
+}
+
+/// Mean of the sinus gate `0.5 * (1 + sin)` over `[pos, pos + step)`
+fn sinus_gate_mean(pos: u32, step: u32) -> f32 {
+    let to_radians = std::f64::consts::TAU / (u32::MAX as f64);
+    let a = pos as f64 * to_radians;
+    if step == 0 {
+        return (0.5 * (1.0 + a.sin())) as f32;
+    }
+    let b = a + step as f64 * to_radians;
+    (0.5 + 0.5 * (a.cos() - b.cos()) / (b - a)) as f32
+}
+
 impl DrumState {
     /// Get current sample value scaled for better bass presence (8-bit × 255 / 3)
     /// Note: STSound reference uses /6, but /3 provides better punch and bass
@@ -107,9 +185,13 @@ impl DrumState {
 ///
 /// This maintains the same timing behavior as the original implementation while
 /// cleanly separating effect logic from core PSG emulation.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectsManager {
     /// Audio sample rate (used for step calculations)
     sample_rate: u32,
+    /// Timing precision of timer-driven effects
+    precision: EffectPrecision,
 
     // === Sync Buzzer Effect ===
     /// Phase accumulator (32-bit fixed-point)
@@ -140,6 +222,7 @@ impl EffectsManager {
     pub fn new(sample_rate: u32) -> Self {
         EffectsManager {
             sample_rate,
+            precision: EffectPrecision::default(),
             sync_buzzer_phase: 0,
             sync_buzzer_step: 0,
             sync_buzzer_enabled: false,
@@ -171,6 +254,16 @@ impl EffectsManager {
         }
     }
 
+    /// Set the timing precision of timer-driven effects
+    pub fn set_precision(&mut self, precision: EffectPrecision) {
+        self.precision = precision;
+    }
+
+    /// Timing precision of timer-driven effects
+    pub fn precision(&self) -> EffectPrecision {
+        self.precision
+    }
+
     // ================================================================================
     // SYNC BUZZER EFFECT
     // ================================================================================
@@ -299,35 +392,34 @@ impl EffectsManager {
     pub fn tick<B: Ym2149Backend>(&mut self, chip: &mut B) {
         // Handle Sync Buzzer effect (timer-based envelope retriggering)
         if self.sync_buzzer_enabled {
-            self.sync_buzzer_phase = self.sync_buzzer_phase.wrapping_add(self.sync_buzzer_step);
-            // When bit 31 overflows, retrigger the envelope
-            if self.sync_buzzer_phase & 0x80000000 != 0 {
-                chip.trigger_envelope();
-                self.sync_buzzer_phase &= 0x7fffffff; // Clear bit 31
+            match self.precision {
+                EffectPrecision::PerSample => {
+                    self.sync_buzzer_phase =
+                        self.sync_buzzer_phase.wrapping_add(self.sync_buzzer_step);
+                    // When bit 31 overflows, retrigger the envelope
+                    if self.sync_buzzer_phase & 0x80000000 != 0 {
+                        chip.trigger_envelope();
+                        self.sync_buzzer_phase &= 0x7fffffff; // Clear bit 31
+                    }
+                }
+                EffectPrecision::PerTimerTick => {
+                    // Each 2^31 of phase is one timer tick; only the last tick of
+                    // the sample matters since every tick restarts the envelope
+                    let phase = self.sync_buzzer_phase as u64 + self.sync_buzzer_step as u64;
+                    let residual = phase & 0x7fff_ffff;
+                    if phase >> 31 != 0 {
+                        let offset = 1.0 - residual as f64 / self.sync_buzzer_step as f64;
+                        chip.trigger_envelope_at(offset as f32);
+                    }
+                    self.sync_buzzer_phase = residual as u32;
+                }
             }
         }
 
         // Apply SID per-voice gating (square or sinus) by writing amplitude register
         for voice in 0..3 {
             if self.sid[voice].active {
-                let vol_idx: u8 = match self.sid[voice].mode {
-                    SidMode::Square => {
-                        let gate_on = (self.sid[voice].pos & 0x8000_0000) != 0;
-                        if gate_on {
-                            self.sid[voice].vol & 0x0F
-                        } else {
-                            0
-                        }
-                    }
-                    SidMode::Sinus => {
-                        // Compute sinusoidal amplitude in [0..vol]
-                        let phase = (self.sid[voice].pos as f32)
-                            * (std::f32::consts::TAU / (u32::MAX as f32));
-                        let s = 0.5f32 * (1.0 + phase.sin());
-                        let a = (s * (self.sid[voice].vol as f32)).round() as i32;
-                        a.clamp(0, 15) as u8
-                    }
-                };
+                let vol_idx = self.sid[voice].volume(self.precision);
                 chip.write_register(0x08 + voice as u8, vol_idx);
                 self.sid[voice].pos = self.sid[voice].pos.wrapping_add(self.sid[voice].step);
             }
@@ -359,3 +451,182 @@ impl EffectsManager {
         chip.set_mixer_overrides(self.force_tone, self.force_noise_mute);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_RATE: u32 = 44_100;
+
+    /// Backend that records envelope restarts and volume writes per sample.
+    #[derive(Default)]
+    struct RecordingChip {
+        sample: usize,
+        triggers: Vec<f64>,
+        volumes: Vec<u8>,
+    }
+
+    impl Ym2149Backend for RecordingChip {
+        fn new() -> Self {
+            Self::default()
+        }
+        fn with_clocks(_master_clock: u32, _sample_rate: u32) -> Self {
+            Self::default()
+        }
+        fn reset(&mut self) {}
+        fn write_register(&mut self, addr: u8, value: u8) {
+            if addr == 0x08 {
+                self.volumes.push(value);
+            }
+        }
+        fn read_register(&self, _addr: u8) -> u8 {
+            0
+        }
+        fn load_registers(&mut self, _regs: &[u8; 16]) {}
+        fn dump_registers(&self) -> [u8; 16] {
+            [0; 16]
+        }
+        fn clock(&mut self) {
+            self.sample += 1;
+        }
+        fn get_sample(&self) -> f32 {
+            0.0
+        }
+        fn get_channel_outputs(&self) -> (f32, f32, f32) {
+            (0.0, 0.0, 0.0)
+        }
+        fn set_channel_mute(&mut self, _channel: usize, _mute: bool) {}
+        fn is_channel_muted(&self, _channel: usize) -> bool {
+            false
+        }
+        fn set_color_filter(&mut self, _enabled: bool) {}
+        fn trigger_envelope(&mut self) {
+            self.triggers.push(self.sample as f64);
+        }
+        fn trigger_envelope_at(&mut self, offset: f32) {
+            self.triggers.push(self.sample as f64 + offset as f64);
+        }
+    }
+
+    fn render(effects: &mut EffectsManager, samples: usize) -> RecordingChip {
+        let mut chip = RecordingChip::default();
+        for _ in 0..samples {
+            effects.tick(&mut chip);
+            chip.clock();
+        }
+        chip
+    }
+
+    /// Distance (in samples) from `time` to the nearest tick of an ideal timer.
+    fn timer_error(time: f64, timer_freq: u32) -> f64 {
+        let period = SAMPLE_RATE as f64 / timer_freq as f64;
+        let ticks = time / period;
+        (ticks - ticks.round()).abs() * period
+    }
+
+    /// Reference square gate volumes: 64x oversampled ideal timer, majority per sample.
+    fn reference_square(timer_freq: u32, vol: u8, samples: usize) -> Vec<u8> {
+        const OVERSAMPLE: usize = 64;
+        let period = SAMPLE_RATE as f64 / timer_freq as f64;
+        (0..samples)
+            .map(|n| {
+                let on = (0..OVERSAMPLE)
+                    .filter(|&k| {
+                        let t = n as f64 + (k as f64 + 0.5) / OVERSAMPLE as f64;
+                        // The gate opens after the first timer tick and flips on every tick
+                        (t / period).floor() as u64 % 2 == 1
+                    })
+                    .count();
+                if on * 2 >= OVERSAMPLE { vol } else { 0 }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_default_precision_is_per_sample() {
+        let mut effects = EffectsManager::new(SAMPLE_RATE);
+        assert_eq!(effects.precision(), EffectPrecision::PerSample);
+        effects.sync_buzzer_start(11_025);
+        let chip = render(&mut effects, 64);
+        // Quarter-rate timer: one restart every 4 samples, on the sample grid
+        assert_eq!(chip.triggers.len(), 16);
+        assert!(chip.triggers.iter().all(|t| t.fract() == 0.0));
+    }
+
+    #[test]
+    fn test_timer_tick_sync_buzzer_matches_ideal_timer() {
+        let timer_freq = 31_333;
+        let mut effects = EffectsManager::new(SAMPLE_RATE);
+        effects.sync_buzzer_start(timer_freq);
+        let coarse = render(&mut effects, 4410);
+
+        effects.set_precision(EffectPrecision::PerTimerTick);
+        effects.sync_buzzer_start(timer_freq);
+        let exact = render(&mut effects, 4410);
+
+        let worst = |chip: &RecordingChip| {
+            chip.triggers
+                .iter()
+                .map(|&t| timer_error(t, timer_freq))
+                .fold(0.0, f64::max)
+        };
+        assert!(worst(&exact) < 1e-3, "per-tick error {}", worst(&exact));
+        assert!(worst(&coarse) > 0.3, "per-sample error {}", worst(&coarse));
+        // Every timer tick lands in its own sample below the output rate
+        assert_eq!(exact.triggers.len(), 3133);
+    }
+
+    #[test]
+    fn test_timer_tick_sync_buzzer_above_sample_rate() {
+        let timer_freq = 60_000;
+        let mut effects = EffectsManager::new(SAMPLE_RATE);
+        effects.set_precision(EffectPrecision::PerTimerTick);
+        effects.sync_buzzer_start(timer_freq);
+        let chip = render(&mut effects, 441);
+
+        // Several ticks per sample: the last one of each sample wins
+        assert_eq!(chip.triggers.len(), 441);
+        for &t in &chip.triggers {
+            assert!(timer_error(t, timer_freq) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_timer_tick_sid_square_matches_reference_render() {
+        let (timer_freq, samples) = (29_000, 4410);
+        let reference = reference_square(timer_freq, 15, samples);
+        let matches = |precision| {
+            let mut effects = EffectsManager::new(SAMPLE_RATE);
+            effects.set_precision(precision);
+            effects.sid_start(0, timer_freq, 15);
+            let chip = render(&mut effects, samples);
+            chip.volumes
+                .iter()
+                .zip(&reference)
+                .filter(|(a, b)| a == b)
+                .count() as f32
+                / samples as f32
+        };
+
+        let exact = matches(EffectPrecision::PerTimerTick);
+        let coarse = matches(EffectPrecision::PerSample);
+        assert!(exact > 0.99, "per-tick match {exact}");
+        assert!(coarse < exact - 0.1, "per-sample match {coarse}");
+    }
+
+    #[test]
+    fn test_timer_tick_sid_sinus_uses_mean_level() {
+        // Half a gate cycle per sample: point samples always hit the zero crossings
+        let mut effects = EffectsManager::new(SAMPLE_RATE);
+        effects.sid_sin_start(0, SAMPLE_RATE, 14);
+        let chip = render(&mut effects, 8);
+        assert_eq!(chip.volumes, [7; 8]);
+
+        // Averaging keeps the alternating upper and lower half-waves
+        let mut effects = EffectsManager::new(SAMPLE_RATE);
+        effects.set_precision(EffectPrecision::PerTimerTick);
+        effects.sid_sin_start(0, SAMPLE_RATE, 14);
+        let chip = render(&mut effects, 8);
+        assert_eq!(chip.volumes, [11, 3, 11, 3, 11, 3, 11, 3]);
+    }
+}
@@ -1,11 +1,13 @@
 use std::sync::Arc;
 
-use super::effects_manager::EffectsManager;
+use super::effects_manager::{EffectPrecision, EffectsManager};
 use ym2149::Ym2149Backend;
 
 /// High-level wrapper around `EffectsManager` that also tracks per-voice metadata
 /// (SID/DigiDrum active flags, last drum index/frequency) for consumers such as
 /// metadata queries and replay heuristics.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectsPipeline {
     manager: EffectsManager,
     sid_active: [bool; 3],
@@ -35,15 +37,27 @@ impl EffectsPipeline {
         self.last_drum_freq = [0; 3];
     }
 
-    /// Recreate the manager with a new sample rate.
+    /// Recreate the manager with a new sample rate, keeping its precision.
     pub fn set_sample_rate(&mut self, sample_rate: u32) {
+        let precision = self.manager.precision();
         self.manager = EffectsManager::new(sample_rate);
+        self.manager.set_precision(precision);
         self.sid_active = [false; 3];
         self.drum_active = [false; 3];
         self.last_drum_index = [None; 3];
         self.last_drum_freq = [0; 3];
     }
 
+    /// Set the timing precision of Sync Buzzer and SID effects.
+    pub fn set_precision(&mut self, precision: EffectPrecision) {
+        self.manager.set_precision(precision);
+    }
+
+    /// Timing precision of Sync Buzzer and SID effects.
+    pub fn precision(&self) -> EffectPrecision {
+        self.manager.precision()
+    }
+
     /// Tick all active effects (call before `chip.clock()`).
     pub fn tick<B: Ym2149Backend>(&mut self, chip: &mut B) {
         self.manager.tick(chip);
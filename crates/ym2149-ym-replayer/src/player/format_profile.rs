@@ -27,8 +27,11 @@ pub trait FormatProfile: Send + Sync {
     fn preprocess_frame(&mut self, _regs: &mut [u8; 16]) {}
 
     /// Decode effect commands encoded within the current frame.
-    fn decode_effects(&mut self, _regs: &[u8; 16]) -> Vec<EffectCommand> {
-        Vec::new()
+    ///
+    /// Both effect slots are always returned (`EffectCommand::None` when
+    /// unused); this runs once per frame on the audio thread.
+    fn decode_effects(&mut self, _regs: &[u8; 16]) -> [EffectCommand; 2] {
+        [EffectCommand::None; 2]
     }
 }
 
@@ -65,7 +68,7 @@ impl FormatProfile for Ym5Profile {
         FormatMode::Ym5
     }
 
-    fn decode_effects(&mut self, regs: &[u8; 16]) -> Vec<EffectCommand> {
+    fn decode_effects(&mut self, regs: &[u8; 16]) -> [EffectCommand; 2] {
         decode_effects_ym5(regs)
     }
 }
@@ -80,7 +83,7 @@ impl FormatProfile for Ym6Profile {
         FormatMode::Ym6
     }
 
-    fn decode_effects(&mut self, regs: &[u8; 16]) -> Vec<EffectCommand> {
-        self.decoder.decode_effects(regs).to_vec()
+    fn decode_effects(&mut self, regs: &[u8; 16]) -> [EffectCommand; 2] {
+        self.decoder.decode_effects(regs)
     }
 }
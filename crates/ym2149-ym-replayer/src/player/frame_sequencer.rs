@@ -143,6 +143,13 @@ impl FrameSequencer {
         }
     }
 
+    /// Skip the rest of the current frame, as if its remaining samples had
+    /// been advanced one by one.
+    pub fn finish_frame(&mut self) -> AdvanceResult {
+        self.samples_in_frame = self.samples_per_frame.saturating_sub(1);
+        self.advance_sample()
+    }
+
     /// Seek to a specific frame (clamped to available frames).
     pub fn seek(&mut self, frame: usize) {
         if self.frames.is_empty() {
@@ -152,6 +159,12 @@ impl FrameSequencer {
         }
         self.samples_in_frame = 0;
     }
+
+    /// Restore a frame and sample position (clamped to the loaded song).
+    pub fn restore_position(&mut self, frame: usize, samples_in_frame: u32) {
+        self.seek(frame);
+        self.samples_in_frame = samples_in_frame.min(self.samples_per_frame - 1);
+    }
 }
 
 impl Default for FrameSequencer {
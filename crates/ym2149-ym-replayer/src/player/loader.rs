@@ -5,6 +5,7 @@
 
 use std::sync::Arc;
 
+use super::digidrums::DigiDrumSample;
 use super::format_profile::{FormatMode, create_profile};
 use super::madmax_digidrums::MADMAX_SAMPLES;
 use super::tracker_player::{
@@ -14,15 +15,31 @@ use super::ym_player::YmPlayerGeneric;
 use super::ym6::{LoadSummary, PlaybackStateInit, Ym6Info, YmFileFormat};
 use super::ym6::{read_be_u16, read_be_u32, read_c_string};
 use crate::parser::FormatParser;
-use crate::parser::{ATTR_LOOP_MODE, ATTR_STREAM_INTERLEAVED, Ym6Parser, YmParser};
-use crate::{Result, compression};
+use crate::parser::{
+    ATTR_DRUM_4BIT, ATTR_LOOP_MODE, ATTR_STREAM_INTERLEAVED, Ym6Parser, YmParser, magic_name,
+};
+use crate::{ReplayerError, Result, compression};
 use ym2149::Ym2149Backend;
+use ym2149_common::{Compression, CompressionInfo};
 
 impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// Load YM data (compressed or raw) and initialize playback state.
     pub fn load_data(&mut self, data: &[u8]) -> Result<LoadSummary> {
         let decompressed = compression::decompress_if_needed(data)?;
-        self.load_decompressed(&decompressed)
+        let summary = self.load_decompressed(&decompressed)?;
+        let method = if compression::is_zip_archive(data) {
+            Some(Compression::Zip)
+        } else if compression::is_lha_compressed(data) {
+            Some(Compression::Lha)
+        } else {
+            None
+        };
+        self.compression = method.map(|method| CompressionInfo {
+            method,
+            packed_size: data.len(),
+            unpacked_size: decompressed.len(),
+        });
+        Ok(summary)
     }
 
     /// Load decompressed YM data and detect format
@@ -65,8 +82,12 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
                 self.load_ym_tracker(data, TrackerFormat::Ymt2)?;
                 YmFileFormat::Ymt2
             }
-            _ => return Err("Unsupported YM format".into()),
+            _ => {
+                return Err(ReplayerError::UnsupportedFormat(magic_name(header)));
+            }
         };
+        self.file_format = Some(format);
+        self.compression = None;
 
         Ok(LoadSummary {
             format,
@@ -79,6 +100,7 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     pub fn load_ym6(&mut self, data: &[u8]) -> Result<()> {
         let parser = Ym6Parser;
         let (frames, header, metadata, digidrums) = parser.parse_full(data)?;
+        let digidrums = digidrum_bank(digidrums, data);
 
         let samples_per_frame = self.calculate_samples_per_frame(header.frame_rate);
         let info = Ym6Info {
@@ -111,6 +133,7 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     pub fn load_ym5(&mut self, data: &[u8]) -> Result<()> {
         let parser = YmParser::new();
         let (frames, header, metadata, digidrums) = parser.parse_ym5_full_with_digidrums(data)?;
+        let digidrums = digidrum_bank(digidrums, data);
 
         // YM5 embeds player frequency in header.player_freq (Some)
         let frame_rate = header.player_freq.unwrap_or(50);
@@ -251,9 +274,9 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
             frames.push(frame);
         }
 
-        let digidrums: Vec<Arc<[u8]>> = MADMAX_SAMPLES
+        let digidrums: Vec<DigiDrumSample> = MADMAX_SAMPLES
             .iter()
-            .map(|sample| Arc::from(sample.to_vec()))
+            .map(|sample| DigiDrumSample::new(Arc::from(sample.to_vec()), 8))
             .collect();
 
         let info = Ym6Info {
@@ -407,6 +430,8 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.sequencer.clear();
         self.format_profile = create_profile(FormatMode::Basic);
         self.digidrums.clear();
+        self.digidrum_triggers.clear();
+        self.custom_digidrums.clear();
         self.sequencer.set_loop_point(None);
         let tracker_samples_per_frame = if player_rate > 0 {
             (self.sample_rate / u32::from(player_rate)).max(1)
@@ -465,6 +490,8 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     pub fn load_frames(&mut self, frames: Vec<[u8; 16]>) {
         let samples_per_frame = self.sequencer.samples_per_frame();
         let info = self.info.clone();
+        self.file_format = None;
+        self.compression = None;
 
         self.initialize_playback_state(PlaybackStateInit {
             frames,
@@ -505,7 +532,9 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.sequencer.set_loop_point(loop_frame);
         self.sequencer.set_samples_per_frame(samples_per_frame);
         self.digidrums = digidrums;
+        self.custom_digidrums.clear();
         self.attributes = attributes;
+        self.scan_digidrum_triggers(format_mode);
 
         // Update cached metadata for ChiptunePlayer trait
         self.cached_metadata = if let Some(ref info) = info {
@@ -614,3 +643,22 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.sample_rate / rate
     }
 }
+
+/// Wrap parsed YM5/YM6 digidrums, noting whether the file stored them as 4-bit.
+fn digidrum_bank(samples: Vec<Arc<[u8]>>, data: &[u8]) -> Vec<DigiDrumSample> {
+    // Both YM5 and YM6 keep the attribute word at offset 16; the parser clears
+    // the 4-bit flag once the samples are expanded, so read it from the file.
+    let attributes = data
+        .get(16..20)
+        .map(|raw| u32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]]))
+        .unwrap_or(0);
+    let source_bits = if attributes & ATTR_DRUM_4BIT != 0 {
+        4
+    } else {
+        8
+    };
+    samples
+        .into_iter()
+        .map(|data| DigiDrumSample::new(data, source_bits))
+        .collect()
+}
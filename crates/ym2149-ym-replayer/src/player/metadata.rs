@@ -2,6 +2,7 @@
 //!
 //! This module handles song metadata access, formatting, and active effect queries.
 
+use super::tracker_player::TrackerVoice;
 use super::ym_player::YmPlayerGeneric;
 use super::ym6::Ym6Info;
 use ym2149::Ym2149Backend;
@@ -33,6 +34,14 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.is_tracker_mode
     }
 
+    /// Current state of each YM Tracker voice (empty for register-based songs)
+    pub fn tracker_voices(&self) -> Vec<TrackerVoice> {
+        match self.tracker.as_ref() {
+            Some(tracker) if self.is_tracker_mode => tracker.voices(),
+            _ => Vec::new(),
+        }
+    }
+
     /// Get current active effects status for visualization
     ///
     /// Returns tuple of (sync_buzzer_active, sid_active_per_voice, drum_active_per_voice)
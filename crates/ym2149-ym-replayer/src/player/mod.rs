@@ -3,8 +3,11 @@
 //! Handles playback of YM music files including frame sequencing,
 //! VBL synchronization, cycle counting, and timing control.
 
+mod analysis;
 mod chiptune_player;
 pub mod cycle_counter;
+mod digidrums;
+mod effect_events;
 pub mod effects_manager;
 /// High-level wrapper around [`EffectsManager`] that tracks active effect state.
 pub mod effects_pipeline;
@@ -14,6 +17,7 @@ mod loader;
 mod madmax_digidrums;
 mod metadata;
 mod sample_generation;
+mod snapshot;
 mod state;
 mod timing;
 mod tracker_player;
@@ -21,12 +25,17 @@ pub mod vbl_sync;
 mod ym6;
 pub mod ym_player;
 
+pub use analysis::{EffectUsage, RegisterAnalysis};
 pub use chiptune_player::Ym6Metadata;
 pub use cycle_counter::CycleCounter;
-pub use effects_manager::EffectsManager;
+pub use digidrums::{DigiDrumSample, DigiDrumTrigger, MAX_CUSTOM_DIGIDRUM_LEN};
+pub use effect_events::{EffectEvent, EffectEventKind};
+pub use effects_manager::{EffectPrecision, EffectsManager};
 pub use effects_pipeline::EffectsPipeline;
 pub use format_profile::{FormatMode, FormatProfile, create_profile};
 pub use frame_sequencer::{AdvanceResult, FrameSequencer};
+pub use snapshot::YmPlayerState;
+pub use tracker_player::TrackerVoice;
 pub use vbl_sync::VblSync;
 pub use ym_player::{Player, Ym6Player, YmPlayer, YmPlayerGeneric, load_song, load_song_with_rate};
 pub use ym6::{LoadSummary, Ym6Info, YmFileFormat};
@@ -35,6 +44,7 @@ use crate::Result;
 
 /// Timing configuration for ATARI ST playback
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TimingConfig {
     /// Audio sample rate in Hz
     pub sample_rate: u32,
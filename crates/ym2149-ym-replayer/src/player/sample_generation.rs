@@ -3,8 +3,6 @@
 //! This module contains the performance-critical audio rendering logic,
 //! including frame register loading, effect application, and sample generation.
 
-use std::sync::Arc;
-
 use super::PlaybackState;
 use super::format_profile::FormatMode;
 use super::madmax_digidrums::MADMAX_SAMPLE_RATE_BASE;
@@ -95,18 +93,14 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
             self.chip.write_register(0x07, mixer);
 
             let sample_idx = (regs[10] & 0x7F) as usize;
-            if let Some(sample) = self.digidrums.get(sample_idx) {
-                let timer = regs[12] as u32;
-                if timer > 0 {
-                    let freq = (MADMAX_SAMPLE_RATE_BASE / 4) / timer;
-                    if freq > 0 {
-                        self.effects.digidrum_start(
-                            2,
-                            Some(sample_idx as u8),
-                            freq,
-                            Arc::clone(sample),
-                        );
-                    }
+            let timer = regs[12] as u32;
+            if timer > 0 {
+                let freq = (MADMAX_SAMPLE_RATE_BASE / 4) / timer;
+                if freq > 0
+                    && let Some((data, rate)) = self.digidrum_playback(sample_idx, freq)
+                {
+                    self.effects
+                        .digidrum_start(2, Some(sample_idx as u8), rate, data);
                 }
             }
         } else if self.effects.is_drum_active(2) {
@@ -219,19 +213,15 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         for voice in 0..3 {
             // Handle DigiDrum
             if let Some((drum_idx, freq)) = drum_intent[voice] {
-                if let Some(sample) = self.digidrums.get(drum_idx as usize) {
+                if let Some((data, rate)) = self.digidrum_playback(drum_idx as usize, freq) {
                     let should_restart = self
                         .effects
                         .drum_signature(voice)
-                        .map(|(idx, f)| idx != drum_idx || f != freq)
+                        .map(|(idx, f)| idx != drum_idx || f != rate)
                         .unwrap_or(true);
                     if should_restart {
-                        self.effects.digidrum_start(
-                            voice,
-                            Some(drum_idx),
-                            freq,
-                            Arc::clone(sample),
-                        );
+                        self.effects
+                            .digidrum_start(voice, Some(drum_idx), rate, data);
                     }
                 }
             } else if self.effects.is_drum_active(voice) {
@@ -253,10 +243,8 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     ///
     /// For performance-critical code, prefer [`Self::generate_samples_into`] to avoid allocations.
     pub fn generate_samples(&mut self, count: usize) -> Vec<f32> {
-        let mut samples = Vec::with_capacity(count);
-        for _ in 0..count {
-            samples.push(self.generate_sample());
-        }
+        let mut samples = vec![0.0; count];
+        self.generate_samples_into(&mut samples);
         samples
     }
 
@@ -275,9 +263,15 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// player.generate_samples_into(&mut buffer); // Reuse same buffer
     /// ```
     pub fn generate_samples_into(&mut self, buffer: &mut [f32]) {
-        for sample in buffer.iter_mut() {
-            *sample = self.generate_sample();
-        }
+        // Take the rate stage temporarily so the closure can borrow self
+        let mut rate = std::mem::take(&mut self.playback_rate);
+        rate.render_mono(buffer, |source| {
+            for sample in source.iter_mut() {
+                *sample = self.generate_sample();
+            }
+        });
+        self.playback_rate = rate;
+        self.output_gain.apply(buffer);
     }
 
     pub(in crate::player) fn generate_tracker_sample(&mut self) -> f32 {
@@ -0,0 +1,132 @@
+//! Playback state snapshots.
+//!
+//! Lets hosts implement instant resume, rewind buffers and synchronized
+//! playback by capturing and restoring everything that evolves while a song
+//! plays.
+
+use super::effects_pipeline::EffectsPipeline;
+use super::tracker_player::TrackerPlayback;
+use super::vbl_sync::VblSync;
+use super::ym_player::YmPlayer;
+use crate::{ReplayerError, Result};
+use ym2149::Ym2149State;
+
+/// Snapshot of a [`YmPlayer`]'s playback state
+///
+/// Captured by [`YmPlayer::save_state`] and restored with
+/// [`YmPlayer::load_state`] into a player that has the same song loaded.
+/// Holds the chip, the frame position and the effect state, so playback
+/// continues sample-exactly. Song data and playback settings (play/pause
+/// state, mutes, gain, rate, output model) are not included.
+///
+/// With the `serde` feature the state can be serialized.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct YmPlayerState {
+    frame_count: usize,
+    chip: Ym2149State,
+    vbl: VblSync,
+    current_frame: usize,
+    samples_in_frame: u32,
+    effects: EffectsPipeline,
+    tracker: Option<TrackerPlayback>,
+    first_frame_pre_loaded: bool,
+    prev_r13: Option<u8>,
+}
+
+impl YmPlayer {
+    /// Capture the playback state (see [`YmPlayerState`]).
+    pub fn save_state(&self) -> YmPlayerState {
+        YmPlayerState {
+            frame_count: self.frame_count(),
+            chip: self.chip.save_state(),
+            vbl: self.vbl.clone(),
+            current_frame: self.sequencer.current_frame(),
+            samples_in_frame: self.sequencer.samples_into_frame(),
+            effects: self.effects.clone(),
+            tracker: self.tracker.as_ref().map(|tracker| tracker.playback()),
+            first_frame_pre_loaded: self.first_frame_pre_loaded,
+            prev_r13: self.prev_r13,
+        }
+    }
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    ///
+    /// Fails without changing anything if the state was saved from a
+    /// different song.
+    pub fn load_state(&mut self, state: &YmPlayerState) -> Result<()> {
+        let same_song = state.frame_count == self.frame_count()
+            && state.tracker.is_some() == self.tracker.is_some();
+        if !same_song {
+            return Err(ReplayerError::Other(
+                "state was saved from a different song".to_string(),
+            ));
+        }
+        if let (Some(tracker), Some(saved)) = (self.tracker.as_mut(), &state.tracker)
+            && !tracker.restore_playback(saved)
+        {
+            return Err(ReplayerError::Other(
+                "state was saved from a different song".to_string(),
+            ));
+        }
+
+        self.chip.load_state(&state.chip);
+        self.vbl.clone_from(&state.vbl);
+        self.sequencer
+            .restore_position(state.current_frame, state.samples_in_frame);
+        let precision = self.effects.precision();
+        self.effects.clone_from(&state.effects);
+        self.effects.set_precision(precision);
+        self.first_frame_pre_loaded = state.first_frame_pre_loaded;
+        self.prev_r13 = state.prev_r13;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlaybackController;
+
+    fn song(frames: usize) -> YmPlayer {
+        let mut player = YmPlayer::new();
+        let frames = (0..frames)
+            .map(|index| {
+                let mut regs = [0u8; 16];
+                regs[0] = (index * 37) as u8;
+                regs[1] = 1;
+                regs[6] = (index % 32) as u8;
+                regs[7] = 0x30;
+                regs[8] = 15;
+                regs[9] = 10;
+                regs
+            })
+            .collect();
+        player.load_frames(frames);
+        player.play().unwrap();
+        player
+    }
+
+    #[test]
+    fn test_load_state_resumes_identically() {
+        let mut player = song(20);
+        player.generate_samples(3_000);
+        let state = player.save_state();
+        let expected = player.generate_samples(5_000);
+        let frame = player.get_current_frame();
+
+        player.load_state(&state).unwrap();
+        assert_eq!(player.generate_samples(5_000), expected);
+        assert_eq!(player.get_current_frame(), frame);
+
+        let mut other = song(20);
+        other.load_state(&state).unwrap();
+        assert_eq!(other.generate_samples(5_000), expected);
+    }
+
+    #[test]
+    fn test_load_state_rejects_other_song() {
+        let state = song(20).save_state();
+        assert!(song(10).load_state(&state).is_err());
+    }
+}
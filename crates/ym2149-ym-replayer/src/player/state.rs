@@ -6,6 +6,7 @@
 use super::{AdvanceResult, PlaybackController, PlaybackState, ym_player::YmPlayerGeneric};
 use crate::Result;
 use ym2149::Ym2149Backend;
+use ym2149_common::RegisterDelta;
 
 impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// Set loop frame for looping playback
@@ -133,6 +134,76 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.prev_r13 = None;
         self.vbl.reset();
     }
+
+    /// Advance exactly `frames` frames while not playing.
+    ///
+    /// Renders silently up to and including the first sample of the last
+    /// stepped frame, so the returned delta shows that frame's registers.
+    /// The player is left paused (or stopped if the song ended). Returns
+    /// `None` while playing and for YM Tracker songs, which have no PSG
+    /// registers.
+    pub fn step_frames(&mut self, frames: usize) -> Option<RegisterDelta> {
+        if self.state == PlaybackState::Playing || self.is_tracker_mode || self.sequencer.is_empty()
+        {
+            return None;
+        }
+
+        let before = vec![self.dump_registers()];
+        self.state = PlaybackState::Playing;
+        let mut loaded = 0;
+        while loaded < frames && self.state == PlaybackState::Playing {
+            let loads_frame = self.sequencer.samples_into_frame() == 0;
+            self.generate_sample();
+            loaded += usize::from(loads_frame);
+        }
+        if self.state == PlaybackState::Playing {
+            self.state = PlaybackState::Paused;
+        }
+        Some(RegisterDelta::new(before, vec![self.dump_registers()]))
+    }
+
+    /// Run `frames` frames without clocking the chip.
+    ///
+    /// Each frame's registers are still written to the chip, so its register
+    /// state matches normal playback, but effects (SID, digidrums, Sync
+    /// Buzzer) are reset afterwards instead of being rendered. Works in any
+    /// state and returns the number of frames advanced, which is less than
+    /// `frames` if the song ended (the player is then stopped).
+    pub fn advance_frames(&mut self, frames: usize) -> usize {
+        if self.is_tracker_mode {
+            let Some(tracker) = self.tracker.as_mut() else {
+                return 0;
+            };
+            let mut advanced = 0;
+            while advanced < frames {
+                if !tracker.advance_frame() {
+                    self.state = PlaybackState::Stopped;
+                    break;
+                }
+                advanced += 1;
+            }
+            tracker.samples_until_update = 0.0;
+            return advanced;
+        }
+
+        if self.sequencer.is_empty() {
+            return 0;
+        }
+
+        let mut advanced = 0;
+        while advanced < frames {
+            if self.sequencer.samples_into_frame() == 0 {
+                self.load_frame_registers();
+            }
+            if self.sequencer.finish_frame() == AdvanceResult::Completed {
+                self.state = PlaybackState::Stopped;
+                break;
+            }
+            advanced += 1;
+        }
+        self.effects.reset();
+        advanced
+    }
 }
 
 impl<B: Ym2149Backend> PlaybackController for YmPlayerGeneric<B> {
@@ -4,7 +4,7 @@
 //! and Sync Buzzer effect control.
 
 use super::ym_player::YmPlayerGeneric;
-use crate::Result;
+use crate::{ReplayerError, Result};
 use ym2149::Ym2149Backend;
 
 impl<B: Ym2149Backend> YmPlayerGeneric<B> {
@@ -21,8 +21,13 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// - 1764: 25Hz at 44.1kHz
     ///
     /// # Errors
-    /// Returns error if `samples` is 0 or exceeds 10000 (which would imply < 4.41Hz frame rate).
+    /// Returns error if `samples` is 0 or exceeds 10000 (which would imply < 4.41Hz frame rate),
+    /// and [`ReplayerError::TrackerModeUnsupported`] for YM Tracker songs, which are timed
+    /// by their own player rate.
     pub fn set_samples_per_frame(&mut self, samples: u32) -> Result<()> {
+        if self.is_tracker_mode {
+            return Err(ReplayerError::TrackerModeUnsupported("Custom frame timing"));
+        }
         if samples == 0 {
             return Err("samples_per_frame cannot be zero".into());
         }
@@ -74,6 +79,10 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// # Arguments
     /// * `timer_freq` - Timer frequency in Hz (typical range: 4000-8000 Hz)
     ///
+    /// # Errors
+    /// Returns error if `timer_freq` is 0, and [`ReplayerError::TrackerModeUnsupported`]
+    /// for YM Tracker songs, which do not drive PSG effects.
+    ///
     /// # Example
     /// ```ignore
     /// // Enable Sync Buzzer at 6 kHz
@@ -81,6 +90,9 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// player.play()?;
     /// ```
     pub fn enable_sync_buzzer(&mut self, timer_freq: u32) -> Result<()> {
+        if self.is_tracker_mode {
+            return Err(ReplayerError::TrackerModeUnsupported("Sync Buzzer"));
+        }
         if timer_freq == 0 {
             return Err("Sync Buzzer timer frequency must be > 0".into());
         }
@@ -29,8 +29,26 @@ pub(crate) struct TrackerSample {
     pub repeat_len: usize,
 }
 
+/// Snapshot of one tracker voice, for visualization and inspection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrackerVoice {
+    /// Index of the sample instrument playing, if any
+    pub sample: Option<usize>,
+    /// Sample replay rate in Hz, including the YMT2 frequency shift
+    pub frequency_hz: u32,
+    /// Voice volume (0-63)
+    pub volume: u8,
+    /// Whether the sample loops over its repeat section
+    pub looping: bool,
+    /// Whether the voice is currently producing sound
+    pub active: bool,
+    /// Whether the voice is muted
+    pub muted: bool,
+}
+
 /// State for a single tracker voice
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 struct TrackerVoiceState {
     sample_index: Option<usize>,
     sample_pos: u32,
@@ -53,6 +71,37 @@ impl TrackerVoiceState {
             sample_inc: 0,
         }
     }
+
+    /// Move the sample position forward by `samples` output samples.
+    ///
+    /// Wraps into the repeat section of looping samples and stops the voice
+    /// at the end of one-shot samples, like per-sample playback does.
+    fn skip(&mut self, sample: &TrackerSample, samples: u64) {
+        if !self.running || samples == 0 {
+            return;
+        }
+        let sample_end = (sample.data.len() as u64) << YM_TRACKER_PRECISION;
+        let pos = self.sample_pos as u64 + self.sample_inc as u64 * samples;
+        if pos < sample_end {
+            self.sample_pos = pos as u32;
+        } else if self.loop_enabled && sample.repeat_len > 0 {
+            let rep = (sample.repeat_len as u64) << YM_TRACKER_PRECISION;
+            let loop_start = sample_end - rep;
+            self.sample_pos = (loop_start + (pos - loop_start) % rep) as u32;
+        } else {
+            self.running = false;
+            self.sample_pos = sample_end as u32;
+        }
+    }
+}
+
+/// Voice positions and timing of tracker playback, for state snapshots
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct TrackerPlayback {
+    voices: Vec<TrackerVoiceState>,
+    current_frame: usize,
+    samples_until_update: f64,
 }
 
 /// Complete tracker playback state
@@ -63,6 +112,7 @@ pub(crate) struct TrackerState {
     volume_table: Vec<i16>,
     freq_shift: u8,
     nb_voice: usize,
+    muted: Vec<bool>,
     pub(crate) total_frames: usize,
     pub(crate) loop_frame: usize,
     pub(crate) loop_enabled: bool,
@@ -116,6 +166,7 @@ impl TrackerState {
             volume_table,
             freq_shift,
             nb_voice,
+            muted: vec![false; nb_voice],
             total_frames,
             loop_frame,
             loop_enabled,
@@ -127,6 +178,29 @@ impl TrackerState {
         }
     }
 
+    /// Capture voice positions and timing.
+    pub(crate) fn playback(&self) -> TrackerPlayback {
+        TrackerPlayback {
+            voices: self.voices.clone(),
+            current_frame: self.current_frame,
+            samples_until_update: self.samples_until_update,
+        }
+    }
+
+    /// Restore a capture from [`playback`](Self::playback).
+    ///
+    /// Returns `false` (leaving the state untouched) if it was taken from a
+    /// song with a different voice count.
+    pub(crate) fn restore_playback(&mut self, playback: &TrackerPlayback) -> bool {
+        if playback.voices.len() != self.voices.len() {
+            return false;
+        }
+        self.voices.clone_from(&playback.voices);
+        self.current_frame = playback.current_frame.min(self.total_frames);
+        self.samples_until_update = playback.samples_until_update;
+        true
+    }
+
     /// Reset playback to beginning
     pub(crate) fn reset(&mut self) {
         self.current_frame = 0;
@@ -146,6 +220,35 @@ impl TrackerState {
         (step / self.sample_rate as u64) as u32
     }
 
+    /// Mute or unmute a voice (muted voices keep running silently)
+    pub(crate) fn set_voice_mute(&mut self, voice: usize, mute: bool) {
+        if let Some(muted) = self.muted.get_mut(voice) {
+            *muted = mute;
+        }
+    }
+
+    /// Whether a voice is muted
+    pub(crate) fn is_voice_muted(&self, voice: usize) -> bool {
+        self.muted.get(voice).copied().unwrap_or(false)
+    }
+
+    /// Snapshot of all voices
+    pub(crate) fn voices(&self) -> Vec<TrackerVoice> {
+        let shift = u32::from(self.freq_shift.min(15));
+        self.voices
+            .iter()
+            .zip(&self.muted)
+            .map(|(voice, &muted)| TrackerVoice {
+                sample: voice.sample_index,
+                frequency_hz: voice.sample_freq << shift,
+                volume: voice.sample_volume,
+                looping: voice.loop_enabled,
+                active: voice.running,
+                muted,
+            })
+            .collect()
+    }
+
     /// Advance to next frame, returns false if playback ended
     pub(crate) fn advance_frame(&mut self) -> bool {
         if self.total_frames == 0 {
@@ -160,7 +263,14 @@ impl TrackerState {
             }
         }
 
-        let start = self.current_frame.saturating_mul(self.nb_voice);
+        self.apply_frame(self.current_frame);
+        self.current_frame += 1;
+        true
+    }
+
+    /// Apply one frame of tracker lines to the voices
+    fn apply_frame(&mut self, frame: usize) {
+        let start = frame.saturating_mul(self.nb_voice);
         for voice_index in 0..self.nb_voice {
             let line = self.lines[start + voice_index];
             let freq = ((line.freq_high as u32) << 8) | (line.freq_low as u32);
@@ -207,29 +317,45 @@ impl TrackerState {
                 voice.sample_inc = 0;
             }
         }
-
-        self.current_frame += 1;
-        true
     }
 
-    /// Jump to a specific tracker frame (clamped) and reset voice state.
+    /// Jump to a specific tracker frame (clamped).
+    ///
+    /// Voices are rebuilt by replaying the lines before `frame` without mixing,
+    /// so notes and looping samples started earlier keep sounding after the seek.
     pub(crate) fn seek_frame(&mut self, frame: usize) {
-        if self.total_frames == 0 {
-            self.current_frame = 0;
+        let target = if self.total_frames == 0 {
+            0
         } else {
-            self.current_frame = frame.min(self.total_frames.saturating_sub(1));
-        }
-        self.samples_until_update = 0.0;
+            frame.min(self.total_frames.saturating_sub(1))
+        };
         for voice in &mut self.voices {
             *voice = TrackerVoiceState::new();
         }
+
+        // Mirror the sample budget of `generate_tracker_sample`
+        let mut budget = 0.0;
+        for past in 0..target {
+            self.apply_frame(past);
+            budget += self.samples_per_step;
+            let samples = budget.ceil().max(0.0);
+            budget -= samples;
+            for voice in &mut self.voices {
+                if let Some(sample) = voice.sample_index.and_then(|idx| self.samples.get(idx)) {
+                    voice.skip(sample, samples as u64);
+                }
+            }
+        }
+
+        self.current_frame = target;
+        self.samples_until_update = budget;
     }
 
     /// Mix all active voices into a single sample
     pub(crate) fn mix_sample(&mut self) -> f32 {
         let mut accumulator: i32 = 0;
 
-        for voice in &mut self.voices {
+        for (voice, &muted) in self.voices.iter_mut().zip(&self.muted) {
             if !voice.running {
                 continue;
             }
@@ -288,7 +414,9 @@ impl TrackerState {
                 base_value
             };
 
-            accumulator += blended;
+            if !muted {
+                accumulator += blended;
+            }
 
             // Advance position
             let mut new_pos = pos.wrapping_add(voice.sample_inc);
@@ -340,3 +468,90 @@ pub(crate) fn deinterleave_tracker_bytes(
     }
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::player::{PlaybackController, YmPlayer};
+
+    const FRAMES: usize = 20;
+
+    /// YMT2 song with a looping sample on voice 0 (started once, then held)
+    /// and a short one-shot sample retriggered on voice 1 every 8 frames.
+    fn ymt2_song() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(b"YMT2LeOnArD!");
+        data.extend_from_slice(&2u16.to_be_bytes()); // voices
+        data.extend_from_slice(&50u16.to_be_bytes()); // player rate
+        data.extend_from_slice(&(FRAMES as u32).to_be_bytes());
+        data.extend_from_slice(&0u32.to_be_bytes()); // loop frame
+        data.extend_from_slice(&2u16.to_be_bytes()); // samples
+        data.extend_from_slice(&0u32.to_be_bytes()); // attributes
+        data.extend_from_slice(b"Song\0Author\0\0");
+        for (size, repeat, base) in [(1000u16, 300u16, 0u8), (200, 200, 64)] {
+            data.extend_from_slice(&size.to_be_bytes());
+            data.extend_from_slice(&repeat.to_be_bytes());
+            data.extend_from_slice(&0u16.to_be_bytes());
+            data.extend((0..size).map(|i| base.wrapping_add((i * 7) as u8)));
+        }
+        for frame in 0..FRAMES {
+            let lead_note = if frame == 0 { 0 } else { 0xFF };
+            data.extend_from_slice(&[lead_note, 0x40 | 40, 0x1F, 0x40]); // 8000 Hz, looping
+            let drum_note = if frame % 8 == 0 { 1 } else { 0xFF };
+            data.extend_from_slice(&[drum_note, 50, 0x2B, 0x11]); // 11025 Hz
+        }
+        data
+    }
+
+    fn loaded_player() -> YmPlayer {
+        let mut player = YmPlayer::new();
+        player.load_data(&ymt2_song()).unwrap();
+        player.play().unwrap();
+        player
+    }
+
+    #[test]
+    fn test_seek_matches_continuous_playback() {
+        let target = 11;
+        let mut continuous = loaded_player();
+        let mut skipped = vec![0.0; 882 * target];
+        continuous.generate_samples_into(&mut skipped);
+
+        let mut seeked = loaded_player();
+        seeked.seek_frame(target);
+        assert_eq!(seeked.tracker_voices(), continuous.tracker_voices());
+
+        let voices = seeked.tracker_voices();
+        assert_eq!(voices[0].sample, Some(0));
+        assert!(voices[0].active && voices[0].looping);
+        assert_eq!(voices[0].frequency_hz, 8000);
+        // Voice 1's one-shot from frame 8 ran out before frame 11
+        assert!(!voices[1].active);
+
+        let mut a = vec![0.0; 882 * 4];
+        let mut b = vec![0.0; 882 * 4];
+        continuous.generate_samples_into(&mut a);
+        seeked.generate_samples_into(&mut b);
+        assert_eq!(a, b);
+        assert!(a.iter().any(|&s| s != 0.0));
+    }
+
+    #[test]
+    fn test_channel_mute_silences_tracker_voice() {
+        let mut reference = loaded_player();
+        let mut muted = loaded_player();
+        muted.set_channel_mute(0, true);
+        assert!(muted.is_channel_muted(0) && !muted.is_channel_muted(1));
+        assert!(muted.tracker_voices()[0].muted);
+
+        let mut full = vec![0.0; 882 * 10];
+        let mut partial = vec![0.0; 882 * 10];
+        reference.generate_samples_into(&mut full);
+        muted.generate_samples_into(&mut partial);
+        // Voice 1 only sounds for the first few hundred samples of each hit
+        assert!(partial[2000..882 * 8].iter().all(|&s| s == 0.0));
+        assert!(full[2000..882 * 8].iter().any(|&s| s != 0.0));
+        assert!(partial[..200].iter().any(|&s| s != 0.0));
+        // The muted voice keeps its position, so unmuting resumes in place
+        assert!(muted.tracker_voices()[0].active);
+    }
+}
@@ -7,6 +7,7 @@ use super::TimingConfig;
 
 /// VBL Synchronization Manager
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VblSync {
     /// Timing configuration
     config: TimingConfig,
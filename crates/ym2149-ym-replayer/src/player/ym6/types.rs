@@ -1,12 +1,13 @@
 //! YM6 file format types
 
 use std::fmt;
-use std::sync::Arc;
 
+use super::super::digidrums::DigiDrumSample;
 use super::super::format_profile::FormatMode;
 
 /// Supported YM file formats handled by the loader.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum YmFileFormat {
     /// YM2 format (Mad Max).
     Ym2,
@@ -44,6 +45,7 @@ impl fmt::Display for YmFileFormat {
 
 /// Summary information returned after loading file data.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LoadSummary {
     /// Detected YM file format.
     pub format: YmFileFormat,
@@ -63,6 +65,7 @@ impl LoadSummary {
 
 /// YM6 File Metadata
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ym6Info {
     /// Song name
     pub song_name: String,
@@ -85,7 +88,7 @@ pub(in crate::player) struct PlaybackStateInit {
     pub frames: Vec<[u8; 16]>,
     pub loop_frame: Option<usize>,
     pub samples_per_frame: u32,
-    pub digidrums: Vec<Arc<[u8]>>,
+    pub digidrums: Vec<DigiDrumSample>,
     pub attributes: u32,
     pub format_mode: FormatMode,
     pub info: Option<Ym6Info>,
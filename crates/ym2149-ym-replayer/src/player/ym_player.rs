@@ -2,17 +2,18 @@
 //!
 //! Plays back YM2-YM6 format chiptune files with proper VBL synchronization.
 
-use std::sync::Arc;
-
 use super::chiptune_player::Ym6Metadata;
+use super::digidrums::{CustomDigiDrum, DigiDrumSample, DigiDrumTrigger};
+use super::effects_manager::EffectPrecision;
 use super::effects_pipeline::EffectsPipeline;
 use super::format_profile::{FormatMode, FormatProfile, create_profile};
 use super::frame_sequencer::FrameSequencer;
 use super::tracker_player::TrackerState;
-use super::ym6::{LoadSummary, Ym6Info};
+use super::ym6::{LoadSummary, Ym6Info, YmFileFormat};
 use super::{PlaybackState, TimingConfig, VblSync};
 use crate::Result;
 use ym2149::{Ym2149, Ym2149Backend};
+use ym2149_common::{CompressionInfo, OutputGain, PlaybackRate};
 
 /// Generic YM File Player
 ///
@@ -33,8 +34,12 @@ pub struct YmPlayerGeneric<B: Ym2149Backend> {
     pub(in crate::player) info: Option<Ym6Info>,
     /// Cached metadata for ChiptunePlayer trait
     pub(in crate::player) cached_metadata: Ym6Metadata,
-    /// Digidrum sample bank (shared to avoid cloning in hot path)
-    pub(in crate::player) digidrums: Vec<Arc<[u8]>>,
+    /// Digidrum sample bank (sample data shared to avoid cloning in hot path)
+    pub(in crate::player) digidrums: Vec<DigiDrumSample>,
+    /// DigiDrum starts found in the frame data
+    pub(in crate::player) digidrum_triggers: Vec<DigiDrumTrigger>,
+    /// User replacements for `digidrums`, by index
+    pub(in crate::player) custom_digidrums: Vec<Option<CustomDigiDrum>>,
     /// YM6 attributes bitfield (A_* flags)
     pub(in crate::player) attributes: u32,
     /// Format-specific behavior adapter
@@ -53,6 +58,14 @@ pub struct YmPlayerGeneric<B: Ym2149Backend> {
     pub(in crate::player) first_frame_pre_loaded: bool,
     /// Cache previous R13 (envelope shape) to avoid redundant resets
     pub(in crate::player) prev_r13: Option<u8>,
+    /// Output gain applied to rendered buffers
+    pub(in crate::player) output_gain: OutputGain,
+    /// Playback rate (speed and pitch) applied to rendered buffers
+    pub(in crate::player) playback_rate: PlaybackRate,
+    /// File format of the song loaded with `load_data` (None for raw frames)
+    pub(in crate::player) file_format: Option<YmFileFormat>,
+    /// Packing of the file loaded with `load_data`
+    pub(in crate::player) compression: Option<CompressionInfo>,
 }
 
 /// Concrete YM player using hardware-accurate Ym2149 emulation
@@ -85,6 +98,8 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
             info: None,
             cached_metadata: Ym6Metadata::default(),
             digidrums: Vec::new(),
+            digidrum_triggers: Vec::new(),
+            custom_digidrums: Vec::new(),
             attributes: 0,
             format_profile: create_profile(FormatMode::Basic),
             effects: EffectsPipeline::new(sample_rate),
@@ -94,6 +109,10 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
             master_clock,
             first_frame_pre_loaded: false,
             prev_r13: None,
+            output_gain: OutputGain::default(),
+            playback_rate: PlaybackRate::default(),
+            file_format: None,
+            compression: None,
         }
     }
 
@@ -101,7 +120,11 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     pub fn set_sample_rate(&mut self, sample_rate: u32) {
         let sample_rate = sample_rate.max(1);
         self.sample_rate = sample_rate;
+        let filter_chain = self.chip.filter_chain();
+        let tap_capacity = self.chip.channel_tap().map(|tap| tap.capacity());
         self.chip = B::with_clocks(self.master_clock, sample_rate);
+        self.chip.set_filter_chain(filter_chain);
+        self.chip.set_channel_tap(tap_capacity);
         self.effects.set_sample_rate(sample_rate);
         self.vbl.set_config(TimingConfig {
             sample_rate,
@@ -113,7 +136,11 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
     /// Recreate the backend with a new master clock while preserving the current sample rate.
     pub(in crate::player) fn apply_master_clock(&mut self, master_clock: u32) {
         self.master_clock = master_clock;
+        let filter_chain = self.chip.filter_chain();
+        let tap_capacity = self.chip.channel_tap().map(|tap| tap.capacity());
         self.chip = B::with_clocks(master_clock, self.sample_rate);
+        self.chip.set_filter_chain(filter_chain);
+        self.chip.set_channel_tap(tap_capacity);
         self.vbl.set_config(TimingConfig {
             sample_rate: self.sample_rate,
             vbl_frequency: 50.0,
@@ -121,14 +148,33 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         });
     }
 
-    /// Mute or unmute a channel (0=A,1=B,2=C)
+    /// Set how precisely Sync Buzzer and SID effects follow their timers.
+    ///
+    /// [`EffectPrecision::PerTimerTick`] matches reference players more closely
+    /// on tunes with very high timer rates; the default is per-sample timing.
+    pub fn set_effect_precision(&mut self, precision: EffectPrecision) {
+        self.effects.set_precision(precision);
+    }
+
+    /// Timing precision of Sync Buzzer and SID effects.
+    pub fn effect_precision(&self) -> EffectPrecision {
+        self.effects.precision()
+    }
+
+    /// Mute or unmute a channel (0=A,1=B,2=C, or a voice index for YM Tracker songs)
     pub fn set_channel_mute(&mut self, channel: usize, mute: bool) {
-        self.chip.set_channel_mute(channel, mute);
+        match self.tracker.as_mut() {
+            Some(tracker) if self.is_tracker_mode => tracker.set_voice_mute(channel, mute),
+            _ => self.chip.set_channel_mute(channel, mute),
+        }
     }
 
     /// Check if a channel is muted
     pub fn is_channel_muted(&self, channel: usize) -> bool {
-        self.chip.is_channel_muted(channel)
+        match self.tracker.as_ref() {
+            Some(tracker) if self.is_tracker_mode => tracker.is_voice_muted(channel),
+            _ => self.chip.is_channel_muted(channel),
+        }
     }
 
     /// Dump chip registers (R0-R15)
@@ -136,6 +182,16 @@ impl<B: Ym2149Backend> YmPlayerGeneric<B> {
         self.chip.dump_registers()
     }
 
+    /// Read-only access to the chip backend, whichever implementation it is
+    pub fn backend(&self) -> &B {
+        &self.chip
+    }
+
+    /// Mutable access to the chip backend, e.g. to drain a capture log
+    pub fn backend_mut(&mut self) -> &mut B {
+        &mut self.chip
+    }
+
     /// Enable or disable the chip's color filter if supported by the backend.
     pub fn set_color_filter(&mut self, enabled: bool) {
         self.chip.set_color_filter(enabled);
@@ -188,6 +244,7 @@ impl YmPlayer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ReplayerError;
     use crate::player::{PlaybackController, YmFileFormat};
 
     #[test]
@@ -236,6 +293,29 @@ mod tests {
         assert!(player.get_current_frame() < summary.frame_count);
     }
 
+    #[test]
+    fn test_capturing_backend_logs_frame_writes() {
+        let mut player = YmPlayerGeneric::<ym2149_common::CapturingBackend>::new();
+        player.load_frames(vec![[0x0Fu8; 16]; 4]);
+        player.play().unwrap();
+
+        let samples_per_frame = 882; // 50 Hz at 44.1 kHz
+        let _ = player.generate_samples(samples_per_frame * 2);
+        let writes = player.backend_mut().take_writes();
+        assert!(!writes.is_empty());
+        assert!(writes.iter().all(|write| write.addr < 16));
+        // The second frame is written one frame after the first
+        assert!(
+            writes
+                .iter()
+                .any(|write| write.sample == samples_per_frame as u64)
+        );
+        assert_eq!(
+            player.backend().samples_clocked(),
+            samples_per_frame as u64 * 2
+        );
+    }
+
     #[test]
     fn test_ym6_player_initialization() {
         // Test that a new player initializes with correct default state
@@ -272,6 +352,75 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_playback_rate_scales_frame_progression() {
+        let mut player = Ym6Player::new();
+        player.load_frames(vec![[0u8; 16]; 100]);
+        ym2149_common::ChiptunePlayerBase::set_playback_rate(&mut player, 2.0);
+        assert_eq!(
+            ym2149_common::ChiptunePlayerBase::playback_rate(&player),
+            2.0
+        );
+
+        player.play().unwrap();
+        // 10 frames of output cover 20 frames of the song
+        let _ = player.generate_samples(8820);
+        assert_eq!(player.get_current_frame(), 20);
+    }
+
+    #[test]
+    fn test_step_frames_returns_register_delta() {
+        let mut player = Ym6Player::new();
+        let frames = (1..=10u8)
+            .map(|value| {
+                let mut regs = [0u8; 16];
+                regs[0] = value;
+                regs
+            })
+            .collect();
+        player.load_frames(frames);
+
+        let delta = player.step_frames(1).unwrap();
+        assert_eq!(delta.before[0][0], 0);
+        assert_eq!(delta.after[0][0], 1);
+        assert_eq!(player.state(), PlaybackState::Paused);
+
+        let delta = player.step_frames(2).unwrap();
+        assert_eq!(delta.changes().count(), 1);
+        assert_eq!(delta.after[0][0], 3);
+        assert_eq!(player.get_current_frame(), 2);
+
+        player.play().unwrap();
+        assert!(player.step_frames(1).is_none());
+    }
+
+    #[test]
+    fn test_advance_frames_skips_without_clocking() {
+        let mut player = YmPlayerGeneric::<ym2149_common::NullBackend>::new();
+        let frames = (1..=10u8)
+            .map(|value| {
+                let mut regs = [0u8; 16];
+                regs[0] = value;
+                regs
+            })
+            .collect();
+        player.load_frames(frames);
+
+        assert_eq!(player.advance_frames(4), 4);
+        assert_eq!(player.get_current_frame(), 4);
+        assert_eq!(player.backend().read_register(0), 4);
+        assert_eq!(player.backend().samples_clocked(), 0);
+
+        player.play().unwrap();
+        let _ = player.generate_samples(1);
+        assert_eq!(player.backend().read_register(0), 5);
+
+        // The rest of frame 4 plus frames 5-8; frame 9 ends the song
+        assert_eq!(player.advance_frames(20), 5);
+        assert_eq!(player.backend().read_register(0), 10);
+        assert_eq!(player.state(), PlaybackState::Stopped);
+    }
+
     #[test]
     fn test_ym6_player_load_frames() {
         let mut player = Ym6Player::new();
@@ -499,6 +648,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_typed_errors_for_unsupported_data() {
+        let mut player = YmPlayer::new();
+        let err = player.load_data(b"XYZ!not a song").unwrap_err();
+        assert!(matches!(err, ReplayerError::UnsupportedFormat(ref magic) if magic == "XYZ!"));
+
+        // Minimal YMT1: one voice, one frame, no digidrums, empty strings
+        let mut tracker = b"YMT1LeOnArD!".to_vec();
+        tracker.extend_from_slice(&[0, 1, 0, 50, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        tracker.extend_from_slice(&[0, 0, 0]);
+        tracker.extend_from_slice(&[0; 4]);
+        player.load_data(&tracker).unwrap();
+        assert!(matches!(
+            player.enable_sync_buzzer(6000),
+            Err(ReplayerError::TrackerModeUnsupported(_))
+        ));
+        assert!(matches!(
+            player.set_samples_per_frame(735),
+            Err(ReplayerError::TrackerModeUnsupported(_))
+        ));
+    }
+
     #[test]
     fn test_sync_buzzer_with_playback() {
         // Test that Sync Buzzer works during playback
@@ -526,4 +697,30 @@ mod tests {
             assert!(sample.is_finite());
         }
     }
+
+    #[test]
+    fn test_load_report_records_format_and_packing() {
+        use crate::YmWriter;
+        use ym2149_common::{ChiptuneFormat, ChiptunePlayer, Compression};
+
+        let writer = YmWriter::new(vec![[0u8; 16]; 100]);
+        let plain = writer.to_bytes().unwrap();
+        let packed = writer.compressed(true).to_bytes().unwrap();
+
+        let (player, _) = load_song(&packed).unwrap();
+        let report = player.load_report();
+        assert_eq!(report.family, ChiptuneFormat::Ym);
+        assert_eq!(report.format, "YM6");
+        assert_eq!(report.frame_count, Some(100));
+        assert_eq!(report.chips, 1);
+        let packing = report.compression.unwrap();
+        assert_eq!(packing.method, Compression::Lha);
+        assert_eq!(packing.packed_size, packed.len());
+        assert_eq!(packing.unpacked_size, plain.len());
+
+        let (mut player, _) = load_song(&plain).unwrap();
+        assert_eq!(player.load_report().compression, None);
+        player.load_frames(vec![[0u8; 16]; 10]);
+        assert_eq!(player.load_report().format, "YM");
+    }
 }
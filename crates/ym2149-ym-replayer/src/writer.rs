@@ -0,0 +1,375 @@
+//! YM6 file writer
+//!
+//! Serializes register frames, metadata and digidrum samples into YM6 files,
+//! optionally LHA-compressed like the files of the original YM archive. This
+//! is the inverse of [`Ym6Parser`](crate::parser::Ym6Parser) and is meant
+//! for format conversion tools and parser round-trip tests.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use ym2149_ym_replayer::YmWriter;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let frames = vec![[0u8; 16]; 500];
+//! let bytes = YmWriter::new(frames)
+//!     .song_name("Silence")
+//!     .author("Nobody")
+//!     .loop_frame(0)
+//!     .compressed(true)
+//!     .to_bytes()?;
+//! std::fs::write("silence.ym", bytes)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::path::Path;
+
+use crate::compression::compress_lha;
+use crate::parser::ATTR_STREAM_INTERLEAVED;
+use crate::parser::ym6::{Ym6ParseResult, Ym6Parser};
+use crate::player::Ym6Info;
+use crate::{ReplayerError, Result};
+
+/// Default LHA entry name used by [`YmWriter::to_bytes`]
+const DEFAULT_ENTRY_NAME: &str = "song.ym";
+
+/// Builder that emits YM6 files
+#[derive(Debug, Clone)]
+pub struct YmWriter {
+    frames: Vec<[u8; 16]>,
+    song_name: String,
+    author: String,
+    comment: String,
+    master_clock: u32,
+    frame_rate: u16,
+    loop_frame: u32,
+    digidrums: Vec<Vec<u8>>,
+    interleaved: bool,
+    compressed: bool,
+    entry_name: String,
+}
+
+impl YmWriter {
+    /// Create a writer for the given register frames
+    ///
+    /// Defaults match Atari ST dumps: 2 MHz master clock, 50 Hz frame rate,
+    /// loop to frame 0, interleaved register stream, no compression.
+    pub fn new(frames: Vec<[u8; 16]>) -> Self {
+        Self {
+            frames,
+            song_name: String::new(),
+            author: String::new(),
+            comment: String::new(),
+            master_clock: 2_000_000,
+            frame_rate: 50,
+            loop_frame: 0,
+            digidrums: Vec::new(),
+            interleaved: true,
+            compressed: false,
+            entry_name: DEFAULT_ENTRY_NAME.to_string(),
+        }
+    }
+
+    /// Set the song name
+    pub fn song_name(mut self, name: impl Into<String>) -> Self {
+        self.song_name = name.into();
+        self
+    }
+
+    /// Set the author name
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.author = author.into();
+        self
+    }
+
+    /// Set the song comment
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = comment.into();
+        self
+    }
+
+    /// Copy song name, author, comment, clock, frame rate and loop frame from `info`
+    pub fn info(self, info: &Ym6Info) -> Self {
+        self.song_name(info.song_name.clone())
+            .author(info.author.clone())
+            .comment(info.comment.clone())
+            .master_clock(info.master_clock)
+            .frame_rate(info.frame_rate)
+            .loop_frame(info.loop_frame)
+    }
+
+    /// Set the YM master clock in Hz
+    pub fn master_clock(mut self, hz: u32) -> Self {
+        self.master_clock = hz;
+        self
+    }
+
+    /// Set the player frame rate in Hz
+    pub fn frame_rate(mut self, hz: u16) -> Self {
+        self.frame_rate = hz;
+        self
+    }
+
+    /// Set the loop frame (must be smaller than the frame count)
+    pub fn loop_frame(mut self, frame: u32) -> Self {
+        self.loop_frame = frame;
+        self
+    }
+
+    /// Set the digidrum samples (8-bit unsigned, referenced by YM6 effects)
+    pub fn digidrums(mut self, samples: Vec<Vec<u8>>) -> Self {
+        self.digidrums = samples;
+        self
+    }
+
+    /// Store registers interleaved (all R0 values, then all R1, ...)
+    ///
+    /// Interleaved streams compress much better and are the YM6 default.
+    pub fn interleaved(mut self, enable: bool) -> Self {
+        self.interleaved = enable;
+        self
+    }
+
+    /// Wrap the YM6 data in an `-lh5-` LHA archive
+    pub fn compressed(mut self, enable: bool) -> Self {
+        self.compressed = enable;
+        self
+    }
+
+    /// Set the file name stored inside the LHA archive (default `song.ym`)
+    pub fn entry_name(mut self, name: impl Into<String>) -> Self {
+        self.entry_name = name.into();
+        self
+    }
+
+    /// Serialize to YM6 bytes (LHA-compressed if enabled)
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let ym6 = self.encode_ym6()?;
+        if self.compressed {
+            compress_lha(&self.entry_name, &ym6)
+        } else {
+            Ok(ym6)
+        }
+    }
+
+    /// Write the YM6 file to disk
+    ///
+    /// When compressed, the archive entry is named after the output file.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let bytes = if self.compressed {
+            let entry_name = path
+                .file_stem()
+                .map(|stem| format!("{}.ym", stem.to_string_lossy()))
+                .unwrap_or_else(|| self.entry_name.clone());
+            compress_lha(&entry_name, &self.encode_ym6()?)?
+        } else {
+            self.encode_ym6()?
+        };
+        std::fs::write(path, bytes)?;
+        Ok(())
+    }
+
+    fn encode_ym6(&self) -> Result<Vec<u8>> {
+        let frame_count = self.frames.len();
+        if frame_count == 0 {
+            return Err(ReplayerError::ConfigError(
+                "YM6 requires at least one frame".to_string(),
+            ));
+        }
+        if frame_count > Ym6Parser::MAX_REASONABLE_FRAMES as usize {
+            return Err(ReplayerError::ConfigError(format!(
+                "YM6 frame count {frame_count} exceeds limit of {}",
+                Ym6Parser::MAX_REASONABLE_FRAMES
+            )));
+        }
+        if self.loop_frame as usize >= frame_count {
+            return Err(ReplayerError::ConfigError(format!(
+                "Loop frame {} is beyond the last frame ({})",
+                self.loop_frame,
+                frame_count - 1
+            )));
+        }
+        let digidrum_count = u16::try_from(self.digidrums.len()).map_err(|_| {
+            ReplayerError::ConfigError(format!("Too many digidrums ({})", self.digidrums.len()))
+        })?;
+
+        let attributes = if self.interleaved {
+            ATTR_STREAM_INTERLEAVED
+        } else {
+            0
+        };
+
+        let digidrum_bytes: usize = self.digidrums.iter().map(|s| s.len() + 4).sum();
+        let mut data = Vec::with_capacity(34 + digidrum_bytes + frame_count * 16 + 256);
+
+        // Header (34 bytes)
+        data.extend_from_slice(b"YM6!");
+        data.extend_from_slice(b"LeOnArD!");
+        data.extend_from_slice(&(frame_count as u32).to_be_bytes());
+        data.extend_from_slice(&attributes.to_be_bytes());
+        data.extend_from_slice(&digidrum_count.to_be_bytes());
+        data.extend_from_slice(&self.master_clock.to_be_bytes());
+        data.extend_from_slice(&self.frame_rate.to_be_bytes());
+        data.extend_from_slice(&self.loop_frame.to_be_bytes());
+        data.extend_from_slice(&0u16.to_be_bytes()); // No extra data
+
+        // Digidrum samples
+        for sample in &self.digidrums {
+            let size = u32::try_from(sample.len()).map_err(|_| {
+                ReplayerError::ConfigError(format!("Digidrum too large ({} bytes)", sample.len()))
+            })?;
+            data.extend_from_slice(&size.to_be_bytes());
+            data.extend_from_slice(sample);
+        }
+
+        // Metadata
+        for text in [&self.song_name, &self.author, &self.comment] {
+            write_nt_string(&mut data, text)?;
+        }
+
+        // Register data
+        if self.interleaved {
+            for reg in 0..16 {
+                data.extend(self.frames.iter().map(|frame| frame[reg]));
+            }
+        } else {
+            data.extend(self.frames.iter().flatten());
+        }
+
+        data.extend_from_slice(b"End!");
+        Ok(data)
+    }
+}
+
+impl From<Ym6ParseResult> for YmWriter {
+    /// Rebuild a writer from [`Ym6Parser::parse_full`] output
+    fn from((frames, header, metadata, digidrums): Ym6ParseResult) -> Self {
+        YmWriter::new(frames)
+            .song_name(metadata.song_name)
+            .author(metadata.author)
+            .comment(metadata.comment)
+            .master_clock(header.master_clock)
+            .frame_rate(header.frame_rate)
+            .loop_frame(header.loop_frame)
+            .interleaved(header.attributes & ATTR_STREAM_INTERLEAVED != 0)
+            .digidrums(digidrums.iter().map(|sample| sample.to_vec()).collect())
+    }
+}
+
+/// Write a null-terminated Latin-1 string (the encoding the parser reads back)
+fn write_nt_string(data: &mut Vec<u8>, text: &str) -> Result<()> {
+    if text.contains('\0') {
+        return Err(ReplayerError::ConfigError(format!(
+            "YM6 metadata must not contain NUL characters: {text:?}"
+        )));
+    }
+    data.extend(text.chars().map(|c| u8::try_from(c).unwrap_or(b'?')));
+    data.push(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PlaybackController, load_song};
+
+    fn test_frames(count: usize) -> Vec<[u8; 16]> {
+        (0..count)
+            .map(|i| {
+                let mut frame = [0u8; 16];
+                for (reg, value) in frame.iter_mut().enumerate() {
+                    *value = (i * 7 + reg * 13) as u8;
+                }
+                frame[7] = 0x38;
+                frame
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_writer_round_trip_through_parser() {
+        for interleaved in [true, false] {
+            let frames = test_frames(40);
+            let bytes = YmWriter::new(frames.clone())
+                .song_name("Title")
+                .author("Author")
+                .comment("Comment")
+                .master_clock(1_000_000)
+                .frame_rate(60)
+                .loop_frame(12)
+                .interleaved(interleaved)
+                .digidrums(vec![vec![0x10, 0x80, 0xFF], vec![0x7F; 5]])
+                .to_bytes()
+                .unwrap();
+
+            let (parsed, header, metadata, digidrums) = Ym6Parser.parse_full(&bytes).unwrap();
+            assert_eq!(parsed, frames);
+            assert_eq!(header.master_clock, 1_000_000);
+            assert_eq!(header.frame_rate, 60);
+            assert_eq!(header.loop_frame, 12);
+            assert_eq!(metadata.song_name, "Title");
+            assert_eq!(metadata.author, "Author");
+            assert_eq!(metadata.comment, "Comment");
+            assert_eq!(digidrums.len(), 2);
+            assert_eq!(&*digidrums[0], &[0x10, 0x80, 0xFF]);
+
+            // Parsing and writing again is lossless
+            let rewritten = YmWriter::from((parsed, header, metadata, digidrums))
+                .to_bytes()
+                .unwrap();
+            assert_eq!(rewritten, bytes);
+        }
+    }
+
+    #[test]
+    fn test_writer_compressed_output_loads() {
+        let frames = test_frames(300);
+        let plain = YmWriter::new(frames.clone())
+            .song_name("Packed")
+            .to_bytes()
+            .unwrap();
+        let packed = YmWriter::new(frames)
+            .song_name("Packed")
+            .compressed(true)
+            .to_bytes()
+            .unwrap();
+
+        assert!(crate::compression::is_lha_compressed(&packed));
+        assert!(packed.len() < plain.len());
+        assert_eq!(crate::decompress_if_needed(&packed).unwrap(), plain);
+
+        let (player, summary) = load_song(&packed).unwrap();
+        assert_eq!(summary.frame_count, 300);
+        assert_eq!(player.info().unwrap().song_name, "Packed");
+        assert_eq!(player.state(), crate::PlaybackState::Stopped);
+    }
+
+    #[test]
+    fn test_writer_latin1_metadata() {
+        let bytes = YmWriter::new(test_frames(1))
+            .author("Jochen Hippel – Mad Max ö")
+            .to_bytes()
+            .unwrap();
+        let (_, _, metadata, _) = Ym6Parser.parse_full(&bytes).unwrap();
+        assert_eq!(metadata.author, "Jochen Hippel ? Mad Max ö");
+    }
+
+    #[test]
+    fn test_writer_rejects_invalid_input() {
+        assert!(YmWriter::new(Vec::new()).to_bytes().is_err());
+        assert!(
+            YmWriter::new(test_frames(4))
+                .loop_frame(4)
+                .to_bytes()
+                .is_err()
+        );
+        assert!(
+            YmWriter::new(test_frames(4))
+                .comment("bad\0comment")
+                .to_bytes()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,33 @@
+//! Mutated real files must be rejected with an error, never a panic.
+
+use ym2149_common::ChiptunePlayerBase;
+use ym2149_common::test_support::{mutants, read_asset};
+use ym2149_ym_replayer::{Ym6Parser, YmParser, decompress_if_needed, load_song};
+
+const SONGS: &[&str] = &["Steps.ym", "Iceage (digi).ym", "Scout.ym"];
+
+#[test]
+fn mutated_ym_files_never_panic() {
+    for &name in SONGS {
+        let Some(packed) = read_asset(name) else {
+            continue;
+        };
+        // The LHA layer first, then the unpacked song
+        for data in mutants(&packed, 30) {
+            let _ = decompress_if_needed(&data);
+        }
+
+        let song = decompress_if_needed(&packed).expect("asset unpacks");
+        for data in mutants(&song, 60) {
+            let _ = YmParser.parse_full(&data);
+            let _ = Ym6Parser.parse_full(&data);
+            if let Ok((mut player, _)) = load_song(&data) {
+                player.play();
+                let mut buffer = [0.0f32; 882];
+                for _ in 0..4 {
+                    player.generate_samples_into(&mut buffer);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Sample generation must not allocate once playback is running.
+
+use ym2149_common::test_support::read_asset;
+use ym2149_common::{ChiptunePlayerBase, CountingAllocator, allocations_per_second};
+use ym2149_ym_replayer::{decompress_if_needed, load_song};
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Songs covering plain frames, SID voices and digidrums.
+const SONGS: &[&str] = &["Steps.ym", "Iceage (digi).ym", "Scout.ym", "Digizak.ym"];
+
+#[test]
+fn playback_does_not_allocate() {
+    for &name in SONGS {
+        let Some(packed) = read_asset(name) else {
+            continue;
+        };
+        let song = decompress_if_needed(&packed).expect("asset unpacks");
+        let (mut player, _) = load_song(&song).expect("asset loads");
+        player.play();
+
+        assert_eq!(
+            allocations_per_second(&mut player, 882, 5),
+            0.0,
+            "{name} allocated while rendering"
+        );
+    }
+}
+
+#[test]
+fn changed_playback_rate_does_not_allocate() {
+    let Some(packed) = read_asset("Steps.ym") else {
+        return;
+    };
+    let song = decompress_if_needed(&packed).expect("asset unpacks");
+    let (mut player, _) = load_song(&song).expect("asset loads");
+    player.set_playback_rate(1.5);
+    player.play();
+    // The resampler sizes its scratch buffer on the first block
+    player.generate_samples_into(&mut [0.0; 882]);
+
+    assert_eq!(allocations_per_second(&mut player, 882, 2), 0.0);
+}
@@ -0,0 +1,79 @@
+//! Backend-independent Z80 interface.
+
+use crate::error::Z80Error;
+
+/// Memory and I/O as the Z80 sees them.
+///
+/// Every memory access of an instruction goes through [`peek`](Self::peek)
+/// or [`poke`](Self::poke), in bus order, so wrappers can time them.
+pub trait Z80Bus {
+    /// Read a byte from memory.
+    fn peek(&self, address: u16) -> u8;
+
+    /// Write a byte to memory.
+    fn poke(&mut self, address: u16, value: u8);
+
+    /// Read from the I/O port `address` (the full 16-bit bus value).
+    fn port_in(&mut self, address: u16) -> u8;
+
+    /// Write to the I/O port `address` (the full 16-bit bus value).
+    fn port_out(&mut self, address: u16, value: u8);
+}
+
+/// 16-bit register pairs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterPair {
+    /// Accumulator and flags.
+    AF,
+    /// BC.
+    BC,
+    /// DE.
+    DE,
+    /// HL.
+    HL,
+    /// Index register IX.
+    IX,
+    /// Index register IY.
+    IY,
+    /// Stack pointer.
+    SP,
+}
+
+/// Unified Z80 CPU interface.
+///
+/// Backends execute whole instructions and report their uncontended
+/// length; memory timing is added by [`Z80`](crate::Z80).
+pub trait Z80Cpu {
+    /// Create a new CPU instance in reset state.
+    fn new() -> Self;
+
+    /// Execute a single instruction and return the T-states it took.
+    fn step<B: Z80Bus + ?Sized>(&mut self, bus: &mut B) -> u32;
+
+    /// Get the program counter.
+    fn pc(&self) -> u16;
+
+    /// Set the program counter.
+    fn set_pc(&mut self, pc: u16);
+
+    /// Get a register pair.
+    fn register(&self, pair: RegisterPair) -> u16;
+
+    /// Set a register pair.
+    fn set_register(&mut self, pair: RegisterPair, value: u16);
+
+    /// Set the interrupt vector register I.
+    fn set_i(&mut self, value: u8);
+
+    /// Whether a HALT instruction is waiting for an interrupt.
+    fn is_halted(&self) -> bool;
+
+    /// Get the total number of T-states executed since reset.
+    fn total_cycles(&self) -> u64;
+
+    /// Capture the CPU state as an opaque byte image.
+    fn save_state(&self) -> Vec<u8>;
+
+    /// Restore a state captured by [`save_state`](Self::save_state).
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Z80Error>;
+}
@@ -0,0 +1,11 @@
+//! Error handling for the Z80 core.
+
+use thiserror::Error;
+
+/// Errors reported by a Z80 backend.
+#[derive(Debug, Error)]
+pub enum Z80Error {
+    /// A saved CPU state could not be restored.
+    #[error("invalid CPU state: {0}")]
+    InvalidState(String),
+}
@@ -0,0 +1,96 @@
+//! Z80 backend built on the `iz80` crate.
+
+use iz80::{Cpu, Machine, Reg8, Reg16};
+
+use crate::cpu::{RegisterPair, Z80Bus, Z80Cpu};
+use crate::error::Z80Error;
+
+/// [`Z80Cpu`] backed by [`iz80`](https://crates.io/crates/iz80).
+pub struct Iz80Backend {
+    cpu: Cpu,
+}
+
+/// Presents a [`Z80Bus`] as an `iz80` machine.
+struct BusAdapter<'a, B: ?Sized>(&'a mut B);
+
+impl<B: Z80Bus + ?Sized> Machine for BusAdapter<'_, B> {
+    fn peek(&self, address: u16) -> u8 {
+        self.0.peek(address)
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        self.0.poke(address, value);
+    }
+
+    fn port_in(&mut self, address: u16) -> u8 {
+        self.0.port_in(address)
+    }
+
+    fn port_out(&mut self, address: u16, value: u8) {
+        self.0.port_out(address, value);
+    }
+}
+
+fn iz80_pair(pair: RegisterPair) -> Reg16 {
+    match pair {
+        RegisterPair::AF => Reg16::AF,
+        RegisterPair::BC => Reg16::BC,
+        RegisterPair::DE => Reg16::DE,
+        RegisterPair::HL => Reg16::HL,
+        RegisterPair::IX => Reg16::IX,
+        RegisterPair::IY => Reg16::IY,
+        RegisterPair::SP => Reg16::SP,
+    }
+}
+
+impl Z80Cpu for Iz80Backend {
+    fn new() -> Self {
+        Self { cpu: Cpu::new() }
+    }
+
+    fn step<B: Z80Bus + ?Sized>(&mut self, bus: &mut B) -> u32 {
+        let before = self.cpu.cycle_count();
+        self.cpu.execute_instruction(&mut BusAdapter(bus));
+        self.cpu.cycle_count().wrapping_sub(before) as u32
+    }
+
+    fn pc(&self) -> u16 {
+        self.cpu.immutable_registers().pc()
+    }
+
+    fn set_pc(&mut self, pc: u16) {
+        self.cpu.registers().set_pc(pc);
+    }
+
+    fn register(&self, pair: RegisterPair) -> u16 {
+        self.cpu.immutable_registers().get16(iz80_pair(pair))
+    }
+
+    fn set_register(&mut self, pair: RegisterPair, value: u16) {
+        self.cpu.registers().set16(iz80_pair(pair), value);
+    }
+
+    fn set_i(&mut self, value: u8) {
+        self.cpu.registers().set8(Reg8::I, value);
+    }
+
+    fn is_halted(&self) -> bool {
+        self.cpu.is_halted()
+    }
+
+    fn total_cycles(&self) -> u64 {
+        self.cpu.cycle_count()
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        self.cpu.serialize()
+    }
+
+    fn load_state(&mut self, state: &[u8]) -> Result<(), Z80Error> {
+        let mut cpu = Cpu::new();
+        cpu.deserialize(state)
+            .map_err(|err| Z80Error::InvalidState(err.to_string()))?;
+        self.cpu = cpu;
+        Ok(())
+    }
+}
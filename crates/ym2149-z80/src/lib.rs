@@ -0,0 +1,57 @@
+//! Z80 CPU core with configurable machine timing.
+//!
+//! Shared by the replayers that run native Z80 player code. The CPU sits
+//! behind the [`Z80Cpu`] trait (the default backend wraps
+//! [`iz80`](https://crates.io/crates/iz80)); [`Z80`] adds the machine's
+//! frame timing on top:
+//!
+//! - [`Timing::ZX48`] / [`Timing::ZX128`] - Spectrum frame lengths with ULA
+//!   memory and I/O contention
+//! - [`Timing::uncontended`] - any other machine
+//!
+//! Contention makes code in the lower 16K of RAM run slower while the
+//! screen is drawn, which timing-sensitive players (digidrums, multi-call
+//! routines) depend on to stay in step with the 50 Hz interrupt.
+//!
+//! # Example
+//!
+//! ```
+//! use ym2149_z80::{Timing, Z80, Z80Bus, Z80Cpu};
+//!
+//! struct Ram(Vec<u8>);
+//!
+//! impl Z80Bus for Ram {
+//!     fn peek(&self, address: u16) -> u8 {
+//!         self.0[address as usize]
+//!     }
+//!     fn poke(&mut self, address: u16, value: u8) {
+//!         self.0[address as usize] = value;
+//!     }
+//!     fn port_in(&mut self, _address: u16) -> u8 {
+//!         0xFF
+//!     }
+//!     fn port_out(&mut self, _address: u16, _value: u8) {}
+//! }
+//!
+//! let mut ram = Ram(vec![0; 0x10000]);
+//! let mut z80: Z80 = Z80::new(Timing::ZX48);
+//! z80.cpu_mut().set_pc(0x8000);
+//! assert_eq!(z80.step(&mut ram), 4);
+//! ```
+
+#![warn(missing_docs)]
+
+mod cpu;
+mod error;
+mod iz80_impl;
+mod timing;
+mod z80;
+
+pub use cpu::{RegisterPair, Z80Bus, Z80Cpu};
+pub use error::Z80Error;
+pub use iz80_impl::Iz80Backend;
+pub use timing::{Contention, Timing};
+pub use z80::Z80;
+
+/// Default CPU backend type alias.
+pub type DefaultZ80 = Iz80Backend;
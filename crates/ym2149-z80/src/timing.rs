@@ -0,0 +1,159 @@
+//! Frame length and ZX Spectrum memory contention.
+//!
+//! While the ULA fetches the screen it holds the CPU off the lower 16K of
+//! RAM (0x4000-0x7FFF). An access there during the 128 fetch T-states of a
+//! display line waits for the ULA, following the 6,5,4,3,2,1,0,0 pattern.
+//! I/O to even ports (the ULA) and to ports whose high byte falls in the
+//! contended range is delayed the same way.
+
+use std::ops::RangeInclusive;
+
+/// Contended address range.
+const CONTENDED: RangeInclusive<u16> = 0x4000..=0x7FFF;
+/// Extra T-states by position within each 8 T-state fetch group.
+const DELAY_PATTERN: [u32; 8] = [6, 5, 4, 3, 2, 1, 0, 0];
+/// T-states of each display line during which the ULA fetches.
+const FETCH_T_STATES: u32 = 128;
+
+/// When the ULA contends memory within a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contention {
+    /// Frame T-state of the first contended cycle.
+    pub first_t_state: u32,
+    /// T-states per scan line.
+    pub line_t_states: u32,
+    /// Display lines fetched per frame.
+    pub lines: u32,
+}
+
+impl Contention {
+    /// ZX Spectrum 48K.
+    pub const ZX48: Self = Self {
+        first_t_state: 14_335,
+        line_t_states: 224,
+        lines: 192,
+    };
+
+    /// ZX Spectrum 128K and +2.
+    pub const ZX128: Self = Self {
+        first_t_state: 14_361,
+        line_t_states: 228,
+        lines: 192,
+    };
+
+    /// Whether accesses to `address` are contended.
+    pub fn is_contended(address: u16) -> bool {
+        CONTENDED.contains(&address)
+    }
+
+    /// T-states a contended access starting at frame T-state `t_state` waits.
+    pub fn delay(&self, t_state: u32) -> u32 {
+        let Some(offset) = t_state.checked_sub(self.first_t_state) else {
+            return 0;
+        };
+        let line_t_states = self.line_t_states.max(1);
+        if offset / line_t_states >= self.lines {
+            return 0;
+        }
+        let in_line = offset % line_t_states;
+        if in_line >= FETCH_T_STATES {
+            return 0;
+        }
+        DELAY_PATTERN[(in_line % 8) as usize]
+    }
+
+    /// T-states an I/O cycle to `port` starting at frame T-state `t_state`
+    /// waits.
+    ///
+    /// The cycle is split into 1 and 3 T-state parts for the ULA, and into
+    /// four single T-states when only the high byte is contended.
+    pub fn io_delay(&self, t_state: u32, port: u16) -> u32 {
+        let high_contended = Self::is_contended(port);
+        let ula = port & 1 == 0;
+        match (high_contended, ula) {
+            (false, false) => 0,
+            (false, true) => self.delay(t_state + 1),
+            (true, true) => {
+                let first = self.delay(t_state);
+                first + self.delay(t_state + first + 1)
+            }
+            (true, false) => (0..4).fold(0, |delay, cycle| {
+                delay + self.delay(t_state + delay + cycle)
+            }),
+        }
+    }
+}
+
+/// Frame length and contention of a machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    /// T-states between two frame interrupts.
+    pub frame_t_states: u32,
+    /// Memory contention, if the machine has any.
+    pub contention: Option<Contention>,
+}
+
+impl Timing {
+    /// ZX Spectrum 48K: 312 lines of 224 T-states.
+    pub const ZX48: Self = Self {
+        frame_t_states: 69_888,
+        contention: Some(Contention::ZX48),
+    };
+
+    /// ZX Spectrum 128K and +2: 311 lines of 228 T-states.
+    pub const ZX128: Self = Self {
+        frame_t_states: 70_908,
+        contention: Some(Contention::ZX128),
+    };
+
+    /// A machine without contention whose frames last `frame_t_states`.
+    pub const fn uncontended(frame_t_states: u32) -> Self {
+        Self {
+            frame_t_states,
+            contention: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delay_follows_fetch_pattern() {
+        let contention = Contention::ZX48;
+        let first = contention.first_t_state;
+        assert_eq!(contention.delay(first - 1), 0);
+        let line: Vec<u32> = (0..8).map(|t| contention.delay(first + t)).collect();
+        assert_eq!(line, DELAY_PATTERN);
+        assert_eq!(contention.delay(first + 127), 0);
+        // Border and retrace of the line are free
+        assert_eq!(contention.delay(first + 128), 0);
+        assert_eq!(contention.delay(first + 224), 6);
+        // Nothing after the last display line
+        assert_eq!(contention.delay(first + 192 * 224), 0);
+    }
+
+    #[test]
+    fn test_io_delay_by_port() {
+        let contention = Contention::ZX48;
+        let first = contention.first_t_state;
+        // AY ports are never contended
+        assert_eq!(contention.io_delay(first, 0xFFFD), 0);
+        assert_eq!(contention.io_delay(first, 0xBFFD), 0);
+        // ULA port with an uncontended high byte: N:1, C:3
+        assert_eq!(contention.io_delay(first, 0xFEFE), 5);
+        // ULA port with a contended high byte: C:1, C:3
+        assert_eq!(contention.io_delay(first, 0x40FE), 6);
+        // Contended high byte only: C:1 four times
+        assert_eq!(contention.io_delay(first, 0x40FF), 12);
+    }
+
+    #[test]
+    fn test_contended_range() {
+        assert!(!Contention::is_contended(0x3FFF));
+        assert!(Contention::is_contended(0x4000));
+        assert!(Contention::is_contended(0x7FFF));
+        assert!(!Contention::is_contended(0x8000));
+    }
+}
@@ -0,0 +1,232 @@
+//! Z80 with machine timing.
+
+use std::cell::Cell;
+
+use crate::DefaultZ80;
+use crate::cpu::{Z80Bus, Z80Cpu};
+use crate::timing::{Contention, Timing};
+
+/// A Z80 backend that keeps track of its position in the video frame.
+///
+/// With contended timing each instruction is lengthened by the T-states
+/// its memory and I/O accesses wait for the ULA. The backend reports
+/// accesses but not when they happen, so they are placed in bus order: the
+/// opcode fetch at the start of the instruction, every later access three
+/// T-states after the previous one. Internal cycles (e.g. the extra T-state
+/// of `PUSH`) are not split out, which can shift a wait by a few T-states
+/// but keeps the frame total close to the hardware.
+pub struct Z80<C: Z80Cpu = DefaultZ80> {
+    cpu: C,
+    timing: Timing,
+    frame_t_state: u32,
+}
+
+impl<C: Z80Cpu> Z80<C> {
+    /// Create a CPU in reset state at the start of a frame.
+    pub fn new(timing: Timing) -> Self {
+        Self {
+            cpu: C::new(),
+            timing,
+            frame_t_state: 0,
+        }
+    }
+
+    /// Reset the CPU and restart the frame, keeping the timing.
+    pub fn reset(&mut self) {
+        self.cpu = C::new();
+        self.frame_t_state = 0;
+    }
+
+    /// The backend (immutable).
+    pub fn cpu(&self) -> &C {
+        &self.cpu
+    }
+
+    /// The backend (mutable).
+    pub fn cpu_mut(&mut self) -> &mut C {
+        &mut self.cpu
+    }
+
+    /// Frame length and contention in use.
+    pub fn timing(&self) -> Timing {
+        self.timing
+    }
+
+    /// Change the timing; takes effect with the next instruction.
+    pub fn set_timing(&mut self, timing: Timing) {
+        self.timing = timing;
+    }
+
+    /// T-states since the last frame interrupt.
+    pub fn frame_t_state(&self) -> u32 {
+        self.frame_t_state
+    }
+
+    /// Mark the frame interrupt: the frame position returns to T-state 0.
+    pub fn start_frame(&mut self) {
+        self.frame_t_state = 0;
+    }
+
+    /// Execute a single instruction and return the T-states it took,
+    /// contention included.
+    pub fn step<B: Z80Bus + ?Sized>(&mut self, bus: &mut B) -> u32 {
+        let frame_t_states = self.timing.frame_t_states.max(1);
+        let cycles = match self.timing.contention {
+            None => self.cpu.step(bus),
+            Some(contention) => {
+                let mut contended =
+                    ContendedBus::new(bus, contention, frame_t_states, self.frame_t_state);
+                let cycles = self.cpu.step(&mut contended);
+                cycles + contended.delay.get()
+            }
+        };
+        self.frame_t_state = (self.frame_t_state + cycles) % frame_t_states;
+        cycles
+    }
+}
+
+/// Adds up the waits of one instruction's accesses.
+struct ContendedBus<'a, B: ?Sized> {
+    bus: &'a mut B,
+    contention: Contention,
+    frame_t_states: u32,
+    /// Frame T-state of the next access.
+    t_state: Cell<u32>,
+    delay: Cell<u32>,
+    fetched: Cell<bool>,
+}
+
+impl<'a, B: Z80Bus + ?Sized> ContendedBus<'a, B> {
+    fn new(bus: &'a mut B, contention: Contention, frame_t_states: u32, t_state: u32) -> Self {
+        Self {
+            bus,
+            contention,
+            frame_t_states,
+            t_state: Cell::new(t_state),
+            delay: Cell::new(0),
+            fetched: Cell::new(false),
+        }
+    }
+
+    fn wait(&self, delay: u32, cycle_t_states: u32) {
+        self.delay.set(self.delay.get() + delay);
+        self.t_state
+            .set(self.t_state.get() + delay + cycle_t_states);
+    }
+
+    fn memory_access(&self, address: u16) {
+        let delay = if Contention::is_contended(address) {
+            self.contention
+                .delay(self.t_state.get() % self.frame_t_states)
+        } else {
+            0
+        };
+        let cycle_t_states = if self.fetched.replace(true) { 3 } else { 4 };
+        self.wait(delay, cycle_t_states);
+    }
+
+    fn io_access(&self, port: u16) {
+        let delay = self
+            .contention
+            .io_delay(self.t_state.get() % self.frame_t_states, port);
+        self.wait(delay, 4);
+    }
+}
+
+impl<B: Z80Bus + ?Sized> Z80Bus for ContendedBus<'_, B> {
+    fn peek(&self, address: u16) -> u8 {
+        self.memory_access(address);
+        self.bus.peek(address)
+    }
+
+    fn poke(&mut self, address: u16, value: u8) {
+        self.memory_access(address);
+        self.bus.poke(address, value);
+    }
+
+    fn port_in(&mut self, address: u16) -> u8 {
+        self.io_access(address);
+        self.bus.port_in(address)
+    }
+
+    fn port_out(&mut self, address: u16, value: u8) {
+        self.io_access(address);
+        self.bus.port_out(address, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cpu::RegisterPair;
+
+    /// Flat 64K of NOPs with ports that read 0xFF.
+    struct Ram([u8; 0x10000]);
+
+    impl Z80Bus for Ram {
+        fn peek(&self, address: u16) -> u8 {
+            self.0[address as usize]
+        }
+
+        fn poke(&mut self, address: u16, value: u8) {
+            self.0[address as usize] = value;
+        }
+
+        fn port_in(&mut self, _address: u16) -> u8 {
+            0xFF
+        }
+
+        fn port_out(&mut self, _address: u16, _value: u8) {}
+    }
+
+    /// A 48K CPU at frame T-state `t_state`, about to execute at `pc`.
+    fn z80_at(t_state: u32, pc: u16) -> Z80 {
+        let mut z80: Z80 = Z80::new(Timing::ZX48);
+        z80.frame_t_state = t_state;
+        z80.cpu_mut().set_pc(pc);
+        z80
+    }
+
+    #[test]
+    fn test_nop_in_contended_memory_waits_for_ula() {
+        let mut ram = Ram([0; 0x10000]);
+        let first = Contention::ZX48.first_t_state;
+
+        let mut z80 = z80_at(first, 0x4000);
+        assert_eq!(z80.step(&mut ram), 4 + 6);
+        assert_eq!(z80.frame_t_state(), first + 10);
+
+        let mut z80 = z80_at(first, 0x8000);
+        assert_eq!(z80.step(&mut ram), 4);
+
+        // Border time is free even in contended memory
+        let mut z80 = z80_at(first + 128, 0x4000);
+        assert_eq!(z80.step(&mut ram), 4);
+    }
+
+    #[test]
+    fn test_contended_store_waits_per_access() {
+        let mut ram = Ram([0; 0x10000]);
+        // LD (HL),A at 0x8000 writing into screen memory
+        ram.0[0x8000] = 0x77;
+        let first = Contention::ZX48.first_t_state;
+        let mut z80 = z80_at(first, 0x8000);
+        z80.cpu_mut().set_register(RegisterPair::HL, 0x4000);
+        // Fetch at T+0 is free, the write at T+4 waits 2
+        assert_eq!(z80.step(&mut ram), 7 + 2);
+    }
+
+    #[test]
+    fn test_frame_position_wraps_and_restarts() {
+        let mut ram = Ram([0; 0x10000]);
+        let mut z80: Z80 = Z80::new(Timing::uncontended(10));
+        z80.step(&mut ram);
+        z80.step(&mut ram);
+        assert_eq!(z80.frame_t_state(), 8);
+        z80.step(&mut ram);
+        assert_eq!(z80.frame_t_state(), 2);
+        z80.start_frame();
+        assert_eq!(z80.frame_t_state(), 0);
+        assert_eq!(z80.cpu().total_cycles(), 12);
+    }
+}
@@ -0,0 +1,16 @@
+//! ICE! 2.4 depacking (`ym2149_sndh_replayer::ice_depack`).
+//!
+//! Inputs are forced to start with the ICE! magic so the fuzzer spends its
+//! time in the bit-stream decoder rather than on header rejection.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_sndh_replayer::ice_depack(data);
+
+    let mut packed = b"ICE!".to_vec();
+    packed.extend_from_slice(data);
+    let _ = ym2149_sndh_replayer::ice_depack(&packed);
+});
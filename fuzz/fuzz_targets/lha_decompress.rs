@@ -0,0 +1,9 @@
+//! LHA and ZIP unpacking in front of the YM parsers (`decompress_if_needed`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_ym_replayer::decompress_if_needed(data);
+});
@@ -0,0 +1,9 @@
+//! Arkos Tracker song parsing, plain XML and zipped (`load_aks`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_arkos_replayer::load_aks(data);
+});
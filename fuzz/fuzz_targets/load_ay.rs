@@ -0,0 +1,9 @@
+//! Project AY container parsing (`ym2149_ay_replayer::load_ay`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_ay_replayer::load_ay(data);
+});
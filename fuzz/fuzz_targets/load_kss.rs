@@ -0,0 +1,9 @@
+//! KSS (MSX) file parsing (`ym2149_kss_replayer::load_kss`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_kss_replayer::load_kss(data);
+});
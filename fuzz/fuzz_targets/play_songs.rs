@@ -0,0 +1,53 @@
+//! Loading and rendering through every format's player.
+//!
+//! Inputs that parse are played for a few frames, which runs the song's own
+//! code on the emulated Z80 (AY) and 68000 (SNDH) and the YM/Arkos effect
+//! decoders. The first byte selects the format.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ym2149_common::ChiptunePlayerBase;
+
+/// Frames rendered per input (one 50Hz frame each).
+const FRAMES: usize = 4;
+
+fn render(player: &mut dyn ChiptunePlayerBase) {
+    player.play();
+    let mut buffer = [0.0f32; 882];
+    for _ in 0..FRAMES {
+        player.generate_samples_into(&mut buffer);
+    }
+}
+
+fuzz_target!(|input: &[u8]| {
+    let Some((&format, data)) = input.split_first() else {
+        return;
+    };
+    match format % 4 {
+        0 => {
+            if let Ok((mut player, _)) = ym2149_ym_replayer::load_song(data) {
+                render(&mut player);
+            }
+        }
+        1 => {
+            if let Ok((mut player, _)) = ym2149_ay_replayer::AyPlayer::load_from_bytes(data, 0) {
+                render(&mut player);
+            }
+        }
+        2 => {
+            if let Ok(mut player) = ym2149_sndh_replayer::load_sndh(data, 44_100)
+                && player.init_subsong(player.default_subsong()).is_ok()
+            {
+                render(&mut player);
+            }
+        }
+        _ => {
+            if let Ok(song) = ym2149_arkos_replayer::load_aks(data)
+                && let Ok(mut player) = ym2149_arkos_replayer::ArkosPlayer::new(song, 0)
+            {
+                render(&mut player);
+            }
+        }
+    }
+});
@@ -0,0 +1,9 @@
+//! SNDH header and tag parsing, including ICE! depacking (`SndhFile::parse`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = ym2149_sndh_replayer::SndhFile::parse(data);
+});
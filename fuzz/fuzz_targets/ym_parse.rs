@@ -0,0 +1,11 @@
+//! YM2-YM6 parsing of uncompressed data (`YmParser`, `Ym6Parser`).
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use ym2149_ym_replayer::{Ym6Parser, YmParser};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = YmParser.parse_full(data);
+    let _ = Ym6Parser.parse_full(data);
+});